@@ -31,6 +31,7 @@ async fn main() {
             result: "id balance".to_owned(),
             limit: None,
             order: None,
+            decode_body: None,
         },
     )
     .await
@@ -54,6 +55,7 @@ async fn main() {
             collection: "transactions".to_owned(),
             filter: None,
             result: "id account_addr".to_owned(),
+            decode_body: None,
         },
         |result| async {
             match result {
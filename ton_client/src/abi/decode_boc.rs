@@ -1,3 +1,4 @@
+use crate::abi::diagnostics::{decode_params_with_diagnostics, DecodeDiagnostics};
 use crate::abi::types::AbiParam;
 use crate::abi::Error;
 use crate::client::ClientContext;
@@ -20,12 +21,18 @@ pub struct ParamsOfDecodeBoc {
     // to handle conditional parsing (when TLB constructor or flags should be 
     // checked to decide how to parse remaining BOC data)
     pub allow_partial: bool,
+    /// Return the successfully decoded leading parameters instead of an error if the data
+    /// can't be decoded completely. The failure is described in `diagnostics` of the result.
+    pub partial: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, ApiType, Default)]
 pub struct ResultOfDecodeBoc {
     /// Decoded data as a JSON structure.
     pub data: Value,
+    /// Describes where and why the decoding has stopped. Returned only in `partial` mode
+    /// if the data can't be decoded completely.
+    pub diagnostics: Option<DecodeDiagnostics>,
 }
 
 /// Decodes BOC into JSON as a set of provided parameters.
@@ -37,14 +44,22 @@ pub struct ResultOfDecodeBoc {
 /// 
 /// To solve this problem we introduce a new ABI type `Ref(<ParamType>)` 
 /// which allows to store `ParamType` ABI parameter in cell reference and, thus, 
-/// decode manually encoded BOCs. This type is available only in `decode_boc` function 
-/// and will not be available in ABI messages encoding until it is included into some ABI revision.
+/// decode manually encoded BOCs. The type can be nested into tuples, arrays and other refs
+/// and is supported by `encode_boc` as well, so the same parameters set can be used
+/// to build such BOCs. In contract ABIs the type is introduced by ABI version 2.4:
+/// function inputs and outputs, events and initial data can use `ref(T)` only if
+/// the contract ABI declares `"version": "2.4"` or higher.
 /// 
 /// Such BOC descriptions covers most users needs. If someone wants to decode some BOC which 
 /// can not be described by these rules (i.e. BOC with TLB containing constructors of flags 
 /// defining some parsing conditions) then they can decode the fields up to fork condition, 
 /// check the parsed data manually, expand the parsing schema and then decode the whole BOC 
 /// with the full schema.
+///
+/// If the decoding fails (e.g. the data is truncated or has extra bits left), the error
+/// contains `data.diagnostics` with the path and ABI type of the failed parameter and
+/// the bit position in the cell. Set `partial` to get the leading parameters decoded before
+/// the failure.
 
 #[api_function]
 pub async fn decode_boc(
@@ -58,15 +73,19 @@ pub async fn decode_boc(
         abi_params.push(param.try_into()?)
     }
 
-    let tokens = ton_abi::TokenValue::decode_params(
+    let (tokens, diagnostics) = decode_params_with_diagnostics(
         &abi_params,
         data.into(),
         &ton_abi::contract::MAX_SUPPORTED_VERSION,
         params.allow_partial
-    )
-        .map_err(|e| Error::invalid_data_for_decode(e))?;
+    );
+    if let Some(diagnostics) = &diagnostics {
+        if !params.partial.unwrap_or(false) {
+            return Err(diagnostics.attach_to(Error::invalid_data_for_decode(&diagnostics.message)));
+        }
+    }
 
     let data = Detokenizer::detokenize_to_json_value(&tokens)
         .map_err(|e| Error::invalid_data_for_decode(e))?;
-    Ok(ResultOfDecodeBoc { data })
+    Ok(ResultOfDecodeBoc { data, diagnostics })
 }
@@ -2,11 +2,48 @@ use crate::abi::types::Abi;
 use crate::abi::Error;
 use crate::client::ClientContext;
 use crate::boc::internal::deserialize_cell_from_boc;
+use crate::encoding::decode_abi_bigint;
 use crate::error::ClientResult;
+use crate::utils::{convert_address, AddressStringFormat, ParamsOfConvertAddress};
+use num_traits::ToPrimitive;
 use serde_json;
-use serde_json::Value;
+use serde_json::{Map, Value};
 use std::sync::Arc;
 use ton_abi::token::Detokenizer;
+use ton_abi::{Param, ParamType};
+
+/// Maximum integer exactly representable by the JSON numbers (`2^53 - 1`)
+const MAX_SAFE_INTEGER: i64 = 9007199254740991;
+
+#[derive(Serialize, Deserialize, ApiType, Debug, Clone, Copy, PartialEq)]
+pub enum NumberOverflow {
+    /// Integer is returned as the decimal string
+    DecimalString,
+    /// Integer is returned as the `0x` prefixed hex string
+    HexString,
+    /// Decoding fails with `InvalidData` error
+    Error,
+}
+
+impl Default for NumberOverflow {
+    fn default() -> Self {
+        NumberOverflow::DecimalString
+    }
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone)]
+pub struct DataLayoutOptions {
+    /// Representation of the integers beyond the safe JSON integer range `±(2^53 - 1)`.
+    /// Default is `DecimalString`.
+    pub number_overflow: Option<NumberOverflow>,
+    /// Format of the addresses. Default is `Hex`.
+    pub address_format: Option<AddressStringFormat>,
+    /// Flatten the nested structures into the top level fields with the dot separated
+    /// names, e.g. `owner.pubkey`. Default is `false`.
+    pub flatten: Option<bool>,
+    /// Return the ABI types of the fields in `types`. Default is `false`.
+    pub type_hints: Option<bool>,
+}
 
 #[derive(Serialize, Deserialize, ApiType, Default)]
 pub struct ParamsOfDecodeAccountData {
@@ -15,16 +52,172 @@ pub struct ParamsOfDecodeAccountData {
 
     /// Data BOC or BOC handle
     pub data: String,
+
+    /// Layout of the decoded data.
+    ///
+    /// If specified, the integers are returned as JSON numbers, the addresses are normalized
+    /// and the cells are returned as base64 BOCs. By default the data is returned as
+    /// detokenized by the ABI, with the integers as strings.
+    pub layout: Option<DataLayoutOptions>,
 }
 
 #[derive(Serialize, Deserialize, ApiType, Default)]
 pub struct ResultOfDecodeAccountData {
     /// Decoded data as a JSON structure.
     pub data: Value,
+
+    /// ABI types of the `data` fields, e.g. `{ "balance": "uint128" }`.
+    /// Returned if `layout.type_hints` is set.
+    pub types: Option<Value>,
+}
+
+struct DataLayout<'a> {
+    context: &'a Arc<ClientContext>,
+    options: &'a DataLayoutOptions,
+}
+
+fn join_path(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", prefix, name)
+    }
+}
+
+/// Components of the tuple or of the optional tuple
+fn tuple_components(kind: &ParamType) -> Option<&Vec<Param>> {
+    match kind {
+        ParamType::Tuple(components) => Some(components),
+        ParamType::Optional(inner) => tuple_components(inner),
+        _ => None,
+    }
+}
+
+impl<'a> DataLayout<'a> {
+    /// Applies the layout to the fields of the `data` object, adding them to `output`
+    /// and their types to `types`
+    fn fields(
+        &self,
+        params: &[Param],
+        mut data: Value,
+        prefix: &str,
+        output: &mut Map<String, Value>,
+        types: &mut Map<String, Value>,
+    ) -> ClientResult<()> {
+        for param in params {
+            let path = join_path(prefix, &param.name);
+            let value = data.get_mut(&param.name).map(Value::take).unwrap_or_default();
+            match tuple_components(&param.kind) {
+                Some(components) if self.options.flatten.unwrap_or(false) && value.is_object() => {
+                    self.fields(components, value, &path, output, types)?;
+                }
+                _ => {
+                    output.insert(path.clone(), self.value(value, &param.kind, &path)?);
+                    types.insert(path, param.kind.type_signature().into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn value(&self, value: Value, kind: &ParamType, path: &str) -> ClientResult<Value> {
+        match kind {
+            ParamType::Uint(_)
+            | ParamType::Int(_)
+            | ParamType::VarUint(_)
+            | ParamType::VarInt(_)
+            | ParamType::Token
+            | ParamType::Time
+            | ParamType::Expire => self.number(value, path),
+            ParamType::Address => Ok(self.address(value)),
+            ParamType::Tuple(components) => {
+                let mut output = Map::new();
+                for param in components {
+                    let item = value.get(&param.name).cloned().unwrap_or_default();
+                    let item_path = join_path(path, &param.name);
+                    output.insert(param.name.clone(), self.value(item, &param.kind, &item_path)?);
+                }
+                Ok(Value::Object(output))
+            }
+            ParamType::Array(item_kind) | ParamType::FixedArray(item_kind, _) => match value {
+                Value::Array(items) => items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, item)| self.value(item, item_kind, &join_path(path, &i.to_string())))
+                    .collect::<ClientResult<Vec<_>>>()
+                    .map(Value::Array),
+                value => Ok(value),
+            },
+            ParamType::Map(_, item_kind) => match value {
+                Value::Object(items) => items
+                    .into_iter()
+                    .map(|(key, item)| {
+                        let item = self.value(item, item_kind, &join_path(path, &key))?;
+                        Ok((key, item))
+                    })
+                    .collect::<ClientResult<Map<_, _>>>()
+                    .map(Value::Object),
+                value => Ok(value),
+            },
+            ParamType::Optional(inner) | ParamType::Ref(inner) if !value.is_null() => {
+                self.value(value, inner, path)
+            }
+            _ => Ok(value),
+        }
+    }
+
+    fn number(&self, value: Value, path: &str) -> ClientResult<Value> {
+        let string = match value.as_str() {
+            Some(string) => string,
+            None => return Ok(value),
+        };
+        let number = decode_abi_bigint(string)?;
+        if let Some(number) = number.to_i64().filter(|number| number.abs() <= MAX_SAFE_INTEGER) {
+            return Ok(number.into());
+        }
+        match self.options.number_overflow.unwrap_or_default() {
+            NumberOverflow::DecimalString => Ok(number.to_str_radix(10).into()),
+            NumberOverflow::HexString => {
+                let hex = number.to_str_radix(16);
+                Ok(match hex.strip_prefix('-') {
+                    Some(hex) => format!("-0x{}", hex),
+                    None => format!("0x{}", hex),
+                }
+                .into())
+            }
+            NumberOverflow::Error => Err(Error::invalid_data_for_decode(format!(
+                "integer `{}` of field `{}` is beyond the safe JSON integer range",
+                string, path,
+            ))),
+        }
+    }
+
+    /// Converts the address to `address_format`. Values which are not valid addresses
+    /// (e.g. empty `addr_none`) are returned as is.
+    fn address(&self, value: Value) -> Value {
+        let address = match value.as_str() {
+            Some(address) if !address.is_empty() => address.to_string(),
+            _ => return value,
+        };
+        convert_address(
+            self.context.clone(),
+            ParamsOfConvertAddress {
+                address,
+                output_format: self.options.address_format.clone().unwrap_or_default(),
+            },
+        )
+        .map(|result| result.address.into())
+        .unwrap_or(value)
+    }
 }
 
 /// Decodes account data using provided data BOC and ABI.
 ///
+/// If `layout` is specified, the data is returned as the typed JSON: integers fitting
+/// the safe JSON integer range are returned as numbers, addresses are converted to
+/// `address_format` and the nested structures can be flattened. This spares the consumers
+/// from parsing the values returned as strings.
+///
 /// Note: this feature requires ABI 2.1 or higher.
 #[api_function]
 pub async fn decode_account_data(
@@ -39,5 +232,21 @@ pub async fn decode_account_data(
 
     let data = Detokenizer::detokenize_to_json_value(&tokens)
         .map_err(|e| Error::invalid_data_for_decode(e))?;
-    Ok(ResultOfDecodeAccountData { data })
+    let options = match &params.layout {
+        Some(options) => options,
+        None => return Ok(ResultOfDecodeAccountData { data, types: None }),
+    };
+
+    let layout = DataLayout { context: &context, options };
+    let mut output = Map::new();
+    let mut types = Map::new();
+    layout.fields(abi.fields(), data, "", &mut output, &mut types)?;
+    Ok(ResultOfDecodeAccountData {
+        data: Value::Object(output),
+        types: if options.type_hints.unwrap_or(false) {
+            Some(Value::Object(types))
+        } else {
+            None
+        },
+    })
 }
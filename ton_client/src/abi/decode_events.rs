@@ -0,0 +1,189 @@
+use crate::abi::types::Abi;
+use crate::abi::Error;
+use crate::boc::internal::deserialize_object_from_boc;
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use serde_json::Value;
+use std::sync::Arc;
+use ton_abi::token::Detokenizer;
+use ton_block::{CommonMsgInfo, Message, Serializable};
+use ton_sdk::AbiContract;
+
+fn load_abi(abi: &Abi) -> ClientResult<AbiContract> {
+    let abi = abi.json_string()?;
+    AbiContract::load(abi.as_bytes()).map_err(|x| Error::invalid_json(x))
+}
+
+fn event_id(abi: &AbiContract, event_name: &str) -> ClientResult<u32> {
+    abi.events()
+        .get(event_name)
+        .map(|event| event.get_id())
+        .ok_or_else(|| Error::invalid_abi(format!("event `{}` not found in ABI", event_name)))
+}
+
+//------------------------------------------------------------------------------------ get_event_id
+
+#[derive(Serialize, Deserialize, ApiType, Default)]
+pub struct ParamsOfGetEventId {
+    /// Contract ABI
+    pub abi: Abi,
+
+    /// Event name
+    pub event_name: String,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, PartialEq)]
+pub struct ResultOfGetEventId {
+    /// Event id. The body of the external outbound message emitted by the event
+    /// starts with this 32-bit value.
+    pub event_id: u32,
+}
+
+/// Calculates the event id (topic) used to tag event messages.
+///
+/// Use it to filter external outbound messages by the first 32 bits of their body
+/// without decoding them.
+#[api_function]
+pub fn get_event_id(
+    _context: Arc<ClientContext>,
+    params: ParamsOfGetEventId,
+) -> ClientResult<ResultOfGetEventId> {
+    let abi = load_abi(&params.abi)?;
+    Ok(ResultOfGetEventId {
+        event_id: event_id(&abi, &params.event_name)?,
+    })
+}
+
+//---------------------------------------------------------------------- decode_events_from_transaction
+
+#[derive(Serialize, Deserialize, ApiType, Default)]
+pub struct ParamsOfDecodeEventsFromTransaction {
+    /// Contract ABI
+    pub abi: Abi,
+
+    /// Transaction BOC encoded as base64 or BOC handle
+    pub transaction: String,
+
+    /// Names of the events to decode. If not specified, all ABI events are decoded.
+    pub event_names: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Debug, PartialEq, Clone)]
+pub struct DecodedEvent {
+    /// Event name
+    pub name: String,
+
+    /// Event id
+    pub event_id: u32,
+
+    /// Event parameters
+    pub value: Value,
+
+    /// Id of the message emitted by the event
+    pub message_id: String,
+
+    /// Index of the message in the transaction outbound messages
+    pub index: u32,
+
+    /// Logical time of the message creation
+    pub created_lt: String,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, PartialEq)]
+pub struct ResultOfDecodeEventsFromTransaction {
+    /// Decoded events in order of emission
+    pub events: Vec<DecodedEvent>,
+}
+
+fn decode_event(
+    abi: &AbiContract,
+    event_ids: &Option<Vec<u32>>,
+    message: &Message,
+    index: u32,
+) -> ClientResult<Option<DecodedEvent>> {
+    let created_lt = match message.header() {
+        CommonMsgInfo::ExtOutMsgInfo(header) => header.created_lt,
+        _ => return Ok(None),
+    };
+    let body = match message.body() {
+        Some(body) => body,
+        None => return Ok(None),
+    };
+    let id = match body.clone().get_next_u32() {
+        Ok(id) => id,
+        Err(_) => return Ok(None),
+    };
+    if let Some(event_ids) = event_ids {
+        if !event_ids.contains(&id) {
+            return Ok(None);
+        }
+    }
+    let event = match abi.events().values().find(|event| event.get_id() == id) {
+        Some(event) => event,
+        None => return Ok(None),
+    };
+    let tokens = event
+        .decode_input(body)
+        .map_err(|err| Error::invalid_message_for_decode(err))?;
+    let value = Detokenizer::detokenize_to_json_value(&tokens)
+        .map_err(|err| Error::invalid_message_for_decode(err))?;
+    let message_id = message
+        .serialize()
+        .map_err(|err| Error::invalid_message_for_decode(err))?
+        .repr_hash()
+        .as_hex_string();
+
+    Ok(Some(DecodedEvent {
+        name: event.name.clone(),
+        event_id: id,
+        value,
+        message_id,
+        index,
+        created_lt: created_lt.to_string(),
+    }))
+}
+
+/// Decodes events emitted by the transaction.
+///
+/// Enumerates the external outbound messages of the transaction, decodes the ones
+/// matching the requested events and returns them in order of emission.
+#[api_function]
+pub async fn decode_events_from_transaction(
+    context: Arc<ClientContext>,
+    params: ParamsOfDecodeEventsFromTransaction,
+) -> ClientResult<ResultOfDecodeEventsFromTransaction> {
+    let abi = load_abi(&params.abi)?;
+    let event_ids = match &params.event_names {
+        Some(names) => Some(
+            names
+                .iter()
+                .map(|name| event_id(&abi, name))
+                .collect::<ClientResult<Vec<_>>>()?,
+        ),
+        None => None,
+    };
+    let transaction = deserialize_object_from_boc::<ton_block::Transaction>(
+        &context,
+        &params.transaction,
+        "transaction",
+    )
+    .await?
+    .object;
+
+    let mut messages = Vec::new();
+    transaction
+        .iterate_out_msgs(|message| {
+            messages.push(message);
+            Ok(true)
+        })
+        .map_err(|err| Error::invalid_message_for_decode(err))?;
+
+    let mut events = Vec::new();
+    for (index, message) in messages.iter().enumerate() {
+        if let Some(event) = decode_event(&abi, &event_ids, message, index as u32)? {
+            events.push(event);
+        }
+    }
+
+    Ok(ResultOfDecodeEventsFromTransaction { events })
+}
@@ -1,4 +1,5 @@
 use crate::{abi::types::Abi, boc::internal::deserialize_cell_from_boc};
+use crate::abi::diagnostics::{decode_params_with_diagnostics, DecodeDiagnostics};
 use crate::abi::{Error, FunctionHeader};
 use crate::boc::internal::deserialize_object_from_boc;
 use crate::client::ClientContext;
@@ -7,6 +8,7 @@ use serde_json::Value;
 use std::sync::Arc;
 use ton_abi::contract::DecodedMessage;
 use ton_abi::token::Detokenizer;
+use ton_abi::{Param, Token};
 use ton_sdk::AbiContract;
 use ton_types::SliceData;
 
@@ -28,7 +30,13 @@ pub enum MessageBodyType {
     Event,
 }
 
-#[derive(Serialize, Deserialize, ApiType, PartialEq, Debug, Clone)]
+impl Default for MessageBodyType {
+    fn default() -> Self {
+        MessageBodyType::Input
+    }
+}
+
+#[derive(Serialize, Deserialize, ApiType, PartialEq, Debug, Clone, Default)]
 pub struct DecodedMessageBody {
     /// Type of the message body content.
     pub body_type: MessageBodyType,
@@ -41,6 +49,11 @@ pub struct DecodedMessageBody {
 
     /// Function header.
     pub header: Option<FunctionHeader>,
+
+    /// Describes where and why the decoding has stopped. Returned only in `partial` mode
+    /// if the body can't be decoded completely, `value` contains the leading parameters
+    /// decoded before the failure.
+    pub diagnostics: Option<DecodeDiagnostics>,
 }
 
 impl DecodedMessageBody {
@@ -49,13 +62,24 @@ impl DecodedMessageBody {
         decoded: DecodedMessage,
         header: Option<FunctionHeader>,
     ) -> ClientResult<Self> {
-        let value = Detokenizer::detokenize_to_json_value(&decoded.tokens)
+        Self::with_tokens(body_type, decoded.function_name, &decoded.tokens, header, None)
+    }
+
+    fn with_tokens(
+        body_type: MessageBodyType,
+        name: String,
+        tokens: &[Token],
+        header: Option<FunctionHeader>,
+        diagnostics: Option<DecodeDiagnostics>,
+    ) -> ClientResult<Self> {
+        let value = Detokenizer::detokenize_to_json_value(tokens)
             .map_err(|x| Error::invalid_message_for_decode(x))?;
         Ok(Self {
             body_type,
-            name: decoded.function_name,
+            name,
             value: Some(value),
             header,
+            diagnostics,
         })
     }
 }
@@ -69,9 +93,19 @@ pub struct ParamsOfDecodeMessage {
 
     /// Message BOC
     pub message: String,
+
+    /// Return the successfully decoded leading parameters instead of an error
+    /// if the body matches the ABI function or event, but can't be decoded completely.
+    /// The failure is described in `diagnostics` of the result.
+    pub partial: Option<bool>,
 }
 
 /// Decodes message body using provided message BOC and ABI.
+///
+/// If the body matches the ABI function or event by its id, but its parameters can't be
+/// decoded (e.g. the body is truncated or has extra bits left because of the ABI version
+/// mismatch), the error contains `data.diagnostics` with the path and ABI type of the failed
+/// parameter and the bit position in the cell.
 #[api_function]
 pub async fn decode_message(
     context: Arc<ClientContext>,
@@ -79,7 +113,7 @@ pub async fn decode_message(
 ) -> ClientResult<DecodedMessageBody> {
     let (abi, message) = prepare_decode(&context, &params).await?;
     if let Some(body) = message.body() {
-        decode_body(abi, body, message.is_internal())
+        decode_body(abi, body, message.is_internal(), params.partial.unwrap_or(false))
     } else {
         Err(Error::invalid_message_for_decode(
             "The message body is empty",
@@ -99,9 +133,16 @@ pub struct ParamsOfDecodeMessageBody {
 
     /// True if the body belongs to the internal message.
     pub is_internal: bool,
+
+    /// Return the successfully decoded leading parameters instead of an error
+    /// if the body matches the ABI function or event, but can't be decoded completely.
+    /// The failure is described in `diagnostics` of the result.
+    pub partial: Option<bool>,
 }
 
 /// Decodes message body using provided body BOC and ABI.
+///
+/// See `decode_message` for the decoding failure diagnostics.
 #[api_function]
 pub async fn decode_message_body(
     context: Arc<ClientContext>,
@@ -110,7 +151,7 @@ pub async fn decode_message_body(
     let abi = params.abi.json_string()?;
     let abi = AbiContract::load(abi.as_bytes()).map_err(|x| Error::invalid_json(x))?;
     let (_, body) = deserialize_cell_from_boc(&context, &params.body, "message body").await?;
-    decode_body(abi, body.into(), params.is_internal)
+    decode_body(abi, body.into(), params.is_internal, params.partial.unwrap_or(false))
 }
 
 async fn prepare_decode(
@@ -129,6 +170,7 @@ fn decode_body(
     abi: AbiContract,
     body: SliceData,
     is_internal: bool,
+    partial: bool,
 ) -> ClientResult<DecodedMessageBody> {
     if let Ok(output) = abi.decode_output(body.clone(), is_internal) {
         if abi.events().get(&output.function_name).is_some() {
@@ -150,6 +192,8 @@ fn decode_body(
             input,
             FunctionHeader::from(&header)?,
         )
+    } else if let Some(candidate) = find_body_candidate(&abi, &body, is_internal)? {
+        decode_candidate(&abi, candidate, partial)
     } else {
         Err(Error::invalid_message_for_decode(
             "The message body does not match the specified ABI.\n
@@ -157,3 +201,79 @@ fn decode_body(
         ))
     }
 }
+
+/// ABI function or event the body is addressed to, but failed to decode
+struct BodyCandidate<'a> {
+    body_type: MessageBodyType,
+    name: &'a str,
+    params: &'a [Param],
+    header: Option<FunctionHeader>,
+    cursor: SliceData,
+}
+
+/// Finds the function or event by the body id the same way as the decoding does
+fn find_body_candidate<'a>(
+    abi: &'a AbiContract,
+    body: &SliceData,
+    is_internal: bool,
+) -> ClientResult<Option<BodyCandidate<'a>>> {
+    let mut cursor = body.clone();
+    if let Ok(id) = cursor.get_next_u32() {
+        if let Ok(event) = abi.event_by_id(id) {
+            return Ok(Some(BodyCandidate {
+                body_type: MessageBodyType::Event,
+                name: &event.name,
+                params: &event.inputs,
+                header: None,
+                cursor,
+            }));
+        }
+        if let Ok(function) = abi.function_by_id(id, false) {
+            return Ok(Some(BodyCandidate {
+                body_type: MessageBodyType::Output,
+                name: &function.name,
+                params: &function.outputs,
+                header: None,
+                cursor,
+            }));
+        }
+    }
+    if let Ok((header, id, cursor)) =
+        ton_abi::Function::decode_header(abi.version(), body.clone(), abi.header(), is_internal)
+    {
+        if let Ok(function) = abi.function_by_id(id, true) {
+            return Ok(Some(BodyCandidate {
+                body_type: MessageBodyType::Input,
+                name: &function.name,
+                params: &function.inputs,
+                header: FunctionHeader::from(&header)?,
+                cursor,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+fn decode_candidate(
+    abi: &AbiContract,
+    candidate: BodyCandidate,
+    partial: bool,
+) -> ClientResult<DecodedMessageBody> {
+    let (tokens, diagnostics) =
+        decode_params_with_diagnostics(candidate.params, candidate.cursor, abi.version(), false);
+    if let Some(diagnostics) = &diagnostics {
+        if !partial {
+            return Err(diagnostics.attach_to(Error::invalid_message_for_decode(format!(
+                "The message body does not match the parameters of `{}`: {}",
+                candidate.name, diagnostics.message,
+            ))));
+        }
+    }
+    DecodedMessageBody::with_tokens(
+        candidate.body_type,
+        candidate.name.to_owned(),
+        &tokens,
+        candidate.header,
+        diagnostics,
+    )
+}
@@ -0,0 +1,142 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::error::ClientError;
+use ton_abi::contract::AbiVersion;
+use ton_abi::{Param, ParamType, Token, TokenValue};
+use ton_types::SliceData;
+
+/// Describes where and why the ABI decoding has stopped
+#[derive(Serialize, Deserialize, ApiType, Default, PartialEq, Debug, Clone)]
+pub struct DecodeDiagnostics {
+    /// Path of the parameter failed to decode, e.g. `value.owner` for the `owner`
+    /// component of the `value` tuple. `None` if all the parameters are decoded,
+    /// but the data has unread bits or references left.
+    pub path: Option<String>,
+    /// ABI type of the parameter failed to decode.
+    pub expected_type: Option<String>,
+    /// Offset in bits in the current cell the failed parameter starts at.
+    pub bit_offset: u32,
+    /// Number of unread bits in the current cell.
+    pub remaining_bits: u32,
+    /// Number of unread references in the current cell.
+    pub remaining_refs: u32,
+    /// Number of the leading parameters decoded successfully.
+    pub decoded_count: u32,
+    /// Decoding error.
+    pub message: String,
+}
+
+impl DecodeDiagnostics {
+    fn new(
+        path: Option<String>,
+        param: Option<&Param>,
+        cursor: &SliceData,
+        decoded_count: usize,
+        error: impl std::fmt::Display,
+    ) -> Self {
+        Self {
+            path,
+            expected_type: param.map(|param| param.kind.type_signature()),
+            bit_offset: cursor.pos() as u32,
+            remaining_bits: cursor.remaining_bits() as u32,
+            remaining_refs: cursor.remaining_references() as u32,
+            decoded_count: decoded_count as u32,
+            message: error.to_string(),
+        }
+    }
+
+    /// Attaches the diagnostics to the `data.diagnostics` of the error
+    pub(crate) fn attach_to(&self, mut error: ClientError) -> ClientError {
+        error.data["diagnostics"] = json!(self);
+        error
+    }
+}
+
+/// Decodes the leading parameters one by one until the first failure. Returns the decoded
+/// tokens and the rest of the data, or the index of the failed parameter together with
+/// the tokens decoded before it, the data it starts at and the decoding error
+fn decode_leading(
+    params: &[Param],
+    cursor: &SliceData,
+    abi_version: &AbiVersion,
+) -> Result<(Vec<Token>, SliceData), (usize, Vec<Token>, SliceData, failure::Error)> {
+    let mut tokens = Vec::new();
+    let mut rest = cursor.clone();
+    for index in 0..params.len() {
+        match TokenValue::decode_params_with_cursor(
+            &params[..=index].to_vec(),
+            cursor.clone(),
+            abi_version,
+            true,
+        ) {
+            Ok((decoded, next)) => {
+                tokens = decoded;
+                rest = next;
+            }
+            Err(err) => return Err((index, tokens, rest, err)),
+        }
+    }
+    Ok((tokens, rest))
+}
+
+fn diagnose(
+    params: &[Param],
+    cursor: SliceData,
+    abi_version: &AbiVersion,
+    error: failure::Error,
+) -> (Vec<Token>, DecodeDiagnostics) {
+    let (index, tokens, mut rest, mut error) = match decode_leading(params, &cursor, abi_version) {
+        Ok((tokens, rest)) => {
+            let count = tokens.len();
+            return (tokens, DecodeDiagnostics::new(None, None, &rest, count, error));
+        }
+        Err(failed) => failed,
+    };
+
+    let mut param = &params[index];
+    let mut path = param.name.clone();
+    // Tuple components are stored one after another as the top level parameters,
+    // so the failed component is found the same way
+    while let ParamType::Tuple(components) = &param.kind {
+        match decode_leading(components, &rest, abi_version) {
+            Err((index, _, component_rest, component_error)) => {
+                param = &components[index];
+                path = format!("{}.{}", path, param.name);
+                rest = component_rest;
+                error = component_error;
+            }
+            Ok(_) => break,
+        }
+    }
+
+    let count = tokens.len();
+    (tokens, DecodeDiagnostics::new(Some(path), Some(param), &rest, count, error))
+}
+
+/// Decodes the parameters. If decoding fails, returns the successfully decoded leading
+/// parameters together with the diagnostics of the failure
+pub(crate) fn decode_params_with_diagnostics(
+    params: &[Param],
+    cursor: SliceData,
+    abi_version: &AbiVersion,
+    allow_partial: bool,
+) -> (Vec<Token>, Option<DecodeDiagnostics>) {
+    match TokenValue::decode_params(&params.to_vec(), cursor.clone(), abi_version, allow_partial) {
+        Ok(tokens) => (tokens, None),
+        Err(err) => {
+            let (tokens, diagnostics) = diagnose(params, cursor, abi_version, err);
+            (tokens, Some(diagnostics))
+        }
+    }
+}
@@ -144,6 +144,25 @@ async fn state_init_from_tvc(
     Ok(image.state_init())
 }
 
+pub(crate) async fn resolve_state_init(
+    context: &Arc<ClientContext>,
+    source: &StateInitSource,
+) -> ClientResult<StateInit> {
+    match source {
+        StateInitSource::Message { source } => state_init_from_message(context, source).await,
+        StateInitSource::StateInit {
+            code,
+            data,
+            library,
+        } => state_init_from_bocs(context, code, data, library).await,
+        StateInitSource::Tvc {
+            tvc,
+            public_key,
+            init_params,
+        } => state_init_from_tvc(context, tvc, public_key, init_params).await,
+    }
+}
+
 /// Creates account state BOC
 ///
 /// Creates account state provided with one of these sets of data :
@@ -154,19 +173,7 @@ pub async fn encode_account(
     context: std::sync::Arc<ClientContext>,
     params: ParamsOfEncodeAccount,
 ) -> ClientResult<ResultOfEncodeAccount> {
-    let state_init = match &params.state_init {
-        StateInitSource::Message { source } => state_init_from_message(&context, source).await,
-        StateInitSource::StateInit {
-            code,
-            data,
-            library,
-        } => state_init_from_bocs(&context, code, data, library).await,
-        StateInitSource::Tvc {
-            tvc,
-            public_key,
-            init_params,
-        } => state_init_from_tvc(&context, tvc, public_key, init_params).await,
-    }?;
+    let state_init = resolve_state_init(&context, &params.state_init).await?;
     let id = state_init.hash().map_err(|err| Error::invalid_tvc_image(err))?;
     let address = MsgAddressInt::with_standart(None, 0, id.clone().into()).unwrap();
     let mut account = Account::with_address(address);
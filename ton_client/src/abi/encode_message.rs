@@ -1,15 +1,19 @@
 use crate::abi;
 use crate::abi::internal::{add_sign_to_message, add_sign_to_message_body, create_tvc_image, try_to_sign_message, update_pubkey};
 use crate::abi::{Abi, Error, FunctionHeader, Signer};
-use crate::boc::internal::{get_boc_hash, deserialize_cell_from_boc};
+use crate::boc::internal::{
+    deserialize_cell_from_boc, deserialize_object_from_boc, get_boc_hash,
+    serialize_cell_to_base64, serialize_object_to_base64,
+};
 use crate::client::ClientContext;
-use crate::encoding::{account_decode, account_encode, decode_abi_number, hex_decode};
+use crate::encoding::{account_decode, account_encode, base64_decode, decode_abi_number, hex_decode};
 use crate::error::ClientResult;
 use serde_json::Value;
 use std::str::FromStr;
 use std::sync::Arc;
 use ton_abi::Contract;
-use ton_block::{MsgAddressInt, CurrencyCollection};
+use ton_block::{MsgAddressInt, CurrencyCollection, Message};
+use ton_types::{BuilderData, Cell, IBitstring, SliceData};
 use ton_sdk::{ContractImage, FunctionCallSet};
 
 //--------------------------------------------------------------------------- encode_deploy_message
@@ -80,10 +84,52 @@ impl CallSet {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default, PartialEq)]
+pub struct MessageExpiration {
+    /// Message expiration time (Unix time in seconds).
+    ///
+    /// Overrides the timeout, so the messages encoded at different moments have
+    /// the same expiration time.
+    pub expire_at: Option<u32>,
+
+    /// Message expiration timeout in ms.
+    ///
+    /// Overrides `AbiConfig.message_expiration_timeout`.
+    pub timeout: Option<u32>,
+
+    /// Factor that increases the expiration timeout for each retry.
+    ///
+    /// Overrides `AbiConfig.message_expiration_timeout_grow_factor`.
+    /// Use `1` to disable the growth.
+    pub timeout_grow_factor: Option<f32>,
+}
+
 fn calc_timeout(timeout: u32, grow_rate: f32, processing_try_index: u8) -> u32 {
     (timeout as f64 * grow_rate.powi(processing_try_index as i32) as f64) as u32
 }
 
+/// Calculates the `expire` header value (Unix time in seconds)
+fn calc_expire(
+    context: &Arc<ClientContext>,
+    expiration: Option<&MessageExpiration>,
+    processing_try_index: Option<u8>,
+) -> u32 {
+    if let Some(expire_at) = expiration.and_then(|expiration| expiration.expire_at) {
+        return expire_at;
+    }
+    let config = &context.config.abi;
+    let timeout = calc_timeout(
+        expiration
+            .and_then(|expiration| expiration.timeout)
+            .unwrap_or(config.message_expiration_timeout),
+        expiration
+            .and_then(|expiration| expiration.timeout_grow_factor)
+            .unwrap_or(config.message_expiration_timeout_grow_factor),
+        processing_try_index.unwrap_or(0),
+    );
+    ((context.network_now_ms() + timeout as u64) / 1000) as u32
+}
+
 fn resolve_header(
     header: Option<&FunctionHeader>,
     pubkey: Option<&str>,
@@ -94,7 +140,7 @@ fn resolve_header(
     if abi.header().len() == 0 {
         return Ok(None);
     }
-    let now = context.env.now_ms();
+    let now = context.network_now_ms();
     let required = |name: &str| abi.header().iter().find(|x| x.name == name).is_some();
     Ok(Some(FunctionHeader {
         time: if required("time") {
@@ -103,15 +149,11 @@ fn resolve_header(
             None
         },
         expire: if required("expire") {
-            Some(header.map_or(None, |x| x.expire).unwrap_or_else(|| {
-                let config = &context.config.abi;
-                let timeout = calc_timeout(
-                    config.message_expiration_timeout,
-                    config.message_expiration_timeout_grow_factor,
-                    processing_try_index.unwrap_or(0),
-                );
-                ((now + timeout as u64) / 1000) as u32
-            }))
+            Some(
+                header
+                    .map_or(None, |x| x.expire)
+                    .unwrap_or_else(|| calc_expire(context, None, processing_try_index)),
+            )
         } else {
             None
         },
@@ -140,6 +182,24 @@ fn header_to_string(header: &FunctionHeader) -> String {
 }
 
 impl CallSet {
+    /// Returns the call set with the `expire` header calculated with the per-call
+    /// expiration parameters (unless the header is specified explicitly)
+    fn with_expiration(
+        &self,
+        expiration: Option<&MessageExpiration>,
+        processing_try_index: Option<u8>,
+        context: &Arc<ClientContext>,
+    ) -> Self {
+        let mut call_set = self.clone();
+        if let Some(expiration) = expiration {
+            let header = call_set.header.get_or_insert_with(Default::default);
+            if header.expire.is_none() {
+                header.expire = Some(calc_expire(context, Some(expiration), processing_try_index));
+            }
+        }
+        call_set
+    }
+
     fn to_function_call_set(
         &self,
         pubkey: Option<&str>,
@@ -225,6 +285,13 @@ pub struct ParamsOfEncodeMessage {
     ///
     /// Default value is 0.
     pub processing_try_index: Option<u8>,
+
+    /// Message expiration parameters of this call (if contract's ABI includes
+    /// "expire" header).
+    ///
+    /// Override the expiration parameters of the Client config. Ignored if the `expire`
+    /// header is specified in `call_set`.
+    pub expiration: Option<MessageExpiration>,
 }
 
 #[derive(Serialize, Deserialize, ApiType, Default)]
@@ -416,6 +483,8 @@ fn encode_run(
 /// without private key disclosure to SDK. For instance, in case of using a cold wallet or HSM,
 /// when application calls some API to sign data.
 ///
+/// `Signer::Default` uses the default signer of the context (see `client.create_derived_context`).
+///
 /// There is an optional public key can be provided in deploy set in order to substitute one
 /// in TVM file.
 ///
@@ -428,11 +497,29 @@ fn encode_run(
 pub async fn encode_message(
     context: std::sync::Arc<ClientContext>,
     params: ParamsOfEncodeMessage,
+) -> ClientResult<ResultOfEncodeMessage> {
+    let mut params = params;
+    params.signer = params.signer.resolve_default(&context)?;
+    encode_message_internal(context, params).await
+}
+
+/// Encodes the message with the specified signer ignoring the default signer of the context.
+/// Used for the messages which must not be signed, e.g. the get-method calls.
+pub(crate) async fn encode_message_internal(
+    context: std::sync::Arc<ClientContext>,
+    params: ParamsOfEncodeMessage,
 ) -> ClientResult<ResultOfEncodeMessage> {
     let abi = params.abi.json_string()?;
 
     let public = params.signer.resolve_public_key(context.clone()).await?;
-    let (message, data_to_sign, address) = if let Some(deploy_set) = params.deploy_set {
+    let call_set = params.call_set.as_ref().map(|call_set| {
+        call_set.with_expiration(
+            params.expiration.as_ref(),
+            params.processing_try_index,
+            &context,
+        )
+    });
+    let (message, data_to_sign, address) = if let Some(deploy_set) = &params.deploy_set {
         let workchain = deploy_set
             .workchain_id
             .unwrap_or(context.config.abi.workchain);
@@ -445,7 +532,7 @@ pub async fn encode_message(
 
         required_public_key(update_pubkey(&deploy_set, &mut image, &public)?)?;
 
-        if let Some(call_set) = &params.call_set {
+        if let Some(call_set) = &call_set {
             encode_deploy(
                 context.clone(),
                 &abi,
@@ -459,7 +546,7 @@ pub async fn encode_message(
         } else {
             encode_empty_deploy(image, workchain)?
         }
-    } else if let Some(call_set) = &params.call_set {
+    } else if let Some(call_set) = &call_set {
         encode_run(
             context.clone(),
             &params,
@@ -697,6 +784,8 @@ pub async fn encode_message_body(
     context: std::sync::Arc<ClientContext>,
     params: ParamsOfEncodeMessageBody,
 ) -> ClientResult<ResultOfEncodeMessageBody> {
+    let mut params = params;
+    params.signer = params.signer.resolve_default(&context)?;
     let abi = params.abi.json_string()?;
 
     let public = params.signer.resolve_public_key(context.clone()).await?;
@@ -855,3 +944,199 @@ pub async fn attach_signature_to_message_body(
         body: base64::encode(&signed),
     })
 }
+
+//--------------------------------------------------------- placeholder signature and late attaching
+
+const SIGNATURE_BITS: usize = 512;
+
+#[derive(Serialize, Deserialize, ApiType, Default)]
+pub struct ParamsOfAttachSignatureToUnsigned {
+    /// Contract ABI
+    pub abi: Abi,
+
+    /// Public key encoded in `hex`.
+    pub public_key: String,
+
+    /// Unsigned or placeholder-signed message BOC encoded in `base64`.
+    pub message: String,
+
+    /// Data to sign encoded in `base64`, as returned by the encoding function.
+    /// Must match the payload of the message.
+    pub data_to_sign: String,
+
+    /// Signature of `data_to_sign` encoded in `hex`.
+    pub signature: String,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default)]
+pub struct ParamsOfAttachSignatureToUnsignedBody {
+    /// Contract ABI
+    pub abi: Abi,
+
+    /// Public key. Must be encoded with `hex`.
+    pub public_key: String,
+
+    /// Unsigned or placeholder-signed message body BOC. Must be encoded with `base64`.
+    pub body: String,
+
+    /// Data to sign encoded in `base64`, as returned by the encoding function.
+    /// Must match the payload of the message body.
+    pub data_to_sign: String,
+
+    /// Signature of `data_to_sign`. Must be encoded with `hex`.
+    pub signature: String,
+}
+
+/// Returns the body with the signature place replaced by the "no signature" bit
+/// and the hash of the signed payload.
+fn unsigned_body_and_hash(mut body: SliceData) -> ClientResult<(SliceData, Vec<u8>)> {
+    let signed = body.get_next_bit().map_err(|err| Error::attach_signature_failed(err))?;
+    if signed {
+        let signature = body
+            .get_next_bits(SIGNATURE_BITS)
+            .map_err(|err| Error::attach_signature_failed(err))?;
+        if signature.iter().any(|byte| *byte != 0) {
+            return Err(Error::attach_signature_failed(
+                "message is already signed with a real signature",
+            ));
+        }
+    }
+    let payload = body
+        .clone()
+        .into_cell();
+    let mut unsigned = BuilderData::new();
+    unsigned
+        .append_bit_zero()
+        .and_then(|builder| builder.append_builder(&BuilderData::from_slice(&body)))
+        .map_err(|err| Error::attach_signature_failed(err))?;
+    let unsigned = unsigned
+        .into_cell()
+        .map_err(|err| Error::attach_signature_failed(err))?;
+    Ok((unsigned.into(), payload.repr_hash().as_slice().to_vec()))
+}
+
+fn sign_unsigned_body(
+    abi: &Abi,
+    public_key: &str,
+    body: SliceData,
+    data_to_sign: &str,
+    signature: &str,
+) -> ClientResult<Cell> {
+    let (unsigned, hash) = unsigned_body_and_hash(body)?;
+    if base64_decode(data_to_sign)? != hash {
+        return Err(Error::attach_signature_failed(
+            "`data_to_sign` doesn't match the message payload",
+        ));
+    }
+
+    let public_key = hex_decode(public_key)?;
+    let signature = hex_decode(signature)?;
+    let public = ed25519_dalek::PublicKey::from_bytes(&public_key)
+        .map_err(|err| Error::attach_signature_failed(err))?;
+    let ed25519_signature = ed25519_dalek::Signature::from_bytes(&signature)
+        .map_err(|err| Error::attach_signature_failed(err))?;
+    public
+        .verify(&hash, &ed25519_signature)
+        .map_err(|_| Error::attach_signature_failed("signature verification failed"))?;
+
+    let unsigned = ton_types::serialize_toc(&unsigned.into_cell())
+        .map_err(|err| Error::attach_signature_failed(err))?;
+    let signed = add_sign_to_message_body(&abi.json_string()?, &signature, Some(&public_key), &unsigned)?;
+    ton_types::deserialize_tree_of_cells(&mut signed.as_slice())
+        .map_err(|err| Error::attach_signature_failed(err))
+}
+
+/// Encodes an ABI-compatible message with a placeholder signature.
+///
+/// Requires `Signer::External`. The message is encoded as by `encode_message`, but
+/// the signature place is filled with zeros, so the message has the same layout and size
+/// as the signed one (e.g. for fees estimation) while the real signature is produced
+/// elsewhere. Returned `data_to_sign` is to be signed by the remote signer.
+///
+/// Use `attach_signature_to_unsigned` to splice the real signature in later. The message
+/// is not re-encoded, so the original `expire` and `time` headers are preserved.
+#[api_function]
+pub async fn encode_message_with_placeholder_signature(
+    context: std::sync::Arc<ClientContext>,
+    params: ParamsOfEncodeMessage,
+) -> ClientResult<ResultOfEncodeMessage> {
+    let mut params = params;
+    params.signer = params.signer.resolve_default(&context)?;
+    let public_key = match &params.signer {
+        Signer::External { public_key } => public_key.clone(),
+        _ => {
+            return Err(Error::invalid_signer(
+                "Placeholder signature requires `External` signer".to_string(),
+            ))
+        }
+    };
+    let abi = params.abi.json_string()?;
+    let encoded = encode_message(context.clone(), params).await?;
+    let (unsigned, _) = deserialize_cell_from_boc(&context, &encoded.message, "message").await?;
+    let placeholder = add_sign_to_message(
+        &abi,
+        &[0u8; SIGNATURE_BITS / 8],
+        Some(&hex_decode(&public_key)?),
+        &unsigned.bytes("message")?,
+    )?;
+
+    Ok(ResultOfEncodeMessage {
+        message: base64::encode(&placeholder),
+        message_id: get_boc_hash(&placeholder)?,
+        ..encoded
+    })
+}
+
+/// Attaches the signature to the unsigned message produced with `Signer::External` or
+/// to the message with a placeholder signature.
+///
+/// Unlike `attach_signature`, checks that `data_to_sign` matches the message payload and
+/// that the signature is valid for it, so the signature produced by a remote signer
+/// for another message can't be attached. The message is not re-encoded: the original
+/// `expire` header is preserved.
+#[api_function]
+pub async fn attach_signature_to_unsigned(
+    context: std::sync::Arc<ClientContext>,
+    params: ParamsOfAttachSignatureToUnsigned,
+) -> ClientResult<ResultOfAttachSignature> {
+    let mut message = deserialize_object_from_boc::<Message>(&context, &params.message, "message")
+        .await?
+        .object;
+    let body = message
+        .body()
+        .ok_or_else(|| Error::attach_signature_failed("message has no body"))?;
+    let signed_body = sign_unsigned_body(
+        &params.abi,
+        &params.public_key,
+        body,
+        &params.data_to_sign,
+        &params.signature,
+    )?;
+    message.set_body(signed_body.into());
+    let signed = serialize_object_to_base64(&message, "message")?;
+    Ok(ResultOfAttachSignature {
+        message_id: get_boc_hash(&base64_decode(&signed)?)?,
+        message: signed,
+    })
+}
+
+/// Attaches the signature to the unsigned or placeholder-signed message body.
+///
+/// Checks `data_to_sign` and the signature the same way as `attach_signature_to_unsigned`.
+#[api_function]
+pub async fn attach_signature_to_unsigned_body(
+    context: std::sync::Arc<ClientContext>,
+    params: ParamsOfAttachSignatureToUnsignedBody,
+) -> ClientResult<ResultOfAttachSignatureToMessageBody> {
+    let (_, body) = deserialize_cell_from_boc(&context, &params.body, "message body").await?;
+    let signed_body = sign_unsigned_body(
+        &params.abi,
+        &params.public_key,
+        body.into(),
+        &params.data_to_sign,
+        &params.signature,
+    )?;
+    Ok(ResultOfAttachSignatureToMessageBody {
+        body: serialize_cell_to_base64(&signed_body, "message body")?,
+    })
+}
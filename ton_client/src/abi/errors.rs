@@ -17,6 +17,7 @@ pub enum ErrorCode {
     InvalidFunctionId = 312,
     InvalidData = 313,
     EncodeInitialDataFailed = 314,
+    InvalidRenderTemplate = 315,
 }
 
 pub struct Error;
@@ -118,4 +119,11 @@ impl Error {
             format!("Encode initial data failed: {}", err),
         )
     }
+
+    pub fn invalid_render_template<E: Display>(err: E) -> ClientError {
+        error(
+            ErrorCode::InvalidRenderTemplate,
+            format!("Invalid render template: {}", err),
+        )
+    }
 }
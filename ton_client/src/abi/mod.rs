@@ -16,11 +16,15 @@ mod tests;
 
 pub(crate) mod decode_boc;
 pub(crate) mod decode_data;
+pub(crate) mod decode_events;
 pub(crate) mod decode_message;
+pub(crate) mod diagnostics;
 pub(crate) mod encode_account;
 pub(crate) mod encode_boc;
 pub(crate) mod encode_message;
 pub(crate) mod init_data;
+pub(crate) mod render_call;
+pub(crate) mod state_init_deploy;
 
 mod errors;
 mod internal;
@@ -30,7 +34,16 @@ mod types;
 use serde::{Deserialize, Deserializer};
 
 pub use decode_boc::{decode_boc, ParamsOfDecodeBoc, ResultOfDecodeBoc};
-pub use decode_data::{decode_account_data, ParamsOfDecodeAccountData, ResultOfDecodeAccountData};
+pub use decode_data::{
+    decode_account_data, DataLayoutOptions, NumberOverflow, ParamsOfDecodeAccountData,
+    ResultOfDecodeAccountData,
+};
+pub use decode_events::{
+    decode_events_from_transaction, get_event_id, DecodedEvent,
+    ParamsOfDecodeEventsFromTransaction, ParamsOfGetEventId, ResultOfDecodeEventsFromTransaction,
+    ResultOfGetEventId,
+};
+pub use diagnostics::DecodeDiagnostics;
 pub use decode_message::{
     decode_message, decode_message_body, DecodedMessageBody, MessageBodyType,
     ParamsOfDecodeMessage, ParamsOfDecodeMessageBody,
@@ -42,9 +55,12 @@ pub use encode_boc::{
     encode_boc, ParamsOfAbiEncodeBoc, ResultOfAbiEncodeBoc,
 };
 pub use encode_message::{
-    attach_signature, attach_signature_to_message_body, encode_internal_message, encode_message,
-    encode_message_body, CallSet, DeploySet, ParamsOfAttachSignature,
-    ParamsOfAttachSignatureToMessageBody, ParamsOfEncodeInternalMessage, ParamsOfEncodeMessage,
+    attach_signature, attach_signature_to_message_body, attach_signature_to_unsigned,
+    attach_signature_to_unsigned_body, encode_internal_message, encode_message,
+    encode_message_body, encode_message_with_placeholder_signature, CallSet, DeploySet,
+    MessageExpiration, ParamsOfAttachSignature, ParamsOfAttachSignatureToMessageBody,
+    ParamsOfAttachSignatureToUnsigned, ParamsOfAttachSignatureToUnsignedBody,
+    ParamsOfEncodeInternalMessage, ParamsOfEncodeMessage,
     ParamsOfEncodeMessageBody, ResultOfAttachSignature, ResultOfAttachSignatureToMessageBody,
     ResultOfEncodeInternalMessage, ResultOfEncodeMessage, ResultOfEncodeMessageBody,
 };
@@ -54,7 +70,13 @@ pub use init_data::{
     ParamsOfEncodeInitialData, ParamsOfDecodeInitialData, ParamsOfUpdateInitialData,
     ResultOfEncodeInitialData, ResultOfDecodeInitialData, ResultOfUpdateInitialData,
 };
+pub use render_call::{render_call, ParamsOfRenderCall, ResultOfRenderCall};
 pub use signing::Signer;
+pub use state_init_deploy::{
+    decode_state_init_deploy_message, encode_state_init_deploy_message,
+    ParamsOfDecodeStateInitDeployMessage, ParamsOfEncodeStateInitDeployMessage,
+    ResultOfDecodeStateInitDeployMessage, ResultOfEncodeStateInitDeployMessage,
+};
 pub use types::{
     Abi, AbiContract, AbiData, AbiEvent, AbiFunction, AbiHandle, AbiParam, FunctionHeader,
     MessageSource,
@@ -89,7 +111,7 @@ fn deserialize_message_expiration_timeout_grow_factor<'de, D: Deserializer<'de>>
         .unwrap_or(default_message_expiration_timeout_grow_factor()))
 }
 
-#[derive(Deserialize, Debug, Clone, ApiType)]
+#[derive(Serialize, Deserialize, Debug, Clone, ApiType)]
 pub struct AbiConfig {
     /// Workchain id that is used by default in DeploySet
     #[serde(
@@ -0,0 +1,229 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::abi::types::{Abi, AbiContract};
+use crate::abi::{DecodedMessageBody, Error, MessageBodyType};
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use serde_json::Value;
+use std::sync::Arc;
+
+const DEFAULT_TOKEN_DECIMALS: usize = 9;
+const ADDRESS_PREFIX_LEN: usize = 6;
+const ADDRESS_SUFFIX_LEN: usize = 4;
+
+#[derive(Serialize, Deserialize, ApiType, Default)]
+pub struct ParamsOfRenderCall {
+    /// Contract ABI
+    pub abi: Abi,
+
+    /// Decoded function call or event returned by `decode_message` or `decode_message_body`
+    pub call: DecodedMessageBody,
+
+    /// Template to render the call with. If not specified, the `render` template of the ABI
+    /// function or event is used.
+    pub template: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, PartialEq)]
+pub struct ResultOfRenderCall {
+    /// Human-readable call text
+    pub text: String,
+}
+
+fn abi_template(abi: &Abi, call: &DecodedMessageBody) -> ClientResult<Option<String>> {
+    let abi: AbiContract =
+        serde_json::from_str(&abi.json_string()?).map_err(|err| Error::invalid_json(err))?;
+    Ok(match call.body_type {
+        MessageBodyType::Event => abi
+            .events
+            .into_iter()
+            .find(|event| event.name == call.name)
+            .and_then(|event| event.render),
+        _ => abi
+            .functions
+            .into_iter()
+            .find(|function| function.name == call.name)
+            .and_then(|function| function.render),
+    })
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        Value::Null => String::new(),
+        value => value.to_string(),
+    }
+}
+
+/// Shifts the decimal point of the integer amount, e.g. `1500000000` with 9 decimals
+/// is rendered as `1.5`
+fn format_tokens(value: &Value, decimals: usize) -> ClientResult<String> {
+    let amount = value_to_string(value);
+    let (sign, digits) = match amount.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", amount.as_str()),
+    };
+    let digits = match digits.strip_prefix("0x") {
+        Some(hex) => u128::from_str_radix(hex, 16)
+            .map(|amount| amount.to_string())
+            .map_err(|err| Error::invalid_render_template(format!("`{}`: {}", amount, err)))?,
+        None => digits.to_string(),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(Error::invalid_render_template(format!(
+            "`{}` is not a token amount",
+            amount
+        )));
+    }
+    let digits = format!("{:0>width$}", digits, width = decimals + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - decimals);
+    let integer = integer.trim_start_matches('0');
+    let fraction = fraction.trim_end_matches('0');
+    Ok(format!(
+        "{}{}{}{}",
+        sign,
+        if integer.is_empty() { "0" } else { integer },
+        if fraction.is_empty() { "" } else { "." },
+        fraction
+    ))
+}
+
+/// Keeps the workchain and the beginning and the end of the account id,
+/// e.g. `0:a1b2c3…e5f6`
+fn format_address(value: &Value) -> String {
+    let address = value_to_string(value);
+    let (workchain, account) = match address.find(':') {
+        Some(pos) => address.split_at(pos + 1),
+        None => ("", address.as_str()),
+    };
+    if account.len() <= ADDRESS_PREFIX_LEN + ADDRESS_SUFFIX_LEN {
+        return address.clone();
+    }
+    format!(
+        "{}{}…{}",
+        workchain,
+        &account[..ADDRESS_PREFIX_LEN],
+        &account[account.len() - ADDRESS_SUFFIX_LEN..]
+    )
+}
+
+fn render_placeholder(placeholder: &str, params: &Value) -> ClientResult<String> {
+    let mut parts = placeholder.splitn(2, '|');
+    let path = parts.next().unwrap_or_default().trim();
+    let mut value = params;
+    for name in path.split('.') {
+        value = value.get(name).ok_or_else(|| {
+            Error::invalid_render_template(format!("parameter `{}` not found", path))
+        })?;
+    }
+    let helper = match parts.next() {
+        Some(helper) => helper.trim(),
+        None => return Ok(value_to_string(value)),
+    };
+    let mut helper_parts = helper.splitn(2, ':');
+    match (helper_parts.next().unwrap_or_default(), helper_parts.next()) {
+        ("tokens", decimals) => {
+            let decimals = match decimals {
+                Some(decimals) => decimals.trim().parse().map_err(|_| {
+                    Error::invalid_render_template(format!("invalid decimals `{}`", decimals))
+                })?,
+                None => DEFAULT_TOKEN_DECIMALS,
+            };
+            format_tokens(value, decimals)
+        }
+        ("address", None) => Ok(format_address(value)),
+        _ => Err(Error::invalid_render_template(format!(
+            "unknown helper `{}`",
+            helper
+        ))),
+    }
+}
+
+fn render_template(template: &str, params: &Value) -> ClientResult<String> {
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => {
+                            return Err(Error::invalid_render_template("unclosed `{`"));
+                        }
+                    }
+                }
+                text.push_str(&render_placeholder(&placeholder, params)?);
+            }
+            '}' => return Err(Error::invalid_render_template("unexpected `}`")),
+            c => text.push(c),
+        }
+    }
+    Ok(text)
+}
+
+fn render_default(call: &DecodedMessageBody) -> String {
+    let params = match &call.value {
+        Some(Value::Object(params)) => params
+            .iter()
+            .map(|(name, value)| format!("{}: {}", name, value_to_string(value)))
+            .collect::<Vec<_>>()
+            .join(", "),
+        _ => String::new(),
+    };
+    format!("{}({})", call.name, params)
+}
+
+/// Renders decoded function call or event as a human-readable text.
+///
+/// The template is taken from the `template` parameter or from the `render` field of
+/// the ABI function or event, e.g.
+/// `"render": "Transfer {value|tokens} to {dest|address}"`.
+///
+/// Template placeholders are the parameter names in braces. Nested parameters are
+/// separated with dots: `{transfer.amount}`. Braces are escaped by doubling: `{{`, `}}`.
+/// A placeholder can be followed by a helper:
+/// - `tokens` - formats the integer amount of nanotokens: `{value|tokens}` renders
+/// `1500000000` as `1.5`. Number of decimals can be specified: `{amount|tokens:6}`.
+/// - `address` - shortens the address: `0:a1b2c3…e5f6`.
+///
+/// If there is no template, the call is rendered as `name(param: value, ...)`.
+#[api_function]
+pub fn render_call(
+    _context: Arc<ClientContext>,
+    params: ParamsOfRenderCall,
+) -> ClientResult<ResultOfRenderCall> {
+    let template = match params.template {
+        Some(template) => Some(template),
+        None => abi_template(&params.abi, &params.call)?,
+    };
+    let text = match template {
+        Some(template) => render_template(
+            &template,
+            params.call.value.as_ref().unwrap_or(&Value::Null),
+        )?,
+        None => render_default(&params.call),
+    };
+    Ok(ResultOfRenderCall { text })
+}
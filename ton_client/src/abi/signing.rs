@@ -1,4 +1,5 @@
 use crate::ClientContext;
+use crate::abi::Error;
 use crate::crypto::{KeyPair, SigningBoxHandle};
 use crate::error::ClientResult;
 use std::sync::Arc;
@@ -16,6 +17,9 @@ pub enum Signer {
     /// Signing Box interface is provided for signing, allows Dapps to sign messages using external APIs,
     /// such as HSM, cold wallet, etc.
     SigningBox { handle: SigningBoxHandle },
+    /// Default signer of the context is used for signing (see `default_signer` parameter
+    /// of `client.create_derived_context`). Fails if the context has no default signer.
+    Default,
 }
 
 impl Default for Signer {
@@ -25,6 +29,16 @@ impl Default for Signer {
 }
 
 impl Signer {
+    /// Replaces `Signer::Default` with the default signer of the context
+    pub(crate) fn resolve_default(self, context: &ClientContext) -> ClientResult<Signer> {
+        match self {
+            Signer::Default => context.default_signer.clone().ok_or_else(|| {
+                Error::invalid_signer("Context has no default signer".to_string())
+            }),
+            signer => Ok(signer),
+        }
+    }
+
     pub(crate) fn is_external(&self) -> bool {
         if let Signer::External { .. } = self {
             true
@@ -36,7 +50,7 @@ impl Signer {
 
 impl Signer {
     pub async fn sign(&self, context: Arc<ClientContext>, data_to_sign: &[u8]) -> ClientResult<Option<Vec<u8>>> {
-        match self {
+        match &self.clone().resolve_default(&context)? {
             Signer::None => Ok(None),
             Signer::Keys { keys } => {
                 crate::crypto::internal::sign_using_keys(data_to_sign, &keys.decode()?)
@@ -44,22 +58,20 @@ impl Signer {
             },
             Signer::External { .. } => Ok(None),
             Signer::SigningBox { handle } => {
-                let result = crate::crypto::signing_box_sign(
-                    context,
-                    crate::crypto::ParamsOfSigningBoxSign {
-                        signing_box: handle.clone(),
-                        unsigned: base64::encode(data_to_sign)
-                    }
-                ).await?;
-
-                Some(crate::encoding::hex_decode(&result.signature))
-                    .transpose()
+                crate::crypto::boxes::signing_box::sign_message_with_signing_box(
+                    &context,
+                    handle,
+                    data_to_sign,
+                ).await.map(Some)
             },
+            Signer::Default => Err(Error::invalid_signer(
+                "Default signer can't be `Default`".to_string(),
+            )),
         }
     }
 
     pub async fn resolve_public_key(&self, context: Arc<ClientContext>) -> ClientResult<Option<String>> {
-        match self {
+        match &self.clone().resolve_default(&context)? {
             Signer::None => Ok(None),
             Signer::Keys { keys } => Ok(Some(keys.public.clone())),
             Signer::External { public_key } => Ok(Some(public_key.clone())),
@@ -73,6 +85,9 @@ impl Signer {
                     .await
                     .map(|result| Some(result.pubkey))
             },
+            Signer::Default => Err(Error::invalid_signer(
+                "Default signer can't be `Default`".to_string(),
+            )),
         }
     }
 }
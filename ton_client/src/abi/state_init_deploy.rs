@@ -0,0 +1,172 @@
+use crate::abi::encode_account::resolve_state_init;
+use crate::abi::{Error, StateInitSource};
+use crate::boc::internal::{
+    deserialize_object_from_boc, serialize_object_to_base64, serialize_object_to_boc,
+};
+use crate::boc::BocCacheType;
+use crate::client::ClientContext;
+use crate::encoding::{account_decode, account_encode};
+use crate::error::ClientResult;
+use std::str::FromStr;
+use std::sync::Arc;
+use ton_block::{
+    CurrencyCollection, ExternalInboundMessageHeader, GetRepresentationHash,
+    InternalMessageHeader, Message, MsgAddressInt, MsgAddressIntOrNone, StateInit,
+};
+
+//-------------------------------------------------------------- encode_state_init_deploy_message
+
+#[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default)]
+pub struct ParamsOfEncodeStateInitDeployMessage {
+    /// Source of the deployed account state init.
+    pub state_init: StateInitSource,
+
+    /// Target workchain for destination address. Default is `0`.
+    pub workchain_id: Option<i32>,
+
+    /// Value in nanotokens to be sent with the message.
+    ///
+    /// If specified, the internal message is produced (deploy by the value transfer).
+    /// Otherwise the external inbound message is produced.
+    pub value: Option<String>,
+
+    /// Source address of the internal message.
+    pub src_address: Option<String>,
+
+    /// Flag of bounceable internal message. Default is false.
+    pub bounce: Option<bool>,
+
+    /// Cache type to put the result. The BOC itself returned if no cache type provided
+    pub boc_cache: Option<BocCacheType>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default)]
+pub struct ResultOfEncodeStateInitDeployMessage {
+    /// Message BOC encoded with `base64`.
+    pub message: String,
+
+    /// Deployed account address derived from the state init.
+    pub address: String,
+
+    /// Message id.
+    pub message_id: String,
+}
+
+fn state_init_address(state_init: &StateInit, workchain_id: i32) -> ClientResult<MsgAddressInt> {
+    let hash = state_init
+        .hash()
+        .map_err(|err| Error::encode_deploy_message_failed(err))?;
+    MsgAddressInt::with_standart(None, workchain_id as i8, hash.into())
+        .map_err(|err| Error::encode_deploy_message_failed(err))
+}
+
+/// Encodes a deploy message consisting only of the state init.
+///
+/// The message has an empty body, so no ABI, call set or public key is required.
+/// Such message deploys the contracts which don't have a constructor or accept
+/// the deploy by a simple value transfer.
+///
+/// The destination address is derived from the state init and the `workchain_id`.
+#[api_function]
+pub async fn encode_state_init_deploy_message(
+    context: Arc<ClientContext>,
+    params: ParamsOfEncodeStateInitDeployMessage,
+) -> ClientResult<ResultOfEncodeStateInitDeployMessage> {
+    let state_init = resolve_state_init(&context, &params.state_init).await?;
+    let workchain_id = params.workchain_id.unwrap_or(context.config.abi.workchain);
+    let address = state_init_address(&state_init, workchain_id)?;
+
+    let mut message = match &params.value {
+        Some(value) => {
+            let value = u64::from_str(value)
+                .map_err(|err| Error::encode_deploy_message_failed(err))?;
+            let mut header = InternalMessageHeader::with_addresses(
+                Default::default(),
+                address.clone(),
+                CurrencyCollection::with_grams(value),
+            );
+            header.src = match &params.src_address {
+                Some(src) => MsgAddressIntOrNone::Some(account_decode(src)?),
+                None => MsgAddressIntOrNone::None,
+            };
+            header.bounce = params.bounce.unwrap_or(false);
+            Message::with_int_header(header)
+        }
+        None => Message::with_ext_in_header(ExternalInboundMessageHeader {
+            dst: address.clone(),
+            ..Default::default()
+        }),
+    };
+    message.set_state_init(state_init);
+
+    let hash = message
+        .hash()
+        .map_err(|err| Error::encode_deploy_message_failed(err))?;
+    Ok(ResultOfEncodeStateInitDeployMessage {
+        message: serialize_object_to_boc(&context, &message, "message", params.boc_cache).await?,
+        address: account_encode(&address),
+        message_id: hex::encode(hash),
+    })
+}
+
+//-------------------------------------------------------------- decode_state_init_deploy_message
+
+#[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default)]
+pub struct ParamsOfDecodeStateInitDeployMessage {
+    /// Message BOC encoded with `base64` or BOC handle.
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default)]
+pub struct ResultOfDecodeStateInitDeployMessage {
+    /// State init BOC encoded with `base64`.
+    pub state_init: String,
+
+    /// Deployed account address.
+    pub address: String,
+
+    /// Value in nanotokens sent with the message. `None` for the external inbound message.
+    pub value: Option<String>,
+}
+
+/// Decodes a deploy message consisting only of the state init.
+///
+/// Fails if the message has a non-empty body, has no state init or its destination
+/// address doesn't match the address derived from the state init.
+#[api_function]
+pub async fn decode_state_init_deploy_message(
+    context: Arc<ClientContext>,
+    params: ParamsOfDecodeStateInitDeployMessage,
+) -> ClientResult<ResultOfDecodeStateInitDeployMessage> {
+    let message = deserialize_object_from_boc::<Message>(&context, &params.message, "message")
+        .await?
+        .object;
+
+    if let Some(body) = message.body() {
+        if body.remaining_bits() > 0 || body.remaining_references() > 0 {
+            return Err(Error::invalid_message_for_decode("message body is not empty"));
+        }
+    }
+    let state_init = message
+        .state_init()
+        .ok_or_else(|| Error::invalid_message_for_decode("missing `state_init`"))?;
+    let dst = message
+        .dst()
+        .ok_or_else(|| Error::invalid_message_for_decode("message has no destination"))?;
+    let address = state_init_address(state_init, dst.workchain_id())
+        .map_err(|err| Error::invalid_message_for_decode(err.message))?;
+    if address != dst {
+        return Err(Error::invalid_message_for_decode(format!(
+            "destination {} doesn't match the state init address {}",
+            dst, address
+        )));
+    }
+
+    Ok(ResultOfDecodeStateInitDeployMessage {
+        state_init: serialize_object_to_base64(state_init, "state init")?,
+        address: account_encode(&address),
+        value: message
+            .int_header()
+            .map(|header| header.value.grams.0.to_string()),
+    })
+}
@@ -81,6 +81,7 @@ fn encode_v2() {
         }),
         signer: signing,
         processing_try_index: None,
+        expiration: None,
     };
 
     let unsigned: ResultOfEncodeMessage = client
@@ -158,6 +159,7 @@ fn encode_v2() {
         }),
         signer: signing,
         processing_try_index: None,
+        expiration: None,
     };
     let body_params = |run_params: ParamsOfEncodeMessage| ParamsOfEncodeMessageBody {
         abi: run_params.abi,
@@ -301,6 +303,7 @@ fn decode_v2() {
                 ParamsOfDecodeMessage {
                     abi: events_abi.clone(),
                     message: message.into(),
+                    partial: None,
                 },
             )
             .unwrap();
@@ -320,6 +323,7 @@ fn decode_v2() {
                     abi: events_abi.clone(),
                     body,
                     is_internal: parsed.parsed["msg_type_name"] == "Internal",
+                    partial: None,
                 },
             )
             .unwrap();
@@ -337,6 +341,7 @@ fn decode_v2() {
             time: Some(1599458364291),
             pubkey: Some("4c7c408ff1ddebb8d6405ee979c716a14fdd6cc08124107a61d3c25597099499".into()),
         }),
+        diagnostics: None,
     };
     assert_eq!(expected, decode_events("te6ccgEBAwEAvAABRYgAC31qq9KF9Oifst6LU9U6FQSQQRlCSEMo+A3LN5MvphIMAQHhrd/b+MJ5Za+AygBc5qS/dVIPnqxCsM9PvqfVxutK+lnQEKzQoRTLYO6+jfM8TF4841bdNjLQwIDWL4UVFdxIhdMfECP8d3ruNZAXul5xxahT91swIEkEHph08JVlwmUmQAAAXRnJcuDX1XMZBW+LBKACAEAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=="));
 
@@ -347,6 +352,7 @@ fn decode_v2() {
             "id": abi_uint(0, 256)
         })),
         header: None,
+        diagnostics: None,
     };
     assert_eq!(expected, decode_events("te6ccgEBAQEAVQAApeACvg5/pmQpY4m61HmJ0ne+zjHJu3MNG8rJxUDLbHKBu/AAAAAAAAAMJL6z6ro48sYvAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABA"));
 
@@ -354,6 +360,7 @@ fn decode_v2() {
         abi: events_abi.clone(),
         body: "te6ccgEBAgEAlgAB4a3f2/jCeWWvgMoAXOakv3VSD56sQrDPT76n1cbrSvpZ0BCs0KEUy2Duvo3zPExePONW3TYy0MCA1i+FFRXcSIXTHxAj/Hd67jWQF7peccWoU/dbMCBJBB6YdPCVZcJlJkAAAF0ZyXLg19VzGQVviwSgAQBAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=".into(),
         is_internal: false,
+        partial: None,
     }).unwrap();
     let expected = DecodedMessageBody {
         body_type: MessageBodyType::Input,
@@ -366,6 +373,7 @@ fn decode_v2() {
             time: Some(1599458364291),
             pubkey: Some("4c7c408ff1ddebb8d6405ee979c716a14fdd6cc08124107a61d3c25597099499".into()),
         }),
+        diagnostics: None,
     };
     assert_eq!(expected, result);
 
@@ -376,10 +384,77 @@ fn decode_v2() {
             "value0": abi_uint(0, 256)
         })),
         header: None,
+        diagnostics: None,
     };
     assert_eq!(expected, decode_events("te6ccgEBAQEAVQAApeACvg5/pmQpY4m61HmJ0ne+zjHJu3MNG8rJxUDLbHKBu/AAAAAAAAAMKr6z6rxK3xYJAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABA"));
 }
 
+#[test]
+fn decode_events_from_transaction() {
+    let client = TestClient::new();
+    let (events_abi, _events_tvc) = TestClient::package(EVENTS, Some(2));
+
+    let event_message = "te6ccgEBAQEAVQAApeACvg5/pmQpY4m61HmJ0ne+zjHJu3MNG8rJxUDLbHKBu/AAAAAAAAAMJL6z6ro48sYvAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABA";
+    let output_message = "te6ccgEBAQEAVQAApeACvg5/pmQpY4m61HmJ0ne+zjHJu3MNG8rJxUDLbHKBu/AAAAAAAAAMKr6z6rxK3xYJAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAABA";
+    let mut transaction = ton_block::Transaction::default();
+    for message in &[output_message, event_message] {
+        let message = deserialize_object_from_base64::<Message>(message, "message").unwrap();
+        transaction.add_out_message(&message.object).unwrap();
+    }
+    let transaction = serialize_object_to_base64(&transaction, "transaction").unwrap();
+
+    let event_id: ResultOfGetEventId = client
+        .request(
+            "abi.get_event_id",
+            ParamsOfGetEventId {
+                abi: events_abi.clone(),
+                event_name: "EventThrown".into(),
+            },
+        )
+        .unwrap();
+
+    let result: ResultOfDecodeEventsFromTransaction = client
+        .request(
+            "abi.decode_events_from_transaction",
+            ParamsOfDecodeEventsFromTransaction {
+                abi: events_abi.clone(),
+                transaction: transaction.clone(),
+                event_names: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(result.events.len(), 1);
+    let event = &result.events[0];
+    assert_eq!(event.name, "EventThrown");
+    assert_eq!(event.event_id, event_id.event_id);
+    assert_eq!(event.value, json!({ "id": abi_uint(0, 256) }));
+    assert_eq!(event.index, 1);
+    assert_eq!(event.message_id, get_boc_hash(&base64::decode(event_message).unwrap()).unwrap());
+
+    let result: ResultOfDecodeEventsFromTransaction = client
+        .request(
+            "abi.decode_events_from_transaction",
+            ParamsOfDecodeEventsFromTransaction {
+                abi: events_abi.clone(),
+                transaction,
+                event_names: Some(vec!["EventThrown".into()]),
+            },
+        )
+        .unwrap();
+    assert_eq!(result.events.len(), 1);
+
+    let error = client
+        .request::<_, ResultOfGetEventId>(
+            "abi.get_event_id",
+            ParamsOfGetEventId {
+                abi: events_abi,
+                event_name: "UnknownEvent".into(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidAbi as u32);
+}
+
 #[test]
 fn test_is_empty_pubkey() -> Result<()> {
     let pubkey = ed25519_dalek::PublicKey::from_bytes(&[0; 32])?;
@@ -542,6 +617,7 @@ async fn test_encode_message_pubkey_internal(
             Signer::None
         },
         processing_try_index: None,
+        expiration: None,
         address: None,
         call_set: CallSet::some_with_function("constructor"),
     };
@@ -914,7 +990,7 @@ fn test_decode_account_data() {
     let client = TestClient::new();
     let decoded = client.request::<_, ResultOfDecodeAccountData>(
         "abi.decode_account_data",
-        ParamsOfDecodeAccountData { data, abi },
+        ParamsOfDecodeAccountData { data, abi, layout: None },
     )
     .unwrap()
     .data;
@@ -939,6 +1015,75 @@ fn test_decode_account_data() {
     );
 }
 
+#[test]
+fn test_decode_account_data_layout() {
+    let abi = Abi::Json(ACCOUNT_ABI.to_owned());
+    let state = deserialize_object_from_base64::<ton_block::StateInit>(ACCOUNT_STATE, "state").unwrap();
+    let data = serialize_cell_to_base64(&state.object.data.unwrap(), "data").unwrap();
+
+    let client = TestClient::new();
+    let decode = |layout: DataLayoutOptions| client.request::<_, ResultOfDecodeAccountData>(
+        "abi.decode_account_data",
+        ParamsOfDecodeAccountData { data: data.clone(), abi: abi.clone(), layout: Some(layout) },
+    );
+
+    let decoded = decode(DataLayoutOptions {
+        number_overflow: Some(NumberOverflow::HexString),
+        flatten: Some(true),
+        type_hints: Some(true),
+        ..Default::default()
+    })
+    .unwrap();
+
+    assert_eq!(
+        decoded.data,
+        json!({
+            "__pubkey": "0xe8b1d839abe27b2abb9d4a2943a9143a9c7e2ae06799bd24dec1d7a8891ae5dd",
+            "__timestamp": 1626254942358u64,
+            "fun": 22,
+            "opt": "48656c6c6f",
+            "big.value0": 2,
+            "big.value1": 8,
+            "big.value2": 2,
+            "big.value3": 0,
+            "a": "49206c696b652069742e",
+            "b": "",
+            "length": 15
+        })
+    );
+    assert_eq!(
+        decoded.types,
+        Some(json!({
+            "__pubkey": "uint256",
+            "__timestamp": "uint64",
+            "fun": "uint32",
+            "opt": "optional(bytes)",
+            "big.value0": "uint256",
+            "big.value1": "uint256",
+            "big.value2": "uint256",
+            "big.value3": "uint256",
+            "a": "bytes",
+            "b": "bytes",
+            "length": "uint256"
+        }))
+    );
+
+    let decoded = decode(Default::default()).unwrap();
+    assert_eq!(
+        decoded.data["__pubkey"],
+        "105250805133813013722741938851571590797703291246458739632151736768847001871837"
+    );
+    assert_eq!(decoded.data["big"], json!({ "value0": 2, "value1": 8, "value2": 2, "value3": 0 }));
+    assert!(decoded.types.is_none());
+
+    let err = decode(DataLayoutOptions {
+        number_overflow: Some(NumberOverflow::Error),
+        ..Default::default()
+    })
+    .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidData as u32);
+}
+
 #[test]
 fn test_init_data() {
     let client = TestClient::new();
@@ -1050,7 +1195,8 @@ fn test_decode_boc() {
         ParamsOfDecodeBoc { 
             boc: boc.clone(),
             params: params.clone(),
-            allow_partial: false
+            allow_partial: false,
+            partial: None,
         },
     )
     .unwrap()
@@ -1072,7 +1218,8 @@ fn test_decode_boc() {
         ParamsOfDecodeBoc { 
             boc: boc.clone(),
             params: params.clone(),
-            allow_partial: true
+            allow_partial: true,
+            partial: None,
         },
     )
     .unwrap()
@@ -1087,6 +1234,65 @@ fn test_decode_boc() {
     );
 }
 
+#[test]
+fn test_decode_boc_diagnostics() {
+    let mut builder = BuilderData::new();
+    builder.append_u32(5).unwrap();
+    builder.append_u8(7).unwrap();
+    builder.append_u8(8).unwrap();
+    let boc = serialize_cell_to_base64(&builder.into_cell().unwrap(), "").unwrap();
+
+    let params = vec![
+        AbiParam { name: "a".to_owned(), param_type: "uint32".to_owned(), ..Default::default() },
+        AbiParam {
+            name: "t".to_owned(),
+            param_type: "tuple".to_owned(),
+            components: vec![
+                AbiParam { name: "x".to_owned(), param_type: "uint8".to_owned(), ..Default::default() },
+                AbiParam { name: "y".to_owned(), param_type: "uint256".to_owned(), ..Default::default() },
+            ],
+        },
+    ];
+    let expected = DecodeDiagnostics {
+        path: Some("t.y".to_owned()),
+        expected_type: Some("uint256".to_owned()),
+        bit_offset: 40,
+        remaining_bits: 8,
+        remaining_refs: 0,
+        decoded_count: 1,
+        message: String::new(),
+    };
+
+    let client = TestClient::new();
+    let error = client.request::<_, ResultOfDecodeBoc>(
+        "abi.decode_boc",
+        ParamsOfDecodeBoc {
+            boc: boc.clone(),
+            params: params.clone(),
+            allow_partial: false,
+            partial: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidData as u32);
+    let diagnostics: DecodeDiagnostics = serde_json::from_value(error.data["diagnostics"].clone()).unwrap();
+    assert_eq!(diagnostics, DecodeDiagnostics { message: diagnostics.message.clone(), ..expected.clone() });
+
+    let decoded = client.request::<_, ResultOfDecodeBoc>(
+        "abi.decode_boc",
+        ParamsOfDecodeBoc {
+            boc,
+            params,
+            allow_partial: false,
+            partial: Some(true),
+        },
+    )
+    .unwrap();
+    assert_eq!(decoded.data, json!({ "a": "5" }));
+    let diagnostics = decoded.diagnostics.unwrap();
+    assert_eq!(diagnostics, DecodeDiagnostics { message: diagnostics.message.clone(), ..expected });
+}
+
 #[test]
 fn test_encode_boc() {
     let client = TestClient::new();
@@ -1114,3 +1320,382 @@ fn test_encode_boc() {
 
     assert_eq!(boc, "te6ccgEBAQEANAAAY5/mZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmZmAAAAAAAAAAAAAAAAACWtD4");
 }
+
+#[test]
+fn test_encode_decode_boc_refs() {
+    let client = TestClient::new();
+
+    let params = vec![
+        AbiParam { name: "a".to_owned(), param_type: "uint32".to_owned(), ..Default::default() },
+        AbiParam {
+            name: "b".to_owned(),
+            param_type: "ref(tuple)".to_owned(),
+            components: vec![
+                AbiParam { name: "c".to_owned(), param_type: "uint64".to_owned(), ..Default::default() },
+                AbiParam { name: "d".to_owned(), param_type: "ref(int64[])".to_owned(), ..Default::default() },
+            ],
+        },
+        AbiParam { name: "e".to_owned(), param_type: "ref(address)".to_owned(), ..Default::default() },
+    ];
+    let data = json!({
+        "a": "1",
+        "b": {
+            "c": "2",
+            "d": ["-3", "4"],
+        },
+        "e": "0:3333333333333333333333333333333333333333333333333333333333333333",
+    });
+
+    let boc = client.request::<_, ResultOfAbiEncodeBoc>(
+        "abi.encode_boc",
+        ParamsOfAbiEncodeBoc {
+            params: params.clone(),
+            data: data.clone(),
+            boc_cache: None,
+        },
+    )
+        .unwrap()
+        .boc;
+
+    let decoded = client.request::<_, ResultOfDecodeBoc>(
+        "abi.decode_boc",
+        ParamsOfDecodeBoc {
+            boc,
+            params,
+            allow_partial: false,
+            partial: None,
+        },
+    )
+        .unwrap()
+        .data;
+
+    assert_eq!(decoded, data);
+}
+
+#[test]
+fn test_placeholder_signature() {
+    let client = TestClient::new();
+    let (abi, _) = TestClient::package(EVENTS, Some(2));
+    let keys = KeyPair {
+        public: "4c7c408ff1ddebb8d6405ee979c716a14fdd6cc08124107a61d3c25597099499".into(),
+        secret: "cc8929d635719612a9478b9cd17675a39cfad52d8959e8a177389b8c0b9122a7".into(),
+    };
+    let params = |signer: Signer| ParamsOfEncodeMessage {
+        address: Some("0:05beb555e942fa744fd96f45a9ea9d0a8248208ca12421947c06e59bc997d309".into()),
+        abi: abi.clone(),
+        deploy_set: None,
+        call_set: Some(CallSet {
+            function_name: "returnValue".into(),
+            header: Some(FunctionHeader {
+                pubkey: None,
+                time: Some(1599458364291),
+                expire: Some(1599458404),
+            }),
+            input: Some(json!({ "id": "0" })),
+        }),
+        signer,
+        processing_try_index: None,
+        expiration: None,
+    };
+    let signed_message = "te6ccgEBAwEAvAABRYgAC31qq9KF9Oifst6LU9U6FQSQQRlCSEMo+A3LN5MvphIMAQHhrd/b+MJ5Za+AygBc5qS/dVIPnqxCsM9PvqfVxutK+lnQEKzQoRTLYO6+jfM8TF4841bdNjLQwIDWL4UVFdxIhdMfECP8d3ruNZAXul5xxahT91swIEkEHph08JVlwmUmQAAAXRnJcuDX1XMZBW+LBKACAEAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA==";
+
+    let placeholder: ResultOfEncodeMessage = client
+        .request(
+            "abi.encode_message_with_placeholder_signature",
+            params(Signer::External { public_key: keys.public.clone() }),
+        )
+        .unwrap();
+    let data_to_sign = placeholder.data_to_sign.clone().unwrap();
+    assert_eq!(data_to_sign, "i4Hs3PB12QA9UBFbOIpkG3JerHHqjm4LgvF4MA7TDsY=");
+    assert_ne!(placeholder.message, signed_message);
+
+    let signature = client.sign_detached(&data_to_sign, &keys);
+    let attach = |message: &str, data_to_sign: &str, signature: &str| {
+        client.request::<_, ResultOfAttachSignature>(
+            "abi.attach_signature_to_unsigned",
+            ParamsOfAttachSignatureToUnsigned {
+                abi: abi.clone(),
+                public_key: keys.public.clone(),
+                message: message.to_owned(),
+                data_to_sign: data_to_sign.to_owned(),
+                signature: signature.to_owned(),
+            },
+        )
+    };
+
+    let signed = attach(&placeholder.message, &data_to_sign, &signature).unwrap();
+    assert_eq!(signed.message, signed_message);
+
+    // unsigned message produced with `External` signer is accepted as well
+    let unsigned: ResultOfEncodeMessage = client
+        .request(
+            "abi.encode_message",
+            params(Signer::External { public_key: keys.public.clone() }),
+        )
+        .unwrap();
+    let signed = attach(&unsigned.message, &data_to_sign, &signature).unwrap();
+    assert_eq!(signed.message, signed_message);
+
+    // payload mismatch, invalid signature and already signed message are rejected
+    let other_data = base64::encode(&[0u8; 32]);
+    assert!(attach(&placeholder.message, &other_data, &signature).is_err());
+    let other_signature = client.sign_detached(&other_data, &keys);
+    assert!(attach(&placeholder.message, &data_to_sign, &other_signature).is_err());
+    assert!(attach(signed_message, &data_to_sign, &signature).is_err());
+
+    // message body
+    let parsed: crate::boc::ResultOfParse = client
+        .request(
+            "boc.parse_message",
+            crate::boc::ParamsOfParse { boc: placeholder.message },
+        )
+        .unwrap();
+    let signed_body: ResultOfAttachSignatureToMessageBody = client
+        .request(
+            "abi.attach_signature_to_unsigned_body",
+            ParamsOfAttachSignatureToUnsignedBody {
+                abi: abi.clone(),
+                public_key: keys.public.clone(),
+                body: parsed.parsed["body"].as_str().unwrap().to_owned(),
+                data_to_sign,
+                signature,
+            },
+        )
+        .unwrap();
+    let parsed: crate::boc::ResultOfParse = client
+        .request(
+            "boc.parse_message",
+            crate::boc::ParamsOfParse { boc: signed_message.to_owned() },
+        )
+        .unwrap();
+    assert_eq!(signed_body.body, parsed.parsed["body"].as_str().unwrap());
+}
+
+#[test]
+fn test_state_init_deploy_message() {
+    let client = TestClient::new();
+    let (abi, tvc) = TestClient::package(HELLO, None);
+    let state_init = StateInitSource::Tvc {
+        tvc: tvc.clone(),
+        public_key: Some(hex::encode(gen_pubkey().as_bytes())),
+        init_params: None,
+    };
+
+    let account: ResultOfEncodeAccount = client
+        .request(
+            "abi.encode_account",
+            ParamsOfEncodeAccount {
+                state_init: state_init.clone(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let external: ResultOfEncodeStateInitDeployMessage = client
+        .request(
+            "abi.encode_state_init_deploy_message",
+            ParamsOfEncodeStateInitDeployMessage {
+                state_init: state_init.clone(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(external.address, format!("0:{}", account.id));
+    assert_eq!(external.message_id, get_boc_hash(&base64::decode(&external.message).unwrap()).unwrap());
+
+    let decoded: ResultOfDecodeStateInitDeployMessage = client
+        .request(
+            "abi.decode_state_init_deploy_message",
+            ParamsOfDecodeStateInitDeployMessage { message: external.message },
+        )
+        .unwrap();
+    assert_eq!(decoded.address, external.address);
+    assert_eq!(decoded.value, None);
+
+    let internal: ResultOfEncodeStateInitDeployMessage = client
+        .request(
+            "abi.encode_state_init_deploy_message",
+            ParamsOfEncodeStateInitDeployMessage {
+                state_init,
+                workchain_id: Some(-1),
+                value: Some("1000000000".to_owned()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    assert_eq!(internal.address, format!("-1:{}", account.id));
+
+    let decoded_internal: ResultOfDecodeStateInitDeployMessage = client
+        .request(
+            "abi.decode_state_init_deploy_message",
+            ParamsOfDecodeStateInitDeployMessage { message: internal.message },
+        )
+        .unwrap();
+    assert_eq!(decoded_internal.address, internal.address);
+    assert_eq!(decoded_internal.state_init, decoded.state_init);
+    assert_eq!(decoded_internal.value, Some("1000000000".to_owned()));
+
+    // Deploy message with the constructor call is not a state init deploy message
+    let deploy: ResultOfEncodeMessage = client
+        .request(
+            "abi.encode_message",
+            ParamsOfEncodeMessage {
+                abi,
+                deploy_set: DeploySet::some_with_tvc(tvc),
+                call_set: CallSet::some_with_function("constructor"),
+                signer: Signer::External {
+                    public_key: hex::encode(gen_pubkey().as_bytes()),
+                },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    let result: crate::error::ClientResult<ResultOfDecodeStateInitDeployMessage> = client.request(
+        "abi.decode_state_init_deploy_message",
+        ParamsOfDecodeStateInitDeployMessage { message: deploy.message },
+    );
+    assert_eq!(result.unwrap_err().code, ErrorCode::InvalidMessage as u32);
+}
+
+#[test]
+fn test_render_call() {
+    let client = TestClient::new();
+
+    let abi = AbiContract {
+        functions: vec![AbiFunction {
+            name: "sendTransaction".to_owned(),
+            render: Some("Send {value|tokens} to {dest|address}, bounce: {bounce}".to_owned()),
+            ..Default::default()
+        }],
+        events: vec![AbiEvent {
+            name: "Paid".to_owned(),
+            render: Some("Paid {{{amount|tokens:6}}} by {payer}".to_owned()),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    let call = DecodedMessageBody {
+        body_type: MessageBodyType::Input,
+        name: "sendTransaction".to_owned(),
+        value: Some(json!({
+            "dest": "0:a1b2c3d4e5f60718293a4b5c6d7e8f90a1b2c3d4e5f60718293a4b5c6d7e8f9",
+            "value": "1500000000",
+            "bounce": true,
+        })),
+        header: None,
+        diagnostics: None,
+    };
+    let render = |abi: Abi, call: DecodedMessageBody, template: Option<&str>| {
+        client.request::<_, ResultOfRenderCall>(
+            "abi.render_call",
+            ParamsOfRenderCall {
+                abi,
+                call,
+                template: template.map(|template| template.to_owned()),
+            },
+        )
+    };
+
+    assert_eq!(
+        render(Abi::Contract(abi.clone()), call.clone(), None).unwrap().text,
+        "Send 1.5 to 0:a1b2c3…e8f9, bounce: true"
+    );
+    assert_eq!(
+        render(Abi::Contract(abi.clone()), call.clone(), Some("{value|tokens:3} {{x}}"))
+            .unwrap()
+            .text,
+        "1500000 {x}"
+    );
+    assert_eq!(
+        render(
+            Abi::Contract(abi.clone()),
+            DecodedMessageBody {
+                body_type: MessageBodyType::Event,
+                name: "Paid".to_owned(),
+                value: Some(json!({ "amount": "0x10", "payer": "0:1" })),
+                ..call.clone()
+            },
+            None,
+        )
+        .unwrap()
+        .text,
+        "Paid {0.000016} by 0:1"
+    );
+
+    // functions without templates are rendered with the parameters list
+    assert_eq!(
+        render(
+            Abi::Contract(AbiContract::default()),
+            DecodedMessageBody {
+                value: Some(json!({ "bounce": false, "value": "1" })),
+                ..call.clone()
+            },
+            None,
+        )
+        .unwrap()
+        .text,
+        "sendTransaction(bounce: false, value: 1)"
+    );
+
+    let err = render(Abi::Contract(abi), call, Some("{amount|tokens}")).unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidRenderTemplate as u32);
+}
+
+#[test]
+fn test_message_expiration() {
+    let client = TestClient::new();
+    let (abi, _) = TestClient::package(EVENTS, Some(2));
+    let encode = |expiration: Option<MessageExpiration>, processing_try_index: Option<u8>| {
+        let message: ResultOfEncodeMessage = client
+            .request(
+                "abi.encode_message",
+                ParamsOfEncodeMessage {
+                    address: Some(
+                        "0:05beb555e942fa744fd96f45a9ea9d0a8248208ca12421947c06e59bc997d309".into(),
+                    ),
+                    abi: abi.clone(),
+                    deploy_set: None,
+                    call_set: CallSet::some_with_function_and_input(
+                        "returnValue",
+                        json!({ "id": "0" }),
+                    ),
+                    signer: Signer::None,
+                    processing_try_index,
+                    expiration,
+                },
+            )
+            .unwrap();
+        let decoded: DecodedMessageBody = client
+            .request(
+                "abi.decode_message",
+                ParamsOfDecodeMessage {
+                    abi: abi.clone(),
+                    message: message.message,
+                    partial: None,
+                },
+            )
+            .unwrap();
+        decoded.header.unwrap().expire.unwrap()
+    };
+    let now = (client.context().env.now_ms() / 1000) as u32;
+
+    // Messages with the fixed expiration time are identical regardless of the retry
+    let fixed = Some(MessageExpiration {
+        expire_at: Some(now + 100),
+        ..Default::default()
+    });
+    assert_eq!(encode(fixed.clone(), None), now + 100);
+    assert_eq!(encode(fixed, Some(3)), now + 100);
+
+    // Timeout overrides the config one, the growth is disabled
+    let timeout = Some(MessageExpiration {
+        timeout: Some(1000000),
+        timeout_grow_factor: Some(1.0),
+        ..Default::default()
+    });
+    assert!((now + 1000..=now + 1002).contains(&encode(timeout.clone(), Some(3))));
+
+    // Default timeout (40 s) grows with the retries (factor 1.5)
+    let expire = encode(None, Some(2));
+    assert!((now + 90..=now + 92).contains(&expire));
+}
@@ -74,6 +74,9 @@ pub struct AbiFunction {
     pub outputs: Vec<AbiParam>,
     #[serde(default)]
     pub id: Option<String>,
+    /// Template used by `abi.render_call`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub render: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default)]
@@ -82,6 +85,9 @@ pub struct AbiEvent {
     pub inputs: Vec<AbiParam>,
     #[serde(default)]
     pub id: Option<String>,
+    /// Template used by `abi.render_call`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub render: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default)]
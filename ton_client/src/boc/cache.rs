@@ -11,7 +11,7 @@
 * limitations under the License.
 */
 
-use crate::boc::internal::{deserialize_cell_from_boc, serialize_cell_to_base64, DeserializedBoc};
+use crate::boc::internal::{deserialize_cell_from_boc, serialize_cell_to_base64};
 use crate::client::ClientContext;
 use crate::error::ClientResult;
 use super::Error;
@@ -22,52 +22,58 @@ use std::sync::Arc;
 #[allow(unused_imports)]
 use std::str::FromStr;
 use tokio::sync::{Mutex, RwLock};
-use ton_types::{Cell, UInt256};
+use ton_types::{BuilderData, Cell, CellType, UInt256};
 
 
 pub const SHA256_SIZE: usize = 32;
 pub const DEPTH_SIZE: usize = 2;
+// Cells of the cache store are not serialized together, so the reference size is fixed
+const CELL_REF_SIZE: usize = 4;
 
-fn number_of_bytes_to_fit(l: usize) -> usize {
-    let mut n = 0;
-    let mut l1 = l;
-    
-    while l1 != 0 {
-        l1 >>= 8;
-        n += 1;
+fn calc_cell_size(cell: &ton_types::Cell) -> usize {
+    let bits = cell.bit_length();
+    2 + if cell.store_hashes() { (cell.level() as usize + 1) * (SHA256_SIZE + DEPTH_SIZE) } else { 0 } +
+        (bits / 8) + if bits % 8 != 0 { 1 } else { 0 } +
+        cell.references_count() * CELL_REF_SIZE
+}
+
+fn calc_subtree_size(cell: &Cell, hashes: &mut HashSet<UInt256>) -> usize {
+    if !hashes.insert(cell.repr_hash()) {
+        return 0;
+    }
+    let mut size = calc_cell_size(cell);
+    for i in 0..cell.references_count() {
+        size += calc_subtree_size(&cell.reference(i).unwrap(), hashes);
     }
+    size
+}
 
-    n
+// Exotic cells are stored as is together with their subtrees, see `CellStore::add_ref`
+fn calc_stored_cell_size(cell: &Cell) -> usize {
+    if cell.cell_type() == CellType::Ordinary {
+        calc_cell_size(cell)
+    } else {
+        calc_subtree_size(cell, &mut HashSet::new())
+    }
 }
 
-fn calc_tree_cells(cell: &ton_types::Cell, hashes: &mut HashSet<ton_types::UInt256>) -> (usize, usize, usize) {
-    let bits = cell.bit_length();
-    let mut size = 2 +
-        if cell.store_hashes() { (cell.level() as usize + 1) * (SHA256_SIZE + DEPTH_SIZE) } else { 0 } +
-        (bits / 8) + if bits % 8 != 0 { 1 } else { 0 };
-    let mut cell_count = 1;
-    let mut refs_count = cell.references_count();
-    hashes.insert(cell.repr_hash());
-
-    for i in 0..refs_count {
-        let cell = cell.reference(i).unwrap();
-        if hashes.contains(&cell.repr_hash()) {
-            continue;
+fn calc_stored_subtree_size(cell: &Cell, hashes: &mut HashSet<UInt256>) -> usize {
+    if !hashes.insert(cell.repr_hash()) {
+        return 0;
+    }
+    let mut size = calc_stored_cell_size(cell);
+    if cell.cell_type() == CellType::Ordinary {
+        for i in 0..cell.references_count() {
+            size += calc_stored_subtree_size(&cell.reference(i).unwrap(), hashes);
         }
-        let subtree = calc_tree_cells(&cell, hashes);
-        size += subtree.0;
-        cell_count += subtree.1;
-        refs_count += subtree.2;
     }
-
-    (size, cell_count, refs_count)
+    size
 }
 
-fn calc_tree_size(cell: &ton_types::Cell) -> usize {
-    let mut hashes = HashSet::new();
-    let (size, cell_count, refs_count) = calc_tree_cells(cell, &mut hashes);
-    let ref_size = number_of_bytes_to_fit(cell_count);
-    size + refs_count * ref_size
+/// Size of the tree accounted against `cache_max_size` when the tree is stored
+/// into the empty unpinned cache
+pub(crate) fn calc_stored_tree_size(cell: &Cell) -> usize {
+    calc_stored_subtree_size(cell, &mut HashSet::new())
 }
 
 #[derive(Serialize, Deserialize, Clone, ApiType, Debug)]
@@ -90,14 +96,121 @@ pub struct PinnedBoc {
     cell: Cell,
 }
 
-pub struct CachedBoc {
-    size: usize,
+struct StoredCell {
     cell: Cell,
+    size: usize,
+    // Number of cached roots and distinct stored parent cells referring to the cell
+    refs: usize,
+}
+
+/// Content-addressable cell store. Each distinct cell is kept once, so the subtrees
+/// shared by several cached BOCs (e.g. the same code of many accounts) don't consume
+/// additional memory.
+#[derive(Default)]
+struct CellStore {
+    cells: HashMap<UInt256, StoredCell>,
+    size: usize,
+}
+
+// Rebuilds ordinary cell on top of the stored child cells so the tree shares memory with
+// the other stored trees
+fn rebuild_cell(cell: &Cell, references: Vec<Cell>) -> Cell {
+    let mut builder = BuilderData::new();
+    let rebuilt = builder
+        .append_raw(cell.data(), cell.bit_length())
+        .ok()
+        .and_then(|builder| {
+            for reference in references {
+                builder.append_reference_cell(reference);
+            }
+            builder.clone().into_cell().ok()
+        });
+    match rebuilt {
+        Some(rebuilt) if rebuilt.repr_hash() == cell.repr_hash() => rebuilt,
+        _ => cell.clone(),
+    }
+}
+
+impl CellStore {
+    /// Adds a reference to the cell, storing the cell and its missing subtree.
+    /// Returns the stored instance of the cell and whether it differs from the `cell`.
+    ///
+    /// Exotic cells are stored as is: their subtrees are neither deduplicated nor
+    /// referred from the store, so the whole subtree is accounted in the exotic cell size.
+    fn add_ref(&mut self, cell: &Cell) -> (Cell, bool) {
+        let hash = cell.repr_hash();
+        if let Some(stored) = self.cells.get_mut(&hash) {
+            stored.refs += 1;
+            return (stored.cell.clone(), true);
+        }
+
+        let mut stored = cell.clone();
+        let mut replaced = false;
+        if cell.cell_type() == CellType::Ordinary {
+            let mut distinct = HashSet::new();
+            let mut references = Vec::with_capacity(cell.references_count());
+            for i in 0..cell.references_count() {
+                let child = cell.reference(i).unwrap();
+                let child_hash = child.repr_hash();
+                // The cell refers to each distinct child once
+                let (child, child_replaced) = if distinct.insert(child_hash.clone()) {
+                    self.add_ref(&child)
+                } else {
+                    (self.get(&child_hash).unwrap_or(child), false)
+                };
+                replaced |= child_replaced;
+                references.push(child);
+            }
+            // Only the cells above the already stored subtrees are rebuilt (and rehashed)
+            if replaced {
+                stored = rebuild_cell(cell, references);
+            }
+        }
+
+        let size = calc_stored_cell_size(cell);
+        self.size += size;
+        self.cells.insert(hash, StoredCell { cell: stored.clone(), size, refs: 1 });
+        (stored, replaced)
+    }
+
+    /// Releases a reference to the cell. Cells without references are removed
+    /// together with the subtree cells which are not referred by other cells.
+    fn release(&mut self, hash: UInt256) {
+        let mut to_release = vec![hash];
+        while let Some(hash) = to_release.pop() {
+            let stored = match self.cells.get_mut(&hash) {
+                Some(stored) => stored,
+                None => continue,
+            };
+            stored.refs -= 1;
+            if stored.refs > 0 {
+                continue;
+            }
+            if let Some(stored) = self.cells.remove(&hash) {
+                self.size -= stored.size;
+                if stored.cell.cell_type() != CellType::Ordinary {
+                    continue;
+                }
+                let mut distinct = HashSet::new();
+                for i in 0..stored.cell.references_count() {
+                    let child_hash = stored.cell.reference(i).unwrap().repr_hash();
+                    if distinct.insert(child_hash.clone()) {
+                        to_release.push(child_hash);
+                    }
+                }
+            }
+        }
+    }
+
+    fn get(&self, hash: &UInt256) -> Option<Cell> {
+        self.cells.get(hash).map(|stored| stored.cell.clone())
+    }
 }
 
 pub struct CachedBocs {
-    bocs: LruCache<UInt256, CachedBoc>,
-    cache_size: usize,
+    // Roots of the cached BOCs in LRU order. Cells are kept in the store
+    roots: LruCache<UInt256, ()>,
+    store: CellStore,
 }
 
 pub struct Bocs {
@@ -113,9 +226,9 @@ impl Bocs {
             .unwrap_or(std::usize::MAX);
         Bocs {
             pinned: RwLock::default(),
-            cached:Mutex::new(CachedBocs {
-                bocs: LruCache::unbounded(),
-                cache_size: 0,
+            cached: Mutex::new(CachedBocs {
+                roots: LruCache::unbounded(),
+                store: CellStore::default(),
             }),
             max_cache_size,
         }
@@ -163,24 +276,28 @@ impl Bocs {
         }
     }
 
-    async fn add_cached(&self, hash: UInt256, cell: Cell, size: usize) -> ClientResult<()> {
-        if size > self.max_cache_size as usize {
+    async fn add_cached(&self, hash: UInt256, cell: Cell) -> ClientResult<()> {
+        // The same metric is used for the cache size, so the BOC which is larger than
+        // the whole cache is rejected instead of evicting all other BOCs
+        let size = calc_stored_tree_size(&cell);
+        if size > self.max_cache_size {
             return Err(Error::insufficient_cache_size(self.max_cache_size, size));
         }
         let mut lock = self.cached.lock().await;
 
-        if let Some(_) = lock.bocs.get(&hash) {
+        if let Some(_) = lock.roots.get(&hash) {
             return Ok(());
         }
 
-        while lock.cache_size + size > self.max_cache_size as usize {
-            let (_, entry) = lock.bocs
-                .pop_lru()
-                .ok_or(Error::insufficient_cache_size(self.max_cache_size, size))?;
-            lock.cache_size -= entry.size;
+        lock.store.add_ref(&cell);
+        lock.roots.put(hash, ());
+
+        // The just added BOC is the most recently used one, so it is evicted the last
+        while lock.store.size > self.max_cache_size && lock.roots.len() > 1 {
+            if let Some((root, _)) = lock.roots.pop_lru() {
+                lock.store.release(root);
+            }
         }
-        lock.bocs.put(hash.clone(), CachedBoc { cell, size });
-        lock.cache_size += size;
 
         Ok(())
     }
@@ -190,7 +307,11 @@ impl Bocs {
     }
 
     async fn get_cached(&self, hash: &UInt256) -> Option<Cell> {
-        self.cached.lock().await.bocs.get(hash).map(|entry| entry.cell.clone())
+        let mut lock = self.cached.lock().await;
+        if lock.roots.get(hash).is_none() {
+            return None;
+        }
+        lock.store.get(hash)
     }
 
     pub(crate) async fn get(&self, hash: &UInt256) -> Option<Cell> {
@@ -206,7 +327,7 @@ impl Bocs {
     }
 
     pub(crate) async fn add(
-        &self, cache_type: BocCacheType, cell: Cell, size: Option<usize>
+        &self, cache_type: BocCacheType, cell: Cell
     ) -> ClientResult<UInt256> {
         let hash = cell.repr_hash();
         log::debug!("Bocs::add {:x}", hash);
@@ -216,8 +337,7 @@ impl Bocs {
                 if let Some(_) = self.get_cached(&hash).await {
                     return Ok(hash);
                 }
-                let size = size.unwrap_or_else(|| calc_tree_size(&cell));
-                self.add_cached(hash.clone(), cell, size).await?;
+                self.add_cached(hash.clone(), cell).await?;
             }
         }
         Ok(hash)
@@ -259,12 +379,8 @@ pub async fn cache_set(
     context: Arc<ClientContext>, 
     params: ParamsOfBocCacheSet,
 ) -> ClientResult<ResultOfBocCacheSet> {
-    let (bytes, cell) = deserialize_cell_from_boc(&context, &params.boc, "BOC").await?;
-    let size = match bytes {
-        DeserializedBoc::Bytes(bytes) => Some(bytes.len()),
-        _ => None,
-    };
-    context.bocs.add(params.cache_type, cell, size)
+    let (_, cell) = deserialize_cell_from_boc(&context, &params.boc, "BOC").await?;
+    context.bocs.add(params.cache_type, cell)
         .await
         .map(|hash| ResultOfBocCacheSet { boc_ref: format!("*{:x}", hash) })
 }
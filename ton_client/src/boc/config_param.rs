@@ -0,0 +1,143 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use crate::boc::internal::{
+    deserialize_cell_from_boc, deserialize_object_from_boc, serialize_cell_to_boc,
+};
+use crate::boc::{BocCacheType, Error};
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use serde_json::{Map, Value};
+use ton_block::{ConfigParamEnum, ConfigParams};
+use ton_types::{BuilderData, SliceData};
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ParamsOfEncodeConfigParam {
+    /// Config parameter index
+    pub number: u32,
+    /// Config parameter value.
+    ///
+    /// JSON structure is compatible with GraphQL API `config.p<number>` field.
+    pub param: Value,
+    /// Cache type to put the result.
+    /// The BOC itself returned if no cache type provided.
+    pub boc_cache: Option<BocCacheType>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ResultOfEncodeConfigParam {
+    /// Config parameter cell BOC encoded as base64 or BOC handle
+    pub boc: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ParamsOfDecodeConfigParam {
+    /// Config parameter index
+    pub number: u32,
+    /// Config parameter cell BOC encoded as base64 or BOC handle
+    pub param_boc: Option<String>,
+    /// Blockchain config BOC encoded as base64 or BOC handle.
+    /// The parameter is read from the config if `param_boc` is not specified.
+    pub config_boc: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ResultOfDecodeConfigParam {
+    /// Config parameter value.
+    ///
+    /// JSON structure is compatible with GraphQL API `config.p<number>` field.
+    pub param: Value,
+}
+
+fn param_field(number: u32) -> String {
+    format!("p{}", number)
+}
+
+/// Encodes the config parameter JSON into the cell stored in the blockchain config
+/// dictionary under the `number` key.
+///
+/// Use it to construct the config-change proposals.
+#[api_function]
+pub async fn encode_config_param(
+    context: std::sync::Arc<ClientContext>,
+    params: ParamsOfEncodeConfigParam,
+) -> ClientResult<ResultOfEncodeConfigParam> {
+    let mut map = Map::new();
+    map.insert(param_field(params.number), params.param);
+    let config = ton_block_json::parse_config(&map)
+        .map_err(|err| Error::invalid_config_param(params.number, err))?;
+    let param = config
+        .config(params.number)
+        .map_err(|err| Error::invalid_config_param(params.number, err))?
+        .ok_or_else(|| Error::invalid_config_param(params.number, "unknown parameter index"))?;
+
+    let mut builder = BuilderData::new();
+    param
+        .write_to_cell(&mut builder)
+        .map_err(|err| Error::serialization_error(err, "config param"))?;
+    let cell = builder
+        .into_cell()
+        .map_err(|err| Error::serialization_error(err, "config param"))?;
+
+    Ok(ResultOfEncodeConfigParam {
+        boc: serialize_cell_to_boc(&context, cell, "config param", params.boc_cache).await?,
+    })
+}
+
+/// Decodes the config parameter cell into JSON.
+///
+/// JSON structure is compatible with GraphQL API `config.p<number>` field.
+#[api_function]
+pub async fn decode_config_param(
+    context: std::sync::Arc<ClientContext>,
+    params: ParamsOfDecodeConfigParam,
+) -> ClientResult<ResultOfDecodeConfigParam> {
+    let config = match (&params.param_boc, &params.config_boc) {
+        (Some(param_boc), _) => {
+            let (_, cell) = deserialize_cell_from_boc(&context, param_boc, "config param").await?;
+            let param = ConfigParamEnum::construct_from_slice_and_number(
+                &mut SliceData::from(cell),
+                params.number,
+            )
+            .map_err(|err| Error::invalid_config_param(params.number, err))?;
+            let mut config = ConfigParams::default();
+            config
+                .set_config(param)
+                .map_err(|err| Error::invalid_config_param(params.number, err))?;
+            config
+        }
+        (None, Some(config_boc)) => {
+            deserialize_object_from_boc::<ConfigParams>(&context, config_boc, "config")
+                .await?
+                .object
+        }
+        (None, None) => {
+            return Err(Error::invalid_config_param(
+                params.number,
+                "either `param_boc` or `config_boc` must be specified",
+            ))
+        }
+    };
+
+    let json = ton_block_json::serialize_config_param(&config, params.number)
+        .map_err(|err| Error::invalid_config_param(params.number, err))?;
+    let mut param: Value = serde_json::from_str(&json)
+        .map_err(|err| Error::serialization_error(err, "config param"))?;
+    let field = param_field(params.number);
+    if param.get(&field).is_some() {
+        param = param[&field].take();
+    }
+
+    Ok(ResultOfDecodeConfigParam { param })
+}
@@ -23,6 +23,8 @@ pub enum ErrorCode {
     InsufficientCacheSize = 205,
     BocRefNotFound = 206,
     InvalidBocRef = 207,
+    InvalidMerkleUpdate = 208,
+    InvalidConfigParam = 209,
 }
 pub struct Error;
 
@@ -83,4 +85,20 @@ impl Error {
         error.data["boc_ref"] = boc_ref.into();
         error
     }
+
+    pub fn invalid_merkle_update<E: Display>(err: E) -> ClientError {
+        error(
+            ErrorCode::InvalidMerkleUpdate,
+            format!("Invalid Merkle update: {}", err),
+        )
+    }
+
+    pub fn invalid_config_param<E: Display>(number: u32, err: E) -> ClientError {
+        let mut error = error(
+            ErrorCode::InvalidConfigParam,
+            format!("Invalid config param {}: {}", number, err),
+        );
+        error.data["number"] = number.into();
+        error
+    }
 }
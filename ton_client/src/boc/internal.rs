@@ -14,6 +14,7 @@
 use crate::ClientContext;
 use crate::boc::{BocCacheType, Error};
 use crate::error::ClientResult;
+#[cfg(feature = "proofs")]
 use std::io::Cursor;
 #[allow(unused_imports)]
 use std::str::FromStr;
@@ -85,6 +86,7 @@ pub(crate) struct DeserializedObject<S: Deserializable> {
     pub object: S,
 }
 
+#[cfg(feature = "net")]
 pub(crate) fn deserialize_object_from_base64<S: Deserializable>(
     b64: &str,
     name: &str,
@@ -159,6 +161,7 @@ pub(crate) async fn deserialize_object_from_boc<S: Deserializable>(
     })
 }
 
+#[cfg(feature = "proofs")]
 pub(crate) fn deserialize_object_from_boc_bin<S: Deserializable>(
     boc: &[u8],
 ) -> ClientResult<(S, UInt256)> {
@@ -175,7 +178,7 @@ pub(crate) async fn serialize_cell_to_boc(
     context: &ClientContext, cell: ton_types::Cell, name: &str, boc_cache: Option<BocCacheType>,
 ) -> ClientResult<String> {
     if let Some(cache_type) = boc_cache {
-        context.bocs.add(cache_type, cell, None)
+        context.bocs.add(cache_type, cell)
             .await
             .map(|hash| format!("*{:x}", hash))
     } else {
@@ -0,0 +1,142 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::boc::internal::{
+    deserialize_cell_from_boc, deserialize_object_from_cell, serialize_cell_to_boc,
+};
+use crate::boc::{BocCacheType, Error};
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use ton_block::MerkleUpdate;
+use ton_types::{Cell, CellType};
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ParamsOfCheckMerkleUpdate {
+    /// Merkle update cell BOC encoded as base64 or BOC handle
+    pub update: String,
+    /// Old state root BOC encoded as base64 or BOC handle.
+    /// If specified, the update is also checked to be applicable to this state.
+    pub old_root: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ResultOfCheckMerkleUpdate {
+    /// Hash of the state the update is applied to, encoded with hex
+    pub old_hash: String,
+    /// Hash of the state produced by the update, encoded with hex
+    pub new_hash: String,
+    /// Depth of the state the update is applied to
+    pub old_depth: u32,
+    /// Depth of the state produced by the update
+    pub new_depth: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ParamsOfApplyMerkleUpdate {
+    /// Merkle update cell BOC encoded as base64 or BOC handle
+    pub update: String,
+    /// Old state root BOC encoded as base64 or BOC handle
+    pub old_root: String,
+    /// Cache type to put the result.
+    /// The BOC itself returned if no cache type provided.
+    pub boc_cache: Option<BocCacheType>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ResultOfApplyMerkleUpdate {
+    /// New state root BOC encoded as base64 or BOC handle
+    pub new_root: String,
+    /// New state root hash encoded with hex
+    pub new_hash: String,
+}
+
+async fn deserialize_update(context: &ClientContext, boc: &str) -> ClientResult<MerkleUpdate> {
+    let (_, cell) = deserialize_cell_from_boc(context, boc, "merkle update").await?;
+    if cell.cell_type() != CellType::MerkleUpdate {
+        return Err(Error::invalid_merkle_update(format!(
+            "cell type is {}",
+            cell.cell_type()
+        )));
+    }
+    let update: MerkleUpdate = deserialize_object_from_cell(cell, "merkle update")?;
+    if update.old.hash(0) != update.old_hash || update.new.hash(0) != update.new_hash {
+        return Err(Error::invalid_merkle_update(
+            "hashes don't match the update branches",
+        ));
+    }
+    Ok(update)
+}
+
+fn apply_update(update: &MerkleUpdate, old_root: &Cell) -> ClientResult<Cell> {
+    if old_root.repr_hash() != update.old_hash {
+        return Err(Error::invalid_merkle_update(format!(
+            "old state hash {:x} doesn't match the update old hash {:x}",
+            old_root.repr_hash(),
+            update.old_hash
+        )));
+    }
+    let new_root = update
+        .apply_for(old_root)
+        .map_err(|err| Error::invalid_merkle_update(err))?;
+    if new_root.repr_hash() != update.new_hash {
+        return Err(Error::invalid_merkle_update(format!(
+            "new state hash {:x} doesn't match the update new hash {:x}",
+            new_root.repr_hash(),
+            update.new_hash
+        )));
+    }
+    Ok(new_root)
+}
+
+/// Checks the Merkle update cell.
+///
+/// Verifies that the cell is a valid Merkle update and its hashes match the old and new
+/// branches. If `old_root` is specified, the update is applied to it and the resulting
+/// state hash is verified, but the new state is not returned.
+#[api_function]
+pub async fn check_merkle_update(
+    context: std::sync::Arc<ClientContext>,
+    params: ParamsOfCheckMerkleUpdate,
+) -> ClientResult<ResultOfCheckMerkleUpdate> {
+    let update = deserialize_update(&context, &params.update).await?;
+    if let Some(old_root) = &params.old_root {
+        let (_, old_root) = deserialize_cell_from_boc(&context, old_root, "old state").await?;
+        apply_update(&update, &old_root)?;
+    }
+    Ok(ResultOfCheckMerkleUpdate {
+        old_hash: update.old_hash.as_hex_string(),
+        new_hash: update.new_hash.as_hex_string(),
+        old_depth: update.old_depth as u32,
+        new_depth: update.new_depth as u32,
+    })
+}
+
+/// Applies the Merkle update to the old state.
+///
+/// The update (e.g. the `state_update` of a block) must be made for the state with
+/// the `old_root` hash. Returns the new state root after verifying its hash
+/// against the update.
+#[api_function]
+pub async fn apply_merkle_update(
+    context: std::sync::Arc<ClientContext>,
+    params: ParamsOfApplyMerkleUpdate,
+) -> ClientResult<ResultOfApplyMerkleUpdate> {
+    let update = deserialize_update(&context, &params.update).await?;
+    let (_, old_root) = deserialize_cell_from_boc(&context, &params.old_root, "old state").await?;
+    let new_root = apply_update(&update, &old_root)?;
+    Ok(ResultOfApplyMerkleUpdate {
+        new_hash: new_root.repr_hash().as_hex_string(),
+        new_root: serialize_cell_to_boc(&context, new_root, "new state", params.boc_cache)
+            .await?,
+    })
+}
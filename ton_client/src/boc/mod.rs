@@ -15,11 +15,17 @@ use serde::{Deserialize, Deserializer};
 
 pub(crate) mod blockchain_config;
 pub(crate) mod cache;
+pub(crate) mod config_param;
 pub(crate) mod encode;
 mod errors;
 pub(crate) mod common;
 pub(crate) mod internal;
+pub(crate) mod merkle_update;
 pub(crate) mod parse;
+pub(crate) mod print_tree;
+#[cfg(feature = "tvm-executor")]
+pub(crate) mod stats;
+pub(crate) mod topology;
 pub(crate) mod tvc;
 
 #[cfg(test)]
@@ -36,16 +42,30 @@ pub use cache::{
     cache_get, cache_set, cache_unpin, BocCacheType, ParamsOfBocCacheGet, ParamsOfBocCacheSet,
     ParamsOfBocCacheUnpin, ResultOfBocCacheGet, ResultOfBocCacheSet,
 };
+pub use config_param::{
+    decode_config_param, encode_config_param, ParamsOfDecodeConfigParam,
+    ParamsOfEncodeConfigParam, ResultOfDecodeConfigParam, ResultOfEncodeConfigParam,
+};
 pub use encode::{encode_boc, BuilderOp, ParamsOfEncodeBoc, ResultOfEncodeBoc};
 pub use errors::{Error, ErrorCode};
 pub use common::{
     get_boc_depth, get_boc_hash,
     ParamsOfGetBocDepth, ResultOfGetBocDepth, ParamsOfGetBocHash, ResultOfGetBocHash,
 };
+pub use merkle_update::{
+    apply_merkle_update, check_merkle_update, ParamsOfApplyMerkleUpdate,
+    ParamsOfCheckMerkleUpdate, ResultOfApplyMerkleUpdate, ResultOfCheckMerkleUpdate,
+};
 pub use parse::{
     parse_account, parse_block, parse_message, parse_shardstate, parse_transaction, required_boc,
     source_boc, ParamsOfParse, ParamsOfParseShardstate, ResultOfParse,
 };
+pub use print_tree::{print_tree, CellNode, ParamsOfPrintTree, ResultOfPrintTree};
+#[cfg(feature = "tvm-executor")]
+pub use stats::{get_stats, ParamsOfGetBocStats, ResultOfGetBocStats};
+pub use topology::{
+    parse_block_topology, BlockRef, ResultOfParseBlockTopology, ShardTopology,
+};
 pub use tvc::{
     decode_tvc, encode_tvc, get_code_from_tvc, get_code_salt, get_compiler_version, get_compiler_version_from_cell, set_code_salt,
     ParamsOfDecodeTvc, ParamsOfEncodeTvc, ParamsOfGetCodeFromTvc, ParamsOfGetCodeSalt,
@@ -61,7 +81,7 @@ fn deserialize_cache_max_size<'de, D: Deserializer<'de>>(deserializer: D) -> Res
     Ok(Option::deserialize(deserializer)?.unwrap_or(default_cache_max_size()))
 }
 
-#[derive(Deserialize, Debug, Clone, ApiType)]
+#[derive(Serialize, Deserialize, Debug, Clone, ApiType)]
 pub struct BocConfig {
     /// Maximum BOC cache size in kilobytes. Default is 10 MB
     #[serde(
@@ -0,0 +1,209 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use crate::boc::internal::deserialize_cell_from_boc;
+use crate::boc::Error;
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use std::collections::HashSet;
+use std::sync::Arc;
+use ton_types::{Cell, CellType, UInt256};
+
+const DEFAULT_MAX_CELLS: u32 = 1000;
+/// Text must have at least this number of bytes to be detected
+const MIN_TEXT_LEN: usize = 4;
+/// Text can be prefixed with zero bytes, e.g. 32-bit zero op of the transfer comment
+const MAX_TEXT_PREFIX_LEN: usize = 4;
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ParamsOfPrintTree {
+    /// BOC encoded as base64 or BOC handle
+    pub boc: String,
+    /// Maximum number of printed cells. The references of the cells beyond
+    /// the limit are not printed. Default is 1000.
+    pub max_cells: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default, Debug, PartialEq)]
+pub struct CellNode {
+    /// Cell representation hash encoded with hex
+    pub hash: String,
+    /// Cell type: `Ordinary`, `PrunedBranch`, `LibraryReference`, `MerkleProof`
+    /// or `MerkleUpdate`
+    pub cell_type: String,
+    /// Cell representation depth
+    pub depth: u32,
+    /// Number of data bits
+    pub bits: u32,
+    /// Cell data encoded with hex. If the number of bits is not a multiple of 4,
+    /// the last hex digit is completed with the `1` tag bit and zeros and the data
+    /// ends with `_`.
+    pub data: String,
+    /// UTF-8 text detected in the cell data
+    pub text: Option<String>,
+    /// Number of the cell references
+    pub references_count: u32,
+    /// Printed references. Empty if the cell is repeated or the cells limit is reached.
+    pub references: Vec<CellNode>,
+    /// The cell was already printed in the tree, so its references are omitted
+    pub repeated: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default, Debug, PartialEq)]
+pub struct ResultOfPrintTree {
+    /// Tree as the indented text, one cell per line
+    pub text: String,
+    /// Tree structure
+    pub tree: CellNode,
+    /// `true` if some cells are not printed because of the `max_cells` limit
+    pub truncated: bool,
+}
+
+fn cell_type_name(cell_type: CellType) -> &'static str {
+    match cell_type {
+        CellType::Ordinary => "Ordinary",
+        CellType::PrunedBranch => "PrunedBranch",
+        CellType::LibraryReference => "LibraryReference",
+        CellType::MerkleProof => "MerkleProof",
+        CellType::MerkleUpdate => "MerkleUpdate",
+        _ => "Unknown",
+    }
+}
+
+fn cell_data_hex(cell: &Cell) -> String {
+    let bits = cell.bit_length();
+    let data = cell.data();
+    let len = std::cmp::min((bits + 7) / 8, data.len());
+    let mut hex = hex::encode(&data[..len]).to_uppercase();
+    // the last incomplete hex digit keeps the completion tag
+    hex.truncate((bits + 3) / 4);
+    if bits % 4 != 0 {
+        hex.push('_');
+    }
+    hex
+}
+
+/// Detects the printable UTF-8 text in the byte aligned data
+pub(crate) fn detect_text(data: &[u8], bits: usize) -> Option<String> {
+    if bits % 8 != 0 {
+        return None;
+    }
+    let data = &data[..std::cmp::min(bits / 8, data.len())];
+    let prefix_len = data
+        .iter()
+        .take(MAX_TEXT_PREFIX_LEN)
+        .take_while(|byte| **byte == 0)
+        .count();
+    let data = &data[prefix_len..];
+    if data.len() < MIN_TEXT_LEN {
+        return None;
+    }
+    let text = std::str::from_utf8(data).ok()?;
+    if text
+        .chars()
+        .any(|c| c.is_control() && c != '\n' && c != '\r' && c != '\t')
+    {
+        return None;
+    }
+    Some(text.to_string())
+}
+
+struct TreePrinter {
+    printed: HashSet<UInt256>,
+    max_cells: usize,
+    truncated: bool,
+    text: String,
+}
+
+impl TreePrinter {
+    fn print(&mut self, cell: &Cell, indent: usize) -> ClientResult<CellNode> {
+        let hash = cell.repr_hash();
+        let repeated = self.printed.contains(&hash);
+        let mut node = CellNode {
+            hash: hash.as_hex_string(),
+            cell_type: cell_type_name(cell.cell_type()).to_string(),
+            depth: cell.repr_depth() as u32,
+            bits: cell.bit_length() as u32,
+            data: cell_data_hex(cell),
+            text: detect_text(cell.data(), cell.bit_length()),
+            references_count: cell.references_count() as u32,
+            references: Vec::new(),
+            repeated,
+        };
+        self.print_line(&node, indent);
+        if repeated {
+            return Ok(node);
+        }
+        self.printed.insert(hash);
+        for i in 0..cell.references_count() {
+            if self.printed.len() >= self.max_cells {
+                self.truncated = true;
+                self.text.push_str(&format!("{:indent$}...\n", "", indent = indent * 2 + 2));
+                break;
+            }
+            let child = cell.reference(i).map_err(|err| Error::invalid_boc(err))?;
+            node.references.push(self.print(&child, indent + 1)?);
+        }
+        Ok(node)
+    }
+
+    fn print_line(&mut self, node: &CellNode, indent: usize) {
+        let mut line = format!("{:indent$}x{{{}}}", "", node.data, indent = indent * 2);
+        if node.cell_type != cell_type_name(CellType::Ordinary) {
+            line.push_str(&format!(" {}", node.cell_type));
+        }
+        line.push_str(&format!(
+            " bits: {}, refs: {}, depth: {}, hash: {}",
+            node.bits, node.references_count, node.depth, node.hash
+        ));
+        if let Some(text) = &node.text {
+            line.push_str(&format!(", text: {:?}", text));
+        }
+        if node.repeated {
+            line.push_str(" (repeated)");
+        }
+        self.text.push_str(&line);
+        self.text.push('\n');
+    }
+}
+
+/// Prints the cell tree of the BOC
+///
+/// Each cell is printed with its data bits in hex, the number of references, the depth
+/// and the hash. Special cells (pruned branches, library references, Merkle proofs and
+/// updates) are labeled with their type, and the byte aligned data that is a printable
+/// UTF-8 text (optionally prefixed with zero bytes, like the transfer comments)
+/// is printed as a text.
+///
+/// Repeated subtrees are printed once. The number of printed cells is limited
+/// by `max_cells`, so the function is safe for the large trees.
+#[api_function]
+pub async fn print_tree(
+    context: Arc<ClientContext>,
+    params: ParamsOfPrintTree,
+) -> ClientResult<ResultOfPrintTree> {
+    let (_, cell) = deserialize_cell_from_boc(&context, &params.boc, "").await?;
+    let mut printer = TreePrinter {
+        printed: HashSet::new(),
+        max_cells: std::cmp::max(params.max_cells.unwrap_or(DEFAULT_MAX_CELLS), 1) as usize,
+        truncated: false,
+        text: String::new(),
+    };
+    let tree = printer.print(&cell, 0)?;
+    Ok(ResultOfPrintTree {
+        text: printer.text,
+        tree,
+        truncated: printer.truncated,
+    })
+}
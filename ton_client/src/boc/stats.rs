@@ -0,0 +1,153 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::boc::internal::deserialize_cell_from_boc;
+use crate::boc::Error;
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use std::collections::HashMap;
+use std::sync::Arc;
+use ton_types::{Cell, UInt256};
+
+const SECONDS_PER_YEAR: u32 = 365 * 24 * 60 * 60;
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ParamsOfGetBocStats {
+    /// BOC encoded as base64 or BOC handle
+    pub boc: String,
+    /// Time period in seconds used to project the storage fee. Default is one year.
+    pub period: Option<u32>,
+    /// Use masterchain storage prices. Default is `false`.
+    pub masterchain: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default, Debug, PartialEq)]
+pub struct ResultOfGetBocStats {
+    /// Number of unique cells in the tree. Equals to the number of cells
+    /// paid for when the tree is stored in an account.
+    pub cells: u32,
+    /// Number of data bits in the unique cells.
+    pub bits: u64,
+    /// Maximum depth of the tree.
+    pub max_depth: u32,
+    /// Number of cells in the tree without deduplication, i.e. counting
+    /// every path from the root.
+    pub tree_cells: u64,
+    /// Number of cells saved by the deduplication of identical subtrees.
+    pub duplicate_cells: u64,
+    /// Number of bits saved by the deduplication of identical subtrees.
+    pub duplicate_bits: u64,
+    /// Projected storage fee in nanotokens for storing the tree in an account
+    /// during `period` according to the current blockchain config.
+    pub storage_fee: String,
+}
+
+struct CellStats {
+    tree_cells: u64,
+    tree_bits: u64,
+}
+
+#[derive(Default)]
+struct StatsCollector {
+    cells: HashMap<UInt256, CellStats>,
+    bits: u64,
+}
+
+impl StatsCollector {
+    fn visit(&mut self, cell: &Cell) -> ClientResult<(u64, u64)> {
+        let hash = cell.repr_hash();
+        if let Some(stats) = self.cells.get(&hash) {
+            return Ok((stats.tree_cells, stats.tree_bits));
+        }
+        let mut tree_cells = 1u64;
+        let mut tree_bits = cell.bit_length() as u64;
+        for i in 0..cell.references_count() {
+            let child = cell.reference(i).map_err(|err| Error::invalid_boc(err))?;
+            let (cells, bits) = self.visit(&child)?;
+            tree_cells = tree_cells.saturating_add(cells);
+            tree_bits = tree_bits.saturating_add(bits);
+        }
+        self.bits += cell.bit_length() as u64;
+        self.cells.insert(hash, CellStats { tree_cells, tree_bits });
+        Ok((tree_cells, tree_bits))
+    }
+}
+
+/// Calculates storage fee in nanotokens using the latest storage prices
+/// from the blockchain config (config param 18).
+fn calc_storage_fee(
+    config: &ton_executor::BlockchainConfig,
+    cells: u64,
+    bits: u64,
+    masterchain: bool,
+    period: u32,
+) -> ClientResult<u128> {
+    let prices = config
+        .raw_config()
+        .storage_prices()
+        .map_err(|err| Error::invalid_boc(format!("can not read storage prices: {}", err)))?;
+    let count = prices
+        .len()
+        .map_err(|err| Error::invalid_boc(format!("can not read storage prices: {}", err)))?;
+    if count == 0 {
+        return Ok(0);
+    }
+    let prices = prices
+        .get(count as u32 - 1)
+        .map_err(|err| Error::invalid_boc(format!("can not read storage prices: {}", err)))?;
+    let (bit_price, cell_price) = if masterchain {
+        (prices.mc_bit_price_ps, prices.mc_cell_price_ps)
+    } else {
+        (prices.bit_price_ps, prices.cell_price_ps)
+    };
+    let fee = (bits as u128 * bit_price as u128 + cells as u128 * cell_price as u128)
+        * period as u128;
+    // prices are stored as fixed point numbers with 16 bit fractional part
+    Ok((fee + 0xffff) >> 16)
+}
+
+/// Calculates cell tree statistics of the BOC
+///
+/// Returns cell and bit counts, maximum depth, the savings provided by
+/// the deduplication of identical subtrees and the projected storage fee
+/// of the tree. Useful for measuring the footprint of contract data structures.
+#[api_function]
+pub async fn get_stats(
+    context: Arc<ClientContext>,
+    params: ParamsOfGetBocStats,
+) -> ClientResult<ResultOfGetBocStats> {
+    let (_, cell) = deserialize_cell_from_boc(&context, &params.boc, "").await?;
+
+    let mut collector = StatsCollector::default();
+    let (tree_cells, tree_bits) = collector.visit(&cell)?;
+    let cells = collector.cells.len() as u64;
+
+    let config = crate::tvm::types::get_default_config(&context).await?;
+    let storage_fee = calc_storage_fee(
+        &config,
+        cells,
+        collector.bits,
+        params.masterchain.unwrap_or_default(),
+        params.period.unwrap_or(SECONDS_PER_YEAR),
+    )?;
+
+    Ok(ResultOfGetBocStats {
+        cells: cells as u32,
+        bits: collector.bits,
+        max_depth: cell.repr_depth() as u32,
+        tree_cells,
+        duplicate_cells: tree_cells - cells,
+        duplicate_bits: tree_bits.saturating_sub(collector.bits),
+        storage_fee: storage_fee.to_string(),
+    })
+}
@@ -318,10 +318,11 @@ async fn test_unpinned_cache() {
     let boc1 = TestClient::tvc(crate::tests::TEST_DEBOT, None);
     let boc2 = TestClient::tvc(crate::tests::SUBSCRIBE, None);
 
-    let boc_max_size = std::cmp::max(
-        base64::decode(&boc1).unwrap().len(),
-        base64::decode(&boc2).unwrap().len(),
-    );
+    let stored_size = |boc: &str| {
+        let cell = internal::deserialize_cell_from_base64(boc, "boc").unwrap().1;
+        super::cache::calc_stored_tree_size(&cell)
+    };
+    let boc_max_size = std::cmp::max(stored_size(&boc1), stored_size(&boc2));
     let client = TestClient::new_with_config(json!({
         "boc": {
             "cache_max_size": boc_max_size / 1024 + 1
@@ -374,6 +375,95 @@ async fn test_unpinned_cache() {
     assert_eq!(boc.boc, Some(boc2.clone()));
 }
 
+#[tokio::test(core_threads = 2)]
+async fn test_unpinned_cache_dedup() {
+    let tvc = TestClient::tvc(crate::tests::TEST_DEBOT, None);
+    let shared = internal::deserialize_cell_from_base64(&tvc, "tvc").unwrap().1;
+    let shared_size = super::cache::calc_stored_tree_size(&shared);
+
+    let make_boc = |tag: u8| {
+        let mut builder = BuilderData::new();
+        builder.append_u8(tag).unwrap();
+        builder.append_reference_cell(shared.clone());
+        serialize_cell_to_base64(&builder.into_cell().unwrap(), "boc").unwrap()
+    };
+    let boc1 = make_boc(1);
+    let boc2 = make_boc(2);
+
+    // cache has room for only one copy of the shared subtree
+    let client = TestClient::new_with_config(json!({
+        "boc": {
+            "cache_max_size": shared_size / 1024 + 4
+        }
+    }));
+    let cache_set = client.wrap_async(cache_set, BocModule::api(), super::cache::cache_set_api());
+    let cache_get = client.wrap_async(cache_get, BocModule::api(), super::cache::cache_get_api());
+
+    let mut refs = Vec::new();
+    for boc in &[&boc1, &boc2] {
+        refs.push(
+            cache_set
+                .call(ParamsOfBocCacheSet {
+                    boc: boc.to_string(),
+                    cache_type: BocCacheType::Unpinned,
+                })
+                .await
+                .unwrap()
+                .boc_ref,
+        );
+    }
+
+    for (boc_ref, boc) in refs.iter().zip(&[&boc1, &boc2]) {
+        let cached = cache_get
+            .call(ParamsOfBocCacheGet { boc_ref: boc_ref.clone() })
+            .await
+            .unwrap();
+        assert_eq!(cached.boc.as_ref(), Some(*boc));
+    }
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_unpinned_cache_rejects_large_boc() {
+    let small = TestClient::tvc(crate::tests::SUBSCRIBE, None);
+    let large = TestClient::tvc(crate::tests::TEST_DEBOT, None);
+    let large_cell = internal::deserialize_cell_from_base64(&large, "boc").unwrap().1;
+    let large_size = super::cache::calc_stored_tree_size(&large_cell);
+
+    let client = TestClient::new_with_config(json!({
+        "boc": {
+            "cache_max_size": (large_size - 1) / 1024
+        }
+    }));
+    let cache_set = client.wrap_async(cache_set, BocModule::api(), super::cache::cache_set_api());
+    let cache_get = client.wrap_async(cache_get, BocModule::api(), super::cache::cache_get_api());
+
+    let small_ref = cache_set
+        .call(ParamsOfBocCacheSet {
+            boc: small.clone(),
+            cache_type: BocCacheType::Unpinned,
+        })
+        .await
+        .unwrap()
+        .boc_ref;
+
+    let error = cache_set
+        .call(ParamsOfBocCacheSet {
+            boc: large,
+            cache_type: BocCacheType::Unpinned,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InsufficientCacheSize as u32);
+    assert_eq!(error.data["boc_size"], json!(large_size));
+
+    // rejected BOC doesn't evict the cached ones
+    let cached = cache_get
+        .call(ParamsOfBocCacheGet { boc_ref: small_ref })
+        .await
+        .unwrap();
+    assert_eq!(cached.boc, Some(small));
+}
+
 #[test]
 fn get_boc_hash() {
     let client = TestClient::new();
@@ -407,6 +497,95 @@ fn get_boc_depth() {
     assert_eq!(result.depth, 8);
 }
 
+#[test]
+fn get_stats() {
+    let client = TestClient::new();
+
+    let result: super::ResultOfGetBocStats = client
+        .request(
+            "boc.get_stats",
+            super::ParamsOfGetBocStats {
+                boc: base64::encode(include_bytes!("test_data/account.boc")),
+                period: Some(1000),
+                masterchain: None,
+            },
+        )
+        .unwrap();
+
+    assert_eq!(result.max_depth, 8);
+    assert!(result.cells > 0);
+    assert!(result.bits > 0);
+    assert_eq!(result.tree_cells, result.cells as u64 + result.duplicate_cells);
+
+    let year: super::ResultOfGetBocStats = client
+        .request(
+            "boc.get_stats",
+            super::ParamsOfGetBocStats {
+                boc: base64::encode(include_bytes!("test_data/account.boc")),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    assert!(
+        year.storage_fee.parse::<u128>().unwrap() > result.storage_fee.parse::<u128>().unwrap()
+    );
+}
+
+#[test]
+fn print_tree() {
+    let client = TestClient::new();
+
+    let mut comment = BuilderData::new();
+    comment.append_u32(0).unwrap();
+    comment.append_raw(b"Hello, world", 96).unwrap();
+    let comment = comment.into_cell().unwrap();
+    let mut root = BuilderData::new();
+    root.append_bits(0b101, 3).unwrap();
+    root.append_reference_cell(comment.clone());
+    root.append_reference_cell(comment.clone());
+    let root = root.into_cell().unwrap();
+    let boc = serialize_cell_to_base64(&root, "cell").unwrap();
+
+    let result: super::ResultOfPrintTree = client
+        .request(
+            "boc.print_tree",
+            super::ParamsOfPrintTree { boc: boc.clone(), max_cells: None },
+        )
+        .unwrap();
+
+    assert!(!result.truncated);
+    assert_eq!(result.tree.data, "B_");
+    assert_eq!(result.tree.bits, 3);
+    assert_eq!(result.tree.text, None);
+    assert_eq!(result.tree.references.len(), 2);
+    let child = &result.tree.references[0];
+    assert_eq!(child.cell_type, "Ordinary");
+    assert_eq!(child.text.as_deref(), Some("Hello, world"));
+    assert_eq!(child.hash, comment.repr_hash().as_hex_string());
+    assert!(!child.repeated);
+    assert!(result.tree.references[1].repeated);
+    let lines: Vec<&str> = result.text.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].starts_with("  x{0000000048656C6C6F2C20776F726C64} bits: 128, refs: 0"));
+    assert!(lines[1].ends_with(", text: \"Hello, world\""));
+    assert!(lines[2].ends_with("(repeated)"));
+
+    let result: super::ResultOfPrintTree = client
+        .request(
+            "boc.print_tree",
+            super::ParamsOfPrintTree { boc, max_cells: Some(1) },
+        )
+        .unwrap();
+    assert!(result.truncated);
+    assert!(result.tree.references.is_empty());
+
+    // Short or binary data is not detected as a text
+    assert_eq!(super::print_tree::detect_text(b"abc", 24), None);
+    assert_eq!(super::print_tree::detect_text(b"\x01\x02text", 48), None);
+    assert_eq!(super::print_tree::detect_text(b"text", 31), None);
+}
+
 #[test]
 fn get_code_from_tvc() {
     let client = TestClient::new();
@@ -508,6 +687,31 @@ fn parse_block() {
     assert_eq!(result.parsed["gen_utime"], 1600234696);
 }
 
+#[test]
+fn parse_block_topology() {
+    let client = TestClient::new();
+
+    let result: ResultOfParseBlockTopology = client
+        .request(
+            "boc.parse_block_topology",
+            ParamsOfParse {
+                boc: base64::encode(&include_bytes!("test_data/block.boc")),
+            },
+        )
+        .unwrap();
+
+    assert_eq!(result.workchain_id, -1);
+    assert_eq!(result.shard, "8000000000000000");
+    assert!(result.key_block);
+    assert!(!result.after_merge);
+    assert!(!result.after_split);
+    assert_eq!(result.prev_ref.seq_no, result.seq_no - 1);
+    assert_eq!(result.prev_alt_ref, None);
+    assert_eq!(result.prev_shards, vec!["-1:8000000000000000"]);
+    assert_eq!(result.master_ref, None);
+    assert!(!result.shards.unwrap().is_empty());
+}
+
 #[test]
 fn parse_shardstate() {
     let client = TestClient::new();
@@ -834,6 +1038,7 @@ fn encode_external_in_message() {
         }),
         signer: signing,
         processing_try_index: None,
+        expiration: None,
     };
 
     let abi_encoded: ResultOfEncodeMessage = client
@@ -873,3 +1078,136 @@ fn encode_external_in_message() {
 
     assert_eq!(boc_encoded.message, abi_encoded.message);
 }
+
+#[test]
+fn test_merkle_update() {
+    let client = TestClient::new();
+
+    let cell = |value: u32, refs: Vec<ton_types::Cell>| {
+        let mut builder = BuilderData::new();
+        builder.append_u32(value).unwrap();
+        for child in refs {
+            builder.append_reference_cell(child);
+        }
+        builder.into_cell().unwrap()
+    };
+    let unchanged = cell(1, vec![cell(2, vec![]), cell(3, vec![])]);
+    let old_root = cell(0, vec![unchanged.clone(), cell(4, vec![])]);
+    let new_root = cell(0, vec![unchanged, cell(5, vec![])]);
+    let update = ton_block::MerkleUpdate::create(&old_root, &new_root).unwrap();
+    let update = serialize_cell_to_base64(&update.serialize().unwrap(), "update").unwrap();
+    let old_boc = serialize_cell_to_base64(&old_root, "old").unwrap();
+    let new_boc = serialize_cell_to_base64(&new_root, "new").unwrap();
+
+    let checked: ResultOfCheckMerkleUpdate = client
+        .request(
+            "boc.check_merkle_update",
+            ParamsOfCheckMerkleUpdate {
+                update: update.clone(),
+                old_root: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(checked.old_hash, old_root.repr_hash().as_hex_string());
+    assert_eq!(checked.new_hash, new_root.repr_hash().as_hex_string());
+    assert_eq!(checked.new_depth, new_root.repr_depth() as u32);
+
+    let _: ResultOfCheckMerkleUpdate = client
+        .request(
+            "boc.check_merkle_update",
+            ParamsOfCheckMerkleUpdate {
+                update: update.clone(),
+                old_root: Some(old_boc.clone()),
+            },
+        )
+        .unwrap();
+
+    let applied: ResultOfApplyMerkleUpdate = client
+        .request(
+            "boc.apply_merkle_update",
+            ParamsOfApplyMerkleUpdate {
+                update: update.clone(),
+                old_root: old_boc.clone(),
+                boc_cache: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(applied.new_hash, new_root.repr_hash().as_hex_string());
+    assert_eq!(applied.new_root, new_boc);
+
+    let error = client
+        .request::<_, ResultOfApplyMerkleUpdate>(
+            "boc.apply_merkle_update",
+            ParamsOfApplyMerkleUpdate {
+                update,
+                old_root: new_boc,
+                boc_cache: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidMerkleUpdate as u32);
+
+    let error = client
+        .request::<_, ResultOfCheckMerkleUpdate>(
+            "boc.check_merkle_update",
+            ParamsOfCheckMerkleUpdate {
+                update: old_boc,
+                old_root: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidMerkleUpdate as u32);
+}
+
+#[test]
+fn config_param() {
+    let client = TestClient::new();
+    let config_boc = base64::encode(&include_bytes!("test_data/block_config.boc"));
+
+    for number in &[1, 15, 17, 34] {
+        let decoded: ResultOfDecodeConfigParam = client
+            .request(
+                "boc.decode_config_param",
+                ParamsOfDecodeConfigParam {
+                    number: *number,
+                    config_boc: Some(config_boc.clone()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert!(decoded.param.is_object());
+
+        let encoded: ResultOfEncodeConfigParam = client
+            .request(
+                "boc.encode_config_param",
+                ParamsOfEncodeConfigParam {
+                    number: *number,
+                    param: decoded.param.clone(),
+                    boc_cache: None,
+                },
+            )
+            .unwrap();
+        let redecoded: ResultOfDecodeConfigParam = client
+            .request(
+                "boc.decode_config_param",
+                ParamsOfDecodeConfigParam {
+                    number: *number,
+                    param_boc: Some(encoded.boc),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(redecoded.param, decoded.param);
+    }
+
+    let error = client
+        .request::<_, ResultOfDecodeConfigParam>(
+            "boc.decode_config_param",
+            ParamsOfDecodeConfigParam {
+                number: 15,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidConfigParam as u32);
+}
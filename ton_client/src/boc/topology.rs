@@ -0,0 +1,206 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::boc::internal::deserialize_object_from_boc;
+use crate::boc::{Error, ParamsOfParse};
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use std::sync::Arc;
+use ton_block::{ExtBlkRef, ShardIdent};
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default, Debug, PartialEq)]
+pub struct BlockRef {
+    /// Sequence number of the referenced block
+    pub seq_no: u32,
+    /// Root hash of the referenced block
+    pub root_hash: String,
+    /// File hash of the referenced block
+    pub file_hash: String,
+    /// Logical time of the referenced block end
+    pub end_lt: String,
+}
+
+impl From<&ExtBlkRef> for BlockRef {
+    fn from(block_ref: &ExtBlkRef) -> Self {
+        Self {
+            seq_no: block_ref.seq_no,
+            root_hash: block_ref.root_hash.as_hex_string(),
+            file_hash: block_ref.file_hash.as_hex_string(),
+            end_lt: block_ref.end_lt.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default, Debug, PartialEq)]
+pub struct ShardTopology {
+    /// Workchain of the shard
+    pub workchain_id: i32,
+    /// Shard prefix with tag encoded as hex
+    pub shard: String,
+    /// Sequence number of the top shard block registered in the master block
+    pub seq_no: u32,
+    /// Root hash of the top shard block registered in the master block
+    pub root_hash: String,
+    /// The shard is going to be split after the top block
+    pub before_split: bool,
+    /// The shard is going to be merged with its sibling after the top block
+    pub before_merge: bool,
+    /// Validators of the shard want it to be split
+    pub want_split: bool,
+    /// Validators of the shard want it to be merged
+    pub want_merge: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default, Debug, PartialEq)]
+pub struct ResultOfParseBlockTopology {
+    /// Block id (root hash of the block cell)
+    pub id: String,
+    /// Workchain of the block
+    pub workchain_id: i32,
+    /// Shard prefix with tag encoded as hex
+    pub shard: String,
+    /// Block sequence number
+    pub seq_no: u32,
+    /// Block generation time
+    pub gen_utime: u32,
+    /// The block is a key block
+    pub key_block: bool,
+    /// The shard is going to be split after this block
+    pub before_split: bool,
+    /// The block is the first one after the shard split.
+    /// `prev_ref` points to the last block of the parent shard.
+    pub after_split: bool,
+    /// The block is the first one after the shards merge.
+    /// `prev_ref` and `prev_alt_ref` point to the last blocks of the merged shards.
+    pub after_merge: bool,
+    /// Reference to the previous block
+    pub prev_ref: BlockRef,
+    /// Reference to the second previous block. Present only after merge.
+    pub prev_alt_ref: Option<BlockRef>,
+    /// Shards of the previous blocks in the same order as `prev_ref` and `prev_alt_ref`.
+    /// Each shard is encoded as `workchain_id:shard`.
+    pub prev_shards: Vec<String>,
+    /// Reference to the master block the shard block is based on. Absent for master blocks.
+    pub master_ref: Option<BlockRef>,
+    /// Top shard blocks with their split/merge state. Present only for master blocks.
+    pub shards: Option<Vec<ShardTopology>>,
+}
+
+fn shard_to_string(shard: &ShardIdent) -> String {
+    format!("{}:{}", shard.workchain_id(), shard.shard_prefix_as_str_with_tag())
+}
+
+fn prev_shards(
+    shard: &ShardIdent,
+    after_split: bool,
+    after_merge: bool,
+) -> ClientResult<Vec<ShardIdent>> {
+    if after_merge {
+        let (left, right) = shard
+            .split()
+            .map_err(|err| Error::invalid_boc(format!("can not split shard {}: {}", shard, err)))?;
+        Ok(vec![left, right])
+    } else if after_split {
+        let parent = shard
+            .merge()
+            .map_err(|err| Error::invalid_boc(format!("can not merge shard {}: {}", shard, err)))?;
+        Ok(vec![parent])
+    } else {
+        Ok(vec![shard.clone()])
+    }
+}
+
+fn read_shards(block: &ton_block::Block) -> ClientResult<Option<Vec<ShardTopology>>> {
+    let extra = block
+        .read_extra()
+        .map_err(|err| Error::invalid_boc(format!("can not read `extra` from block: {}", err)))?;
+    let master = match extra
+        .read_custom()
+        .map_err(|err| Error::invalid_boc(format!("can not read `master` from block: {}", err)))?
+    {
+        Some(master) => master,
+        None => return Ok(None),
+    };
+
+    let mut shards = Vec::new();
+    master
+        .shards()
+        .iterate_shards(|ident, descr| {
+            shards.push(ShardTopology {
+                workchain_id: ident.workchain_id(),
+                shard: ident.shard_prefix_as_str_with_tag(),
+                seq_no: descr.seq_no,
+                root_hash: descr.root_hash.as_hex_string(),
+                before_split: descr.before_split,
+                before_merge: descr.before_merge,
+                want_split: descr.want_split,
+                want_merge: descr.want_merge,
+            });
+            Ok(true)
+        })
+        .map_err(|err| Error::invalid_boc(format!("can not read shard hashes: {}", err)))?;
+
+    Ok(Some(shards))
+}
+
+/// Parses block boc into a normalized topology record
+///
+/// Extracts references to the previous blocks (taking split and merge into account),
+/// master block reference and, for master blocks, split/merge state of the top shard blocks.
+#[api_function]
+pub async fn parse_block_topology(
+    context: Arc<ClientContext>,
+    params: ParamsOfParse,
+) -> ClientResult<ResultOfParseBlockTopology> {
+    let object = deserialize_object_from_boc::<ton_block::Block>(&context, &params.boc, "block").await?;
+    let block = object.object;
+
+    let info = block
+        .info
+        .read_struct()
+        .map_err(|err| Error::invalid_boc(format!("can not read `info` from block: {}", err)))?;
+
+    let prev_ref = info
+        .read_prev_ref()
+        .map_err(|err| Error::invalid_boc(format!("can not read `prev_ref` from block: {}", err)))?;
+    let prev1 = prev_ref
+        .prev1()
+        .map_err(|err| Error::invalid_boc(format!("can not read `prev_ref` from block: {}", err)))?;
+    let prev2 = prev_ref
+        .prev2()
+        .map_err(|err| Error::invalid_boc(format!("can not read `prev_alt_ref` from block: {}", err)))?;
+
+    let master_ref = info
+        .read_master_ref()
+        .map_err(|err| Error::invalid_boc(format!("can not read `master_ref` from block: {}", err)))?;
+
+    let shard = info.shard();
+    let prev_shards = prev_shards(shard, info.after_split(), info.after_merge())?;
+
+    Ok(ResultOfParseBlockTopology {
+        id: object.cell.repr_hash().as_hex_string(),
+        workchain_id: shard.workchain_id(),
+        shard: shard.shard_prefix_as_str_with_tag(),
+        seq_no: info.seq_no(),
+        gen_utime: info.gen_utime().0,
+        key_block: info.key_block(),
+        before_split: info.before_split(),
+        after_split: info.after_split(),
+        after_merge: info.after_merge(),
+        prev_ref: BlockRef::from(&prev1),
+        prev_alt_ref: prev2.as_ref().map(BlockRef::from),
+        prev_shards: prev_shards.iter().map(shard_to_string).collect(),
+        master_ref: master_ref.as_ref().map(|master_ref| BlockRef::from(&master_ref.master)),
+        shards: read_shards(&block)?,
+    })
+}
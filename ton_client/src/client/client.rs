@@ -14,25 +14,52 @@
 use lockfree::map::Map as LockfreeMap;
 use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
-use tokio::sync::{mpsc, oneshot, Mutex, RwLock};
+#[cfg(feature = "net")]
+use tokio::sync::mpsc;
+use tokio::sync::{oneshot, Mutex, RwLock};
+#[cfg(feature = "tvm-executor")]
+use ton_types::Cell;
+#[cfg(feature = "proofs")]
 use ton_types::UInt256;
 
-use super::{AppRequestResult, Error, ParamsOfAppRequest};
-use crate::abi::AbiConfig;
+use super::{
+    AppRequestResult, Error, EventSinkConfig, ParamsOfAppObjectHandshake, ParamsOfAppRequest,
+    ResultOfAppObjectHandshake, APP_OBJECT_INTERFACE_VERSION,
+};
+use crate::abi::{AbiConfig, Signer};
 use crate::boc::{BocConfig, cache::Bocs};
+#[cfg(feature = "proofs")]
 use crate::client::storage::KeyValueStorage;
 use crate::crypto::CryptoConfig;
-use crate::crypto::boxes::{signing_box::SigningBox, encryption_box::EncryptionBox};
+use crate::crypto::boxes::{signing_box::SigningBoxEntry, encryption_box::EncryptionBox};
+#[cfg(feature = "debot")]
 use crate::debot::DEngine;
 use crate::error::ClientResult;
+use crate::json_interface::interceptors::Interceptors;
 use crate::json_interface::interop::ResponseType;
 use crate::json_interface::request::Request;
+#[cfg(feature = "net")]
+use crate::net::accounts_sync::AccountsSyncs;
+#[cfg(feature = "net")]
 use crate::net::{
-    subscriptions::SubscriptionAction, ChainIterator, NetworkConfig, ServerLink,
+    lite::LiteClient, subscriptions::SubscriptionAction, ChainIterator, NetworkConfig, ServerLink,
 };
+#[cfg(feature = "net")]
+use crate::processing::message_monitor::MessageMonitors;
+#[cfg(feature = "net")]
+use crate::processing::scheduler::ProcessingScheduler;
+#[cfg(feature = "net")]
+use crate::processing::sequence::SequenceLocks;
+#[cfg(feature = "net")]
+use crate::processing::waiters::TransactionWaiters;
+#[cfg(feature = "proofs")]
+use crate::proofs::archive::ArchiveDataSource;
+#[cfg(feature = "proofs")]
 use crate::proofs::ProofsConfig;
+#[cfg(feature = "net")]
+use crate::tvm::accounts_cache::AccountsCache;
 #[cfg(not(feature = "wasm"))]
 use super::std_client_env::ClientEnv;
 #[cfg(feature = "wasm")]
@@ -40,86 +67,234 @@ use super::wasm_client_env::ClientEnv;
 
 #[derive(Default)]
 pub struct Boxes {
-    pub(crate) signing_boxes: LockfreeMap<u32, Box<dyn SigningBox + Send + Sync>>,
+    pub(crate) signing_boxes: LockfreeMap<u32, SigningBoxEntry>,
     pub(crate) encryption_boxes: LockfreeMap<u32, Box<dyn EncryptionBox + Send + Sync>>,
 }
 
+#[cfg(feature = "proofs")]
 #[derive(Debug)]
 pub(crate) struct NetworkUID {
     pub(crate) zerostate_root_hash: UInt256,
     pub(crate) first_master_block_root_hash: UInt256,
 }
 
+#[cfg(feature = "net")]
 pub struct NetworkContext {
     pub(crate) server_link: Option<ServerLink>,
     pub(crate) subscriptions: Mutex<HashMap<u32, mpsc::Sender<SubscriptionAction>>>,
     pub(crate) iterators: Mutex<HashMap<u32, Arc<Mutex<Box<dyn ChainIterator + Send + Sync>>>>>,
+    #[cfg(feature = "proofs")]
     pub(crate) network_uid: RwLock<Option<Arc<NetworkUID>>>,
+    pub(crate) processing_scheduler: Arc<ProcessingScheduler>,
+    pub(crate) sequence_locks: SequenceLocks,
+    pub(crate) transaction_waiters: TransactionWaiters,
+    pub(crate) message_monitors: MessageMonitors,
+    pub(crate) accounts_syncs: AccountsSyncs,
+    pub(crate) accounts_cache: AccountsCache,
+    pub(crate) lite_client: Option<Arc<LiteClient>>,
+    // Key-blocks pinned with `proofs.set_trusted_key_block` by zerostate root hash
+    #[cfg(feature = "proofs")]
+    pub(crate) pinned_key_blocks: std::sync::RwLock<HashMap<[u8; 32], Vec<(u32, [u8; 32])>>>,
 }
 
 pub struct ClientContext {
-    pub(crate) net: NetworkContext,
+    #[cfg(feature = "net")]
+    pub(crate) net: Arc<NetworkContext>,
+    // Shard block id recorded by the last message sent from this context, used as
+    // a consistency token. Not shared with derived contexts
+    #[cfg(feature = "net")]
+    pub(crate) last_sent_block: RwLock<Option<String>>,
     pub(crate) config: ClientConfig,
+    /// Signer used instead of `Signer::None` by the message encoding functions
+    pub(crate) default_signer: Option<Signer>,
     pub(crate) env: Arc<ClientEnv>,
+    #[cfg(feature = "debot")]
     pub(crate) debots: LockfreeMap<u32, Mutex<DEngine>>,
+    #[cfg(feature = "tvm-executor")]
+    pub(crate) account_snapshots: LockfreeMap<u32, Cell>,
     pub(crate) boxes: Boxes,
-    pub(crate) bocs: Bocs,
-    pub(crate) blockchain_config: RwLock<Option<Arc<ton_executor::BlockchainConfig>>>,
+    pub(crate) bocs: Arc<Bocs>,
+    #[cfg(feature = "tvm-executor")]
+    pub(crate) blockchain_config: Arc<RwLock<Option<Arc<ton_executor::BlockchainConfig>>>>,
 
     pub(crate) app_requests: Mutex<HashMap<u32, oneshot::Sender<AppRequestResult>>>,
-    pub(crate) proofs_storage: RwLock<Option<Arc<dyn KeyValueStorage>>>,
+    #[cfg(feature = "proofs")]
+    pub(crate) proofs_storage: Arc<RwLock<Option<Arc<dyn KeyValueStorage>>>>,
+    #[cfg(feature = "proofs")]
+    pub(crate) proofs_archive: RwLock<Option<Arc<ArchiveDataSource>>>,
+    pub(crate) interceptors: Interceptors,
 
-    next_id: AtomicU32,
+    // Number of the API calls finished with a panic
+    pub(crate) panics: AtomicU32,
+    offline: AtomicBool,
+    // Shared with derived contexts, because they register subscriptions and iterators
+    // in the same network context
+    next_id: Arc<AtomicU32>,
 }
 
 impl ClientContext {
+    #[cfg(feature = "net")]
     pub(crate) fn get_server_link(&self) -> ClientResult<&ServerLink> {
+        if self.is_offline() {
+            return Err(Error::not_available_offline());
+        }
         self.net
             .server_link
             .as_ref()
             .ok_or_else(|| Error::net_module_not_init())
     }
 
+    /// Current network time in ms. Equals to the local time if the network is not used.
+    #[cfg(feature = "net")]
+    pub(crate) fn network_now_ms(&self) -> u64 {
+        match &self.net.server_link {
+            Some(server_link) => server_link.clock().now_ms(&self.env),
+            None => self.env.now_ms(),
+        }
+    }
+
+    /// Current network time in ms. Equals to the local time if the network is not used.
+    #[cfg(not(feature = "net"))]
+    pub(crate) fn network_now_ms(&self) -> u64 {
+        self.env.now_ms()
+    }
+
+    pub(crate) fn is_offline(&self) -> bool {
+        self.offline.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::Relaxed);
+    }
+
     pub async fn set_timer(&self, ms: u64) -> ClientResult<()> {
         self.env.set_timer(ms).await
     }
 
-    pub fn new(config: ClientConfig) -> ClientResult<ClientContext> {
-        let env = Arc::new(ClientEnv::new()?);
+    #[cfg(feature = "net")]
+    fn has_network(config: &ClientConfig) -> bool {
+        config.network.server_address.is_some() || config.network.endpoints.is_some()
+    }
 
-        let server_link = if config.network.server_address.is_some()
-            || config.network.endpoints.is_some()
-        {
-            if config.network.out_of_sync_threshold > config.abi.message_expiration_timeout / 2 {
-                return Err(Error::invalid_config(format!(
-                    r#"`out_of_sync_threshold` can not be more then `message_expiration_timeout / 2`.
+    #[cfg(feature = "net")]
+    fn check_expiration_timeout(config: &ClientConfig) -> ClientResult<()> {
+        if config.network.out_of_sync_threshold > config.abi.message_expiration_timeout / 2 {
+            return Err(Error::invalid_config(format!(
+                r#"`out_of_sync_threshold` can not be more then `message_expiration_timeout / 2`.
 `out_of_sync_threshold` = {}, `message_expiration_timeout` = {}
 Note that default values are used if parameters are omitted in config"#,
-                    config.network.out_of_sync_threshold, config.abi.message_expiration_timeout
-                )));
-            }
-            Some(ServerLink::new(config.network.clone(), env.clone())?)
+                config.network.out_of_sync_threshold, config.abi.message_expiration_timeout
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn new(config: ClientConfig) -> ClientResult<ClientContext> {
+        if let Some(event_sink) = &config.event_sink {
+            event_sink.validate()?;
+        }
+        let env = Arc::new(ClientEnv::new()?);
+
+        #[cfg(feature = "net")]
+        let server_link = if Self::has_network(&config) {
+            Self::check_expiration_timeout(&config)?;
+            Some(ServerLink::new(
+                config.network.clone(),
+                env.clone(),
+                config.local_storage_path.clone(),
+            )?)
         } else {
             None
         };
 
         let bocs = Bocs::new(config.boc.cache_max_size);
         Ok(Self {
-            net: NetworkContext {
+            #[cfg(feature = "net")]
+            net: Arc::new(NetworkContext {
                 server_link,
                 subscriptions: Default::default(),
                 iterators: Default::default(),
+                #[cfg(feature = "proofs")]
                 network_uid: Default::default(),
-            },
+                processing_scheduler: Arc::new(ProcessingScheduler::new(
+                    config.network.processing_concurrency_limit,
+                )),
+                sequence_locks: Default::default(),
+                transaction_waiters: Default::default(),
+                message_monitors: Default::default(),
+                accounts_syncs: Default::default(),
+                accounts_cache: AccountsCache::new(config.network.accounts_cache_size),
+                lite_client: LiteClient::new(&config.network)?.map(Arc::new),
+                #[cfg(feature = "proofs")]
+                pinned_key_blocks: Default::default(),
+            }),
+            #[cfg(feature = "net")]
+            last_sent_block: Default::default(),
             config,
+            default_signer: None,
             env,
+            #[cfg(feature = "debot")]
             debots: LockfreeMap::new(),
+            #[cfg(feature = "tvm-executor")]
+            account_snapshots: LockfreeMap::new(),
             boxes: Default::default(),
-            bocs,
-            blockchain_config: RwLock::new(None),
+            bocs: Arc::new(bocs),
+            #[cfg(feature = "tvm-executor")]
+            blockchain_config: Default::default(),
             app_requests: Mutex::new(HashMap::new()),
+            #[cfg(feature = "proofs")]
             proofs_storage: Default::default(),
-            next_id: AtomicU32::new(1),
+            #[cfg(feature = "proofs")]
+            proofs_archive: Default::default(),
+            interceptors: Default::default(),
+            panics: AtomicU32::new(0),
+            offline: AtomicBool::new(false),
+            next_id: Arc::new(AtomicU32::new(1)),
+        })
+    }
+
+    /// Creates a context which shares network connections, subscriptions, BOC cache,
+    /// blockchain config and proofs storage with this one, but uses its own `config`.
+    /// Debots, signing and encryption boxes, account snapshots and API call interceptors
+    /// are not shared. The default signer is inherited unless `default_signer` is specified.
+    pub(crate) fn derive(
+        &self,
+        config: ClientConfig,
+        default_signer: Option<Signer>,
+    ) -> ClientResult<ClientContext> {
+        #[cfg(feature = "net")]
+        if Self::has_network(&config) {
+            Self::check_expiration_timeout(&config)?;
+        }
+        if let Some(event_sink) = &config.event_sink {
+            event_sink.validate()?;
+        }
+
+        Ok(Self {
+            #[cfg(feature = "net")]
+            net: Arc::clone(&self.net),
+            #[cfg(feature = "net")]
+            last_sent_block: Default::default(),
+            config,
+            default_signer: default_signer.or_else(|| self.default_signer.clone()),
+            env: Arc::clone(&self.env),
+            #[cfg(feature = "debot")]
+            debots: LockfreeMap::new(),
+            #[cfg(feature = "tvm-executor")]
+            account_snapshots: LockfreeMap::new(),
+            boxes: Default::default(),
+            bocs: Arc::clone(&self.bocs),
+            #[cfg(feature = "tvm-executor")]
+            blockchain_config: Arc::clone(&self.blockchain_config),
+            app_requests: Mutex::new(HashMap::new()),
+            #[cfg(feature = "proofs")]
+            proofs_storage: Arc::clone(&self.proofs_storage),
+            #[cfg(feature = "proofs")]
+            proofs_archive: Default::default(),
+            interceptors: Default::default(),
+            panics: AtomicU32::new(0),
+            offline: AtomicBool::new(self.is_offline()),
+            next_id: Arc::clone(&self.next_id),
         })
     }
 
@@ -157,8 +332,9 @@ Note that default values are used if parameters are omitted in config"#,
     }
 }
 
-#[derive(Deserialize, Debug, Clone, ApiType)]
+#[derive(Serialize, Deserialize, Debug, Clone, ApiType)]
 pub struct ClientConfig {
+    #[cfg(feature = "net")]
     #[serde(default, deserialize_with = "deserialize_network_config")]
     pub network: NetworkConfig,
     #[serde(default, deserialize_with = "deserialize_crypto_config")]
@@ -167,6 +343,7 @@ pub struct ClientConfig {
     pub abi: AbiConfig,
     #[serde(default, deserialize_with = "deserialize_boc_config")]
     pub boc: BocConfig,
+    #[cfg(feature = "proofs")]
     #[serde(default, deserialize_with = "deserialize_proofs_config")]
     pub proofs: ProofsConfig,
 
@@ -175,8 +352,33 @@ pub struct ClientConfig {
     /// Default (recommended) value is "~/.tonclient" for native environments and ".tonclient"
     /// for web-browser.
     pub local_storage_path: Option<String>,
+
+    /// Maximum size in bytes of the JSON passed to the response handler at once.
+    /// Larger successful results of async functions are delivered by chunks
+    /// with `ResponseChunk` response type followed by the final `Success` response
+    /// with empty params. Application must concatenate the chunks to get the result JSON.
+    /// Default is `None` - results are delivered as a whole.
+    pub response_chunk_size: Option<u32>,
+
+    /// Webhook the subscription and processing events are POSTed to by the library itself,
+    /// in addition to the callbacks passed to the corresponding functions.
+    /// Default is `None` - events are delivered to the callbacks only.
+    pub event_sink: Option<EventSinkConfig>,
+
+    /// Request the capabilities of the app objects (signing boxes, encryption boxes,
+    /// debot browsers) with `GetCapabilities` handshake when they are registered.
+    /// Default is `false`.
+    ///
+    /// The application reports the implemented callbacks, so the registration fails early
+    /// if the required callbacks are missing, calls of the missing callbacks fail with
+    /// `AppObjectCallbackNotImplemented` error without reaching the application and
+    /// notifications are not sent. App objects failed the handshake request are considered
+    /// implementing all the callbacks. Enable only if the application answers the handshake
+    /// request (with the result or an error) for all its app objects.
+    pub app_objects_handshake: Option<bool>,
 }
 
+#[cfg(feature = "net")]
 fn deserialize_network_config<'de, D: Deserializer<'de>>(
     deserializer: D,
 ) -> Result<NetworkConfig, D::Error> {
@@ -201,6 +403,7 @@ fn deserialize_boc_config<'de, D: Deserializer<'de>>(
     Ok(Option::deserialize(deserializer)?.unwrap_or(Default::default()))
 }
 
+#[cfg(feature = "proofs")]
 fn deserialize_proofs_config<'de, D: Deserializer<'de>>(
     deserializer: D,
 ) -> Result<ProofsConfig, D::Error> {
@@ -210,19 +413,34 @@ fn deserialize_proofs_config<'de, D: Deserializer<'de>>(
 impl Default for ClientConfig {
     fn default() -> Self {
         Self {
+            #[cfg(feature = "net")]
             network: Default::default(),
             crypto: Default::default(),
             abi: Default::default(),
             boc: Default::default(),
+            #[cfg(feature = "proofs")]
             proofs: Default::default(),
             local_storage_path: Default::default(),
+            response_chunk_size: Default::default(),
+            event_sink: Default::default(),
+            app_objects_handshake: Default::default(),
         }
     }
 }
 
+/// Callbacks implemented by the app object, reported by the handshake
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AppObjectCapabilities {
+    pub version: u32,
+    pub methods: Vec<String>,
+}
+
 pub(crate) struct AppObject<P: Serialize, R: DeserializeOwned> {
     context: Arc<ClientContext>,
     object_handler: Arc<Request>,
+    /// `None` if the app object didn't take part in the handshake, so all the callbacks
+    /// are considered implemented
+    capabilities: Option<AppObjectCapabilities>,
     phantom: std::marker::PhantomData<(P, R)>,
 }
 
@@ -235,15 +453,83 @@ where
         AppObject {
             context,
             object_handler,
+            capabilities: None,
             phantom: std::marker::PhantomData,
         }
     }
 
+    /// Creates the app object and requests its capabilities if `app_objects_handshake`
+    /// is enabled. Applications that fail the handshake request are considered
+    /// implementing all the callbacks.
+    pub async fn with_handshake(
+        context: Arc<ClientContext>,
+        object_handler: Arc<Request>,
+    ) -> AppObject<P, R> {
+        let mut app_object = Self::new(context, object_handler);
+        if app_object.context.config.app_objects_handshake.unwrap_or(false) {
+            let result = app_object.context.app_request(
+                &app_object.object_handler,
+                ParamsOfAppObjectHandshake::GetCapabilities {
+                    version: APP_OBJECT_INTERFACE_VERSION,
+                },
+            ).await;
+            match result {
+                Ok(ResultOfAppObjectHandshake::GetCapabilities { version, methods }) => {
+                    app_object.capabilities = Some(AppObjectCapabilities { version, methods });
+                }
+                Err(err) => log::debug!("App object handshake failed: {}", err.message),
+            }
+        }
+        app_object
+    }
+
+    fn implements(&self, callback: &str) -> bool {
+        self.capabilities
+            .as_ref()
+            .map(|capabilities| capabilities.methods.iter().any(|method| method == callback))
+            .unwrap_or(true)
+    }
+
+    fn check_implemented(&self, callback: &str) -> ClientResult<()> {
+        match &self.capabilities {
+            Some(capabilities) if !self.implements(callback) => Err(
+                Error::app_object_callback_not_implemented(callback, capabilities.version)
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fails if the app object reported its capabilities and some of the `callbacks`
+    /// are missing. Used to reject the app object at registration instead of failing
+    /// in the middle of an operation.
+    pub fn require(&self, callbacks: &[&str]) -> ClientResult<()> {
+        for callback in callbacks {
+            self.check_implemented(callback)?;
+        }
+        Ok(())
+    }
+
+    fn callback_name(params: &P) -> Option<String> {
+        serde_json::to_value(params)
+            .ok()
+            .and_then(|value| value["type"].as_str().map(|name| name.to_string()))
+    }
+
     pub async fn call(&self, params: P) -> ClientResult<R> {
+        if let Some(callback) = Self::callback_name(&params) {
+            self.check_implemented(&callback)?;
+        }
         self.context.app_request(&self.object_handler, params).await
     }
 
+    /// Sends the notification. Notifications the app object doesn't implement are skipped.
+    #[cfg(feature = "net")]
     pub fn notify(&self, params: P) {
+        if let Some(callback) = Self::callback_name(&params) {
+            if !self.implements(&callback) {
+                return;
+            }
+        }
         self.object_handler
             .response(params, ResponseType::AppNotify as u32)
     }
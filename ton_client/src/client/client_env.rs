@@ -22,6 +22,13 @@ pub(crate) struct WebSocket {
     pub receiver: Pin<Box<dyn Stream<Item = ClientResult<String>> + Send>>,
 }
 
+/// Http response with the body received chunk by chunk
+pub(crate) struct FetchStream {
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    pub body: Pin<Box<dyn Stream<Item = ClientResult<Vec<u8>>> + Send>>,
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct FetchResult {
     pub status: u16,
@@ -0,0 +1,336 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::client::{ClientConfig, ClientContext};
+use crate::error::ClientResult;
+use crate::json_interface::runtime::Runtime;
+use api_info::{ApiType, NumberType, Type, API};
+use serde_json::Value;
+use std::sync::Arc;
+
+#[derive(Serialize, Deserialize, ApiType, Debug, Clone, PartialEq)]
+pub enum ConfigDiagnosticKind {
+    /// Config object contains a key that is not defined in the schema.
+    /// Such keys are ignored by the library.
+    UnknownField,
+    /// Value type doesn't match the schema
+    InvalidType,
+    /// Value has the right type but is out of the allowed range or set
+    InvalidValue,
+    /// Endpoint address can not be used to connect to the DApp Server
+    InvalidEndpoint,
+}
+
+impl Default for ConfigDiagnosticKind {
+    fn default() -> Self {
+        ConfigDiagnosticKind::InvalidValue
+    }
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone, PartialEq)]
+pub struct ConfigDiagnostic {
+    /// Path to the invalid value, e.g. `network.endpoints[1]`
+    pub path: String,
+    /// Kind of the problem
+    pub kind: ConfigDiagnosticKind,
+    /// Human readable description of the problem
+    pub message: String,
+    /// Name of the schema field the unknown key is probably a typo of
+    pub suggestion: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ParamsOfValidateConfig {
+    /// Client config to validate. Has the same format as the config passed
+    /// to `tc_create_context`.
+    pub config: Value,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ResultOfValidateConfig {
+    /// Config has no problems
+    pub valid: bool,
+    /// Found problems
+    pub diagnostics: Vec<ConfigDiagnostic>,
+    /// Config with all defaults applied, exactly as the library will use it.
+    /// Absent if the config can not be parsed.
+    pub effective_config: Option<ClientConfig>,
+}
+
+struct ConfigValidator<'a> {
+    api: &'a API,
+    diagnostics: Vec<ConfigDiagnostic>,
+}
+
+fn child_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '_' && *c != '-')
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let cost = if a_char == *b_char { 0 } else { 1 };
+            current[j + 1] = std::cmp::min(
+                std::cmp::min(prev[j + 1] + 1, current[j] + 1),
+                prev[j] + cost,
+            );
+        }
+        prev = current;
+    }
+    prev[b.len()]
+}
+
+fn suggest_field(key: &str, fields: &[api_info::Field]) -> Option<String> {
+    let key = normalize_name(key);
+    fields
+        .iter()
+        .map(|field| (edit_distance(&key, &normalize_name(&field.name)), &field.name))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name.clone())
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn check_endpoint(address: &str) -> Result<(), String> {
+    let address = address.trim();
+    if address.is_empty() {
+        return Err("endpoint address is empty".to_string());
+    }
+    let host = match address.find("://") {
+        Some(pos) => {
+            let scheme = address[..pos].to_lowercase();
+            if scheme != "http" && scheme != "https" {
+                return Err(format!(
+                    "unsupported protocol `{}`, only `http` and `https` are allowed",
+                    scheme
+                ));
+            }
+            &address[pos + 3..]
+        }
+        None => address,
+    };
+    let host = host.split('/').next().unwrap_or_default();
+    if host.is_empty() {
+        return Err("endpoint host is empty".to_string());
+    }
+    if host.chars().any(|c| c.is_whitespace()) {
+        return Err("endpoint host contains whitespaces".to_string());
+    }
+    Ok(())
+}
+
+impl<'a> ConfigValidator<'a> {
+    fn report(&mut self, path: &str, kind: ConfigDiagnosticKind, message: String) {
+        self.diagnostics.push(ConfigDiagnostic {
+            path: path.to_string(),
+            kind,
+            message,
+            suggestion: None,
+        });
+    }
+
+    fn invalid_type(&mut self, path: &str, expected: &str, value: &Value) {
+        self.report(
+            path,
+            ConfigDiagnosticKind::InvalidType,
+            format!("expected {}, found {}", expected, type_name(value)),
+        );
+    }
+
+    fn check_number(&mut self, path: &str, number_type: &NumberType, size: usize, value: &Value) {
+        let (min, max) = match number_type {
+            NumberType::Float => {
+                if !value.is_number() {
+                    self.invalid_type(path, "number", value);
+                }
+                return;
+            }
+            NumberType::UInt => (0i128, (1i128 << size) - 1),
+            NumberType::Int => (-(1i128 << (size - 1)), (1i128 << (size - 1)) - 1),
+        };
+        let number = value
+            .as_i64()
+            .map(|number| number as i128)
+            .or_else(|| value.as_u64().map(|number| number as i128));
+        match number {
+            Some(number) if number < min || number > max => self.report(
+                path,
+                ConfigDiagnosticKind::InvalidValue,
+                format!("value {} is out of range {}..{}", number, min, max),
+            ),
+            Some(_) => {}
+            None => self.invalid_type(path, "integer", value),
+        }
+    }
+
+    fn check_struct(&mut self, path: &str, fields: &[api_info::Field], value: &Value) {
+        let object = match value.as_object() {
+            Some(object) => object,
+            None => return self.invalid_type(path, "object", value),
+        };
+        for (key, item) in object {
+            let item_path = child_path(path, key);
+            match fields.iter().find(|field| &field.name == key) {
+                Some(field) => self.check_value(&item_path, &field.value, item),
+                None => {
+                    let suggestion = suggest_field(key, fields);
+                    self.diagnostics.push(ConfigDiagnostic {
+                        message: match &suggestion {
+                            Some(name) => format!(
+                                "unknown field `{}`, did you mean `{}`?",
+                                key, name
+                            ),
+                            None => format!("unknown field `{}`", key),
+                        },
+                        path: item_path,
+                        kind: ConfigDiagnosticKind::UnknownField,
+                        suggestion,
+                    });
+                }
+            }
+        }
+    }
+
+    fn check_value(&mut self, path: &str, value_type: &Type, value: &Value) {
+        // All config fields have defaults, so `null` is always accepted
+        if value.is_null() {
+            return;
+        }
+        match value_type {
+            Type::Ref { name } => {
+                let api = self.api;
+                if let Some(field) = api.find_type(name) {
+                    self.check_value(path, &field.value, value);
+                }
+            }
+            Type::Optional { inner } => self.check_value(path, inner, value),
+            Type::Struct { fields } => self.check_struct(path, fields, value),
+            Type::Array { item } => match value.as_array() {
+                Some(items) => {
+                    for (index, item_value) in items.iter().enumerate() {
+                        self.check_value(&format!("{}[{}]", path, index), item, item_value);
+                    }
+                }
+                None => self.invalid_type(path, "array", value),
+            },
+            Type::Boolean => {
+                if !value.is_boolean() {
+                    self.invalid_type(path, "boolean", value);
+                }
+            }
+            Type::String => {
+                if !value.is_string() {
+                    self.invalid_type(path, "string", value);
+                }
+            }
+            Type::Number { number_type, number_size } => {
+                self.check_number(path, number_type, *number_size, value)
+            }
+            Type::EnumOfConsts { consts } => {
+                let name = value.as_str().unwrap_or_default();
+                if !consts.iter().any(|c| c.name == name) {
+                    let names: Vec<&str> = consts.iter().map(|c| c.name.as_str()).collect();
+                    self.report(
+                        path,
+                        ConfigDiagnosticKind::InvalidValue,
+                        format!("expected one of: {}", names.join(", ")),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_endpoints(&mut self, config: &Value) {
+        let network = &config["network"];
+        if let Some(address) = network["server_address"].as_str() {
+            if let Err(message) = check_endpoint(address) {
+                self.report("network.server_address", ConfigDiagnosticKind::InvalidEndpoint, message);
+            }
+        }
+        if let Some(endpoints) = network["endpoints"].as_array() {
+            for (index, address) in endpoints.iter().enumerate() {
+                if let Some(Err(message)) = address.as_str().map(check_endpoint) {
+                    self.report(
+                        &format!("network.endpoints[{}]", index),
+                        ConfigDiagnosticKind::InvalidEndpoint,
+                        message,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Validates client config against the config schema.
+///
+/// Unknown (e.g. misspelled) keys and values of the wrong type are silently replaced
+/// with defaults on context creation. Use this function to detect such problems
+/// before creating a context.
+#[api_function]
+pub fn validate_config(
+    _context: Arc<ClientContext>,
+    params: ParamsOfValidateConfig,
+) -> ClientResult<ResultOfValidateConfig> {
+    let config = if params.config.is_null() {
+        json!({})
+    } else {
+        params.config
+    };
+    let mut validator = ConfigValidator {
+        api: Runtime::api(),
+        diagnostics: Vec::new(),
+    };
+    validator.check_value("", &ClientConfig::api().value, &config);
+    validator.check_endpoints(&config);
+
+    let effective_config = match serde_json::from_value::<ClientConfig>(config) {
+        Ok(config) => Some(config),
+        Err(err) => {
+            if validator.diagnostics.is_empty() {
+                validator.report("", ConfigDiagnosticKind::InvalidValue, err.to_string());
+            }
+            None
+        }
+    };
+
+    Ok(ResultOfValidateConfig {
+        valid: validator.diagnostics.is_empty(),
+        diagnostics: validator.diagnostics,
+        effective_config,
+    })
+}
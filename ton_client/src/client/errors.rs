@@ -40,6 +40,8 @@ pub enum ErrorCode {
     InternalError = 33,
     InvalidHandle = 34,
     LocalStorageError = 35,
+    NotAvailableOffline = 36,
+    AppObjectCallbackNotImplemented = 37,
 }
 pub struct Error;
 
@@ -60,10 +62,19 @@ impl Error {
             || error.code == ErrorCode::WebsocketReceiveError as u32
             || error.code == ErrorCode::WebsocketSendError as u32
             || error.code == ErrorCode::HttpRequestSendError as u32
-            || (error.code == crate::net::ErrorCode::GraphqlError as u32
-                && error.data["server_code"].as_i64() >= Some(500)
-                && error.data["server_code"].as_i64() <= Some(599)
-            )
+            || Self::is_graphql_server_error(error)
+    }
+
+    #[cfg(feature = "net")]
+    fn is_graphql_server_error(error: &ClientError) -> bool {
+        error.code == crate::net::ErrorCode::GraphqlError as u32
+            && error.data["server_code"].as_i64() >= Some(500)
+            && error.data["server_code"].as_i64() <= Some(599)
+    }
+
+    #[cfg(not(feature = "net"))]
+    fn is_graphql_server_error(_error: &ClientError) -> bool {
+        false
     }
 
     pub fn internal_error<E: Display>(message: E) -> ClientError {
@@ -323,4 +334,23 @@ impl Error {
             ),
         )
     }
+
+    pub fn not_available_offline() -> ClientError {
+        error(
+            ErrorCode::NotAvailableOffline,
+            "Network access is not available: client is switched to the offline mode".to_owned(),
+        )
+    }
+
+    pub fn app_object_callback_not_implemented(callback: &str, version: u32) -> ClientError {
+        let mut error = error(
+            ErrorCode::AppObjectCallbackNotImplemented,
+            format!(
+                "Application object (interface version {}) doesn't implement `{}` callback",
+                version, callback,
+            ),
+        );
+        error.data = json!({ "callback": callback, "version": version });
+        error
+    }
 }
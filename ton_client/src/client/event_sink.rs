@@ -0,0 +1,184 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use super::Error;
+#[cfg(feature = "net")]
+use super::{ClientContext, FetchMethod};
+#[cfg(feature = "net")]
+use super::trace::{current_trace_id, TRACE_ID_FIELD};
+use crate::error::ClientResult;
+#[cfg(feature = "net")]
+use hmac::{Hmac, Mac, NewMac};
+#[cfg(feature = "net")]
+use serde::Serialize;
+#[cfg(feature = "net")]
+use serde_json::Value;
+#[cfg(feature = "net")]
+use sha2::Sha256;
+#[cfg(feature = "net")]
+use std::collections::HashMap;
+#[cfg(feature = "net")]
+use std::sync::Arc;
+
+// Events are reported by the `net` and `processing` modules only, so the delivery is
+// compiled with the `net` feature
+#[cfg(feature = "net")]
+const DEFAULT_RETRIES_COUNT: u8 = 3;
+#[cfg(feature = "net")]
+const DEFAULT_RETRY_TIMEOUT: u32 = 1000;
+#[cfg(feature = "net")]
+const DEFAULT_TIMEOUT: u32 = 10000;
+
+#[cfg(feature = "net")]
+pub(crate) const SIGNATURE_HEADER: &str = "X-Event-Signature";
+
+#[derive(Serialize, Deserialize, Debug, Clone, ApiType, Default, PartialEq)]
+pub struct EventSinkConfig {
+    /// Webhook URL the events are POSTed to. Must use `https` scheme.
+    pub url: String,
+
+    /// Secret used to sign the events.
+    ///
+    /// If specified, each request has `X-Event-Signature` header with hex encoded
+    /// HMAC-SHA256 of the request body computed with this secret.
+    pub secret: Option<String>,
+
+    /// Maximum number of the delivery retries. Default is 3.
+    ///
+    /// Delivery is retried on network errors and on `429` and `5xx` responses.
+    pub retries_count: Option<u8>,
+
+    /// Timeout in ms before the first retry. Timeout is doubled on each next retry.
+    /// Default is 1000 ms.
+    pub retry_timeout: Option<u32>,
+
+    /// Timeout of the webhook request in ms. Default is 10000 ms.
+    pub timeout: Option<u32>,
+}
+
+impl EventSinkConfig {
+    pub(crate) fn validate(&self) -> ClientResult<()> {
+        if !self.url.to_lowercase().starts_with("https://") {
+            return Err(Error::invalid_config(format!(
+                "`event_sink.url` must use `https` scheme: {}",
+                self.url
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "net")]
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub(crate) enum EventKind {
+    /// Event of the `send_message`, `wait_for_transaction` or `process_message`
+    ProcessingEvent,
+    /// Data received by a subscription
+    SubscriptionData,
+    /// Error reported by a subscription
+    SubscriptionError,
+    /// Event reported by a subscription
+    SubscriptionEvent,
+    /// Message resolution reported by `processing.monitor_messages`
+    MessageMonitoringEvent,
+}
+
+#[cfg(feature = "net")]
+pub(crate) fn sign_event(secret: &str, body: &str) -> String {
+    let mut hmac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    hmac.update(body.as_bytes());
+    hex::encode(hmac.finalize().into_bytes())
+}
+
+#[cfg(feature = "net")]
+pub(crate) fn event_body(context: &ClientContext, kind: EventKind, data: Value) -> String {
+    let mut body = json!({
+        "id": context.get_next_id(),
+        "time": context.env.now_ms(),
+        "kind": kind,
+        "data": data,
+    });
+    if let Some(trace_id) = current_trace_id() {
+        body[TRACE_ID_FIELD] = trace_id.into();
+    }
+    body.to_string()
+}
+
+/// Delivers the event body to the webhook. Returns the last error if all the
+/// attempts are failed.
+#[cfg(feature = "net")]
+pub(crate) async fn deliver_event(
+    context: &ClientContext,
+    config: &EventSinkConfig,
+    body: String,
+) -> ClientResult<()> {
+    let mut headers = HashMap::new();
+    headers.insert("Content-Type".to_string(), "application/json".to_string());
+    if let Some(secret) = &config.secret {
+        headers.insert(SIGNATURE_HEADER.to_string(), sign_event(secret, &body));
+    }
+
+    let retries_count = config.retries_count.unwrap_or(DEFAULT_RETRIES_COUNT);
+    let mut retry_timeout = config.retry_timeout.unwrap_or(DEFAULT_RETRY_TIMEOUT) as u64;
+    let mut retry = 0;
+    loop {
+        let result = context
+            .env
+            .fetch(
+                &config.url,
+                FetchMethod::Post,
+                Some(headers.clone()),
+                Some(body.clone()),
+                config.timeout.unwrap_or(DEFAULT_TIMEOUT),
+            )
+            .await;
+        let err = match result {
+            Ok(response) if response.status < 300 => return Ok(()),
+            Ok(response) => {
+                let err = Error::http_request_send_error(format!(
+                    "Event sink responded with code {}",
+                    response.status
+                ));
+                if response.status != 429 && response.status < 500 {
+                    return Err(err);
+                }
+                err
+            }
+            Err(err) => err,
+        };
+        if retry >= retries_count {
+            return Err(err);
+        }
+        retry += 1;
+        context.env.set_timer(retry_timeout).await?;
+        retry_timeout *= 2;
+    }
+}
+
+/// Posts the event to the configured event sink in background.
+/// Does nothing if the event sink is not configured.
+#[cfg(feature = "net")]
+pub(crate) fn post_event(context: &Arc<ClientContext>, kind: EventKind, data: impl Serialize) {
+    let config = match &context.config.event_sink {
+        Some(config) => config.clone(),
+        None => return,
+    };
+    let body = event_body(context, kind, serde_json::to_value(data).unwrap_or_default());
+    let context_copy = context.clone();
+    context.env.spawn(async move {
+        if let Err(err) = deliver_event(&context_copy, &config, body).await {
+            log::warn!("Event sink delivery failed: {}", err.message);
+        }
+    });
+}
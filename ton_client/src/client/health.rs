@@ -0,0 +1,149 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+// Panic containment: a panic inside of an API function (e.g. an `unwrap` deep in the
+// BOC parsing) is caught and returned to the caller as `InternalError`, so the other
+// calls of the context are not affected.
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Once};
+
+use futures::FutureExt;
+use serde_json::Value;
+
+use super::{ClientContext, Error};
+use crate::error::{ClientError, ClientResult};
+
+/// Time in ms the spawned task must be executed in during the health check
+const RUNTIME_RESPONSE_TIMEOUT: u64 = 5000;
+
+static INSTALL_HOOK: Once = Once::new();
+
+thread_local! {
+    static LAST_PANIC: RefCell<Option<(String, String)>> = RefCell::new(None);
+}
+
+/// Installs the panic hook recording the panic message and backtrace of the current
+/// thread. The previously installed hook is still called.
+fn install_panic_hook() {
+    INSTALL_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let message = match info.payload().downcast_ref::<&str>() {
+                Some(message) => message.to_string(),
+                None => match info.payload().downcast_ref::<String>() {
+                    Some(message) => message.clone(),
+                    None => "Box<Any>".to_string(),
+                },
+            };
+            let message = match info.location() {
+                Some(location) => format!("{} at {}", message, location),
+                None => message,
+            };
+            // Backtrace is captured only if `RUST_BACKTRACE` is set
+            let backtrace = format!("{}", failure::Backtrace::new());
+            LAST_PANIC.with(|last| *last.borrow_mut() = Some((message, backtrace)));
+            previous(info);
+        }));
+    });
+}
+
+fn panic_error(context: &ClientContext) -> ClientError {
+    context.panics.fetch_add(1, Ordering::Relaxed);
+    let (message, backtrace) = LAST_PANIC
+        .with(|last| last.borrow_mut().take())
+        .unwrap_or_else(|| ("unknown panic".to_string(), String::new()));
+    let mut error = Error::internal_error(format!("API function panicked: {}", message));
+    error.data["panic"] = Value::String(message);
+    error.data["backtrace"] = Value::String(backtrace);
+    error
+}
+
+/// Executes the function and converts its panic to `InternalError`
+pub(crate) fn catch_panic_sync<R>(
+    context: &ClientContext,
+    f: impl FnOnce() -> ClientResult<R>,
+) -> ClientResult<R> {
+    install_panic_hook();
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(_) => Err(panic_error(context)),
+    }
+}
+
+/// Executes the future and converts its panic to `InternalError`
+pub(crate) async fn catch_panic<R>(
+    context: &ClientContext,
+    future: impl Future<Output = R>,
+) -> ClientResult<R> {
+    install_panic_hook();
+    match AssertUnwindSafe(future).catch_unwind().await {
+        Ok(result) => Ok(result),
+        Err(_) => Err(panic_error(context)),
+    }
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ResultOfHealthCheck {
+    /// `true` if no problems are found and the context can be used.
+    pub healthy: bool,
+    /// Number of the API calls of the context finished with a panic.
+    pub panics_count: u32,
+    /// Descriptions of the found problems.
+    pub problems: Vec<String>,
+}
+
+/// Verifies the context integrity
+///
+/// Checks that the shared state of the context is not left broken by the panicked
+/// API calls and that the async runtime of the context executes the tasks.
+#[api_function]
+pub async fn health_check(context: Arc<ClientContext>) -> ClientResult<ResultOfHealthCheck> {
+    let mut problems = Vec::new();
+    #[cfg(feature = "net")]
+    if context.net.processing_scheduler.is_poisoned() {
+        problems.push("Processing scheduler state is poisoned by a panic".to_string());
+    }
+    #[cfg(feature = "net")]
+    if context.net.sequence_locks.is_poisoned() {
+        problems.push("Message sequence locks are poisoned by a panic".to_string());
+    }
+    if context.interceptors.is_poisoned() {
+        problems.push("API call interceptors are poisoned by a panic".to_string());
+    }
+
+    let (sender, receiver) = futures::channel::oneshot::channel();
+    context.env.spawn(async move {
+        let _ = sender.send(());
+    });
+    let spawned = receiver.fuse();
+    let timer = context.env.set_timer(RUNTIME_RESPONSE_TIMEOUT).fuse();
+    futures::pin_mut!(spawned, timer);
+    let runtime_responds = futures::select! {
+        result = spawned => result.is_ok(),
+        _ = timer => false,
+    };
+    if !runtime_responds {
+        problems.push("Async runtime doesn't execute the spawned tasks".to_string());
+    }
+
+    Ok(ResultOfHealthCheck {
+        healthy: problems.is_empty(),
+        panics_count: context.panics.load(Ordering::Relaxed),
+        problems,
+    })
+}
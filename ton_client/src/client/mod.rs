@@ -12,17 +12,29 @@
 */
 
 mod client;
+#[cfg(feature = "net")]
 mod client_env;
+pub(crate) mod config_validation;
 pub(crate) mod errors;
+pub(crate) mod event_sink;
+pub(crate) mod health;
+#[cfg(feature = "net")]
 pub(crate) mod storage;
+pub(crate) mod trace;
 #[cfg(not(feature = "wasm"))]
 mod std_client_env;
 #[cfg(not(feature = "wasm"))]
-pub(crate) use std_client_env::{ClientEnv, LocalStorage};
+pub(crate) use std_client_env::ClientEnv;
+#[cfg(not(feature = "wasm"))]
+#[cfg(feature = "net")]
+pub(crate) use std_client_env::LocalStorage;
 #[cfg(feature = "wasm")]
 mod wasm_client_env;
 #[cfg(feature = "wasm")]
-pub(crate) use wasm_client_env::{ClientEnv, LocalStorage};
+pub(crate) use wasm_client_env::ClientEnv;
+#[cfg(feature = "wasm")]
+#[cfg(feature = "net")]
+pub(crate) use wasm_client_env::LocalStorage;
 
 #[cfg(not(feature = "wasm"))]
 #[cfg(test)]
@@ -36,16 +48,26 @@ mod tests;
 mod network_mock;
 
 pub use client::{ClientConfig, ClientContext};
+pub use config_validation::{
+    validate_config, ConfigDiagnostic, ConfigDiagnosticKind, ParamsOfValidateConfig,
+    ResultOfValidateConfig,
+};
 pub use errors::{Error, ErrorCode};
+pub use event_sink::EventSinkConfig;
+pub use health::{health_check, ResultOfHealthCheck};
 
-pub(crate) use client_env::{FetchMethod, FetchResult, WebSocket};
-pub(crate) use client::{AppObject, NetworkUID};
+#[cfg(feature = "net")]
+pub(crate) use client_env::{FetchMethod, FetchResult, FetchStream, WebSocket};
+pub(crate) use client::AppObject;
+#[cfg(feature = "proofs")]
+pub(crate) use client::NetworkUID;
 
 use crate::error::ClientResult;
 use crate::json_interface::runtime::Runtime;
 use api_info::API;
 use std::sync::Arc;
 
+#[cfg(feature = "net")]
 pub(crate) const LOCAL_STORAGE_DEFAULT_DIR_NAME: &str = ".tonclient";
 
 pub fn core_version() -> String {
@@ -106,6 +128,126 @@ pub fn build_info(_context: Arc<ClientContext>) -> ClientResult<ResultOfBuildInf
     )
 }
 
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ParamsOfSetOffline {
+    /// Enables or disables the offline mode.
+    ///
+    /// While the offline mode is enabled all functions requiring network access
+    /// fail with `NotAvailableOffline` error. Functions that work with local data only
+    /// (crypto, abi, boc, tvm with the provided account state) are not affected.
+    pub offline: bool,
+}
+
+/// Switches the client to or from the offline mode
+#[api_function]
+pub fn set_offline(
+    context: Arc<ClientContext>,
+    params: ParamsOfSetOffline,
+) -> ClientResult<()> {
+    context.set_offline(params.offline);
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ResultOfIsOffline {
+    /// `true` if the client is in the offline mode.
+    pub offline: bool,
+}
+
+/// Returns the current state of the offline mode
+#[api_function]
+pub fn is_offline(context: Arc<ClientContext>) -> ClientResult<ResultOfIsOffline> {
+    Ok(ResultOfIsOffline {
+        offline: context.is_offline(),
+    })
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ParamsOfCreateDerivedContext {
+    /// Config overrides applied on top of the parent context's config.
+    ///
+    /// Only `abi` and `crypto` sections can be overridden, because network connections, caches
+    /// and proofs storage are shared with the parent context. Fields omitted in a section keep
+    /// the parent's values.
+    pub config: serde_json::Value,
+
+    /// Signer used by the derived context when `Signer::Default` is passed to
+    /// `abi.encode_message`, `abi.encode_message_body` and the functions encoding
+    /// messages with them (e.g. `processing.process_message`). `Signer::None` still
+    /// creates unsigned messages.
+    ///
+    /// Inherited from the parent context if omitted. `SigningBox` handle must refer
+    /// to a box registered in the derived context. Can't be `Signer::Default`.
+    pub default_signer: Option<crate::abi::Signer>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ResultOfCreateDerivedContext {
+    /// Handle of the created context. Must be destroyed with `tc_destroy_context`
+    /// as a regular one.
+    pub context: u32,
+}
+
+fn apply_config_section<T: serde::Serialize + serde::de::DeserializeOwned>(
+    section: &mut T,
+    name: &str,
+    overrides: &serde_json::Value,
+) -> ClientResult<()> {
+    let overrides = match overrides.as_object() {
+        Some(overrides) => overrides,
+        None => return Err(Error::invalid_config(format!("`{}` must be an object", name))),
+    };
+    let mut value = serde_json::to_value(&*section)
+        .map_err(|err| Error::internal_error(err))?;
+    for (key, field) in overrides {
+        value[key] = field.clone();
+    }
+    *section = serde_json::from_value(value)
+        .map_err(|err| Error::invalid_config(format!("Invalid `{}` section: {}", name, err)))?;
+    Ok(())
+}
+
+/// Creates a context derived from the current one.
+///
+/// The derived context shares network connections, subscriptions, BOC cache and proofs
+/// storage with the parent, but applies the specified config overrides. It allows to serve
+/// clients with different settings (e.g. message expiration timeouts) without opening
+/// a separate connection for each of them.
+///
+/// Debots, signing and encryption boxes registered in the parent context are not available
+/// in the derived one. Destroying the parent context doesn't affect derived contexts.
+#[api_function]
+pub fn create_derived_context(
+    context: Arc<ClientContext>,
+    params: ParamsOfCreateDerivedContext,
+) -> ClientResult<ResultOfCreateDerivedContext> {
+    let overrides = params.config.as_object()
+        .ok_or_else(|| Error::invalid_config("`config` must be an object".to_string()))?;
+    if params.default_signer == Some(crate::abi::Signer::Default) {
+        return Err(crate::abi::Error::invalid_signer(
+            "`default_signer` can't be `Signer::Default`".to_string(),
+        ));
+    }
+
+    let mut config = context.config.clone();
+    for (name, section) in overrides {
+        match name.as_str() {
+            "abi" => apply_config_section(&mut config.abi, name, section)?,
+            "crypto" => apply_config_section(&mut config.crypto, name, section)?,
+            _ => return Err(Error::invalid_config(format!(
+                "`{}` config section can not be overridden in a derived context",
+                name,
+            ))),
+        }
+    }
+
+    let derived = context.derive(config, params.default_signer)?;
+
+    Ok(ResultOfCreateDerivedContext {
+        context: Runtime::register_context(derived),
+    })
+}
+
 #[derive(Serialize, Deserialize, ApiType, Default, Clone)]
 pub struct ParamsOfAppRequest {
     /// Request ID. Should be used in `resolve_app_request` call
@@ -135,6 +277,34 @@ impl Default for AppRequestResult {
     }
 }
 
+/// Version of the app object interface implemented by the library
+pub const APP_OBJECT_INTERFACE_VERSION: u32 = 1;
+
+/// Handshake request sent to the app object if `app_objects_handshake` is enabled
+#[derive(Serialize, Deserialize, ApiType, Clone, Debug, PartialEq)]
+#[serde(tag="type")]
+pub enum ParamsOfAppObjectHandshake {
+    /// Get callbacks implemented by the app object
+    GetCapabilities {
+        /// Version of the app object interface used by the library
+        version: u32,
+    },
+}
+
+/// Returning values from the app object handshake request
+#[derive(Serialize, Deserialize, ApiType, Clone, Debug, PartialEq)]
+#[serde(tag="type")]
+pub enum ResultOfAppObjectHandshake {
+    /// Result of getting the app object capabilities
+    GetCapabilities {
+        /// Version of the app object interface implemented by the application
+        version: u32,
+        /// Implemented callbacks: values of the `type` field of the callback params,
+        /// e.g. `["GetPublicKey", "Sign"]` for the signing box
+        methods: Vec<String>,
+    },
+}
+
 #[derive(Serialize, Deserialize, ApiType, Default, Clone)]
 pub struct ParamsOfResolveAppRequest {
     /// Request ID received from SDK
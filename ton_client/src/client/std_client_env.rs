@@ -11,24 +11,36 @@
 * limitations under the License.
 */
 
-use super::{Error, FetchMethod, FetchResult, WebSocket};
+use super::trace::{current_trace_id, Traced};
+use super::Error;
+#[cfg(feature = "net")]
+use super::{FetchMethod, FetchResult, FetchStream, WebSocket};
+#[cfg(feature = "net")]
 use crate::client::{LOCAL_STORAGE_DEFAULT_DIR_NAME};
 #[cfg(test)]
 use crate::client::network_mock::NetworkMock;
+#[cfg(feature = "net")]
 use crate::client::storage::KeyValueStorage;
 use crate::error::ClientResult;
-use futures::{Future, SinkExt, StreamExt};
+use futures::Future;
+#[cfg(feature = "net")]
+use futures::{SinkExt, StreamExt};
 use lazy_static::lazy_static;
+#[cfg(feature = "net")]
 use reqwest::{
     header::{HeaderMap, HeaderName, HeaderValue},
     Client as HttpClient, ClientBuilder, Method,
 };
+#[cfg(feature = "net")]
 use std::collections::HashMap;
+#[cfg(feature = "net")]
 use std::path::PathBuf;
+#[cfg(feature = "net")]
 use std::str::FromStr;
 use tokio::runtime::Runtime;
 #[cfg(test)]
 use tokio::sync::RwLock;
+#[cfg(feature = "net")]
 use tokio_tungstenite::tungstenite::Message as WsMessage;
 
 #[cfg(test)]
@@ -49,6 +61,7 @@ fn create_runtime() -> ClientResult<Runtime> {
 }
 
 pub(crate) struct ClientEnv {
+    #[cfg(feature = "net")]
     http_client: HttpClient,
     async_runtime_handle: tokio::runtime::Handle,
     #[cfg(test)]
@@ -57,6 +70,7 @@ pub(crate) struct ClientEnv {
 
 impl ClientEnv {
     pub fn new() -> ClientResult<Self> {
+        #[cfg(feature = "net")]
         let client = ClientBuilder::new()
             .build()
             .map_err(|err| Error::http_client_create_error(err))?;
@@ -71,6 +85,7 @@ impl ClientEnv {
         };
 
         Ok(Self {
+            #[cfg(feature = "net")]
             http_client: client,
             async_runtime_handle,
             #[cfg(test)]
@@ -78,6 +93,7 @@ impl ClientEnv {
         })
     }
 
+    #[cfg(feature = "net")]
     fn string_map_to_header_map(headers: HashMap<String, String>) -> ClientResult<HeaderMap> {
         let mut map = HeaderMap::new();
         for (key, value) in headers {
@@ -90,6 +106,7 @@ impl ClientEnv {
         Ok(map)
     }
 
+    #[cfg(feature = "net")]
     fn header_map_to_string_map(headers: &HeaderMap) -> HashMap<String, String> {
         headers
             .into_iter()
@@ -116,8 +133,10 @@ impl ClientEnv {
         Ok(())
     }
 
-    /// Sends asynchronous task to scheduler
+    /// Sends asynchronous task to scheduler. The task is executed with the trace id
+    /// of the API call which spawned it
     pub fn spawn(&self, future: impl Future<Output = ()> + Send + 'static) {
+        let future = Traced::new(current_trace_id(), future);
         self.async_runtime_handle
             .enter(move || tokio::spawn(future));
     }
@@ -128,6 +147,7 @@ impl ClientEnv {
     }
 
     /// Connects to the websocket endpoint
+    #[cfg(feature = "net")]
     pub async fn websocket_connect(
         &self,
         url: &str,
@@ -186,6 +206,7 @@ impl ClientEnv {
     }
 
     /// Executes http request
+    #[cfg(feature = "net")]
     pub async fn fetch(
         &self,
         url: &str,
@@ -231,17 +252,64 @@ impl ClientEnv {
                 .map_err(|err| Error::http_request_parse_error(err))?,
         })
     }
+
+    /// Executes http request without a timeout and returns the response body
+    /// as a stream of chunks. Used for long living responses like Server-Sent Events.
+    #[cfg(feature = "net")]
+    pub async fn fetch_stream(
+        &self,
+        url: &str,
+        method: FetchMethod,
+        headers: Option<HashMap<String, String>>,
+        body: Option<String>,
+    ) -> ClientResult<FetchStream> {
+        let method = Method::from_str(method.as_str())
+            .map_err(|err| Error::http_request_create_error(err))?;
+
+        let mut request = self.http_client.request(method, url);
+        if let Some(headers) = headers {
+            request = request.headers(Self::string_map_to_header_map(headers)?);
+        }
+        if let Some(body) = body {
+            request = request.body(body);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|err| Error::http_request_send_error(err))?;
+
+        let status = response.status().as_u16();
+        let headers = Self::header_map_to_string_map(response.headers());
+        let body = futures::stream::unfold(Some(response), |response| async move {
+            let mut response = response?;
+            match response.chunk().await {
+                Ok(Some(chunk)) => Some((Ok(chunk.to_vec()), Some(response))),
+                Ok(None) => None,
+                Err(err) => Some((Err(Error::http_request_parse_error(err)), None)),
+            }
+        });
+
+        Ok(FetchStream {
+            status,
+            headers,
+            body: Box::pin(body),
+        })
+    }
 }
 
+#[cfg(feature = "net")]
 lazy_static! {
     static ref KEY_FORMAT_RE: regex::Regex = regex::Regex::new(r#"^[a-zA-Z0-9_\.]+?$"#).unwrap();
 }
 
+#[cfg(feature = "net")]
 pub(crate) struct LocalStorage {
     local_storage_path: Option<String>,
     storage_name: String,
 }
 
+#[cfg(feature = "net")]
 impl LocalStorage {
     pub async fn new(
         local_storage_path: Option<String>,
@@ -289,6 +357,7 @@ impl LocalStorage {
     }
 }
 
+#[cfg(feature = "net")]
 #[async_trait::async_trait]
 impl KeyValueStorage for LocalStorage {
     /// Get binary value by a given key from the storage
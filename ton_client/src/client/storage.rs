@@ -1,5 +1,12 @@
+#[cfg(feature = "proofs")]
 use crate::client::Error;
 use crate::error::ClientResult;
+#[cfg(feature = "proofs")]
+use lru::LruCache;
+#[cfg(feature = "proofs")]
+use std::sync::Arc;
+#[cfg(feature = "proofs")]
+use tokio::sync::Mutex;
 
 #[async_trait::async_trait]
 pub trait KeyValueStorage: Send + Sync {
@@ -24,10 +31,12 @@ pub trait KeyValueStorage: Send + Sync {
     async fn remove(&self, key: &str) -> ClientResult<()>;
 }
 
+#[cfg(feature = "proofs")]
 pub struct InMemoryKeyValueStorage {
     map: lockfree::map::Map<String, Vec<u8>>,
 }
 
+#[cfg(feature = "proofs")]
 impl InMemoryKeyValueStorage {
     pub fn new() -> Self {
         Self {
@@ -57,6 +66,7 @@ impl InMemoryKeyValueStorage {
     }
 }
 
+#[cfg(feature = "proofs")]
 #[async_trait::async_trait]
 impl KeyValueStorage for InMemoryKeyValueStorage {
     #[cfg(test)]
@@ -93,3 +103,142 @@ impl KeyValueStorage for InMemoryKeyValueStorage {
         Ok(())
     }
 }
+
+/// Key of the LRU index record in the wrapped storage
+#[cfg(feature = "proofs")]
+const LRU_INDEX_KEY: &str = "lru_index";
+
+#[cfg(feature = "proofs")]
+struct LruIndex {
+    // Key -> value size
+    entries: LruCache<String, u64>,
+    size: u64,
+}
+
+#[cfg(feature = "proofs")]
+impl LruIndex {
+    fn touch(&mut self, key: &str, size: u64) {
+        if self.entries.get(&key.to_string()).is_none() {
+            self.put(key, size);
+        }
+    }
+
+    fn put(&mut self, key: &str, size: u64) {
+        if let Some(prev_size) = self.entries.put(key.to_string(), size) {
+            self.size -= prev_size;
+        }
+        self.size += size;
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(size) = self.entries.pop(&key.to_string()) {
+            self.size -= size;
+        }
+    }
+}
+
+/// Key-value storage limiting the total size of the values stored in the wrapped storage.
+///
+/// Least recently used values are removed when the size limit is exceeded.
+/// Keys and sizes of the values are kept in the index record of the wrapped storage,
+/// so the limit is respected by the persistent storages across the client restarts.
+/// Recency of the values read since the last write is not persisted.
+#[cfg(feature = "proofs")]
+pub struct LruKeyValueStorage {
+    storage: Arc<dyn KeyValueStorage>,
+    max_size: u64,
+    index: Mutex<LruIndex>,
+}
+
+#[cfg(feature = "proofs")]
+impl LruKeyValueStorage {
+    pub async fn new(storage: Arc<dyn KeyValueStorage>, max_size: u64) -> ClientResult<Self> {
+        let mut index = LruIndex {
+            entries: LruCache::unbounded(),
+            size: 0,
+        };
+        if let Some(saved) = storage.get_str(LRU_INDEX_KEY).await? {
+            // Index is saved from the most recently used entry
+            let saved: Vec<(String, u64)> = serde_json::from_str(&saved)
+                .map_err(|err| Error::internal_error(format!("Invalid LRU index: {}", err)))?;
+            for (key, size) in saved.into_iter().rev() {
+                index.put(&key, size);
+            }
+        }
+        Ok(Self {
+            storage,
+            max_size,
+            index: Mutex::new(index),
+        })
+    }
+
+    #[cfg(test)]
+    pub async fn size(&self) -> u64 {
+        self.index.lock().await.size
+    }
+
+    async fn on_get(&self, key: &str, size: Option<usize>) {
+        if let Some(size) = size {
+            self.index.lock().await.touch(key, size as u64);
+        }
+    }
+
+    async fn on_put(&self, key: &str, size: usize) -> ClientResult<()> {
+        let mut index = self.index.lock().await;
+        index.put(key, size as u64);
+        while index.size > self.max_size && index.entries.len() > 1 {
+            if let Some((key, size)) = index.entries.pop_lru() {
+                index.size -= size;
+                self.storage.remove(&key).await?;
+            }
+        }
+        self.save_index(&index).await
+    }
+
+    async fn save_index(&self, index: &LruIndex) -> ClientResult<()> {
+        let saved = index.entries.iter()
+            .map(|(key, size)| (key.clone(), *size))
+            .collect::<Vec<_>>();
+        let saved = serde_json::to_string(&saved)
+            .map_err(|err| Error::internal_error(err))?;
+        self.storage.put_str(LRU_INDEX_KEY, &saved).await
+    }
+}
+
+#[cfg(feature = "proofs")]
+#[async_trait::async_trait]
+impl KeyValueStorage for LruKeyValueStorage {
+    #[cfg(test)]
+    fn in_memory(&self) -> &InMemoryKeyValueStorage {
+        self.storage.in_memory()
+    }
+
+    async fn get_bin(&self, key: &str) -> ClientResult<Option<Vec<u8>>> {
+        let value = self.storage.get_bin(key).await?;
+        self.on_get(key, value.as_ref().map(|value| value.len())).await;
+        Ok(value)
+    }
+
+    async fn put_bin(&self, key: &str, value: &[u8]) -> ClientResult<()> {
+        self.storage.put_bin(key, value).await?;
+        self.on_put(key, value.len()).await
+    }
+
+    async fn get_str(&self, key: &str) -> ClientResult<Option<String>> {
+        let value = self.storage.get_str(key).await?;
+        self.on_get(key, value.as_ref().map(|value| value.len())).await;
+        Ok(value)
+    }
+
+    async fn put_str(&self, key: &str, value: &str) -> ClientResult<()> {
+        self.storage.put_str(key, value).await?;
+        self.on_put(key, value.len()).await
+    }
+
+    async fn remove(&self, key: &str) -> ClientResult<()> {
+        self.storage.remove(key).await?;
+        let mut index = self.index.lock().await;
+        index.remove(key);
+        self.save_index(&index).await
+    }
+}
@@ -57,3 +57,385 @@ fn test_invalid_params_error_secret_stripped() {
     );
     assert!(!error.message.contains(secret));
 }
+
+#[test]
+fn test_offline_mode() {
+    let client = TestClient::new();
+    client
+        .request::<_, ()>(
+            "client.set_offline",
+            crate::client::ParamsOfSetOffline { offline: true },
+        )
+        .unwrap();
+    let state: crate::client::ResultOfIsOffline =
+        client.request_no_params("client.is_offline").unwrap();
+    assert!(state.offline);
+
+    let error = client
+        .request::<_, serde_json::Value>(
+            "net.query",
+            json!({ "query": "query{info{version}}" }),
+        )
+        .unwrap_err();
+    assert_eq!(error.code, super::ErrorCode::NotAvailableOffline as u32);
+
+    let keys: crate::crypto::KeyPair = client
+        .request_no_params("crypto.generate_random_sign_keys")
+        .unwrap();
+    assert_eq!(keys.public.len(), 64);
+
+    client
+        .request::<_, ()>(
+            "client.set_offline",
+            crate::client::ParamsOfSetOffline { offline: false },
+        )
+        .unwrap();
+    let state: crate::client::ResultOfIsOffline =
+        client.request_no_params("client.is_offline").unwrap();
+    assert!(!state.offline);
+}
+
+#[test]
+fn test_derived_context() {
+    let client = TestClient::new_with_config(json!({
+        "abi": {
+            "message_expiration_timeout": 50000,
+            "workchain": -1,
+        },
+    }));
+
+    let result: crate::client::ResultOfCreateDerivedContext = client
+        .request(
+            "client.create_derived_context",
+            crate::client::ParamsOfCreateDerivedContext {
+                config: json!({ "abi": { "message_expiration_timeout": 100000 } }),
+                default_signer: None,
+            },
+        )
+        .unwrap();
+
+    let parent = client.context();
+    let derived = crate::json_interface::runtime::Runtime::required_context(result.context).unwrap();
+    assert_eq!(derived.config.abi.message_expiration_timeout, 100000);
+    assert_eq!(derived.config.abi.workchain, -1);
+    assert_eq!(parent.config.abi.message_expiration_timeout, 50000);
+    assert!(std::sync::Arc::ptr_eq(&parent.net, &derived.net));
+    assert!(std::sync::Arc::ptr_eq(&parent.bocs, &derived.bocs));
+    crate::json_interface::runtime::Runtime::destroy_context(result.context);
+
+    let error = client
+        .request::<_, crate::client::ResultOfCreateDerivedContext>(
+            "client.create_derived_context",
+            crate::client::ParamsOfCreateDerivedContext {
+                config: json!({ "network": { "endpoints": ["localhost"] } }),
+                default_signer: None,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(error.code, super::ErrorCode::InvalidConfig as u32);
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_derived_context_default_signer() {
+    use crate::abi::{
+        encode_message, CallSet, FunctionHeader, ParamsOfEncodeMessage, ResultOfEncodeMessage,
+        Signer,
+    };
+    use crate::json_interface::runtime::Runtime;
+
+    let client = TestClient::new();
+    let keys = client.generate_sign_keys();
+    let result: crate::client::ResultOfCreateDerivedContext = client
+        .request_async(
+            "client.create_derived_context",
+            crate::client::ParamsOfCreateDerivedContext {
+                config: json!({}),
+                default_signer: Some(Signer::Keys { keys: keys.clone() }),
+            },
+        )
+        .await
+        .unwrap();
+    let derived = Runtime::required_context(result.context).unwrap();
+
+    let params = |signer: Signer| ParamsOfEncodeMessage {
+        abi: TestClient::abi(crate::tests::HELLO, None),
+        address: Some(
+            "0:1111111111111111111111111111111111111111111111111111111111111111".into(),
+        ),
+        call_set: Some(CallSet {
+            function_name: "touch".into(),
+            header: Some(FunctionHeader {
+                pubkey: None,
+                time: Some(1599458364291),
+                expire: Some(1599458404),
+            }),
+            input: None,
+        }),
+        signer,
+        ..Default::default()
+    };
+
+    let by_default: ResultOfEncodeMessage = encode_message(derived.clone(), params(Signer::Default))
+        .await
+        .unwrap();
+    let derived_unsigned: ResultOfEncodeMessage =
+        encode_message(derived.clone(), params(Signer::None))
+            .await
+            .unwrap();
+    let explicit: ResultOfEncodeMessage = client
+        .request_async("abi.encode_message", params(Signer::Keys { keys }))
+        .await
+        .unwrap();
+    let unsigned: ResultOfEncodeMessage = client
+        .request_async("abi.encode_message", params(Signer::None))
+        .await
+        .unwrap();
+
+    assert_eq!(by_default.message, explicit.message);
+    assert_ne!(by_default.message, unsigned.message);
+    assert_eq!(derived_unsigned.message, unsigned.message);
+
+    let error = client
+        .request_async::<_, ResultOfEncodeMessage>("abi.encode_message", params(Signer::Default))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, crate::abi::ErrorCode::InvalidSigner as u32);
+    Runtime::destroy_context(result.context);
+}
+
+#[test]
+fn test_validate_config() {
+    use crate::client::{ConfigDiagnosticKind, ParamsOfValidateConfig, ResultOfValidateConfig};
+
+    let client = TestClient::new();
+    let result: ResultOfValidateConfig = client
+        .request(
+            "client.validate_config",
+            ParamsOfValidateConfig {
+                config: json!({
+                    "network": {
+                        "endpoints": ["net.ton.dev", "wss://main.ton.dev"],
+                        "max_reconect_timeout": 100,
+                        "message_retries_count": 1000,
+                    },
+                    "abi": {
+                        "workchain": "0",
+                    },
+                }),
+            },
+        )
+        .unwrap();
+    assert!(!result.valid);
+    assert!(result.effective_config.is_none());
+
+    let find = |path: &str| {
+        result
+            .diagnostics
+            .iter()
+            .find(|diagnostic| diagnostic.path == path)
+            .unwrap_or_else(|| panic!("no diagnostic for {}", path))
+    };
+    let unknown = find("network.max_reconect_timeout");
+    assert_eq!(unknown.kind, ConfigDiagnosticKind::UnknownField);
+    assert_eq!(unknown.suggestion.as_deref(), Some("max_reconnect_timeout"));
+    assert_eq!(find("network.message_retries_count").kind, ConfigDiagnosticKind::InvalidValue);
+    assert_eq!(find("network.endpoints[1]").kind, ConfigDiagnosticKind::InvalidEndpoint);
+    assert_eq!(find("abi.workchain").kind, ConfigDiagnosticKind::InvalidType);
+    assert_eq!(result.diagnostics.len(), 4);
+
+    let result: ResultOfValidateConfig = client
+        .request(
+            "client.validate_config",
+            ParamsOfValidateConfig {
+                config: json!({ "network": { "server_address": "http://localhost" } }),
+            },
+        )
+        .unwrap();
+    assert!(result.valid);
+    assert!(result.diagnostics.is_empty());
+    let config = serde_json::to_value(result.effective_config.unwrap()).unwrap();
+    assert_eq!(config["network"]["server_address"], "http://localhost");
+    assert_eq!(
+        config["network"]["max_reconnect_timeout"],
+        crate::net::NetworkConfig::default().max_reconnect_timeout
+    );
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_event_sink() {
+    use crate::client::event_sink::{deliver_event, sign_event};
+    use crate::client::{ClientContext, EventSinkConfig, NetworkMock};
+    use crate::net::NetworkConfig;
+
+    assert_eq!(
+        sign_event("key", "The quick brown fox jumps over the lazy dog"),
+        "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8",
+    );
+
+    let error = ClientContext::new(ClientConfig {
+        event_sink: Some(EventSinkConfig {
+            url: "http://hook.test/events".to_string(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    })
+    .err()
+    .unwrap();
+    assert_eq!(error.code, super::ErrorCode::InvalidConfig as u32);
+
+    let sink = EventSinkConfig {
+        url: "https://hook.test/events".to_string(),
+        secret: Some("secret".to_string()),
+        retries_count: Some(2),
+        retry_timeout: Some(1),
+        ..Default::default()
+    };
+    let client = ClientContext::new(ClientConfig {
+        network: NetworkConfig {
+            endpoints: Some(vec!["a".to_string()]),
+            ..Default::default()
+        },
+        event_sink: Some(sink.clone()),
+        ..Default::default()
+    })
+    .unwrap();
+
+    // Network errors and server errors are retried
+    NetworkMock::build()
+        .url(&sink.url)
+        .status(503, "")
+        .network_err()
+        .ok("")
+        .reset_client(&client)
+        .await;
+    deliver_event(&client, &sink, "{}".to_string()).await.unwrap();
+    assert_eq!(NetworkMock::get_len(&client).await, 0);
+
+    // Retries count is limited
+    NetworkMock::build()
+        .url(&sink.url)
+        .repeat(4)
+        .status(500, "")
+        .reset_client(&client)
+        .await;
+    assert!(deliver_event(&client, &sink, "{}".to_string()).await.is_err());
+    assert_eq!(NetworkMock::get_len(&client).await, 1);
+
+    // Client errors are not retried
+    NetworkMock::build()
+        .url(&sink.url)
+        .status(400, "")
+        .ok("")
+        .reset_client(&client)
+        .await;
+    assert!(deliver_event(&client, &sink, "{}".to_string()).await.is_err());
+    assert_eq!(NetworkMock::get_len(&client).await, 1);
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_lru_storage() {
+    use crate::client::storage::{InMemoryKeyValueStorage, KeyValueStorage, LruKeyValueStorage};
+
+    let memory = std::sync::Arc::new(InMemoryKeyValueStorage::new());
+    let storage = LruKeyValueStorage::new(memory.clone(), 10).await.unwrap();
+    storage.put_bin("a", &[1; 4]).await.unwrap();
+    storage.put_bin("b", &[2; 4]).await.unwrap();
+    // `a` becomes the most recently used one, so `b` is evicted
+    assert!(storage.get_bin("a").await.unwrap().is_some());
+    storage.put_str("c", "ccc").await.unwrap();
+    assert_eq!(storage.size().await, 7);
+    assert!(storage.get_bin("b").await.unwrap().is_none());
+    assert!(memory.get_bin("b").await.unwrap().is_none());
+
+    // Index is restored from the wrapped storage
+    let storage = LruKeyValueStorage::new(memory.clone(), 10).await.unwrap();
+    assert_eq!(storage.size().await, 7);
+    storage.put_bin("d", &[4; 4]).await.unwrap();
+    assert!(memory.get_bin("a").await.unwrap().is_none());
+    assert_eq!(memory.get_str("c").await.unwrap(), Some("ccc".to_string()));
+    assert_eq!(storage.size().await, 7);
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_panic_containment() {
+    use crate::client::health::{catch_panic, catch_panic_sync};
+    use crate::client::ResultOfHealthCheck;
+
+    let client = TestClient::new();
+    let context = client.context();
+
+    let result: crate::error::ClientResult<()> = catch_panic_sync(&context, || panic!("sync panic"));
+    let err = result.unwrap_err();
+    assert_eq!(err.code, crate::client::ErrorCode::InternalError as u32);
+    assert!(err.data["panic"].as_str().unwrap().starts_with("sync panic"));
+    assert!(err.data["backtrace"].is_string());
+
+    let mutex = std::sync::Mutex::new(0);
+    let err = catch_panic(&context, async {
+        let _guard = mutex.lock().unwrap();
+        panic!("async panic");
+    })
+    .await
+    .unwrap_err();
+    assert!(err.message.contains("async panic"));
+    assert!(mutex.is_poisoned());
+
+    // The context is still usable after the panics
+    let result: ResultOfHealthCheck = client
+        .request_async("client.health_check", ())
+        .await
+        .unwrap();
+    assert!(result.healthy);
+    assert_eq!(result.panics_count, 2);
+    assert!(result.problems.is_empty());
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_app_objects_handshake() {
+    use crate::client::{AppRequestResult, ParamsOfAppRequest, ParamsOfResolveAppRequest};
+    use crate::crypto::RegisteredSigningBox;
+    use std::sync::Arc;
+
+    // application implements only `GetPublicKey`, `Sign` is missing
+    let client = Arc::new(TestClient::new_with_config(json!({ "app_objects_handshake": true })));
+    let client_copy = client.clone();
+    let callback = move |request: ParamsOfAppRequest, _: u32| {
+        let client = client_copy.clone();
+        tokio::spawn(async move {
+            assert_eq!(request.request_data, json!({ "type": "GetCapabilities", "version": 1 }));
+            client.resolve_app_request(
+                request.app_request_id,
+                json!({ "type": "GetCapabilities", "version": 1, "methods": ["GetPublicKey"] }),
+            ).await;
+        });
+        futures::future::ready(())
+    };
+    let error = client.request_async_callback::<_, RegisteredSigningBox, _, _, _>(
+        "crypto.register_signing_box",
+        (),
+        callback,
+    ).await.unwrap_err();
+    assert_eq!(error.code, super::ErrorCode::AppObjectCallbackNotImplemented as u32);
+    assert_eq!(error.data["callback"], "Sign");
+
+    // application which doesn't support the handshake is considered implementing everything
+    let client_copy = client.clone();
+    let callback = move |request: ParamsOfAppRequest, _: u32| {
+        let client = client_copy.clone();
+        tokio::spawn(async move {
+            client.request_async::<_, ()>(
+                "client.resolve_app_request",
+                ParamsOfResolveAppRequest {
+                    app_request_id: request.app_request_id,
+                    result: AppRequestResult::Error { text: "Unknown callback".to_string() },
+                },
+            ).await.unwrap();
+        });
+        futures::future::ready(())
+    };
+    client.request_async_callback::<_, RegisteredSigningBox, _, _, _>(
+        "crypto.register_signing_box",
+        (),
+        callback,
+    ).await.unwrap();
+}
@@ -0,0 +1,101 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+// Trace id of the API call. It is passed by the application in the `trace_id` field
+// of the function parameters and is available to the code executed by the call, so it can be
+// attached to the logs, network requests, events and errors.
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use serde_json::Value;
+
+use crate::error::ClientError;
+
+/// Reserved field of the function parameters with the trace id of the call
+pub(crate) const TRACE_ID_FIELD: &str = "trace_id";
+/// HTTP header with the trace id of the call sent with the network requests
+#[cfg(feature = "net")]
+pub(crate) const TRACE_ID_HEADER: &str = "X-Request-Id";
+
+thread_local! {
+    static CURRENT_TRACE_ID: RefCell<Option<String>> = RefCell::new(None);
+}
+
+/// Returns the trace id of the API call executed by the current thread
+pub(crate) fn current_trace_id() -> Option<String> {
+    CURRENT_TRACE_ID.with(|current| current.borrow().clone())
+}
+
+/// Executes `f` with the `trace_id` set as the current one
+pub(crate) fn with_trace_id<R>(trace_id: Option<&String>, f: impl FnOnce() -> R) -> R {
+    if trace_id.is_none() {
+        return f();
+    }
+    let previous = CURRENT_TRACE_ID.with(|current| current.replace(trace_id.cloned()));
+    let result = f();
+    CURRENT_TRACE_ID.with(|current| *current.borrow_mut() = previous);
+    result
+}
+
+/// Future which sets the trace id as the current one each time it is polled
+pub(crate) struct Traced<F: Future> {
+    trace_id: Option<String>,
+    future: Pin<Box<F>>,
+}
+
+impl<F: Future> Traced<F> {
+    pub fn new(trace_id: Option<String>, future: F) -> Self {
+        Self {
+            trace_id,
+            future: Box::pin(future),
+        }
+    }
+}
+
+impl<F: Future> Future for Traced<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        with_trace_id(this.trace_id.as_ref(), || this.future.as_mut().poll(cx))
+    }
+}
+
+/// Removes the trace id field from the function parameters JSON.
+/// Returns the parameters without the field and the trace id.
+pub(crate) fn extract_trace_id(params_json: String) -> (String, Option<String>) {
+    // avoid parsing of the parameters without the field
+    if !params_json.contains(&format!("\"{}\"", TRACE_ID_FIELD)) {
+        return (params_json, None);
+    }
+    let mut params = match serde_json::from_str::<Value>(&params_json) {
+        Ok(Value::Object(params)) => params,
+        _ => return (params_json, None),
+    };
+    match params.remove(TRACE_ID_FIELD) {
+        Some(Value::String(trace_id)) => (Value::Object(params).to_string(), Some(trace_id)),
+        _ => (params_json, None),
+    }
+}
+
+/// Adds the current trace id to the error data
+pub(crate) fn add_trace_id(mut error: ClientError) -> ClientError {
+    if let Some(trace_id) = current_trace_id() {
+        error.data[TRACE_ID_FIELD] = trace_id.into();
+    }
+    error
+}
@@ -11,24 +11,39 @@
 * limitations under the License.
 */
 
-use super::{Error, FetchMethod, FetchResult, WebSocket};
+use super::trace::{current_trace_id, Traced};
+use super::Error;
+#[cfg(feature = "net")]
+use super::{FetchMethod, FetchResult, FetchStream, WebSocket};
+#[cfg(feature = "net")]
 use crate::client::LOCAL_STORAGE_DEFAULT_DIR_NAME;
+#[cfg(feature = "net")]
 use crate::client::storage::KeyValueStorage;
 use crate::error::ClientResult;
-use futures::{Future, FutureExt, SinkExt, StreamExt};
+use futures::{Future, FutureExt};
+#[cfg(feature = "net")]
+use futures::{SinkExt, StreamExt};
+#[cfg(feature = "net")]
 use indexed_db_futures::{IdbDatabase, IdbQuerySource, IdbVersionChangeEvent};
+#[cfg(feature = "net")]
 use indexed_db_futures::request::IdbOpenDbRequestLike;
+#[cfg(feature = "net")]
 use std::collections::HashMap;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
+#[cfg(feature = "net")]
 use wasm_bindgen_futures::JsFuture;
-use web_sys::{Event, MessageEvent, Request, RequestInit, Response, Window, IdbTransactionMode};
+use web_sys::Window;
+#[cfg(feature = "net")]
+use web_sys::{Event, MessageEvent, Request, RequestInit, Response, IdbTransactionMode};
+#[cfg(feature = "net")]
 use js_sys::JSON;
 
 #[cfg(test)]
 #[path = "client_env_tests.rs"]
 mod client_env_tests;
 
+#[cfg(feature = "net")]
 fn js_error_to_string(js_value: JsValue) -> String {
     if let Ok(txt) = js_value.clone().dyn_into::<js_sys::Error>() {
         String::from(txt.message())
@@ -39,6 +54,7 @@ fn js_error_to_string(js_value: JsValue) -> String {
     }
 }
 
+#[cfg(feature = "net")]
 fn js_value_to_string(js_value: &JsValue) -> ClientResult<String> {
     if let Ok(txt) = js_value.clone().dyn_into::<js_sys::JsString>() {
         Ok(String::from(txt))
@@ -133,6 +149,7 @@ impl ClientEnv {
     }
 
     /// Connects to the websocket endpoint
+    #[cfg(feature = "net")]
     pub async fn websocket_connect_internal(
         url: &str,
         headers: Option<HashMap<String, String>>,
@@ -266,6 +283,7 @@ impl ClientEnv {
     }
 
     /// Executes http request
+    #[cfg(feature = "net")]
     async fn fetch_internal(
         url: &str,
         method: FetchMethod,
@@ -346,12 +364,14 @@ impl ClientEnv {
         execute_spawned(move || Self::set_timer_internal(ms)).await?
     }
 
-    /// Sends asynchronous task to scheduler
+    /// Sends asynchronous task to scheduler. The task is executed with the trace id
+    /// of the API call which spawned it
     pub fn spawn(&self, future: impl Future<Output = ()> + 'static) {
-        wasm_bindgen_futures::spawn_local(future);
+        wasm_bindgen_futures::spawn_local(Traced::new(current_trace_id(), future));
     }
 
     /// Connects to the websocket endpoint
+    #[cfg(feature = "net")]
     pub async fn websocket_connect(
         &self,
         url: &str,
@@ -365,6 +385,7 @@ impl ClientEnv {
     }
 
     /// Executes http request
+    #[cfg(feature = "net")]
     pub async fn fetch(
         &self,
         url: &str,
@@ -379,13 +400,29 @@ impl ClientEnv {
         })
         .await?
     }
+
+    /// Executes http request and returns the response body as a stream of chunks
+    #[cfg(feature = "net")]
+    pub async fn fetch_stream(
+        &self,
+        _url: &str,
+        _method: FetchMethod,
+        _headers: Option<HashMap<String, String>>,
+        _body: Option<String>,
+    ) -> ClientResult<FetchStream> {
+        Err(Error::http_request_send_error(
+            "streaming responses are not supported in wasm environment",
+        ))
+    }
 }
 
+#[cfg(feature = "net")]
 pub(crate) struct LocalStorage {
     local_storage_path: Option<String>,
     storage_name: String,
 }
 
+#[cfg(feature = "net")]
 impl LocalStorage {
     pub async fn new(
         local_storage_path: Option<String>,
@@ -503,6 +540,7 @@ impl LocalStorage {
     }
 }
 
+#[cfg(feature = "net")]
 #[async_trait::async_trait]
 impl KeyValueStorage for LocalStorage {
     async fn get_bin(&self, key: &str) -> ClientResult<Option<Vec<u8>>> {
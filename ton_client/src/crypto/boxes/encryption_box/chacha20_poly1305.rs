@@ -0,0 +1,111 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use chacha20poly1305::aead::{Aead, NewAead, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngCore;
+
+use crate::crypto::Error;
+use crate::encoding::{base64_decode, hex_decode};
+use crate::error::ClientResult;
+use super::{EncryptionBox, EncryptionBoxInfo};
+
+const KEY_SIZE: usize = 32;
+const NONCE_SIZE: usize = 12;
+
+#[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default)]
+pub struct ChaCha20Poly1305Params {
+    /// 256-bit key. Must be encoded with `hex`.
+    pub key: String,
+}
+
+/// Encryption box generating a random 96-bit nonce for each encryption, so the same
+/// (key, nonce) pair is never reused. The nonce is prepended to the encrypted data.
+pub(crate) struct ChaCha20Poly1305EncryptionBox {
+    key: Vec<u8>,
+}
+
+impl ChaCha20Poly1305EncryptionBox {
+    pub fn new(params: ChaCha20Poly1305Params) -> ClientResult<Self> {
+        let key = hex_decode(&params.key)?;
+        if key.len() != KEY_SIZE {
+            return Err(Error::invalid_key_size(key.len(), &[KEY_SIZE * 8]));
+        }
+
+        Ok(Self { key })
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(Key::from_slice(&self.key))
+    }
+
+    fn encrypt_payload(&self, data: &str, aad: &[u8]) -> ClientResult<String> {
+        let msg = base64_decode(data)?;
+        let mut nonce = [0u8; NONCE_SIZE];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let encrypted = self.cipher()
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: &msg, aad })
+            .map_err(|err| Error::encrypt_data_error(format!("{:?}", err)))?;
+        Ok(base64::encode(&[&nonce[..], &encrypted].concat()))
+    }
+
+    fn decrypt_payload(&self, data: &str, aad: &[u8]) -> ClientResult<String> {
+        let data = base64_decode(data)?;
+        if data.len() < NONCE_SIZE {
+            return Err(Error::decrypt_data_error("encrypted data is shorter than the nonce"));
+        }
+        let (nonce, msg) = data.split_at(NONCE_SIZE);
+        let decrypted = self.cipher()
+            .decrypt(Nonce::from_slice(nonce), Payload { msg, aad })
+            .map_err(|err| Error::decrypt_data_error(format!("{:?}", err)))?;
+        Ok(base64::encode(&decrypted))
+    }
+}
+
+#[async_trait::async_trait]
+impl EncryptionBox for ChaCha20Poly1305EncryptionBox {
+    /// Gets encryption box information
+    async fn get_info(&self) -> ClientResult<EncryptionBoxInfo> {
+        Ok(EncryptionBoxInfo {
+            algorithm: Some("ChaCha20Poly1305".to_owned()),
+            hdpath: None,
+            public: None,
+            options: None,
+        })
+    }
+    /// Encrypts data with a random nonce. Encrypted data is preceded by the 12-byte nonce
+    /// and followed by the 16-byte authentication tag
+    async fn encrypt(&self, data: &String) -> ClientResult<String> {
+        self.encrypt_payload(data, &[])
+    }
+    /// Decrypts data and checks the authentication tag
+    async fn decrypt(&self, data: &String) -> ClientResult<String> {
+        self.decrypt_payload(data, &[])
+    }
+    /// Encrypts data and authenticates it together with the associated data
+    async fn encrypt_with_associated_data(
+        &self,
+        data: &String,
+        associated_data: &String,
+    ) -> ClientResult<String> {
+        self.encrypt_payload(data, &base64_decode(associated_data)?)
+    }
+    /// Decrypts data and checks the authentication tag against the associated data
+    async fn decrypt_with_associated_data(
+        &self,
+        data: &String,
+        associated_data: &String,
+    ) -> ClientResult<String> {
+        self.decrypt_payload(data, &base64_decode(associated_data)?)
+    }
+}
@@ -20,7 +20,12 @@ use crate::client::ClientContext;
 use crate::crypto::Error;
 use crate::error::ClientResult;
 
+#[cfg(feature = "crypto-extended")]
 pub(crate) mod aes;
+#[cfg(feature = "crypto-extended")]
+pub(crate) mod chacha20_poly1305;
+#[cfg(feature = "crypto-extended")]
+pub(crate) mod threshold;
 
 #[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default, PartialEq)]
 pub struct EncryptionBoxHandle(pub u32);
@@ -52,6 +57,23 @@ pub trait EncryptionBox {
     async fn encrypt(&self, data: &String) -> ClientResult<String>;
     /// Decrypts data
     async fn decrypt(&self, data: &String) -> ClientResult<String>;
+    /// Encrypts data and authenticates it together with the associated data, which is not
+    /// encrypted. Not supported by default
+    async fn encrypt_with_associated_data(
+        &self,
+        _data: &String,
+        _associated_data: &String,
+    ) -> ClientResult<String> {
+        Err(Error::associated_data_not_supported(&self.get_info().await?.algorithm))
+    }
+    /// Decrypts data, authenticated together with the associated data. Not supported by default
+    async fn decrypt_with_associated_data(
+        &self,
+        _data: &String,
+        _associated_data: &String,
+    ) -> ClientResult<String> {
+        Err(Error::associated_data_not_supported(&self.get_info().await?.algorithm))
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default, PartialEq)]
@@ -124,6 +146,10 @@ pub struct ParamsOfEncryptionBoxEncrypt {
     pub encryption_box: EncryptionBoxHandle,
     /// Data to be encrypted, encoded in Base64
     pub data: String,
+    /// Associated data, encoded in Base64. It is authenticated together with the encrypted
+    /// data, but is not encrypted and is not included into the result.
+    /// Supported only by AEAD algorithms, e.g. `ChaCha20Poly1305`.
+    pub associated_data: Option<String>,
 }
 
 
@@ -142,12 +168,14 @@ pub async fn encryption_box_encrypt(
     context: Arc<ClientContext>,
     params: ParamsOfEncryptionBoxEncrypt,
 ) -> ClientResult<ResultOfEncryptionBoxEncrypt> {
-    Ok(ResultOfEncryptionBoxEncrypt {
-        data: get_registered_encryption_box(&context, &params.encryption_box)?
-            .val()
-            .encrypt(&params.data)
-            .await?
-    })
+    let encryption_box = get_registered_encryption_box(&context, &params.encryption_box)?;
+    let data = match params.associated_data {
+        Some(ref associated_data) => encryption_box.val()
+            .encrypt_with_associated_data(&params.data, associated_data)
+            .await?,
+        None => encryption_box.val().encrypt(&params.data).await?,
+    };
+    Ok(ResultOfEncryptionBoxEncrypt { data })
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default, PartialEq)]
@@ -156,6 +184,9 @@ pub struct ParamsOfEncryptionBoxDecrypt {
     pub encryption_box: EncryptionBoxHandle,
     /// Data to be decrypted, encoded in Base64
     pub data: String,
+    /// Associated data, encoded in Base64, which was used during encryption.
+    /// Decryption fails if it differs from the one used during encryption.
+    pub associated_data: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default, PartialEq)]
@@ -173,12 +204,14 @@ pub async fn encryption_box_decrypt(
     context: Arc<ClientContext>,
     params: ParamsOfEncryptionBoxDecrypt,
 ) -> ClientResult<ResultOfEncryptionBoxDecrypt> {
-    Ok(ResultOfEncryptionBoxDecrypt {
-        data: get_registered_encryption_box(&context, &params.encryption_box)?
-            .val()
-            .decrypt(&params.data)
-            .await?
-    })
+    let encryption_box = get_registered_encryption_box(&context, &params.encryption_box)?;
+    let data = match params.associated_data {
+        Some(ref associated_data) => encryption_box.val()
+            .decrypt_with_associated_data(&params.data, associated_data)
+            .await?,
+        None => encryption_box.val().decrypt(&params.data).await?,
+    };
+    Ok(ResultOfEncryptionBoxDecrypt { data })
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, ApiType)]
@@ -196,18 +229,23 @@ impl Default for CipherMode {
     }
 }
 
+#[cfg(feature = "crypto-extended")]
 #[derive(Serialize, Deserialize, Clone, Debug, ApiType)]
 #[serde(tag = "type", content = "value")]
 pub enum EncryptionAlgorithm {
     AES(aes::AesParams),
+    ChaCha20Poly1305(chacha20_poly1305::ChaCha20Poly1305Params),
+    Threshold(threshold::ThresholdParams),
 }
 
+#[cfg(feature = "crypto-extended")]
 impl Default for EncryptionAlgorithm {
     fn default() -> Self {
         EncryptionAlgorithm::AES(Default::default())
     }
 }
 
+#[cfg(feature = "crypto-extended")]
 #[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default)]
 pub struct ParamsOfCreateEncryptionBox {
     /// Encryption algorithm specifier including cipher parameters (key, IV, etc)
@@ -215,12 +253,21 @@ pub struct ParamsOfCreateEncryptionBox {
 }
 
 /// Creates encryption box with specified algorithm
+#[cfg(feature = "crypto-extended")]
 #[api_function]
 pub async fn create_encryption_box(
     context: Arc<ClientContext>,
     params: ParamsOfCreateEncryptionBox,
 ) -> ClientResult<RegisteredEncryptionBox> {
     match params.algorithm {
-        EncryptionAlgorithm::AES(params) => register_encryption_box(context, aes::AesEncryptionBox::new(params)?).await
+        EncryptionAlgorithm::AES(params) => register_encryption_box(context, aes::AesEncryptionBox::new(params)?).await,
+        EncryptionAlgorithm::ChaCha20Poly1305(params) => register_encryption_box(
+            context,
+            chacha20_poly1305::ChaCha20Poly1305EncryptionBox::new(params)?,
+        ).await,
+        EncryptionAlgorithm::Threshold(params) => register_encryption_box(
+            context,
+            threshold::ThresholdEncryptionBox::new(params)?,
+        ).await,
     }
 }
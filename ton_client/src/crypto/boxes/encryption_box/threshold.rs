@@ -0,0 +1,288 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use chacha20poly1305::aead::{Aead, NewAead, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngCore;
+
+use crate::crypto::internal::{key192, key256};
+use crate::crypto::{shamir, Error};
+use crate::encoding::{base64_decode, hex_decode};
+use crate::error::ClientResult;
+use super::{EncryptionBox, EncryptionBoxInfo};
+
+const ENVELOPE_VERSION: u8 = 1;
+const KEY_SIZE: usize = 32;
+const NONCE_SIZE: usize = 12;
+const BOX_NONCE_SIZE: usize = 24;
+
+#[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default)]
+pub struct ThresholdParams {
+    /// NaCl box public keys of the trustees. Must be encoded with `hex`.
+    pub trustees: Vec<String>,
+    /// Number of the trustees required to decrypt the data.
+    pub threshold: u32,
+    /// NaCl box secret keys of the trustees taking part in the decryption.
+    /// Must be encoded with `hex`. Not required for the encryption.
+    pub secrets: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default)]
+pub struct ThresholdInfo {
+    /// NaCl box public keys of the trustees. Encoded with `hex`.
+    pub trustees: Vec<String>,
+    /// Number of the trustees required to decrypt the data.
+    pub threshold: u32,
+}
+
+/// Share of the data key encrypted to one of the trustees
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct EnvelopeShare {
+    /// Trustee public key in `hex`
+    trustee: String,
+    /// Share `x` coordinate
+    x: u8,
+    /// NaCl box nonce in `hex`
+    nonce: String,
+    /// Share encrypted with NaCl box in `base64`
+    share: String,
+}
+
+/// Self-contained encryption result. It is serialized to JSON and encoded with `base64`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Envelope {
+    version: u8,
+    threshold: u32,
+    /// ChaCha20Poly1305 nonce of the data in `hex`
+    nonce: String,
+    /// Ephemeral NaCl box public key the shares are encrypted with, in `hex`
+    ephemeral_public: String,
+    shares: Vec<EnvelopeShare>,
+    /// Data encrypted with ChaCha20Poly1305 in `base64`
+    ciphertext: String,
+}
+
+/// Encrypts data to a set of trustees. The data is encrypted with ChaCha20Poly1305 under
+/// a random key, the key is split with Shamir's secret sharing and each share is encrypted
+/// to its trustee with NaCl box. Any `threshold` trustees can decrypt the data together.
+pub(crate) struct ThresholdEncryptionBox {
+    trustees: Vec<[u8; 32]>,
+    threshold: u32,
+    secrets: Vec<([u8; 32], [u8; 32])>,
+}
+
+fn public_from_secret(secret: &[u8; 32]) -> [u8; 32] {
+    let mut public = [0u8; 32];
+    sodalite::scalarmult_base(&mut public, secret);
+    public
+}
+
+impl ThresholdEncryptionBox {
+    pub fn new(params: ThresholdParams) -> ClientResult<Self> {
+        if params.trustees.is_empty() || params.trustees.len() > shamir::MAX_SHARES {
+            return Err(Error::invalid_threshold_params(format!(
+                "number of trustees must be from 1 to {}",
+                shamir::MAX_SHARES
+            )));
+        }
+        if params.threshold == 0 || params.threshold as usize > params.trustees.len() {
+            return Err(Error::invalid_threshold_params(format!(
+                "threshold must be from 1 to the number of trustees ({})",
+                params.trustees.len()
+            )));
+        }
+        let mut trustees = Vec::with_capacity(params.trustees.len());
+        for trustee in &params.trustees {
+            let key = key256(&hex_decode(trustee)?)?;
+            if trustees.contains(&key) {
+                return Err(Error::invalid_threshold_params(format!(
+                    "duplicate trustee {}",
+                    trustee
+                )));
+            }
+            trustees.push(key);
+        }
+        let mut secrets = Vec::new();
+        for secret in params.secrets.unwrap_or_default() {
+            let secret = key256(&hex_decode(&secret)?)?;
+            secrets.push((public_from_secret(&secret), secret));
+        }
+
+        Ok(Self {
+            trustees,
+            threshold: params.threshold,
+            secrets,
+        })
+    }
+
+    fn encrypt_payload(&self, data: &str, aad: &[u8]) -> ClientResult<String> {
+        let msg = base64_decode(data)?;
+        let mut key = [0u8; KEY_SIZE];
+        let mut nonce = [0u8; NONCE_SIZE];
+        rand::thread_rng().fill_bytes(&mut key);
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&key))
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: &msg, aad })
+            .map_err(|err| Error::encrypt_data_error(format!("{:?}", err)))?;
+
+        let mut ephemeral_public = [0u8; 32];
+        let mut ephemeral_secret = [0u8; 32];
+        sodalite::box_keypair(&mut ephemeral_public, &mut ephemeral_secret);
+
+        let key_shares = shamir::split(&key, self.trustees.len(), self.threshold as usize);
+        let mut shares = Vec::with_capacity(key_shares.len());
+        for (trustee, (x, share)) in self.trustees.iter().zip(key_shares) {
+            let mut box_nonce = [0u8; BOX_NONCE_SIZE];
+            rand::thread_rng().fill_bytes(&mut box_nonce);
+            let mut padded_input = vec![0u8; 32];
+            padded_input.extend(share);
+            let mut padded_output = vec![0u8; padded_input.len()];
+            sodalite::box_(
+                &mut padded_output,
+                &padded_input,
+                &box_nonce,
+                trustee,
+                &ephemeral_secret,
+            )
+            .map_err(|_| Error::encrypt_data_error("share box failed"))?;
+            padded_output.drain(..16);
+            shares.push(EnvelopeShare {
+                trustee: hex::encode(trustee),
+                x,
+                nonce: hex::encode(box_nonce),
+                share: base64::encode(&padded_output),
+            });
+        }
+
+        let envelope = Envelope {
+            version: ENVELOPE_VERSION,
+            threshold: self.threshold,
+            nonce: hex::encode(nonce),
+            ephemeral_public: hex::encode(ephemeral_public),
+            shares,
+            ciphertext: base64::encode(&ciphertext),
+        };
+        let json = serde_json::to_vec(&envelope)
+            .map_err(|err| Error::encrypt_data_error(err))?;
+        Ok(base64::encode(&json))
+    }
+
+    fn open_share(
+        share: &EnvelopeShare,
+        ephemeral_public: &[u8; 32],
+        secret: &[u8; 32],
+    ) -> ClientResult<(u8, Vec<u8>)> {
+        let mut padded_input = vec![0u8; 16];
+        padded_input.extend(base64_decode(&share.share)?);
+        let mut padded_output = vec![0u8; padded_input.len()];
+        sodalite::box_open(
+            &mut padded_output,
+            &padded_input,
+            &key192(&hex_decode(&share.nonce)?)?,
+            ephemeral_public,
+            secret,
+        )
+        .map_err(|_| Error::decrypt_data_error("share box open failed"))?;
+        padded_output.drain(..32);
+        Ok((share.x, padded_output))
+    }
+
+    fn decrypt_payload(&self, data: &str, aad: &[u8]) -> ClientResult<String> {
+        let envelope: Envelope = serde_json::from_slice(&base64_decode(data)?)
+            .map_err(|err| Error::decrypt_data_error(format!("invalid envelope: {}", err)))?;
+        if envelope.version != ENVELOPE_VERSION {
+            return Err(Error::decrypt_data_error(format!(
+                "unsupported envelope version {}",
+                envelope.version
+            )));
+        }
+        let ephemeral_public = key256(&hex_decode(&envelope.ephemeral_public)?)?;
+
+        let mut key_shares: Vec<(u8, Vec<u8>)> = Vec::new();
+        for share in &envelope.shares {
+            if key_shares.len() == envelope.threshold as usize {
+                break;
+            }
+            let trustee = hex_decode(&share.trustee)?;
+            let secret = self.secrets.iter()
+                .find(|(public, _)| public[..] == trustee[..])
+                .map(|(_, secret)| secret);
+            if let Some(secret) = secret {
+                if share.x == 0 || key_shares.iter().any(|(x, _)| *x == share.x) {
+                    return Err(Error::decrypt_data_error("invalid share coordinate"));
+                }
+                key_shares.push(Self::open_share(share, &ephemeral_public, secret)?);
+            }
+        }
+        if envelope.threshold == 0 || key_shares.len() < envelope.threshold as usize {
+            return Err(Error::not_enough_trustee_shares(
+                key_shares.len(),
+                envelope.threshold,
+            ));
+        }
+
+        let key = shamir::combine(&key_shares);
+        if key.len() != KEY_SIZE {
+            return Err(Error::decrypt_data_error("invalid share size"));
+        }
+        let nonce = hex_decode(&envelope.nonce)?;
+        if nonce.len() != NONCE_SIZE {
+            return Err(Error::invalid_iv_size(nonce.len(), NONCE_SIZE));
+        }
+        let msg = base64_decode(&envelope.ciphertext)?;
+        let decrypted = ChaCha20Poly1305::new(Key::from_slice(&key))
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: &msg, aad })
+            .map_err(|err| Error::decrypt_data_error(format!("{:?}", err)))?;
+        Ok(base64::encode(&decrypted))
+    }
+}
+
+#[async_trait::async_trait]
+impl EncryptionBox for ThresholdEncryptionBox {
+    /// Gets encryption box information
+    async fn get_info(&self) -> ClientResult<EncryptionBoxInfo> {
+        Ok(EncryptionBoxInfo {
+            algorithm: Some("Threshold".to_owned()),
+            hdpath: None,
+            public: None,
+            options: Some(json!(ThresholdInfo {
+                trustees: self.trustees.iter().map(hex::encode).collect(),
+                threshold: self.threshold,
+            })),
+        })
+    }
+    /// Encrypts data into the envelope containing the encrypted key shares
+    async fn encrypt(&self, data: &String) -> ClientResult<String> {
+        self.encrypt_payload(data, &[])
+    }
+    /// Decrypts the envelope with the key restored from the trustee shares
+    async fn decrypt(&self, data: &String) -> ClientResult<String> {
+        self.decrypt_payload(data, &[])
+    }
+    /// Encrypts data and authenticates it together with the associated data
+    async fn encrypt_with_associated_data(
+        &self,
+        data: &String,
+        associated_data: &String,
+    ) -> ClientResult<String> {
+        self.encrypt_payload(data, &base64_decode(associated_data)?)
+    }
+    /// Decrypts the envelope and checks the authentication tag against the associated data
+    async fn decrypt_with_associated_data(
+        &self,
+        data: &String,
+        associated_data: &String,
+    ) -> ClientResult<String> {
+        self.decrypt_payload(data, &base64_decode(associated_data)?)
+    }
+}
@@ -12,4 +12,5 @@
 */
 
 pub(crate) mod signing_box;
-pub(crate) mod encryption_box;
\ No newline at end of file
+pub(crate) mod encryption_box;
+pub(crate) mod webauthn;
\ No newline at end of file
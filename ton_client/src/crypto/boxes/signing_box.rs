@@ -11,7 +11,8 @@
 * limitations under the License.
 */
 
-use std::sync::Arc;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
 
 use crate::client::ClientContext;
 use crate::crypto::Error;
@@ -33,6 +34,11 @@ pub trait SigningBox {
     async fn get_public_key(&self) -> ClientResult<Vec<u8>>;
     /// Sign data with key pair
     async fn sign(&self, unsigned: &[u8]) -> ClientResult<Vec<u8>>;
+    /// Returns `false` if the signature doesn't cover the unsigned data itself
+    /// (e.g. WebAuthn assertion signature), so contracts can't check it
+    fn signs_unsigned_data(&self) -> bool {
+        true
+    }
 }
 
 pub(crate) struct KeysSigningBox {
@@ -62,19 +68,112 @@ impl SigningBox for KeysSigningBox {
     }
 }
 
+const DEFAULT_RATE_PERIOD: u32 = 60000;
+
+#[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default, PartialEq)]
+pub struct SigningBoxLimits {
+    /// Maximum total number of signatures. The signing box refuses to sign
+    /// when it is reached.
+    pub max_signatures: Option<u64>,
+    /// Maximum number of signatures within `rate_period`.
+    pub rate_limit: Option<u32>,
+    /// Rate limit period in ms. Default is 60000.
+    pub rate_period: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default, PartialEq)]
+pub struct SigningBoxCallerStats {
+    /// Name of the function which requested the signature, e.g. `crypto.signing_box_sign`
+    /// or `abi` for the messages signed with `Signer::SigningBox`.
+    pub caller: String,
+    /// Number of signatures produced for this caller.
+    pub signatures_count: u64,
+}
+
+#[derive(Default)]
+struct SigningBoxStats {
+    signatures_count: u64,
+    last_used: Option<u64>,
+    callers: Vec<SigningBoxCallerStats>,
+    recent: VecDeque<u64>,
+}
+
+pub(crate) struct SigningBoxEntry {
+    signing_box: Arc<dyn SigningBox + Send + Sync>,
+    limits: Option<SigningBoxLimits>,
+    stats: Mutex<SigningBoxStats>,
+}
+
+impl SigningBoxEntry {
+    fn new(signing_box: Arc<dyn SigningBox + Send + Sync>, limits: Option<SigningBoxLimits>) -> Self {
+        Self {
+            signing_box,
+            limits,
+            stats: Default::default(),
+        }
+    }
+
+    /// Checks the limits and counts the signature before it is produced
+    fn acquire(&self, id: u32, caller: &str, now: u64) -> ClientResult<()> {
+        let mut stats = self.stats.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(limits) = &self.limits {
+            if let Some(max_signatures) = limits.max_signatures {
+                if stats.signatures_count >= max_signatures {
+                    return Err(Error::signing_box_limit_exceeded(id, "total signatures limit is reached"));
+                }
+            }
+            if let Some(rate_limit) = limits.rate_limit {
+                let period = limits.rate_period.unwrap_or(DEFAULT_RATE_PERIOD) as u64;
+                while stats.recent.front().map(|time| time + period <= now).unwrap_or(false) {
+                    stats.recent.pop_front();
+                }
+                if stats.recent.len() >= rate_limit as usize {
+                    return Err(Error::signing_box_limit_exceeded(id, "rate limit is reached"));
+                }
+                stats.recent.push_back(now);
+            }
+        }
+        stats.signatures_count += 1;
+        stats.last_used = Some(now);
+        match stats.callers.iter_mut().find(|stats| stats.caller == caller) {
+            Some(caller) => caller.signatures_count += 1,
+            None => stats.callers.push(SigningBoxCallerStats {
+                caller: caller.to_string(),
+                signatures_count: 1,
+            }),
+        }
+        Ok(())
+    }
+
+    /// Reverts the counters if the signing failed
+    fn release(&self, caller: &str, now: u64) {
+        let mut stats = self.stats.lock().unwrap_or_else(PoisonError::into_inner);
+        stats.signatures_count -= 1;
+        if let Some(caller) = stats.callers.iter_mut().find(|stats| stats.caller == caller) {
+            caller.signatures_count -= 1;
+        }
+        if let Some(position) = stats.recent.iter().rposition(|time| *time == now) {
+            stats.recent.remove(position);
+        }
+    }
+}
+
+fn register_entry(context: &ClientContext, entry: SigningBoxEntry) -> RegisteredSigningBox {
+    let id = context.get_next_id();
+    context.boxes.signing_boxes.insert(id, entry);
+    RegisteredSigningBox {
+        handle: SigningBoxHandle(id),
+    }
+}
+
 /// Creates a default signing box implementation.
 #[api_function]
 pub async fn get_signing_box(
     context: std::sync::Arc<ClientContext>,
     params: KeyPair,
 ) -> ClientResult<RegisteredSigningBox> {
-    let id = context.get_next_id();
     let signing_box = KeysSigningBox::from_encoded(params)?;
-    context.boxes.signing_boxes.insert(id, Box::new(signing_box));
-
-    Ok(RegisteredSigningBox {
-        handle: SigningBoxHandle(id),
-    })
+    Ok(register_entry(&context, SigningBoxEntry::new(Arc::new(signing_box), None)))
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default, PartialEq)]
@@ -88,14 +187,105 @@ pub async fn register_signing_box(
     context: std::sync::Arc<ClientContext>,
     signing_box: impl SigningBox + Send + Sync + 'static,
 ) -> ClientResult<RegisteredSigningBox> {
-    let id = context.get_next_id();
-    context.boxes.signing_boxes.insert(id, Box::new(signing_box));
+    Ok(register_entry(&context, SigningBoxEntry::new(Arc::new(signing_box), None)))
+}
 
-    Ok(RegisteredSigningBox {
-        handle: SigningBoxHandle(id),
+#[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default, PartialEq)]
+pub struct ParamsOfLimitSigningBox {
+    /// Signing box to be limited.
+    pub signing_box: SigningBoxHandle,
+    /// Limits of the new signing box.
+    pub limits: SigningBoxLimits,
+}
+
+/// Registers a signing box with limited usage.
+///
+/// The new signing box signs with the given one, but refuses to sign when the total
+/// signatures limit or the rate limit is reached. The limited handle can be passed
+/// to semi-trusted components instead of the original one.
+///
+/// The new box has its own usage statistics and stays valid after the original
+/// box is removed.
+#[api_function]
+pub async fn limit_signing_box(
+    context: Arc<ClientContext>,
+    params: ParamsOfLimitSigningBox,
+) -> ClientResult<RegisteredSigningBox> {
+    let signing_box = context.boxes.signing_boxes
+        .get(&params.signing_box.0)
+        .ok_or(Error::signing_box_not_registered(params.signing_box.0))?
+        .val()
+        .signing_box
+        .clone();
+    Ok(register_entry(&context, SigningBoxEntry::new(signing_box, Some(params.limits))))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default, PartialEq)]
+pub struct ResultOfSigningBoxInfo {
+    /// Number of signatures produced by the signing box.
+    pub signatures_count: u64,
+    /// Time of the last signature in ms.
+    pub last_used: Option<u64>,
+    /// Signatures count by the caller function.
+    pub callers: Vec<SigningBoxCallerStats>,
+    /// Usage limits of the signing box.
+    pub limits: Option<SigningBoxLimits>,
+}
+
+/// Returns usage statistics of the signing box.
+#[api_function]
+pub async fn signing_box_info(
+    context: Arc<ClientContext>,
+    params: RegisteredSigningBox,
+) -> ClientResult<ResultOfSigningBoxInfo> {
+    let entry = context.boxes.signing_boxes
+        .get(&params.handle.0)
+        .ok_or(Error::signing_box_not_registered(params.handle.0))?;
+    let stats = entry.val().stats.lock().unwrap_or_else(PoisonError::into_inner);
+    Ok(ResultOfSigningBoxInfo {
+        signatures_count: stats.signatures_count,
+        last_used: stats.last_used,
+        callers: stats.callers.clone(),
+        limits: entry.val().limits.clone(),
     })
 }
 
+/// Signs data with the registered signing box counting the signature for the `caller`
+pub(crate) async fn sign_with_signing_box(
+    context: &ClientContext,
+    handle: &SigningBoxHandle,
+    unsigned: &[u8],
+    caller: &str,
+) -> ClientResult<Vec<u8>> {
+    let entry = context.boxes.signing_boxes
+        .get(&handle.0)
+        .ok_or(Error::signing_box_not_registered(handle.0))?;
+    let now = context.env.now_ms();
+    entry.val().acquire(handle.0, caller, now)?;
+    let result = entry.val().signing_box.sign(unsigned).await;
+    if result.is_err() {
+        entry.val().release(caller, now);
+    }
+    result
+}
+
+/// Signs the message hash with the signing box. Fails if the box signatures
+/// don't cover the signed data itself, because contracts would reject such messages
+pub(crate) async fn sign_message_with_signing_box(
+    context: &ClientContext,
+    handle: &SigningBoxHandle,
+    hash: &[u8],
+) -> ClientResult<Vec<u8>> {
+    let signs_unsigned_data = context.boxes.signing_boxes
+        .get(&handle.0)
+        .ok_or(Error::signing_box_not_registered(handle.0))?
+        .val().signing_box.signs_unsigned_data();
+    if !signs_unsigned_data {
+        return Err(Error::message_signing_not_supported(handle.0));
+    }
+    sign_with_signing_box(context, handle, hash, "abi").await
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default, PartialEq)]
 pub struct ResultOfSigningBoxGetPublicKey {
     /// Public key of signing box. Encoded with hex
@@ -112,7 +302,7 @@ pub async fn signing_box_get_public_key(
         .get(&params.handle.0)
         .ok_or(Error::signing_box_not_registered(params.handle.0))?;
 
-    let key = signing_box.1.get_public_key().await?;
+    let key = signing_box.val().signing_box.get_public_key().await?;
 
     Ok(ResultOfSigningBoxGetPublicKey {
         pubkey: hex::encode(&key)
@@ -139,13 +329,14 @@ pub async fn signing_box_sign(
     context: Arc<ClientContext>,
     params: ParamsOfSigningBoxSign,
 ) -> ClientResult<ResultOfSigningBoxSign> {
-    let signing_box = context.boxes.signing_boxes
-        .get(&params.signing_box.0)
-        .ok_or(Error::signing_box_not_registered(params.signing_box.0))?;
-
     let unsigned = crate::encoding::base64_decode(&params.unsigned)?;
 
-    let signed = signing_box.1.sign(&unsigned).await?;
+    let signed = sign_with_signing_box(
+        &context,
+        &params.signing_box,
+        &unsigned,
+        "crypto.signing_box_sign",
+    ).await?;
 
     Ok(ResultOfSigningBoxSign {
         signature: hex::encode(&signed)
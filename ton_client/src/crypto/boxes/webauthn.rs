@@ -0,0 +1,102 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+// Checks of the WebAuthn assertions used as signatures by the signing box backed by
+// an application authenticator (passkey). The data to sign is passed as the assertion
+// challenge, the authenticator signs `authenticatorData || sha256(clientDataJSON)`.
+
+use sha2::{Digest, Sha256};
+
+use crate::crypto::internal::Key256;
+use crate::crypto::Error;
+use crate::error::ClientResult;
+
+/// `type` of the client data of the assertion
+const ASSERTION_TYPE: &str = "webauthn.get";
+/// User present flag of the authenticator data
+const FLAG_USER_PRESENT: u8 = 0x01;
+/// User verified flag of the authenticator data
+const FLAG_USER_VERIFIED: u8 = 0x04;
+/// Length of `rpIdHash`, `flags` and `signCount` of the authenticator data
+const AUTHENTICATOR_DATA_MIN_LEN: usize = 37;
+/// Length of the Ed25519 (COSE algorithm -8) signature
+const SIGNATURE_LEN: usize = 64;
+
+/// Assertion returned by the authenticator
+pub(crate) struct WebAuthnAssertion {
+    pub authenticator_data: Vec<u8>,
+    pub client_data_json: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+/// Challenge of the assertion for the data to sign: base64url without padding
+pub(crate) fn assertion_challenge(unsigned: &[u8]) -> String {
+    base64::encode_config(unsigned, base64::URL_SAFE_NO_PAD)
+}
+
+/// Checks that the assertion is made for the `unsigned` data by the Ed25519 credential
+/// with the `public_key` scoped to the `rp_id` relying party
+pub(crate) fn verify_assertion(
+    assertion: &WebAuthnAssertion,
+    unsigned: &[u8],
+    public_key: &Key256,
+    rp_id: &str,
+    user_verification: bool,
+) -> ClientResult<()> {
+    let client_data: serde_json::Value = serde_json::from_slice(&assertion.client_data_json)
+        .map_err(|err| {
+            Error::invalid_webauthn_assertion(format!("invalid clientDataJSON: {}", err))
+        })?;
+    if client_data["type"] != ASSERTION_TYPE {
+        return Err(Error::invalid_webauthn_assertion(format!(
+            "clientDataJSON type is {} instead of {}",
+            client_data["type"], ASSERTION_TYPE
+        )));
+    }
+    if client_data["challenge"] != assertion_challenge(unsigned).as_str() {
+        return Err(Error::invalid_webauthn_assertion(
+            "challenge doesn't match the data to sign",
+        ));
+    }
+
+    let data = &assertion.authenticator_data;
+    if data.len() < AUTHENTICATOR_DATA_MIN_LEN {
+        return Err(Error::invalid_webauthn_assertion("authenticator data is too short"));
+    }
+    if data[..32] != Sha256::digest(rp_id.as_bytes())[..] {
+        return Err(Error::invalid_webauthn_assertion(format!(
+            "authenticator data is not scoped to {}",
+            rp_id
+        )));
+    }
+    let flags = data[32];
+    if flags & FLAG_USER_PRESENT == 0 {
+        return Err(Error::invalid_webauthn_assertion("user presence flag is not set"));
+    }
+    if user_verification && flags & FLAG_USER_VERIFIED == 0 {
+        return Err(Error::invalid_webauthn_assertion("user verification flag is not set"));
+    }
+
+    if assertion.signature.len() != SIGNATURE_LEN {
+        return Err(Error::invalid_webauthn_assertion(
+            "only Ed25519 credentials (COSE algorithm -8) are supported",
+        ));
+    }
+    let mut signed = assertion.signature.clone();
+    signed.extend_from_slice(data);
+    signed.extend_from_slice(&Sha256::digest(&assertion.client_data_json));
+    let mut opened = vec![0; signed.len()];
+    sodalite::sign_attached_open(&mut opened, &signed, public_key)
+        .map_err(|_| Error::invalid_webauthn_assertion("signature verification failed"))?;
+    Ok(())
+}
@@ -0,0 +1,308 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use chacha20poly1305::aead::{Aead, NewAead, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngCore;
+
+use crate::client::ClientContext;
+use crate::crypto::mnemonic::mnemonics;
+use crate::crypto::Error;
+use crate::encoding::{base64_decode, hex_decode};
+use crate::error::ClientResult;
+
+const CONTAINER_VERSION: u8 = 1;
+const KDF_SCRYPT: &str = "scrypt";
+const CIPHER_CHACHA20_POLY1305: &str = "chacha20-poly1305";
+const KEY_SIZE: usize = 32;
+const NONCE_SIZE: usize = 12;
+const SALT_SIZE: usize = 32;
+
+const DEFAULT_LOG_N: u8 = 15;
+const DEFAULT_R: u32 = 8;
+const DEFAULT_P: u32 = 1;
+
+/// Limits of the Scrypt cost, so a hostile container can't exhaust the memory or CPU
+const MAX_LOG_N: u8 = 20;
+const MAX_R_P: u64 = 1 << 16;
+
+#[derive(Serialize, Deserialize, ApiType, Clone, Debug, PartialEq)]
+pub struct EncryptedMnemonicKdfParams {
+    /// The log2 of the Scrypt parameter `N`. Default is `15`, maximum is `20`.
+    pub log_n: Option<u8>,
+    /// The Scrypt parameter `r`. Default is `8`. The product `r * p` must be less than `2^16`.
+    pub r: Option<u32>,
+    /// The Scrypt parameter `p`. Default is `1`.
+    pub p: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct KdfHeader {
+    #[serde(rename = "type")]
+    kdf_type: String,
+    log_n: u8,
+    r: u32,
+    p: u32,
+    /// Encoded with `base64`
+    salt: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct CipherHeader {
+    #[serde(rename = "type")]
+    cipher_type: String,
+    /// Encoded with `hex`
+    nonce: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Container {
+    version: u8,
+    kdf: KdfHeader,
+    cipher: CipherHeader,
+    dictionary: u8,
+    word_count: u8,
+    /// Encoded with `base64`
+    ciphertext: String,
+}
+
+impl Container {
+    /// Associated data authenticated together with the phrase. It is built from the
+    /// header fields, so the result doesn't depend on the JSON formatting of the container
+    fn associated_data(&self) -> Vec<u8> {
+        format!(
+            "{}:{}:{}:{}:{}:{}:{}:{}:{}:{}",
+            self.version,
+            self.kdf.kdf_type,
+            self.kdf.log_n,
+            self.kdf.r,
+            self.kdf.p,
+            self.kdf.salt,
+            self.cipher.cipher_type,
+            self.cipher.nonce,
+            self.dictionary,
+            self.word_count,
+        )
+        .into_bytes()
+    }
+
+    /// Checks that the key derivation cost doesn't exceed the limits
+    fn check_kdf_cost(&self) -> Result<(), String> {
+        if self.kdf.log_n > MAX_LOG_N {
+            return Err(format!("scrypt `log_n` must not exceed {}", MAX_LOG_N));
+        }
+        if self.kdf.r as u64 * self.kdf.p as u64 >= MAX_R_P {
+            return Err(format!("scrypt `r * p` must be less than {}", MAX_R_P));
+        }
+        Ok(())
+    }
+
+    fn derive_key(&self, password: &[u8]) -> ClientResult<Vec<u8>> {
+        let params = scrypt::Params::new(self.kdf.log_n, self.kdf.r, self.kdf.p)
+            .map_err(|err| Error::scrypt_failed(err))?;
+        let salt = base64_decode(&self.kdf.salt)?;
+        let mut key = vec![0u8; KEY_SIZE];
+        scrypt::scrypt(password, &salt, &params, &mut key)
+            .map_err(|err| Error::scrypt_failed(err))?;
+        Ok(key)
+    }
+
+    fn nonce(&self) -> ClientResult<Vec<u8>> {
+        let nonce = hex_decode(&self.cipher.nonce)?;
+        if nonce.len() != NONCE_SIZE {
+            return Err(Error::invalid_encrypted_mnemonic(format!(
+                "nonce must be {} bytes",
+                NONCE_SIZE
+            )));
+        }
+        Ok(nonce)
+    }
+}
+
+//----------------------------------------------------------------------- export_encrypted_mnemonic
+
+#[derive(Serialize, Deserialize, ApiType, Default)]
+pub struct ParamsOfExportEncryptedMnemonic {
+    /// Phrase
+    pub phrase: String,
+    /// Password the phrase is encrypted with. Must be encoded with `base64`.
+    pub password: String,
+    /// Dictionary identifier
+    pub dictionary: Option<u8>,
+    /// Word count
+    pub word_count: Option<u8>,
+    /// Key derivation parameters. Recommended values are used by default.
+    pub kdf_params: Option<EncryptedMnemonicKdfParams>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug)]
+pub struct ResultOfExportEncryptedMnemonic {
+    /// Encrypted mnemonic container. JSON text.
+    pub container: String,
+}
+
+/// Encrypts the seed phrase into a portable container
+///
+/// The container is a JSON object:
+/// ```json
+/// {
+///     "version": 1,
+///     "kdf": { "type": "scrypt", "log_n": 15, "r": 8, "p": 1, "salt": "<base64>" },
+///     "cipher": { "type": "chacha20-poly1305", "nonce": "<hex>" },
+///     "dictionary": 1,
+///     "word_count": 12,
+///     "ciphertext": "<base64>"
+/// }
+/// ```
+/// The 256-bit key is derived from the password with `scrypt` using the random 32-byte salt.
+/// The UTF-8 phrase is encrypted with ChaCha20-Poly1305 using the random 96-bit nonce.
+/// The header fields are authenticated as the associated data joined with `:` in the order
+/// `version:kdf.type:log_n:r:p:salt:cipher.type:nonce:dictionary:word_count`,
+/// so any modification of the container is detected on import.
+///
+/// The phrase is validated with the dictionary and word count before the encryption.
+/// The Scrypt cost is limited with `log_n <= 20` and `r * p < 2^16` both on export and import.
+#[api_function]
+pub fn export_encrypted_mnemonic(
+    context: std::sync::Arc<ClientContext>,
+    params: ParamsOfExportEncryptedMnemonic,
+) -> ClientResult<ResultOfExportEncryptedMnemonic> {
+    let config = &context.config.crypto;
+    let dictionary = params.dictionary.unwrap_or(config.mnemonic_dictionary);
+    let word_count = params.word_count.unwrap_or(config.mnemonic_word_count);
+    if !mnemonics(config, Some(dictionary), Some(word_count))?.is_phrase_valid(&params.phrase)? {
+        return Err(Error::bip39_invalid_phrase(
+            "phrase is not valid for the dictionary and word count",
+        ));
+    }
+    let kdf_params = params.kdf_params.unwrap_or(EncryptedMnemonicKdfParams {
+        log_n: None,
+        r: None,
+        p: None,
+    });
+
+    let mut salt = [0u8; SALT_SIZE];
+    let mut nonce = [0u8; NONCE_SIZE];
+    rand::thread_rng().fill_bytes(&mut salt);
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let mut container = Container {
+        version: CONTAINER_VERSION,
+        kdf: KdfHeader {
+            kdf_type: KDF_SCRYPT.to_string(),
+            log_n: kdf_params.log_n.unwrap_or(DEFAULT_LOG_N),
+            r: kdf_params.r.unwrap_or(DEFAULT_R),
+            p: kdf_params.p.unwrap_or(DEFAULT_P),
+            salt: base64::encode(&salt),
+        },
+        cipher: CipherHeader {
+            cipher_type: CIPHER_CHACHA20_POLY1305.to_string(),
+            nonce: hex::encode(&nonce),
+        },
+        dictionary,
+        word_count,
+        ciphertext: String::new(),
+    };
+    container.check_kdf_cost().map_err(|err| Error::scrypt_failed(err))?;
+    let key = container.derive_key(&base64_decode(&params.password)?)?;
+    let aad = container.associated_data();
+    let encrypted = ChaCha20Poly1305::new(Key::from_slice(&key))
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: params.phrase.as_bytes(),
+                aad: &aad,
+            },
+        )
+        .map_err(|err| Error::encrypt_data_error(format!("{:?}", err)))?;
+    container.ciphertext = base64::encode(&encrypted);
+
+    Ok(ResultOfExportEncryptedMnemonic {
+        container: serde_json::to_string(&container)
+            .map_err(|err| Error::encrypt_data_error(err))?,
+    })
+}
+
+//----------------------------------------------------------------------- import_encrypted_mnemonic
+
+#[derive(Serialize, Deserialize, ApiType, Default)]
+pub struct ParamsOfImportEncryptedMnemonic {
+    /// Encrypted mnemonic container returned by `export_encrypted_mnemonic`. JSON text.
+    pub container: String,
+    /// Password the phrase was encrypted with. Must be encoded with `base64`.
+    pub password: String,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug)]
+pub struct ResultOfImportEncryptedMnemonic {
+    /// Phrase
+    pub phrase: String,
+    /// Dictionary identifier
+    pub dictionary: u8,
+    /// Word count
+    pub word_count: u8,
+}
+
+/// Decrypts the seed phrase from the container created with `export_encrypted_mnemonic`
+///
+/// Fails with `DecryptDataError` if the password is wrong or the container was modified.
+#[api_function]
+pub fn import_encrypted_mnemonic(
+    _context: std::sync::Arc<ClientContext>,
+    params: ParamsOfImportEncryptedMnemonic,
+) -> ClientResult<ResultOfImportEncryptedMnemonic> {
+    let container: Container = serde_json::from_str(&params.container)
+        .map_err(|err| Error::invalid_encrypted_mnemonic(err))?;
+    if container.version != CONTAINER_VERSION {
+        return Err(Error::invalid_encrypted_mnemonic(format!(
+            "unsupported version {}",
+            container.version
+        )));
+    }
+    if container.kdf.kdf_type != KDF_SCRYPT {
+        return Err(Error::invalid_encrypted_mnemonic(format!(
+            "unsupported key derivation function `{}`",
+            container.kdf.kdf_type
+        )));
+    }
+    if container.cipher.cipher_type != CIPHER_CHACHA20_POLY1305 {
+        return Err(Error::invalid_encrypted_mnemonic(format!(
+            "unsupported cipher `{}`",
+            container.cipher.cipher_type
+        )));
+    }
+
+    container.check_kdf_cost().map_err(|err| Error::invalid_encrypted_mnemonic(err))?;
+
+    let nonce = container.nonce()?;
+    let key = container.derive_key(&base64_decode(&params.password)?)?;
+    let aad = container.associated_data();
+    let ciphertext = base64_decode(&container.ciphertext)?;
+    let decrypted = ChaCha20Poly1305::new(Key::from_slice(&key))
+        .decrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &ciphertext,
+                aad: &aad,
+            },
+        )
+        .map_err(|_| Error::decrypt_data_error("wrong password or corrupted container"))?;
+    let phrase = String::from_utf8(decrypted)
+        .map_err(|err| Error::invalid_encrypted_mnemonic(err))?;
+
+    Ok(ResultOfImportEncryptedMnemonic {
+        phrase,
+        dictionary: container.dictionary,
+        word_count: container.word_count,
+    })
+}
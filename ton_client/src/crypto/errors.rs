@@ -32,6 +32,15 @@ pub enum ErrorCode {
     EncryptDataError = 127,
     DecryptDataError = 128,
     IvRequired = 129,
+    InvalidHashSize = 130,
+    InvalidDomainLabel = 131,
+    AssociatedDataNotSupported = 132,
+    SigningBoxLimitExceeded = 133,
+    InvalidThresholdParams = 134,
+    NotEnoughTrusteeShares = 135,
+    InvalidEncryptedMnemonic = 136,
+    InvalidWebAuthnAssertion = 137,
+    MessageSigningNotSupported = 138,
 }
 
 pub struct Error;
@@ -230,4 +239,76 @@ impl Error {
             format!("initialization vector is required for {:?} cipher mode", mode),
         )
     }
+
+    pub fn invalid_hash_size(actual: usize) -> ClientError {
+        error(
+            ErrorCode::InvalidHashSize,
+            format!("Invalid hash size {}. Expected 32 bytes.", actual),
+        )
+    }
+
+    pub fn invalid_domain_label(domain: &str) -> ClientError {
+        error(
+            ErrorCode::InvalidDomainLabel,
+            format!("Invalid domain separation label [{}]: label must not be empty", domain),
+        )
+    }
+
+    pub fn associated_data_not_supported(algorithm: &Option<String>) -> ClientError {
+        error(
+            ErrorCode::AssociatedDataNotSupported,
+            format!(
+                "Associated data is not supported by the encryption box algorithm: {}",
+                algorithm.as_deref().unwrap_or("unknown"),
+            ),
+        )
+    }
+
+    pub fn signing_box_limit_exceeded(id: u32, reason: &str) -> ClientError {
+        error(
+            ErrorCode::SigningBoxLimitExceeded,
+            format!("Signing box refused to sign: {}. ID {}", reason, id),
+        )
+    }
+
+    pub fn invalid_threshold_params(reason: impl Display) -> ClientError {
+        error(
+            ErrorCode::InvalidThresholdParams,
+            format!("Invalid threshold encryption box params: {}", reason),
+        )
+    }
+
+    pub fn not_enough_trustee_shares(available: usize, threshold: u32) -> ClientError {
+        error(
+            ErrorCode::NotEnoughTrusteeShares,
+            format!(
+                "Not enough trustee shares to decrypt data: {} available, {} required",
+                available, threshold,
+            ),
+        )
+    }
+
+    pub fn invalid_encrypted_mnemonic(reason: impl Display) -> ClientError {
+        error(
+            ErrorCode::InvalidEncryptedMnemonic,
+            format!("Invalid encrypted mnemonic container: {}", reason),
+        )
+    }
+
+    pub fn invalid_webauthn_assertion(reason: impl Display) -> ClientError {
+        error(
+            ErrorCode::InvalidWebAuthnAssertion,
+            format!("Invalid WebAuthn assertion: {}", reason),
+        )
+    }
+
+    pub fn message_signing_not_supported(handle: u32) -> ClientError {
+        error(
+            ErrorCode::MessageSigningNotSupported,
+            format!(
+                "Signing box {} can't sign messages: its signatures don't cover the message hash",
+                handle,
+            ),
+        )
+    }
 }
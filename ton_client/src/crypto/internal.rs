@@ -1,12 +1,16 @@
 use crate::crypto;
 use crate::error::ClientResult;
 use ed25519_dalek::{Keypair, PublicKey, SecretKey};
+#[cfg(feature = "crypto-extended")]
 use hmac::*;
 use sha2::Digest;
+#[cfg(feature = "crypto-extended")]
 use sha2::Sha512;
 
+#[cfg(feature = "crypto-extended")]
 pub(crate) type Key192 = [u8; 24];
 pub(crate) type Key256 = [u8; 32];
+#[cfg(feature = "crypto-extended")]
 pub(crate) type Key264 = [u8; 33];
 pub(crate) type Key512 = [u8; 64];
 
@@ -58,6 +62,7 @@ pub(crate) fn key256(slice: &[u8]) -> ClientResult<Key256> {
     Ok(key)
 }
 
+#[cfg(feature = "crypto-extended")]
 pub(crate) fn key192(slice: &[u8]) -> ClientResult<Key192> {
     if slice.len() != 24 {
         return Err(crypto::Error::invalid_key_size(slice.len(), &[24]));
@@ -69,6 +74,7 @@ pub(crate) fn key192(slice: &[u8]) -> ClientResult<Key192> {
     Ok(key)
 }
 
+#[cfg(feature = "crypto-extended")]
 pub(crate) fn hmac_sha512(key: &[u8], data: &[u8]) -> [u8; 64] {
     let mut hmac = Hmac::<Sha512>::new_from_slice(key).unwrap();
     hmac.update(&data);
@@ -77,6 +83,7 @@ pub(crate) fn hmac_sha512(key: &[u8], data: &[u8]) -> [u8; 64] {
     result
 }
 
+#[cfg(feature = "crypto-extended")]
 pub(crate) fn pbkdf2_hmac_sha512(password: &[u8], salt: &[u8], c: u32) -> [u8; 64] {
     let mut result = [0u8; 64];
     pbkdf2::pbkdf2::<Hmac<Sha512>>(password, salt, c, &mut result);
@@ -14,9 +14,9 @@
 use crate::client::ClientContext;
 use crate::crypto;
 use crate::crypto::internal::{
-    decode_public_key, decode_secret_key, key256, sign_using_keys, ton_crc16,
+    decode_public_key, decode_secret_key, key256, sha256, sign_using_keys, ton_crc16,
 };
-use crate::encoding::{base64_decode, hex_decode};
+use crate::encoding::{account_decode, base64_decode, hex_decode};
 use crate::error::ClientResult;
 use base64::URL_SAFE;
 use ed25519_dalek::Keypair;
@@ -97,6 +97,68 @@ pub fn convert_public_key_to_ton_safe_format(
     })
 }
 
+//--------------------------------------------------------------------------------- key_fingerprint
+
+const FINGERPRINT_KEY_DOMAIN: &[u8] = b"ton-fingerprint-v1:key:";
+const FINGERPRINT_ADDRESS_DOMAIN: &[u8] = b"ton-fingerprint-v1:address:";
+const FINGERPRINT_GROUPS: usize = 4;
+const FINGERPRINT_GROUP_BYTES: usize = 2;
+
+#[derive(Serialize, Deserialize, ApiType, Default)]
+pub struct ParamsOfKeyFingerprint {
+    /// Public key - 64 symbols hex string, or account address in any format.
+    pub key: String,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default)]
+pub struct ResultOfKeyFingerprint {
+    /// Short fingerprint to compare keys visually: four groups of four upper case
+    /// hex digits separated by `-`, e.g. `1A2B-3C4D-5E6F-7A8B`.
+    pub fingerprint: String,
+    /// Seed for identicon rendering - 32 bytes encoded as hex string.
+    pub identicon_seed: String,
+}
+
+/// Calculates the fingerprint and the identicon seed of a public key or an address.
+///
+/// Both are taken from `sha256(domain + data)` where `domain` is
+/// `ton-fingerprint-v1:key:` for public keys with `data` of 32 key bytes and
+/// `ton-fingerprint-v1:address:` for addresses with `data` of the workchain id
+/// (4 bytes big endian) followed by 32 account id bytes.
+/// The fingerprint is the first 8 bytes of the hash, the identicon seed is the whole hash.
+/// So the same key or address gives the same visuals in all the applications.
+#[api_function]
+pub fn key_fingerprint(
+    _context: std::sync::Arc<ClientContext>,
+    params: ParamsOfKeyFingerprint,
+) -> ClientResult<ResultOfKeyFingerprint> {
+    let mut data = Vec::new();
+    if params.key.len() == 64 && !params.key.contains(':') {
+        let public_key = hex_decode(&params.key)
+            .map_err(|err| crypto::Error::invalid_public_key(err, &params.key))?;
+        data.extend_from_slice(FINGERPRINT_KEY_DOMAIN);
+        data.extend_from_slice(&public_key);
+    } else {
+        let address = account_decode(&params.key)?;
+        data.extend_from_slice(FINGERPRINT_ADDRESS_DOMAIN);
+        data.extend_from_slice(&address.get_workchain_id().to_be_bytes());
+        data.extend_from_slice(&address.address().get_bytestring(0));
+    }
+    let hash = sha256(&data);
+
+    let fingerprint = hash
+        .chunks(FINGERPRINT_GROUP_BYTES)
+        .take(FINGERPRINT_GROUPS)
+        .map(|group| hex::encode_upper(group))
+        .collect::<Vec<_>>()
+        .join("-");
+
+    Ok(ResultOfKeyFingerprint {
+        fingerprint,
+        identicon_seed: hex::encode(&hash),
+    })
+}
+
 //----------------------------------------------------------------------- generate_random_sign_keys
 
 /// Generates random ed25519 key pair.
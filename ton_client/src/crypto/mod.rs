@@ -12,39 +12,64 @@
 */
 
 pub(crate) mod boxes;
+#[cfg(feature = "crypto-extended")]
+pub(crate) mod encrypted_mnemonic;
+#[cfg(feature = "crypto-extended")]
 pub(crate) mod encscrypt;
 mod errors;
 pub(crate) mod hash;
+#[cfg(feature = "crypto-extended")]
 pub(crate) mod hdkey;
 pub(crate) mod internal;
 pub(crate) mod keys;
 pub(crate) mod math;
+#[cfg(feature = "crypto-extended")]
 pub(crate) mod mnemonic;
+#[cfg(feature = "crypto-extended")]
 pub(crate) mod nacl;
+#[cfg(feature = "crypto-extended")]
+pub(crate) mod shamir;
 
 pub use errors::{Error, ErrorCode};
+#[cfg(feature = "crypto-extended")]
 pub(crate) mod encryption;
 #[cfg(test)]
 mod tests;
 
 pub use crate::crypto::boxes::signing_box::{
-    get_signing_box, register_signing_box, remove_signing_box, signing_box_get_public_key,
-    signing_box_sign,
-    ParamsOfSigningBoxSign, RegisteredSigningBox, ResultOfSigningBoxGetPublicKey,
-    ResultOfSigningBoxSign, SigningBox, SigningBoxHandle,
+    get_signing_box, limit_signing_box, register_signing_box, remove_signing_box,
+    signing_box_get_public_key, signing_box_info, signing_box_sign,
+    ParamsOfLimitSigningBox, ParamsOfSigningBoxSign, RegisteredSigningBox,
+    ResultOfSigningBoxGetPublicKey, ResultOfSigningBoxInfo, ResultOfSigningBoxSign, SigningBox,
+    SigningBoxCallerStats, SigningBoxHandle, SigningBoxLimits,
 };
 pub use crate::crypto::boxes::encryption_box::{
-    register_encryption_box, remove_encryption_box, create_encryption_box,
+    register_encryption_box, remove_encryption_box,
     encryption_box_get_info, encryption_box_encrypt, encryption_box_decrypt,
     EncryptionBox, CipherMode, RegisteredEncryptionBox, EncryptionBoxHandle,
-    EncryptionBoxInfo, EncryptionAlgorithm,
+    EncryptionBoxInfo,
     ParamsOfEncryptionBoxGetInfo, ResultOfEncryptionBoxGetInfo,
     ParamsOfEncryptionBoxEncrypt, ResultOfEncryptionBoxEncrypt,
     ParamsOfEncryptionBoxDecrypt, ResultOfEncryptionBoxDecrypt,
 };
+#[cfg(feature = "crypto-extended")]
+pub use crate::crypto::boxes::encryption_box::{create_encryption_box, EncryptionAlgorithm};
+#[cfg(feature = "crypto-extended")]
 pub use crate::crypto::boxes::encryption_box::aes::{AesInfo, AesParams};
+#[cfg(feature = "crypto-extended")]
+pub use crate::crypto::boxes::encryption_box::chacha20_poly1305::ChaCha20Poly1305Params;
+#[cfg(feature = "crypto-extended")]
+pub use crate::crypto::boxes::encryption_box::threshold::{ThresholdInfo, ThresholdParams};
+#[cfg(feature = "crypto-extended")]
+pub use crate::crypto::encrypted_mnemonic::{
+    export_encrypted_mnemonic, import_encrypted_mnemonic, EncryptedMnemonicKdfParams,
+    ParamsOfExportEncryptedMnemonic, ParamsOfImportEncryptedMnemonic,
+    ResultOfExportEncryptedMnemonic, ResultOfImportEncryptedMnemonic,
+};
+#[cfg(feature = "crypto-extended")]
 pub use crate::crypto::encscrypt::{scrypt, ParamsOfScrypt, ResultOfScrypt};
 pub use crate::crypto::hash::{sha256, sha512, ParamsOfHash, ResultOfHash};
+#[cfg(feature = "crypto-extended")]
 pub use crate::crypto::hdkey::{
     hdkey_derive_from_xprv, hdkey_derive_from_xprv_path, hdkey_public_from_xprv,
     hdkey_secret_from_xprv, hdkey_xprv_from_mnemonic, ParamsOfHDKeyDeriveFromXPrv,
@@ -53,15 +78,17 @@ pub use crate::crypto::hdkey::{
     ResultOfHDKeyPublicFromXPrv, ResultOfHDKeySecretFromXPrv, ResultOfHDKeyXPrvFromMnemonic,
 };
 pub use crate::crypto::keys::{
-    convert_public_key_to_ton_safe_format, generate_random_sign_keys, sign, verify_signature,
-    KeyPair, ParamsOfConvertPublicKeyToTonSafeFormat, ParamsOfSign, ParamsOfVerifySignature,
-    ResultOfConvertPublicKeyToTonSafeFormat, ResultOfSign, ResultOfVerifySignature,
+    convert_public_key_to_ton_safe_format, generate_random_sign_keys, key_fingerprint, sign,
+    verify_signature, KeyPair, ParamsOfConvertPublicKeyToTonSafeFormat, ParamsOfKeyFingerprint,
+    ParamsOfSign, ParamsOfVerifySignature, ResultOfConvertPublicKeyToTonSafeFormat,
+    ResultOfKeyFingerprint, ResultOfSign, ResultOfVerifySignature,
 };
 pub use crate::crypto::math::{
     factorize, generate_random_bytes, modular_power, ton_crc16, ParamsOfFactorize,
     ParamsOfGenerateRandomBytes, ParamsOfModularPower, ParamsOfTonCrc16, ResultOfFactorize,
     ResultOfGenerateRandomBytes, ResultOfModularPower, ResultOfTonCrc16,
 };
+#[cfg(feature = "crypto-extended")]
 pub use crate::crypto::mnemonic::{
     mnemonic_derive_sign_keys, mnemonic_from_entropy, mnemonic_from_random, mnemonic_verify,
     mnemonic_words, ParamsOfMnemonicDeriveSignKeys, ParamsOfMnemonicFromEntropy,
@@ -69,16 +96,20 @@ pub use crate::crypto::mnemonic::{
     ResultOfMnemonicFromEntropy, ResultOfMnemonicFromRandom, ResultOfMnemonicVerify,
     ResultOfMnemonicWords,
 };
+#[cfg(feature = "crypto-extended")]
 pub use crate::crypto::nacl::{
     nacl_box, nacl_box_keypair, nacl_box_keypair_from_secret_key, nacl_box_open, nacl_secret_box,
-    nacl_secret_box_open, nacl_sign, nacl_sign_detached, nacl_sign_detached_verify,
-    nacl_sign_keypair_from_secret_key, nacl_sign_open, ParamsOfNaclBox,
+    nacl_secret_box_open, nacl_sign, nacl_sign_detached, nacl_sign_detached_hash,
+    nacl_sign_detached_verify, nacl_sign_keypair_from_secret_key, nacl_sign_open,
+    nacl_verify_detached_hash, ParamsOfNaclBox,
     ParamsOfNaclBoxKeyPairFromSecret, ParamsOfNaclBoxOpen, ParamsOfNaclSecretBox,
     ParamsOfNaclSecretBoxOpen, ParamsOfNaclSign, ParamsOfNaclSignDetached,
-    ParamsOfNaclSignDetachedVerify, ParamsOfNaclSignKeyPairFromSecret, ParamsOfNaclSignOpen,
+    ParamsOfNaclSignDetachedHash, ParamsOfNaclSignDetachedVerify,
+    ParamsOfNaclSignKeyPairFromSecret, ParamsOfNaclSignOpen, ParamsOfNaclVerifyDetachedHash,
     ResultOfNaclBox, ResultOfNaclBoxOpen, ResultOfNaclSign, ResultOfNaclSignDetached,
     ResultOfNaclSignDetachedVerify, ResultOfNaclSignOpen,
 };
+#[cfg(feature = "crypto-extended")]
 pub use encryption::{chacha20, ParamsOfChaCha20, ResultOfChaCha20};
 
 use serde::{Deserialize, Deserializer};
@@ -117,7 +148,7 @@ fn deserialize_hdkey_derivation_path<'de, D: Deserializer<'de>>(
     Ok(Option::deserialize(deserializer)?.unwrap_or(default_hdkey_derivation_path()))
 }
 
-#[derive(Deserialize, Debug, Clone, ApiType)]
+#[derive(Serialize, Deserialize, Debug, Clone, ApiType)]
 /// Crypto config.
 pub struct CryptoConfig {
     /// Mnemonic dictionary that will be used by default in crypto functions. 
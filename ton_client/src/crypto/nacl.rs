@@ -194,6 +194,88 @@ pub fn nacl_sign_detached_verify(
     Ok(ResultOfNaclSignDetachedVerify { succeeded })
 }
 
+//------------------------------------------------------------------------- nacl_sign_detached_hash
+
+fn hash_signing_payload(domain: &str, hash: &str) -> ClientResult<Vec<u8>> {
+    if domain.is_empty() {
+        return Err(Error::invalid_domain_label(domain));
+    }
+    let hash = hex_decode(hash)?;
+    if hash.len() != 32 {
+        return Err(Error::invalid_hash_size(hash.len()));
+    }
+    let mut payload = Vec::with_capacity(domain.len() + 1 + hash.len());
+    payload.extend_from_slice(domain.as_bytes());
+    payload.push(0);
+    payload.extend_from_slice(&hash);
+    Ok(payload)
+}
+
+///
+#[derive(Serialize, Deserialize, ApiType, Default)]
+pub struct ParamsOfNaclSignDetachedHash {
+    /// 32-byte hash of the data that must be signed. Encoded with `hex`.
+    pub hash: String,
+    /// Domain separation label, e.g. `"my-app/file-signature/v1"`. Must not be empty.
+    pub domain: String,
+    /// Signer's secret key - unprefixed 0-padded to 128 symbols hex string
+    /// (concatenation of 64 symbols secret and 64 symbols public keys).
+    /// See `nacl_sign_keypair_from_secret_key`.
+    pub secret: String,
+}
+
+/// Signs a hash of the data using the secret key and returns a signature.
+///
+/// Allows to sign large payloads which are hashed by the caller (e.g. streamed through
+/// SHA-256) instead of passing the whole payload to the SDK.
+///
+/// The signed message is the UTF-8 `domain` label followed by a zero byte and the 32-byte
+/// `hash`, so a signature produced for one domain can't be reused in another one.
+/// Use `nacl_verify_detached_hash` to verify the signature.
+#[api_function]
+pub fn nacl_sign_detached_hash(
+    _context: std::sync::Arc<ClientContext>,
+    params: ParamsOfNaclSignDetachedHash,
+) -> ClientResult<ResultOfNaclSignDetached> {
+    let (_, signature) = internal::sign_using_secret(
+        &hash_signing_payload(&params.domain, &params.hash)?,
+        &hex_decode(&params.secret)?,
+    )?;
+    Ok(ResultOfNaclSignDetached {
+        signature: hex::encode(signature),
+    })
+}
+
+//----------------------------------------------------------------------- nacl_verify_detached_hash
+
+///
+#[derive(Serialize, Deserialize, ApiType, Default)]
+pub struct ParamsOfNaclVerifyDetachedHash {
+    /// 32-byte hash of the signed data. Encoded with `hex`.
+    pub hash: String,
+    /// Domain separation label used for signing.
+    pub domain: String,
+    /// Signature that must be verified. Encoded with `hex`.
+    pub signature: String,
+    /// Signer's public key - unprefixed 0-padded to 64 symbols hex string.
+    pub public: String,
+}
+
+/// Verifies the signature produced by `nacl_sign_detached_hash`.
+#[api_function]
+pub fn nacl_verify_detached_hash(
+    _context: std::sync::Arc<ClientContext>,
+    params: ParamsOfNaclVerifyDetachedHash,
+) -> ClientResult<ResultOfNaclSignDetachedVerify> {
+    let public = ed25519_dalek::PublicKey::from_bytes(&hex_decode(&params.public)?)
+        .map_err(|err| Error::invalid_public_key(err, &params.public))?;
+    let message = hash_signing_payload(&params.domain, &params.hash)?;
+    let signature = ed25519_dalek::Signature::from_bytes(&key512(&hex_decode(&params.signature)?)?)
+        .map_err(|err| Error::invalid_signature(err, &params.signature))?;
+    let succeeded = public.verify(&message, &signature).is_ok();
+    Ok(ResultOfNaclSignDetachedVerify { succeeded })
+}
+
 // Box
 
 fn prepare_to_convert(
@@ -0,0 +1,94 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+// Shamir's secret sharing over GF(256) with the AES reduction polynomial.
+// Each byte of the secret is shared independently with its own random polynomial.
+
+use rand::RngCore;
+
+/// Maximum number of shares: `x` coordinates are the non-zero field elements
+pub(crate) const MAX_SHARES: usize = 255;
+
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80 != 0;
+        a <<= 1;
+        if carry {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+fn gf_inv(a: u8) -> u8 {
+    // a^254 = a^-1 in GF(256)
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exp = 254u8;
+    while exp != 0 {
+        if exp & 1 != 0 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+/// Splits the secret into `count` shares, any `threshold` of which restore it.
+/// Share `i` has `x = i + 1`. Caller must check `0 < threshold <= count <= MAX_SHARES`.
+pub(crate) fn split(secret: &[u8], count: usize, threshold: usize) -> Vec<(u8, Vec<u8>)> {
+    let mut shares: Vec<(u8, Vec<u8>)> = (1..=count)
+        .map(|x| (x as u8, Vec::with_capacity(secret.len())))
+        .collect();
+    let mut coefficients = vec![0u8; threshold];
+    for byte in secret {
+        coefficients[0] = *byte;
+        rand::thread_rng().fill_bytes(&mut coefficients[1..]);
+        for (x, share) in shares.iter_mut() {
+            // Horner's scheme
+            let y = coefficients
+                .iter()
+                .rev()
+                .fold(0u8, |acc, coefficient| gf_mul(acc, *x) ^ coefficient);
+            share.push(y);
+        }
+    }
+    shares
+}
+
+/// Restores the secret from the shares using Lagrange interpolation at `x = 0`.
+/// Caller must provide shares with distinct non-zero `x` and the same length.
+pub(crate) fn combine(shares: &[(u8, Vec<u8>)]) -> Vec<u8> {
+    let len = shares.first().map(|(_, share)| share.len()).unwrap_or_default();
+    let mut secret = vec![0u8; len];
+    for (i, (xi, share)) in shares.iter().enumerate() {
+        let mut basis = 1u8;
+        for (j, (xj, _)) in shares.iter().enumerate() {
+            if i != j {
+                // xj / (xj - xi), subtraction is XOR in GF(2^n)
+                basis = gf_mul(basis, gf_mul(*xj, gf_inv(xj ^ xi)));
+            }
+        }
+        for (byte, y) in secret.iter_mut().zip(share.iter()) {
+            *byte ^= gf_mul(basis, *y);
+        }
+    }
+    secret
+}
@@ -8,8 +8,9 @@ use crate::crypto::hdkey::{
     ResultOfHDKeyXPrvFromMnemonic,
 };
 use crate::crypto::keys::{
-    KeyPair, ParamsOfConvertPublicKeyToTonSafeFormat, ParamsOfSign, ParamsOfVerifySignature,
-    ResultOfConvertPublicKeyToTonSafeFormat, ResultOfSign, ResultOfVerifySignature, strip_secret
+    KeyPair, ParamsOfConvertPublicKeyToTonSafeFormat, ParamsOfKeyFingerprint, ParamsOfSign,
+    ParamsOfVerifySignature, ResultOfConvertPublicKeyToTonSafeFormat, ResultOfKeyFingerprint,
+    ResultOfSign, ResultOfVerifySignature, strip_secret
 };
 use crate::crypto::math::{
     ParamsOfFactorize, ParamsOfGenerateRandomBytes, ParamsOfModularPower, ParamsOfTonCrc16,
@@ -22,9 +23,10 @@ use crate::crypto::mnemonic::{
 };
 use crate::crypto::nacl::{
     ParamsOfNaclBox, ParamsOfNaclBoxKeyPairFromSecret, ParamsOfNaclBoxOpen, ParamsOfNaclSecretBox,
-    ParamsOfNaclSecretBoxOpen, ParamsOfNaclSign, ParamsOfNaclSignKeyPairFromSecret,
-    ParamsOfNaclSignOpen, ResultOfNaclBox, ResultOfNaclBoxOpen, ResultOfNaclSign,
-    ResultOfNaclSignDetached, ResultOfNaclSignOpen,
+    ParamsOfNaclSecretBoxOpen, ParamsOfNaclSign, ParamsOfNaclSignDetachedHash,
+    ParamsOfNaclSignKeyPairFromSecret, ParamsOfNaclSignOpen, ParamsOfNaclVerifyDetachedHash,
+    ResultOfNaclBox, ResultOfNaclBoxOpen, ResultOfNaclSign, ResultOfNaclSignDetached,
+    ResultOfNaclSignOpen,
 };
 use crate::crypto::{ParamsOfChaCha20, ResultOfChaCha20};
 use crate::json_interface::crypto::{ParamsOfAppSigningBox, ResultOfAppSigningBox};
@@ -203,6 +205,30 @@ fn keys() {
         result.ton_public_key
     );
 
+    let result: ResultOfKeyFingerprint = client
+        .request(
+            "crypto.key_fingerprint",
+            ParamsOfKeyFingerprint {
+                key: "06117f59ade83e097e0fb33e5d29e8735bda82b3bf78a015542aaa853bb69600".into(),
+            },
+        )
+        .unwrap();
+    assert_eq!(result.fingerprint, "997B-60F7-68D5-8476");
+    assert_eq!(
+        result.identicon_seed,
+        "997b60f768d584764bddfcea625f227c9ab049d3f7e5b129516184ea02c896b5"
+    );
+
+    let result: ResultOfKeyFingerprint = client
+        .request(
+            "crypto.key_fingerprint",
+            ParamsOfKeyFingerprint {
+                key: "0:f18d106c11586689b11e946269ec1550b69654a8d5964de668149c28877fb65a".into(),
+            },
+        )
+        .unwrap();
+    assert_eq!(result.fingerprint, "B8D5-860D-ADB2-EACC");
+
     let result: KeyPair = client
         .request_no_params("crypto.generate_random_sign_keys")
         .unwrap();
@@ -307,6 +333,29 @@ fn nacl() {
     }).unwrap();
     assert_eq!(result.succeeded, false);
 
+    let hash = "0000000000000000000000000000000000000000000000000000000000000001";
+    let result: ResultOfNaclSignDetached = client.request("crypto.nacl_sign_detached_hash", ParamsOfNaclSignDetachedHash {
+        hash: hash.into(),
+        domain: "test/v1".into(),
+        secret: "56b6a77093d6fdf14e593f36275d872d75de5b341942376b2a08759f3cbae78f1869b7ef29d58026217e9cf163cbfbd0de889bdf1bf4daebf5433a312f5b8d6e".into(),
+    }).unwrap();
+    let signature = result.signature;
+    let result: ResultOfNaclSignDetachedVerify = client.request("crypto.nacl_verify_detached_hash", ParamsOfNaclVerifyDetachedHash {
+        hash: hash.into(),
+        domain: "test/v1".into(),
+        signature: signature.clone(),
+        public: "1869b7ef29d58026217e9cf163cbfbd0de889bdf1bf4daebf5433a312f5b8d6e".into(),
+    }).unwrap();
+    assert_eq!(result.succeeded, true);
+
+    let result: ResultOfNaclSignDetachedVerify = client.request("crypto.nacl_verify_detached_hash", ParamsOfNaclVerifyDetachedHash {
+        hash: hash.into(),
+        domain: "test/v2".into(),
+        signature: signature.clone(),
+        public: "1869b7ef29d58026217e9cf163cbfbd0de889bdf1bf4daebf5433a312f5b8d6e".into(),
+    }).unwrap();
+    assert_eq!(result.succeeded, false);
+
     // Box
 
     let result: KeyPair = client.request_no_params("crypto.nacl_box_keypair").unwrap();
@@ -622,6 +671,130 @@ fn mnemonic() {
     );
 }
 
+#[test]
+fn encrypted_mnemonic() {
+    TestClient::init_log();
+    let client = TestClient::new();
+
+    let phrase = "abandon math mimic master filter design carbon crystal rookie group knife young";
+    let kdf_params = Some(EncryptedMnemonicKdfParams {
+        log_n: Some(10),
+        r: None,
+        p: None,
+    });
+    let exported: ResultOfExportEncryptedMnemonic = client
+        .request(
+            "crypto.export_encrypted_mnemonic",
+            ParamsOfExportEncryptedMnemonic {
+                phrase: phrase.into(),
+                password: base64::encode("Test Password"),
+                dictionary: None,
+                word_count: None,
+                kdf_params: kdf_params.clone(),
+            },
+        )
+        .unwrap();
+    let container: serde_json::Value = serde_json::from_str(&exported.container).unwrap();
+    assert_eq!(container["version"], 1);
+    assert_eq!(container["kdf"]["type"], "scrypt");
+    assert_eq!(container["kdf"]["log_n"], 10);
+    assert_eq!(container["cipher"]["type"], "chacha20-poly1305");
+    assert_eq!(container["word_count"], 12);
+    assert!(!exported.container.contains("abandon"));
+
+    let imported: ResultOfImportEncryptedMnemonic = client
+        .request(
+            "crypto.import_encrypted_mnemonic",
+            ParamsOfImportEncryptedMnemonic {
+                container: exported.container.clone(),
+                password: base64::encode("Test Password"),
+            },
+        )
+        .unwrap();
+    assert_eq!(imported.phrase, phrase);
+    assert_eq!(imported.dictionary, 1);
+    assert_eq!(imported.word_count, 12);
+
+    let error = client
+        .request::<_, ResultOfImportEncryptedMnemonic>(
+            "crypto.import_encrypted_mnemonic",
+            ParamsOfImportEncryptedMnemonic {
+                container: exported.container.clone(),
+                password: base64::encode("Wrong Password"),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::DecryptDataError as u32);
+
+    // Header fields are authenticated
+    let mut tampered = container.clone();
+    tampered["word_count"] = 24.into();
+    let error = client
+        .request::<_, ResultOfImportEncryptedMnemonic>(
+            "crypto.import_encrypted_mnemonic",
+            ParamsOfImportEncryptedMnemonic {
+                container: tampered.to_string(),
+                password: base64::encode("Test Password"),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::DecryptDataError as u32);
+
+    // Key derivation cost of the imported container is limited
+    let mut oversized = container.clone();
+    oversized["kdf"]["log_n"] = 30.into();
+    let error = client
+        .request::<_, ResultOfImportEncryptedMnemonic>(
+            "crypto.import_encrypted_mnemonic",
+            ParamsOfImportEncryptedMnemonic {
+                container: oversized.to_string(),
+                password: base64::encode("Test Password"),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidEncryptedMnemonic as u32);
+
+    let mut oversized = container.clone();
+    oversized["kdf"]["r"] = 65536.into();
+    let error = client
+        .request::<_, ResultOfImportEncryptedMnemonic>(
+            "crypto.import_encrypted_mnemonic",
+            ParamsOfImportEncryptedMnemonic {
+                container: oversized.to_string(),
+                password: base64::encode("Test Password"),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidEncryptedMnemonic as u32);
+
+    let mut unsupported = container;
+    unsupported["version"] = 2.into();
+    let error = client
+        .request::<_, ResultOfImportEncryptedMnemonic>(
+            "crypto.import_encrypted_mnemonic",
+            ParamsOfImportEncryptedMnemonic {
+                container: unsupported.to_string(),
+                password: base64::encode("Test Password"),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidEncryptedMnemonic as u32);
+
+    let error = client
+        .request::<_, ResultOfExportEncryptedMnemonic>(
+            "crypto.export_encrypted_mnemonic",
+            ParamsOfExportEncryptedMnemonic {
+                phrase: "abandon math mimic".into(),
+                password: base64::encode("Test Password"),
+                dictionary: None,
+                word_count: None,
+                kdf_params,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::Bip39InvalidPhrase as u32);
+}
+
 #[test]
 fn hdkey() {
     TestClient::init_log();
@@ -852,6 +1025,72 @@ async fn test_signing_box() {
     ).await.unwrap();
 }
 
+#[tokio::test(core_threads = 2)]
+async fn test_signing_box_limits() {
+    let client = TestClient::new();
+    let keys = client.generate_sign_keys();
+
+    let keys_box: RegisteredSigningBox = client
+        .request_async("crypto.get_signing_box", keys)
+        .await
+        .unwrap();
+    let limited_box: RegisteredSigningBox = client
+        .request_async(
+            "crypto.limit_signing_box",
+            ParamsOfLimitSigningBox {
+                signing_box: keys_box.handle.clone(),
+                limits: SigningBoxLimits {
+                    max_signatures: Some(3),
+                    rate_limit: Some(2),
+                    rate_period: Some(1000),
+                },
+            },
+        )
+        .await
+        .unwrap();
+
+    let sign = |signing_box: &RegisteredSigningBox| client.request_async::<_, ResultOfSigningBoxSign>(
+        "crypto.signing_box_sign",
+        ParamsOfSigningBoxSign {
+            signing_box: signing_box.handle.clone(),
+            unsigned: base64::encode("Test Message"),
+        },
+    );
+
+    sign(&limited_box).await.unwrap();
+    sign(&limited_box).await.unwrap();
+    let err = sign(&limited_box).await.unwrap_err();
+    assert_eq!(err.code, ErrorCode::SigningBoxLimitExceeded as u32);
+
+    tokio::time::delay_for(tokio::time::Duration::from_millis(1100)).await;
+    sign(&limited_box).await.unwrap();
+    tokio::time::delay_for(tokio::time::Duration::from_millis(1100)).await;
+    let err = sign(&limited_box).await.unwrap_err();
+    assert_eq!(err.code, ErrorCode::SigningBoxLimitExceeded as u32);
+
+    // Original box is not limited and has its own statistics
+    sign(&keys_box).await.unwrap();
+
+    let info: ResultOfSigningBoxInfo = client
+        .request_async("crypto.signing_box_info", limited_box.clone())
+        .await
+        .unwrap();
+    assert_eq!(info.signatures_count, 3);
+    assert!(info.last_used.is_some());
+    assert_eq!(info.callers, vec![SigningBoxCallerStats {
+        caller: "crypto.signing_box_sign".to_owned(),
+        signatures_count: 3,
+    }]);
+    assert_eq!(info.limits.unwrap().max_signatures, Some(3));
+
+    let info: ResultOfSigningBoxInfo = client
+        .request_async("crypto.signing_box_info", keys_box)
+        .await
+        .unwrap();
+    assert_eq!(info.signatures_count, 1);
+    assert_eq!(info.limits, None);
+}
+
 #[test]
 fn test_strip_secret() {
     assert_eq!(strip_secret(""), r#""""#);
@@ -910,6 +1149,7 @@ async fn test_aes_params(key: &str, data: &str, encrypted: &str) {
             ParamsOfEncryptionBoxEncrypt {
                 encryption_box: box_handle.clone(),
                 data: base64::encode(&data.clone()),
+                associated_data: None,
             },
         ).await.unwrap();
 
@@ -921,6 +1161,7 @@ async fn test_aes_params(key: &str, data: &str, encrypted: &str) {
             ParamsOfEncryptionBoxDecrypt {
                 encryption_box: box_handle.clone(),
                 data: encrypted,
+                associated_data: None,
             },
         ).await.unwrap();
 
@@ -952,3 +1193,327 @@ async fn test_aes_encryption_box() {
         "src/crypto/test_data/cbc-aes256.ciphertext.padded.bin"
     ).await;
 }
+
+#[tokio::test(core_threads = 2)]
+async fn test_encryption_box_associated_data() {
+    let client = std::sync::Arc::new(TestClient::new());
+
+    let box_handle = client
+        .request_async::<_, RegisteredEncryptionBox>(
+            "crypto.create_encryption_box",
+            ParamsOfCreateEncryptionBox {
+                algorithm: EncryptionAlgorithm::ChaCha20Poly1305(ChaCha20Poly1305Params {
+                    key: "01".repeat(32),
+                }),
+            },
+        )
+        .await
+        .unwrap()
+        .handle;
+
+    let data = base64::encode("Message");
+    let associated_data = base64::encode("0:1234");
+
+    let encrypted: ResultOfEncryptionBoxEncrypt = client
+        .request_async(
+            "crypto.encryption_box_encrypt",
+            ParamsOfEncryptionBoxEncrypt {
+                encryption_box: box_handle.clone(),
+                data: data.clone(),
+                associated_data: Some(associated_data.clone()),
+            },
+        ).await.unwrap();
+    // ciphertext is preceded by 12-byte nonce and followed by 16-byte tag
+    assert_eq!(base64::decode(&encrypted.data).unwrap().len(), 12 + "Message".len() + 16);
+
+    // each encryption uses a fresh nonce
+    let encrypted_again: ResultOfEncryptionBoxEncrypt = client
+        .request_async(
+            "crypto.encryption_box_encrypt",
+            ParamsOfEncryptionBoxEncrypt {
+                encryption_box: box_handle.clone(),
+                data: data.clone(),
+                associated_data: Some(associated_data.clone()),
+            },
+        ).await.unwrap();
+    assert_ne!(encrypted_again.data, encrypted.data);
+    let decrypted: ResultOfEncryptionBoxDecrypt = client
+        .request_async(
+            "crypto.encryption_box_decrypt",
+            ParamsOfEncryptionBoxDecrypt {
+                encryption_box: box_handle.clone(),
+                data: encrypted_again.data,
+                associated_data: Some(associated_data.clone()),
+            },
+        ).await.unwrap();
+    assert_eq!(decrypted.data, data);
+
+    let decrypted: ResultOfEncryptionBoxDecrypt = client
+        .request_async(
+            "crypto.encryption_box_decrypt",
+            ParamsOfEncryptionBoxDecrypt {
+                encryption_box: box_handle.clone(),
+                data: encrypted.data.clone(),
+                associated_data: Some(associated_data),
+            },
+        ).await.unwrap();
+    assert_eq!(decrypted.data, data);
+
+    let error = client
+        .request_async::<_, ResultOfEncryptionBoxDecrypt>(
+            "crypto.encryption_box_decrypt",
+            ParamsOfEncryptionBoxDecrypt {
+                encryption_box: box_handle.clone(),
+                data: encrypted.data.clone(),
+                associated_data: Some(base64::encode("0:5678")),
+            },
+        ).await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::DecryptDataError as u32);
+
+    let error = client
+        .request_async::<_, ResultOfEncryptionBoxDecrypt>(
+            "crypto.encryption_box_decrypt",
+            ParamsOfEncryptionBoxDecrypt {
+                encryption_box: box_handle,
+                data: encrypted.data,
+                associated_data: None,
+            },
+        ).await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::DecryptDataError as u32);
+
+    let aes_box = client
+        .request_async::<_, RegisteredEncryptionBox>(
+            "crypto.create_encryption_box",
+            ParamsOfCreateEncryptionBox {
+                algorithm: EncryptionAlgorithm::AES(AesParams {
+                    key: "01".repeat(16),
+                    iv: Some("02".repeat(16)),
+                    mode: CipherMode::CBC,
+                }),
+            },
+        )
+        .await
+        .unwrap()
+        .handle;
+    let error = client
+        .request_async::<_, ResultOfEncryptionBoxEncrypt>(
+            "crypto.encryption_box_encrypt",
+            ParamsOfEncryptionBoxEncrypt {
+                encryption_box: aes_box,
+                data,
+                associated_data: Some(base64::encode("0:1234")),
+            },
+        ).await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::AssociatedDataNotSupported as u32);
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_threshold_encryption_box() {
+    let client = std::sync::Arc::new(TestClient::new());
+    let trustees: Vec<KeyPair> = (0..3)
+        .map(|_| client.request_no_params("crypto.nacl_box_keypair").unwrap())
+        .collect();
+    let public_keys: Vec<String> = trustees.iter().map(|keys| keys.public.clone()).collect();
+
+    let create_box = |secrets: Option<Vec<String>>| {
+        let client = client.clone();
+        let trustees = public_keys.clone();
+        async move {
+            client
+                .request_async::<_, RegisteredEncryptionBox>(
+                    "crypto.create_encryption_box",
+                    ParamsOfCreateEncryptionBox {
+                        algorithm: EncryptionAlgorithm::Threshold(ThresholdParams {
+                            trustees,
+                            threshold: 2,
+                            secrets,
+                        }),
+                    },
+                )
+                .await
+                .map(|registered| registered.handle)
+        }
+    };
+
+    let data = base64::encode("Last will");
+    let encrypted: ResultOfEncryptionBoxEncrypt = client
+        .request_async(
+            "crypto.encryption_box_encrypt",
+            ParamsOfEncryptionBoxEncrypt {
+                encryption_box: create_box(None).await.unwrap(),
+                data: data.clone(),
+                associated_data: None,
+            },
+        ).await.unwrap();
+
+    // any two trustees decrypt the data
+    let decrypt_box = create_box(Some(vec![
+        trustees[2].secret.clone(),
+        trustees[0].secret.clone(),
+    ])).await.unwrap();
+    let decrypted: ResultOfEncryptionBoxDecrypt = client
+        .request_async(
+            "crypto.encryption_box_decrypt",
+            ParamsOfEncryptionBoxDecrypt {
+                encryption_box: decrypt_box,
+                data: encrypted.data.clone(),
+                associated_data: None,
+            },
+        ).await.unwrap();
+    assert_eq!(decrypted.data, data);
+
+    // a single trustee can't
+    let error = client
+        .request_async::<_, ResultOfEncryptionBoxDecrypt>(
+            "crypto.encryption_box_decrypt",
+            ParamsOfEncryptionBoxDecrypt {
+                encryption_box: create_box(Some(vec![trustees[1].secret.clone()])).await.unwrap(),
+                data: encrypted.data,
+                associated_data: None,
+            },
+        ).await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::NotEnoughTrusteeShares as u32);
+
+    let error = client
+        .request_async::<_, RegisteredEncryptionBox>(
+            "crypto.create_encryption_box",
+            ParamsOfCreateEncryptionBox {
+                algorithm: EncryptionAlgorithm::Threshold(ThresholdParams {
+                    trustees: public_keys.clone(),
+                    threshold: 4,
+                    secrets: None,
+                }),
+            },
+        ).await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidThresholdParams as u32);
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_webauthn_signing_box() {
+    use crate::crypto::boxes::webauthn::{assertion_challenge, verify_assertion, WebAuthnAssertion};
+    use crate::json_interface::crypto::{
+        ParamsOfAppWebAuthnSigningBox, ParamsOfRegisterWebAuthnSigningBox,
+        ResultOfAppWebAuthnSigningBox,
+    };
+    use sha2::{Digest, Sha256};
+    use std::sync::Arc;
+
+    let client = Arc::new(TestClient::new());
+    let keys = client.generate_sign_keys();
+    let key_pair = keys.decode().unwrap();
+
+    // authenticator emulation: assertions are scoped to `example.com`,
+    // user presence and user verification flags are set
+    let authenticator_data = [&Sha256::digest(b"example.com")[..], &[0x05, 0, 0, 0, 1]].concat();
+    let authenticator = Arc::new(move |challenge: &str| {
+        let client_data_json = json!({
+            "type": "webauthn.get",
+            "challenge": challenge,
+            "origin": "https://example.com",
+        })
+        .to_string();
+        let signed = [
+            &authenticator_data[..],
+            &Sha256::digest(client_data_json.as_bytes())[..],
+        ]
+        .concat();
+        WebAuthnAssertion {
+            authenticator_data: authenticator_data.clone(),
+            client_data_json: client_data_json.into_bytes(),
+            signature: crate::crypto::internal::sign_using_keys(&signed, &key_pair)
+                .unwrap()
+                .1,
+        }
+    });
+
+    let client_copy = client.clone();
+    let authenticator_copy = authenticator.clone();
+    let callback = move |request: crate::client::ParamsOfAppRequest, _: u32| {
+        let client = client_copy.clone();
+        let authenticator = authenticator_copy.clone();
+        tokio::spawn(async move {
+            match serde_json::from_value(request.request_data).unwrap() {
+                ParamsOfAppWebAuthnSigningBox::GetAssertion { challenge, .. } => {
+                    let assertion = authenticator(&challenge);
+                    client.resolve_app_request(
+                        request.app_request_id,
+                        ResultOfAppWebAuthnSigningBox::GetAssertion {
+                            authenticator_data: base64::encode(&assertion.authenticator_data),
+                            client_data_json: base64::encode(&assertion.client_data_json),
+                            signature: hex::encode(&assertion.signature),
+                        },
+                    ).await;
+                }
+            }
+        });
+        futures::future::ready(())
+    };
+    let register = |rp_id: &str| {
+        client.request_async_callback::<_, RegisteredSigningBox, _, _, _>(
+            "crypto.register_webauthn_signing_box",
+            ParamsOfRegisterWebAuthnSigningBox {
+                credential_id: "AQID".to_owned(),
+                public_key: keys.public.clone(),
+                rp_id: rp_id.to_owned(),
+                user_verification: Some(true),
+            },
+            callback.clone(),
+        )
+    };
+
+    let webauthn_box = register("example.com").await.unwrap();
+    let box_pubkey: ResultOfSigningBoxGetPublicKey = client
+        .request_async(
+            "crypto.signing_box_get_public_key",
+            RegisteredSigningBox { handle: webauthn_box.handle.clone() },
+        ).await.unwrap();
+    assert_eq!(box_pubkey.pubkey, keys.public);
+
+    let unsigned = b"Test Message";
+    let box_sign: ResultOfSigningBoxSign = client
+        .request_async(
+            "crypto.signing_box_sign",
+            ParamsOfSigningBoxSign {
+                signing_box: webauthn_box.handle.clone(),
+                unsigned: base64::encode(unsigned),
+            },
+        ).await.unwrap();
+    let assertion = authenticator(&assertion_challenge(unsigned));
+    assert_eq!(box_sign.signature, hex::encode(&assertion.signature));
+
+    // assertion signatures don't cover the message hash, so messages are not signed
+    let error = client
+        .request_async::<_, crate::abi::ResultOfEncodeMessageBody>(
+            "abi.encode_message_body",
+            crate::abi::ParamsOfEncodeMessageBody {
+                abi: TestClient::abi(crate::tests::HELLO, None),
+                call_set: crate::abi::CallSet::some_with_function("touch").unwrap(),
+                signer: crate::abi::Signer::SigningBox { handle: webauthn_box.handle.clone() },
+                ..Default::default()
+            },
+        ).await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::MessageSigningNotSupported as u32);
+
+    // assertions of the other relying party or for the other data are rejected
+    let other_box = register("other.com").await.unwrap();
+    let error = client
+        .request_async::<_, ResultOfSigningBoxSign>(
+            "crypto.signing_box_sign",
+            ParamsOfSigningBoxSign {
+                signing_box: other_box.handle,
+                unsigned: base64::encode(unsigned),
+            },
+        ).await.unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidWebAuthnAssertion as u32);
+
+    let public_key = crate::crypto::internal::key256(&hex::decode(&keys.public).unwrap()).unwrap();
+    let error = verify_assertion(&assertion, b"Other Message", &public_key, "example.com", true)
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidWebAuthnAssertion as u32);
+    let mut tampered = authenticator(&assertion_challenge(unsigned));
+    tampered.authenticator_data[36] += 1;
+    let error = verify_assertion(&tampered, unsigned, &public_key, "example.com", true)
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidWebAuthnAssertion as u32);
+    verify_assertion(&assertion, unsigned, &public_key, "example.com", true).unwrap();
+}
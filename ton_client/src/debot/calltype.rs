@@ -1,3 +1,4 @@
+use super::chain_backend::ChainBackend;
 use super::errors::Error;
 use super::helpers::build_internal_message;
 use super::{BrowserCallbacks, DebotActivity, Spending, TonClient};
@@ -7,17 +8,12 @@ use crate::boc::{get_boc_hash, parse_message, ParamsOfParse, ParamsOfGetBocHash}
 use crate::crypto::{KeyPair, SigningBoxHandle, get_signing_box};
 use crate::encoding::decode_abi_number;
 use crate::error::{ClientError, ClientResult};
-use crate::processing::{
-    send_message, wait_for_transaction, ParamsOfSendMessage, ParamsOfWaitForTransaction,
-    ProcessingEvent,
-};
 use crate::tvm::{run_executor, run_tvm, AccountForExecutor, ParamsOfRunExecutor, ParamsOfRunTvm};
 use std::convert::TryFrom;
 use std::fmt::Display;
 use std::sync::Arc;
 use ton_block::{Message, MsgAddressExt, MsgAddressInt};
 use ton_types::{BuilderData, IBitstring, SliceData};
-use crate::net::{query_transaction_tree, ParamsOfQueryTransactionTree};
 
 const SUPPORTED_ABI_VERSION: u8 = 2;
 
@@ -211,6 +207,7 @@ async fn decode_and_fix_ext_msg(
 pub(crate) struct ContractCall {
     browser: Arc<dyn BrowserCallbacks + Send + Sync>,
     ton: TonClient,
+    backend: Arc<dyn ChainBackend>,
     msg: Message,
     signer: Signer,
     target_state: String,
@@ -224,6 +221,7 @@ impl ContractCall {
     pub async fn new(
         browser: Arc<dyn BrowserCallbacks + Send + Sync>,
         ton: TonClient,
+        backend: Arc<dyn ChainBackend>,
         msg: String,
         signer: Signer,
         target_state: String,
@@ -245,7 +243,7 @@ impl ContractCall {
             .get_dst_address()
             .map(|x| x.to_string())
             .unwrap_or_default();
-        Ok(Self { browser, ton, msg, signer, target_state, debot_addr, dest_addr, local_run, meta })
+        Ok(Self { browser, ton, backend, msg, signer, target_state, debot_addr, dest_addr, local_run, meta })
     }
 
     pub async fn execute(&self, wait_tx: bool) -> ClientResult<String> {
@@ -276,6 +274,7 @@ impl ContractCall {
                 execution_options: None,
                 boc_cache: None,
                 return_updated_account: Some(true),
+                address: None,
             },
         )
         .await
@@ -317,62 +316,16 @@ impl ContractCall {
             },
         }
 
-        let browser = self.browser.clone();
-        let callback = move |event| {
-            debug!("{:?}", event);
-            let browser = browser.clone();
-            async move {
-                match event {
-                    ProcessingEvent::WillSend {
-                        shard_block_id: _,
-                        message_id: _,
-                        message: _,
-                    } => {
-                        browser.log("Sending message...".to_owned()).await;
-                    }
-                    _ => (),
-                };
-            }
-        };
-
-        let result = send_message(
-            self.ton.clone(),
-            ParamsOfSendMessage {
-                message: fixed_msg.clone(),
-                abi: None,
-                send_events: true,
-            },
-            callback.clone(),
-        )
-        .await
-        .map(|e| { error!("{:?}", e); e })?;
-        let msg_id = get_boc_hash(self.ton.clone(), ParamsOfGetBocHash { boc: fixed_msg.clone() }).await?.hash;
+        self.browser.log("Sending message...".to_owned()).await;
+        let result = self.backend
+            .send_message(fixed_msg.clone())
+            .await
+            .map_err(|e| { error!("{:?}", e); e })?;
 
         if wait_tx {
-            let result = wait_for_transaction(
-                self.ton.clone(),
-                ParamsOfWaitForTransaction {
-                    abi: None,
-                    message: fixed_msg,
-                    shard_block_id: result.shard_block_id,
-                    send_events: true,
-                    sending_endpoints: Some(result.sending_endpoints),
-                },
-                callback,
-            )
-            .await;
+            let result = self.backend.wait_for_transaction(fixed_msg, result).await;
             match result {
                 Ok(res) => {
-                    let result = query_transaction_tree(
-                        self.ton.clone(),
-                        ParamsOfQueryTransactionTree {
-                            in_msg: msg_id,
-                            ..Default::default()
-                        },
-                    ).await;
-                    if let Err(e) = result {
-                        return self.build_error_answer_msg(e);
-                    }
                     for out_msg in &res.out_messages {
                         let res = build_answer_msg(
                             out_msg,
@@ -398,6 +351,7 @@ impl ContractCall {
                 }
             }
         } else {
+            let msg_id = get_boc_hash(self.ton.clone(), ParamsOfGetBocHash { boc: fixed_msg }).await?.hash;
             let msg_id = hex::decode(msg_id).map_err(msg_err)?;
             let mut new_body = BuilderData::new();
             new_body
@@ -0,0 +1,281 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::errors::Error;
+use super::TonClient;
+use crate::abi::{encode_message, Abi};
+use crate::boc::{get_boc_hash, ParamsOfGetBocHash};
+use crate::error::ClientResult;
+use crate::net::{
+    query_collection, query_transaction_tree, ParamsOfQueryCollection,
+    ParamsOfQueryTransactionTree,
+};
+use crate::processing::{
+    process_message, send_message, wait_for_transaction, ParamsOfProcessMessage,
+    ParamsOfSendMessage, ParamsOfWaitForTransaction, ProcessingEvent, ResultOfProcessMessage,
+    ResultOfSendMessage,
+};
+use crate::tvm::{run_executor, AccountForExecutor, ParamsOfRunExecutor};
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Callback receiving the processing events of `ChainBackend::process_message`.
+pub type ProcessingCallback =
+    Arc<dyn Fn(ProcessingEvent) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// Blockchain access used by debot engine to read account states and
+/// deliver messages.
+///
+/// Default implementation (`NetworkChainBackend`) works with the network
+/// the client is connected to. `LocalChainBackend` executes messages
+/// in-process against a set of local account states, so debots can be
+/// developed and tested without a running node.
+#[async_trait::async_trait]
+pub trait ChainBackend: Send + Sync {
+    /// Returns account BOC encoded as base64 or `None` if account doesn't exist.
+    async fn get_account(&self, address: &str) -> ClientResult<Option<String>>;
+
+    /// Encodes external inbound message, delivers it and waits for the
+    /// result transaction. Processing events are passed to `callback`
+    /// if `params.send_events` is set.
+    async fn process_message(
+        &self,
+        params: ParamsOfProcessMessage,
+        callback: ProcessingCallback,
+    ) -> ClientResult<ResultOfProcessMessage>;
+
+    /// Delivers prepared external inbound message.
+    async fn send_message(&self, message: String) -> ClientResult<ResultOfSendMessage>;
+
+    /// Waits for the transaction of the message delivered by `send_message`
+    /// and for the whole transaction tree it produces.
+    async fn wait_for_transaction(
+        &self,
+        message: String,
+        sent: ResultOfSendMessage,
+    ) -> ClientResult<ResultOfProcessMessage>;
+}
+
+/// Debot chain backend working with the network.
+pub struct NetworkChainBackend {
+    ton: TonClient,
+}
+
+impl NetworkChainBackend {
+    pub fn new(ton: TonClient) -> Self {
+        Self { ton }
+    }
+}
+
+fn skip_event(_: ProcessingEvent) -> futures::future::Ready<()> {
+    futures::future::ready(())
+}
+
+#[async_trait::async_trait]
+impl ChainBackend for NetworkChainBackend {
+    async fn get_account(&self, address: &str) -> ClientResult<Option<String>> {
+        let mut accounts = query_collection(
+            self.ton.clone(),
+            ParamsOfQueryCollection {
+                collection: "accounts".to_owned(),
+                filter: Some(serde_json::json!({
+                    "id": { "eq": address }
+                })),
+                result: "boc".to_owned(),
+                limit: Some(1),
+                order: None,
+                min_block: None,
+                decode_body: None,
+            },
+        )
+        .await?
+        .result;
+        if accounts.is_empty() {
+            return Ok(None);
+        }
+        Ok(accounts[0]["boc"].take().as_str().map(|boc| boc.to_owned()))
+    }
+
+    async fn process_message(
+        &self,
+        params: ParamsOfProcessMessage,
+        callback: ProcessingCallback,
+    ) -> ClientResult<ResultOfProcessMessage> {
+        process_message(self.ton.clone(), params, move |event| callback(event)).await
+    }
+
+    async fn send_message(&self, message: String) -> ClientResult<ResultOfSendMessage> {
+        send_message(
+            self.ton.clone(),
+            ParamsOfSendMessage {
+                message,
+                abi: None,
+                send_events: false,
+                broadcast_fanout: None,
+                validate_locally: None,
+                ..Default::default()
+            },
+            skip_event,
+        )
+        .await
+    }
+
+    async fn wait_for_transaction(
+        &self,
+        message: String,
+        sent: ResultOfSendMessage,
+    ) -> ClientResult<ResultOfProcessMessage> {
+        let in_msg = get_boc_hash(
+            self.ton.clone(),
+            ParamsOfGetBocHash { boc: message.clone() },
+        )
+        .await?
+        .hash;
+        let result = wait_for_transaction(
+            self.ton.clone(),
+            ParamsOfWaitForTransaction {
+                abi: None,
+                message,
+                shard_block_id: sent.shard_block_id,
+                send_events: false,
+                sending_endpoints: Some(sent.sending_endpoints),
+                finality: None,
+                ..Default::default()
+            },
+            skip_event,
+        )
+        .await?;
+        query_transaction_tree(
+            self.ton.clone(),
+            ParamsOfQueryTransactionTree {
+                in_msg,
+                ..Default::default()
+            },
+        )
+        .await?;
+        Ok(result)
+    }
+}
+
+/// Debot chain backend executing messages in-process.
+///
+/// Holds account states in memory. External inbound messages are executed
+/// with `tvm.run_executor` and the resulting account states replace the
+/// stored ones. Output messages are not delivered further.
+pub struct LocalChainBackend {
+    ton: TonClient,
+    accounts: Mutex<HashMap<String, String>>,
+    pending: Mutex<HashMap<String, ClientResult<ResultOfProcessMessage>>>,
+}
+
+impl LocalChainBackend {
+    pub fn new(ton: TonClient) -> Self {
+        Self {
+            ton,
+            accounts: Default::default(),
+            pending: Default::default(),
+        }
+    }
+
+    /// Puts account state (BOC encoded as base64) to the backend.
+    pub async fn set_account(&self, address: String, boc: String) {
+        self.accounts.lock().await.insert(address, boc);
+    }
+
+    async fn execute(
+        &self,
+        message: String,
+        abi: Option<Abi>,
+    ) -> ClientResult<ResultOfProcessMessage> {
+        let parsed = crate::boc::parse_message(
+            self.ton.clone(),
+            crate::boc::ParamsOfParse { boc: message.clone() },
+        )
+        .await?
+        .parsed;
+        let address = parsed["dst"]
+            .as_str()
+            .ok_or_else(|| Error::invalid_msg("message has no destination address"))?
+            .to_owned();
+        let account = self
+            .get_account(&address)
+            .await?
+            .ok_or_else(|| Error::account_not_found(&address))?;
+        let result = run_executor(
+            self.ton.clone(),
+            ParamsOfRunExecutor {
+                message,
+                account: AccountForExecutor::Account {
+                    boc: account,
+                    unlimited_balance: None,
+                },
+                abi,
+                return_updated_account: Some(true),
+                ..Default::default()
+            },
+        )
+        .await?;
+        self.set_account(address, result.account).await;
+        Ok(ResultOfProcessMessage {
+            transaction: result.transaction,
+            out_messages: result.out_messages,
+            decoded: result.decoded,
+            fees: result.fees,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl ChainBackend for LocalChainBackend {
+    async fn get_account(&self, address: &str) -> ClientResult<Option<String>> {
+        Ok(self.accounts.lock().await.get(address).cloned())
+    }
+
+    async fn process_message(
+        &self,
+        params: ParamsOfProcessMessage,
+        callback: ProcessingCallback,
+    ) -> ClientResult<ResultOfProcessMessage> {
+        let abi = params.message_encode_params.abi.clone();
+        let encoded = encode_message(self.ton.clone(), params.message_encode_params).await?;
+        if params.send_events {
+            callback(ProcessingEvent::WillSend {
+                shard_block_id: String::new(),
+                message_id: encoded.message_id,
+                message: encoded.message.clone(),
+            })
+            .await;
+        }
+        self.execute(encoded.message, Some(abi)).await
+    }
+
+    async fn send_message(&self, message: String) -> ClientResult<ResultOfSendMessage> {
+        let result = self.execute(message.clone(), None).await;
+        self.pending.lock().await.insert(message, result);
+        Ok(ResultOfSendMessage::default())
+    }
+
+    async fn wait_for_transaction(
+        &self,
+        message: String,
+        _sent: ResultOfSendMessage,
+    ) -> ClientResult<ResultOfProcessMessage> {
+        self.pending
+            .lock()
+            .await
+            .remove(&message)
+            .unwrap_or_else(|| Err(Error::invalid_msg("message was not sent")))
+    }
+}
@@ -6,8 +6,8 @@ use crate::boc::internal::{deserialize_cell_from_base64};
 use crate::crypto::{remove_signing_box, RegisteredSigningBox, SigningBoxHandle};
 use crate::encoding::{decode_abi_number};
 use crate::error::{ClientError, ClientResult};
-use crate::net::{query_collection, NetworkConfig, ParamsOfQueryCollection};
-use crate::processing::{process_message, ParamsOfProcessMessage, ProcessingEvent};
+use crate::net::NetworkConfig;
+use crate::processing::{ParamsOfProcessMessage, ProcessingEvent};
 use crate::tvm::{run_tvm, ParamsOfRunTvm};
 use crate::{ClientConfig, ClientContext};
 use std::collections::VecDeque;
@@ -18,6 +18,7 @@ use super::context::{
     str_hex_to_utf8, DContext, STATE_CURRENT, STATE_EXIT, STATE_PREV, STATE_ZERO,
 };
 use super::calltype::{ContractCall, DebotCallType};
+use super::chain_backend::{ChainBackend, NetworkChainBackend, ProcessingCallback};
 use super::dinterface::{BuiltinInterfaces, DebotInterfaceExecutor};
 use super::json_interface::JsonInterface;
 use super::{JsonValue, TonClient, DInfo, info::{fetch_target_abi_version, parse_debot_info}};
@@ -25,6 +26,7 @@ use super::{errors::Error, routines, DEBOT_WC, debot_abi::DEBOT_ABI};
 use super::helpers::build_internal_message;
 use super::msg_interface::MsgInterface;
 use super::run_output::RunOutput;
+use futures::FutureExt;
 use ton_abi::Contract;
 
 const EMPTY_CELL: &'static str = "te6ccgEBAQEAAgAAAA==";
@@ -65,6 +67,7 @@ pub struct DEngine {
     abi: Abi,
     addr: String,
     ton: TonClient,
+    backend: Arc<dyn ChainBackend>,
     state: String,
     state_machine: Vec<DContext>,
     curr_state: u8,
@@ -91,6 +94,19 @@ impl DEngine {
         abi: Option<String>,
         ton: TonClient,
         browser: Arc<dyn BrowserCallbacks + Send + Sync>,
+    ) -> Self {
+        let backend = Arc::new(NetworkChainBackend::new(ton.clone()));
+        DEngine::new_with_backend(addr, abi, ton, backend, browser)
+    }
+
+    /// Creates debot engine which reads account states and delivers messages
+    /// through the supplied chain backend instead of the network.
+    pub fn new_with_backend(
+        addr: String,
+        abi: Option<String>,
+        ton: TonClient,
+        backend: Arc<dyn ChainBackend>,
+        browser: Arc<dyn BrowserCallbacks + Send + Sync>,
     ) -> Self {
         let abi = abi
             .map(|s| load_abi(&s))
@@ -101,6 +117,7 @@ impl DEngine {
             abi,
             addr,
             ton: ton.clone(),
+            backend,
             state: String::new(),
             state_machine: vec![],
             curr_state: STATE_EXIT,
@@ -114,7 +131,8 @@ impl DEngine {
     }
 
     pub async fn fetch(ton: TonClient, addr: String) -> Result<DInfo, String> {
-        let state = Self::load_state(ton.clone(), addr.clone()).await?;
+        let backend = NetworkChainBackend::new(ton.clone());
+        let state = Self::load_state(&backend, addr.clone()).await?;
         Self::fetch_info(ton, addr, state).await
     }
 
@@ -191,7 +209,7 @@ impl DEngine {
     }
 
     async fn fetch_state(&mut self) -> Result<(), String> {
-        self.state = Self::load_state(self.ton.clone(), self.addr.clone()).await?;
+        self.state = Self::load_state(self.backend.as_ref(), self.addr.clone()).await?;
         self.info = Self::fetch_info(self.ton.clone(), self.addr.clone(), self.state.clone()).await?;
         if let Some(dabi) = self.info.dabi.as_ref() {
             self.raw_abi = dabi.clone();
@@ -199,6 +217,7 @@ impl DEngine {
             self.builtin_interfaces.add(
                 Arc::new(MsgInterface::new(
                     self.ton.clone(),
+                    self.backend.clone(),
                     self.addr.clone(),
                     self.abi.clone(),
                     self.browser.clone(),
@@ -583,6 +602,7 @@ impl DEngine {
                 abi: abi.clone(),
                 body: body.to_string(),
                 is_internal: true,
+                partial: None,
             },
         )
         .await
@@ -605,7 +625,7 @@ impl DEngine {
             return Err(format!("target address is undefined"));
         }
         let (addr, abi) = self.get_target()?;
-        let state = Self::load_state(self.ton.clone(), addr.clone()).await?;
+        let state = Self::load_state(self.backend.as_ref(), addr.clone()).await?;
         let result = Self::run(self.ton.clone(), state, addr, abi, getmethod, args).await;
         let result = match result {
             Ok(r) => Ok(r.return_value),
@@ -615,29 +635,18 @@ impl DEngine {
         Ok(result.return_value)
     }
 
-    pub(crate) async fn load_state(ton: TonClient, addr: String) -> Result<String, String> {
-        let account_request = query_collection(
-            ton,
-            ParamsOfQueryCollection {
-                collection: "accounts".to_owned(),
-                filter: Some(serde_json::json!({
-                    "id": { "eq": addr }
-                })),
-                result: "boc".to_owned(),
-                limit: Some(1),
-                order: None,
-            },
-        )
-        .await;
-        let acc = account_request.map_err(|e| format!("failed to query account: {}", e))?;
-        if acc.result.is_empty() {
-            return Err(format!(
-                "Cannot find smart contract with this address {} in blockchain",
-                addr
-            ));
-        }
-        let state = acc.result[0]["boc"].as_str().unwrap().to_owned();
-        Ok(state)
+    pub(crate) async fn load_state(
+        backend: &dyn ChainBackend,
+        addr: String,
+    ) -> Result<String, String> {
+        let state = backend
+            .get_account(&addr)
+            .await
+            .map_err(|e| format!("failed to query account: {}", e))?;
+        state.ok_or_else(|| format!(
+            "Cannot find smart contract with this address {} in blockchain",
+            addr
+        ))
     }
 
     async fn update_options(&mut self) -> Result<(), String> {
@@ -720,6 +729,7 @@ impl DEngine {
             },
             signer: Signer::None,
             processing_try_index: None,
+            expiration: None,
         };
 
         let result = encode_message(ton.clone(), msg_params).await?;
@@ -773,10 +783,11 @@ impl DEngine {
                 None => Signer::None,
             },
             processing_try_index: None,
+            expiration: None,
         };
 
         let browser = self.browser.clone();
-        let callback = move |event| {
+        let callback: ProcessingCallback = Arc::new(move |event| {
             debug!("{:?}", event);
             let browser = browser.clone();
             async move {
@@ -786,14 +797,17 @@ impl DEngine {
                     },
                     _ => (),
                 };
-            }
-        };
+            }.boxed()
+        });
 
-        match process_message(
-            self.ton.clone(),
+        match self.backend.process_message(
             ParamsOfProcessMessage {
                 message_encode_params: call_params,
                 send_events: true,
+                sequence: None,
+                decode_tree: None,
+                retry_policy: None,
+                ..Default::default()
             },
             callback,
         )
@@ -836,11 +850,12 @@ impl DEngine {
                 },
                 DebotCallType::GetMethod{msg, dest} => {
                     debug!("GetMethod call");
-                    let target_state = Self::load_state(self.ton.clone(), dest.clone()).await
+                    let target_state = Self::load_state(self.backend.as_ref(), dest.clone()).await
                         .map_err(|e| Error::execute_failed(e))?;
                     let callobj = ContractCall::new(
                         self.browser.clone(),
                         self.ton.clone(),
+                        self.backend.clone(),
                         msg,
                         Signer::None,
                         target_state,
@@ -852,11 +867,12 @@ impl DEngine {
                 },
                 DebotCallType::External{msg, dest} => {
                     debug!("External call");
-                    let target_state = Self::load_state(self.ton.clone(), dest.clone()).await
+                    let target_state = Self::load_state(self.backend.as_ref(), dest.clone()).await
                         .map_err(|e| Error::execute_failed(e))?;
                     let callobj = ContractCall::new(
                         self.browser.clone(),
                         self.ton.clone(),
+                        self.backend.clone(),
                         msg,
                         Signer::None,
                         target_state,
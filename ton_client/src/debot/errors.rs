@@ -29,6 +29,7 @@ pub enum ErrorCode {
     DebotBrowserCallbackFailed = 811,
     DebotOperationRejected = 812,
     DebotNoCode = 813,
+    DebotAccountNotFound = 814,
 }
 pub struct Error;
 
@@ -127,4 +128,11 @@ impl Error {
             format!("Debot has no code"),
         )
     }
+
+    pub fn account_not_found(address: impl Display) -> ClientError {
+        error(
+            ErrorCode::DebotAccountNotFound,
+            format!("Account {} not found", address),
+        )
+    }
 }
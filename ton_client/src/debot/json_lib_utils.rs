@@ -191,6 +191,15 @@ pub(crate) fn bypass_json(
                 bypass_json(&pointer, obj, p, string_or_bytes.clone())?;
             }
         }
+        ParamType::Ref(ref inner) => {
+            // Referenced value has the same JSON representation as the inner type
+            bypass_json(
+                top_pointer,
+                obj,
+                Param::new(&p.name, (**inner).clone()),
+                string_or_bytes,
+            )?;
+        }
         ParamType::Array(ref elem_type) => {
             let elem_count = obj
                 .pointer(&pointer)
@@ -19,6 +19,7 @@ mod json_interface;
 mod json_lib_utils;
 mod browser;
 pub mod calltype;
+mod chain_backend;
 mod context;
 mod debot_abi;
 mod dengine;
@@ -43,6 +44,9 @@ pub use calltype::prepare_ext_in_message;
 pub use action::DAction;
 pub use activity::{DebotActivity, Spending};
 pub use browser::BrowserCallbacks;
+pub use chain_backend::{
+    ChainBackend, LocalChainBackend, NetworkChainBackend, ProcessingCallback,
+};
 pub use context::{DContext, STATE_EXIT, STATE_ZERO};
 pub use dengine::DEngine;
 pub use dinterface::{DebotInterface, DebotInterfaceExecutor, InterfaceResult};
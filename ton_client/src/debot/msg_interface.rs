@@ -1,4 +1,5 @@
 use super::calltype::{ContractCall};
+use super::chain_backend::ChainBackend;
 use super::dinterface::{get_arg, DebotInterface, InterfaceResult};
 use crate::abi::{decode_message, Abi, ParamsOfDecodeMessage};
 use crate::crypto::{get_signing_box, KeyPair};
@@ -48,6 +49,7 @@ const ID: &str = "475a5d1729acee4601c2a8cb67240e4da5316cc90a116e1b181d905e79401c
 
 pub struct MsgInterface {
     ton: TonClient,
+    backend: Arc<dyn ChainBackend>,
     debot_addr: String,
     debot_abi: Abi,
     browser: Arc<dyn BrowserCallbacks + Send + Sync>,
@@ -56,12 +58,14 @@ pub struct MsgInterface {
 impl MsgInterface {
     pub fn new(
         ton: TonClient,
+        backend: Arc<dyn ChainBackend>,
         debot_addr: String,
         debot_abi: Abi,
         browser: Arc<dyn BrowserCallbacks + Send + Sync>,
     ) -> Self {
         Self {
             ton,
+            backend,
             debot_addr,
             debot_abi,
             browser,
@@ -84,12 +88,13 @@ impl MsgInterface {
             .map_err(|e| format!("{}", e))?
             .parsed;
         let dest = parsed_msg["dst"].as_str().ok_or(format!("failed to parse dst address"))?.to_owned();
-        let target_state = DEngine::load_state(self.ton.clone(), dest)
+        let target_state = DEngine::load_state(self.backend.as_ref(), dest)
             .await
             .map_err(|e| format!("{}", e))?;
         let callobj = ContractCall::new(
             self.browser.clone(),
             self.ton.clone(),
+            self.backend.clone(),
             message,
             Signer::SigningBox{handle: signing_box},
             target_state,
@@ -105,6 +110,7 @@ impl MsgInterface {
             ParamsOfDecodeMessage {
                 abi: self.debot_abi.clone(),
                 message: answer_msg,
+                partial: None,
             },
         )
         .await
@@ -125,12 +131,13 @@ impl MsgInterface {
             .map_err(|e| format!("{}", e))?
             .parsed;
         let dest = parsed_msg["dst"].as_str().ok_or(format!("failed to parse dst address"))?.to_owned();
-        let target_state = DEngine::load_state(self.ton.clone(), dest)
+        let target_state = DEngine::load_state(self.backend.as_ref(), dest)
             .await
             .map_err(|e| format!("{}", e))?;
         let callobj = ContractCall::new(
             self.browser.clone(),
             self.ton.clone(),
+            self.backend.clone(),
             message,
             Signer::None,
             target_state,
@@ -146,6 +153,7 @@ impl MsgInterface {
             ParamsOfDecodeMessage {
                 abi: self.debot_abi.clone(),
                 message: answer_msg,
+                partial: None,
             },
         )
         .await
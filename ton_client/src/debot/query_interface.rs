@@ -148,6 +148,8 @@ impl QueryInterface {
                 result,
                 order: Some(vec![order_by]),
                 limit: Some(limit),
+                min_block: None,
+                decode_body: None,
             },
         )
         .await
@@ -249,6 +251,7 @@ impl QueryInterface {
             ParamsOfQuery {
                 query: query_str,
                 variables,
+                allow_partial_result: None,
             },
         )
         .await
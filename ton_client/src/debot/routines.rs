@@ -295,6 +295,8 @@ pub(super) async fn get_account(
             result: "boc".to_owned(),
             order: None,
             limit: Some(1),
+            min_block: None,
+            decode_body: None,
         },
     )
     .await
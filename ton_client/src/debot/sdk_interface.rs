@@ -683,6 +683,7 @@ impl SdkInterface {
                 ParamsOfEncryptionBoxEncrypt {
                     encryption_box,
                     data,
+                    associated_data: None,
                 },
             )
             .await
@@ -694,6 +695,7 @@ impl SdkInterface {
                 ParamsOfEncryptionBoxDecrypt {
                     encryption_box,
                     data,
+                    associated_data: None,
                 },
             )
             .await
@@ -740,6 +742,8 @@ impl SdkInterface {
                     direction: SortDirection::ASC,
                 }]),
                 limit: None,
+                min_block: None,
+                decode_body: None,
             },
         )
         .await
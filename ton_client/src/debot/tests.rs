@@ -384,7 +384,7 @@ impl TestBrowser {
                 };
                 let decoded: DecodedMessageBody = client.request_async(
                     "abi.decode_message_body",
-                    ParamsOfDecodeMessageBody { abi, body, is_internal: true },
+                    ParamsOfDecodeMessageBody { abi, body, is_internal: true, partial: None },
                 ).await.unwrap();
                 let (func, args) = (decoded.name, decoded.value.unwrap());
                 log::info!("request: {} ({})", func, args);
@@ -483,6 +483,7 @@ async fn init_debot(client: Arc<TestClient>) -> DebotData {
         }),
         signer: Signer::Keys { keys: keys.clone() },
         processing_try_index: None,
+        expiration: None,
         address: None,
         call_set: CallSet::some_with_function("constructor"),
     };
@@ -499,6 +500,7 @@ async fn init_debot(client: Arc<TestClient>) -> DebotData {
             }),
             signer: Signer::Keys { keys: keys.clone() },
             processing_try_index: None,
+            expiration: None,
             address: None,
             call_set: CallSet::some_with_function("constructor"),
         },
@@ -513,6 +515,7 @@ async fn init_debot(client: Arc<TestClient>) -> DebotData {
             }),
             signer: Signer::Keys { keys: keys.clone() },
             processing_try_index: None,
+            expiration: None,
             address: None,
             call_set: Some(CallSet {
                 function_name: "constructor".to_owned(),
@@ -564,6 +567,7 @@ async fn init_debot2(client: Arc<TestClient>) -> DebotData {
         deploy_set: DeploySet::some_with_tvc(TestClient::tvc(TEST_DEBOT2, Some(2))),
         signer: Signer::Keys { keys: keys.clone() },
         processing_try_index: None,
+        expiration: None,
         address: None,
         call_set,
     };
@@ -588,6 +592,7 @@ async fn init_debot4(client: Arc<TestClient>) -> DebotData {
         deploy_set: DeploySet::some_with_tvc(TestClient::tvc(TEST_DEBOT_TARGET, Some(2))),
         signer: Signer::Keys { keys: keys.clone() },
         processing_try_index: None,
+        expiration: None,
         address: None,
         call_set: CallSet::some_with_function("constructor"),
     };
@@ -605,6 +610,7 @@ async fn init_debot4(client: Arc<TestClient>) -> DebotData {
         deploy_set: DeploySet::some_with_tvc(TestClient::tvc(TEST_DEBOT4, Some(2))),
         signer: Signer::Keys { keys: keys.clone() },
         processing_try_index: None,
+        expiration: None,
         address: None,
         call_set,
     };
@@ -651,6 +657,7 @@ async fn init_simple_debot(client: Arc<TestClient>, name: &str) -> DebotData {
         deploy_set: DeploySet::some_with_tvc(TestClient::tvc(name, Some(2))),
         signer: Signer::Keys { keys: keys.clone() },
         processing_try_index: None,
+        expiration: None,
         address: None,
         call_set,
     };
@@ -716,6 +723,7 @@ async fn init_debot_pair(client: Arc<TestClient>, debot1: &str, debot2: &str) ->
         }),
         signer: Signer::Keys { keys: keys.clone() },
         processing_try_index: None,
+        expiration: None,
         address: None,
         call_set: CallSet::some_with_function("constructor"),
     };
@@ -730,6 +738,7 @@ async fn init_debot_pair(client: Arc<TestClient>, debot1: &str, debot2: &str) ->
         deploy_set: DeploySet::some_with_tvc(TestClient::tvc(debot1, Some(2))),
         signer: Signer::Keys { keys: keys.clone() },
         processing_try_index: None,
+        expiration: None,
         address: None,
         call_set,
     };
@@ -782,6 +791,8 @@ async fn count_accounts_by_codehash(client: Arc<TestClient>, code_hash: String)
                 result: "id".to_owned(),
                 limit: None,
                 order: None,
+                min_block: None,
+                decode_body: None,
             },
         )
         .await
@@ -1506,6 +1517,8 @@ async fn download_account(client: &Arc<TestClient>, addr: &str) -> Option<String
             result: format!("boc"),
             limit: Some(1),
             order: None,
+            min_block: None,
+            decode_body: None,
         }
     ).await.unwrap();
 
@@ -1531,6 +1544,7 @@ async fn assert_get_method(
         deploy_set: None,
         signer: Signer::None,
         processing_try_index: None,
+        expiration: None,
         address: Some(addr.clone()),
         call_set: CallSet::some_with_function_and_input(func, params),
     };
@@ -1546,9 +1560,120 @@ async fn assert_get_method(
             execution_options: None,
             boc_cache: None,
             return_updated_account: Some(true),
+            address: None,
         },
     ).await.unwrap();
 
     let output = result.decoded.unwrap().output.expect("output must exist");
     assert_eq!(returns, output);
 }
+
+#[tokio::test(core_threads = 2)]
+async fn test_debot_local_chain_backend() {
+    let client = TestClient::new();
+    let backend = LocalChainBackend::new(client.context());
+    let address = format!("0:{}", "1".repeat(64));
+
+    assert_eq!(backend.get_account(&address).await.unwrap(), None);
+    let state = DEngine::load_state(&backend, address.clone()).await;
+    assert!(state.is_err());
+
+    let account = base64::encode(include_bytes!("../boc/test_data/account.boc"));
+    backend.set_account(address.clone(), account.clone()).await;
+    assert_eq!(backend.get_account(&address).await.unwrap(), Some(account.clone()));
+    assert_eq!(DEngine::load_state(&backend, address).await.unwrap(), account);
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_debot_local_chain_backend_execute() {
+    use crate::abi::ResultOfEncodeMessage;
+    use crate::processing::{ParamsOfProcessMessage, ProcessingEvent, ResultOfSendMessage};
+    use crate::tvm::{AccountForExecutor, ParamsOfRunExecutor, ResultOfRunExecutor};
+
+    let client = TestClient::new();
+    let backend = LocalChainBackend::new(client.context());
+    let keys = client.generate_sign_keys();
+    let (abi, tvc) = TestClient::package(crate::tests::HELLO, None);
+
+    let deploy: ResultOfEncodeMessage = client
+        .request_async(
+            "abi.encode_message",
+            ParamsOfEncodeMessage {
+                abi: abi.clone(),
+                deploy_set: Some(DeploySet { tvc, ..Default::default() }),
+                call_set: CallSet::some_with_function("constructor"),
+                signer: Signer::Keys { keys: keys.clone() },
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let deployed: ResultOfRunExecutor = client
+        .request_async(
+            "tvm.run_executor",
+            ParamsOfRunExecutor {
+                message: deploy.message,
+                account: AccountForExecutor::Uninit,
+                return_updated_account: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    backend.set_account(deploy.address.clone(), deployed.account.clone()).await;
+
+    let touch = |address: String| ParamsOfEncodeMessage {
+        abi: abi.clone(),
+        address: Some(address),
+        call_set: CallSet::some_with_function("touch"),
+        signer: Signer::Keys { keys: keys.clone() },
+        ..Default::default()
+    };
+
+    // message is executed against the stored state which is replaced with the updated one
+    let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let events_copy = events.clone();
+    let callback: ProcessingCallback = Arc::new(move |event| {
+        events_copy.lock().unwrap().push(event);
+        futures::future::ready(()).boxed()
+    });
+    let result = backend
+        .process_message(
+            ParamsOfProcessMessage {
+                message_encode_params: touch(deploy.address.clone()),
+                send_events: true,
+                ..Default::default()
+            },
+            callback,
+        )
+        .await
+        .unwrap();
+    let events = events.lock().unwrap().clone();
+    match events.as_slice() {
+        [ProcessingEvent::WillSend { message_id, .. }] => {
+            assert_eq!(result.transaction["in_msg"], message_id.as_str())
+        }
+        _ => panic!("single WillSend event expected"),
+    }
+    assert!(result.decoded.is_some());
+    let updated = backend.get_account(&deploy.address).await.unwrap().unwrap();
+    assert_ne!(updated, deployed.account);
+
+    // execution result of the sent message is returned by `wait_for_transaction`
+    let unknown = format!("0:{}", "1".repeat(64));
+    let message: ResultOfEncodeMessage = client
+        .request_async("abi.encode_message", touch(unknown.clone()))
+        .await
+        .unwrap();
+    let sent = backend.send_message(message.message.clone()).await.unwrap();
+    let err = backend
+        .wait_for_transaction(message.message.clone(), sent)
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::DebotAccountNotFound as u32);
+    let err = backend
+        .wait_for_transaction(message.message, ResultOfSendMessage::default())
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::DebotInvalidMsg as u32);
+}
@@ -12,6 +12,7 @@ pub struct ClientError {
 
 pub type ClientResult<T> = Result<T, ClientError>;
 
+#[cfg(feature = "net")]
 #[async_trait::async_trait]
 pub(crate) trait AddNetworkUrl: Sized {
     async fn add_endpoint_from_context(
@@ -47,6 +48,7 @@ pub(crate) trait AddNetworkUrl: Sized {
     async fn add_network_url_from_state(self, state: &crate::net::NetworkState) -> Self;
 }
 
+#[cfg(feature = "net")]
 #[async_trait::async_trait]
 impl<T: Send> AddNetworkUrl for ClientResult<T> {
     async fn add_endpoint(
@@ -72,6 +74,7 @@ impl<T: Send> AddNetworkUrl for ClientResult<T> {
     }
 }
 
+#[cfg(feature = "net")]
 #[async_trait::async_trait]
 impl AddNetworkUrl for ClientError {
     async fn add_endpoint(
@@ -16,6 +16,9 @@ use crate::client::{AppObject, ClientContext, Error};
 use crate::error::ClientResult;
 use crate::crypto::{EncryptionBoxInfo, RegisteredEncryptionBox, RegisteredSigningBox, SigningBox};
 use crate::crypto::boxes::encryption_box::EncryptionBox;
+use crate::crypto::boxes::webauthn::{assertion_challenge, verify_assertion, WebAuthnAssertion};
+use crate::crypto::internal::{key256, Key256};
+use crate::encoding::{base64_decode, hex_decode};
 
 /// Signing box callbacks.
 #[derive(Serialize, Deserialize, Clone, Debug, ApiType, PartialEq)]
@@ -91,9 +94,132 @@ pub(crate) async fn register_signing_box(
     context: std::sync::Arc<ClientContext>,
     app_object: AppObject<ParamsOfAppSigningBox, ResultOfAppSigningBox>,
 ) -> ClientResult<RegisteredSigningBox> {
+    app_object.require(&["GetPublicKey", "Sign"])?;
     crate::crypto::register_signing_box(context, ExternalSigningBox::new(app_object)).await
 }
 
+/// WebAuthn signing box callbacks.
+#[derive(Serialize, Deserialize, Clone, Debug, ApiType, PartialEq)]
+#[serde(tag="type")]
+pub enum ParamsOfAppWebAuthnSigningBox {
+    /// Get the assertion of the credential for the challenge
+    GetAssertion {
+        /// Credential id encoded as base64url
+        credential_id: String,
+        /// Relying party id
+        rp_id: String,
+        /// Assertion challenge encoded as base64url. Contains the data to sign.
+        challenge: String,
+        /// User verification is required
+        user_verification: bool,
+    },
+}
+
+/// Returning values from WebAuthn signing box callbacks.
+#[derive(Serialize, Deserialize, Clone, Debug, ApiType, PartialEq)]
+#[serde(tag="type")]
+pub enum ResultOfAppWebAuthnSigningBox {
+    /// Assertion returned by the authenticator
+    GetAssertion {
+        /// `authenticatorData` of the assertion encoded as base64
+        authenticator_data: String,
+        /// `clientDataJSON` of the assertion encoded as base64
+        client_data_json: String,
+        /// Assertion signature encoded as hex
+        signature: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default)]
+pub struct ParamsOfRegisterWebAuthnSigningBox {
+    /// Credential id encoded as base64url.
+    pub credential_id: String,
+    /// Ed25519 public key of the credential encoded as hex.
+    pub public_key: String,
+    /// Relying party id the credential is scoped to.
+    pub rp_id: String,
+    /// Require user verification by the authenticator. Default is `false`.
+    pub user_verification: Option<bool>,
+}
+
+struct WebAuthnSigningBox {
+    app_object: AppObject<ParamsOfAppWebAuthnSigningBox, ResultOfAppWebAuthnSigningBox>,
+    credential_id: String,
+    public_key: Key256,
+    rp_id: String,
+    user_verification: bool,
+}
+
+#[async_trait::async_trait]
+impl SigningBox for WebAuthnSigningBox {
+    async fn get_public_key(&self) -> ClientResult<Vec<u8>> {
+        Ok(self.public_key.to_vec())
+    }
+
+    async fn sign(&self, unsigned: &[u8]) -> ClientResult<Vec<u8>> {
+        let response = self.app_object.call(ParamsOfAppWebAuthnSigningBox::GetAssertion {
+            credential_id: self.credential_id.clone(),
+            rp_id: self.rp_id.clone(),
+            challenge: assertion_challenge(unsigned),
+            user_verification: self.user_verification,
+        }).await?;
+
+        match response {
+            ResultOfAppWebAuthnSigningBox::GetAssertion {
+                authenticator_data,
+                client_data_json,
+                signature,
+            } => {
+                let assertion = WebAuthnAssertion {
+                    authenticator_data: base64_decode(&authenticator_data)?,
+                    client_data_json: base64_decode(&client_data_json)?,
+                    signature: hex_decode(&signature)?,
+                };
+                verify_assertion(
+                    &assertion,
+                    unsigned,
+                    &self.public_key,
+                    &self.rp_id,
+                    self.user_verification,
+                )?;
+                Ok(assertion.signature)
+            }
+        }
+    }
+
+    fn signs_unsigned_data(&self) -> bool {
+        false
+    }
+}
+
+/// Register a signing box backed by an application WebAuthn credential (passkey).
+///
+/// The data to sign is passed to the authenticator as the assertion challenge. The returned
+/// assertion is checked: the client data type and challenge, the relying party id hash,
+/// the user presence (and user verification if required) flags and the signature.
+/// Only Ed25519 credentials (COSE algorithm -8) are supported.
+///
+/// The authenticator signs `authenticatorData || sha256(clientDataJSON)` rather than
+/// the data itself, so contracts can't check such signatures: the box can't be used
+/// as a message signer and `abi` functions fail with `MessageSigningNotSupported` error.
+/// Use `crypto.signing_box_sign` to get the checked assertion signature.
+#[api_function]
+pub(crate) async fn register_webauthn_signing_box(
+    context: std::sync::Arc<ClientContext>,
+    params: ParamsOfRegisterWebAuthnSigningBox,
+    app_object: AppObject<ParamsOfAppWebAuthnSigningBox, ResultOfAppWebAuthnSigningBox>,
+) -> ClientResult<RegisteredSigningBox> {
+    app_object.require(&["GetAssertion"])?;
+    let signing_box = WebAuthnSigningBox {
+        app_object,
+        credential_id: params.credential_id,
+        public_key: key256(&hex_decode(&params.public_key)?)?,
+        rp_id: params.rp_id,
+        user_verification: params.user_verification.unwrap_or(false),
+    };
+    crate::crypto::register_signing_box(context, signing_box).await
+}
+
 /// Encryption box callbacks.
 #[derive(Serialize, Deserialize, Clone, Debug, ApiType, PartialEq)]
 #[serde(tag="type")]
@@ -183,5 +309,6 @@ pub(crate) async fn register_encryption_box(
     context: std::sync::Arc<ClientContext>,
     app_object: AppObject<ParamsOfAppEncryptionBox, ResultOfAppEncryptionBox>,
 ) -> ClientResult<RegisteredEncryptionBox> {
+    app_object.require(&["GetInfo"])?;
     crate::crypto::register_encryption_box(context, ExternalEncryptionBox::new(app_object)).await
 }
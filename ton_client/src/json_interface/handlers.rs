@@ -12,6 +12,8 @@
  *
  */
 
+use crate::client::health::catch_panic;
+use crate::client::trace::Traced;
 use crate::client::{AppObject, ClientContext, Error};
 use crate::error::ClientResult;
 use crate::json_interface::runtime::Runtime;
@@ -253,6 +255,26 @@ fn get_incorrect_enum_errors(
     }
 }
 
+/// Spawns the request handling. If the handling panics, the request is finished
+/// with `InternalError` describing the panic.
+fn spawn_request<Fut>(
+    context: Arc<ClientContext>,
+    request: Request,
+    handle: impl FnOnce(Arc<ClientContext>, Arc<Request>) -> Fut + Send + 'static,
+) where
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let env = context.env.clone();
+    let trace_id = request.trace_id().cloned();
+    env.spawn(Traced::new(trace_id, async move {
+        let request = Arc::new(request);
+        if let Err(err) = catch_panic(&context, handle(context.clone(), request.clone())).await {
+            request.finish_with_error(err);
+        }
+    }));
+}
+
+#[cfg(feature = "tvm-executor")]
 pub(crate) struct SpawnHandlerCallback<P, R, Fut, F>
 where
     P: Send + DeserializeOwned + 'static,
@@ -265,6 +287,7 @@ where
     phantom: PhantomData<std::sync::Mutex<(P, R, Fut)>>,
 }
 
+#[cfg(feature = "tvm-executor")]
 impl<P, R, Fut, F> SpawnHandlerCallback<P, R, Fut, F>
 where
     P: Send + DeserializeOwned + 'static,
@@ -280,6 +303,7 @@ where
     }
 }
 
+#[cfg(feature = "tvm-executor")]
 impl<P, R, Fut, F> AsyncHandler for SpawnHandlerCallback<P, R, Fut, F>
 where
     P: Send + DeserializeOwned + ApiType + 'static,
@@ -289,13 +313,10 @@ where
 {
     fn handle(&self, context: Arc<ClientContext>, params_json: String, request: Request) {
         let handler = self.handler.clone();
-        let context_copy = context.clone();
-
-        context.env.spawn(async move {
-            let request = Arc::new(request);
+        spawn_request(context, request, move |context, request| async move {
             match parse_params(&params_json) {
                 Ok(params) => {
-                    let result = handler(context_copy, params, request.clone()).await;
+                    let result = handler(context, params, request.clone()).await;
                     request.response_result(result);
                 }
                 Err(err) => request.finish_with_error(err),
@@ -346,13 +367,11 @@ where
 {
     fn handle(&self, context: Arc<ClientContext>, params_json: String, request: Request) {
         let handler = self.handler.clone();
-        let context_copy = context.clone();
-        context.env.spawn(async move {
-            let request = Arc::new(request);
+        spawn_request(context, request, move |context, request| async move {
             match parse_params(&params_json) {
                 Ok(params) => {
-                    let app_object = AppObject::new(context_copy.clone(), request.clone());
-                    let result = handler(context_copy, params, app_object).await;
+                    let app_object = AppObject::with_handshake(context.clone(), request.clone()).await;
+                    let result = handler(context, params, app_object).await;
                     request.response_result(result);
                 }
                 Err(err) => request.finish_with_error(err),
@@ -400,11 +419,9 @@ where
 {
     fn handle(&self, context: Arc<ClientContext>, _params_json: String, request: Request) {
         let handler = self.handler.clone();
-        let context_copy = context.clone();
-        context.env.spawn(async move {
-            let request = Arc::new(request);
-            let app_object = AppObject::new(context_copy.clone(), request.clone());
-            let result = handler(context_copy, app_object).await;
+        spawn_request(context, request, move |context, request| async move {
+            let app_object = AppObject::with_handshake(context.clone(), request.clone()).await;
+            let result = handler(context, app_object).await;
             request.response_result(result);
         });
     }
@@ -446,11 +463,10 @@ where
 {
     fn handle(&self, context: Arc<ClientContext>, params_json: String, request: Request) {
         let handler = self.handler.clone();
-        let context_copy = context.clone();
-        context.env.spawn(async move {
+        spawn_request(context, request, move |context, request| async move {
             match parse_params(&params_json) {
                 Ok(params) => {
-                    let result = handler(context_copy, params).await;
+                    let result = handler(context, params).await;
                     request.finish_with_result(result);
                 }
                 Err(err) => request.finish_with_error(err),
@@ -492,9 +508,8 @@ where
 {
     fn handle(&self, context: Arc<ClientContext>, _params_json: String, request: Request) {
         let handler = self.handler.clone();
-        let context_copy = context.clone();
-        context.env.spawn(async move {
-            request.finish_with_result(handler(context_copy).await);
+        spawn_request(context, request, move |context, request| async move {
+            request.finish_with_result(handler(context).await);
         });
     }
 }
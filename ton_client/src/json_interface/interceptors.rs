@@ -0,0 +1,131 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use crate::error::ClientResult;
+use sha2::Digest;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// API function call passed to the interceptors.
+#[derive(Debug, Clone)]
+pub struct ApiCallInfo {
+    /// Full function name, e.g. `crypto.sha256`
+    pub function_name: String,
+    /// SHA-256 of the function parameters JSON encoded as hex.
+    /// Can be used as a key by caching or mocking interceptors.
+    pub params_digest: String,
+    /// Trace id passed by the application in the `trace_id` field of the parameters.
+    /// The field is not included into `params_digest`.
+    pub trace_id: Option<String>,
+}
+
+impl ApiCallInfo {
+    pub(crate) fn new(function_name: &str, params_json: &str, trace_id: Option<String>) -> Self {
+        Self {
+            function_name: function_name.to_string(),
+            params_digest: hex::encode(sha2::Sha256::digest(params_json.as_bytes())),
+            trace_id,
+        }
+    }
+}
+
+/// Completed API function call passed to the interceptors.
+#[derive(Debug, Clone)]
+pub struct ApiCallOutcome {
+    /// Time spent by the function, in milliseconds
+    pub duration: u64,
+    /// Error code if the function has failed
+    pub error_code: Option<u32>,
+    /// Result JSON if the function has succeeded
+    pub result_json: Option<String>,
+    /// The result was provided by an interceptor instead of the function
+    pub intercepted: bool,
+}
+
+/// Interceptor of the API function calls dispatched to a context.
+///
+/// Allows to build auditing, caching or mocking layers on top of the library.
+pub trait ApiCallInterceptor: Send + Sync {
+    /// Called before the function. If some result JSON or error is returned, the function
+    /// is not called and the returned result is passed to the caller.
+    fn before_call(&self, _call: &ApiCallInfo) -> Option<ClientResult<String>> {
+        None
+    }
+
+    /// Called after the function (or the interceptor replacing it) has finished
+    fn after_call(&self, _call: &ApiCallInfo, _outcome: &ApiCallOutcome) {}
+}
+
+#[derive(Default)]
+pub(crate) struct Interceptors {
+    interceptors: RwLock<Vec<(u32, Arc<dyn ApiCallInterceptor>)>>,
+}
+
+impl Interceptors {
+    pub fn add(&self, id: u32, interceptor: Arc<dyn ApiCallInterceptor>) {
+        self.interceptors.write().unwrap_or_else(PoisonError::into_inner).push((id, interceptor));
+    }
+
+    pub fn remove(&self, id: u32) {
+        self.interceptors.write().unwrap_or_else(PoisonError::into_inner).retain(|(item_id, _)| *item_id != id);
+    }
+
+    pub fn is_poisoned(&self) -> bool {
+        self.interceptors.is_poisoned()
+    }
+
+    pub fn list(&self) -> Vec<Arc<dyn ApiCallInterceptor>> {
+        self.interceptors
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(_, interceptor)| interceptor.clone())
+            .collect()
+    }
+}
+
+/// Interceptors registered at the moment of the call
+pub(crate) struct InterceptedCall {
+    call: ApiCallInfo,
+    interceptors: Vec<Arc<dyn ApiCallInterceptor>>,
+}
+
+impl InterceptedCall {
+    pub fn new(
+        interceptors: Vec<Arc<dyn ApiCallInterceptor>>,
+        function_name: &str,
+        params_json: &str,
+        trace_id: Option<String>,
+    ) -> Option<Self> {
+        if interceptors.is_empty() {
+            return None;
+        }
+        Some(Self {
+            call: ApiCallInfo::new(function_name, params_json, trace_id),
+            interceptors,
+        })
+    }
+
+    /// Returns the result provided by the first interceptor replacing the function
+    pub fn before(&self) -> Option<ClientResult<String>> {
+        self.interceptors
+            .iter()
+            .find_map(|interceptor| interceptor.before_call(&self.call))
+    }
+
+    pub fn after(&self, outcome: ApiCallOutcome) {
+        for interceptor in &self.interceptors {
+            interceptor.after_call(&self.call, &outcome);
+        }
+    }
+}
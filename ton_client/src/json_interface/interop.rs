@@ -12,6 +12,7 @@
  *
  */
 
+use super::interceptors::ApiCallInterceptor;
 use super::request::Request;
 use super::runtime::Runtime;
 use crate::client::Error;
@@ -19,6 +20,7 @@ use crate::error::ClientResult;
 use serde_json::Value;
 use std::ffi::c_void;
 use std::ptr::null;
+use std::sync::Arc;
 
 pub type ContextHandle = u32;
 
@@ -29,6 +31,7 @@ pub enum ResponseType {
     Nop = 2,
     AppRequest = 3,
     AppNotify = 4,
+    ResponseChunk = 5,
     Custom = 100,
 }
 
@@ -93,6 +96,23 @@ pub fn request_sync(context: ContextHandle, function_name: String, params_json:
     convert_result_to_sync_response(result_value)
 }
 
+/// Registers the interceptor of the API calls dispatched to the context.
+/// Returns the interceptor id to be passed to `unregister_interceptor`.
+pub fn register_interceptor(
+    context: ContextHandle,
+    interceptor: Arc<dyn ApiCallInterceptor>,
+) -> ClientResult<u32> {
+    let context = Runtime::required_context(context)?;
+    let id = context.get_next_id();
+    context.interceptors.add(id, interceptor);
+    Ok(id)
+}
+
+pub fn unregister_interceptor(context: ContextHandle, id: u32) -> ClientResult<()> {
+    Runtime::required_context(context)?.interceptors.remove(id);
+    Ok(())
+}
+
 // C-style interface
 
 #[no_mangle]
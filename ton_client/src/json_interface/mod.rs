@@ -13,11 +13,19 @@
  */
 
 pub(crate) mod crypto;
+#[cfg(feature = "debot")]
 pub(crate) mod debot;
 pub(crate) mod handlers;
+pub(crate) mod interceptors;
 pub(crate) mod interop;
+#[cfg(feature = "net")]
 pub(crate) mod net;
+#[cfg(feature = "net")]
 pub(crate) mod processing;
+#[cfg(feature = "proofs")]
+pub(crate) mod proofs;
+#[cfg(feature = "tvm-executor")]
+pub(crate) mod tvm;
 pub(crate) mod utils;
 
 pub(crate) mod modules;
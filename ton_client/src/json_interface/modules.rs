@@ -26,14 +26,35 @@ fn register_client(handlers: &mut RuntimeHandlers) {
     module.register_error_code::<crate::client::errors::ErrorCode>();
     module.register_type::<crate::error::ClientError>();
     module.register_type::<crate::client::ClientConfig>();
+    #[cfg(feature = "net")]
     module.register_type::<crate::net::NetworkConfig>();
+    #[cfg(feature = "net")]
+    module.register_type::<crate::net::LiteServerConfig>();
+    #[cfg(feature = "net")]
+    module.register_type::<crate::net::SubscriptionsTransport>();
+    #[cfg(feature = "net")]
+    module.register_type::<crate::net::NetworkQueriesProtocol>();
     module.register_type::<crate::crypto::CryptoConfig>();
     module.register_type::<crate::abi::AbiConfig>();
     module.register_type::<crate::boc::BocConfig>();
+    #[cfg(feature = "proofs")]
     module.register_type::<crate::proofs::ProofsConfig>();
+    #[cfg(feature = "proofs")]
+    module.register_type::<crate::proofs::ProofsStorageType>();
+    #[cfg(feature = "proofs")]
+    module.register_type::<crate::proofs::ProofsVerificationPolicy>();
+    #[cfg(feature = "proofs")]
+    module.register_type::<crate::proofs::NetworkTrustedKeyBlocks>();
+    #[cfg(feature = "net")]
+    module.register_type::<crate::net::TrustedMcBlockId>();
+    module.register_type::<crate::client::EventSinkConfig>();
     module.register_type::<crate::client::BuildInfoDependency>();
     module.register_type::<crate::client::ParamsOfAppRequest>();
     module.register_type::<crate::client::AppRequestResult>();
+    module.register_type::<crate::client::ParamsOfAppObjectHandshake>();
+    module.register_type::<crate::client::ResultOfAppObjectHandshake>();
+    module.register_type::<crate::client::ConfigDiagnosticKind>();
+    module.register_type::<crate::client::ConfigDiagnostic>();
 
     module.register_sync_fn_without_args(
         crate::client::get_api_reference,
@@ -45,6 +66,23 @@ fn register_client(handlers: &mut RuntimeHandlers) {
         crate::client::resolve_app_request,
         crate::client::resolve_app_request_api,
     );
+    module.register_sync_fn(crate::client::set_offline, crate::client::set_offline_api);
+    module.register_sync_fn_without_args(
+        crate::client::is_offline,
+        crate::client::is_offline_api,
+    );
+    module.register_sync_fn(
+        crate::client::create_derived_context,
+        crate::client::create_derived_context_api,
+    );
+    module.register_sync_fn(
+        crate::client::validate_config,
+        crate::client::config_validation::validate_config_api,
+    );
+    module.register_async_fn_no_args(
+        crate::client::health_check,
+        crate::client::health::health_check_api,
+    );
     module.register();
 }
 
@@ -60,10 +98,19 @@ fn register_crypto(handlers: &mut RuntimeHandlers) {
     module.register_type::<crate::crypto::SigningBoxHandle>();
     module.register_type::<crate::crypto::EncryptionBoxHandle>();
     module.register_type::<crate::crypto::EncryptionBoxInfo>();
+    #[cfg(feature = "crypto-extended")]
     module.register_type::<crate::crypto::EncryptionAlgorithm>();
     module.register_type::<crate::crypto::CipherMode>();
+    #[cfg(feature = "crypto-extended")]
     module.register_type::<crate::crypto::AesParams>();
+    #[cfg(feature = "crypto-extended")]
     module.register_type::<crate::crypto::AesInfo>();
+    #[cfg(feature = "crypto-extended")]
+    module.register_type::<crate::crypto::ChaCha20Poly1305Params>();
+    #[cfg(feature = "crypto-extended")]
+    module.register_type::<crate::crypto::ThresholdParams>();
+    #[cfg(feature = "crypto-extended")]
+    module.register_type::<crate::crypto::ThresholdInfo>();
 
     // Math
 
@@ -84,6 +131,10 @@ fn register_crypto(handlers: &mut RuntimeHandlers) {
         crate::crypto::convert_public_key_to_ton_safe_format,
         crate::crypto::keys::convert_public_key_to_ton_safe_format_api,
     );
+    module.register_sync_fn(
+        crate::crypto::key_fingerprint,
+        crate::crypto::keys::key_fingerprint_api,
+    );
 
     module.register_sync_fn_without_args(
         crate::crypto::generate_random_sign_keys,
@@ -102,6 +153,7 @@ fn register_crypto(handlers: &mut RuntimeHandlers) {
 
     // Scrypt
 
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::encscrypt::scrypt,
         crate::crypto::encscrypt::scrypt_api,
@@ -109,41 +161,62 @@ fn register_crypto(handlers: &mut RuntimeHandlers) {
 
     // NaCl
 
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::nacl_sign_keypair_from_secret_key,
         crate::crypto::nacl::nacl_sign_keypair_from_secret_key_api,
     );
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(crate::crypto::nacl_sign, crate::crypto::nacl::nacl_sign_api);
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::nacl_sign_open,
         crate::crypto::nacl::nacl_sign_open_api,
     );
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::nacl_sign_detached,
         crate::crypto::nacl::nacl_sign_detached_api,
     );
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::nacl_sign_detached_verify,
         crate::crypto::nacl::nacl_sign_detached_verify_api,
     );
+    #[cfg(feature = "crypto-extended")]
+    module.register_sync_fn(
+        crate::crypto::nacl_sign_detached_hash,
+        crate::crypto::nacl::nacl_sign_detached_hash_api,
+    );
+    #[cfg(feature = "crypto-extended")]
+    module.register_sync_fn(
+        crate::crypto::nacl_verify_detached_hash,
+        crate::crypto::nacl::nacl_verify_detached_hash_api,
+    );
 
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn_without_args(
         crate::crypto::nacl_box_keypair,
         crate::crypto::nacl::nacl_box_keypair_api,
     );
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::nacl_box_keypair_from_secret_key,
         crate::crypto::nacl::nacl_box_keypair_from_secret_key_api,
     );
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(crate::crypto::nacl_box, crate::crypto::nacl::nacl_box_api);
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::nacl_box_open,
         crate::crypto::nacl::nacl_box_open_api,
     );
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::nacl_secret_box,
         crate::crypto::nacl::nacl_secret_box_api,
     );
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::nacl_secret_box_open,
         crate::crypto::nacl::nacl_secret_box_open_api,
@@ -151,45 +224,67 @@ fn register_crypto(handlers: &mut RuntimeHandlers) {
 
     // Mnemonic
 
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::mnemonic_words,
         crate::crypto::mnemonic::mnemonic_words_api,
     );
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::mnemonic_from_random,
         crate::crypto::mnemonic::mnemonic_from_random_api,
     );
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::mnemonic_from_entropy,
         crate::crypto::mnemonic::mnemonic_from_entropy_api,
     );
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::mnemonic_verify,
         crate::crypto::mnemonic::mnemonic_verify_api,
     );
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::mnemonic_derive_sign_keys,
         crate::crypto::mnemonic::mnemonic_derive_sign_keys_api,
     );
+    #[cfg(feature = "crypto-extended")]
+    module.register_type::<crate::crypto::EncryptedMnemonicKdfParams>();
+    #[cfg(feature = "crypto-extended")]
+    module.register_sync_fn(
+        crate::crypto::export_encrypted_mnemonic,
+        crate::crypto::encrypted_mnemonic::export_encrypted_mnemonic_api,
+    );
+    #[cfg(feature = "crypto-extended")]
+    module.register_sync_fn(
+        crate::crypto::import_encrypted_mnemonic,
+        crate::crypto::encrypted_mnemonic::import_encrypted_mnemonic_api,
+    );
 
     // HDKey
 
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::hdkey_xprv_from_mnemonic,
         crate::crypto::hdkey::hdkey_xprv_from_mnemonic_api,
     );
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::hdkey_derive_from_xprv,
         crate::crypto::hdkey::hdkey_derive_from_xprv_api,
     );
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::hdkey_derive_from_xprv_path,
         crate::crypto::hdkey::hdkey_derive_from_xprv_path_api,
     );
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::hdkey_secret_from_xprv,
         crate::crypto::hdkey::hdkey_secret_from_xprv_api,
     );
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::hdkey_public_from_xprv,
         crate::crypto::hdkey::hdkey_public_from_xprv_api,
@@ -197,6 +292,7 @@ fn register_crypto(handlers: &mut RuntimeHandlers) {
 
     // Encryption
 
+    #[cfg(feature = "crypto-extended")]
     module.register_sync_fn(
         crate::crypto::chacha20,
         crate::crypto::encryption::chacha20_api,
@@ -209,6 +305,10 @@ fn register_crypto(handlers: &mut RuntimeHandlers) {
         super::crypto::register_signing_box,
         super::crypto::register_signing_box_api,
     );
+    module.register_async_fn_with_app_object(
+        super::crypto::register_webauthn_signing_box,
+        super::crypto::register_webauthn_signing_box_api,
+    );
     module.register_async_fn(
         crate::crypto::get_signing_box,
         crate::crypto::boxes::signing_box::get_signing_box_api,
@@ -221,6 +321,14 @@ fn register_crypto(handlers: &mut RuntimeHandlers) {
         crate::crypto::signing_box_sign,
         crate::crypto::boxes::signing_box::signing_box_sign_api,
     );
+    module.register_async_fn(
+        crate::crypto::limit_signing_box,
+        crate::crypto::boxes::signing_box::limit_signing_box_api,
+    );
+    module.register_async_fn(
+        crate::crypto::signing_box_info,
+        crate::crypto::boxes::signing_box::signing_box_info_api,
+    );
     module.register_sync_fn(
         crate::crypto::remove_signing_box,
         crate::crypto::boxes::signing_box::remove_signing_box_api,
@@ -247,6 +355,7 @@ fn register_crypto(handlers: &mut RuntimeHandlers) {
         crate::crypto::encryption_box_decrypt,
         crate::crypto::boxes::encryption_box::encryption_box_decrypt_api,
     );
+    #[cfg(feature = "crypto-extended")]
     module.register_async_fn(
         crate::crypto::create_encryption_box,
         crate::crypto::boxes::encryption_box::create_encryption_box_api,
@@ -269,8 +378,10 @@ fn register_abi(handlers: &mut RuntimeHandlers) {
     module.register_type::<crate::abi::FunctionHeader>();
     module.register_type::<crate::abi::CallSet>();
     module.register_type::<crate::abi::DeploySet>();
+    module.register_type::<crate::abi::MessageExpiration>();
     module.register_type::<crate::abi::Signer>();
     module.register_type::<crate::abi::MessageBodyType>();
+    module.register_type::<crate::abi::DecodeDiagnostics>();
     module.register_type::<crate::abi::StateInitSource>();
     module.register_type::<crate::abi::StateInitParams>();
     module.register_type::<crate::abi::MessageSource>();
@@ -279,6 +390,8 @@ fn register_abi(handlers: &mut RuntimeHandlers) {
     module.register_type::<crate::abi::AbiData>();
     module.register_type::<crate::abi::AbiFunction>();
     module.register_type::<crate::abi::AbiContract>();
+    module.register_type::<crate::abi::DataLayoutOptions>();
+    module.register_type::<crate::abi::NumberOverflow>();
 
     module.register_async_fn(
         crate::abi::encode_message_body,
@@ -300,6 +413,18 @@ fn register_abi(handlers: &mut RuntimeHandlers) {
         crate::abi::attach_signature,
         crate::abi::encode_message::attach_signature_api,
     );
+    module.register_async_fn(
+        crate::abi::encode_message_with_placeholder_signature,
+        crate::abi::encode_message::encode_message_with_placeholder_signature_api,
+    );
+    module.register_async_fn(
+        crate::abi::attach_signature_to_unsigned,
+        crate::abi::encode_message::attach_signature_to_unsigned_api,
+    );
+    module.register_async_fn(
+        crate::abi::attach_signature_to_unsigned_body,
+        crate::abi::encode_message::attach_signature_to_unsigned_body_api,
+    );
     module.register_async_fn(
         crate::abi::decode_message,
         crate::abi::decode_message::decode_message_api,
@@ -308,10 +433,26 @@ fn register_abi(handlers: &mut RuntimeHandlers) {
         crate::abi::decode_message_body,
         crate::abi::decode_message::decode_message_body_api,
     );
+    module.register_sync_fn(
+        crate::abi::get_event_id,
+        crate::abi::decode_events::get_event_id_api,
+    );
+    module.register_async_fn(
+        crate::abi::decode_events_from_transaction,
+        crate::abi::decode_events::decode_events_from_transaction_api,
+    );
     module.register_async_fn(
         crate::abi::encode_account,
         crate::abi::encode_account::encode_account_api,
     );
+    module.register_async_fn(
+        crate::abi::encode_state_init_deploy_message,
+        crate::abi::state_init_deploy::encode_state_init_deploy_message_api,
+    );
+    module.register_async_fn(
+        crate::abi::decode_state_init_deploy_message,
+        crate::abi::state_init_deploy::decode_state_init_deploy_message_api,
+    );
     module.register_async_fn(
         crate::abi::decode_account_data,
         crate::abi::decode_data::decode_account_data_api,
@@ -336,6 +477,10 @@ fn register_abi(handlers: &mut RuntimeHandlers) {
         crate::abi::encode_boc,
         crate::abi::encode_boc::encode_boc_api,
     );
+    module.register_sync_fn(
+        crate::abi::render_call,
+        crate::abi::render_call::render_call_api,
+    );
     module.register();
 }
 
@@ -365,6 +510,10 @@ fn register_boc(handlers: &mut RuntimeHandlers) {
         crate::boc::parse_shardstate,
         crate::boc::parse::parse_shardstate_api,
     );
+    module.register_async_fn(
+        crate::boc::parse_block_topology,
+        crate::boc::topology::parse_block_topology_api,
+    );
     module.register_async_fn(
         crate::boc::get_blockchain_config,
         crate::boc::blockchain_config::get_blockchain_config_api,
@@ -377,6 +526,12 @@ fn register_boc(handlers: &mut RuntimeHandlers) {
         crate::boc::get_boc_depth,
         crate::boc::common::get_boc_depth_api,
     );
+    #[cfg(feature = "tvm-executor")]
+    module.register_async_fn(crate::boc::get_stats, crate::boc::stats::get_stats_api);
+    module.register_async_fn(
+        crate::boc::print_tree,
+        crate::boc::print_tree::print_tree_api,
+    );
     module.register_async_fn(
         crate::boc::get_code_from_tvc,
         crate::boc::tvc::get_code_from_tvc_api,
@@ -404,14 +559,32 @@ fn register_boc(handlers: &mut RuntimeHandlers) {
         crate::boc::get_compiler_version,
         crate::boc::tvc::get_compiler_version_api,
     );
+    module.register_async_fn(
+        crate::boc::check_merkle_update,
+        crate::boc::merkle_update::check_merkle_update_api,
+    );
+    module.register_async_fn(
+        crate::boc::apply_merkle_update,
+        crate::boc::merkle_update::apply_merkle_update_api,
+    );
+    module.register_async_fn(
+        crate::boc::encode_config_param,
+        crate::boc::config_param::encode_config_param_api,
+    );
+    module.register_async_fn(
+        crate::boc::decode_config_param,
+        crate::boc::config_param::decode_config_param_api,
+    );
     module.register();
 }
 
+#[cfg(feature = "net")]
 /// Network access.
 #[derive(ApiModule)]
 #[api_module(name = "net")]
 pub(crate) struct NetModule;
 
+#[cfg(feature = "net")]
 fn register_net(handlers: &mut RuntimeHandlers) {
     let mut module = ModuleReg::new::<NetModule>(handlers);
     module.register_error_code::<crate::net::ErrorCode>();
@@ -421,8 +594,17 @@ fn register_net(handlers: &mut RuntimeHandlers) {
     module.register_type::<crate::net::ParamsOfQueryOperation>();
     module.register_type::<crate::net::FieldAggregation>();
     module.register_type::<crate::net::AggregationFn>();
+    module.register_type::<crate::net::AggregationGroupBy>();
+    module.register_type::<crate::net::AggregationGroup>();
     module.register_type::<crate::net::TransactionNode>();
     module.register_type::<crate::net::MessageNode>();
+    module.register_type::<crate::net::SubscriptionMapping>();
+    module.register_type::<crate::net::SubscriptionEvent>();
+    module.register_type::<crate::net::FieldPredicate>();
+    module.register_type::<crate::net::PredicateOperator>();
+    module.register_type::<crate::net::BodyDecoding>();
+    module.register_type::<crate::net::SchemaField>();
+    module.register_type::<crate::net::SchemaType>();
 
     module.register_async_fn(crate::net::query, crate::net::queries::query_api);
     module.register_async_fn(crate::net::batch_query, crate::net::batch::batch_query_api);
@@ -438,6 +620,10 @@ fn register_net(handlers: &mut RuntimeHandlers) {
         crate::net::wait_for_collection,
         crate::net::queries::wait_for_collection_api,
     );
+    module.register_async_fn_no_args(
+        crate::net::get_consistency_token,
+        crate::net::queries::get_consistency_token_api,
+    );
     module.register_async_fn(
         crate::net::unsubscribe,
         crate::net::subscriptions::unsubscribe_api,
@@ -447,6 +633,22 @@ fn register_net(handlers: &mut RuntimeHandlers) {
         super::net::subscribe_collection_api,
     );
     module.register_async_fn_with_callback(super::net::subscribe, super::net::subscribe_api);
+    module.register_type::<crate::net::BlockHeader>();
+    module.register_async_fn_with_callback(
+        super::net::subscribe_block_headers,
+        super::net::subscribe_block_headers_api,
+    );
+    module.register_type::<crate::net::SyncedAccount>();
+    module.register_type::<crate::net::AccountsSyncState>();
+    module.register_type::<crate::net::AccountsSyncEvent>();
+    module.register_async_fn_with_callback(
+        super::net::sync_accounts,
+        super::net::sync_accounts_api,
+    );
+    module.register_async_fn(
+        crate::net::stop_sync_accounts,
+        crate::net::accounts_sync::stop_sync_accounts_api,
+    );
     module.register_async_fn_no_args(crate::net::suspend, crate::net::suspend_api);
     module.register_async_fn_no_args(crate::net::resume, crate::net::resume_api);
     module.register_async_fn(
@@ -456,13 +658,27 @@ fn register_net(handlers: &mut RuntimeHandlers) {
     module.register_async_fn_no_args(crate::net::fetch_endpoints, crate::net::fetch_endpoints_api);
     module.register_async_fn(crate::net::set_endpoints, crate::net::set_endpoints_api);
     module.register_async_fn_no_args(crate::net::get_endpoints, crate::net::get_endpoints_api);
+    module.register_type::<crate::net::EndpointStats>();
+    module.register_async_fn_no_args(
+        crate::net::get_endpoint_stats,
+        crate::net::endpoint_stats::get_endpoint_stats_api,
+    );
+    module.register_async_fn_no_args(
+        crate::net::reset_endpoint_stats,
+        crate::net::endpoint_stats::reset_endpoint_stats_api,
+    );
     module.register_async_fn(
         crate::net::query_counterparties,
         crate::net::queries::query_counterparties_api,
     );
+    module.register_type::<crate::net::TransactionTreeNodeEvent>();
+    module.register_async_fn_with_callback(
+        super::net::query_transaction_tree,
+        super::net::query_transaction_tree_api,
+    );
     module.register_async_fn(
-        crate::net::transaction_tree::query_transaction_tree,
-        crate::net::transaction_tree::query_transaction_tree_api,
+        crate::net::get_collection_schema,
+        crate::net::schema::get_collection_schema_api,
     );
 
     module.register_async_fn(
@@ -473,6 +689,16 @@ fn register_net(handlers: &mut RuntimeHandlers) {
         crate::net::iterators::block_iterator::resume_block_iterator,
         crate::net::iterators::block_iterator::resume_block_iterator_api,
     );
+    #[cfg(feature = "proofs")]
+    module.register_async_fn(
+        crate::net::iterators::local_block_iterator::create_local_block_iterator,
+        crate::net::iterators::local_block_iterator::create_local_block_iterator_api,
+    );
+    #[cfg(feature = "proofs")]
+    module.register_async_fn(
+        crate::net::iterators::local_block_iterator::resume_local_block_iterator,
+        crate::net::iterators::local_block_iterator::resume_local_block_iterator_api,
+    );
     module.register_async_fn(
         crate::net::iterators::transaction_iterator::create_transaction_iterator,
         crate::net::iterators::transaction_iterator::create_transaction_iterator_api,
@@ -481,6 +707,14 @@ fn register_net(handlers: &mut RuntimeHandlers) {
         crate::net::iterators::transaction_iterator::resume_transaction_iterator,
         crate::net::iterators::transaction_iterator::resume_transaction_iterator_api,
     );
+    module.register_async_fn(
+        crate::net::iterators::collection_iterator::query_collection_all,
+        crate::net::iterators::collection_iterator::query_collection_all_api,
+    );
+    module.register_async_fn(
+        crate::net::iterators::collection_iterator::resume_query_collection_all,
+        crate::net::iterators::collection_iterator::resume_query_collection_all_api,
+    );
     module.register_async_fn(
         crate::net::iterators::iterator_next,
         crate::net::iterators::iterator_next_api,
@@ -489,9 +723,41 @@ fn register_net(handlers: &mut RuntimeHandlers) {
         crate::net::iterators::remove_iterator,
         crate::net::iterators::remove_iterator_api,
     );
+    module.register_type::<crate::net::LiteBlockId>();
+    module.register_async_fn_no_args(
+        crate::net::lite_get_masterchain_info,
+        crate::net::lite::lite_get_masterchain_info_api,
+    );
+    module.register_async_fn(
+        crate::net::lite_get_account_state,
+        crate::net::lite::lite_get_account_state_api,
+    );
+    module.register_async_fn(
+        crate::net::lite_get_block,
+        crate::net::lite::lite_get_block_api,
+    );
+    module.register_async_fn(
+        crate::net::lite_send_message,
+        crate::net::lite::lite_send_message_api,
+    );
+    module.register_type::<crate::net::BlockchainSeqNoRange>();
+    module.register_async_fn(
+        crate::net::blockchain_key_blocks,
+        crate::net::blockchain::blockchain_key_blocks_api,
+    );
+    module.register_async_fn(
+        crate::net::blockchain_workchain_blocks,
+        crate::net::blockchain::blockchain_workchain_blocks_api,
+    );
+    module.register_async_fn(
+        crate::net::blockchain_account_transactions,
+        crate::net::blockchain::blockchain_account_transactions_api,
+    );
     module.register();
 }
 
+#[cfg(feature = "net")]
+#[cfg(feature = "net")]
 /// Message processing module.
 ///
 /// This module incorporates functions related to complex message
@@ -500,6 +766,7 @@ fn register_net(handlers: &mut RuntimeHandlers) {
 #[api_module(name = "processing")]
 pub struct ProcessingModule;
 
+#[cfg(feature = "net")]
 fn register_processing(handlers: &mut RuntimeHandlers) {
     let mut module = ModuleReg::new::<ProcessingModule>(handlers);
     module.register_error_code::<crate::processing::ErrorCode>();
@@ -507,11 +774,27 @@ fn register_processing(handlers: &mut RuntimeHandlers) {
     module.register_type::<crate::processing::ProcessingEvent>();
     module.register_type::<crate::processing::ResultOfProcessMessage>();
     module.register_type::<crate::processing::DecodedOutput>();
+    module.register_type::<crate::processing::ParamsOfDecodeTree>();
+    module.register_type::<crate::processing::TreeDecodingDepth>();
+    module.register_type::<crate::processing::ProcessingPriority>();
+    module.register_type::<crate::processing::ProcessingLaneStats>();
+    module.register_type::<crate::processing::TransactionFinality>();
+    module.register_type::<crate::processing::SequenceProvider>();
+    module.register_type::<crate::processing::MessageSequence>();
+    module.register_type::<crate::processing::RetryPolicy>();
+    module.register_type::<crate::processing::RetryCondition>();
+    module.register_type::<crate::processing::MessageSendingResult>();
+    module.register_type::<crate::processing::MonitoredMessage>();
+    module.register_type::<crate::processing::MessageMonitoringEvent>();
 
     module.register_async_fn_with_callback(
         super::processing::send_message,
         super::processing::send_message_api,
     );
+    module.register_async_fn(
+        crate::processing::send_messages,
+        crate::processing::send_message::send_messages_api,
+    );
     module.register_async_fn_with_callback(
         super::processing::wait_for_transaction,
         super::processing::wait_for_transaction_api,
@@ -520,13 +803,31 @@ fn register_processing(handlers: &mut RuntimeHandlers) {
         super::processing::process_message,
         super::processing::process_message_api,
     );
+    module.register_async_fn_with_callback(
+        super::processing::send_raw_message,
+        super::processing::send_raw_message_api,
+    );
+    module.register_async_fn_with_callback(
+        super::processing::monitor_messages,
+        super::processing::monitor_messages_api,
+    );
+    module.register_async_fn(
+        crate::processing::stop_monitoring,
+        crate::processing::message_monitor::stop_monitoring_api,
+    );
+    module.register_async_fn_no_args(
+        crate::processing::get_scheduler_stats,
+        crate::processing::scheduler::get_scheduler_stats_api,
+    );
     module.register();
 }
 
+#[cfg(feature = "tvm-executor")]
 #[derive(ApiModule)]
 #[api_module(name = "tvm")]
 pub struct TvmModule;
 
+#[cfg(feature = "tvm-executor")]
 fn register_tvm(handlers: &mut RuntimeHandlers) {
     let mut module = ModuleReg::new::<TvmModule>(handlers);
     module.register_error_code::<crate::tvm::ErrorCode>();
@@ -534,12 +835,48 @@ fn register_tvm(handlers: &mut RuntimeHandlers) {
     module.register_type::<crate::tvm::types::ExecutionOptions>();
     module.register_type::<crate::tvm::AccountForExecutor>();
     module.register_type::<crate::tvm::TransactionFees>();
+    module.register_type::<crate::tvm::AccountStateDiff>();
+    module.register_type::<crate::tvm::ExecutorAction>();
+    module.register_type::<crate::tvm::PageFetchedEvent>();
+    module.register_type::<crate::tvm::BalanceChange>();
+    module.register_type::<crate::tvm::TokenTransfer>();
+    module.register_type::<crate::tvm::TokenApproval>();
+    module.register_type::<crate::tvm::DeployedContract>();
+    module.register_type::<crate::tvm::AccountSnapshotHandle>();
+    #[cfg(feature = "proofs")]
+    module.register_type::<crate::tvm::SystemContract>();
     module.register_async_fn(
         crate::tvm::run_executor,
         crate::tvm::run_message::run_executor_api,
     );
     module.register_async_fn(crate::tvm::run_tvm, crate::tvm::run_message::run_tvm_api);
     module.register_async_fn(crate::tvm::run_get, crate::tvm::run_get::run_get_api);
+    #[cfg(feature = "proofs")]
+    module.register_async_fn(
+        crate::tvm::run_system_getter,
+        crate::tvm::system_getter::run_system_getter_api,
+    );
+    module.register_async_fn_with_callback(
+        super::tvm::run_tvm_paginated,
+        super::tvm::run_tvm_paginated_api,
+    );
+    module.register_async_fn(
+        crate::tvm::simulate_and_summarize,
+        crate::tvm::simulation::simulate_and_summarize_api,
+    );
+    module.register_async_fn(
+        crate::tvm::snapshot_account,
+        crate::tvm::snapshots::snapshot_account_api,
+    );
+    module.register_sync_fn(crate::tvm::fork, crate::tvm::snapshots::fork_api);
+    module.register_async_fn(
+        crate::tvm::get_snapshot_account,
+        crate::tvm::snapshots::get_snapshot_account_api,
+    );
+    module.register_sync_fn(
+        crate::tvm::release_snapshot,
+        crate::tvm::snapshots::release_snapshot_api,
+    );
     module.register();
 }
 
@@ -560,6 +897,12 @@ fn register_utils(handlers: &mut RuntimeHandlers) {
         crate::utils::get_address_type,
         crate::utils::conversion::get_address_type_api,
     );
+    #[cfg(feature = "net")]
+    module.register_async_fn(
+        crate::utils::suggest_address_format,
+        crate::utils::address_format::suggest_address_format_api,
+    );
+    #[cfg(feature = "tvm-executor")]
     module.register_async_fn(
         crate::utils::calc_storage_fee,
         crate::utils::calc_storage_fee::calc_storage_fee_api,
@@ -569,14 +912,25 @@ fn register_utils(handlers: &mut RuntimeHandlers) {
         super::utils::decompress_zstd,
         super::utils::decompress_zstd_api,
     );
+    module.register_type::<crate::utils::PaymentUrlScheme>();
+    module.register_sync_fn(
+        crate::utils::encode_payment_url,
+        crate::utils::payment_url::encode_payment_url_api,
+    );
+    module.register_sync_fn(
+        crate::utils::decode_payment_url,
+        crate::utils::payment_url::decode_payment_url_api,
+    );
     module.register();
 }
 
+#[cfg(feature = "debot")]
 /// [UNSTABLE](UNSTABLE.md) Module for working with debot.
 #[derive(ApiModule)]
 #[api_module(name = "debot")]
 pub struct DebotModule;
 
+#[cfg(feature = "debot")]
 fn register_debot(handlers: &mut RuntimeHandlers) {
     let mut module = ModuleReg::new::<DebotModule>(handlers);
     module.register_error_code::<crate::debot::ErrorCode>();
@@ -599,11 +953,13 @@ fn register_debot(handlers: &mut RuntimeHandlers) {
     module.register();
 }
 
+#[cfg(feature = "proofs")]
 /// [UNSTABLE](UNSTABLE.md) Module for proving data, retrieved from TONOS API.
 #[derive(ApiModule)]
 #[api_module(name = "proofs")]
 pub struct ProofsModule;
 
+#[cfg(feature = "proofs")]
 fn register_proofs(handlers: &mut RuntimeHandlers) {
     let mut module = ModuleReg::new::<ProofsModule>(handlers);
     module.register_error_code::<crate::proofs::ErrorCode>();
@@ -611,10 +967,15 @@ fn register_proofs(handlers: &mut RuntimeHandlers) {
     module.register_type::<crate::proofs::ParamsOfProofBlockData>();
     module.register_type::<crate::proofs::ParamsOfProofTransactionData>();
     module.register_type::<crate::proofs::ParamsOfProofMessageData>();
+    module.register_type::<crate::proofs::AttestedDataKind>();
+    module.register_type::<crate::proofs::ProofAttestation>();
+    module.register_type::<crate::proofs::FieldVerdict>();
+    module.register_type::<crate::proofs::TransactionProofResult>();
 
-    module.register_async_fn(
-        crate::proofs::proof_block_data,
-        crate::proofs::proof_block_data_api,
+    module.register_type::<crate::proofs::ProofChainProgressEvent>();
+    module.register_async_fn_with_callback(
+        super::proofs::proof_block_data,
+        super::proofs::proof_block_data_api,
     );
     module.register_async_fn(
         crate::proofs::proof_transaction_data,
@@ -624,6 +985,50 @@ fn register_proofs(handlers: &mut RuntimeHandlers) {
         crate::proofs::proof_message_data,
         crate::proofs::proof_message_data_api,
     );
+    module.register_async_fn(
+        crate::proofs::set_trusted_key_block,
+        crate::proofs::set_trusted_key_block_api,
+    );
+    module.register_async_fn(
+        crate::proofs::verify_local_cache,
+        crate::proofs::verify_local_cache_api,
+    );
+    module.register_async_fn(
+        crate::proofs::attest,
+        crate::proofs::attestation::attest_api,
+    );
+    module.register_sync_fn(
+        crate::proofs::verify_attestation,
+        crate::proofs::attestation::verify_attestation_api,
+    );
+    module.register_async_fn(
+        crate::proofs::proof_block_field,
+        crate::proofs::fields::proof_block_field_api,
+    );
+    module.register_async_fn(
+        crate::proofs::proof_transaction_field,
+        crate::proofs::fields::proof_transaction_field_api,
+    );
+    module.register_async_fn(
+        crate::proofs::proof_transaction_list,
+        crate::proofs::transaction_list::proof_transaction_list_api,
+    );
+    module.register_async_fn(
+        crate::proofs::get_forensic_bundle,
+        crate::proofs::forensics::get_forensic_bundle_api,
+    );
+    module.register_async_fn_no_args(
+        crate::proofs::prefetch_key_blocks,
+        crate::proofs::prefetch::prefetch_key_blocks_api,
+    );
+    module.register_async_fn_no_args(
+        crate::proofs::export_trusted_state,
+        crate::proofs::trusted_state::export_trusted_state_api,
+    );
+    module.register_async_fn(
+        crate::proofs::import_trusted_state,
+        crate::proofs::trusted_state::import_trusted_state_api,
+    );
     module.register();
 }
 
@@ -632,10 +1037,15 @@ pub(crate) fn register_modules(handlers: &mut RuntimeHandlers) {
     register_crypto(handlers);
     register_abi(handlers);
     register_boc(handlers);
+    #[cfg(feature = "net")]
     register_processing(handlers);
     register_utils(handlers);
+    #[cfg(feature = "tvm-executor")]
     register_tvm(handlers);
+    #[cfg(feature = "net")]
     register_net(handlers);
+    #[cfg(feature = "debot")]
     register_debot(handlers);
+    #[cfg(feature = "proofs")]
     register_proofs(handlers);
 }
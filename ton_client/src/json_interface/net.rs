@@ -13,10 +13,19 @@
  */
 
 use super::request::Request;
+use crate::client::event_sink::{post_event, EventKind};
 use crate::client::ClientContext;
 use crate::error::ClientResult;
-use crate::net::{ParamsOfSubscribeCollection, ResultOfSubscribeCollection, ResultOfSubscription};
+use crate::net::{
+    AccountsSyncEvent, BlockHeader, ParamsOfSubscribeBlockHeaders, ParamsOfSyncAccounts,
+    ResultOfSyncAccounts, ParamsOfSubscribeCollection,
+    ResultOfSubscribeCollection, ResultOfSubscription, SubscriptionEvent,
+};
 use crate::net::subscriptions::ParamsOfSubscribe;
+use crate::net::transaction_tree::{
+    ParamsOfQueryTransactionTree, ResultOfQueryTransactionTree, TransactionTreeNodeEvent,
+    TransactionTreeResponseType,
+};
 
 /// Creates a collection subscription
 ///
@@ -36,15 +45,7 @@ use crate::net::subscriptions::ParamsOfSubscribe;
 /// This reconnection sequence can take significant time.
 /// All of this time the client is disconnected from the network.
 ///
-/// Bad news is that all blockchain changes that happened while
-/// the client was disconnected are lost.
-///
-/// Good news is that the client report errors to the callback when
-/// it loses and resumes connection.
-///
-/// So, if the lost changes are important to the application then
-/// the application must handle these error reports.
-///
+/// The client report errors to the callback when it loses and resumes connection.
 /// Library reports errors with `responseType` == 101
 /// and the error object passed via `params`.
 ///
@@ -52,7 +53,18 @@ use crate::net::subscriptions::ParamsOfSubscribe;
 /// the application receives callback with
 /// `responseType` == 101 and `params.code` == 614 (NetworkModuleResumed).
 ///
-/// Application can use several ways to handle this situation:
+/// Then the library queries the blockchain changes that happened while
+/// the client was disconnected and passes them to the callback as a regular
+/// subscription data. Missed items are queried for the `transactions`,
+/// `messages`, `accounts`, `blocks` and `block_signatures` collections starting
+/// from the `lt` (or `gen_utime`) of the last received item. Items received
+/// by both the query and the restarted subscription are passed only once.
+///
+/// After that the application receives callback with `responseType` == 102
+/// and the `SubscriptionEvent::Resumed` passed via `params`.
+/// If `gap_recovered` is `false` then some changes could be lost
+/// (e.g. no items were received before the connection was lost or there are too many
+/// missed items) and the application must handle this situation:
 /// - If application monitors changes for the single blockchain
 /// object (for example specific account):  application
 /// can perform a query for this object and handle actual data as a
@@ -67,17 +79,29 @@ pub(crate) async fn subscribe_collection(
     params: ParamsOfSubscribeCollection,
     callback: std::sync::Arc<Request>,
 ) -> ClientResult<ResultOfSubscribeCollection> {
+    let event_context = context.clone();
+    let event_request = callback.clone();
+    let event_callback = move |event: SubscriptionEvent| {
+        post_event(&event_context, EventKind::SubscriptionEvent, &event);
+        event_request.response(event, crate::net::SubscriptionResponseType::Event as u32);
+        futures::future::ready(())
+    };
+    let sink_context = context.clone();
     let callback = move |result: ClientResult<ResultOfSubscription>| {
         match result {
             Ok(result) => {
+                post_event(&sink_context, EventKind::SubscriptionData, &result);
                 callback.response(result, crate::net::SubscriptionResponseType::Ok as u32)
             }
-            Err(err) => callback.response(err, crate::net::SubscriptionResponseType::Error as u32),
+            Err(err) => {
+                post_event(&sink_context, EventKind::SubscriptionError, &err);
+                callback.response(err, crate::net::SubscriptionResponseType::Error as u32)
+            }
         }
         futures::future::ready(())
     };
 
-    crate::net::subscribe_collection(context, params, callback).await
+    crate::net::subscribe_collection_with_events(context, params, callback, event_callback).await
 }
 
 /// Creates a subscription
@@ -123,15 +147,153 @@ pub(crate) async fn subscribe(
     params: ParamsOfSubscribe,
     callback: std::sync::Arc<Request>,
 ) -> ClientResult<ResultOfSubscribeCollection> {
+    let sink_context = context.clone();
     let callback = move |result: ClientResult<ResultOfSubscription>| {
         match result {
             Ok(result) => {
+                post_event(&sink_context, EventKind::SubscriptionData, &result);
                 callback.response(result, crate::net::SubscriptionResponseType::Ok as u32)
             }
-            Err(err) => callback.response(err, crate::net::SubscriptionResponseType::Error as u32),
+            Err(err) => {
+                post_event(&sink_context, EventKind::SubscriptionError, &err);
+                callback.response(err, crate::net::SubscriptionResponseType::Error as u32)
+            }
         }
         futures::future::ready(())
     };
 
     crate::net::subscribe(context, params, callback).await
 }
+
+/// Creates a subscription to the block headers
+///
+/// Delivers compact typed headers (id, seq_no, shard, gen_utime, tr_count, file_hash)
+/// of the new blocks satisfying the `filter` instead of full block documents.
+/// Intended for light clients that only need chain progress signals.
+///
+/// If `verify_proofs` is set, each block is proven before its header is passed to the
+/// callback. Blocks failed the proof check are reported as errors.
+///
+/// Headers are reported with `responseType` == 100, errors with `responseType` == 101.
+/// See `subscribe_collection` for notes on the connection loss handling.
+#[api_function]
+pub(crate) async fn subscribe_block_headers(
+    context: std::sync::Arc<ClientContext>,
+    params: ParamsOfSubscribeBlockHeaders,
+    callback: std::sync::Arc<Request>,
+) -> ClientResult<ResultOfSubscribeCollection> {
+    let sink_context = context.clone();
+    let callback = move |result: ClientResult<BlockHeader>| {
+        match result {
+            Ok(header) => {
+                post_event(&sink_context, EventKind::SubscriptionData, &header);
+                callback.response(header, crate::net::SubscriptionResponseType::Ok as u32)
+            }
+            Err(err) => {
+                post_event(&sink_context, EventKind::SubscriptionError, &err);
+                callback.response(err, crate::net::SubscriptionResponseType::Error as u32)
+            }
+        }
+        futures::future::ready(())
+    };
+
+    crate::net::subscribe_block_headers(context, params, callback).await
+}
+
+/// Starts the synchronization of the accounts set matching the filter.
+///
+/// The synchronization starts with the snapshot of the matching accounts reported
+/// by pages of `page_size` accounts. When the snapshot is completed, the account changes
+/// are reported by the subscription updates. The subscription is started before
+/// the snapshot, so no change is lost, and each account state is reported only
+/// if it is newer (by the `last_trans_lt`) than the already reported one.
+///
+/// The accounts that no longer match the filter are not reported by the subscription,
+/// so the set is reconciled periodically (each `reconcile_interval` ms) and after
+/// the subscription restarts: all of the matching accounts are queried again,
+/// and the changed and removed accounts are reported.
+///
+/// The `resume_state` of the `SnapshotCompleted` and `Reconciled` events can be persisted
+/// by the application and passed to the next `sync_accounts` call. In this case
+/// the snapshot is replaced by the reconciliation pass.
+///
+/// Events are reported with `responseType` == 100, errors with `responseType` == 101.
+/// Use `stop_sync_accounts` with the returned handle to stop the synchronization.
+#[api_function]
+pub(crate) async fn sync_accounts(
+    context: std::sync::Arc<ClientContext>,
+    params: ParamsOfSyncAccounts,
+    callback: std::sync::Arc<Request>,
+) -> ClientResult<ResultOfSyncAccounts> {
+    let sink_context = context.clone();
+    let callback = move |result: ClientResult<AccountsSyncEvent>| {
+        match result {
+            Ok(event) => {
+                post_event(&sink_context, EventKind::SubscriptionData, &event);
+                callback.response(event, crate::net::SubscriptionResponseType::Ok as u32)
+            }
+            Err(err) => {
+                post_event(&sink_context, EventKind::SubscriptionError, &err);
+                callback.response(err, crate::net::SubscriptionResponseType::Error as u32)
+            }
+        }
+        futures::future::ready(())
+    };
+
+    crate::net::sync_accounts(context, params, callback).await
+}
+
+/// Returns a tree of transactions triggered by a specific message.
+///
+/// Performs recursive retrieval of a transactions tree produced by a specific message:
+/// in_msg -> dst_transaction -> out_messages -> dst_transaction -> ...
+/// If the chain of transactions execution is in progress while the function is running,
+/// it will wait for the next transactions to appear until the full tree or more than
+/// `max_transactions` (50 by default) transactions are received. 
+///
+/// All the retrieved messages and transactions are included
+/// into `result.messages` and `result.transactions` respectively.
+///
+/// Function reads transactions layer by layer, by pages of 20 transactions. 
+/// 
+/// The retrieval prosess goes like this: 
+/// Let's assume we have an infinite chain of transactions and each transaction generates 5 messages.
+/// 1. Retrieve 1st message (input parameter) and corresponding transaction - put it into result.
+/// It is the first level of the tree of transactions - its root. 
+/// Retrieve 5 out message ids from the transaction for next steps.
+/// 2. Retrieve 5 messages and corresponding transactions on the 2nd layer. Put them into result. 
+/// Retrieve 5*5 out message ids from these transactions for next steps
+/// 3. Retrieve 20 (size of the page) messages and transactions (3rd layer) and 20*5=100 message ids (4th layer).
+/// 4. Retrieve the last 5 messages and 5 transactions on the 3rd layer + 15 messages and transactions (of 100) from the 4th layer
+/// + 25 message ids of the 4th layer + 75 message ids of the 5th layer.
+/// 5. Retrieve 20 more messages and 20 more transactions of the 4th layer + 100 more message ids of the 5th layer. 
+/// 6. Now we have 1+5+20+20+20 = 66 transactions, which is more than 50. Function exits with the tree of
+/// 1m->1t->5m->5t->25m->25t->35m->35t. If we see any message ids in the last transactions out_msgs, which don't have 
+/// corresponding messages in the function result, it means that the full tree was not received and we need to continue iteration. 
+///
+/// To summarize, it is guaranteed that each message in `result.messages` has the corresponding transaction
+/// in the `result.transactions`.
+/// But there is no guarantee that all messages from transactions `out_msgs` are
+/// presented in `result.messages`.
+/// So the application has to continue retrieval for missing messages if it requires.
+///
+/// **Limits and streaming**
+///
+/// Contracts sending many internal messages produce wide trees. The retrieval can be limited
+/// with `max_depth` (the depth of the messages, the input message has depth 0) and
+/// `max_transactions` (50 by default). If `send_events` is set, each retrieved message
+/// with its transaction is reported via `TransactionTreeNodeEvent` to the supplied
+/// callback as soon as it is resolved, so the application doesn't have to wait for
+/// the whole tree.
+#[api_function]
+pub(crate) async fn query_transaction_tree(
+    context: std::sync::Arc<ClientContext>,
+    params: ParamsOfQueryTransactionTree,
+    callback: std::sync::Arc<Request>,
+) -> ClientResult<ResultOfQueryTransactionTree> {
+    let callback = move |event: TransactionTreeNodeEvent| {
+        callback.response(event, TransactionTreeResponseType::TransactionTreeNode as u32);
+        futures::future::ready(())
+    };
+    crate::net::query_transaction_tree_with_callback(context, params, callback).await
+}
@@ -13,11 +13,13 @@
  */
 
 use super::request::Request;
+use crate::client::event_sink::{post_event, EventKind};
 use crate::client::ClientContext;
 use crate::error::ClientResult;
 use crate::processing::{
-    ParamsOfProcessMessage, ParamsOfSendMessage, ParamsOfWaitForTransaction, ProcessingEvent,
-    ProcessingResponseType, ResultOfProcessMessage, ResultOfSendMessage,
+    MessageMonitoringEvent, ParamsOfMonitorMessages, ParamsOfProcessMessage, ParamsOfSendMessage,
+    ParamsOfSendRawMessage, ParamsOfWaitForTransaction, ProcessingEvent, ProcessingResponseType,
+    ResultOfMonitorMessages, ResultOfProcessMessage, ResultOfSendMessage,
 };
 use std::sync::Arc;
 
@@ -45,7 +47,9 @@ pub(crate) async fn process_message(
     params: ParamsOfProcessMessage,
     request: std::sync::Arc<Request>,
 ) -> ClientResult<ResultOfProcessMessage> {
+    let sink_context = context.clone();
     let callback = move |event: ProcessingEvent| {
+        post_event(&sink_context, EventKind::ProcessingEvent, &event);
         request.response(event, ProcessingResponseType::ProcessingEvent as u32);
         futures::future::ready(())
     };
@@ -62,7 +66,9 @@ pub(crate) async fn send_message(
     params: ParamsOfSendMessage,
     callback: std::sync::Arc<Request>,
 ) -> ClientResult<ResultOfSendMessage> {
+    let sink_context = context.clone();
     let callback = move |result: ProcessingEvent| {
+        post_event(&sink_context, EventKind::ProcessingEvent, &result);
         callback.response(result, ProcessingResponseType::ProcessingEvent as u32);
         futures::future::ready(())
     };
@@ -97,15 +103,78 @@ pub(crate) async fn send_message(
 ///
 /// - If maximum block gen time is reached and no result transaction is found,
 /// the processing will exit with an error.
+///
+/// Concurrent calls waiting for the same message with the same `abi` and `finality`
+/// share a single network monitor, the number of such calls is reported in
+/// the `WillFetchNextBlock` event.
 #[api_function]
 pub(crate) async fn wait_for_transaction(
     context: Arc<ClientContext>,
     params: ParamsOfWaitForTransaction,
     callback: std::sync::Arc<Request>,
 ) -> ClientResult<ResultOfProcessMessage> {
+    let sink_context = context.clone();
     let callback = move |result: ProcessingEvent| {
+        post_event(&sink_context, EventKind::ProcessingEvent, &result);
         callback.response(result, ProcessingResponseType::ProcessingEvent as u32);
         futures::future::ready(())
     };
     crate::processing::wait_for_transaction(context, params, callback).await
 }
+
+/// Sends the message produced outside of the library and monitors its processing.
+///
+/// The message (e.g. signed by a hardware wallet or another SDK) is checked before sending:
+/// it must be an external inbound message, addressed to `dst` if specified, with
+/// the destination matching the attached state init, not expired, and decodable with
+/// `abi` if specified.
+///
+/// Then the message is sent and the result transaction is awaited as `process_message` does.
+/// The message can't be re-encoded, so if the transaction is not found within
+/// `message_processing_timeout`, the same message is sent again until the `expire` time.
+///
+/// The intermediate events are switched on/off by `send_events` flag
+/// and logged into the supplied callback function.
+#[api_function]
+pub(crate) async fn send_raw_message(
+    context: Arc<ClientContext>,
+    params: ParamsOfSendRawMessage,
+    callback: std::sync::Arc<Request>,
+) -> ClientResult<ResultOfProcessMessage> {
+    let sink_context = context.clone();
+    let callback = move |result: ProcessingEvent| {
+        post_event(&sink_context, EventKind::ProcessingEvent, &result);
+        callback.response(result, ProcessingResponseType::ProcessingEvent as u32);
+        futures::future::ready(())
+    };
+    crate::processing::send_raw_message(context, params, callback).await
+}
+
+/// Starts the monitoring of the sent messages.
+///
+/// Each message is specified by its hash and expiration time. The transactions of all
+/// the messages are received by a single subscription, so it is cheaper than
+/// `wait_for_transaction` per message when many messages are sent at once.
+///
+/// Each message is resolved by one event passed to the callback:
+/// - `Finalized` - the message is processed by a successful transaction;
+/// - `Rejected` - the message is processed by an aborted transaction;
+/// - `Expired` - no transaction is found until the message expiration time
+///   plus `message_processing_timeout`.
+///
+/// The monitoring is finished when all of the messages are resolved.
+/// Use `stop_monitoring` with the returned handle to finish it earlier.
+#[api_function]
+pub(crate) async fn monitor_messages(
+    context: Arc<ClientContext>,
+    params: ParamsOfMonitorMessages,
+    callback: std::sync::Arc<Request>,
+) -> ClientResult<ResultOfMonitorMessages> {
+    let sink_context = context.clone();
+    let callback = move |event: MessageMonitoringEvent| {
+        post_event(&sink_context, EventKind::MessageMonitoringEvent, &event);
+        callback.response(event, ProcessingResponseType::MonitoringEvent as u32);
+        futures::future::ready(())
+    };
+    crate::processing::monitor_messages(context, params, callback).await
+}
@@ -0,0 +1,96 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use super::request::Request;
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use crate::proofs::{ParamsOfProofBlockData, ProofChainProgressEvent, ProofsResponseType};
+use std::sync::Arc;
+
+/// Proves that a given block's data, which is queried from TONOS API, can be trusted.
+/// 
+/// This function checks block proofs and compares given data with the proven.
+/// If the given data differs from the proven, the exception will be thrown.
+/// The input param is a single block's JSON object, which was queried from DApp server using
+/// functions such as `net.query`, `net.query_collection` or `net.wait_for_collection`.
+/// If block's BOC is not provided in the JSON, it will be queried from DApp server
+/// (in this case it is required to provide at least `id` of block).
+///
+/// Please note, that joins (like `signatures` in `Block`) are separated entities and not supported,
+/// so function will throw an exception in a case if JSON being checked has such entities in it.
+///
+/// If `cache_in_local_storage` in config is set to `true` (default), downloaded proofs and
+/// master-chain BOCs are saved into the persistent local storage (e.g. file system for native
+/// environments or browser's IndexedDB for the web); otherwise all the data is cached only in
+/// memory in current client's context and will be lost after destruction of the client.
+///
+/// **Why Proofs are needed**
+///
+/// Proofs are needed to ensure that the data downloaded from a DApp server is real blockchain
+/// data. Checking proofs can protect from the malicious DApp server which can potentially provide
+/// fake data, or also from "Man in the Middle" attacks class.
+///
+/// **What Proofs are**
+///
+/// Simply, proof is a list of signatures of validators', which have signed this particular master-
+/// block.
+///
+/// The very first validator set's public keys are included in the zero-state. Whe know a root hash
+/// of the zero-state, because it is stored in the network configuration file, it is our authority
+/// root. For proving zero-state it is enough to calculate and compare its root hash.
+///
+/// In each new validator cycle the validator set is changed. The new one is stored in a key-block,
+/// which is signed by the validator set, which we already trust, the next validator set will be
+/// stored to the new key-block and signed by the current validator set, and so on.
+///
+/// In order to prove any block in the master-chain we need to check, that it has been signed by
+/// a trusted validator set. So we need to check all key-blocks' proofs, started from the zero-state
+/// and until the block, which we want to prove. But it can take a lot of time and traffic to
+/// download and prove all key-blocks on a client. For solving this, special trusted blocks are used
+/// in TON-SDK.
+///
+/// The trusted block is the authority root, as well, as the zero-state. Each trusted block is the
+/// `id` (e.g. `root_hash`) of the already proven key-block. There can be plenty of trusted
+/// blocks, so there can be a lot of authority roots. The hashes of trusted blocks for MainNet
+/// and DevNet are hardcoded in SDK in a separated binary file (trusted_key_blocks.bin) and can 
+/// be updated for each release.
+/// In future SDK releases, one will also be able to provide their hashes of trusted blocks for
+/// other networks, besides for MainNet and DevNet.
+/// By using trusted key-blocks, in order to prove any block, we can prove chain of key-blocks to
+/// the closest previous trusted key-block, not only to the zero-state.
+///
+/// But shard-blocks don't have proofs on DApp server. In this case, in order to prove any shard-
+/// block data, we search for a corresponding master-block, which contains the root hash of this
+/// shard-block, or some shard block which is linked to that block in shard-chain. After proving
+/// this master-block, we traverse through each link and calculate and compare hashes with links,
+/// one-by-one. After that we can ensure that this shard-block has also been proven.
+///
+/// **Progress**
+///
+/// Downloading of the key blocks chain can take minutes when there are no cached proofs close
+/// to the block. If `send_events` is set, each verified key block of the chain is reported
+/// via `ProofChainProgressEvent` to the supplied callback, so the application can display
+/// "verified N of M key blocks".
+#[api_function]
+pub(crate) async fn proof_block_data(
+    context: Arc<ClientContext>,
+    params: ParamsOfProofBlockData,
+    callback: std::sync::Arc<Request>,
+) -> ClientResult<()> {
+    let callback = move |event: ProofChainProgressEvent| {
+        callback.response(event, ProofsResponseType::ProofChainProgress as u32);
+        futures::future::ready(())
+    };
+    crate::proofs::proof_block_data_with_progress(context, params, callback).await
+}
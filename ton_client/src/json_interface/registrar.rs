@@ -14,8 +14,11 @@
 
 use super::handlers::{
     CallHandler, CallNoArgsHandler, SpawnHandler, SpawnHandlerAppObject,
-    SpawnHandlerAppObjectNoArgs, SpawnHandlerCallback, SpawnNoArgsHandler,
+    SpawnHandlerAppObjectNoArgs, SpawnNoArgsHandler,
 };
+#[cfg(feature = "tvm-executor")]
+use super::handlers::SpawnHandlerCallback;
+#[cfg(feature = "tvm-executor")]
 use super::request::Request;
 use super::runtime::RuntimeHandlers;
 use crate::client::{AppObject, ClientContext};
@@ -24,6 +27,7 @@ use api_info::{ApiModule, ApiType, Module};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::future::Future;
+#[cfg(feature = "tvm-executor")]
 use std::sync::Arc;
 
 pub(crate) struct ModuleReg<'h> {
@@ -122,6 +126,8 @@ impl<'h> ModuleReg<'h> {
         );
     }
 
+    // Functions with callbacks are provided by `tvm` and the modules depending on it
+    #[cfg(feature = "tvm-executor")]
     pub fn register_async_fn_with_callback<P, R, F>(
         &mut self,
         handler: fn(context: std::sync::Arc<ClientContext>, params: P, callback: Arc<Request>) -> F,
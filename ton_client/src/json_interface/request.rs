@@ -20,6 +20,9 @@ use crate::{
 use serde::Serialize;
 use std::ffi::c_void;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+type FinishObserver = Box<dyn FnOnce(u32, &str) + Send>;
 
 enum ResponseHandlerImpl {
     Rust(u32, ResponseHandler),
@@ -31,6 +34,9 @@ enum ResponseHandlerImpl {
 pub struct Request {
     response_handler: ResponseHandlerImpl,
     finished: AtomicBool,
+    finish_observer: Mutex<Option<FinishObserver>>,
+    chunk_size: usize,
+    trace_id: Option<String>,
 }
 
 impl Request {
@@ -38,6 +44,9 @@ impl Request {
         Self {
             response_handler: ResponseHandlerImpl::Rust(request_id, response_handler),
             finished: AtomicBool::new(false),
+            finish_observer: Mutex::new(None),
+            chunk_size: 0,
+            trace_id: None,
         }
     }
 
@@ -45,6 +54,9 @@ impl Request {
         Self {
             response_handler: ResponseHandlerImpl::C(request_id, response_handler),
             finished: AtomicBool::new(false),
+            finish_observer: Mutex::new(None),
+            chunk_size: 0,
+            trace_id: None,
         }
     }
 
@@ -55,6 +67,9 @@ impl Request {
         Self {
             response_handler: ResponseHandlerImpl::RustPtr(request_ptr as usize, response_handler),
             finished: AtomicBool::new(false),
+            finish_observer: Mutex::new(None),
+            chunk_size: 0,
+            trace_id: None,
         }
     }
 
@@ -65,6 +80,36 @@ impl Request {
         Self {
             response_handler: ResponseHandlerImpl::CPtr(request_ptr as usize, response_handler),
             finished: AtomicBool::new(false),
+            finish_observer: Mutex::new(None),
+            chunk_size: 0,
+            trace_id: None,
+        }
+    }
+
+    /// Sets the function called with the final response type and JSON
+    pub(crate) fn set_finish_observer(&mut self, observer: FinishObserver) {
+        self.finish_observer = Mutex::new(Some(observer));
+    }
+
+    /// Sets the maximum size of the final result JSON passed to the handler at once.
+    /// Larger results are delivered by `ResponseChunk` responses. `0` disables chunking.
+    pub(crate) fn set_chunk_size(&mut self, chunk_size: usize) {
+        self.chunk_size = chunk_size;
+    }
+
+    /// Sets the trace id of the call. It is added to the data of the error result.
+    pub(crate) fn set_trace_id(&mut self, trace_id: Option<String>) {
+        self.trace_id = trace_id;
+    }
+
+    pub(crate) fn trace_id(&self) -> Option<&String> {
+        self.trace_id.as_ref()
+    }
+
+    pub(crate) fn finish_with_json(&self, result: ClientResult<String>) {
+        match result {
+            Ok(json) => self.call_response_handler(json, ResponseType::Success as u32, true),
+            Err(error) => self.finish_with_error(error),
         }
     }
 
@@ -81,16 +126,25 @@ impl Request {
     }
 
     pub fn finish_with_error(&self, error: ClientError) {
-        self.response_serialize(error, ResponseType::Error as u32, true);
+        self.response_serialize(self.add_trace_id(error), ResponseType::Error as u32, true);
     }
 
     fn response_result_with_finished(&self, result: ClientResult<impl Serialize>, finished: bool) {
         match result {
             Ok(success) => self.response_serialize(success, ResponseType::Success as u32, finished),
-            Err(error) => self.response_serialize(error, ResponseType::Error as u32, finished),
+            Err(error) => {
+                self.response_serialize(self.add_trace_id(error), ResponseType::Error as u32, finished)
+            }
         }
     }
 
+    fn add_trace_id(&self, mut error: ClientError) -> ClientError {
+        if let Some(trace_id) = &self.trace_id {
+            error.data[crate::client::trace::TRACE_ID_FIELD] = trace_id.clone().into();
+        }
+        error
+    }
+
     fn response_serialize(&self, params: impl Serialize, response_type: u32, finished: bool) {
         match serde_json::to_string(&params) {
             Ok(result) => self.call_response_handler(result, response_type, finished),
@@ -119,6 +173,24 @@ impl Request {
         if was_finished {
             return;
         }
+        if finished {
+            if let Some(observer) = self.finish_observer.lock().unwrap_or_else(PoisonError::into_inner).take() {
+                observer(response_type, &params_json);
+            }
+            if response_type == ResponseType::Success as u32
+                && self.chunk_size > 0
+                && params_json.len() > self.chunk_size
+            {
+                for chunk in split_chunks(&params_json, self.chunk_size) {
+                    self.invoke_handler(chunk.to_string(), ResponseType::ResponseChunk as u32, false);
+                }
+                return self.invoke_handler(String::new(), response_type, true);
+            }
+        }
+        self.invoke_handler(params_json, response_type, finished);
+    }
+
+    fn invoke_handler(&self, params_json: String, response_type: u32, finished: bool) {
         match self.response_handler {
             ResponseHandlerImpl::Rust(id, handler) => {
                 handler(id, params_json, response_type, finished)
@@ -139,6 +211,26 @@ impl Request {
     }
 }
 
+/// Splits the string into parts not longer than `chunk_size` bytes on the char boundaries
+pub(crate) fn split_chunks(s: &str, chunk_size: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = s;
+    while !rest.is_empty() {
+        let mut end = std::cmp::min(chunk_size, rest.len());
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        // Chunk size is less than the first char length
+        if end == 0 {
+            end = rest.chars().next().map(|c| c.len_utf8()).unwrap_or(rest.len());
+        }
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    chunks
+}
+
 impl Drop for Request {
     fn drop(&mut self) {
         self.call_response_handler("".into(), ResponseType::Nop as u32, true)
@@ -12,14 +12,18 @@
  *
  */
 
+use super::interceptors::{ApiCallOutcome, InterceptedCall};
+use super::interop::ResponseType;
 use super::modules::register_modules;
 use super::request::Request;
+use crate::client::health::catch_panic_sync;
+use crate::client::trace::{add_trace_id, extract_trace_id, with_trace_id};
 use crate::client::{ClientConfig, ClientContext, Error};
 use crate::error::ClientResult;
 use crate::ContextHandle;
 use api_info::{Module, API};
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 
 pub(crate) trait SyncHandler {
     fn handle(&self, context: Arc<ClientContext>, params_json: &str) -> ClientResult<String>;
@@ -94,7 +98,7 @@ impl Runtime {
     }
 
     fn contexts() -> MutexGuard<'static, RuntimeContexts> {
-        CONTEXTS.lock().unwrap()
+        CONTEXTS.lock().unwrap_or_else(PoisonError::into_inner)
     }
 
     pub fn dispatch_sync(
@@ -102,9 +106,55 @@ impl Runtime {
         function_name: String,
         params_json: String,
     ) -> ClientResult<String> {
-        match Self::handlers().sync_handlers.get(&function_name) {
-            Some(handler) => handler.handle(context, params_json.as_str()),
-            None => Err(Error::unknown_function(&function_name)),
+        let (params_json, trace_id) = extract_trace_id(params_json);
+        with_trace_id(trace_id.as_ref(), || {
+            Self::dispatch_sync_traced(context, function_name, params_json, trace_id.clone())
+                .map_err(add_trace_id)
+        })
+    }
+
+    fn dispatch_sync_traced(
+        context: Arc<ClientContext>,
+        function_name: String,
+        params_json: String,
+        trace_id: Option<String>,
+    ) -> ClientResult<String> {
+        if let Some(trace_id) = &trace_id {
+            log::debug!("API call {} trace_id {}", function_name, trace_id);
+        }
+        let intercepted = InterceptedCall::new(
+            context.interceptors.list(),
+            &function_name,
+            &params_json,
+            trace_id,
+        );
+        let intercepted = match intercepted {
+            Some(intercepted) => intercepted,
+            None => return Self::call_sync(context, &function_name, &params_json),
+        };
+
+        if let Some(result) = intercepted.before() {
+            intercepted.after(Self::outcome(&result, 0, true));
+            return result;
+        }
+        let started = context.env.now_ms();
+        let result = Self::call_sync(context.clone(), &function_name, &params_json);
+        let duration = context.env.now_ms().saturating_sub(started);
+        intercepted.after(Self::outcome(&result, duration, false));
+        result
+    }
+
+    fn call_sync(
+        context: Arc<ClientContext>,
+        function_name: &str,
+        params_json: &str,
+    ) -> ClientResult<String> {
+        match Self::handlers().sync_handlers.get(function_name) {
+            Some(handler) => {
+                let context_copy = context.clone();
+                catch_panic_sync(&context_copy, || handler.handle(context, params_json))
+            }
+            None => Err(Error::unknown_function(function_name)),
         }
     }
 
@@ -112,14 +162,56 @@ impl Runtime {
         context: Arc<ClientContext>,
         function_name: String,
         params_json: String,
-        request: Request,
+        mut request: Request,
     ) {
+        if let Some(chunk_size) = context.config.response_chunk_size {
+            request.set_chunk_size(chunk_size as usize);
+        }
+        let (params_json, trace_id) = extract_trace_id(params_json);
+        if let Some(trace_id) = &trace_id {
+            log::debug!("API call {} trace_id {}", function_name, trace_id);
+        }
+        request.set_trace_id(trace_id.clone());
+        let intercepted = InterceptedCall::new(
+            context.interceptors.list(),
+            &function_name,
+            &params_json,
+            trace_id,
+        );
+        if let Some(intercepted) = intercepted {
+            if let Some(result) = intercepted.before() {
+                intercepted.after(Self::outcome(&result, 0, true));
+                return request.finish_with_json(result);
+            }
+            let env = context.env.clone();
+            let started = env.now_ms();
+            request.set_finish_observer(Box::new(move |response_type: u32, json: &str| {
+                let result: ClientResult<String> = if response_type == ResponseType::Success as u32 {
+                    Ok(json.to_string())
+                } else {
+                    Err(serde_json::from_str(json)
+                        .unwrap_or_else(|err| Error::callback_params_cant_be_converted_to_json(err)))
+                };
+                let duration = env.now_ms().saturating_sub(started);
+                intercepted.after(Self::outcome(&result, duration, false));
+            }));
+        }
+
         match Self::handlers().async_handlers.get(&function_name) {
             Some(handler) => handler.handle(context, params_json, request),
             None => request.finish_with_error(Error::unknown_function(&function_name)),
         }
     }
 
+    fn outcome(result: &ClientResult<String>, duration: u64, intercepted: bool) -> ApiCallOutcome {
+        ApiCallOutcome {
+            duration,
+            error_code: result.as_ref().err().map(|err| err.code),
+            result_json: result.as_ref().ok().cloned(),
+            intercepted,
+        }
+    }
+
     pub fn api() -> &'static API {
         &Self::handlers().api
     }
@@ -133,12 +225,15 @@ impl Runtime {
         let config = serde_json::from_str::<ClientConfig>(config_json)
             .map_err(|err| Error::invalid_params(config_json, err))?;
 
+        Ok(Self::register_context(ClientContext::new(config)?))
+    }
+
+    pub(crate) fn register_context(context: ClientContext) -> ContextHandle {
         let mut contexts = Self::contexts();
         let handle = contexts.next_context_handle;
         contexts.next_context_handle = handle.wrapping_add(1);
-        let context = Arc::new(ClientContext::new(config)?);
-        contexts.contexts.insert(handle, context.clone());
-        Ok(handle)
+        contexts.contexts.insert(handle, Arc::new(context));
+        handle
     }
 
     pub fn destroy_context(handle: ContextHandle) {
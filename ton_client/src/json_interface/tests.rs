@@ -1,6 +1,10 @@
 use serde_json::Value;
+use std::sync::{Arc, Mutex};
 
+use super::interop::ResponseType;
+use super::request::{split_chunks, Request};
 use crate::tests::TestClient;
+use crate::{ApiCallInfo, ApiCallInterceptor, ApiCallOutcome};
 
 #[test]
 fn test_invalid_params_errors() {
@@ -135,3 +139,178 @@ fn check_client_error_msg(params: Value, expected_tips: &[&str], expected_helper
     }
 }
 
+
+#[derive(Default)]
+struct RecordingInterceptor {
+    calls: Mutex<Vec<(String, Option<u32>, bool)>>,
+}
+
+impl ApiCallInterceptor for RecordingInterceptor {
+    fn before_call(&self, call: &ApiCallInfo) -> Option<crate::error::ClientResult<String>> {
+        if call.function_name == "crypto.sha256" {
+            Some(Ok(json!({ "hash": "mocked" }).to_string()))
+        } else {
+            None
+        }
+    }
+
+    fn after_call(&self, call: &ApiCallInfo, outcome: &ApiCallOutcome) {
+        assert_eq!(call.params_digest.len(), 64);
+        self.calls.lock().unwrap().push((
+            call.function_name.clone(),
+            outcome.error_code,
+            outcome.intercepted,
+        ));
+    }
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_interceptors() {
+    let client = TestClient::new();
+    let interceptor = Arc::new(RecordingInterceptor::default());
+    let id = crate::register_interceptor(client.handle(), interceptor.clone()).unwrap();
+
+    let result: Value = client
+        .request("crypto.sha256", json!({ "data": "" }))
+        .unwrap();
+    assert_eq!(result["hash"], "mocked");
+
+    let error = client
+        .request_async::<_, Value>("boc.get_boc_hash", json!({ "boc": "invalid" }))
+        .await
+        .unwrap_err();
+
+    let _: Value = client.request_no_params("client.version").unwrap();
+
+    assert_eq!(
+        *interceptor.calls.lock().unwrap(),
+        vec![
+            ("crypto.sha256".to_string(), None, true),
+            ("boc.get_boc_hash".to_string(), Some(error.code), false),
+            ("client.version".to_string(), None, false),
+        ]
+    );
+
+    crate::unregister_interceptor(client.handle(), id).unwrap();
+    let result: Value = client
+        .request("crypto.sha256", json!({ "data": "" }))
+        .unwrap();
+    assert_ne!(result["hash"], "mocked");
+    assert_eq!(interceptor.calls.lock().unwrap().len(), 3);
+}
+
+lazy_static! {
+    static ref CHUNKED_RESPONSES: Mutex<Vec<(String, u32, bool)>> = Mutex::new(Vec::new());
+}
+
+fn on_chunked_response(_request_id: u32, params_json: String, response_type: u32, finished: bool) {
+    CHUNKED_RESPONSES.lock().unwrap().push((params_json, response_type, finished));
+}
+
+#[test]
+fn test_response_chunks() {
+    assert_eq!(split_chunks("abcdefg", 3), vec!["abc", "def", "g"]);
+    assert_eq!(split_chunks("aПривет", 2), vec!["a", "П", "р", "и", "в", "е", "т"]);
+    assert_eq!(split_chunks("Привет", 1), vec!["П", "р", "и", "в", "е", "т"]);
+
+    let mut request = Request::new(1, on_chunked_response);
+    request.set_chunk_size(10);
+    let result = json!({ "boc": "x".repeat(25) });
+    request.finish_with_result(Ok(result.clone()));
+    drop(request);
+
+    let responses = std::mem::take(&mut *CHUNKED_RESPONSES.lock().unwrap());
+    let (last, chunks) = responses.split_last().unwrap();
+    assert_eq!(*last, (String::new(), ResponseType::Success as u32, true));
+    assert_eq!(chunks.len(), 4);
+    let mut json = String::new();
+    for (chunk, response_type, finished) in chunks {
+        assert_eq!(*response_type, ResponseType::ResponseChunk as u32);
+        assert!(!finished);
+        assert!(chunk.len() <= 10);
+        json.push_str(chunk);
+    }
+    assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), result);
+
+    // Errors and small results are not chunked
+    let mut request = Request::new(2, on_chunked_response);
+    request.set_chunk_size(10);
+    request.finish_with_result(Ok(json!({})));
+    drop(request);
+    assert_eq!(
+        *CHUNKED_RESPONSES.lock().unwrap(),
+        vec![("{}".to_string(), ResponseType::Success as u32, true)]
+    );
+}
+
+#[derive(Default)]
+struct TraceInterceptor {
+    calls: Mutex<Vec<(Option<String>, String)>>,
+}
+
+impl ApiCallInterceptor for TraceInterceptor {
+    fn after_call(&self, call: &ApiCallInfo, _outcome: &ApiCallOutcome) {
+        self.calls
+            .lock()
+            .unwrap()
+            .push((call.trace_id.clone(), call.params_digest.clone()));
+    }
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_trace_id() {
+    use crate::client::trace::{current_trace_id, extract_trace_id, Traced};
+
+    assert_eq!(
+        extract_trace_id(json!({ "data": "", "trace_id": "1" }).to_string()),
+        (json!({ "data": "" }).to_string(), Some("1".to_string()))
+    );
+    assert_eq!(extract_trace_id(String::new()), (String::new(), None));
+    // Only string trace ids are extracted
+    let params = json!({ "trace_id": 1 }).to_string();
+    assert_eq!(extract_trace_id(params.clone()), (params, None));
+
+    let trace_id = Traced::new(Some("2".to_string()), async { current_trace_id() }).await;
+    assert_eq!(trace_id, Some("2".to_string()));
+    assert_eq!(current_trace_id(), None);
+
+    let client = TestClient::new();
+
+    // tasks spawned by the call keep its trace id
+    let context = client.context();
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    Traced::new(Some("6".to_string()), async {
+        context.env.spawn(async move {
+            let _ = sender.send(current_trace_id());
+        });
+    })
+    .await;
+    assert_eq!(receiver.await.unwrap(), Some("6".to_string()));
+
+    let interceptor = Arc::new(TraceInterceptor::default());
+    let id = crate::register_interceptor(client.handle(), interceptor.clone()).unwrap();
+
+    let error = client
+        .request::<_, Value>("crypto.factorize", json!({ "composite": "zz", "trace_id": "3" }))
+        .unwrap_err();
+    assert_eq!(error.data["trace_id"], "3");
+
+    let error = client
+        .request_async::<_, Value>("boc.get_boc_hash", json!({ "boc": "invalid", "trace_id": "4" }))
+        .await
+        .unwrap_err();
+    assert_eq!(error.data["trace_id"], "4");
+
+    let _: Value = client
+        .request("crypto.sha256", json!({ "data": "", "trace_id": "5" }))
+        .unwrap();
+    let _: Value = client.request("crypto.sha256", json!({ "data": "" })).unwrap();
+
+    let calls = interceptor.calls.lock().unwrap().clone();
+    let trace_ids: Vec<Option<&str>> = calls.iter().map(|(id, _)| id.as_deref()).collect();
+    assert_eq!(trace_ids, vec![Some("3"), Some("4"), Some("5"), None]);
+    // Trace id doesn't affect the parameters digest
+    assert_eq!(calls[2].1, calls[3].1);
+
+    crate::unregister_interceptor(client.handle(), id).unwrap();
+}
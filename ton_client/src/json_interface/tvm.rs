@@ -0,0 +1,45 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use super::request::Request;
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use crate::tvm::{
+    PageFetchedEvent, PaginationResponseType, ParamsOfRunTvmPaginated, ResultOfRunTvmPaginated,
+};
+use std::sync::Arc;
+
+/// Executes a paginated get-method of the contract and merges all pages.
+///
+/// Repeatedly runs the getter on TVM via `run_tvm`, passing the cursor returned
+/// by the previous call into `cursor_param`, and concatenates the `items_output`
+/// fields of all pages into one collection. The iteration stops when the getter
+/// returns an empty page, a `null` or unchanged cursor, or when `max_pages` is reached.
+///
+/// Useful for reading huge mappings which can't be returned by a single getter call
+/// because of the gas limit.
+///
+/// Each fetched page is reported via `PageFetchedEvent` to the supplied callback.
+#[api_function]
+pub(crate) async fn run_tvm_paginated(
+    context: Arc<ClientContext>,
+    params: ParamsOfRunTvmPaginated,
+    callback: std::sync::Arc<Request>,
+) -> ClientResult<ResultOfRunTvmPaginated> {
+    let callback = move |event: PageFetchedEvent| {
+        callback.response(event, PaginationResponseType::PageFetched as u32);
+        futures::future::ready(())
+    };
+    crate::tvm::run_tvm_paginated(context, params, callback).await
+}
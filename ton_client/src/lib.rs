@@ -27,18 +27,24 @@ pub mod abi;
 pub mod boc;
 pub mod client;
 pub mod crypto;
+#[cfg(feature = "debot")]
 pub mod debot;
 pub mod encoding;
 pub mod error;
 pub mod json_interface;
+#[cfg(feature = "net")]
 pub mod net;
+// Processing functions require `net`, the output decoding and errors are used by `abi` and `tvm`
 pub mod processing;
+#[cfg(feature = "proofs")]
 pub mod proofs;
+#[cfg(feature = "tvm-executor")]
 pub mod tvm;
 pub mod utils;
 
 #[cfg(test)]
 mod tests;
 
+pub use self::json_interface::interceptors::{ApiCallInfo, ApiCallInterceptor, ApiCallOutcome};
 pub use self::json_interface::interop::*;
 pub use client::{ClientConfig, ClientContext};
@@ -0,0 +1,465 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+// Synchronization of the set of accounts matching a filter: the initial paged snapshot
+// is followed by the subscription updates and the periodic reconciliation passes.
+//
+// The subscription is started before the snapshot, so no update is lost between the pages
+// and the subscription start. The updates are applied only if they are newer (by the
+// `last_trans_lt`) than the already reported account state, so the order of the snapshot
+// pages, the subscription updates and the reconciliation passes does not matter.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use futures::{Future, FutureExt};
+use rand::RngCore;
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use crate::net::iterators::collection_iterator::and_filters;
+use crate::net::{
+    query_collection, subscribe_collection_with_events, unsubscribe, OrderBy,
+    ParamsOfQueryCollection, ParamsOfSubscribeCollection, ResultOfSubscription, SortDirection,
+    SubscriptionEvent, ACCOUNTS_COLLECTION,
+};
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const DEFAULT_RECONCILE_INTERVAL: u32 = 600000;
+/// Delay before the retry of the failed snapshot page query
+const SNAPSHOT_RETRY_DELAY: u64 = 1000;
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone, PartialEq)]
+pub struct SyncedAccount {
+    /// Account address
+    pub id: String,
+    /// Logical time of the last reported account transaction, decimal string
+    pub last_trans_lt: String,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone, PartialEq)]
+pub struct AccountsSyncState {
+    /// Accounts of the synchronized set with their last reported state versions
+    pub accounts: Vec<SyncedAccount>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone)]
+pub struct ParamsOfSyncAccounts {
+    /// Accounts filter, e.g. `{ "code_hash": { "eq": "..." } }`
+    pub filter: Option<Value>,
+    /// Projection (result) string of the reported accounts. The `id` and
+    /// `last_trans_lt` fields are always included.
+    pub result: String,
+    /// Number of accounts in the snapshot and reconciliation pages. Default is 50.
+    pub page_size: Option<u32>,
+    /// Interval between the reconciliation passes in ms. 0 disables the periodic
+    /// reconciliation. Default is 600000 (10 min).
+    pub reconcile_interval: Option<u32>,
+    /// State reported by the `SnapshotCompleted` or `Reconciled` event of the previous
+    /// synchronization. If specified, the snapshot is replaced by the reconciliation pass
+    /// reporting the changes since the state was reported.
+    pub resume_state: Option<AccountsSyncState>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone)]
+pub struct ResultOfSyncAccounts {
+    /// Synchronization handle. Must be passed to `stop_sync_accounts` to stop the
+    /// synchronization.
+    pub handle: u32,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone)]
+pub struct ParamsOfStopSyncAccounts {
+    /// Synchronization handle returned by `sync_accounts`.
+    pub handle: u32,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum AccountsSyncEvent {
+    /// Page of the initial snapshot.
+    Snapshot { accounts: Vec<Value> },
+    /// Snapshot is completed. Further changes are reported by `Updated`
+    /// and `Removed` events.
+    SnapshotCompleted { resume_state: AccountsSyncState },
+    /// Account is added to the set or its state is changed.
+    Updated { account: Value },
+    /// Account no longer matches the filter.
+    Removed { id: String },
+    /// Reconciliation pass is completed.
+    Reconciled {
+        /// Number of accounts updated by the pass
+        updated: u32,
+        /// Number of accounts removed by the pass
+        removed: u32,
+        resume_state: AccountsSyncState,
+    },
+}
+
+/// Parses the `last_trans_lt` of the account: hex (`0x` prefixed) or decimal string or number
+pub(crate) fn account_lt(account: &Value) -> Option<u64> {
+    match &account["last_trans_lt"] {
+        Value::String(lt) => match lt.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).ok(),
+            None => lt.parse().ok(),
+        },
+        Value::Number(lt) => lt.as_u64(),
+        _ => None,
+    }
+}
+
+/// Reported state versions of the synchronized accounts
+#[derive(Default)]
+pub(crate) struct SyncedAccounts {
+    accounts: HashMap<String, u64>,
+    // Accounts removed by the last reconciliation pass. Updates not newer than the removed
+    // state are delayed subscription updates and must not restore the account.
+    removed: HashMap<String, u64>,
+}
+
+impl SyncedAccounts {
+    pub fn from_state(state: &AccountsSyncState) -> Self {
+        Self {
+            accounts: state
+                .accounts
+                .iter()
+                .filter_map(|account| {
+                    Some((account.id.clone(), account.last_trans_lt.parse().ok()?))
+                })
+                .collect(),
+            removed: HashMap::new(),
+        }
+    }
+
+    pub fn state(&self) -> AccountsSyncState {
+        let mut accounts: Vec<SyncedAccount> = self
+            .accounts
+            .iter()
+            .map(|(id, lt)| SyncedAccount {
+                id: id.clone(),
+                last_trans_lt: lt.to_string(),
+            })
+            .collect();
+        accounts.sort_by(|a, b| a.id.cmp(&b.id));
+        AccountsSyncState { accounts }
+    }
+
+    /// Applies the received account state. Returns `true` if the state is newer
+    /// than the reported one, so it must be reported.
+    pub fn update(&mut self, account: &Value) -> bool {
+        let (id, lt) = match (account["id"].as_str(), account_lt(account)) {
+            (Some(id), Some(lt)) => (id, lt),
+            _ => return false,
+        };
+        let reported = self.accounts.get(id).or_else(|| self.removed.get(id));
+        if reported.map(|reported| *reported >= lt).unwrap_or(false) {
+            return false;
+        }
+        self.removed.remove(id);
+        self.accounts.insert(id.to_string(), lt);
+        true
+    }
+
+    /// Removes the accounts not found by the completed reconciliation pass
+    pub fn remove_missing(&mut self, found: &HashSet<String>) -> Vec<String> {
+        let mut removed: Vec<String> = self
+            .accounts
+            .keys()
+            .filter(|id| !found.contains(*id))
+            .cloned()
+            .collect();
+        removed.sort();
+        self.removed.clear();
+        for id in &removed {
+            if let Some(lt) = self.accounts.remove(id) {
+                self.removed.insert(id.clone(), lt);
+            }
+        }
+        removed
+    }
+}
+
+pub(crate) enum SyncAction {
+    Update(Value),
+    /// Subscription was restarted, so the updates could be lost
+    Reconcile,
+    Stop,
+}
+
+struct SyncHandle {
+    sender: mpsc::UnboundedSender<SyncAction>,
+    stopped: Arc<AtomicBool>,
+}
+
+/// Running synchronizations by the handle
+#[derive(Default)]
+pub(crate) struct AccountsSyncs {
+    syncs: Mutex<HashMap<u32, SyncHandle>>,
+}
+
+impl AccountsSyncs {
+    fn add(&self, handle: u32, sync: SyncHandle) {
+        self.syncs.lock().unwrap_or_else(PoisonError::into_inner).insert(handle, sync);
+    }
+
+    fn remove(&self, handle: u32) -> Option<SyncHandle> {
+        self.syncs.lock().unwrap_or_else(PoisonError::into_inner).remove(&handle)
+    }
+}
+
+struct AccountsSync<C> {
+    context: Arc<ClientContext>,
+    filter: Value,
+    result: String,
+    page_size: u32,
+    synced: SyncedAccounts,
+    stopped: Arc<AtomicBool>,
+    callback: C,
+}
+
+impl<F, C> AccountsSync<C>
+where
+    F: Future<Output = ()> + Send,
+    C: Fn(ClientResult<AccountsSyncEvent>) -> F + Send + Sync,
+{
+    async fn query_page(&self, cursor: &Option<String>) -> ClientResult<Vec<Value>> {
+        let filter = match cursor {
+            Some(cursor) => and_filters(&self.filter, &json!({ "id": { "gt": cursor } })),
+            None => self.filter.clone(),
+        };
+        query_collection(
+            self.context.clone(),
+            ParamsOfQueryCollection {
+                collection: ACCOUNTS_COLLECTION.to_string(),
+                filter: Some(filter),
+                result: self.result.clone(),
+                order: Some(vec![OrderBy {
+                    path: "id".to_string(),
+                    direction: SortDirection::ASC,
+                }]),
+                limit: Some(self.page_size),
+                ..Default::default()
+            },
+        )
+        .await
+        .map(|result| result.result)
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+
+    async fn snapshot(&mut self) {
+        let mut cursor = None;
+        while !self.is_stopped() {
+            let page = match self.query_page(&cursor).await {
+                Ok(page) => page,
+                Err(err) => {
+                    (self.callback)(Err(err)).await;
+                    let _ = self.context.env.set_timer(SNAPSHOT_RETRY_DELAY).await;
+                    continue;
+                }
+            };
+            let finished = page.len() < self.page_size as usize;
+            cursor = page
+                .last()
+                .and_then(|account| account["id"].as_str())
+                .map(|id| id.to_string());
+            let accounts: Vec<Value> =
+                page.into_iter().filter(|account| self.synced.update(account)).collect();
+            if !accounts.is_empty() {
+                (self.callback)(Ok(AccountsSyncEvent::Snapshot { accounts })).await;
+            }
+            if finished || cursor.is_none() {
+                let resume_state = self.synced.state();
+                (self.callback)(Ok(AccountsSyncEvent::SnapshotCompleted { resume_state })).await;
+                return;
+            }
+        }
+    }
+
+    async fn reconcile(&mut self) {
+        let mut cursor = None;
+        let mut found = HashSet::new();
+        let mut updated = 0;
+        while !self.is_stopped() {
+            let page = match self.query_page(&cursor).await {
+                Ok(page) => page,
+                Err(err) => {
+                    // incomplete pass can't detect the removed accounts
+                    (self.callback)(Err(err)).await;
+                    return;
+                }
+            };
+            let finished = page.len() < self.page_size as usize;
+            cursor = page
+                .last()
+                .and_then(|account| account["id"].as_str())
+                .map(|id| id.to_string());
+            for account in page {
+                if let Some(id) = account["id"].as_str() {
+                    found.insert(id.to_string());
+                }
+                if self.synced.update(&account) {
+                    updated += 1;
+                    (self.callback)(Ok(AccountsSyncEvent::Updated { account })).await;
+                }
+            }
+            if finished || cursor.is_none() {
+                let removed = self.synced.remove_missing(&found);
+                for id in &removed {
+                    (self.callback)(Ok(AccountsSyncEvent::Removed { id: id.clone() })).await;
+                }
+                let resume_state = self.synced.state();
+                (self.callback)(Ok(AccountsSyncEvent::Reconciled {
+                    updated,
+                    removed: removed.len() as u32,
+                    resume_state,
+                }))
+                .await;
+                return;
+            }
+        }
+    }
+
+    async fn run(
+        &mut self,
+        resumed: bool,
+        reconcile_interval: u32,
+        mut actions: mpsc::UnboundedReceiver<SyncAction>,
+    ) {
+        // the subscription updates received meanwhile are queued in `actions`
+        if resumed {
+            self.reconcile().await;
+        } else {
+            self.snapshot().await;
+        }
+        let context = self.context.clone();
+        while !self.is_stopped() {
+            let timer = match reconcile_interval {
+                0 => futures::future::pending().boxed(),
+                interval => context.env.set_timer(interval as u64).boxed(),
+            }
+            .fuse();
+            let action = actions.recv().fuse();
+            futures::pin_mut!(timer, action);
+            futures::select!(
+                action = action => match action {
+                    Some(SyncAction::Update(account)) => {
+                        if self.synced.update(&account) {
+                            (self.callback)(Ok(AccountsSyncEvent::Updated { account })).await;
+                        }
+                    }
+                    Some(SyncAction::Reconcile) => self.reconcile().await,
+                    Some(SyncAction::Stop) | None => break,
+                },
+                _ = timer => self.reconcile().await
+            );
+        }
+    }
+}
+
+/// Starts the synchronization of the accounts set matching the filter.
+/// Returns the synchronization handle.
+///
+/// The synchronization starts with the snapshot of the accounts reported by pages,
+/// or with the reconciliation pass if `resume_state` is specified. After that the account
+/// changes are reported by the subscription updates, and the reconciliation passes are
+/// performed periodically and after the subscription restarts.
+pub async fn sync_accounts<F: Future<Output = ()> + Send>(
+    context: Arc<ClientContext>,
+    params: ParamsOfSyncAccounts,
+    callback: impl Fn(ClientResult<AccountsSyncEvent>) -> F + Send + Sync + 'static,
+) -> ClientResult<ResultOfSyncAccounts> {
+    let handle = rand::thread_rng().next_u32();
+    let filter = params.filter.unwrap_or_else(|| json!({}));
+    let result = format!("id last_trans_lt {}", params.result);
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let updates = sender.clone();
+    let reconcile = sender.clone();
+    let subscription = subscribe_collection_with_events(
+        context.clone(),
+        ParamsOfSubscribeCollection {
+            collection: ACCOUNTS_COLLECTION.to_string(),
+            filter: Some(filter.clone()),
+            result: result.clone(),
+            mapping: None,
+            decode_body: None,
+        },
+        move |result: ClientResult<ResultOfSubscription>| {
+            if let Ok(result) = result {
+                let _ = updates.send(SyncAction::Update(result.result));
+            }
+            futures::future::ready(())
+        },
+        move |event: SubscriptionEvent| {
+            match event {
+                SubscriptionEvent::Resumed { .. } => {
+                    let _ = reconcile.send(SyncAction::Reconcile);
+                }
+            }
+            futures::future::ready(())
+        },
+    )
+    .await?;
+
+    let stopped = Arc::new(AtomicBool::new(false));
+    context.net.accounts_syncs.add(
+        handle,
+        SyncHandle {
+            sender,
+            stopped: stopped.clone(),
+        },
+    );
+    let resumed = params.resume_state.is_some();
+    let mut sync = AccountsSync {
+        context: context.clone(),
+        filter,
+        result,
+        page_size: params.page_size.unwrap_or(DEFAULT_PAGE_SIZE).max(1),
+        synced: params
+            .resume_state
+            .as_ref()
+            .map(SyncedAccounts::from_state)
+            .unwrap_or_default(),
+        stopped,
+        callback,
+    };
+    let reconcile_interval = params.reconcile_interval.unwrap_or(DEFAULT_RECONCILE_INTERVAL);
+    let sync_context = context.clone();
+    context.env.spawn(async move {
+        sync.run(resumed, reconcile_interval, receiver).await;
+        sync_context.net.accounts_syncs.remove(handle);
+        let _ = unsubscribe(sync_context, subscription).await;
+    });
+
+    Ok(ResultOfSyncAccounts { handle })
+}
+
+/// Stops the accounts synchronization started with `sync_accounts`.
+#[api_function]
+pub async fn stop_sync_accounts(
+    context: Arc<ClientContext>,
+    params: ParamsOfStopSyncAccounts,
+) -> ClientResult<()> {
+    if let Some(sync) = context.net.accounts_syncs.remove(params.handle) {
+        sync.stopped.store(true, Ordering::Relaxed);
+        let _ = sync.sender.send(SyncAction::Stop);
+    }
+    Ok(())
+}
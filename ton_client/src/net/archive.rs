@@ -0,0 +1,138 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+// Archive routing: production endpoints prune the old data, so the queries
+// for the data older than `archive_horizon` are sent to `archive_endpoints`.
+
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use crate::net::{
+    query_collection, NetworkConfig, ParamsOfQueryCollection, ParamsOfQueryOperation,
+    ResultOfQueryCollection,
+};
+
+/// Default age of the data considered historical, in seconds (7 days)
+pub const DEFAULT_ARCHIVE_HORIZON: u32 = 7 * 24 * 3600;
+
+/// Filter fields containing unix time
+const TIME_FIELDS: [&str; 3] = ["gen_utime", "now", "created_at"];
+
+/// Filter operators with a single time value
+const TIME_OPERATORS: [&str; 5] = ["eq", "gt", "ge", "lt", "le"];
+
+fn time_value(value: &Value) -> Option<u32> {
+    match value {
+        Value::Number(number) => number.as_u64().map(|time| time as u32),
+        Value::String(string) => string.parse().ok(),
+        _ => None,
+    }
+}
+
+fn min_of(a: Option<u32>, b: Option<u32>) -> Option<u32> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn time_condition_min(condition: &Value) -> Option<u32> {
+    let mut result = None;
+    for operator in &TIME_OPERATORS {
+        result = min_of(result, time_value(&condition[operator]));
+    }
+    if let Some(values) = condition["in"].as_array() {
+        for value in values {
+            result = min_of(result, time_value(value));
+        }
+    }
+    result
+}
+
+/// Returns the minimal unix time referenced by the time fields of the filter,
+/// including the `OR` branches and the nested objects filters
+pub(crate) fn filter_min_time(filter: &Value) -> Option<u32> {
+    let mut result = None;
+    if let Some(fields) = filter.as_object() {
+        for (name, condition) in fields {
+            let time = if TIME_FIELDS.contains(&name.as_str()) {
+                time_condition_min(condition)
+            } else if condition.is_object() {
+                filter_min_time(condition)
+            } else {
+                None
+            };
+            result = min_of(result, time);
+        }
+    }
+    result
+}
+
+/// Checks if the data of the `time` is older than `archive_horizon`.
+/// `None` time means the very beginning of the blockchain.
+pub(crate) fn is_archive_time(config: &NetworkConfig, now_ms: u64, time: Option<u32>) -> bool {
+    if config.archive_endpoints.is_none() {
+        return false;
+    }
+    let horizon = config.archive_horizon.unwrap_or(DEFAULT_ARCHIVE_HORIZON) as u64;
+    match time {
+        Some(time) => (time as u64) < (now_ms / 1000).saturating_sub(horizon),
+        None => true,
+    }
+}
+
+/// Checks if the query operation reads the data older than `archive_horizon`
+pub(crate) fn is_archive_operation(
+    config: &NetworkConfig,
+    now_ms: u64,
+    operation: &ParamsOfQueryOperation,
+) -> bool {
+    let filter = match operation {
+        ParamsOfQueryOperation::QueryCollection(params) => &params.filter,
+        ParamsOfQueryOperation::AggregateCollection(params) => &params.filter,
+        _ => return false,
+    };
+    match filter.as_ref().and_then(filter_min_time) {
+        Some(time) => is_archive_time(config, now_ms, Some(time)),
+        None => false,
+    }
+}
+
+/// Queries the collection from the archive endpoint if `archive` is set and
+/// `archive_endpoints` are configured, and with the regular query otherwise
+pub(crate) async fn archive_query_collection(
+    context: &Arc<ClientContext>,
+    params: ParamsOfQueryCollection,
+    archive: bool,
+) -> ClientResult<ResultOfQueryCollection> {
+    let server_link = context.get_server_link()?;
+    let endpoint = if archive {
+        server_link.archive_endpoint().await?
+    } else {
+        None
+    };
+    match endpoint {
+        Some(endpoint) => {
+            let result = server_link.query_collection(params, Some(endpoint)).await?;
+            Ok(ResultOfQueryCollection {
+                result: serde_json::from_value(result)
+                    .map_err(|err| crate::net::Error::invalid_server_response(err))?,
+            })
+        }
+        None => query_collection(context.clone(), params).await,
+    }
+}
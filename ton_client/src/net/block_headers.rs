@@ -0,0 +1,148 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::subscriptions::{
+    subscribe_collection, ParamsOfSubscribeCollection, ResultOfSubscribeCollection,
+    ResultOfSubscription,
+};
+use super::Error;
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+#[cfg(feature = "proofs")]
+use crate::proofs::{proof_block_data, ParamsOfProofBlockData};
+use futures::Future;
+use serde_json::Value;
+use std::sync::Arc;
+
+const BLOCK_HEADER_FIELDS: &str = "id seq_no workchain_id shard gen_utime tr_count file_hash";
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ParamsOfSubscribeBlockHeaders {
+    /// Blocks filter. Use it to limit headers to the specific workchain or shard,
+    /// e.g. `{ "workchain_id": { "eq": -1 } }` for masterchain blocks only.
+    pub filter: Option<Value>,
+    /// Verify proof of each block before passing its header to the callback.
+    /// Default is `false`.
+    ///
+    /// Headers which fail the proof check are reported to the callback as errors.
+    pub verify_proofs: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone, Debug, PartialEq)]
+pub struct BlockHeader {
+    /// Block id (root hash) encoded in hex.
+    pub id: String,
+    /// Block sequence number.
+    pub seq_no: u32,
+    /// Workchain id.
+    pub workchain_id: i32,
+    /// Shard prefix encoded in hex.
+    pub shard: String,
+    /// Block generation time.
+    pub gen_utime: u32,
+    /// Number of transactions in the block.
+    pub tr_count: u32,
+    /// Block file hash encoded in hex.
+    pub file_hash: String,
+    /// `true` if block proof was checked, `false` if proof verification was not requested.
+    pub proof_verified: bool,
+}
+
+impl BlockHeader {
+    fn from_value(value: &Value) -> ClientResult<Self> {
+        let get_str = |name: &str| {
+            value[name]
+                .as_str()
+                .map(|value| value.to_string())
+                .ok_or_else(|| Error::invalid_server_response(format!("block has no `{}`", name)))
+        };
+        let get_num = |name: &str| {
+            value[name]
+                .as_i64()
+                .ok_or_else(|| Error::invalid_server_response(format!("block has no `{}`", name)))
+        };
+        Ok(Self {
+            id: get_str("id")?,
+            seq_no: get_num("seq_no")? as u32,
+            workchain_id: get_num("workchain_id")? as i32,
+            shard: get_str("shard")?,
+            gen_utime: get_num("gen_utime")? as u32,
+            tr_count: value["tr_count"].as_u64().unwrap_or_default() as u32,
+            file_hash: get_str("file_hash")?,
+            proof_verified: false,
+        })
+    }
+}
+
+async fn resolve_block_header(
+    context: Arc<ClientContext>,
+    block: Value,
+    verify_proofs: bool,
+) -> ClientResult<BlockHeader> {
+    let mut header = BlockHeader::from_value(&block)?;
+    if verify_proofs {
+        verify_block_proof(context, block).await?;
+        header.proof_verified = true;
+    }
+    Ok(header)
+}
+
+#[cfg(feature = "proofs")]
+async fn verify_block_proof(context: Arc<ClientContext>, block: Value) -> ClientResult<()> {
+    proof_block_data(context, ParamsOfProofBlockData { block, send_events: None, verification_policy: None }).await
+}
+
+#[cfg(not(feature = "proofs"))]
+async fn verify_block_proof(_context: Arc<ClientContext>, _block: Value) -> ClientResult<()> {
+    Err(crate::client::Error::not_implemented("library is built without `proofs` feature"))
+}
+
+/// Creates a subscription to the block headers.
+///
+/// Delivers compact typed headers of the new blocks instead of full block documents.
+/// Useful for light clients which only need signals of the chain progress.
+///
+/// The subscription must be closed with `unsubscribe`.
+pub async fn subscribe_block_headers<F: Future<Output = ()> + Send>(
+    context: Arc<ClientContext>,
+    params: ParamsOfSubscribeBlockHeaders,
+    callback: impl Fn(ClientResult<BlockHeader>) -> F + Send + Sync + 'static,
+) -> ClientResult<ResultOfSubscribeCollection> {
+    let verify_proofs = params.verify_proofs.unwrap_or_default();
+    let callback = Arc::new(callback);
+    let callback_context = context.clone();
+    let callback = move |result: ClientResult<ResultOfSubscription>| {
+        let callback = callback.clone();
+        let context = callback_context.clone();
+        async move {
+            let header = match result {
+                Ok(result) => resolve_block_header(context, result.result, verify_proofs).await,
+                Err(err) => Err(err),
+            };
+            callback(header).await
+        }
+    };
+
+    subscribe_collection(
+        context,
+        ParamsOfSubscribeCollection {
+            collection: "blocks".to_string(),
+            filter: params.filter,
+            result: BLOCK_HEADER_FIELDS.to_string(),
+            mapping: None,
+            decode_body: None,
+        },
+        callback,
+    )
+    .await
+}
@@ -0,0 +1,249 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+// Wrappers of the `blockchain` GraphQL API root. Unlike the collections, its queries
+// return the relay-style connections, so the results are paginated with cursors.
+
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::client::ClientContext;
+use crate::error::{AddNetworkUrl, ClientResult};
+use crate::net::ton_gql::GraphQLQuery;
+
+use super::Error;
+
+/// Maximum number of items returned by the server in one page
+const MAX_PAGE_SIZE: u32 = 50;
+
+const DEFAULT_BLOCK_FIELDS: &str = "id seq_no workchain_id shard gen_utime";
+const DEFAULT_TRANSACTION_FIELDS: &str = "id lt now aborted balance_delta";
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct BlockchainSeqNoRange {
+    /// Masterchain block `seq_no` the range starts from (inclusive).
+    pub start: Option<u32>,
+    /// Masterchain block `seq_no` the range ends at (exclusive).
+    pub end: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ParamsOfBlockchainKeyBlocks {
+    /// Masterchain `seq_no` range of the key blocks.
+    pub seq_no: Option<BlockchainSeqNoRange>,
+    /// Block fields to return. Default is `id seq_no workchain_id shard gen_utime`.
+    pub result: Option<String>,
+    /// Cursor to continue the pagination from. Use `cursor` of the previous result.
+    pub after: Option<String>,
+    /// Maximum number of items to return. Default is 50.
+    ///
+    /// Limits larger than the server page size are fetched with several requests.
+    pub limit: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ParamsOfBlockchainWorkchainBlocks {
+    /// Masterchain `seq_no` range of the blocks. For shard blocks the `seq_no` of the
+    /// masterchain block committing the shard block is used.
+    pub master_seq_no_range: Option<BlockchainSeqNoRange>,
+    /// Workchain id. By default the blocks of all workchains are returned.
+    pub workchain: Option<i32>,
+    /// Block fields to return. Default is `id seq_no workchain_id shard gen_utime`.
+    pub result: Option<String>,
+    /// Cursor to continue the pagination from. Use `cursor` of the previous result.
+    pub after: Option<String>,
+    /// Maximum number of items to return. Default is 50.
+    ///
+    /// Limits larger than the server page size are fetched with several requests.
+    pub limit: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ParamsOfBlockchainAccountTransactions {
+    /// Account address.
+    pub address: String,
+    /// Masterchain `seq_no` range of the blocks containing the transactions.
+    pub master_seq_no_range: Option<BlockchainSeqNoRange>,
+    /// Transaction fields to return. Default is `id lt now aborted balance_delta`.
+    pub result: Option<String>,
+    /// Cursor to continue the pagination from. Use `cursor` of the previous result.
+    pub after: Option<String>,
+    /// Maximum number of items to return. Default is 50.
+    ///
+    /// Limits larger than the server page size are fetched with several requests.
+    pub limit: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ResultOfBlockchainQuery {
+    /// Items in the blockchain order.
+    pub items: Vec<Value>,
+    /// Cursor of the last returned item. Pass it as `after` to get the next page.
+    pub cursor: Option<String>,
+    /// `true` if there are more items after the `cursor`.
+    pub has_more: bool,
+}
+
+fn connection_selection(result: &str) -> String {
+    format!(
+        "edges {{ node {{ {} }} cursor }} pageInfo {{ hasNextPage endCursor }}",
+        result
+    )
+}
+
+fn seq_no_range(range: Option<BlockchainSeqNoRange>) -> Value {
+    range.map(|range| json!(range)).unwrap_or(Value::Null)
+}
+
+/// Fetches the pages of the connection until `limit` items are collected or the
+/// connection is over. `query` must declare `$first` and `$after` variables.
+async fn query_connection(
+    context: &ClientContext,
+    query: String,
+    path: &[&str],
+    mut variables: Value,
+    after: Option<String>,
+    limit: Option<u32>,
+) -> ClientResult<ResultOfBlockchainQuery> {
+    let server_link = context.get_server_link()?;
+    let limit = limit.unwrap_or(MAX_PAGE_SIZE) as usize;
+    let mut result = ResultOfBlockchainQuery {
+        items: Vec::new(),
+        cursor: after,
+        has_more: true,
+    };
+    while result.has_more && result.items.len() < limit {
+        let first = std::cmp::min(limit - result.items.len(), MAX_PAGE_SIZE as usize);
+        variables["first"] = first.into();
+        variables["after"] = result.cursor.clone().into();
+        let response = server_link
+            .query(
+                &GraphQLQuery {
+                    query: query.clone(),
+                    variables: Some(variables.clone()),
+                    timeout: None,
+                    is_batch: false,
+                },
+                None,
+            )
+            .await
+            .map_err(|err| Error::queries_query_failed(err))
+            .add_network_url(server_link)
+            .await?;
+
+        let mut connection = &response["data"];
+        for field in path {
+            connection = &connection[field];
+        }
+        let edges = connection["edges"].as_array().ok_or_else(|| {
+            Error::invalid_server_response(format!("connection has no edges: {}", response))
+        })?;
+        for edge in edges {
+            result.items.push(edge["node"].clone());
+        }
+        if let Some(cursor) = connection["pageInfo"]["endCursor"].as_str() {
+            result.cursor = Some(cursor.to_owned());
+        }
+        result.has_more =
+            connection["pageInfo"]["hasNextPage"].as_bool().unwrap_or(false) && !edges.is_empty();
+    }
+    Ok(result)
+}
+
+/// Returns the key blocks of the `blockchain` API.
+///
+/// The result can be paginated with the returned `cursor`.
+#[api_function]
+pub async fn blockchain_key_blocks(
+    context: Arc<ClientContext>,
+    params: ParamsOfBlockchainKeyBlocks,
+) -> ClientResult<ResultOfBlockchainQuery> {
+    let query = format!(
+        "query($seq_no: BlockchainMasterSeqNoFilter, $first: Int, $after: String) {{ \
+            blockchain {{ key_blocks(seq_no: $seq_no, first: $first, after: $after) {{ {} }} }} \
+        }}",
+        connection_selection(params.result.as_deref().unwrap_or(DEFAULT_BLOCK_FIELDS)),
+    );
+    query_connection(
+        &context,
+        query,
+        &["blockchain", "key_blocks"],
+        json!({ "seq_no": seq_no_range(params.seq_no) }),
+        params.after,
+        params.limit,
+    )
+    .await
+}
+
+/// Returns the blocks of the `blockchain` API ordered by the committing masterchain blocks.
+///
+/// The result can be paginated with the returned `cursor`.
+#[api_function]
+pub async fn blockchain_workchain_blocks(
+    context: Arc<ClientContext>,
+    params: ParamsOfBlockchainWorkchainBlocks,
+) -> ClientResult<ResultOfBlockchainQuery> {
+    let query = format!(
+        "query($master_seq_no_range: BlockchainMasterSeqNoFilter, $workchain: Int, \
+            $first: Int, $after: String) {{ \
+            blockchain {{ workchain_blocks(master_seq_no_range: $master_seq_no_range, \
+                workchain: $workchain, first: $first, after: $after) {{ {} }} }} \
+        }}",
+        connection_selection(params.result.as_deref().unwrap_or(DEFAULT_BLOCK_FIELDS)),
+    );
+    query_connection(
+        &context,
+        query,
+        &["blockchain", "workchain_blocks"],
+        json!({
+            "master_seq_no_range": seq_no_range(params.master_seq_no_range),
+            "workchain": params.workchain,
+        }),
+        params.after,
+        params.limit,
+    )
+    .await
+}
+
+/// Returns the account transactions of the `blockchain` API in the `lt` order.
+///
+/// The result can be paginated with the returned `cursor`.
+#[api_function]
+pub async fn blockchain_account_transactions(
+    context: Arc<ClientContext>,
+    params: ParamsOfBlockchainAccountTransactions,
+) -> ClientResult<ResultOfBlockchainQuery> {
+    let query = format!(
+        "query($address: String!, $master_seq_no_range: BlockchainMasterSeqNoFilter, \
+            $first: Int, $after: String) {{ \
+            blockchain {{ account(address: $address) {{ \
+                transactions(master_seq_no_range: $master_seq_no_range, \
+                    first: $first, after: $after) {{ {} }} }} }} \
+        }}",
+        connection_selection(params.result.as_deref().unwrap_or(DEFAULT_TRANSACTION_FIELDS)),
+    );
+    query_connection(
+        &context,
+        query,
+        &["blockchain", "account", "transactions"],
+        json!({
+            "address": params.address,
+            "master_seq_no_range": seq_no_range(params.master_seq_no_range),
+        }),
+        params.after,
+        params.limit,
+    )
+    .await
+}
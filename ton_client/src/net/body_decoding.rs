@@ -0,0 +1,171 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+// Decoding of the message bodies returned by the collection queries and subscriptions:
+// the bodies are decoded by the endpoint if its schema has the decoded body field
+// and locally with the ABI otherwise.
+
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::abi::{decode_message_body, Abi, DecodedMessageBody, ParamsOfDecodeMessageBody};
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use crate::net::{Error, MESSAGES_COLLECTION};
+
+/// Field of the message with the body decoded by the endpoint
+pub(crate) const DECODED_BODY_FIELD: &str = "decoded_body";
+const BODY_FIELDS: &str = "id body msg_type";
+const DECODED_BODY_RESULT: &str = "body_type name value header { expire time pubkey }";
+const MSG_TYPE_INTERNAL: u64 = 0;
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone)]
+pub struct BodyDecoding {
+    /// Contract ABI used to decode the message bodies.
+    pub abi: Abi,
+    /// Compare the bodies decoded by the endpoint with the local decoding.
+    /// Default is `false`.
+    ///
+    /// The mismatch is reported with `DecodedBodyMismatch` error. Ignored if the endpoint
+    /// doesn't support the decoded bodies, since they are decoded locally anyway.
+    pub verify_decoding: Option<bool>,
+}
+
+/// Decoding of the collection items resolved for the endpoint
+pub(crate) struct BodyDecoder {
+    context: Arc<ClientContext>,
+    decoding: BodyDecoding,
+    server_side: bool,
+}
+
+impl BodyDecoder {
+    /// Checks if the endpoint schema has the decoded body field. Bodies are decoded locally
+    /// if the schema can't be introspected.
+    async fn new(
+        context: Arc<ClientContext>,
+        collection: &str,
+        decoding: BodyDecoding,
+    ) -> ClientResult<Self> {
+        if collection != MESSAGES_COLLECTION {
+            return Err(Error::invalid_query(
+                collection,
+                "decode_body",
+                "",
+                None,
+                "message bodies can be decoded only for `messages` collection",
+            ));
+        }
+        let server_side = match context.get_server_link()?.get_schema().await {
+            Ok(schema) => schema.has_field(collection, DECODED_BODY_FIELD),
+            Err(err) => {
+                log::debug!("Endpoint schema is not available, bodies are decoded locally: {}", err);
+                false
+            }
+        };
+        Ok(Self { context, decoding, server_side })
+    }
+
+    /// Creates the decoder if the `decoding` is requested and extends the `result`
+    /// with the fields required to decode the bodies
+    pub async fn resolve(
+        context: &Arc<ClientContext>,
+        collection: &str,
+        decoding: Option<BodyDecoding>,
+        result: &mut String,
+    ) -> ClientResult<Option<Self>> {
+        let decoding = match decoding {
+            Some(decoding) => decoding,
+            None => return Ok(None),
+        };
+        let decoder = Self::new(context.clone(), collection, decoding).await?;
+        *result = decoder.extend_result(result)?;
+        Ok(Some(decoder))
+    }
+
+    /// Extends the `result` with the fields required to decode the bodies
+    fn extend_result(&self, result: &str) -> ClientResult<String> {
+        let mut result = format!("{} {}", result, BODY_FIELDS);
+        if self.server_side {
+            let abi = serde_json::to_string(&self.decoding.abi.json_string()?)
+                .map_err(|err| Error::invalid_query(
+                    MESSAGES_COLLECTION,
+                    "decode_body",
+                    "abi",
+                    None,
+                    &err.to_string(),
+                ))?;
+            result.push_str(&format!(
+                " {}(abi: {}) {{ {} }}",
+                DECODED_BODY_FIELD, abi, DECODED_BODY_RESULT,
+            ));
+        }
+        Ok(result)
+    }
+
+    /// Sets `decoded_body` field of the item: the body decoded by the endpoint or decoded
+    /// locally. `null` if the body is absent or doesn't match the ABI.
+    pub async fn decode(&self, item: &mut Value) -> ClientResult<()> {
+        let server_decoded = match item.get(DECODED_BODY_FIELD) {
+            Some(decoded) if self.server_side && !decoded.is_null() => Some(decoded.clone()),
+            _ => None,
+        };
+        let verify = self.decoding.verify_decoding.unwrap_or(false);
+        match server_decoded {
+            Some(_) if !verify => {}
+            Some(server_decoded) => {
+                let local = self.decode_locally(item).await;
+                let matched = local.as_ref().map(|local| {
+                    serde_json::from_value::<DecodedMessageBody>(server_decoded.clone())
+                        .map(|server| server == *local)
+                        .unwrap_or(false)
+                });
+                if matched != Some(true) {
+                    return Err(Error::decoded_body_mismatch(
+                        item["id"].as_str().unwrap_or_default(),
+                        &server_decoded,
+                        &json!(local),
+                    ));
+                }
+            }
+            None => {
+                let local = self.decode_locally(item).await;
+                if self.server_side && verify && local.is_some() {
+                    return Err(Error::decoded_body_mismatch(
+                        item["id"].as_str().unwrap_or_default(),
+                        &Value::Null,
+                        &json!(local),
+                    ));
+                }
+                item[DECODED_BODY_FIELD] = json!(local);
+            }
+        }
+        Ok(())
+    }
+
+    async fn decode_locally(&self, item: &Value) -> Option<DecodedMessageBody> {
+        let body = item["body"].as_str()?;
+        decode_message_body(
+            self.context.clone(),
+            ParamsOfDecodeMessageBody {
+                abi: self.decoding.abi.clone(),
+                body: body.to_string(),
+                is_internal: item["msg_type"].as_u64() == Some(MSG_TYPE_INTERNAL),
+                partial: None,
+            },
+        )
+        .await
+        .ok()
+    }
+}
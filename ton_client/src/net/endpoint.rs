@@ -14,13 +14,15 @@
 
 use crate::client::{core_version, ClientEnv, FetchMethod};
 use crate::error::ClientResult;
-use crate::net::{Error, NetworkConfig};
+use crate::net::{Error, NetworkConfig, NetworkQueriesProtocol};
 use serde_json::Value;
 use std::sync::atomic::{AtomicI64, AtomicU32, AtomicU64, Ordering};
 
 const V_0_39_0: u32 = 39000;
 
 pub(crate) struct Endpoint {
+    /// Endpoint address as it is specified in the config
+    pub address: String,
     pub query_url: String,
     pub subscription_url: String,
     pub ip_address: Option<String>,
@@ -33,6 +35,7 @@ pub(crate) struct Endpoint {
 impl Clone for Endpoint {
     fn clone(&self) -> Self {
         Self {
+            address: self.address.clone(),
             query_url: self.query_url.clone(),
             subscription_url: self.subscription_url.clone(),
             ip_address: self.ip_address.clone(),
@@ -58,8 +61,12 @@ impl Endpoint {
     }
 
     fn expand_address(base_url: &str) -> String {
+        format!("{}/graphql", Self::with_protocol(base_url).trim_end_matches("/"))
+    }
+
+    fn with_protocol(base_url: &str) -> String {
         let url = base_url.trim_end_matches("/").to_lowercase();
-        let base_url = if url.starts_with(HTTP_PROTOCOL) || url.starts_with(HTTPS_PROTOCOL) {
+        if url.starts_with(HTTP_PROTOCOL) || url.starts_with(HTTPS_PROTOCOL) {
             base_url.to_owned()
         } else {
             let protocol = if url == "localhost" || url == "127.0.0.1" || url == "0.0.0.0" {
@@ -68,9 +75,7 @@ impl Endpoint {
                 HTTPS_PROTOCOL
             };
             format!("{}{}", protocol, base_url)
-        };
-
-        format!("{}/graphql", base_url.trim_end_matches("/"))
+        }
     }
 
     async fn fetch_info_with_url(
@@ -98,14 +103,21 @@ impl Endpoint {
         config: &NetworkConfig,
         address: &str,
     ) -> ClientResult<Self> {
-        let address = Self::expand_address(address);
+        if config.queries_protocol == Some(NetworkQueriesProtocol::Jrpc) {
+            return Ok(Self::jrpc(address));
+        }
         let info_request_time = client_env.now_ms();
-        let (info, query_url, ip_address) =
-            Self::fetch_info_with_url(client_env, &address, QUERY_INFO_SCHEMA, config.query_timeout).await?;
+        let (info, query_url, ip_address) = Self::fetch_info_with_url(
+            client_env,
+            &Self::expand_address(address),
+            QUERY_INFO_SCHEMA,
+            config.query_timeout,
+        ).await?;
         let subscription_url = query_url
             .replace("https://", "wss://")
             .replace("http://", "ws://");
         let endpoint = Self {
+            address: address.to_owned(),
             query_url,
             subscription_url,
             ip_address,
@@ -119,6 +131,25 @@ impl Endpoint {
         Ok(endpoint)
     }
 
+    /// JRPC gateways don't provide the server info, so the endpoint is used as is
+    /// without the latency and time checks
+    fn jrpc(address: &str) -> Self {
+        let query_url = Self::with_protocol(address).trim_end_matches("/").to_owned();
+        let subscription_url = query_url
+            .replace("https://", "wss://")
+            .replace("http://", "ws://");
+        Self {
+            address: address.to_owned(),
+            query_url,
+            subscription_url,
+            ip_address: None,
+            server_time_delta: AtomicI64::default(),
+            server_version: AtomicU32::default(),
+            server_latency: AtomicU64::default(),
+            next_latency_detection_time: AtomicU64::default(),
+        }
+    }
+
     pub async fn refresh(
         &self,
         client_env: &ClientEnv,
@@ -0,0 +1,300 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::client::storage::KeyValueStorage;
+use crate::client::trace::current_trace_id;
+use crate::client::ClientContext;
+use crate::error::{ClientError, ClientResult};
+
+/// Number of the sequential failures after which the endpoint is considered unhealthy
+const UNHEALTHY_FAILURES_COUNT: u32 = 3;
+/// Time after the last failure after which the unhealthy endpoint is tried again, ms
+const HEALTH_RECOVERY_TIME: u64 = 60000;
+/// Weight of the last response time in the average response time
+const RESPONSE_TIME_WEIGHT: f64 = 0.2;
+/// Key of the persisted stats in the local storage
+const STATS_KEY: &str = "endpoint_stats";
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+struct EndpointHealth {
+    requests_count: u32,
+    failures_count: u32,
+    consecutive_failures: u32,
+    average_response_time: Option<f64>,
+    last_failure_time: Option<u64>,
+    last_error: Option<String>,
+    last_error_trace_id: Option<String>,
+}
+
+impl EndpointHealth {
+    fn is_healthy(&self, now: u64) -> bool {
+        self.consecutive_failures < UNHEALTHY_FAILURES_COUNT
+            || self
+                .last_failure_time
+                .map(|time| now >= time + HEALTH_RECOVERY_TIME)
+                .unwrap_or(true)
+    }
+
+    /// Lower is better. Failures are penalized by the doubled response time for each one
+    fn score(&self) -> f64 {
+        self.average_response_time.unwrap_or(0.0) * (1 + self.consecutive_failures * 2) as f64
+    }
+}
+
+/// Local storage keeping the stats between the sessions. It is opened on the first use
+pub(crate) enum StatsStorage {
+    None,
+    Closed {
+        local_storage_path: Option<String>,
+        storage_name: String,
+    },
+    Opened(Arc<dyn KeyValueStorage>),
+}
+
+impl Default for StatsStorage {
+    fn default() -> Self {
+        StatsStorage::None
+    }
+}
+
+impl StatsStorage {
+    async fn open(&mut self) -> Option<Arc<dyn KeyValueStorage>> {
+        if let StatsStorage::Closed { local_storage_path, storage_name } = self {
+            *self = match crate::client::LocalStorage::new(
+                local_storage_path.clone(),
+                storage_name.clone(),
+            )
+            .await
+            {
+                Ok(storage) => StatsStorage::Opened(Arc::new(storage)),
+                Err(err) => {
+                    log::warn!("Endpoint stats storage is not available: {}", err.message);
+                    StatsStorage::None
+                }
+            };
+        }
+        match self {
+            StatsStorage::Opened(storage) => Some(storage.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Response times and failures of the endpoints collected by address
+#[derive(Default)]
+pub(crate) struct EndpointStatsCollector {
+    endpoints: Mutex<HashMap<String, EndpointHealth>>,
+    storage: tokio::sync::Mutex<StatsStorage>,
+    loaded: AtomicBool,
+}
+
+impl EndpointStatsCollector {
+    pub fn with_storage(storage: StatsStorage) -> Self {
+        Self {
+            endpoints: Default::default(),
+            storage: tokio::sync::Mutex::new(storage),
+            loaded: AtomicBool::new(false),
+        }
+    }
+
+    /// Loads the stats persisted by the previous sessions. The stats collected
+    /// in this session take precedence. The stats are loaded once.
+    pub async fn load(&self) {
+        let mut storage = self.storage.lock().await;
+        if self.loaded.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let storage = match storage.open().await {
+            Some(storage) => storage,
+            None => return,
+        };
+        let persisted = match storage.get_str(STATS_KEY).await {
+            Ok(Some(value)) => value,
+            Ok(None) => return,
+            Err(err) => {
+                log::warn!("Endpoint stats are not loaded: {}", err.message);
+                return;
+            }
+        };
+        match serde_json::from_str::<HashMap<String, EndpointHealth>>(&persisted) {
+            Ok(persisted) => {
+                let mut endpoints = self.endpoints.lock().unwrap();
+                for (address, health) in persisted {
+                    endpoints.entry(address).or_insert(health);
+                }
+            }
+            Err(err) => log::warn!("Endpoint stats are not loaded: {}", err),
+        }
+    }
+
+    /// Writes the stats to the storage if it is opened
+    pub async fn save(&self) {
+        let storage = match &*self.storage.lock().await {
+            StatsStorage::Opened(storage) => storage.clone(),
+            _ => return,
+        };
+        let value = serde_json::to_string(&*self.endpoints.lock().unwrap());
+        let result = match value {
+            Ok(value) => storage.put_str(STATS_KEY, &value).await,
+            Err(err) => Err(crate::client::Error::internal_error(err)),
+        };
+        if let Err(err) = result {
+            log::warn!("Endpoint stats are not saved: {}", err.message);
+        }
+    }
+
+    /// Clears the collected and the persisted stats
+    pub async fn reset(&self) -> ClientResult<()> {
+        self.loaded.store(true, Ordering::Relaxed);
+        self.endpoints.lock().unwrap().clear();
+        if let Some(storage) = self.storage.lock().await.open().await {
+            storage.remove(STATS_KEY).await?;
+        }
+        Ok(())
+    }
+
+    /// Records the request result. Only network errors are counted as failures:
+    /// errors of the query itself don't indicate the endpoint health
+    pub fn record(&self, address: &str, response_time: u64, now: u64, error: Option<&ClientError>) {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let health = endpoints.entry(address.to_string()).or_default();
+        health.requests_count += 1;
+        match error {
+            Some(error) if crate::client::Error::is_network_error(error) => {
+                health.failures_count += 1;
+                health.consecutive_failures += 1;
+                health.last_failure_time = Some(now);
+                health.last_error = Some(error.message.clone());
+                health.last_error_trace_id = current_trace_id();
+            }
+            _ => {
+                health.consecutive_failures = 0;
+                let response_time = response_time as f64;
+                health.average_response_time = Some(match health.average_response_time {
+                    Some(average) => {
+                        average + (response_time - average) * RESPONSE_TIME_WEIGHT
+                    }
+                    None => response_time,
+                });
+            }
+        }
+    }
+
+    /// Orders the addresses by the health score. Unhealthy endpoints are excluded
+    /// unless all the endpoints are unhealthy
+    pub fn prefer_healthy(&self, addresses: Vec<String>, now: u64) -> Vec<String> {
+        let endpoints = self.endpoints.lock().unwrap();
+        let default_health = EndpointHealth::default();
+        let health = |address: &String| endpoints.get(address).unwrap_or(&default_health);
+        let (mut healthy, unhealthy): (Vec<_>, Vec<_>) = addresses
+            .into_iter()
+            .partition(|address| health(address).is_healthy(now));
+        if healthy.is_empty() {
+            return unhealthy;
+        }
+        healthy.sort_by(|a, b| {
+            health(a)
+                .score()
+                .partial_cmp(&health(b).score())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        healthy
+    }
+
+    pub fn get_stats(
+        &self,
+        addresses: &[String],
+        current: Option<&str>,
+        now: u64,
+    ) -> Vec<EndpointStats> {
+        let endpoints = self.endpoints.lock().unwrap();
+        addresses
+            .iter()
+            .map(|address| {
+                let health = endpoints.get(address).cloned().unwrap_or_default();
+                EndpointStats {
+                    address: address.clone(),
+                    current: current == Some(address.as_str()),
+                    healthy: health.is_healthy(now),
+                    requests_count: health.requests_count,
+                    failures_count: health.failures_count,
+                    consecutive_failures: health.consecutive_failures,
+                    average_response_time: health
+                        .average_response_time
+                        .map(|time| time.round() as u32),
+                    last_error: health.last_error,
+                    last_error_trace_id: health.last_error_trace_id,
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone, Debug, PartialEq)]
+pub struct EndpointStats {
+    /// Endpoint address as it is specified in the config.
+    pub address: String,
+    /// `true` if the endpoint is the current querying endpoint.
+    pub current: bool,
+    /// `false` if the last requests to the endpoint failed with network errors.
+    /// Unhealthy endpoints are not selected as the querying endpoint for a minute
+    /// unless all the endpoints are unhealthy.
+    pub healthy: bool,
+    /// Number of the requests sent to the endpoint.
+    pub requests_count: u32,
+    /// Number of the requests failed with network errors.
+    pub failures_count: u32,
+    /// Number of the sequential requests failed with network errors.
+    pub consecutive_failures: u32,
+    /// Average response time in ms. Recent responses have more weight.
+    pub average_response_time: Option<u32>,
+    /// Message of the last network error.
+    pub last_error: Option<String>,
+    /// Trace id of the API call which got the last network error.
+    pub last_error_trace_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ResultOfGetEndpointStats {
+    /// Collected metrics of the endpoints used by client.
+    pub endpoints: Vec<EndpointStats>,
+}
+
+/// Returns the response times and failures collected for the endpoints.
+///
+/// The querying endpoint is selected among the healthy endpoints preferring the ones with
+/// less response time and is kept until it fails, so applications can display
+/// the connection quality using these metrics.
+#[api_function]
+pub async fn get_endpoint_stats(
+    context: std::sync::Arc<ClientContext>,
+) -> ClientResult<ResultOfGetEndpointStats> {
+    let server_link = context.get_server_link()?;
+    Ok(ResultOfGetEndpointStats {
+        endpoints: server_link.get_endpoint_stats().await,
+    })
+}
+
+/// Clears the collected endpoint statistics.
+///
+/// If `NetworkConfig.persist_endpoint_stats` is enabled, the statistics kept
+/// in the local storage are removed too.
+#[api_function]
+pub async fn reset_endpoint_stats(context: std::sync::Arc<ClientContext>) -> ClientResult<()> {
+    context.get_server_link()?.reset_endpoint_stats().await
+}
@@ -18,6 +18,13 @@ pub enum ErrorCode {
     NoEndpointsProvided = 612,
     GraphqlWebsocketInitError = 613,
     NetworkModuleResumed = 614,
+    LiteServerError = 615,
+    LiteServerConnectionFailed = 616,
+    LiteServersNotAvailable = 617,
+    InvalidQuery = 618,
+    ConflictingEndpointData = 619,
+    JrpcError = 620,
+    DecodedBodyMismatch = 621,
 }
 
 pub struct Error;
@@ -90,6 +97,23 @@ impl Error {
         (None, None)
     }
 
+    /// Maps well-known extensions of the GraphQL server error into a flat structure
+    pub(crate) fn graphql_server_error_data(server_error: &Value) -> Value {
+        let extensions = &server_error["extensions"];
+        let exchange = if extensions["exchange"].is_null() {
+            &extensions["exception"]["exchange"]
+        } else {
+            &extensions["exchange"]
+        };
+        json!({
+            "message": server_error["message"],
+            "path": server_error["path"],
+            "code": extensions["code"],
+            "server_code": extensions["exception"]["code"],
+            "exchange": exchange,
+        })
+    }
+
     pub fn graphql_server_error(operation: Option<&str>, errors: &[Value]) -> ClientError {
         let (message, code) = Self::try_get_message_and_code(errors);
         let operation = operation.unwrap_or("server returned");
@@ -105,6 +129,16 @@ impl Error {
         if let Some(code) = code {
             err.data["server_code"] = code.into();
         }
+        let server_errors: Vec<Value> = errors.iter().map(Self::graphql_server_error_data).collect();
+        if let Some(first) = server_errors.first() {
+            if !first["code"].is_null() {
+                err.data["graphql_code"] = first["code"].clone();
+            }
+            if !first["exchange"].is_null() {
+                err.data["exchange"] = first["exchange"].clone();
+            }
+        }
+        err.data["server_errors"] = server_errors.into();
 
         err
     }
@@ -149,4 +183,113 @@ impl Error {
             "Network module has been resumed".to_owned(),
         )
     }
+
+    pub fn lite_server_error(code: i32, message: &str) -> ClientError {
+        let mut err = error(
+            ErrorCode::LiteServerError,
+            format!("Lite server error: {}", message),
+        );
+        err.data = json!({
+            "lite_server_error_code": code,
+        });
+        err
+    }
+
+    pub fn lite_server_connection_failed<E: Display>(err: E) -> ClientError {
+        error(
+            ErrorCode::LiteServerConnectionFailed,
+            format!("Lite server connection failed: {}", err),
+        )
+    }
+
+    pub fn lite_servers_not_available(reason: &str) -> ClientError {
+        error(
+            ErrorCode::LiteServersNotAvailable,
+            format!("Lite servers are not available: {}", reason),
+        )
+    }
+
+    pub fn invalid_query(
+        collection: &str,
+        param: &str,
+        path: &str,
+        position: Option<usize>,
+        message: &str,
+    ) -> ClientError {
+        let location = match position {
+            Some(position) => format!("`{}` at position {}", param, position),
+            None => format!("`{}`", param),
+        };
+        let mut err = error(
+            ErrorCode::InvalidQuery,
+            format!(
+                "Invalid `{}` collection query: {} (path `{}` in {})",
+                collection, message, path, location,
+            ),
+        );
+        err.data = json!({
+            "collection": collection,
+            "param": param,
+            "path": path,
+            "position": position,
+        });
+        err
+    }
+
+    pub fn jrpc_server_error(server_error: &Value) -> ClientError {
+        let mut err = error(
+            ErrorCode::JrpcError,
+            match server_error["message"].as_str() {
+                Some(message) => format!("JRPC server error: {}.", message),
+                None => "JRPC server error.".to_owned(),
+            },
+        );
+        err.data = json!({
+            "server_code": server_error["code"],
+            "server_data": server_error["data"],
+        });
+        err
+    }
+
+    pub fn conflicting_endpoint_data(
+        collection: &str,
+        endpoints: &[String],
+        hashes: &[String],
+    ) -> ClientError {
+        let mut err = error(
+            ErrorCode::ConflictingEndpointData,
+            format!(
+                "Endpoints returned different `{}` collection data: {}",
+                collection,
+                endpoints
+                    .iter()
+                    .zip(hashes)
+                    .map(|(endpoint, hash)| format!("{} (hash {})", endpoint, hash))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+        );
+        err.data = json!({
+            "collection": collection,
+            "endpoints": endpoints,
+            "hashes": hashes,
+        });
+        err
+    }
+
+    pub fn decoded_body_mismatch(message_id: &str, server: &Value, local: &Value) -> ClientError {
+        let mut err = error(
+            ErrorCode::DecodedBodyMismatch,
+            format!(
+                "Body of the message {} decoded by the endpoint mismatches the local decoding",
+                message_id,
+            ),
+        );
+        err.data = json!({
+            "message_id": message_id,
+            "server_decoded": server,
+            "local_decoded": local,
+        });
+        err
+    }
 }
@@ -19,6 +19,7 @@ use serde_json::Value;
 use std::fmt;
 
 use crate::error::ClientResult;
+use crate::net::iterators::index::BlockIndex;
 use crate::net::{query_collection, OrderBy, ParamsOfQueryCollection, SortDirection};
 use crate::ClientContext;
 use serde::Serializer;
@@ -249,7 +250,14 @@ impl MasterBlock {
         context: &Arc<ClientContext>,
         start_time: Option<u32>,
         fields: &str,
+        index: Option<&BlockIndex>,
     ) -> ClientResult<Value> {
+        if let (Some(time), Some(index)) = (start_time, index) {
+            if let Some(block) = Self::query_indexed(context, time, fields, index).await? {
+                return Ok(block);
+            }
+        }
+
         let mut blocks = if let Some(time) = start_time {
             Self::query_blocks(
                 context,
@@ -294,6 +302,28 @@ impl MasterBlock {
         ))
     }
 
+    async fn query_indexed(
+        context: &Arc<ClientContext>,
+        time: u32,
+        fields: &str,
+        index: &BlockIndex,
+    ) -> ClientResult<Option<Value>> {
+        let id = match index.seek(&ShardIdent::masterchain(), time).await? {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+        let block = Self::query_blocks(
+            context,
+            json!({ "id": { "eq": id } }),
+            SortDirection::ASC,
+            1,
+            fields,
+        )
+        .await?
+        .pop();
+        Ok(block.filter(|block| BlockFields(block).has_shards()))
+    }
+
     async fn query_blocks(
         context: &Arc<ClientContext>,
         filter: Value,
@@ -312,6 +342,8 @@ impl MasterBlock {
                 }]),
                 result: format!("{} {}", BLOCK_MASTER_FIELDS, fields),
                 limit: Some(limit),
+                min_block: None,
+                decode_body: None,
             },
         )
         .await
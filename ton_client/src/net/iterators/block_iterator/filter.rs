@@ -47,14 +47,14 @@ impl Filter {
         })
     }
 
-    fn match_start_time(&self, time: u32) -> bool {
+    pub fn match_start_time(&self, time: u32) -> bool {
         match self.start_time {
             Some(start_time) => time >= start_time,
             None => true,
         }
     }
 
-    fn match_end_time(&self, time: u32) -> bool {
+    pub fn match_end_time(&self, time: u32) -> bool {
         match self.end_time {
             Some(end_time) => time < end_time,
             None => true,
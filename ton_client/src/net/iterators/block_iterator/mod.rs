@@ -29,12 +29,14 @@ use crate::net::iterators::block::{
     BLOCK_TRAVERSE_FIELDS,
 };
 use crate::net::iterators::block_iterator::state::StateBuilder;
+use crate::net::iterators::index::BlockIndex;
 use crate::net::iterators::{query_by_ids, register_iterator, ResultOfIteratorNext};
-use crate::net::{query_collection, ChainIterator, ParamsOfQueryCollection, RegisteredIterator};
+use crate::net::archive::{archive_query_collection, is_archive_time};
+use crate::net::{ChainIterator, ParamsOfQueryCollection, RegisteredIterator};
 use ton_block::ShardIdent;
 
 mod branch;
-mod filter;
+pub(crate) mod filter;
 mod state;
 
 #[derive(Deserialize, Serialize, Clone, PartialEq)]
@@ -69,6 +71,7 @@ impl ResumeState {
 pub(crate) struct BlockIterator {
     pub filter: Filter,
     pub state: State,
+    index: Option<Arc<BlockIndex>>,
 }
 
 impl BlockIterator {
@@ -77,13 +80,20 @@ impl BlockIterator {
         params: ParamsOfCreateBlockIterator,
     ) -> ClientResult<Self> {
         let filter = Filter::from(&params)?;
-        let master_block =
-            MasterBlock::query(context, params.start_time, &filter.result_fields).await?;
+        let index = BlockIndex::open(context).await?;
+        let master_block = MasterBlock::query(
+            context,
+            params.start_time,
+            &filter.result_fields,
+            index.as_deref(),
+        )
+        .await?;
         let branches = Branch::branches_from_master_block(context, master_block, &filter)?;
         let branches_blocks = Self::query_blocks(
             context,
             branches.iter().map(|x| x.block_id.clone()).collect(),
             &filter.result_fields,
+            Self::is_archive(context, params.start_time)?,
         )
         .await?;
         let mut next = Vec::new();
@@ -99,6 +109,7 @@ impl BlockIterator {
                 visited_merge_blocks: HashSet::new(),
                 next,
             },
+            index,
         })
     }
 
@@ -138,7 +149,13 @@ impl BlockIterator {
         resume: ResumeState,
     ) -> ClientResult<Self> {
         let shards = resume.get_shards()?;
-        let next = Self::query_blocks(context, resume.next, &resume.result_fields).await?;
+        let next = Self::query_blocks(
+            context,
+            resume.next,
+            &resume.result_fields,
+            Self::is_archive(context, resume.start_time)?,
+        )
+        .await?;
         Ok(Self {
             filter: Filter {
                 shards,
@@ -151,6 +168,7 @@ impl BlockIterator {
                 visited_merge_blocks: resume.visited_merge_blocks,
                 next,
             },
+            index: BlockIndex::open(context).await?,
         })
     }
 
@@ -164,16 +182,27 @@ impl BlockIterator {
         Self::from_resume_state(context, resume).await
     }
 
+    /// Iterators started in the past read all the blocks from the archive endpoints
+    pub(crate) fn is_archive(
+        context: &Arc<ClientContext>,
+        start_time: Option<u32>,
+    ) -> ClientResult<bool> {
+        let config = context.get_server_link()?.config();
+        Ok(is_archive_time(config, context.env.now_ms(), start_time))
+    }
+
     async fn query_blocks(
         context: &Arc<ClientContext>,
         block_ids: Vec<String>,
         fields: &str,
+        archive: bool,
     ) -> ClientResult<Vec<Value>> {
         query_by_ids(
             context,
             "blocks",
             block_ids,
             &format!("{} {}", BLOCK_TRAVERSE_FIELDS, fields),
+            archive,
         )
         .await
     }
@@ -234,8 +263,8 @@ impl BlockIterator {
             let by_prev_ids = prev_ids_by(NextLink::ByPrev);
             let by_prev_alt_ids = prev_ids_by(NextLink::ByPrevAlt);
 
-            let mut blocks = query_collection(
-                context.clone(),
+            let mut blocks = archive_query_collection(
+                context,
                 ParamsOfQueryCollection {
                     collection: "blocks".to_string(),
                     filter: Some(json!({
@@ -245,6 +274,7 @@ impl BlockIterator {
                     result: format!("{} {}", BLOCK_TRAVERSE_FIELDS, self.filter.result_fields),
                     ..Default::default()
                 },
+                Self::is_archive(context, self.filter.start_time)?,
             )
             .await?
             .result;
@@ -288,6 +318,12 @@ impl ChainIterator for BlockIterator {
             items.push(self.state.next.remove(0));
         }
 
+        if let Some(index) = &self.index {
+            if let Err(err) = index.record_blocks(&items).await {
+                log::warn!("Iterated blocks are not indexed: {}", err.message);
+            }
+        }
+
         let resume_state = if return_resume_state {
             Some(self.get_resume_state_value()?)
         } else {
@@ -0,0 +1,391 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+// Cursor-based pagination of the collection queries. Items are ordered by the cursor field
+// and `id`, each page is queried with the filter selecting the items following the last
+// item of the previous page, so the items having the same cursor value are neither
+// skipped nor returned twice.
+
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use crate::net::iterators::{register_iterator, ResultOfIteratorNext};
+use crate::net::subscription_replay::has_top_level_field;
+use crate::net::{
+    query_collection, ChainIterator, OrderBy, ParamsOfQueryCollection, RegisteredIterator,
+    SortDirection,
+};
+use crate::tvm::accounts_cache::parse_lt;
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const SCALAR_OPERATORS: [&str; 8] = ["eq", "ne", "gt", "lt", "ge", "le", "in", "notIn"];
+
+/// Field which orders the collection items
+fn default_cursor_field(collection: &str) -> &'static str {
+    match collection {
+        "blocks" => "seq_no",
+        "transactions" => "lt",
+        "messages" => "created_lt",
+        "block_signatures" => "gen_utime",
+        _ => "id",
+    }
+}
+
+/// Ordering of the filter values. `lt` fields are hex strings of different lengths,
+/// so they are compared as numbers
+fn compare_values(a: &Value, b: &Value) -> Option<std::cmp::Ordering> {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        (Value::String(a_str), Value::String(b_str)) => match (parse_lt(a), parse_lt(b)) {
+            (Some(a), Some(b)) => Some(a.cmp(&b)),
+            _ => Some(a_str.cmp(b_str)),
+        },
+        _ => None,
+    }
+}
+
+/// Merges the conditions of the same field. If both conditions have the same operator,
+/// the stricter one is kept. Returns `None` if the conditions can't be satisfied together.
+fn merge_conditions(target: &mut Map<String, Value>, source: &Map<String, Value>) -> Option<()> {
+    for (op, value) in source {
+        let existing = match target.get(op) {
+            Some(existing) => existing,
+            None => {
+                target.insert(op.clone(), value.clone());
+                continue;
+            }
+        };
+        let ordering = compare_values(existing, value);
+        let replace = match op.as_str() {
+            "gt" | "ge" => ordering == Some(std::cmp::Ordering::Less),
+            "lt" | "le" => ordering == Some(std::cmp::Ordering::Greater),
+            _ if existing == value => false,
+            _ => return None,
+        };
+        if replace {
+            target.insert(op.clone(), value.clone());
+        }
+    }
+    Some(())
+}
+
+/// Merges two filters without `OR`. Returns `None` if the filters can't be satisfied together.
+fn merge_branches(a: &Map<String, Value>, b: &Map<String, Value>) -> Option<Map<String, Value>> {
+    let mut merged = a.clone();
+    for (field, condition) in b {
+        match (merged.get_mut(field), condition) {
+            (Some(Value::Object(existing)), Value::Object(condition)) => {
+                let is_scalar = |filter: &Map<String, Value>| {
+                    filter.keys().all(|key| SCALAR_OPERATORS.contains(&key.as_str()))
+                };
+                if is_scalar(existing) && is_scalar(condition) {
+                    merge_conditions(existing, condition)?
+                } else {
+                    // nested struct filter
+                    *existing = merge_branches(existing, condition)?;
+                }
+            }
+            (Some(existing), condition) if existing == condition => {}
+            (Some(_), _) => return None,
+            (None, condition) => {
+                merged.insert(field.clone(), condition.clone());
+            }
+        }
+    }
+    Some(merged)
+}
+
+/// Splits the filter into the alternatives joined with `OR`
+fn or_branches(filter: &Value) -> Vec<Map<String, Value>> {
+    let mut branches = Vec::new();
+    let mut next = Some(filter);
+    while let Some(Value::Object(filter)) = next {
+        let mut branch = filter.clone();
+        branch.remove("OR");
+        branches.push(branch);
+        next = filter.get("OR");
+    }
+    branches
+}
+
+/// Builds the filter matching both filters: `(a1 OR a2) AND (b1 OR b2)` is expanded
+/// to `a1 b1 OR a1 b2 OR a2 b1 OR a2 b2`
+pub(crate) fn and_filters(a: &Value, b: &Value) -> Value {
+    let mut branches = Vec::new();
+    for a in or_branches(a) {
+        for b in or_branches(b) {
+            branches.extend(merge_branches(&a, &b));
+        }
+    }
+    if branches.is_empty() {
+        // no items can satisfy the filter
+        return json!({ "id": { "in": [] } });
+    }
+    let mut filter = None;
+    for mut branch in branches.into_iter().rev() {
+        if let Some(next) = filter {
+            branch.insert("OR".to_string(), next);
+        }
+        filter = Some(Value::Object(branch));
+    }
+    filter.unwrap_or_else(|| json!({}))
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ParamsOfQueryCollectionAll {
+    /// Collection name (accounts, blocks, transactions, messages, block_signatures)
+    pub collection: String,
+    /// Collection filter
+    pub filter: Option<Value>,
+    /// Projection (result) string
+    pub result: String,
+    /// Field the items are ordered and paged by. Must be a top level scalar field.
+    ///
+    /// Default is `seq_no` for blocks, `lt` for transactions, `created_lt` for messages,
+    /// `gen_utime` for block signatures and `id` for other collections.
+    pub cursor_field: Option<String>,
+    /// Sorting direction. Default is `ASC`.
+    pub direction: Option<SortDirection>,
+    /// Number of items queried by a single request. Default is 50.
+    pub page_size: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ResumeState {
+    collection: String,
+    filter: Value,
+    result: String,
+    cursor_field: String,
+    descending: bool,
+    page_size: u32,
+    /// Cursor field value and `id` of the last returned item
+    cursor: Option<(Value, String)>,
+    finished: bool,
+}
+
+pub(crate) struct CollectionIterator {
+    state: ResumeState,
+    added_fields: Vec<String>,
+    query_result: String,
+    next: Vec<Value>,
+}
+
+impl CollectionIterator {
+    pub fn new(params: ParamsOfQueryCollectionAll) -> Self {
+        let cursor_field = params
+            .cursor_field
+            .unwrap_or_else(|| default_cursor_field(&params.collection).to_string());
+        Self::with_state(ResumeState {
+            collection: params.collection,
+            filter: params.filter.unwrap_or_else(|| json!({})),
+            result: params.result,
+            cursor_field,
+            descending: matches!(params.direction, Some(SortDirection::DESC)),
+            page_size: params.page_size.unwrap_or(DEFAULT_PAGE_SIZE).max(1),
+            cursor: None,
+            finished: false,
+        })
+    }
+
+    pub fn resume(params: ParamsOfResumeQueryCollectionAll) -> ClientResult<Self> {
+        let state = ResumeState::deserialize(&params.resume_state).map_err(|e| {
+            crate::client::Error::internal_error(format!("Invalid iterator resume state: {}", e))
+        })?;
+        Ok(Self::with_state(state))
+    }
+
+    fn with_state(state: ResumeState) -> Self {
+        let mut query_result = state.result.clone();
+        let mut added_fields = Vec::new();
+        for field in &["id", state.cursor_field.as_str()] {
+            if !has_top_level_field(&query_result, field) && !added_fields.contains(&field.to_string()) {
+                query_result.push(' ');
+                query_result.push_str(field);
+                added_fields.push(field.to_string());
+            }
+        }
+        Self {
+            state,
+            added_fields,
+            query_result,
+            next: Vec::new(),
+        }
+    }
+
+    fn direction(&self) -> SortDirection {
+        if self.state.descending {
+            SortDirection::DESC
+        } else {
+            SortDirection::ASC
+        }
+    }
+
+    /// Filter selecting the items following the cursor
+    fn page_filter(&self) -> Value {
+        let (value, id) = match &self.state.cursor {
+            Some(cursor) => cursor,
+            None => return self.state.filter.clone(),
+        };
+        let op = if self.state.descending { "lt" } else { "gt" };
+        let field = &self.state.cursor_field;
+        let following = if field == "id" {
+            json!({ "id": { op: id } })
+        } else {
+            json!({
+                field: { op: value },
+                "OR": {
+                    field: { "eq": value },
+                    "id": { op: id },
+                },
+            })
+        };
+        and_filters(&self.state.filter, &following)
+    }
+
+    async fn query_next(&mut self, context: &Arc<ClientContext>) -> ClientResult<()> {
+        let mut order = vec![OrderBy {
+            path: self.state.cursor_field.clone(),
+            direction: self.direction(),
+        }];
+        if self.state.cursor_field != "id" {
+            order.push(OrderBy {
+                path: "id".to_string(),
+                direction: self.direction(),
+            });
+        }
+        let page = query_collection(
+            context.clone(),
+            ParamsOfQueryCollection {
+                collection: self.state.collection.clone(),
+                filter: Some(self.page_filter()),
+                result: self.query_result.clone(),
+                order: Some(order),
+                limit: Some(self.state.page_size),
+                ..Default::default()
+            },
+        )
+        .await?
+        .result;
+        self.state.finished = page.len() < self.state.page_size as usize;
+        self.next = page;
+        Ok(())
+    }
+
+    fn take_item(&mut self) -> ClientResult<Value> {
+        let mut item = self.next.remove(0);
+        let id = item["id"].as_str().ok_or_else(|| {
+            crate::net::Error::invalid_server_response(format!(
+                "required `{}.id` field is missing",
+                self.state.collection
+            ))
+        })?;
+        self.state.cursor = Some((item[&self.state.cursor_field].clone(), id.to_string()));
+        if let Some(item) = item.as_object_mut() {
+            for field in &self.added_fields {
+                item.remove(field);
+            }
+        }
+        Ok(item)
+    }
+
+    fn get_resume_state_value(&self) -> ClientResult<Value> {
+        let mut state = serde_json::to_value(&self.state)
+            .map_err(|err| crate::client::Error::internal_error(err))?;
+        // Queried items which are not returned yet are queried again after resuming
+        state["finished"] = json!(self.state.finished && self.next.is_empty());
+        Ok(state)
+    }
+}
+
+#[async_trait::async_trait]
+impl ChainIterator for CollectionIterator {
+    async fn next(
+        &mut self,
+        context: &Arc<ClientContext>,
+        limit: u32,
+        return_resume_state: bool,
+    ) -> ClientResult<ResultOfIteratorNext> {
+        let limit = limit.max(1) as usize;
+        let mut items = Vec::new();
+        while items.len() < limit {
+            if self.next.is_empty() {
+                if self.state.finished {
+                    break;
+                }
+                self.query_next(context).await?;
+                continue;
+            }
+            items.push(self.take_item()?);
+        }
+
+        let resume_state = if return_resume_state {
+            Some(self.get_resume_state_value()?)
+        } else {
+            None
+        };
+
+        Ok(ResultOfIteratorNext {
+            items,
+            has_more: !self.next.is_empty() || !self.state.finished,
+            resume_state,
+        })
+    }
+
+    fn after_remove(&mut self, _context: &Arc<ClientContext>) {}
+}
+
+/// Creates the iterator over all the collection items matching the filter.
+///
+/// The collection is queried page by page: items are ordered by the cursor field and `id`,
+/// and each page is requested with the filter selecting the items following the last
+/// returned item. So the application receives all the items with `iterator_next`
+/// without writing the pagination loops.
+///
+/// Note that the cursor is kept in the resume state, so the iteration can be continued
+/// later with `resume_query_collection_all`. Items produced after the iteration has
+/// finished are not returned.
+///
+/// Application should call the `remove_iterator` when iterator is no longer required.
+#[api_function]
+pub async fn query_collection_all(
+    context: std::sync::Arc<ClientContext>,
+    params: ParamsOfQueryCollectionAll,
+) -> ClientResult<RegisteredIterator> {
+    register_iterator(&context, Box::new(CollectionIterator::new(params))).await
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ParamsOfResumeQueryCollectionAll {
+    /// Iterator state from which to resume.
+    ///
+    /// Same as value returned from `iterator_next`.
+    pub resume_state: Value,
+}
+
+/// Resumes the collection iterator created with `query_collection_all`.
+///
+/// The iterator stays exactly at the same position where the `resume_state` was catched.
+///
+/// Application should call the `remove_iterator` when iterator is no longer required.
+#[api_function]
+pub async fn resume_query_collection_all(
+    context: std::sync::Arc<ClientContext>,
+    params: ParamsOfResumeQueryCollectionAll,
+) -> ClientResult<RegisteredIterator> {
+    register_iterator(&context, Box::new(CollectionIterator::resume(params)?)).await
+}
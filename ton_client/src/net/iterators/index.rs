@@ -0,0 +1,127 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+// Persistent index of the iterated blocks: shard -> time bucket -> (gen_utime, block id).
+// The index is filled while iterating, so creating an iterator at an old timestamp
+// seeks to the indexed block instead of searching for it again.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use serde_json::Value;
+use sha2::Digest;
+use ton_block::ShardIdent;
+
+use crate::client::storage::KeyValueStorage;
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use crate::net::iterators::block::BlockFields;
+
+/// Time range covered by a single index record, in seconds
+const BUCKET_SECONDS: u32 = 3600;
+
+/// Maximum distance between the requested time and the indexed block time, in seconds.
+/// Iteration starting from a more distant block passes too many blocks before the
+/// requested time, so such index entries are ignored.
+const MAX_SEEK_GAP: u32 = 60;
+
+pub(crate) struct BlockIndex {
+    storage: Arc<dyn KeyValueStorage>,
+}
+
+impl BlockIndex {
+    pub fn with_storage(storage: Arc<dyn KeyValueStorage>) -> Self {
+        Self { storage }
+    }
+
+    /// Opens the index in the local storage if `iterators_index` is enabled in the config
+    pub async fn open(context: &Arc<ClientContext>) -> ClientResult<Option<Arc<Self>>> {
+        let server_link = context.get_server_link()?;
+        if !server_link.config().iterators_index.unwrap_or(false) {
+            return Ok(None);
+        }
+        let endpoints = server_link.get_all_endpoint_addresses().await?.join(",");
+        let storage_name = format!(
+            "iterators/{}",
+            hex::encode(&sha2::Sha256::digest(endpoints.as_bytes())[..4]),
+        );
+        let storage = crate::client::LocalStorage::new(
+            context.config.local_storage_path.clone(),
+            storage_name,
+        )
+        .await?;
+        Ok(Some(Arc::new(Self::with_storage(Arc::new(storage)))))
+    }
+
+    fn bucket_key(shard: &ShardIdent, bucket: u32) -> String {
+        format!(
+            "blocks_{:x}_{:016x}_{}",
+            shard.workchain_id() as u32,
+            shard.shard_prefix_with_tag(),
+            bucket
+        )
+    }
+
+    async fn read_bucket(&self, key: &str) -> ClientResult<BTreeMap<u32, String>> {
+        Ok(match self.storage.get_str(key).await? {
+            Some(value) => serde_json::from_str(&value)
+                .map_err(|err| crate::client::Error::internal_error(err))?,
+            None => BTreeMap::new(),
+        })
+    }
+
+    /// Adds the iterated blocks into the index
+    pub async fn record_blocks(&self, blocks: &[Value]) -> ClientResult<()> {
+        let mut buckets = BTreeMap::<String, Vec<(u32, String)>>::new();
+        for block in blocks {
+            let fields = BlockFields(block);
+            if fields.id().is_empty() || fields.gen_utime() == 0 {
+                continue;
+            }
+            let shard = fields.as_shard_ident().shard_ident()?;
+            buckets
+                .entry(Self::bucket_key(&shard, fields.gen_utime() / BUCKET_SECONDS))
+                .or_default()
+                .push((fields.gen_utime(), fields.id().to_string()));
+        }
+        for (key, entries) in buckets {
+            let mut bucket = self.read_bucket(&key).await?;
+            let len = bucket.len();
+            bucket.extend(entries);
+            if bucket.len() != len {
+                let value = serde_json::to_string(&bucket)
+                    .map_err(|err| crate::client::Error::internal_error(err))?;
+                self.storage.put_str(&key, &value).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the id of the latest indexed shard block with `gen_utime` <= `time`
+    pub async fn seek(&self, shard: &ShardIdent, time: u32) -> ClientResult<Option<String>> {
+        let bucket = time / BUCKET_SECONDS;
+        let min_time = time.saturating_sub(MAX_SEEK_GAP);
+        for bucket in (min_time / BUCKET_SECONDS..=bucket).rev() {
+            let entries = self.read_bucket(&Self::bucket_key(shard, bucket)).await?;
+            if let Some((gen_utime, id)) = entries.range(..=time).next_back() {
+                return Ok(if *gen_utime >= min_time {
+                    Some(id.clone())
+                } else {
+                    None
+                });
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,311 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde_json::Value;
+use ton_block::{Block, Deserializable};
+
+use crate::boc::{parse_block, ParamsOfParse};
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use crate::net::iterators::block::{shard_ident_parse, shard_ident_to_string};
+use crate::net::iterators::block_iterator::filter::Filter;
+use crate::net::iterators::block_iterator::ParamsOfCreateBlockIterator;
+use crate::net::iterators::{register_iterator, ResultOfIteratorNext};
+use crate::net::{ChainIterator, RegisteredIterator};
+use crate::proofs::archive::parse_entry_name;
+
+/// Iteration order of the local blocks: `gen_utime`, `workchain_id`,
+/// tagged shard prefix, `seq_no`
+type EntryKey = (u32, i32, u64, u32);
+
+#[derive(Serialize, Deserialize)]
+struct ResumeState {
+    path: String,
+    shards: Vec<String>,
+    start_time: Option<u32>,
+    end_time: Option<u32>,
+    position: Option<EntryKey>,
+}
+
+fn is_block_file(name: &str) -> bool {
+    match parse_entry_name(name) {
+        Some((kind, ..)) => kind == "block",
+        None => name.ends_with(".boc"),
+    }
+}
+
+async fn read_file(path: &Path) -> ClientResult<Vec<u8>> {
+    tokio::fs::read(path).await.map_err(|err| {
+        crate::client::Error::local_storage_error(format!(
+            "unable to read {}: {}",
+            path.display(),
+            err
+        ))
+    })
+}
+
+fn read_block(path: &Path, boc: &[u8]) -> ClientResult<Block> {
+    Block::construct_from_bytes(boc).map_err(|err| {
+        crate::boc::Error::invalid_boc(format!("{} is not a block: {}", path.display(), err))
+    })
+}
+
+pub(crate) struct LocalBlockIterator {
+    path: PathBuf,
+    filter: Filter,
+    entries: VecDeque<(EntryKey, PathBuf)>,
+    position: Option<EntryKey>,
+}
+
+impl LocalBlockIterator {
+    pub async fn new(params: ParamsOfCreateLocalBlockIterator) -> ClientResult<Self> {
+        let filter = Filter::from(&ParamsOfCreateBlockIterator {
+            start_time: params.start_time,
+            end_time: params.end_time,
+            shard_filter: params.shard_filter,
+            result: None,
+        })?;
+        Self::with_position(PathBuf::from(params.path), filter, None).await
+    }
+
+    async fn with_position(
+        path: PathBuf,
+        filter: Filter,
+        position: Option<EntryKey>,
+    ) -> ClientResult<Self> {
+        let mut iterator = Self {
+            path,
+            filter,
+            entries: VecDeque::new(),
+            position,
+        };
+        iterator.scan().await?;
+        Ok(iterator)
+    }
+
+    pub async fn resume(params: ParamsOfResumeLocalBlockIterator) -> ClientResult<Self> {
+        let resume = ResumeState::deserialize(&params.resume_state).map_err(|e| {
+            crate::client::Error::internal_error(format!("Invalid iterator resume state: {}", e))
+        })?;
+        let mut shards = Vec::new();
+        for shard in &resume.shards {
+            shards.push(shard_ident_parse(shard)?);
+        }
+        let filter = Filter {
+            shards,
+            start_time: resume.start_time,
+            end_time: resume.end_time,
+            result_fields: String::new(),
+        };
+        Self::with_position(PathBuf::from(resume.path), filter, resume.position).await
+    }
+
+    /// Collects the blocks of the directory which satisfy the filter and follow
+    /// the current position
+    async fn scan(&mut self) -> ClientResult<()> {
+        let mut dir = tokio::fs::read_dir(&self.path).await.map_err(|err| {
+            crate::client::Error::local_storage_error(format!(
+                "unable to read directory {}: {}",
+                self.path.display(),
+                err
+            ))
+        })?;
+        let mut entries = Vec::new();
+        while let Some(entry) = dir
+            .next_entry()
+            .await
+            .map_err(|err| crate::client::Error::local_storage_error(err))?
+        {
+            if !is_block_file(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+            let path = entry.path();
+            let info = read_block(&path, &read_file(&path).await?)?
+                .read_info()
+                .map_err(|err| crate::boc::Error::invalid_boc(err))?;
+            let shard = info.shard();
+            let key = (
+                info.gen_utime().0,
+                shard.workchain_id(),
+                shard.shard_prefix_with_tag(),
+                info.seq_no(),
+            );
+            if self.position.map(|position| key <= position).unwrap_or(false)
+                || !self.filter.match_shard(shard)
+                || !self.filter.match_start_time(key.0)
+                || !self.filter.match_end_time(key.0)
+            {
+                continue;
+            }
+            entries.push((key, path));
+        }
+        entries.sort_by_key(|(key, _)| *key);
+        self.entries = entries.into();
+        Ok(())
+    }
+
+    fn get_resume_state_value(&self) -> ClientResult<Value> {
+        serde_json::to_value(ResumeState {
+            path: self.path.to_string_lossy().to_string(),
+            shards: self.filter.shards.iter().map(shard_ident_to_string).collect(),
+            start_time: self.filter.start_time,
+            end_time: self.filter.end_time,
+            position: self.position,
+        })
+        .map_err(|e| {
+            crate::client::Error::internal_error(format!(
+                "Can't serialize iterator resume state: {}",
+                e
+            ))
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl ChainIterator for LocalBlockIterator {
+    async fn next(
+        &mut self,
+        context: &Arc<ClientContext>,
+        limit: u32,
+        return_resume_state: bool,
+    ) -> ClientResult<ResultOfIteratorNext> {
+        let limit = limit.max(1) as usize;
+
+        // Blocks added to the directory after the last scan are picked up
+        // when the scanned ones are exhausted
+        if self.entries.is_empty() {
+            self.scan().await?;
+        }
+
+        let mut items = Vec::new();
+        while items.len() < limit {
+            let (key, path) = match self.entries.pop_front() {
+                Some(entry) => entry,
+                None => break,
+            };
+            let boc = read_file(&path).await?;
+            let parsed = parse_block(
+                context.clone(),
+                ParamsOfParse {
+                    boc: base64::encode(&boc),
+                },
+            )
+            .await?;
+            items.push(parsed.parsed);
+            self.position = Some(key);
+        }
+
+        let resume_state = if return_resume_state {
+            Some(self.get_resume_state_value()?)
+        } else {
+            None
+        };
+
+        Ok(ResultOfIteratorNext {
+            has_more: !self.entries.is_empty(),
+            items,
+            resume_state,
+        })
+    }
+
+    fn after_remove(&mut self, _context: &Arc<ClientContext>) {}
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ParamsOfCreateLocalBlockIterator {
+    /// Path to the directory with the block BOC files.
+    pub path: String,
+
+    /// Starting time to iterate from.
+    ///
+    /// If the application specifies this parameter then the iteration
+    /// includes blocks with `gen_utime` >= `start_time`.
+    ///
+    /// Must be specified in seconds.
+    pub start_time: Option<u32>,
+
+    /// Optional end time to iterate for.
+    ///
+    /// If the application specifies this parameter then the iteration
+    /// includes blocks with `gen_utime` < `end_time`.
+    ///
+    /// Must be specified in seconds.
+    pub end_time: Option<u32>,
+
+    /// Shard prefix filter.
+    ///
+    /// Same as the `shard_filter` parameter of the `create_block_iterator` function.
+    pub shard_filter: Option<Vec<String>>,
+}
+
+/// Creates block iterator over the blocks stored in a local directory.
+///
+/// The iterator doesn't require the network, so the block processing code written
+/// for `create_block_iterator` can be reused for reprocessing and backtesting with the
+/// blocks saved before. Iterator is used with `iterator_next` and `remove_iterator`
+/// functions the same way as the block iterator.
+///
+/// Blocks are read from the files named as node archive entries
+/// (`block_(<workchain_id>,<shard>,<seq_no>):<root_hash>:<file_hash>`) and from the files
+/// with `.boc` extension. Files must contain the binary block BOCs.
+///
+/// Blocks are iterated in the order of `gen_utime`, blocks with the same time are ordered by
+/// the workchain, the shard and `seq_no`. Iterated items are the blocks parsed the same way as
+/// `boc.parse_block` does (all the fields and the `boc` are returned). `has_more` is `false`
+/// when all the blocks of the directory are iterated. The directory is scanned again on the
+/// next `iterator_next` call, so the blocks added later are iterated too.
+///
+/// Application should call the `remove_iterator` when iterator is no longer required.
+#[api_function]
+pub async fn create_local_block_iterator(
+    context: std::sync::Arc<ClientContext>,
+    params: ParamsOfCreateLocalBlockIterator,
+) -> ClientResult<RegisteredIterator> {
+    register_iterator(
+        &context,
+        Box::new(LocalBlockIterator::new(params).await?),
+    )
+    .await
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ParamsOfResumeLocalBlockIterator {
+    /// Iterator state from which to resume.
+    ///
+    /// Same as value returned from `iterator_next`.
+    pub resume_state: Value,
+}
+
+/// Resumes local block iterator.
+///
+/// The iterator continues with the blocks following the last block iterated before
+/// the `resume_state` was catched.
+///
+/// Application should call the `remove_iterator` when iterator is no longer required.
+#[api_function]
+pub async fn resume_local_block_iterator(
+    context: std::sync::Arc<ClientContext>,
+    params: ParamsOfResumeLocalBlockIterator,
+) -> ClientResult<RegisteredIterator> {
+    register_iterator(
+        &context,
+        Box::new(LocalBlockIterator::resume(params).await?),
+    )
+    .await
+}
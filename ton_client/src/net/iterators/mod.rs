@@ -13,6 +13,10 @@
 
 pub(crate) mod block;
 pub(crate) mod block_iterator;
+pub(crate) mod collection_iterator;
+pub(crate) mod index;
+#[cfg(feature = "proofs")]
+pub(crate) mod local_block_iterator;
 pub(crate) mod transaction;
 pub(crate) mod transaction_iterator;
 
@@ -21,7 +25,8 @@ mod tests;
 
 use crate::client::ClientContext;
 use crate::error::ClientResult;
-use crate::net::{query_collection, ParamsOfQueryCollection};
+use crate::net::archive::archive_query_collection;
+use crate::net::ParamsOfQueryCollection;
 use rand::RngCore;
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
@@ -176,6 +181,7 @@ pub(crate) async fn query_by_ids(
     collection: &str,
     ids: Vec<String>,
     result_fields: &str,
+    archive: bool,
 ) -> ClientResult<Vec<Value>> {
     let mut items = Vec::new();
     let mut tail_ids = ids;
@@ -187,14 +193,15 @@ pub(crate) async fn query_by_ids(
         let mut query_queue: HashSet<String> = head_ids.iter().cloned().collect();
         while !query_queue.is_empty() {
             let portion_ids: Vec<String> = query_queue.iter().cloned().collect();
-            let portion = query_collection(
-                client.clone(),
+            let portion = archive_query_collection(
+                client,
                 ParamsOfQueryCollection {
                     collection: collection.to_string(),
                     filter: Some(json!({ "id": { "in": portion_ids } })),
                     result: result_fields.to_string(),
                     ..Default::default()
                 },
+                archive,
             )
             .await?
             .result;
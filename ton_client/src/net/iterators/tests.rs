@@ -237,3 +237,216 @@ while (has_more) {
 await client.net.remove_iterator(iterator);
 
  */
+
+#[tokio::test(core_threads = 2)]
+async fn block_index() {
+    use crate::client::storage::InMemoryKeyValueStorage;
+    use crate::net::iterators::index::BlockIndex;
+    use ton_block::ShardIdent;
+
+    let index = BlockIndex::with_storage(Arc::new(InMemoryKeyValueStorage::new()));
+    let block = |id: &str, workchain_id: i32, shard: &str, gen_utime: u32| json!({
+        "id": id,
+        "workchain_id": workchain_id,
+        "shard": shard,
+        "gen_utime": gen_utime,
+    });
+    index.record_blocks(&[
+        block("m1", -1, "8000000000000000", 7190),
+        block("m2", -1, "8000000000000000", 7205),
+        block("w1", 0, "8000000000000000", 7203),
+    ]).await.unwrap();
+
+    let master = ShardIdent::masterchain();
+    assert_eq!(index.seek(&master, 7204).await.unwrap(), Some("m1".to_string()));
+    assert_eq!(index.seek(&master, 7205).await.unwrap(), Some("m2".to_string()));
+    assert_eq!(index.seek(&master, 7189).await.unwrap(), None);
+    // Indexed block is too far from the requested time
+    assert_eq!(index.seek(&master, 7300).await.unwrap(), None);
+
+    let workchain = ShardIdent::with_tagged_prefix(0, 0x8000000000000000).unwrap();
+    assert_eq!(index.seek(&workchain, 7204).await.unwrap(), Some("w1".to_string()));
+}
+
+#[tokio::test(core_threads = 2)]
+async fn local_block_iterator() {
+    use crate::net::{
+        create_local_block_iterator, resume_local_block_iterator, ParamsOfCreateLocalBlockIterator,
+        ParamsOfResumeLocalBlockIterator,
+    };
+    use ton_block::{Block, Deserializable};
+
+    let context = Arc::new(ClientContext::new(Default::default()).unwrap());
+    let boc = std::fs::read("src/proofs/tests/data/key_block.boc").unwrap();
+    let gen_utime = Block::construct_from_bytes(&boc)
+        .unwrap()
+        .read_info()
+        .unwrap()
+        .gen_utime()
+        .0;
+
+    let path = std::env::temp_dir().join(format!("local_blocks_{}", std::process::id()));
+    std::fs::create_dir_all(&path).unwrap();
+    std::fs::write(path.join("key_block.boc"), &boc).unwrap();
+    std::fs::write(path.join("unrelated.txt"), "skipped").unwrap();
+
+    let next = |iterator: RegisteredIterator| {
+        let context = context.clone();
+        async move {
+            let result = iterator_next(
+                context.clone(),
+                ParamsOfIteratorNext {
+                    iterator: iterator.handle,
+                    limit: Some(10),
+                    return_resume_state: Some(true),
+                },
+            )
+            .await
+            .unwrap();
+            remove_iterator(context, iterator).await.unwrap();
+            result
+        }
+    };
+    let create = |start_time: Option<u32>, shard_filter: Option<Vec<String>>| {
+        create_local_block_iterator(
+            context.clone(),
+            ParamsOfCreateLocalBlockIterator {
+                path: path.to_string_lossy().to_string(),
+                start_time,
+                end_time: None,
+                shard_filter,
+            },
+        )
+    };
+
+    let result = next(create(Some(gen_utime), None).await.unwrap()).await;
+    assert_eq!(result.items.len(), 1);
+    assert_eq!(result.items[0]["gen_utime"], gen_utime);
+    assert_eq!(result.items[0]["workchain_id"], -1);
+    assert!(!result.has_more);
+
+    // iterated block is not returned after resume
+    let resumed = resume_local_block_iterator(
+        context.clone(),
+        ParamsOfResumeLocalBlockIterator {
+            resume_state: result.resume_state.unwrap(),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(next(resumed).await.items.is_empty());
+
+    let result = next(create(Some(gen_utime + 1), None).await.unwrap()).await;
+    assert!(result.items.is_empty());
+
+    let shard_filter = Some(vec!["0:8000000000000000".to_string()]);
+    let result = next(create(None, shard_filter).await.unwrap()).await;
+    assert!(result.items.is_empty());
+
+    std::fs::remove_dir_all(&path).unwrap();
+}
+
+#[test]
+fn collection_filters_intersection() {
+    use super::collection_iterator::and_filters;
+
+    // the stricter condition of the same operator is kept
+    assert_eq!(
+        and_filters(
+            &json!({ "workchain_id": { "eq": -1 }, "seq_no": { "gt": 100 } }),
+            &json!({ "seq_no": { "gt": 200 } }),
+        ),
+        json!({ "workchain_id": { "eq": -1 }, "seq_no": { "gt": 200 } }),
+    );
+    assert_eq!(
+        and_filters(&json!({ "lt": { "lt": "0x10" } }), &json!({ "lt": { "lt": "0xf" } })),
+        json!({ "lt": { "lt": "0xf" } }),
+    );
+
+    // alternatives are expanded, unsatisfiable alternatives are dropped
+    assert_eq!(
+        and_filters(
+            &json!({ "status": { "eq": 1 }, "OR": { "status": { "eq": 2 } } }),
+            &json!({ "seq_no": { "gt": 5 }, "OR": { "status": { "eq": 1 }, "seq_no": { "eq": 5 } } }),
+        ),
+        json!({
+            "status": { "eq": 1 },
+            "seq_no": { "gt": 5 },
+            "OR": {
+                "status": { "eq": 1 },
+                "seq_no": { "eq": 5 },
+                "OR": {
+                    "status": { "eq": 2 },
+                    "seq_no": { "gt": 5 },
+                },
+            },
+        }),
+    );
+    assert_eq!(
+        and_filters(&json!({ "status": { "eq": 1 } }), &json!({ "status": { "eq": 2 } })),
+        json!({ "id": { "in": [] } }),
+    );
+}
+
+#[tokio::test(core_threads = 2)]
+async fn query_collection_all() {
+    let client = TestClient::new();
+    let filter = json!({ "workchain_id": { "eq": -1 }, "seq_no": { "gt": 1 } });
+    let expected: ResultOfQueryCollection = client
+        .request_async(
+            "net.query_collection",
+            ParamsOfQueryCollection {
+                collection: "blocks".to_string(),
+                filter: Some(filter.clone()),
+                result: "id".to_string(),
+                order: Some(vec![crate::net::OrderBy {
+                    path: "seq_no".to_string(),
+                    direction: crate::net::SortDirection::ASC,
+                }]),
+                limit: Some(7),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let last_seq_no: ResultOfQueryCollection = client
+        .request_async(
+            "net.query_collection",
+            ParamsOfQueryCollection {
+                collection: "blocks".to_string(),
+                filter: Some(json!({ "id": { "eq": expected.result[6]["id"] } })),
+                result: "seq_no".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let iterator: RegisteredIterator = client
+        .request_async(
+            "net.query_collection_all",
+            json!({
+                "collection": "blocks",
+                "filter": {
+                    "workchain_id": { "eq": -1 },
+                    "seq_no": { "gt": 1, "le": last_seq_no.result[0]["seq_no"] },
+                },
+                "result": "id workchain_id",
+                "page_size": 3,
+            }),
+        )
+        .await
+        .unwrap();
+    let mut ids: HashSet<String> = expected
+        .result
+        .iter()
+        .map(|block| block["id"].as_str().unwrap().to_string())
+        .collect();
+    let mut extra_ids = HashSet::new();
+    let resume_state = iterate(&client, iterator.handle, &mut ids, &mut extra_ids, 100).await;
+    remove_iterator(&client, iterator.handle).await;
+
+    assert!(resume_state.is_none());
+    assert!(ids.is_empty());
+    assert!(extra_ids.is_empty());
+}
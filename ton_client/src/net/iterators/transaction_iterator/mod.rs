@@ -99,7 +99,13 @@ impl TransactionIterator {
         accounts_filter: Option<Vec<String>>,
     ) -> ClientResult<Self> {
         let blocks = BlockIterator::from_resume_state(context, resume.blocks).await?;
-        let next = Self::query_transactions(context, resume.next, &resume.result_fields).await?;
+        let next = Self::query_transactions(
+            context,
+            resume.next,
+            &resume.result_fields,
+            BlockIterator::is_archive(context, blocks.filter.start_time)?,
+        )
+        .await?;
         Ok(Self {
             blocks,
             accounts_filter: accounts_filter
@@ -125,12 +131,14 @@ impl TransactionIterator {
         context: &Arc<ClientContext>,
         transaction_ids: Vec<String>,
         fields: &str,
+        archive: bool,
     ) -> ClientResult<Vec<Value>> {
         query_by_ids(
             context,
             "transactions",
             transaction_ids,
             &format!("{} {}", TRANSACTION_FIELDS, fields),
+            archive,
         )
         .await
     }
@@ -166,7 +174,13 @@ impl TransactionIterator {
                 }
             }
         }
-        self.next = Self::query_transactions(context, transaction_ids, &self.result_fields).await?;
+        self.next = Self::query_transactions(
+            context,
+            transaction_ids,
+            &self.result_fields,
+            BlockIterator::is_archive(context, blocks.filter.start_time)?,
+        )
+        .await?;
         self.blocks = blocks;
         Ok(())
     }
@@ -0,0 +1,140 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+// Queries protocol over JSON-RPC 2.0 gateways.
+//
+// Collection queries and message sending are mapped to the JRPC methods with the same
+// names and parameters as the corresponding `net` functions.
+
+use crate::error::ClientResult;
+use crate::net::{Error, ParamsOfQueryOperation, PostRequest};
+use serde_json::Value;
+
+const JRPC_VERSION: &str = "2.0";
+const FETCH_ADDITIONAL_TIMEOUT: u32 = 5000;
+
+pub(crate) struct JrpcRequest {
+    pub body: String,
+    pub timeout: Option<u32>,
+}
+
+fn request(id: usize, method: &str, params: Value) -> String {
+    json!({
+        "jsonrpc": JRPC_VERSION,
+        "id": id,
+        "method": method,
+        "params": params,
+    })
+    .to_string()
+}
+
+impl JrpcRequest {
+    pub fn with_operation(
+        id: usize,
+        operation: &ParamsOfQueryOperation,
+        default_wait_for_timeout: u32,
+    ) -> ClientResult<Self> {
+        match operation {
+            ParamsOfQueryOperation::QueryCollection(params) => Ok(Self {
+                body: request(
+                    id,
+                    "query_collection",
+                    json!({
+                        "collection": params.collection,
+                        "filter": params.filter,
+                        "result": params.result,
+                        "order": params.order,
+                        "limit": params.limit,
+                    }),
+                ),
+                timeout: None,
+            }),
+            ParamsOfQueryOperation::WaitForCollection(params) => {
+                let timeout = params.timeout.unwrap_or(default_wait_for_timeout);
+                Ok(Self {
+                    body: request(
+                        id,
+                        "wait_for_collection",
+                        json!({
+                            "collection": params.collection,
+                            "filter": params.filter,
+                            "result": params.result,
+                            "timeout": timeout,
+                        }),
+                    ),
+                    timeout: Some(timeout + FETCH_ADDITIONAL_TIMEOUT),
+                })
+            }
+            ParamsOfQueryOperation::AggregateCollection(_) => {
+                Err(Error::not_supported("aggregate_collection over JRPC"))
+            }
+            ParamsOfQueryOperation::QueryCounterparties(_) => {
+                Err(Error::not_supported("query_counterparties over JRPC"))
+            }
+        }
+    }
+
+    pub fn with_post_request(id: usize, request_params: &PostRequest) -> Self {
+        Self {
+            body: request(
+                id,
+                "send_message",
+                json!({
+                    "id": request_params.id,
+                    "boc": request_params.body,
+                }),
+            ),
+            timeout: None,
+        }
+    }
+}
+
+/// Extracts the result from the JRPC response. Server errors are returned as `JrpcError`
+pub(crate) fn get_result(response: Value) -> ClientResult<Value> {
+    if response["jsonrpc"] != JRPC_VERSION {
+        return Err(Error::invalid_server_response(format!(
+            "JRPC {} response expected: {}",
+            JRPC_VERSION, response
+        )));
+    }
+    if !response["error"].is_null() {
+        return Err(Error::jrpc_server_error(&response["error"]));
+    }
+    match response.get("result") {
+        Some(result) => Ok(result.clone()),
+        None => Err(Error::invalid_server_response(format!(
+            "Missing result in: {}",
+            response
+        ))),
+    }
+}
+
+/// Checks the result of the query operation the same way as the GraphQL results are checked
+pub(crate) fn get_operation_result(
+    operation: &ParamsOfQueryOperation,
+    result: Value,
+) -> ClientResult<Value> {
+    match operation {
+        ParamsOfQueryOperation::WaitForCollection(_) if result.is_null() => {
+            Err(Error::wait_for_timeout())
+        }
+        ParamsOfQueryOperation::QueryCollection(_) if !result.is_array() => {
+            Err(Error::invalid_server_response(format!(
+                "Array of the collection items expected: {}",
+                result
+            )))
+        }
+        _ => Ok(result),
+    }
+}
@@ -0,0 +1,214 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+// ADNL over TCP transport used by lite servers
+
+use super::tl::{TlReader, TlWriter, ADNL_MESSAGE_ANSWER, ADNL_MESSAGE_QUERY, PUB_ED25519};
+use crate::error::ClientResult;
+use crate::net::Error;
+use aes::cipher::{NewCipher, StreamCipher};
+use aes::Aes256Ctr;
+use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use curve25519_dalek::scalar::Scalar;
+use rand::RngCore;
+use sha2::Digest;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const MAX_PACKET_SIZE: usize = 16 << 20;
+
+fn sha256(data: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = sha2::Sha256::new();
+    for item in data {
+        hasher.update(item);
+    }
+    hasher.finalize().into()
+}
+
+fn cipher(key: &[u8], iv: &[u8]) -> Aes256Ctr {
+    Aes256Ctr::new(key.into(), iv.into())
+}
+
+/// ADNL short id of the ed25519 public key
+pub(crate) fn key_id(public_key: &[u8; 32]) -> [u8; 32] {
+    sha256(&[&PUB_ED25519.to_le_bytes(), public_key])
+}
+
+/// Ed25519 key pair used for the ECDH key exchange
+pub(crate) struct KeyPair {
+    scalar: Scalar,
+    pub public: [u8; 32],
+}
+
+impl KeyPair {
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        Self::from_seed(bytes)
+    }
+
+    pub fn from_seed(mut bytes: [u8; 32]) -> Self {
+        bytes[0] &= 248;
+        bytes[31] &= 127;
+        bytes[31] |= 64;
+        let scalar = Scalar::from_bits(bytes);
+        let public = (&ED25519_BASEPOINT_TABLE * &scalar).compress().to_bytes();
+        Self { scalar, public }
+    }
+
+    pub fn shared_secret(&self, other_public: &[u8; 32]) -> ClientResult<[u8; 32]> {
+        let point = CompressedEdwardsY(*other_public)
+            .decompress()
+            .ok_or_else(|| Error::lite_server_connection_failed("invalid server public key"))?;
+        Ok((point.to_montgomery() * self.scalar).to_bytes())
+    }
+}
+
+/// Cipher used to encrypt the handshake nonce
+pub(crate) fn handshake_cipher(secret: &[u8; 32], nonce_hash: &[u8; 32]) -> Aes256Ctr {
+    let mut key = [0u8; 32];
+    key[..16].copy_from_slice(&secret[..16]);
+    key[16..].copy_from_slice(&nonce_hash[16..]);
+    let mut iv = [0u8; 16];
+    iv[..4].copy_from_slice(&nonce_hash[..4]);
+    iv[4..].copy_from_slice(&secret[20..]);
+    cipher(&key, &iv)
+}
+
+/// Session ciphers derived from the handshake nonce: (client receiving, client sending)
+pub(crate) fn session_ciphers(nonce: &[u8]) -> (Aes256Ctr, Aes256Ctr) {
+    (
+        cipher(&nonce[0..32], &nonce[64..80]),
+        cipher(&nonce[32..64], &nonce[80..96]),
+    )
+}
+
+/// Encrypted packet stream of the established ADNL connection
+pub(crate) struct AdnlStream {
+    stream: TcpStream,
+    receiver: Aes256Ctr,
+    sender: Aes256Ctr,
+}
+
+impl AdnlStream {
+    pub fn new(stream: TcpStream, receiver: Aes256Ctr, sender: Aes256Ctr) -> Self {
+        Self { stream, receiver, sender }
+    }
+
+    pub async fn send(&mut self, payload: &[u8]) -> ClientResult<()> {
+        let mut nonce = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let mut packet = Vec::with_capacity(payload.len() + 68);
+        packet.extend_from_slice(&((payload.len() + 64) as u32).to_le_bytes());
+        packet.extend_from_slice(&nonce);
+        packet.extend_from_slice(payload);
+        packet.extend_from_slice(&sha256(&[&nonce, payload]));
+        self.sender.apply_keystream(&mut packet);
+        self.stream
+            .write_all(&packet)
+            .await
+            .map_err(Error::lite_server_connection_failed)
+    }
+
+    pub async fn receive(&mut self) -> ClientResult<Vec<u8>> {
+        let mut size = [0u8; 4];
+        self.read_exact(&mut size).await?;
+        self.receiver.apply_keystream(&mut size);
+        let size = u32::from_le_bytes(size) as usize;
+        if size < 64 || size > MAX_PACKET_SIZE {
+            return Err(Error::invalid_server_response(format!(
+                "invalid ADNL packet size {}",
+                size
+            )));
+        }
+        let mut packet = vec![0u8; size];
+        self.read_exact(&mut packet).await?;
+        self.receiver.apply_keystream(&mut packet);
+        let (data, hash) = packet.split_at(size - 32);
+        if sha256(&[data])[..] != *hash {
+            return Err(Error::invalid_server_response("ADNL packet checksum mismatch"));
+        }
+        Ok(data[32..].to_vec())
+    }
+
+    async fn read_exact(&mut self, buf: &mut [u8]) -> ClientResult<()> {
+        self.stream
+            .read_exact(buf)
+            .await
+            .map(|_| ())
+            .map_err(Error::lite_server_connection_failed)
+    }
+}
+
+/// Client connection to a lite server
+pub(crate) struct AdnlConnection {
+    stream: AdnlStream,
+}
+
+impl AdnlConnection {
+    pub async fn connect(address: &str, server_key: &[u8; 32]) -> ClientResult<Self> {
+        let stream = TcpStream::connect(address)
+            .await
+            .map_err(|err| Error::lite_server_connection_failed(format!("{}: {}", address, err)))?;
+
+        let mut nonce = [0u8; 160];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let (receiver, sender) = session_ciphers(&nonce);
+
+        let key_pair = KeyPair::generate();
+        let secret = key_pair.shared_secret(server_key)?;
+        let nonce_hash = sha256(&[&nonce]);
+
+        let mut handshake = Vec::with_capacity(256);
+        handshake.extend_from_slice(&key_id(server_key));
+        handshake.extend_from_slice(&key_pair.public);
+        handshake.extend_from_slice(&nonce_hash);
+        let mut encrypted_nonce = nonce;
+        handshake_cipher(&secret, &nonce_hash).apply_keystream(&mut encrypted_nonce);
+        handshake.extend_from_slice(&encrypted_nonce);
+
+        let mut stream = AdnlStream::new(stream, receiver, sender);
+        stream
+            .stream
+            .write_all(&handshake)
+            .await
+            .map_err(Error::lite_server_connection_failed)?;
+
+        // Server confirms the session with an empty packet
+        stream.receive().await?;
+
+        Ok(Self { stream })
+    }
+
+    /// Sends `adnl.message.query` and waits for the answer with the same query id
+    pub async fn query(&mut self, query: &[u8]) -> ClientResult<Vec<u8>> {
+        let mut query_id = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut query_id);
+        let mut message = TlWriter::new(ADNL_MESSAGE_QUERY);
+        message.int256(&query_id).bytes(query);
+        self.stream.send(&message.finish()).await?;
+
+        loop {
+            let packet = self.stream.receive().await?;
+            if packet.is_empty() {
+                continue;
+            }
+            let mut reader = TlReader::new(&packet);
+            if reader.u32()? != ADNL_MESSAGE_ANSWER || reader.int256()? != query_id {
+                continue;
+            }
+            return reader.bytes();
+        }
+    }
+}
@@ -0,0 +1,436 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+#[cfg(feature = "lite")]
+mod adnl;
+pub(crate) mod tl;
+
+#[cfg(all(test, feature = "lite"))]
+mod tests;
+
+use crate::client::ClientContext;
+use crate::encoding::{account_decode, base64_decode};
+use crate::error::ClientResult;
+use crate::net::{Error, NetworkConfig};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use ton_block::{BlockIdExt, MsgAddressInt, ShardIdent};
+use ton_types::UInt256;
+
+#[cfg(feature = "lite")]
+use adnl::AdnlConnection;
+
+#[cfg(not(feature = "lite"))]
+struct AdnlConnection;
+
+#[cfg(not(feature = "lite"))]
+impl AdnlConnection {
+    async fn connect(_address: &str, _server_key: &[u8; 32]) -> ClientResult<Self> {
+        Err(Error::lite_servers_not_available(
+            "library is built without `lite` feature",
+        ))
+    }
+
+    async fn query(&mut self, _query: &[u8]) -> ClientResult<Vec<u8>> {
+        Err(Error::lite_servers_not_available(
+            "library is built without `lite` feature",
+        ))
+    }
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone)]
+pub struct LiteServerConfig {
+    /// Lite server address in `ip:port` format
+    pub address: String,
+    /// Lite server ed25519 public key encoded with base64 as in the `liteservers`
+    /// section of the network global config
+    pub public_key: String,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone, PartialEq)]
+pub struct LiteBlockId {
+    /// Workchain id
+    pub workchain_id: i32,
+    /// Shard prefix with tag encoded as hex, e.g. `8000000000000000`
+    pub shard: String,
+    /// Block sequence number
+    pub seq_no: u32,
+    /// Block root hash encoded as hex
+    pub root_hash: String,
+    /// Block file hash encoded as hex
+    pub file_hash: String,
+}
+
+impl From<&BlockIdExt> for LiteBlockId {
+    fn from(id: &BlockIdExt) -> Self {
+        Self {
+            workchain_id: id.shard().workchain_id(),
+            shard: id.shard().shard_prefix_as_str_with_tag(),
+            seq_no: id.seq_no(),
+            root_hash: id.root_hash().as_hex_string(),
+            file_hash: id.file_hash().as_hex_string(),
+        }
+    }
+}
+
+impl LiteBlockId {
+    fn to_block_id(&self) -> ClientResult<BlockIdExt> {
+        let params = serde_json::to_string(self).unwrap_or_default();
+        let invalid = |err: String| {
+            crate::client::Error::invalid_params(&params, format!("invalid block id: {}", err))
+        };
+        let shard = u64::from_str_radix(&self.shard, 16).map_err(|err| invalid(err.to_string()))?;
+        Ok(BlockIdExt::with_params(
+            ShardIdent::with_tagged_prefix(self.workchain_id, shard)
+                .map_err(|err| invalid(err.to_string()))?,
+            self.seq_no,
+            UInt256::from_str(&self.root_hash).map_err(|err| invalid(err.to_string()))?,
+            UInt256::from_str(&self.file_hash).map_err(|err| invalid(err.to_string()))?,
+        ))
+    }
+}
+
+struct LiteServer {
+    address: String,
+    public_key: [u8; 32],
+    connection: Mutex<Option<AdnlConnection>>,
+}
+
+/// Client of the lite servers specified in `network.lite_servers`.
+///
+/// Queries are sent to the servers in turn. A server is skipped if the connection to it
+/// fails, while lite server errors are returned to the caller as is.
+/// Note that the data returned by lite servers is not checked against the proofs
+/// except for the block and state hashes.
+pub(crate) struct LiteClient {
+    servers: Vec<LiteServer>,
+    next_server: AtomicUsize,
+    #[cfg_attr(not(feature = "lite"), allow(dead_code))]
+    query_timeout: u32,
+}
+
+impl LiteClient {
+    pub fn new(config: &NetworkConfig) -> ClientResult<Option<Self>> {
+        let configs = match &config.lite_servers {
+            Some(configs) if !configs.is_empty() => configs,
+            _ => return Ok(None),
+        };
+        let mut servers = Vec::with_capacity(configs.len());
+        for server in configs {
+            let key = base64_decode(&server.public_key)?;
+            if key.len() != 32 {
+                return Err(crate::client::Error::invalid_config(format!(
+                    "invalid public key of lite server {}: 32 bytes expected",
+                    server.address
+                )));
+            }
+            let mut public_key = [0u8; 32];
+            public_key.copy_from_slice(&key);
+            servers.push(LiteServer {
+                address: server.address.clone(),
+                public_key,
+                connection: Mutex::new(None),
+            });
+        }
+        Ok(Some(Self {
+            servers,
+            next_server: AtomicUsize::new(0),
+            query_timeout: config.query_timeout,
+        }))
+    }
+
+    async fn query_server(server: &LiteServer, query: &[u8]) -> ClientResult<Vec<u8>> {
+        let mut connection = server.connection.lock().await;
+        if connection.is_none() {
+            *connection = Some(AdnlConnection::connect(&server.address, &server.public_key).await?);
+        }
+        let result = match connection.as_mut() {
+            Some(connection) => connection.query(query).await,
+            None => unreachable!(),
+        };
+        if result.is_err() {
+            // Connection state is unknown after the failure
+            *connection = None;
+        }
+        result
+    }
+
+    #[cfg(feature = "lite")]
+    async fn query_server_with_timeout(&self, server: &LiteServer, query: &[u8]) -> ClientResult<Vec<u8>> {
+        let timeout = std::time::Duration::from_millis(self.query_timeout as u64);
+        match tokio::time::timeout(timeout, Self::query_server(server, query)).await {
+            Ok(result) => result,
+            Err(_) => {
+                // Interrupted query leaves the stream in the unknown state
+                *server.connection.lock().await = None;
+                Err(Error::lite_server_connection_failed(format!(
+                    "{}: query timeout",
+                    server.address
+                )))
+            }
+        }
+    }
+
+    #[cfg(not(feature = "lite"))]
+    async fn query_server_with_timeout(&self, server: &LiteServer, query: &[u8]) -> ClientResult<Vec<u8>> {
+        Self::query_server(server, query).await
+    }
+
+    /// Sends the query to the servers in turn until one of them answers.
+    /// Returns the answering server address and the answer.
+    async fn query(&self, query: Vec<u8>) -> ClientResult<(String, Vec<u8>)> {
+        let first = self.next_server.fetch_add(1, Ordering::Relaxed);
+        let mut last_error = None;
+        for i in 0..self.servers.len() {
+            let server = &self.servers[(first + i) % self.servers.len()];
+            match self.query_server_with_timeout(server, &query).await {
+                Ok(answer) => return Ok((server.address.clone(), answer)),
+                Err(err) => last_error = Some(err),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| Error::lite_servers_not_available("no servers")))
+    }
+
+    async fn query_answer(&self, query: Vec<u8>) -> ClientResult<Vec<u8>> {
+        Ok(self.query(query).await?.1)
+    }
+
+    pub async fn get_masterchain_info(&self) -> ClientResult<tl::MasterchainInfo> {
+        tl::parse_masterchain_info(&self.query_answer(tl::get_masterchain_info()).await?)
+    }
+
+    pub async fn lookup_block(&self, shard: &ShardIdent, seq_no: u32) -> ClientResult<BlockIdExt> {
+        let id = tl::parse_block_header(&self.query_answer(tl::lookup_block(shard, seq_no)).await?)?;
+        if id.shard() != shard || id.seq_no() != seq_no {
+            return Err(Error::invalid_server_response(format!(
+                "block {} returned instead of {}:{}",
+                id, shard, seq_no
+            )));
+        }
+        Ok(id)
+    }
+
+    /// Downloads the block and checks that it matches the `id`
+    pub async fn get_block(&self, id: &BlockIdExt) -> ClientResult<Vec<u8>> {
+        let (_, data) = tl::parse_block_data(&self.query_answer(tl::get_block(id)).await?)?;
+        let root_hash = crate::boc::internal::get_boc_hash(&data)?;
+        if UInt256::calc_file_hash(&data) != *id.file_hash()
+            || root_hash != id.root_hash().as_hex_string()
+        {
+            return Err(Error::invalid_server_response(format!(
+                "block data doesn't match block id {}",
+                id
+            )));
+        }
+        Ok(data)
+    }
+
+    /// Downloads the shard state and checks its file hash
+    pub async fn get_state(&self, id: &BlockIdExt) -> ClientResult<Vec<u8>> {
+        let (_, data) = tl::parse_block_state(&self.query_answer(tl::get_state(id)).await?)?;
+        if UInt256::calc_file_hash(&data) != *id.file_hash() {
+            return Err(Error::invalid_server_response(format!(
+                "state data doesn't match block id {}",
+                id
+            )));
+        }
+        Ok(data)
+    }
+
+    pub async fn get_block_proof(
+        &self,
+        known_block: &BlockIdExt,
+        target_block: &BlockIdExt,
+    ) -> ClientResult<tl::PartialBlockProof> {
+        tl::parse_partial_block_proof(
+            &self
+                .query_answer(tl::get_block_proof(known_block, target_block))
+                .await?,
+        )
+    }
+
+    /// Returns the account state at the specified masterchain block
+    /// or at the last one if the block is not specified
+    pub async fn get_account_state(
+        &self,
+        block: Option<BlockIdExt>,
+        address: &MsgAddressInt,
+    ) -> ClientResult<tl::AccountState> {
+        let block = match block {
+            Some(block) => block,
+            None => self.get_masterchain_info().await?.last,
+        };
+        let account_id = address.address().get_bytestring(0);
+        if account_id.len() != 32 {
+            return Err(crate::client::Error::invalid_address(
+                "only 256-bit account addresses are supported",
+                &address.to_string(),
+            ));
+        }
+        tl::parse_account_state(
+            &self
+                .query_answer(tl::get_account_state(
+                    &block,
+                    address.workchain_id(),
+                    &account_id,
+                ))
+                .await?,
+        )
+    }
+
+    /// Sends the message and returns the address of the server which accepted it
+    pub async fn send_message(&self, body: &[u8]) -> ClientResult<String> {
+        let (address, answer) = self.query(tl::send_message(body)).await?;
+        tl::parse_send_msg_status(&answer)?;
+        Ok(address)
+    }
+}
+
+/// Returns the lite client if it is configured
+pub(crate) fn get_lite_client(context: &ClientContext) -> ClientResult<&Arc<LiteClient>> {
+    if context.is_offline() {
+        return Err(crate::client::Error::not_available_offline());
+    }
+    context
+        .net
+        .lite_client
+        .as_ref()
+        .ok_or_else(|| Error::lite_servers_not_available("`network.lite_servers` are not specified"))
+}
+
+/// Returns the lite client if lite servers are the only backend of the context,
+/// so core operations must use them instead of GraphQL
+pub(crate) fn lite_backend(context: &ClientContext) -> Option<&Arc<LiteClient>> {
+    if context.net.server_link.is_some() || context.is_offline() {
+        return None;
+    }
+    context.net.lite_client.as_ref()
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ResultOfLiteGetMasterchainInfo {
+    /// The last masterchain block known to the lite server
+    pub last: LiteBlockId,
+    /// Root hash of the last masterchain state encoded as hex
+    pub state_root_hash: String,
+    /// Root hash of the masterchain zerostate encoded as hex
+    pub zerostate_root_hash: String,
+    /// File hash of the masterchain zerostate encoded as hex
+    pub zerostate_file_hash: String,
+}
+
+/// Returns the last masterchain block known to a lite server
+#[api_function]
+pub async fn lite_get_masterchain_info(
+    context: Arc<ClientContext>,
+) -> ClientResult<ResultOfLiteGetMasterchainInfo> {
+    let info = get_lite_client(&context)?.get_masterchain_info().await?;
+    Ok(ResultOfLiteGetMasterchainInfo {
+        last: LiteBlockId::from(&info.last),
+        state_root_hash: info.state_root_hash.as_hex_string(),
+        zerostate_root_hash: info.zerostate_root_hash.as_hex_string(),
+        zerostate_file_hash: info.zerostate_file_hash.as_hex_string(),
+    })
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ParamsOfLiteGetAccountState {
+    /// Account address
+    pub address: String,
+    /// Masterchain block to get the account state at. The last masterchain block is used
+    /// if not specified.
+    pub block: Option<LiteBlockId>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ResultOfLiteGetAccountState {
+    /// Masterchain block the state is taken at
+    pub block: LiteBlockId,
+    /// Shard block containing the account state
+    pub shard_block: LiteBlockId,
+    /// Account BOC encoded as base64. Absent if the account doesn't exist.
+    pub boc: Option<String>,
+}
+
+/// Returns the account state from a lite server
+#[api_function]
+pub async fn lite_get_account_state(
+    context: Arc<ClientContext>,
+    params: ParamsOfLiteGetAccountState,
+) -> ClientResult<ResultOfLiteGetAccountState> {
+    let address = account_decode(&params.address)?;
+    let block = params.block.map(|block| block.to_block_id()).transpose()?;
+    let state = get_lite_client(&context)?
+        .get_account_state(block, &address)
+        .await?;
+    Ok(ResultOfLiteGetAccountState {
+        block: LiteBlockId::from(&state.block),
+        shard_block: LiteBlockId::from(&state.shard_block),
+        boc: if state.state.is_empty() {
+            None
+        } else {
+            Some(base64::encode(&state.state))
+        },
+    })
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ParamsOfLiteGetBlock {
+    /// Block id
+    pub block: LiteBlockId,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ResultOfLiteGetBlock {
+    /// Block BOC encoded as base64
+    pub boc: String,
+}
+
+/// Downloads the block from a lite server.
+///
+/// Block data is checked against the root and file hashes of the block id.
+#[api_function]
+pub async fn lite_get_block(
+    context: Arc<ClientContext>,
+    params: ParamsOfLiteGetBlock,
+) -> ClientResult<ResultOfLiteGetBlock> {
+    let block = params.block.to_block_id()?;
+    let data = get_lite_client(&context)?.get_block(&block).await?;
+    Ok(ResultOfLiteGetBlock {
+        boc: base64::encode(&data),
+    })
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ParamsOfLiteSendMessage {
+    /// Message BOC encoded as base64
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ResultOfLiteSendMessage {
+    /// Address of the lite server which accepted the message
+    pub server: String,
+}
+
+/// Sends the message through a lite server
+#[api_function]
+pub async fn lite_send_message(
+    context: Arc<ClientContext>,
+    params: ParamsOfLiteSendMessage,
+) -> ClientResult<ResultOfLiteSendMessage> {
+    let body = base64_decode(&params.message)?;
+    let server = get_lite_client(&context)?.send_message(&body).await?;
+    Ok(ResultOfLiteSendMessage { server })
+}
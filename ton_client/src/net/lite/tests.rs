@@ -0,0 +1,213 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use super::adnl::{handshake_cipher, key_id, session_ciphers, AdnlStream, KeyPair};
+use super::tl::{TlReader, TlWriter};
+use super::{ResultOfLiteGetMasterchainInfo, ResultOfLiteSendMessage};
+use crate::net::transport::{find_shard_block, get_transport};
+use crate::net::ErrorCode;
+use crate::tests::TestClient;
+use aes::cipher::StreamCipher;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpListener;
+use ton_block::{Block, Deserializable};
+
+const ROOT_HASH: [u8; 32] = [1; 32];
+const FILE_HASH: [u8; 32] = [2; 32];
+const ZEROSTATE_ROOT_HASH: [u8; 32] = [3; 32];
+
+fn answer(function: &[u8]) -> Vec<u8> {
+    let mut reader = TlReader::new(function);
+    match reader.u32().unwrap() {
+        // liteServer.getMasterchainInfo
+        0x89b5e62e => {
+            let mut answer = TlWriter::new(0x85832881);
+            answer
+                .i32(-1)
+                .u64(0x8000_0000_0000_0000)
+                .i32(100)
+                .int256(&ROOT_HASH)
+                .int256(&FILE_HASH)
+                .int256(&[0; 32])
+                .i32(-1)
+                .int256(&ZEROSTATE_ROOT_HASH)
+                .int256(&[4; 32]);
+            answer.finish()
+        }
+        // liteServer.sendMessage
+        0x690ad482 => {
+            let mut answer = TlWriter::new(0x3950e597);
+            answer.i32(1);
+            answer.finish()
+        }
+        _ => {
+            let mut answer = TlWriter::new(0xbba9e148);
+            answer.i32(404).bytes(b"not implemented");
+            answer.finish()
+        }
+    }
+}
+
+async fn run_server(mut listener: TcpListener, key_pair: KeyPair) {
+    let (mut socket, _) = listener.accept().await.unwrap();
+    let mut handshake = [0u8; 256];
+    socket.read_exact(&mut handshake).await.unwrap();
+    assert_eq!(handshake[..32], key_id(&key_pair.public));
+
+    let mut client_public = [0u8; 32];
+    client_public.copy_from_slice(&handshake[32..64]);
+    let mut nonce_hash = [0u8; 32];
+    nonce_hash.copy_from_slice(&handshake[64..96]);
+    let mut nonce = handshake[96..].to_vec();
+    let secret = key_pair.shared_secret(&client_public).unwrap();
+    handshake_cipher(&secret, &nonce_hash).apply_keystream(&mut nonce);
+
+    // Server receives with the client sending cipher and vice versa
+    let (client_receiver, client_sender) = session_ciphers(&nonce);
+    let mut stream = AdnlStream::new(socket, client_sender, client_receiver);
+    stream.send(&[]).await.unwrap();
+
+    while let Ok(packet) = stream.receive().await {
+        let mut reader = TlReader::new(&packet);
+        assert_eq!(reader.u32().unwrap(), 0xb48bf97a);
+        let query_id = reader.int256().unwrap();
+        let query = reader.bytes().unwrap();
+        let mut query = TlReader::new(&query);
+        assert_eq!(query.u32().unwrap(), 0x798c06df);
+        let function = query.bytes().unwrap();
+
+        let mut message = TlWriter::new(0x0fac8416);
+        message.int256(&query_id).bytes(&answer(&function));
+        stream.send(&message.finish()).await.unwrap();
+    }
+}
+
+#[test]
+fn test_tl_bytes() {
+    for len in &[0usize, 1, 3, 253, 254, 255, 1000] {
+        let data = vec![7u8; *len];
+        let mut writer = TlWriter::default();
+        writer.bytes(&data).u32(0xdeadbeef);
+        let serialized = writer.finish();
+        assert_eq!(serialized.len() % 4, 0);
+
+        let mut reader = TlReader::new(&serialized);
+        assert_eq!(reader.bytes().unwrap(), data);
+        assert_eq!(reader.u32().unwrap(), 0xdeadbeef);
+    }
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_lite_server_queries() {
+    let key_pair = KeyPair::generate();
+    let public_key = base64::encode(&key_pair.public);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap().to_string();
+    tokio::spawn(run_server(listener, key_pair));
+
+    let client = TestClient::new_with_config(json!({
+        "network": {
+            "lite_servers": [{
+                "address": address,
+                "public_key": public_key,
+            }],
+        },
+    }));
+
+    let info: ResultOfLiteGetMasterchainInfo = client
+        .request_async("net.lite_get_masterchain_info", ())
+        .await
+        .unwrap();
+    assert_eq!(info.last.seq_no, 100);
+    assert_eq!(info.last.shard, "8000000000000000");
+    assert_eq!(info.last.root_hash, hex::encode(&ROOT_HASH));
+    assert_eq!(info.zerostate_root_hash, hex::encode(&ZEROSTATE_ROOT_HASH));
+
+    let sent: ResultOfLiteSendMessage = client
+        .request_async(
+            "net.lite_send_message",
+            json!({ "message": base64::encode(&[1, 2, 3]) }),
+        )
+        .await
+        .unwrap();
+    assert_eq!(sent.server, address);
+
+    // Lite server errors are returned as is over the same connection
+    let err = client
+        .request_async::<_, ResultOfLiteGetMasterchainInfo>(
+            "net.lite_get_block",
+            json!({
+                "block": {
+                    "workchain_id": -1,
+                    "shard": "8000000000000000",
+                    "seq_no": 100,
+                    "root_hash": hex::encode(&ROOT_HASH),
+                    "file_hash": hex::encode(&FILE_HASH),
+                },
+            }),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::LiteServerError as u32);
+    assert_eq!(err.data["lite_server_error_code"], 404);
+
+    // Lite servers are the only backend, so the core operations use the lite transport
+    let transport = get_transport(&client.context());
+    let address = crate::encoding::account_decode(&format!("-1:{}", hex::encode(&[5; 32]))).unwrap();
+    assert_eq!(
+        transport.get_last_block_id(&address).await.unwrap(),
+        hex::encode(&ROOT_HASH)
+    );
+    // shard block is resolved from the last masterchain block
+    let address = crate::encoding::account_decode(&format!("0:{}", hex::encode(&[5; 32]))).unwrap();
+    let err = transport.get_last_block_id(&address).await.unwrap_err();
+    assert_eq!(err.code, ErrorCode::LiteServerError as u32);
+}
+
+#[test]
+fn test_find_shard_block() {
+    let mc_block = std::fs::read("src/proofs/tests/data/key_block.boc").unwrap();
+    let block = Block::construct_from_bytes(&mc_block).unwrap();
+    let mut shards = Vec::new();
+    block
+        .read_extra()
+        .unwrap()
+        .read_custom()
+        .unwrap()
+        .unwrap()
+        .shards()
+        .iterate_shards(|shard, descr| {
+            shards.push((shard, descr.root_hash));
+            Ok(true)
+        })
+        .unwrap();
+    assert!(!shards.is_empty());
+
+    for (shard, root_hash) in shards {
+        let address = crate::encoding::account_decode(&format!(
+            "{}:{:016x}{}",
+            shard.workchain_id(),
+            shard.shard_prefix_with_tag(),
+            "0".repeat(48),
+        ))
+        .unwrap();
+        assert_eq!(
+            find_shard_block(&mc_block, &address).unwrap(),
+            root_hash.as_hex_string()
+        );
+    }
+
+    let address = crate::encoding::account_decode(&format!("5:{}", "0".repeat(64))).unwrap();
+    let err = find_shard_block(&mc_block, &address).unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidServerResponse as u32);
+}
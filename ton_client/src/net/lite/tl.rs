@@ -0,0 +1,416 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+// Minimal TL serialization of the lite server API (lite_api.tl) and ADNL messages
+
+use crate::error::ClientResult;
+use crate::net::Error;
+use ton_block::{BlockIdExt, ShardIdent};
+use ton_types::UInt256;
+
+pub(crate) const ADNL_MESSAGE_QUERY: u32 = 0xb48bf97a;
+pub(crate) const ADNL_MESSAGE_ANSWER: u32 = 0x0fac8416;
+pub(crate) const PUB_ED25519: u32 = 0x4813b4c6;
+
+const LITE_SERVER_QUERY: u32 = 0x798c06df;
+const LITE_SERVER_ERROR: u32 = 0xbba9e148;
+const GET_MASTERCHAIN_INFO: u32 = 0x89b5e62e;
+const MASTERCHAIN_INFO: u32 = 0x85832881;
+const GET_ACCOUNT_STATE: u32 = 0x6b890e25;
+const ACCOUNT_STATE: u32 = 0x7079c751;
+const GET_BLOCK: u32 = 0x6377cf0d;
+const BLOCK_DATA: u32 = 0xa574ed6c;
+const SEND_MESSAGE: u32 = 0x690ad482;
+const SEND_MSG_STATUS: u32 = 0x3950e597;
+const LOOKUP_BLOCK: u32 = 0xfac8f71e;
+const BLOCK_HEADER: u32 = 0x752d8219;
+const GET_STATE: u32 = 0xba6e2eb6;
+const BLOCK_STATE: u32 = 0xabaddc0c;
+const GET_BLOCK_PROOF: u32 = 0x8aea9c44;
+const PARTIAL_BLOCK_PROOF: u32 = 0x8ed0d2c1;
+const BLOCK_LINK_BACK: u32 = 0xef7e1bef;
+const BLOCK_LINK_FORWARD: u32 = 0x520fce1c;
+const SIGNATURE_SET: u32 = 0xf644a6e6;
+const BOOL_TRUE: u32 = 0x997275b5;
+const BOOL_FALSE: u32 = 0xbc799737;
+
+#[derive(Default)]
+pub(crate) struct TlWriter {
+    data: Vec<u8>,
+}
+
+impl TlWriter {
+    pub fn new(id: u32) -> Self {
+        let mut writer = Self::default();
+        writer.u32(id);
+        writer
+    }
+
+    pub fn u32(&mut self, value: u32) -> &mut Self {
+        self.data.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn i32(&mut self, value: i32) -> &mut Self {
+        self.data.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn u64(&mut self, value: u64) -> &mut Self {
+        self.data.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn int256(&mut self, value: &[u8]) -> &mut Self {
+        self.data.extend_from_slice(value);
+        self
+    }
+
+    pub fn bytes(&mut self, value: &[u8]) -> &mut Self {
+        let len = value.len();
+        let header_len = if len < 254 {
+            self.data.push(len as u8);
+            1
+        } else {
+            self.data.push(254);
+            self.data.extend_from_slice(&(len as u32).to_le_bytes()[..3]);
+            4
+        };
+        self.data.extend_from_slice(value);
+        let padding = (4 - (header_len + len) % 4) % 4;
+        self.data.extend(std::iter::repeat(0).take(padding));
+        self
+    }
+
+    pub fn block_id(&mut self, id: &BlockIdExt) -> &mut Self {
+        self.i32(id.shard_id.workchain_id())
+            .u64(id.shard_id.shard_prefix_with_tag())
+            .i32(id.seq_no as i32)
+            .int256(id.root_hash.as_slice())
+            .int256(id.file_hash.as_slice())
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+pub(crate) struct TlReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> TlReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn take(&mut self, len: usize) -> ClientResult<&'a [u8]> {
+        if self.data.len() < len {
+            return Err(Error::invalid_server_response("unexpected end of TL data"));
+        }
+        let (value, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(value)
+    }
+
+    pub fn u32(&mut self) -> ClientResult<u32> {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    pub fn i32(&mut self) -> ClientResult<i32> {
+        Ok(self.u32()? as i32)
+    }
+
+    pub fn u64(&mut self) -> ClientResult<u64> {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    pub fn int256(&mut self) -> ClientResult<[u8; 32]> {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(self.take(32)?);
+        Ok(bytes)
+    }
+
+    pub fn bytes(&mut self) -> ClientResult<Vec<u8>> {
+        let first = self.take(1)?[0];
+        let (header_len, len) = if first < 254 {
+            (1, first as usize)
+        } else {
+            let len = self.take(3)?;
+            (4, len[0] as usize | (len[1] as usize) << 8 | (len[2] as usize) << 16)
+        };
+        let value = self.take(len)?.to_vec();
+        self.take((4 - (header_len + len) % 4) % 4)?;
+        Ok(value)
+    }
+
+    pub fn bool(&mut self) -> ClientResult<bool> {
+        match self.u32()? {
+            BOOL_TRUE => Ok(true),
+            BOOL_FALSE => Ok(false),
+            id => Err(Error::invalid_server_response(format!(
+                "unexpected TL Bool constructor 0x{:08x}",
+                id
+            ))),
+        }
+    }
+
+    pub fn string(&mut self) -> ClientResult<String> {
+        Ok(String::from_utf8_lossy(&self.bytes()?).to_string())
+    }
+
+    pub fn block_id(&mut self) -> ClientResult<BlockIdExt> {
+        let workchain_id = self.i32()?;
+        let shard = self.u64()?;
+        let seq_no = self.u32()?;
+        let root_hash = UInt256::from(self.int256()?);
+        let file_hash = UInt256::from(self.int256()?);
+        Ok(BlockIdExt::with_params(
+            ShardIdent::with_tagged_prefix(workchain_id, shard)
+                .map_err(|err| Error::invalid_server_response(err))?,
+            seq_no,
+            root_hash,
+            file_hash,
+        ))
+    }
+
+    /// Reads the constructor id of the answer. Lite server errors are returned as `Err`.
+    pub fn answer(&mut self, expected_id: u32) -> ClientResult<()> {
+        match self.u32()? {
+            id if id == expected_id => Ok(()),
+            LITE_SERVER_ERROR => {
+                let code = self.i32()?;
+                let message = self.string()?;
+                Err(Error::lite_server_error(code, &message))
+            }
+            id => Err(Error::invalid_server_response(format!(
+                "unexpected TL constructor 0x{:08x}, expected 0x{:08x}",
+                id, expected_id
+            ))),
+        }
+    }
+}
+
+/// Wraps the lite server function into `liteServer.query`
+fn lite_query(function: TlWriter) -> Vec<u8> {
+    let mut query = TlWriter::new(LITE_SERVER_QUERY);
+    query.bytes(&function.finish());
+    query.finish()
+}
+
+pub(crate) struct MasterchainInfo {
+    pub last: BlockIdExt,
+    pub state_root_hash: UInt256,
+    pub zerostate_root_hash: UInt256,
+    pub zerostate_file_hash: UInt256,
+}
+
+pub(crate) struct BlockSignature {
+    pub node_id_short: [u8; 32],
+    pub signature: Vec<u8>,
+}
+
+pub(crate) struct SignatureSet {
+    pub validator_set_hash: u32,
+    pub catchain_seqno: u32,
+    pub signatures: Vec<BlockSignature>,
+}
+
+pub(crate) struct BlockLinkForward {
+    pub to_key_block: bool,
+    pub to: BlockIdExt,
+    pub dest_proof: Vec<u8>,
+    pub signatures: SignatureSet,
+}
+
+pub(crate) struct PartialBlockProof {
+    pub complete: bool,
+    // Backward links are skipped: they carry no signatures
+    pub forward_links: Vec<BlockLinkForward>,
+}
+
+pub(crate) struct AccountState {
+    pub block: BlockIdExt,
+    pub shard_block: BlockIdExt,
+    pub state: Vec<u8>,
+}
+
+pub(crate) fn get_masterchain_info() -> Vec<u8> {
+    lite_query(TlWriter::new(GET_MASTERCHAIN_INFO))
+}
+
+pub(crate) fn parse_masterchain_info(data: &[u8]) -> ClientResult<MasterchainInfo> {
+    let mut reader = TlReader::new(data);
+    reader.answer(MASTERCHAIN_INFO)?;
+    let last = reader.block_id()?;
+    let state_root_hash = UInt256::from(reader.int256()?);
+    let _zerostate_workchain_id = reader.i32()?;
+    Ok(MasterchainInfo {
+        last,
+        state_root_hash,
+        zerostate_root_hash: UInt256::from(reader.int256()?),
+        zerostate_file_hash: UInt256::from(reader.int256()?),
+    })
+}
+
+pub(crate) fn get_account_state(block: &BlockIdExt, workchain_id: i32, address: &[u8]) -> Vec<u8> {
+    let mut function = TlWriter::new(GET_ACCOUNT_STATE);
+    function.block_id(block).i32(workchain_id).int256(address);
+    lite_query(function)
+}
+
+pub(crate) fn parse_account_state(data: &[u8]) -> ClientResult<AccountState> {
+    let mut reader = TlReader::new(data);
+    reader.answer(ACCOUNT_STATE)?;
+    let block = reader.block_id()?;
+    let shard_block = reader.block_id()?;
+    let _shard_proof = reader.bytes()?;
+    let _proof = reader.bytes()?;
+    Ok(AccountState {
+        block,
+        shard_block,
+        state: reader.bytes()?,
+    })
+}
+
+pub(crate) fn get_block(block: &BlockIdExt) -> Vec<u8> {
+    let mut function = TlWriter::new(GET_BLOCK);
+    function.block_id(block);
+    lite_query(function)
+}
+
+pub(crate) fn parse_block_data(data: &[u8]) -> ClientResult<(BlockIdExt, Vec<u8>)> {
+    let mut reader = TlReader::new(data);
+    reader.answer(BLOCK_DATA)?;
+    let id = reader.block_id()?;
+    Ok((id, reader.bytes()?))
+}
+
+pub(crate) fn send_message(body: &[u8]) -> Vec<u8> {
+    let mut function = TlWriter::new(SEND_MESSAGE);
+    function.bytes(body);
+    lite_query(function)
+}
+
+pub(crate) fn parse_send_msg_status(data: &[u8]) -> ClientResult<i32> {
+    let mut reader = TlReader::new(data);
+    reader.answer(SEND_MSG_STATUS)?;
+    reader.i32()
+}
+
+pub(crate) fn lookup_block(shard: &ShardIdent, seq_no: u32) -> Vec<u8> {
+    let mut function = TlWriter::new(LOOKUP_BLOCK);
+    // mode 1: lookup by seq_no
+    function
+        .u32(1)
+        .i32(shard.workchain_id())
+        .u64(shard.shard_prefix_with_tag())
+        .i32(seq_no as i32);
+    lite_query(function)
+}
+
+pub(crate) fn parse_block_header(data: &[u8]) -> ClientResult<BlockIdExt> {
+    let mut reader = TlReader::new(data);
+    reader.answer(BLOCK_HEADER)?;
+    reader.block_id()
+}
+
+pub(crate) fn get_state(block: &BlockIdExt) -> Vec<u8> {
+    let mut function = TlWriter::new(GET_STATE);
+    function.block_id(block);
+    lite_query(function)
+}
+
+pub(crate) fn parse_block_state(data: &[u8]) -> ClientResult<(BlockIdExt, Vec<u8>)> {
+    let mut reader = TlReader::new(data);
+    reader.answer(BLOCK_STATE)?;
+    let id = reader.block_id()?;
+    let _root_hash = reader.int256()?;
+    let _file_hash = reader.int256()?;
+    Ok((id, reader.bytes()?))
+}
+
+pub(crate) fn get_block_proof(known_block: &BlockIdExt, target_block: &BlockIdExt) -> Vec<u8> {
+    let mut function = TlWriter::new(GET_BLOCK_PROOF);
+    // mode 1: target block is specified
+    function.u32(1).block_id(known_block).block_id(target_block);
+    lite_query(function)
+}
+
+fn read_signature_set(reader: &mut TlReader) -> ClientResult<SignatureSet> {
+    reader.answer(SIGNATURE_SET)?;
+    let validator_set_hash = reader.u32()?;
+    let catchain_seqno = reader.u32()?;
+    let count = reader.u32()?;
+    let mut signatures = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        signatures.push(BlockSignature {
+            node_id_short: reader.int256()?,
+            signature: reader.bytes()?,
+        });
+    }
+    Ok(SignatureSet {
+        validator_set_hash,
+        catchain_seqno,
+        signatures,
+    })
+}
+
+pub(crate) fn parse_partial_block_proof(data: &[u8]) -> ClientResult<PartialBlockProof> {
+    let mut reader = TlReader::new(data);
+    reader.answer(PARTIAL_BLOCK_PROOF)?;
+    let complete = reader.bool()?;
+    let _from = reader.block_id()?;
+    let _to = reader.block_id()?;
+    let count = reader.u32()?;
+    let mut forward_links = Vec::new();
+    for _ in 0..count {
+        match reader.u32()? {
+            BLOCK_LINK_BACK => {
+                reader.bool()?;
+                reader.block_id()?;
+                reader.block_id()?;
+                reader.bytes()?;
+                reader.bytes()?;
+                reader.bytes()?;
+            }
+            BLOCK_LINK_FORWARD => {
+                let to_key_block = reader.bool()?;
+                let _from = reader.block_id()?;
+                let to = reader.block_id()?;
+                let dest_proof = reader.bytes()?;
+                let _config_proof = reader.bytes()?;
+                forward_links.push(BlockLinkForward {
+                    to_key_block,
+                    to,
+                    dest_proof,
+                    signatures: read_signature_set(&mut reader)?,
+                });
+            }
+            id => {
+                return Err(Error::invalid_server_response(format!(
+                    "unexpected TL BlockLink constructor 0x{:08x}",
+                    id
+                )))
+            }
+        }
+    }
+    Ok(PartialBlockProof {
+        complete,
+        forward_links,
+    })
+}
@@ -11,13 +11,36 @@
 * limitations under the License.
 */
 
+pub use accounts_sync::{
+    stop_sync_accounts, sync_accounts, AccountsSyncEvent, AccountsSyncState,
+    ParamsOfStopSyncAccounts, ParamsOfSyncAccounts, ResultOfSyncAccounts, SyncedAccount,
+};
 pub use batch::{batch_query, ParamsOfBatchQuery, ResultOfBatchQuery};
+pub use body_decoding::BodyDecoding;
+pub use block_headers::{subscribe_block_headers, BlockHeader, ParamsOfSubscribeBlockHeaders};
+pub use blockchain::{
+    blockchain_account_transactions, blockchain_key_blocks, blockchain_workchain_blocks,
+    BlockchainSeqNoRange, ParamsOfBlockchainAccountTransactions, ParamsOfBlockchainKeyBlocks,
+    ParamsOfBlockchainWorkchainBlocks, ResultOfBlockchainQuery,
+};
 pub(crate) use endpoint::Endpoint;
+pub use endpoint_stats::{
+    get_endpoint_stats, reset_endpoint_stats, EndpointStats, ResultOfGetEndpointStats,
+};
 pub use errors::{Error, ErrorCode};
 pub use iterators::block_iterator::{
     create_block_iterator, resume_block_iterator, ParamsOfCreateBlockIterator,
     ParamsOfResumeBlockIterator,
 };
+pub use iterators::collection_iterator::{
+    query_collection_all, resume_query_collection_all, ParamsOfQueryCollectionAll,
+    ParamsOfResumeQueryCollectionAll,
+};
+#[cfg(feature = "proofs")]
+pub use iterators::local_block_iterator::{
+    create_local_block_iterator, resume_local_block_iterator, ParamsOfCreateLocalBlockIterator,
+    ParamsOfResumeLocalBlockIterator,
+};
 pub use iterators::transaction_iterator::{
     create_transaction_iterator, resume_transaction_iterator, ParamsOfCreateTransactionIterator,
     ParamsOfResumeTransactionIterator,
@@ -26,43 +49,75 @@ pub use iterators::{
     iterator_next, remove_iterator, ChainIterator, ParamsOfIteratorNext, RegisteredIterator,
     ResultOfIteratorNext,
 };
+pub use lite::{
+    lite_get_account_state, lite_get_block, lite_get_masterchain_info, lite_send_message,
+    LiteBlockId, LiteServerConfig, ParamsOfLiteGetAccountState, ParamsOfLiteGetBlock,
+    ParamsOfLiteSendMessage, ResultOfLiteGetAccountState, ResultOfLiteGetBlock,
+    ResultOfLiteGetMasterchainInfo, ResultOfLiteSendMessage,
+};
 pub use queries::{
-    aggregate_collection, query, query_collection, query_counterparties, wait_for_collection,
-    ParamsOfQuery, ParamsOfWaitForCollection, ResultOfAggregateCollection, ResultOfQuery,
-    ResultOfQueryCollection, ResultOfWaitForCollection,
+    aggregate_collection, get_consistency_token, query, query_collection, query_counterparties,
+    wait_for_collection, AggregationGroup, GraphQLWarning, ParamsOfQuery, ParamsOfWaitForCollection,
+    ResultOfAggregateCollection,
+    ResultOfGetConsistencyToken, ResultOfQuery, ResultOfQueryCollection,
+    ResultOfWaitForCollection,
+};
+pub use schema::{
+    get_collection_schema, ParamsOfGetCollectionSchema, ResultOfGetCollectionSchema, SchemaField,
+    SchemaType,
 };
 pub(crate) use server_link::{EndpointStat, NetworkState, ServerLink, MAX_TIMEOUT};
+pub use subscription_mapping::{FieldPredicate, PredicateOperator, SubscriptionMapping};
 pub use subscriptions::{
-    subscribe, subscribe_collection, unsubscribe, ParamsOfSubscribeCollection,
-    ResultOfSubscribeCollection, ResultOfSubscription, SubscriptionResponseType,
+    subscribe, subscribe_collection, subscribe_collection_with_events, unsubscribe,
+    ParamsOfSubscribeCollection, ResultOfSubscribeCollection, ResultOfSubscription,
+    SubscriptionEvent, SubscriptionResponseType,
 };
 pub use ton_gql::{
-    AggregationFn, FieldAggregation, GraphQLQueryEvent, OrderBy, ParamsOfAggregateCollection,
+    AggregationFn, AggregationGroupBy, FieldAggregation, GraphQLQueryEvent, OrderBy, ParamsOfAggregateCollection,
     ParamsOfQueryCollection, ParamsOfQueryCounterparties, ParamsOfQueryOperation, PostRequest,
     SortDirection,
 };
 pub use transaction_tree::{
-    query_transaction_tree, MessageNode, ParamsOfQueryTransactionTree,
-    ResultOfQueryTransactionTree, TransactionNode,
+    query_transaction_tree, query_transaction_tree_with_callback, MessageNode,
+    ParamsOfQueryTransactionTree, ResultOfQueryTransactionTree, TransactionNode,
+    TransactionTreeNodeEvent, TransactionTreeResponseType,
 };
 pub use types::{
-    NetworkConfig, ACCOUNTS_COLLECTION, BLOCKS_COLLECTION, MESSAGES_COLLECTION,
-    TRANSACTIONS_COLLECTION,
+    NetworkConfig, NetworkQueriesProtocol, SubscriptionsTransport, TrustedMcBlockId,
+    ACCOUNTS_COLLECTION, BLOCKS_COLLECTION, MESSAGES_COLLECTION, TRANSACTIONS_COLLECTION,
 };
 
 use crate::client::ClientContext;
 use crate::error::ClientResult;
 
+pub(crate) mod accounts_sync;
+pub(crate) mod archive;
 pub(crate) mod batch;
+pub(crate) mod block_headers;
+pub(crate) mod body_decoding;
+pub(crate) mod blockchain;
 mod endpoint;
+pub(crate) mod endpoint_stats;
 mod errors;
 mod gql;
+mod jrpc;
+pub(crate) mod network_time;
 pub(crate) mod iterators;
+pub(crate) mod lite;
 pub(crate) mod queries;
+pub(crate) mod query_cache;
+pub(crate) mod query_dedup;
+pub(crate) mod quorum;
+pub(crate) mod schema;
 mod server_link;
+mod sse;
+pub(crate) mod subscription_mapping;
+pub(crate) mod subscription_replay;
 pub(crate) mod subscriptions;
 mod ton_gql;
 pub(crate) mod transaction_tree;
+pub(crate) mod transport;
 pub(crate) mod types;
 mod websocket_link;
 
@@ -0,0 +1,90 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Mutex;
+
+use crate::client::ClientEnv;
+
+/// Network time oracle. Message expiration is calculated with the network time, so the devices
+/// with the clock out of sync produce messages that are valid on the blockchain.
+///
+/// The offset of the network time from the local clock is measured with the endpoint info
+/// request and corrected by the server timestamps observed during the message processing.
+/// The correction is the median of the recent observations clamped to `MAX_OBSERVED_SKEW_MS`,
+/// so a single bogus timestamp can't shift the clock for the following processings.
+/// The clock is shared by the concurrent processings.
+pub(crate) struct NetworkClock {
+    /// Server time minus the local time measured by the endpoint, in ms
+    endpoint_offset: AtomicI64,
+    /// Median of the `observations`, in ms
+    observed_offset: AtomicI64,
+    /// Recent offsets implied by the observed server timestamps, in ms
+    observations: Mutex<VecDeque<i64>>,
+}
+
+/// Number of the recent server timestamps used to correct the clock
+const OBSERVATIONS_WINDOW: usize = 5;
+/// Maximal correction of the clock by the observed server timestamps, in ms
+const MAX_OBSERVED_SKEW_MS: i64 = 600_000;
+
+impl Default for NetworkClock {
+    fn default() -> Self {
+        Self {
+            endpoint_offset: AtomicI64::new(0),
+            observed_offset: AtomicI64::new(i64::MIN),
+            observations: Mutex::new(VecDeque::with_capacity(OBSERVATIONS_WINDOW + 1)),
+        }
+    }
+}
+
+impl NetworkClock {
+    /// Network time minus the local time, in ms
+    pub fn offset(&self) -> i64 {
+        std::cmp::max(
+            self.endpoint_offset.load(Ordering::Relaxed),
+            self.observed_offset.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Current network time, in ms
+    pub fn now_ms(&self, env: &ClientEnv) -> u64 {
+        std::cmp::max(env.now_ms() as i64 + self.offset(), 0) as u64
+    }
+
+    /// Applies the time delta measured by the endpoint. Deltas below the `threshold`
+    /// are ignored and the local clock is trusted.
+    pub fn set_endpoint_offset(&self, delta: i64, threshold: u32) {
+        let offset = if delta.abs() >= threshold as i64 { delta } else { 0 };
+        self.endpoint_offset.store(offset, Ordering::Relaxed);
+    }
+
+    /// Corrects the clock by the server timestamp which can't be later than the current
+    /// network time (e.g. `gen_utime` of the fetched block). The correction is kept for
+    /// the following processing attempts until the timestamp leaves the observation window.
+    pub fn observe_server_time(&self, server_time_ms: u64, local_now_ms: u64) {
+        let offset = (server_time_ms as i64 - local_now_ms as i64)
+            .max(-MAX_OBSERVED_SKEW_MS)
+            .min(MAX_OBSERVED_SKEW_MS);
+        let mut observations = self.observations.lock().unwrap();
+        observations.push_back(offset);
+        if observations.len() > OBSERVATIONS_WINDOW {
+            observations.pop_front();
+        }
+        let mut sorted = observations.iter().cloned().collect::<Vec<_>>();
+        sorted.sort_unstable();
+        self.observed_offset.store(sorted[(sorted.len() - 1) / 2], Ordering::Relaxed);
+    }
+}
@@ -15,7 +15,8 @@ use serde_json::Value;
 
 use crate::client::ClientContext;
 use crate::error::{AddNetworkUrl, ClientResult};
-use crate::net::{ParamsOfQueryCollection, ParamsOfQueryCounterparties, ServerLink};
+use crate::net::body_decoding::BodyDecoder;
+use crate::net::{Endpoint, ParamsOfQueryCollection, ParamsOfQueryCounterparties, ServerLink};
 
 use super::Error;
 
@@ -28,12 +29,47 @@ pub struct ParamsOfQuery {
     /// Variables used in query. Must be a map with named values that
     /// can be used in query.
     pub variables: Option<serde_json::Value>,
+    /// Return partial result instead of the error if the server responded with
+    /// both `errors` and `data`. Default is `false`.
+    ///
+    /// Server errors are returned in `warnings` in this case.
+    pub allow_partial_result: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone, Debug, PartialEq)]
+pub struct GraphQLWarning {
+    /// Error message
+    pub message: String,
+    /// Path to the response field the error relates to
+    pub path: Option<Value>,
+    /// GraphQL error code, e.g. `INTERNAL_SERVER_ERROR`
+    pub code: Option<String>,
+    /// Server specific error code
+    pub server_code: Option<i64>,
+    /// Exchange the error originated from, if reported by the server
+    pub exchange: Option<Value>,
+}
+
+impl From<Value> for GraphQLWarning {
+    fn from(data: Value) -> Self {
+        let optional = |value: &Value| if value.is_null() { None } else { Some(value.clone()) };
+        Self {
+            message: data["message"].as_str().unwrap_or_default().to_string(),
+            path: optional(&data["path"]),
+            code: data["code"].as_str().map(|code| code.to_string()),
+            server_code: data["server_code"].as_i64(),
+            exchange: optional(&data["exchange"]),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, ApiType, Default, Clone)]
 pub struct ResultOfQuery {
     /// Result provided by DAppServer.
     pub result: Value,
+    /// Server errors accompanying the partial result.
+    /// Present only if `allow_partial_result` is set and the result is partial.
+    pub warnings: Option<Vec<GraphQLWarning>>,
 }
 
 async fn deserialize_result<T>(
@@ -51,7 +87,9 @@ where
                 .await
         }
         Err(err) => {
-            Err(Error::queries_query_failed(err))
+            let mut error = Error::queries_query_failed(&err);
+            error.data = err.data;
+            Err(error)
                 .add_network_url(server_link)
                 .await?
         }
@@ -71,10 +109,17 @@ pub async fn query(
         is_batch: false,
         timeout: None,
     };
-    let result = server_link.query(&query, None).await;
-    Ok(ResultOfQuery {
-        result: deserialize_result(result, server_link).await?,
-    })
+    let result = server_link
+        .query_with_partial_result(&query, None, params.allow_partial_result.unwrap_or(false))
+        .await;
+    let result: Value = deserialize_result(result, server_link).await?;
+    let warnings = result["errors"].as_array().map(|errors| {
+        errors
+            .iter()
+            .map(|error| GraphQLWarning::from(Error::graphql_server_error_data(error)))
+            .collect()
+    });
+    Ok(ResultOfQuery { result, warnings })
 }
 
 //------------------------------------------------------------------------------- query_collection
@@ -96,9 +141,90 @@ pub async fn query_collection(
     params: ParamsOfQueryCollection,
 ) -> ClientResult<ResultOfQueryCollection> {
     let server_link = context.get_server_link()?;
-    let result = server_link.query_collection(params, None).await;
-    Ok(ResultOfQueryCollection {
-        result: deserialize_result(result, server_link).await?,
+    // The query is pinned to the endpoint which has caught up with `min_block`,
+    // other endpoints may still lag behind it
+    let endpoint = match &params.min_block {
+        Some(min_block) => {
+            let endpoint = server_link.get_query_endpoint().await?.as_ref().clone();
+            wait_for_min_block(&context, server_link, min_block, &endpoint).await?;
+            Some(endpoint)
+        }
+        None => None,
+    };
+    let mut params = params;
+    let decoder = BodyDecoder::resolve(
+        &context,
+        &params.collection,
+        params.decode_body.take(),
+        &mut params.result,
+    ).await?;
+    let result = server_link.query_collection(params, endpoint).await;
+    let mut result: Vec<Value> = deserialize_result(result, server_link).await?;
+    if let Some(decoder) = decoder {
+        for item in result.iter_mut() {
+            decoder.decode(item).await?;
+        }
+    }
+    Ok(ResultOfQueryCollection { result })
+}
+
+async fn wait_for_min_block(
+    context: &ClientContext,
+    server_link: &ServerLink,
+    block_id: &str,
+    endpoint: &Endpoint,
+) -> ClientResult<()> {
+    let filter = json!({ "id": { "eq": block_id } });
+    server_link
+        .wait_for_collection(
+            ParamsOfWaitForCollection {
+                collection: "blocks".to_string(),
+                filter: Some(filter.clone()),
+                result: "id".to_string(),
+                timeout: None,
+            },
+            Some(endpoint.clone()),
+        )
+        .await
+        .map_err(|err| {
+            Error::queries_wait_for_failed(err, Some(filter), (context.env.now_ms() / 1000) as u32)
+        })
+        .add_network_url(server_link)
+        .await?;
+
+    Ok(())
+}
+
+//------------------------------------------------------------------------ get_consistency_token
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ResultOfGetConsistencyToken {
+    /// Id of the shard block recorded when the last message was sent from this context.
+    /// Pass it as `min_block` to `net.query_collection` to read your own writes.
+    /// `None` if no messages were sent yet.
+    pub min_block: Option<String>,
+}
+
+/// Returns the consistency token of the context
+///
+/// The token is updated by `processing.send_message` (and functions using it) each time
+/// a message is successfully sent, so the subsequent queries can wait until the endpoint
+/// has caught up with the moment of sending. The token is the same as `shard_block_id`
+/// returned by `processing.send_message`.
+///
+/// The token is the last shard block of the destination account generated *before* the
+/// message was sent, so it guarantees only that the endpoint has caught up with the send
+/// point, not that the message is already processed. Use `processing.wait_for_transaction`
+/// to wait for the message processing.
+///
+/// Each context (including derived ones) has its own token, so the messages sent from
+/// the other contexts don't affect it.
+#[api_function]
+pub async fn get_consistency_token(
+    context: std::sync::Arc<ClientContext>,
+) -> ClientResult<ResultOfGetConsistencyToken> {
+    Ok(ResultOfGetConsistencyToken {
+        min_block: context.last_sent_block.read().await.clone(),
     })
 }
 
@@ -150,31 +276,133 @@ pub async fn wait_for_collection(
 //--------------------------------------------------------------------------- aggregate_collection
 
 use crate::net::ton_gql::GraphQLQuery;
-use crate::net::ParamsOfAggregateCollection;
+use crate::net::{ParamsOfAggregateCollection, ParamsOfQueryOperation};
 use serde::de::DeserializeOwned;
 
-#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+#[derive(Serialize, Deserialize, ApiType, Default, Clone, Debug)]
+pub struct AggregationGroup {
+    /// Value of the `group_by` field
+    pub value: Value,
+    /// Values for requested fields aggregated for the group records.
+    /// Same format as the `values` of the result.
+    pub values: Value,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone, Debug)]
 pub struct ResultOfAggregateCollection {
     /// Values for requested fields.
     ///
     /// Returns an array of strings. Each string refers to the corresponding `fields` item.
     /// Numeric value is returned as a decimal string representations.
     pub values: Value,
+    /// Aggregated values of the groups satisfying the `having` predicates,
+    /// in the order of the `group_by` values. Returned only if `group_by` is specified,
+    /// `values` contains the aggregation of all the filtered records in this case.
+    pub groups: Option<Vec<AggregationGroup>>,
+}
+
+/// Adds the `eq` condition for the group value to the filter
+fn group_filter(filter: &Option<Value>, path: &str, value: &Value) -> Value {
+    let mut filter = match filter {
+        Some(filter) if filter.is_object() => filter.clone(),
+        _ => json!({}),
+    };
+    let mut condition = &mut filter;
+    for name in path.split('.') {
+        if !condition[name].is_object() {
+            condition[name] = json!({});
+        }
+        condition = &mut condition[name];
+    }
+    condition["eq"] = value.clone();
+    filter
+}
+
+/// Aggregated numeric values are returned as decimal strings, so they are converted
+/// to numbers to be compared by the `having` predicates
+fn having_document(values: &Value) -> Value {
+    match values {
+        Value::Array(values) => Value::Array(
+            values
+                .iter()
+                .map(|value| {
+                    value
+                        .as_str()
+                        .and_then(|value| serde_json::from_str::<Value>(value).ok())
+                        .filter(|value| value.is_number())
+                        .unwrap_or_else(|| value.clone())
+                })
+                .collect(),
+        ),
+        values => values.clone(),
+    }
 }
 
 /// Aggregates collection data.
 ///
 /// Aggregates values from the specified `fields` for records
 /// that satisfies the `filter` conditions,
+///
+/// If `group_by` is specified, the values are also aggregated for each group
+/// within the same request, e.g. sums per shard. Groups not satisfying the `having`
+/// predicates are excluded from the result.
 #[api_function]
 pub async fn aggregate_collection(
     context: std::sync::Arc<ClientContext>,
     params: ParamsOfAggregateCollection,
 ) -> ClientResult<ResultOfAggregateCollection> {
     let server_link = context.get_server_link()?;
-    let result = server_link.aggregate_collection(params, None).await;
+    let group_by = match params.group_by.clone() {
+        Some(group_by) => group_by,
+        None => {
+            if params.having.is_some() {
+                return Err(Error::invalid_query(
+                    &params.collection,
+                    "having",
+                    "",
+                    None,
+                    "`group_by` is required",
+                ));
+            }
+            let result = server_link.aggregate_collection(params, None).await;
+            return Ok(ResultOfAggregateCollection {
+                values: deserialize_result(result, server_link).await?,
+                groups: None,
+            });
+        }
+    };
+
+    let operation = |filter: Option<Value>| {
+        ParamsOfQueryOperation::AggregateCollection(ParamsOfAggregateCollection {
+            collection: params.collection.clone(),
+            filter,
+            fields: params.fields.clone(),
+            group_by: None,
+            having: None,
+        })
+    };
+    let mut operations = vec![operation(params.filter.clone())];
+    for value in &group_by.values {
+        operations.push(operation(Some(group_filter(&params.filter, &group_by.field, value))));
+    }
+    let result = server_link.batch_query(&operations, None).await;
+    let mut results: Vec<Value> = deserialize_result(result.map(Value::Array), server_link).await?;
+
+    let values = results.remove(0);
+    let having = params.having.unwrap_or_default();
+    let groups = group_by
+        .values
+        .into_iter()
+        .zip(results.into_iter())
+        .filter(|(_, values)| {
+            let document = having_document(values);
+            having.iter().all(|predicate| predicate.is_satisfied(&document))
+        })
+        .map(|(value, values)| AggregationGroup { value, values })
+        .collect();
     Ok(ResultOfAggregateCollection {
-        values: deserialize_result(result, server_link).await?,
+        values,
+        groups: Some(groups),
     })
 }
 
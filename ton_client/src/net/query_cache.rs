@@ -0,0 +1,127 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+// Local cache of the collection queries. Blocks, transactions and messages are immutable
+// once they are finalized, so the repeated queries of the same finalized items are served
+// locally. Queries by other filters (e.g. time or `lt` ranges) are never cached, because
+// their results change when the new items arrive.
+
+use std::sync::{Mutex, PoisonError};
+
+use lru::LruCache;
+use serde_json::Value;
+
+use crate::net::{
+    ParamsOfQueryCollection, BLOCKS_COLLECTION, MESSAGES_COLLECTION, TRANSACTIONS_COLLECTION,
+};
+
+pub const DEFAULT_QUERY_CACHE_TTL: u32 = 60000;
+
+/// `status` of the finalized items of the collection
+fn finalized_status(collection: &str) -> Option<u64> {
+    match collection {
+        BLOCKS_COLLECTION => Some(2),
+        TRANSACTIONS_COLLECTION => Some(3),
+        MESSAGES_COLLECTION => Some(5),
+        _ => None,
+    }
+}
+
+/// Checks that the filter selects the items by the exact ids only
+fn is_filter_by_ids(filter: Option<&Value>) -> bool {
+    let filter = match filter.and_then(|filter| filter.as_object()) {
+        Some(filter) if filter.len() == 1 => filter,
+        _ => return false,
+    };
+    match filter.get("id").and_then(|id| id.as_object()) {
+        Some(id) if id.len() == 1 => {
+            id.get("eq").map(|eq| eq.is_string()).unwrap_or(false)
+                || id.get("in").map(|ids| ids.is_array()).unwrap_or(false)
+        }
+        _ => false,
+    }
+}
+
+struct CachedQuery {
+    result: Value,
+    expiration_time: u64,
+}
+
+pub(crate) struct QueryCache {
+    queries: Option<Mutex<LruCache<String, CachedQuery>>>,
+    ttl: u64,
+}
+
+impl QueryCache {
+    pub fn new(max_size: u32, ttl: u32) -> Self {
+        Self {
+            queries: if max_size > 0 && ttl > 0 {
+                Some(Mutex::new(LruCache::new(max_size as usize)))
+            } else {
+                None
+            },
+            ttl: ttl as u64,
+        }
+    }
+
+    /// Only the queries of the immutable collections by the exact ids are cached
+    pub fn is_cacheable(&self, params: &ParamsOfQueryCollection) -> bool {
+        self.queries.is_some()
+            && finalized_status(&params.collection).is_some()
+            && is_filter_by_ids(params.filter.as_ref())
+    }
+
+    pub fn get(&self, key: &str, now: u64) -> Option<Value> {
+        let mut queries = self.queries.as_ref()?.lock().unwrap_or_else(PoisonError::into_inner);
+        let expired = queries.peek(key)?.expiration_time <= now;
+        if expired {
+            queries.pop(key);
+            return None;
+        }
+        queries.get(key).map(|query| query.result.clone())
+    }
+
+    /// Stores the query result if all the items are finalized, so they can't change.
+    /// The items must include `status` field to be cached. Empty results are not cached
+    /// because the requested items can be produced later
+    pub fn put(&self, key: String, collection: &str, result: &Value, now: u64) {
+        if let Some(queries) = &self.queries {
+            let finalized = match (finalized_status(collection), result.as_array()) {
+                (Some(status), Some(items)) => {
+                    !items.is_empty()
+                        && items.iter().all(|item| item["status"].as_u64() == Some(status))
+                }
+                _ => false,
+            };
+            if !finalized {
+                return;
+            }
+            queries.lock().unwrap_or_else(PoisonError::into_inner).put(
+                key,
+                CachedQuery {
+                    result: result.clone(),
+                    expiration_time: now + self.ttl,
+                },
+            );
+        }
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.queries
+            .as_ref()
+            .map(|queries| queries.lock().unwrap().len())
+            .unwrap_or_default()
+    }
+}
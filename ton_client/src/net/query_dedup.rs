@@ -0,0 +1,112 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+// Query deduplication: identical collection queries issued concurrently are coalesced
+// into a single in-flight request, all the callers receive the result of this request.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use futures::Future;
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+use crate::error::ClientResult;
+
+type Waiters = Vec<oneshot::Sender<ClientResult<Value>>>;
+
+pub(crate) struct QueryDeduplicator {
+    in_flight: Mutex<HashMap<String, Waiters>>,
+}
+
+/// Removes the in-flight entry if the leading request is dropped before completion.
+/// Waiters get the closed channel and run the query by themselves.
+struct LeaderGuard<'a> {
+    deduplicator: &'a QueryDeduplicator,
+    key: Option<String>,
+}
+
+impl<'a> LeaderGuard<'a> {
+    fn complete(mut self, result: &ClientResult<Value>) {
+        if let Some(key) = self.key.take() {
+            for waiter in self.deduplicator.take_waiters(&key) {
+                let _ = waiter.send(result.clone());
+            }
+        }
+    }
+}
+
+impl<'a> Drop for LeaderGuard<'a> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.deduplicator.take_waiters(&key);
+        }
+    }
+}
+
+impl QueryDeduplicator {
+    pub fn new() -> Self {
+        Self {
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn take_waiters(&self, key: &str) -> Waiters {
+        self.in_flight.lock().unwrap().remove(key).unwrap_or_default()
+    }
+
+    /// Runs the `query` unless the query with the same `key` is already in flight.
+    /// Otherwise waits for the in-flight query and returns its result.
+    pub async fn run<F>(&self, key: String, query: impl FnOnce() -> F) -> ClientResult<Value>
+    where
+        F: Future<Output = ClientResult<Value>>,
+    {
+        let receiver = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get_mut(&key) {
+                Some(waiters) => {
+                    let (sender, receiver) = oneshot::channel();
+                    waiters.push(sender);
+                    Some(receiver)
+                }
+                None => {
+                    in_flight.insert(key.clone(), Vec::new());
+                    None
+                }
+            }
+        };
+
+        match receiver {
+            Some(receiver) => match receiver.await {
+                Ok(result) => result,
+                // leading request was dropped, so the result is never sent
+                Err(_) => query().await,
+            },
+            None => {
+                let guard = LeaderGuard {
+                    deduplicator: self,
+                    key: Some(key),
+                };
+                let result = query().await;
+                guard.complete(&result);
+                result
+            }
+        }
+    }
+
+    #[cfg(test)]
+    pub fn in_flight_count(&self) -> usize {
+        self.in_flight.lock().unwrap().len()
+    }
+}
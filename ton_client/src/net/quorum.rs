@@ -0,0 +1,102 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+// Quorum reads: critical queries are fetched from several distinct endpoints
+// and the results are compared, so a single compromised endpoint can't substitute
+// the data unnoticed.
+
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use crate::net::{
+    query_collection, Endpoint, Error, ParamsOfQueryCollection, ResultOfQueryCollection,
+};
+use serde_json::Value;
+use sha2::Digest;
+use std::sync::Arc;
+
+fn result_hash(result: &Value) -> String {
+    hex::encode(sha2::Sha256::digest(result.to_string().as_bytes()))
+}
+
+async fn query_endpoint(
+    context: &Arc<ClientContext>,
+    address: &str,
+    params: ParamsOfQueryCollection,
+) -> ClientResult<Value> {
+    let server_link = context.get_server_link()?;
+    let endpoint = Endpoint::resolve(&context.env, server_link.config(), address).await?;
+    server_link.query_collection(params, Some(endpoint)).await
+}
+
+/// Queries the collection with the quorum read if `quorum_endpoints_count` is configured,
+/// and with the regular query otherwise.
+///
+/// The query is sent to `quorum_endpoints_count` distinct endpoints and the results are
+/// compared by hash. Fails with `ConflictingEndpointData` if the results disagree.
+pub(crate) async fn quorum_query_collection(
+    context: &Arc<ClientContext>,
+    params: ParamsOfQueryCollection,
+) -> ClientResult<ResultOfQueryCollection> {
+    quorum_query_collection_with_min_count(context, params, 1).await
+}
+
+/// Same as `quorum_query_collection`, but the query is sent to at least `min_count`
+/// distinct endpoints regardless of `quorum_endpoints_count`.
+pub(crate) async fn quorum_query_collection_with_min_count(
+    context: &Arc<ClientContext>,
+    params: ParamsOfQueryCollection,
+    min_count: u32,
+) -> ClientResult<ResultOfQueryCollection> {
+    let server_link = context.get_server_link()?;
+    let count = std::cmp::max(
+        server_link.config().quorum_endpoints_count.unwrap_or(1),
+        min_count,
+    ) as usize;
+    if count <= 1 {
+        return query_collection(context.clone(), params).await;
+    }
+
+    let mut addresses = server_link.get_all_endpoint_addresses().await?;
+    addresses.dedup();
+    if addresses.len() < count {
+        return Err(crate::client::Error::invalid_config(format!(
+            "quorum read requires {} endpoints but only {} endpoints are specified",
+            count,
+            addresses.len()
+        )));
+    }
+    addresses.truncate(count);
+
+    let results = futures::future::join_all(
+        addresses
+            .iter()
+            .map(|address| query_endpoint(context, address, params.clone())),
+    )
+    .await;
+
+    let mut values = results.into_iter().collect::<ClientResult<Vec<_>>>()?;
+    let hashes: Vec<String> = values.iter().map(result_hash).collect();
+    if hashes.iter().any(|hash| hash != &hashes[0]) {
+        return Err(Error::conflicting_endpoint_data(
+            &params.collection,
+            &addresses,
+            &hashes,
+        ));
+    }
+
+    Ok(ResultOfQueryCollection {
+        result: serde_json::from_value(values.remove(0))
+            .map_err(|err| Error::queries_query_failed(err))?,
+    })
+}
@@ -0,0 +1,570 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use crate::client::ClientContext;
+use crate::error::{ClientError, ClientResult};
+use crate::net::{Error, ParamsOfQueryOperation};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+pub(crate) const INTROSPECTION_QUERY: &str = r#"query {
+    __schema {
+        queryType { name }
+        types {
+            kind
+            name
+            fields { name args { name type { ...TypeRef } } type { ...TypeRef } }
+            inputFields { name type { ...TypeRef } }
+        }
+    }
+}
+fragment TypeRef on __Type {
+    kind name ofType { kind name ofType { kind name ofType { kind name } } }
+}"#;
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone, PartialEq)]
+pub struct SchemaField {
+    /// Field name.
+    pub name: String,
+    /// Name of the field type. List and non-null wrappers are omitted.
+    pub type_name: String,
+    /// Kind of the field type: `SCALAR`, `ENUM`, `OBJECT`, `INPUT_OBJECT`, etc.
+    pub kind: String,
+    /// The field value is a list.
+    pub is_list: bool,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone, PartialEq)]
+pub struct SchemaType {
+    /// Type name.
+    pub name: String,
+    /// Type kind: `OBJECT` for the collection items and nested objects,
+    /// `INPUT_OBJECT` for the filters.
+    pub kind: String,
+    /// Type fields. Input fields for `INPUT_OBJECT` types.
+    pub fields: Vec<SchemaField>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone)]
+pub struct ParamsOfGetCollectionSchema {
+    /// Collection name (accounts, blocks, transactions, messages, block_signatures)
+    pub collection: String,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone, PartialEq)]
+pub struct ResultOfGetCollectionSchema {
+    /// Name of the collection item type.
+    pub type_name: String,
+    /// Name of the collection filter type.
+    pub filter_type_name: String,
+    /// Item and filter types followed by all the types reachable from their fields.
+    pub types: Vec<SchemaType>,
+}
+
+struct Collection {
+    type_name: String,
+    filter_type_name: String,
+}
+
+/// Collections and types of the endpoint GraphQL schema
+pub(crate) struct GraphQLSchema {
+    collections: HashMap<String, Collection>,
+    types: HashMap<String, SchemaType>,
+}
+
+/// Unwraps `NON_NULL` and `LIST` wrappers of the type reference.
+/// Returns type name, kind and the list flag
+fn read_type_ref(mut type_ref: &Value) -> (String, String, bool) {
+    let mut is_list = false;
+    loop {
+        match type_ref["kind"].as_str() {
+            Some("NON_NULL") => type_ref = &type_ref["ofType"],
+            Some("LIST") => {
+                is_list = true;
+                type_ref = &type_ref["ofType"];
+            }
+            kind => {
+                return (
+                    type_ref["name"].as_str().unwrap_or_default().to_string(),
+                    kind.unwrap_or_default().to_string(),
+                    is_list,
+                )
+            }
+        }
+    }
+}
+
+fn read_fields(fields: &Value) -> Vec<SchemaField> {
+    fields
+        .as_array()
+        .map(|fields| {
+            fields
+                .iter()
+                .map(|field| {
+                    let (type_name, kind, is_list) = read_type_ref(&field["type"]);
+                    SchemaField {
+                        name: field["name"].as_str().unwrap_or_default().to_string(),
+                        type_name,
+                        kind,
+                        is_list,
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    Name(&'a str),
+    Punct(char),
+    Spread,
+    End,
+}
+
+/// Splits `result` string into tokens keeping their byte positions
+struct Lexer<'a> {
+    text: &'a str,
+    pos: usize,
+    peeked: Option<(usize, Token<'a>)>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            pos: 0,
+            peeked: None,
+        }
+    }
+
+    fn next(&mut self) -> (usize, Token<'a>) {
+        if let Some(token) = self.peeked.take() {
+            return token;
+        }
+        let bytes = self.text.as_bytes();
+        while self.pos < bytes.len()
+            && (bytes[self.pos].is_ascii_whitespace() || bytes[self.pos] == b',')
+        {
+            self.pos += 1;
+        }
+        let start = self.pos;
+        if start >= bytes.len() {
+            return (start, Token::End);
+        }
+        if bytes[start] == b'_' || bytes[start].is_ascii_alphabetic() {
+            while self.pos < bytes.len()
+                && (bytes[self.pos] == b'_' || bytes[self.pos].is_ascii_alphanumeric())
+            {
+                self.pos += 1;
+            }
+            return (start, Token::Name(&self.text[start..self.pos]));
+        }
+        if self.text[start..].starts_with("...") {
+            self.pos += 3;
+            return (start, Token::Spread);
+        }
+        let ch = self.text[start..].chars().next().unwrap_or_default();
+        self.pos += ch.len_utf8();
+        (start, Token::Punct(ch))
+    }
+
+    fn peek(&mut self) -> &Token<'a> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.next());
+        }
+        &self.peeked.as_ref().unwrap().1
+    }
+
+    /// Skips field arguments after the opening parenthesis up to the matching closing one
+    fn skip_arguments(&mut self) {
+        let mut depth = 1;
+        let mut in_string = false;
+        let mut escaped = false;
+        for (offset, ch) in self.text[self.pos..].char_indices() {
+            if in_string {
+                match ch {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match ch {
+                '"' => in_string = true,
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.pos += offset + 1;
+                        return;
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.pos = self.text.len();
+    }
+}
+
+fn join_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn is_leaf_kind(kind: &str) -> bool {
+    kind == "SCALAR" || kind == "ENUM"
+}
+
+impl GraphQLSchema {
+    /// Reads the schema from the `__schema` field of the introspection query result
+    pub fn from_introspection(schema: &Value) -> ClientResult<Self> {
+        let schema_types = schema["types"]
+            .as_array()
+            .ok_or_else(|| Error::invalid_server_response("GraphQL schema has no types"))?;
+        let query_type = schema["queryType"]["name"].as_str().unwrap_or("Query");
+
+        let mut collections = HashMap::new();
+        let mut types = HashMap::new();
+        for schema_type in schema_types {
+            let name = schema_type["name"].as_str().unwrap_or_default();
+            let kind = schema_type["kind"].as_str().unwrap_or_default();
+            if name.starts_with("__") {
+                continue;
+            }
+            if name == query_type {
+                // Collections are the list fields of the query type accepting `filter`
+                for field in schema_type["fields"].as_array().into_iter().flatten() {
+                    let (type_name, kind, is_list) = read_type_ref(&field["type"]);
+                    let filter = field["args"]
+                        .as_array()
+                        .and_then(|args| args.iter().find(|arg| arg["name"] == "filter"));
+                    if let (Some(filter), "OBJECT", true) = (filter, kind.as_str(), is_list) {
+                        collections.insert(
+                            field["name"].as_str().unwrap_or_default().to_string(),
+                            Collection {
+                                type_name,
+                                filter_type_name: read_type_ref(&filter["type"]).0,
+                            },
+                        );
+                    }
+                }
+                continue;
+            }
+            let fields = match kind {
+                "OBJECT" => read_fields(&schema_type["fields"]),
+                "INPUT_OBJECT" => read_fields(&schema_type["inputFields"]),
+                _ => continue,
+            };
+            types.insert(
+                name.to_string(),
+                SchemaType {
+                    name: name.to_string(),
+                    kind: kind.to_string(),
+                    fields,
+                },
+            );
+        }
+
+        Ok(Self { collections, types })
+    }
+
+    fn collection(&self, name: &str) -> ClientResult<&Collection> {
+        self.collections.get(name).ok_or_else(|| {
+            Error::invalid_query(
+                name,
+                "collection",
+                name,
+                None,
+                "collection is not found in the endpoint schema",
+            )
+        })
+    }
+
+    fn field(&self, type_name: &str, name: &str) -> Option<&SchemaField> {
+        self.types
+            .get(type_name)
+            .and_then(|schema_type| schema_type.fields.iter().find(|field| field.name == name))
+    }
+
+    /// Checks if the collection item type has the field
+    pub fn has_field(&self, collection: &str, name: &str) -> bool {
+        self.collections
+            .get(collection)
+            .and_then(|info| self.field(&info.type_name, name))
+            .is_some()
+    }
+
+    pub fn get_collection_schema(&self, collection: &str) -> ClientResult<ResultOfGetCollectionSchema> {
+        let info = self.collection(collection)?;
+        let mut types = Vec::new();
+        let mut visited = HashSet::new();
+        let mut pending = VecDeque::new();
+        pending.push_back(info.type_name.clone());
+        pending.push_back(info.filter_type_name.clone());
+        while let Some(name) = pending.pop_front() {
+            if !visited.insert(name.clone()) {
+                continue;
+            }
+            if let Some(schema_type) = self.types.get(&name) {
+                for field in &schema_type.fields {
+                    if !is_leaf_kind(&field.kind) {
+                        pending.push_back(field.type_name.clone());
+                    }
+                }
+                types.push(schema_type.clone());
+            }
+        }
+
+        Ok(ResultOfGetCollectionSchema {
+            type_name: info.type_name.clone(),
+            filter_type_name: info.filter_type_name.clone(),
+            types,
+        })
+    }
+
+    /// Checks that the filter uses only the fields and operators defined in the schema
+    pub fn validate_filter(&self, collection: &str, filter: &Value) -> ClientResult<()> {
+        let info = self.collection(collection)?;
+        self.check_filter(collection, &info.filter_type_name, filter, "")
+    }
+
+    fn check_filter(
+        &self,
+        collection: &str,
+        type_name: &str,
+        filter: &Value,
+        path: &str,
+    ) -> ClientResult<()> {
+        let filter = match filter.as_object() {
+            Some(filter) if self.types.contains_key(type_name) => filter,
+            _ => return Ok(()),
+        };
+        for (name, value) in filter {
+            let field_path = join_path(path, name);
+            let field = self.field(type_name, name).ok_or_else(|| {
+                Error::invalid_query(
+                    collection,
+                    "filter",
+                    &field_path,
+                    None,
+                    &format!("field `{}` is not defined in type `{}`", name, type_name),
+                )
+            })?;
+            if field.kind == "INPUT_OBJECT" {
+                if let Value::Array(items) = value {
+                    for item in items {
+                        self.check_filter(collection, &field.type_name, item, &field_path)?;
+                    }
+                } else {
+                    self.check_filter(collection, &field.type_name, value, &field_path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the dot separated path (used in sorting and aggregation) refers
+    /// to the existing field
+    pub fn validate_field_path(&self, collection: &str, param: &str, path: &str) -> ClientResult<()> {
+        let mut type_name = self.collection(collection)?.type_name.clone();
+        let mut current_path = String::new();
+        for name in path.split('.').filter(|name| !name.is_empty()) {
+            current_path = join_path(&current_path, name);
+            if !self.types.contains_key(&type_name) {
+                return Ok(());
+            }
+            let field = self.field(&type_name, name).ok_or_else(|| {
+                Error::invalid_query(
+                    collection,
+                    param,
+                    &current_path,
+                    None,
+                    &format!("field `{}` is not defined in type `{}`", name, type_name),
+                )
+            })?;
+            type_name = field.type_name.clone();
+        }
+        Ok(())
+    }
+
+    /// Checks that the `result` string selects only the fields defined in the schema.
+    /// Error contains the position of the invalid field in the `result`
+    pub fn validate_result(&self, collection: &str, result: &str) -> ClientResult<()> {
+        let info = self.collection(collection)?;
+        let mut lexer = Lexer::new(result);
+        self.check_selection(collection, &mut lexer, &info.type_name, "", false)
+    }
+
+    fn check_selection(
+        &self,
+        collection: &str,
+        lexer: &mut Lexer,
+        type_name: &str,
+        path: &str,
+        nested: bool,
+    ) -> ClientResult<()> {
+        let known_type = self.types.contains_key(type_name);
+        let invalid = |path: &str, position: usize, message: String| -> ClientError {
+            Error::invalid_query(collection, "result", path, Some(position), &message)
+        };
+        loop {
+            let (position, token) = lexer.next();
+            match token {
+                Token::End if !nested => return Ok(()),
+                Token::End => {
+                    return Err(invalid(path, position, "`}` expected".to_string()));
+                }
+                Token::Punct('}') if nested => return Ok(()),
+                Token::Spread => {
+                    // Inline fragment `... on Type { fields }`
+                    let fragment_type = match (lexer.next(), lexer.next(), lexer.next()) {
+                        ((_, Token::Name("on")), (_, Token::Name(name)), (_, Token::Punct('{'))) => {
+                            name
+                        }
+                        _ => {
+                            return Err(invalid(
+                                path,
+                                position,
+                                "inline fragment `... on Type { }` expected".to_string(),
+                            ))
+                        }
+                    };
+                    self.check_selection(collection, lexer, fragment_type, path, true)?;
+                }
+                Token::Name(name) => {
+                    let (position, name) = if lexer.peek() == &Token::Punct(':') {
+                        lexer.next();
+                        match lexer.next() {
+                            (position, Token::Name(name)) => (position, name),
+                            (position, _) => {
+                                return Err(invalid(
+                                    path,
+                                    position,
+                                    format!("field name expected after alias `{}`", name),
+                                ))
+                            }
+                        }
+                    } else {
+                        (position, name)
+                    };
+                    let field_path = join_path(path, name);
+                    let field = if known_type && name != "__typename" {
+                        Some(self.field(type_name, name).ok_or_else(|| {
+                            invalid(
+                                &field_path,
+                                position,
+                                format!("field `{}` is not defined in type `{}`", name, type_name),
+                            )
+                        })?)
+                    } else {
+                        None
+                    };
+                    if lexer.peek() == &Token::Punct('(') {
+                        lexer.next();
+                        lexer.skip_arguments();
+                    }
+                    let has_selection = lexer.peek() == &Token::Punct('{');
+                    if has_selection {
+                        lexer.next();
+                    }
+                    match field {
+                        Some(field) if is_leaf_kind(&field.kind) && has_selection => {
+                            return Err(invalid(
+                                &field_path,
+                                position,
+                                format!("field `{}` of type `{}` has no subfields", name, field.type_name),
+                            ));
+                        }
+                        Some(field) if !is_leaf_kind(&field.kind) && !has_selection => {
+                            return Err(invalid(
+                                &field_path,
+                                position,
+                                format!(
+                                    "field `{}` of type `{}` must have a selection of subfields",
+                                    name, field.type_name,
+                                ),
+                            ));
+                        }
+                        Some(field) if has_selection => {
+                            self.check_selection(collection, lexer, &field.type_name, &field_path, true)?;
+                        }
+                        None if has_selection => {
+                            self.check_selection(collection, lexer, "", &field_path, true)?;
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {
+                    return Err(invalid(path, position, "field name expected".to_string()));
+                }
+            }
+        }
+    }
+
+    pub fn validate_operation(&self, operation: &ParamsOfQueryOperation) -> ClientResult<()> {
+        match operation {
+            ParamsOfQueryOperation::QueryCollection(params) => {
+                if let Some(filter) = &params.filter {
+                    self.validate_filter(&params.collection, filter)?;
+                }
+                for order in params.order.iter().flatten() {
+                    self.validate_field_path(&params.collection, "order", &order.path)?;
+                }
+                self.validate_result(&params.collection, &params.result)
+            }
+            ParamsOfQueryOperation::WaitForCollection(params) => {
+                if let Some(filter) = &params.filter {
+                    self.validate_filter(&params.collection, filter)?;
+                }
+                self.validate_result(&params.collection, &params.result)
+            }
+            ParamsOfQueryOperation::AggregateCollection(params) => {
+                if let Some(filter) = &params.filter {
+                    self.validate_filter(&params.collection, filter)?;
+                }
+                for field in params.fields.iter().flatten() {
+                    self.validate_field_path(&params.collection, "fields", &field.field)?;
+                }
+                Ok(())
+            }
+            ParamsOfQueryOperation::QueryCounterparties(_) => Ok(()),
+        }
+    }
+}
+
+/// Returns the schema of the collection.
+///
+/// The schema is introspected from the endpoint GraphQL schema: the result contains
+/// the collection item type, the filter type and all the types reachable from their
+/// fields. Endpoint schema is requested once and cached by the client.
+///
+/// The same schema is used to validate collection queries and subscriptions when
+/// `validate_queries` is enabled in the network config.
+#[api_function]
+pub async fn get_collection_schema(
+    context: Arc<ClientContext>,
+    params: ParamsOfGetCollectionSchema,
+) -> ClientResult<ResultOfGetCollectionSchema> {
+    context
+        .get_server_link()?
+        .get_schema()
+        .await?
+        .get_collection_schema(&params.collection)
+}
@@ -12,17 +12,27 @@
 */
 
 use crate::client::{ClientEnv, FetchMethod};
+use crate::client::trace::{current_trace_id, TRACE_ID_HEADER};
 use crate::error::{AddNetworkUrl, ClientError, ClientResult};
+use crate::net::archive::is_archive_operation;
 use crate::net::endpoint::Endpoint;
+use crate::net::endpoint_stats::{EndpointStats, EndpointStatsCollector, StatsStorage};
+use crate::net::jrpc::{self, JrpcRequest};
+use crate::net::network_time::NetworkClock;
+use crate::net::query_cache::{QueryCache, DEFAULT_QUERY_CACHE_TTL};
+use crate::net::query_dedup::QueryDeduplicator;
+use crate::net::schema::{GraphQLSchema, INTROSPECTION_QUERY};
 use crate::net::ton_gql::GraphQLQuery;
 use crate::net::websocket_link::WebsocketLink;
 use crate::net::{
-    Error, GraphQLQueryEvent, NetworkConfig, ParamsOfAggregateCollection, ParamsOfQueryCollection,
-    ParamsOfQueryCounterparties, ParamsOfQueryOperation, ParamsOfWaitForCollection, PostRequest,
+    Error, GraphQLQueryEvent, NetworkConfig, NetworkQueriesProtocol, ParamsOfAggregateCollection,
+    ParamsOfQueryCollection, ParamsOfQueryCounterparties, ParamsOfQueryOperation,
+    ParamsOfWaitForCollection, PostRequest,
 };
 use futures::{Future, Stream, StreamExt};
 use rand::seq::SliceRandom;
 use serde_json::Value;
+use sha2::Digest;
 use std::cmp::{max, min};
 use std::collections::{HashMap, HashSet};
 use std::pin::Pin;
@@ -86,6 +96,8 @@ pub(crate) struct NetworkState {
     resume_timeout: AtomicU32,
     query_endpoint: RwLock<Option<Arc<Endpoint>>>,
     time_checked: AtomicBool,
+    clock: NetworkClock,
+    stats: EndpointStatsCollector,
 }
 
 async fn query_by_url(client_env: &ClientEnv, address: &str, query: &str, timeout: u32) -> ClientResult<Value> {
@@ -107,6 +119,7 @@ impl NetworkState {
         client_env: Arc<ClientEnv>,
         config: NetworkConfig,
         endpoint_addresses: Vec<String>,
+        stats: EndpointStatsCollector,
     ) -> Self {
         let (sender, receiver) = watch::channel(false);
         let regulation = SuspendRegulation {
@@ -124,6 +137,8 @@ impl NetworkState {
             resume_timeout: AtomicU32::new(0),
             query_endpoint: RwLock::new(None),
             time_checked: AtomicBool::new(false),
+            clock: NetworkClock::default(),
+            stats,
         }
     }
 
@@ -186,21 +201,17 @@ impl NetworkState {
     pub async fn get_addresses_for_sending(&self) -> Vec<String> {
         let mut addresses = self.endpoint_addresses.read().await.clone();
         addresses.shuffle(&mut rand::thread_rng());
-        let bad_delivery = self.bad_delivery_addresses.read().await.clone();
+        self.order_by_delivery_health(&mut addresses).await;
+        addresses
+    }
+
+    /// Moves the addresses with recent delivery failures to the end keeping the order
+    /// of the others
+    pub async fn order_by_delivery_health(&self, addresses: &mut Vec<String>) {
+        let bad_delivery = self.bad_delivery_addresses.read().await;
         if !bad_delivery.is_empty() {
-            let mut i = 0;
-            let mut processed = 0;
-            while processed < addresses.len() {
-                if bad_delivery.contains(&addresses[i]) {
-                    let address = addresses.remove(i);
-                    addresses.push(address);
-                } else {
-                    i += 1;
-                }
-                processed += 1;
-            }
+            addresses.sort_by_key(|address| bad_delivery.contains(address));
         }
-        addresses
     }
 
     pub async fn update_stat(&self, addresses: &Vec<String>, stat: EndpointStat) {
@@ -236,30 +247,15 @@ impl NetworkState {
         self.query_endpoint.read().await.clone()
     }
 
-    async fn check_time_delta(
-        &self,
-        endpoint: &Endpoint,
-        out_of_sync_threshold: u32,
-    ) -> ClientResult<()> {
-        let server_time_delta = endpoint.time_delta().abs();
-        if server_time_delta >= out_of_sync_threshold as i64 {
-            Err(Error::clock_out_of_sync(
-                server_time_delta,
-                out_of_sync_threshold,
-            ))
-        } else {
-            Ok(())
-        }
-    }
-
-    async fn check_sync(&self) -> ClientResult<()> {
+    /// Corrects the network clock by the time delta of the querying endpoint, so the messages
+    /// are not expired on the devices with the clock out of sync
+    async fn sync_clock(&self) -> ClientResult<()> {
         if self.time_checked.load(Ordering::Relaxed) {
             return Ok(());
         }
 
         let endpoint = self.get_query_endpoint().await?;
-        self.check_time_delta(&endpoint, self.config.out_of_sync_threshold)
-            .await?;
+        self.clock.set_endpoint_offset(endpoint.time_delta(), self.config.out_of_sync_threshold);
 
         self.time_checked.store(true, Ordering::Relaxed);
 
@@ -267,6 +263,15 @@ impl NetworkState {
     }
 
     async fn select_querying_endpoint(&self) -> ClientResult<Endpoint> {
+        // Persisted stats order the endpoints at the first selection,
+        // the stats collected while selecting are persisted for the next sessions
+        self.stats.load().await;
+        let result = self.select_querying_endpoint_impl().await;
+        self.stats.save().await;
+        result
+    }
+
+    async fn select_querying_endpoint_impl(&self) -> ClientResult<Endpoint> {
         let is_better = |a: &ClientResult<Endpoint>, b: &ClientResult<Endpoint>| match (a, b) {
             (Ok(a), Ok(b)) => a.latency() < b.latency(),
             (Ok(_), Err(_)) => true,
@@ -276,10 +281,16 @@ impl NetworkState {
         let mut retry_count = 0i8;
         loop {
             let mut futures = vec![];
-            for address in self.endpoint_addresses.read().await.iter() {
-                let address = address.clone();
+            let addresses = self.stats.prefer_healthy(
+                self.endpoint_addresses.read().await.clone(),
+                self.client_env.now_ms(),
+            );
+            for address in addresses {
                 futures.push(Box::pin(async move {
-                    Endpoint::resolve(&self.client_env, &self.config, &address).await
+                    let start = self.client_env.now_ms();
+                    let result = Endpoint::resolve(&self.client_env, &self.config, &address).await;
+                    self.record_request(&address, start, result.as_ref().err());
+                    result
                 }));
             }
             let mut selected = Err(crate::client::Error::net_module_not_init());
@@ -330,6 +341,24 @@ impl NetworkState {
     pub async fn get_all_endpoint_addresses(&self) -> ClientResult<Vec<String>> {
         Ok(self.endpoint_addresses.read().await.clone())
     }
+
+    pub fn record_request(&self, address: &str, start: u64, error: Option<&ClientError>) {
+        let now = self.client_env.now_ms();
+        self.stats.record(address, now.saturating_sub(start), now, error);
+    }
+
+    pub async fn reset_endpoint_stats(&self) -> ClientResult<()> {
+        self.stats.reset().await
+    }
+
+    pub async fn get_endpoint_stats(&self) -> Vec<EndpointStats> {
+        let current = self.query_endpoint().await.map(|endpoint| endpoint.address.clone());
+        self.stats.get_stats(
+            &self.endpoint_addresses.read().await,
+            current.as_deref(),
+            self.client_env.now_ms(),
+        )
+    }
 }
 
 pub(crate) struct ServerLink {
@@ -337,6 +366,10 @@ pub(crate) struct ServerLink {
     pub(crate) client_env: Arc<ClientEnv>,
     websocket_link: WebsocketLink,
     state: Arc<NetworkState>,
+    schema: RwLock<Option<Arc<GraphQLSchema>>>,
+    archive_endpoint: Mutex<Option<Endpoint>>,
+    query_dedup: QueryDeduplicator,
+    query_cache: QueryCache,
 }
 
 fn strip_endpoint(endpoint: &str) -> &str {
@@ -364,7 +397,11 @@ fn replace_endpoints(mut endpoints: Vec<String>) -> Vec<String> {
 }
 
 impl ServerLink {
-    pub fn new(config: NetworkConfig, client_env: Arc<ClientEnv>) -> ClientResult<Self> {
+    pub fn new(
+        config: NetworkConfig,
+        client_env: Arc<ClientEnv>,
+        local_storage_path: Option<String>,
+    ) -> ClientResult<Self> {
         let endpoint_addresses = config
             .endpoints
             .clone()
@@ -374,11 +411,23 @@ impl ServerLink {
             return Err(crate::client::Error::net_module_not_init());
         }
         let endpoint_addresses = replace_endpoints(endpoint_addresses);
+        let stats_storage = if config.persist_endpoint_stats.unwrap_or(false) {
+            StatsStorage::Closed {
+                local_storage_path,
+                storage_name: format!(
+                    "endpoints/{}",
+                    hex::encode(&sha2::Sha256::digest(endpoint_addresses.join(",").as_bytes())[..4]),
+                ),
+            }
+        } else {
+            StatsStorage::None
+        };
 
         let state = Arc::new(NetworkState::new(
             client_env.clone(),
             config.clone(),
             endpoint_addresses,
+            EndpointStatsCollector::with_storage(stats_storage),
         ));
 
         Ok(ServerLink {
@@ -386,6 +435,13 @@ impl ServerLink {
             client_env: client_env.clone(),
             state: state.clone(),
             websocket_link: WebsocketLink::new(client_env, state, config),
+            schema: RwLock::new(None),
+            archive_endpoint: Mutex::new(None),
+            query_dedup: QueryDeduplicator::new(),
+            query_cache: QueryCache::new(
+                config.query_cache_size.unwrap_or_default(),
+                config.query_cache_ttl.unwrap_or(DEFAULT_QUERY_CACHE_TTL),
+            ),
         })
     }
 
@@ -393,6 +449,10 @@ impl ServerLink {
         &self.config
     }
 
+    fn is_jrpc(&self) -> bool {
+        self.config.queries_protocol == Some(NetworkQueriesProtocol::Jrpc)
+    }
+
     pub async fn config_servers(&self) -> Vec<String> {
         self.state.config_servers().await
     }
@@ -401,6 +461,63 @@ impl ServerLink {
         self.state.clone()
     }
 
+    /// Returns the endpoint GraphQL schema. Schema is requested once and cached
+    pub async fn get_schema(&self) -> ClientResult<Arc<GraphQLSchema>> {
+        if let Some(schema) = self.schema.read().await.as_ref() {
+            return Ok(schema.clone());
+        }
+        let query = GraphQLQuery {
+            query: INTROSPECTION_QUERY.to_string(),
+            variables: None,
+            timeout: None,
+            is_batch: false,
+        };
+        let result = self.query(&query, None).await?;
+        let schema = Arc::new(GraphQLSchema::from_introspection(&result["data"]["__schema"])?);
+        *self.schema.write().await = Some(schema.clone());
+
+        Ok(schema)
+    }
+
+    /// Returns the first available endpoint of `archive_endpoints`.
+    /// Endpoint is resolved once and cached
+    pub async fn archive_endpoint(&self) -> ClientResult<Option<Endpoint>> {
+        let addresses = match &self.config.archive_endpoints {
+            Some(addresses) if !addresses.is_empty() => addresses,
+            _ => return Ok(None),
+        };
+        let mut archive_endpoint = self.archive_endpoint.lock().await;
+        if let Some(endpoint) = archive_endpoint.as_ref() {
+            return Ok(Some(endpoint.clone()));
+        }
+        let mut last_error = None;
+        for address in addresses {
+            match Endpoint::resolve(&self.client_env, &self.config, address).await {
+                Ok(endpoint) => {
+                    *archive_endpoint = Some(endpoint.clone());
+                    return Ok(Some(endpoint));
+                }
+                Err(err) => last_error = Some(err),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| Error::no_endpoints_provided()))
+    }
+
+    /// Returns the schema to validate queries with if `validate_queries` is enabled.
+    /// Queries are not validated if the schema is not available
+    async fn get_validation_schema(&self) -> Option<Arc<GraphQLSchema>> {
+        if !self.config.validate_queries.unwrap_or_default() {
+            return None;
+        }
+        match self.get_schema().await {
+            Ok(schema) => Some(schema),
+            Err(err) => {
+                log::warn!("Queries are not validated, GraphQL schema is not available: {}", err);
+                None
+            }
+        }
+    }
+
     // Returns Stream with updates database fields by provided filter
     pub async fn subscribe_collection(
         &self,
@@ -408,6 +525,13 @@ impl ServerLink {
         filter: &Value,
         fields: &str,
     ) -> ClientResult<Subscription> {
+        if self.is_jrpc() {
+            return Err(Error::not_supported("subscription over JRPC"));
+        }
+        if let Some(schema) = self.get_validation_schema().await {
+            schema.validate_filter(table, filter)?;
+            schema.validate_result(table, fields)?;
+        }
         let event_receiver = self
             .websocket_link
             .start_operation(GraphQLQuery::with_collection_subscription(table, filter, fields))
@@ -450,6 +574,9 @@ impl ServerLink {
         subscription: String,
         variables: Option<Value>,
     ) -> ClientResult<Subscription> {
+        if self.is_jrpc() {
+            return Err(Error::not_supported("subscription over JRPC"));
+        }
         let event_receiver = self
             .websocket_link
             .start_operation(GraphQLQuery::with_subscription(subscription, variables))
@@ -500,22 +627,68 @@ impl ServerLink {
         return None;
     }
 
+    fn has_partial_data(value: &Value) -> bool {
+        value["data"]
+            .as_object()
+            .map(|data| data.values().any(|value| !value.is_null()))
+            .unwrap_or(false)
+    }
+
     pub(crate) async fn query(
         &self,
         query: &GraphQLQuery,
         endpoint: Option<&Endpoint>,
     ) -> ClientResult<Value> {
+        self.query_with_partial_result(query, endpoint, false).await
+    }
+
+    /// Performs the query. If `allow_partial_result` is set, then the response containing
+    /// both `errors` and non empty `data` is returned as is instead of the error.
+    pub(crate) async fn query_with_partial_result(
+        &self,
+        query: &GraphQLQuery,
+        endpoint: Option<&Endpoint>,
+        allow_partial_result: bool,
+    ) -> ClientResult<Value> {
+        if self.is_jrpc() {
+            return Err(Error::not_supported("GraphQL query over JRPC"));
+        }
         let request = json!({
             "query": query.query,
             "variables": query.variables,
         })
         .to_string();
 
+        self.post_with_retries(
+            request,
+            endpoint,
+            query.timeout.unwrap_or(self.config.query_timeout),
+            |value| match Self::try_extract_error(&value) {
+                Some(_) if allow_partial_result && Self::has_partial_data(&value) => Ok(value),
+                Some(err) => Err(err),
+                None => Ok(value),
+            },
+        )
+        .await
+    }
+
+    /// Posts the request to the `endpoint` or to the querying endpoint if it isn't specified.
+    /// Response is checked with `get_result`. Requests failed with network errors are retried
+    async fn post_with_retries(
+        &self,
+        request: String,
+        endpoint: Option<&Endpoint>,
+        timeout: u32,
+        get_result: impl Fn(Value) -> ClientResult<Value>,
+    ) -> ClientResult<Value> {
         let mut headers = HashMap::new();
         headers.insert("content-type".to_owned(), "application/json".to_owned());
         for (name, value) in Endpoint::http_headers() {
             headers.insert(name, value);
         }
+        if let Some(trace_id) = current_trace_id() {
+            headers.insert(TRACE_ID_HEADER.to_owned(), trace_id);
+        }
 
         let network_retries_count = self.config.network_retries_count;
         let mut current_endpoint: Option<Arc<Endpoint>>;
@@ -527,6 +700,7 @@ impl ServerLink {
                 current_endpoint = Some(self.state.get_query_endpoint().await?.clone());
                 current_endpoint.as_ref().unwrap()
             };
+            let start = self.client_env.now_ms();
             let result = self
                 .client_env
                 .fetch(
@@ -534,20 +708,15 @@ impl ServerLink {
                     FetchMethod::Post,
                     Some(headers.clone()),
                     Some(request.clone()),
-                    query.timeout.unwrap_or(self.config.query_timeout),
+                    timeout,
                 )
                 .await;
 
             let result = match result {
                 Err(err) => Err(err),
-                Ok(response) => match response.body_as_json() {
-                    Err(err) => Err(err),
-                    Ok(value) => match Self::try_extract_error(&value) {
-                        Some(err) => Err(err),
-                        None => Ok(value)
-                    }
-                }
+                Ok(response) => response.body_as_json().and_then(&get_result),
             };
+            self.state.record_request(&endpoint.address, start, result.as_ref().err());
 
             if let Err(err) = &result {
                 if crate::client::Error::is_network_error(err) {
@@ -570,6 +739,20 @@ impl ServerLink {
         params: &[ParamsOfQueryOperation],
         endpoint: Option<Endpoint>,
     ) -> ClientResult<Vec<Value>> {
+        if let Some(schema) = self.get_validation_schema().await {
+            for operation in params {
+                schema.validate_operation(operation)?;
+            }
+        }
+        let endpoint = match endpoint {
+            None if params.iter().any(|operation| {
+                is_archive_operation(&self.config, self.client_env.now_ms(), operation)
+            }) => self.archive_endpoint().await?,
+            endpoint => endpoint,
+        };
+        if self.is_jrpc() {
+            return self.jrpc_batch_query(params, endpoint.as_ref()).await;
+        }
         let latency_detection_required = if endpoint.is_none() {
             let endpoint = self.state.get_query_endpoint().await?;
             self.client_env.now_ms() > endpoint.next_latency_detection_time()
@@ -604,10 +787,73 @@ impl ServerLink {
         query.get_results(params, &result)
     }
 
+    /// JRPC has no batches, so the operations are requested concurrently one per request
+    async fn jrpc_batch_query(
+        &self,
+        params: &[ParamsOfQueryOperation],
+        endpoint: Option<&Endpoint>,
+    ) -> ClientResult<Vec<Value>> {
+        let mut requests = Vec::with_capacity(params.len());
+        for (index, operation) in params.iter().enumerate() {
+            let request =
+                JrpcRequest::with_operation(index + 1, operation, self.config.wait_for_timeout)?;
+            requests.push(async move {
+                let result = self.jrpc_call(request, endpoint).await?;
+                jrpc::get_operation_result(operation, result)
+            });
+        }
+        futures::future::try_join_all(requests).await
+    }
+
+    async fn jrpc_call(
+        &self,
+        request: JrpcRequest,
+        endpoint: Option<&Endpoint>,
+    ) -> ClientResult<Value> {
+        self.post_with_retries(
+            request.body,
+            endpoint,
+            request.timeout.unwrap_or(self.config.query_timeout),
+            jrpc::get_result,
+        )
+        .await
+    }
+
     pub async fn query_collection(
         &self,
         params: ParamsOfQueryCollection,
         endpoint: Option<Endpoint>,
+    ) -> ClientResult<Value> {
+        let cacheable = self.query_cache.is_cacheable(&params);
+        let deduplicate = self.config.deduplicate_queries.unwrap_or(false);
+        if endpoint.is_some() || (!cacheable && !deduplicate) {
+            return self.query_collection_impl(params, endpoint).await;
+        }
+        let key = serde_json::to_string(&params)
+            .map_err(|err| crate::client::Error::internal_error(err))?;
+        if cacheable {
+            if let Some(result) = self.query_cache.get(&key, self.client_env.now_ms()) {
+                return Ok(result);
+            }
+        }
+        let collection = params.collection.clone();
+        let result = if deduplicate {
+            self.query_dedup
+                .run(key.clone(), || self.query_collection_impl(params, None))
+                .await
+        } else {
+            self.query_collection_impl(params, None).await
+        };
+        if let (true, Ok(result)) = (cacheable, &result) {
+            self.query_cache.put(key, &collection, result, self.client_env.now_ms());
+        }
+        result
+    }
+
+    async fn query_collection_impl(
+        &self,
+        params: ParamsOfQueryCollection,
+        endpoint: Option<Endpoint>,
     ) -> ClientResult<Value> {
         Ok(self
             .batch_query(&[ParamsOfQueryOperation::QueryCollection(params)], endpoint)
@@ -665,14 +911,18 @@ impl ServerLink {
             body: base64::encode(value),
         };
 
-        self.state.check_sync().await?;
+        self.state.sync_clock().await?;
 
-        let result = self
-            .query(
+        let result = if self.is_jrpc() {
+            self.jrpc_call(JrpcRequest::with_post_request(1, &request), endpoint.as_ref())
+                .await
+        } else {
+            self.query(
                 &GraphQLQuery::with_post_requests(&[request]),
                 endpoint.as_ref(),
             )
-            .await;
+            .await
+        };
 
         // send message is always successful in order to process case when server received message
         // but client didn't receive response
@@ -694,6 +944,9 @@ impl ServerLink {
     }
 
     pub async fn fetch_endpoint_addresses(&self) -> ClientResult<Vec<String>> {
+        if self.is_jrpc() {
+            return Err(Error::not_supported("endpoints list over JRPC"));
+        }
         let endpoint = self.state.get_query_endpoint().await?;
 
         let result = query_by_url(
@@ -726,6 +979,14 @@ impl ServerLink {
         self.state.get_query_endpoint().await
     }
 
+    pub(crate) fn clock(&self) -> &NetworkClock {
+        &self.state.clock
+    }
+
+    pub async fn sync_clock(&self) -> ClientResult<()> {
+        self.state.sync_clock().await
+    }
+
     pub async fn get_all_endpoint_addresses(&self) -> ClientResult<Vec<String>> {
         self.state.get_all_endpoint_addresses().await
     }
@@ -734,7 +995,19 @@ impl ServerLink {
         self.state.update_stat(addresses, stat).await
     }
 
+    pub async fn order_by_delivery_health(&self, addresses: &mut Vec<String>) {
+        self.state.order_by_delivery_health(addresses).await
+    }
+
     pub async fn invalidate_querying_endpoint(&self) {
         self.state.invalidate_querying_endpoint().await
     }
+
+    pub async fn reset_endpoint_stats(&self) -> ClientResult<()> {
+        self.state.reset_endpoint_stats().await
+    }
+
+    pub async fn get_endpoint_stats(&self) -> Vec<EndpointStats> {
+        self.state.get_endpoint_stats().await
+    }
 }
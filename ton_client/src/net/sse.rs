@@ -0,0 +1,304 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+// Subscriptions transport over Server-Sent Events (`graphql-sse` protocol,
+// distinct connections mode).
+//
+// The transport is presented to the link handler as a websocket speaking `graphql-ws`
+// messages, so the resubscription, keep-alive and latency logic is shared with
+// the websocket transport.
+
+use crate::client::{ClientEnv, FetchMethod, WebSocket};
+use crate::error::{ClientError, ClientResult};
+use crate::net::Error;
+use futures::channel::mpsc::{channel, Receiver, Sender};
+use futures::future::{abortable, AbortHandle};
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+const SSE_CONTENT_TYPE: &str = "text/event-stream";
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum SseEvent {
+    /// Comment line. Servers send comments to keep the connection alive.
+    Comment,
+    Event { event: String, data: String },
+}
+
+/// Splits `text/event-stream` body received by chunks into events
+#[derive(Default)]
+pub(crate) struct SseParser {
+    buffer: Vec<u8>,
+    event: String,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(&['\n', '\r'][..]);
+            if line.is_empty() {
+                if !self.event.is_empty() || !self.data.is_empty() {
+                    let event = std::mem::take(&mut self.event);
+                    events.push(SseEvent::Event {
+                        event: if event.is_empty() { "message".to_string() } else { event },
+                        data: self.data.join("\n"),
+                    });
+                    self.data.clear();
+                }
+            } else if line.starts_with(':') {
+                events.push(SseEvent::Comment);
+            } else {
+                let (field, value) = match line.find(':') {
+                    Some(pos) => {
+                        let value = &line[pos + 1..];
+                        (&line[..pos], value.strip_prefix(' ').unwrap_or(value))
+                    }
+                    None => (line, ""),
+                };
+                match field {
+                    "event" => self.event = value.to_string(),
+                    "data" => self.data.push(value.to_string()),
+                    _ => {}
+                }
+            }
+        }
+        events
+    }
+}
+
+enum StreamEvent {
+    KeepAlive,
+    Message(Value),
+    Finished,
+    Failed(ClientError),
+}
+
+struct StreamMessage {
+    id: String,
+    event: StreamEvent,
+}
+
+async fn notify(sender: &mut Sender<StreamMessage>, id: &str, event: StreamEvent) {
+    let _ = sender
+        .send(StreamMessage {
+            id: id.to_string(),
+            event,
+        })
+        .await;
+}
+
+async fn run_stream(
+    client_env: Arc<ClientEnv>,
+    url: String,
+    mut headers: HashMap<String, String>,
+    id: String,
+    payload: Value,
+    mut sender: Sender<StreamMessage>,
+) {
+    headers.insert("Accept".into(), SSE_CONTENT_TYPE.into());
+    headers.insert("Content-Type".into(), "application/json".into());
+    let response = match client_env
+        .fetch_stream(&url, FetchMethod::Post, Some(headers), Some(payload.to_string()))
+        .await
+    {
+        Ok(response) => response,
+        Err(err) => return notify(&mut sender, &id, StreamEvent::Failed(err)).await,
+    };
+
+    if response.status >= 500 {
+        let err = Error::invalid_server_response(format!(
+            "Server responded with code {}",
+            response.status
+        ));
+        return notify(&mut sender, &id, StreamEvent::Failed(err)).await;
+    }
+    let content_type = response.headers.get("content-type").cloned().unwrap_or_default();
+    if response.status >= 300 || !content_type.starts_with(SSE_CONTENT_TYPE) {
+        let error = json!({
+            "message": format!(
+                "Endpoint doesn't support Server-Sent Events subscriptions: \
+                    status {}, content type `{}`",
+                response.status, content_type,
+            ),
+        });
+        notify(
+            &mut sender,
+            &id,
+            StreamEvent::Message(json!({ "type": "error", "id": id, "payload": error })),
+        )
+        .await;
+        return notify(&mut sender, &id, StreamEvent::Finished).await;
+    }
+
+    let mut parser = SseParser::default();
+    let mut body = response.body;
+    while let Some(chunk) = body.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(err) => return notify(&mut sender, &id, StreamEvent::Failed(err)).await,
+        };
+        for event in parser.push(&chunk) {
+            match event {
+                SseEvent::Comment => notify(&mut sender, &id, StreamEvent::KeepAlive).await,
+                SseEvent::Event { event, data } if event == "next" => {
+                    let payload = serde_json::from_str::<Value>(&data).unwrap_or_default();
+                    let message = json!({ "type": "data", "id": id, "payload": payload });
+                    notify(&mut sender, &id, StreamEvent::Message(message)).await;
+                }
+                SseEvent::Event { event, .. } if event == "complete" => {
+                    let message = json!({ "type": "complete", "id": id });
+                    notify(&mut sender, &id, StreamEvent::Message(message)).await;
+                    return notify(&mut sender, &id, StreamEvent::Finished).await;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let err = Error::invalid_server_response("Server-Sent Events stream closed by server");
+    notify(&mut sender, &id, StreamEvent::Failed(err)).await;
+}
+
+struct SseTransport {
+    client_env: Arc<ClientEnv>,
+    url: String,
+    headers: HashMap<String, String>,
+    server_sender: Sender<ClientResult<String>>,
+    // Ordered by operation id: keep-alives of the first stream only are passed to the link
+    streams: BTreeMap<u32, AbortHandle>,
+}
+
+impl SseTransport {
+    async fn run(mut self, client_receiver: Receiver<String>) {
+        let (stream_sender, stream_receiver) = channel(10);
+        let mut client_receiver = client_receiver.fuse();
+        let mut stream_receiver = stream_receiver.fuse();
+        loop {
+            futures::select!(
+                message = client_receiver.next() => match message {
+                    Some(message) if !message.is_empty() => {
+                        if !self.handle_client_message(&message, &stream_sender).await {
+                            break;
+                        }
+                    }
+                    _ => break,
+                },
+                message = stream_receiver.select_next_some() => {
+                    self.handle_stream_message(message).await
+                },
+            );
+        }
+        for (_, handle) in self.streams {
+            handle.abort();
+        }
+    }
+
+    async fn send_to_client(&mut self, message: ClientResult<String>) {
+        let _ = self.server_sender.send(message).await;
+    }
+
+    async fn handle_client_message(
+        &mut self,
+        message: &str,
+        stream_sender: &Sender<StreamMessage>,
+    ) -> bool {
+        let message = serde_json::from_str::<Value>(message).unwrap_or_default();
+        let id = message["id"].as_str().unwrap_or("").to_string();
+        match message["type"].as_str().unwrap_or("") {
+            "connection_init" => {
+                self.send_to_client(Ok(json!({ "type": "connection_ack" }).to_string()))
+                    .await;
+            }
+            "start" => {
+                let (stream, handle) = abortable(run_stream(
+                    self.client_env.clone(),
+                    self.url.clone(),
+                    self.headers.clone(),
+                    id.clone(),
+                    message["payload"].clone(),
+                    stream_sender.clone(),
+                ));
+                self.client_env.spawn(async move {
+                    let _ = stream.await;
+                });
+                if let Ok(key) = id.parse() {
+                    self.streams.insert(key, handle);
+                }
+            }
+            "stop" => {
+                if let Some(handle) = id.parse().ok().and_then(|key| self.streams.remove(&key)) {
+                    handle.abort();
+                }
+            }
+            "connection_terminate" => return false,
+            _ => {}
+        }
+        true
+    }
+
+    async fn handle_stream_message(&mut self, message: StreamMessage) {
+        let key = message.id.parse::<u32>().unwrap_or_default();
+        match message.event {
+            StreamEvent::KeepAlive => {
+                if self.streams.keys().next() == Some(&key) {
+                    self.send_to_client(Ok(json!({ "type": "ka" }).to_string())).await;
+                }
+            }
+            StreamEvent::Message(message) => {
+                self.send_to_client(Ok(message.to_string())).await;
+            }
+            StreamEvent::Finished => {
+                self.streams.remove(&key);
+            }
+            StreamEvent::Failed(err) => {
+                if self.streams.remove(&key).is_some() {
+                    self.send_to_client(Err(err)).await;
+                }
+            }
+        }
+    }
+}
+
+/// Starts the subscriptions transport over Server-Sent Events.
+/// Subscriptions are sent as streaming POST requests to the `url`.
+pub(crate) fn connect(
+    client_env: Arc<ClientEnv>,
+    url: &str,
+    headers: HashMap<String, String>,
+) -> WebSocket {
+    let (client_sender, client_receiver) = channel::<String>(10);
+    let (server_sender, server_receiver) = channel::<ClientResult<String>>(10);
+    let transport = SseTransport {
+        client_env: client_env.clone(),
+        url: url.to_string(),
+        headers,
+        server_sender,
+        streams: BTreeMap::new(),
+    };
+    client_env.spawn(transport.run(client_receiver));
+
+    WebSocket {
+        sender: Box::pin(
+            client_sender.sink_map_err(|err| crate::client::Error::websocket_send_error(err)),
+        ),
+        receiver: Box::pin(server_receiver),
+    }
+}
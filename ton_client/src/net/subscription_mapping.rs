@@ -0,0 +1,133 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+
+#[derive(Serialize, Deserialize, ApiType, Clone, Debug, PartialEq)]
+pub enum PredicateOperator {
+    /// Field value equals to the predicate value
+    Eq,
+    /// Field value doesn't equal to the predicate value
+    Ne,
+    /// Field value is greater than the predicate value
+    Gt,
+    /// Field value is greater than or equal to the predicate value
+    Ge,
+    /// Field value is less than the predicate value
+    Lt,
+    /// Field value is less than or equal to the predicate value
+    Le,
+    /// Field is present and is not `null`. Predicate value is ignored
+    Exists,
+}
+
+impl Default for PredicateOperator {
+    fn default() -> Self {
+        PredicateOperator::Eq
+    }
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone, Debug)]
+pub struct FieldPredicate {
+    /// Dot separated path to the field in the event document, e.g. `master.min_shard_gen_utime`.
+    /// Array items are addressed by index: `out_messages.0.dst`.
+    pub path: String,
+    /// Comparison operator
+    pub operator: PredicateOperator,
+    /// Value to compare the field with. Numbers are compared numerically, strings
+    /// lexicographically
+    pub value: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone, Debug)]
+pub struct SubscriptionMapping {
+    /// Predicates which an event must satisfy to be passed to the callback.
+    /// All predicates must be satisfied. Events not satisfying them are dropped without
+    /// crossing the FFI boundary.
+    pub predicates: Option<Vec<FieldPredicate>>,
+    /// Projection of the event document: a map of output field names to the dot separated
+    /// paths of the source fields, e.g. `{ "seq_no": "seq_no", "shard": "shard" }`.
+    /// The whole document is passed if projection is omitted.
+    pub projection: Option<Value>,
+}
+
+fn get_by_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .filter(|name| !name.is_empty())
+        .try_fold(value, |value, name| match value {
+            Value::Object(map) => map.get(name),
+            Value::Array(items) => name.parse::<usize>().ok().and_then(|index| items.get(index)),
+            _ => None,
+        })
+}
+
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => {
+            left.as_f64()?.partial_cmp(&right.as_f64()?)
+        }
+        (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+        (Value::Bool(left), Value::Bool(right)) => Some(left.cmp(right)),
+        _ => None,
+    }
+}
+
+impl FieldPredicate {
+    pub(crate) fn is_satisfied(&self, document: &Value) -> bool {
+        let field = get_by_path(document, &self.path).unwrap_or(&Value::Null);
+        let value = self.value.as_ref().unwrap_or(&Value::Null);
+        match self.operator {
+            PredicateOperator::Exists => !field.is_null(),
+            PredicateOperator::Eq => field == value,
+            PredicateOperator::Ne => field != value,
+            PredicateOperator::Gt => compare(field, value) == Some(Ordering::Greater),
+            PredicateOperator::Ge => {
+                matches!(compare(field, value), Some(Ordering::Greater) | Some(Ordering::Equal))
+            }
+            PredicateOperator::Lt => compare(field, value) == Some(Ordering::Less),
+            PredicateOperator::Le => {
+                matches!(compare(field, value), Some(Ordering::Less) | Some(Ordering::Equal))
+            }
+        }
+    }
+}
+
+impl SubscriptionMapping {
+    /// Applies the mapping to the subscription event document. Returns `None` if the event
+    /// must be dropped.
+    pub(crate) fn apply(&self, document: Value) -> Option<Value> {
+        if let Some(predicates) = &self.predicates {
+            if !predicates.iter().all(|predicate| predicate.is_satisfied(&document)) {
+                return None;
+            }
+        }
+
+        let projection = match &self.projection {
+            Some(Value::Object(projection)) => projection,
+            _ => return Some(document),
+        };
+
+        let mut result = Map::new();
+        for (name, path) in projection {
+            let value = path
+                .as_str()
+                .and_then(|path| get_by_path(&document, path))
+                .cloned()
+                .unwrap_or(Value::Null);
+            result.insert(name.clone(), value);
+        }
+
+        Some(Value::Object(result))
+    }
+}
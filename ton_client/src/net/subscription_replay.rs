@@ -0,0 +1,210 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+// Recovery of the collection subscription items missed while the network was suspended.
+//
+// Subscription keeps the cursor (`lt` or time field) of the last received item. When the
+// network module is resumed the collection is queried for the items following the cursor,
+// and the items received by both the query and the restarted subscription are skipped.
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::Arc;
+
+use serde_json::{Map, Value};
+
+use crate::client::ClientContext;
+use crate::net::{query_collection, OrderBy, ParamsOfQueryCollection, SortDirection};
+use crate::tvm::accounts_cache::parse_lt;
+
+const REPLAY_PAGE_SIZE: u32 = 50;
+const MAX_REPLAY_PAGES: usize = 20;
+const MAX_RECENT_ITEMS: usize = 1000;
+
+/// Field which orders the collection items by the time they are produced
+fn cursor_field(collection: &str) -> Option<&'static str> {
+    match collection {
+        "transactions" => Some("lt"),
+        "messages" => Some("created_lt"),
+        "accounts" => Some("last_trans_lt"),
+        "blocks" | "block_signatures" => Some("gen_utime"),
+        _ => None,
+    }
+}
+
+/// Checks if the field is selected on the top level of the result projection
+pub(crate) fn has_top_level_field(result: &str, field: &str) -> bool {
+    let mut depth = 0;
+    let mut name = String::new();
+    for c in result.chars().chain(std::iter::once(' ')) {
+        if c.is_alphanumeric() || c == '_' {
+            name.push(c);
+            continue;
+        }
+        if depth == 0 && name == field {
+            return true;
+        }
+        name.clear();
+        match c {
+            '{' | '(' => depth += 1,
+            '}' | ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    false
+}
+
+pub(crate) struct SubscriptionReplay {
+    collection: String,
+    filter: Value,
+    result: String,
+    cursor_field: Option<&'static str>,
+    added_fields: Vec<&'static str>,
+    cursor: Option<(u64, Value)>,
+    recent: HashSet<String>,
+    recent_order: VecDeque<String>,
+}
+
+impl SubscriptionReplay {
+    pub fn new(collection: &str, filter: Option<&Value>, result: &str) -> Self {
+        let cursor_field = cursor_field(collection);
+        let mut result = result.to_string();
+        let mut added_fields = Vec::new();
+        if let Some(cursor_field) = cursor_field {
+            for field in &["id", cursor_field] {
+                if !has_top_level_field(&result, field) {
+                    result.push(' ');
+                    result.push_str(field);
+                    added_fields.push(*field);
+                }
+            }
+        }
+        Self {
+            collection: collection.to_string(),
+            filter: filter.cloned().unwrap_or_else(|| json!({})),
+            result,
+            cursor_field,
+            added_fields,
+            cursor: None,
+            recent: HashSet::new(),
+            recent_order: VecDeque::new(),
+        }
+    }
+
+    /// Result projection of the subscription, the cursor fields are added if required
+    pub fn result(&self) -> &str {
+        &self.result
+    }
+
+    /// Remembers the item cursor. Returns `false` if the item was already received
+    pub fn accept(&mut self, item: &Value) -> bool {
+        let cursor_field = match self.cursor_field {
+            Some(field) => field,
+            None => return true,
+        };
+        // `lt` fields are returned as hex strings, time fields as numbers
+        let cursor = item[cursor_field].clone();
+        if let (Some(id), Some(value)) = (item["id"].as_str(), parse_lt(&cursor)) {
+            let key = format!("{}:{}", id, value);
+            if !self.recent.insert(key.clone()) {
+                return false;
+            }
+            self.recent_order.push_back(key);
+            if self.recent_order.len() > MAX_RECENT_ITEMS {
+                if let Some(key) = self.recent_order.pop_front() {
+                    self.recent.remove(&key);
+                }
+            }
+            if self.cursor.as_ref().map(|(last, _)| value > *last).unwrap_or(true) {
+                self.cursor = Some((value, cursor));
+            }
+        }
+        true
+    }
+
+    /// Removes the fields added to the result projection
+    pub fn strip(&self, mut item: Value) -> Value {
+        if let Some(object) = item.as_object_mut() {
+            for field in &self.added_fields {
+                object.remove(*field);
+            }
+        }
+        item
+    }
+
+    fn replay_filter(&self, cursor_field: &str, cursor: &Value) -> Value {
+        let mut filter = self.filter.clone();
+        if !filter.is_object() {
+            filter = json!({});
+        }
+        let condition = filter
+            .as_object_mut()
+            .unwrap()
+            .entry(cursor_field)
+            .or_insert_with(|| Value::Object(Map::new()));
+        if !condition.is_object() {
+            *condition = Value::Object(Map::new());
+        }
+        condition["ge"] = cursor.clone();
+        filter
+    }
+
+    /// Queries the items following the last received one. Returns the items which were
+    /// not received before and `true` if all the missed items are queried.
+    pub async fn query_missed(&mut self, context: &Arc<ClientContext>) -> (Vec<Value>, bool) {
+        let (cursor_field, mut cursor) = match (self.cursor_field, self.cursor.clone()) {
+            (Some(field), Some(cursor)) => (field, cursor),
+            _ => return (Vec::new(), false),
+        };
+        let mut missed = Vec::new();
+        for _ in 0..MAX_REPLAY_PAGES {
+            let page = query_collection(
+                context.clone(),
+                ParamsOfQueryCollection {
+                    collection: self.collection.clone(),
+                    filter: Some(self.replay_filter(cursor_field, &cursor.1)),
+                    result: self.result.clone(),
+                    order: Some(vec![OrderBy {
+                        path: cursor_field.to_string(),
+                        direction: SortDirection::ASC,
+                    }]),
+                    limit: Some(REPLAY_PAGE_SIZE),
+                    ..Default::default()
+                },
+            )
+            .await;
+            let page = match page {
+                Ok(page) => page.result,
+                Err(err) => {
+                    log::warn!("Missed subscription items are not queried: {}", err.message);
+                    return (missed, false);
+                }
+            };
+            let complete = page.len() < REPLAY_PAGE_SIZE as usize;
+            for item in page {
+                if self.accept(&item) {
+                    missed.push(item);
+                }
+            }
+            if complete {
+                return (missed, true);
+            }
+            match self.cursor.clone() {
+                // The whole page has the same cursor, so the next page can't be queried
+                Some(next) if next.0 > cursor.0 => cursor = next,
+                _ => return (missed, false),
+            }
+        }
+        (missed, false)
+    }
+}
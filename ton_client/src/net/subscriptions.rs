@@ -12,8 +12,11 @@
 */
 
 use super::Error;
+use super::body_decoding::{BodyDecoder, BodyDecoding};
+use super::subscription_mapping::SubscriptionMapping;
+use super::subscription_replay::SubscriptionReplay;
 use crate::client::ClientContext;
-use crate::error::{AddNetworkUrl, ClientResult};
+use crate::error::{AddNetworkUrl, ClientError, ClientResult};
 use futures::{Future, FutureExt, StreamExt};
 use rand::RngCore;
 use tokio::sync::mpsc::{channel, Sender};
@@ -22,6 +25,19 @@ use tokio::sync::mpsc::{channel, Sender};
 pub enum SubscriptionResponseType {
     Ok = 100,
     Error = 101,
+    Event = 102,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Clone, Debug, PartialEq)]
+#[serde(tag = "type")]
+pub enum SubscriptionEvent {
+    /// Collection subscription is restarted after the network module was resumed.
+    Resumed {
+        /// `true` if the items missed while the network was suspended were queried
+        /// and passed to the callback before this event. `false` if some items could
+        /// be lost, so the application should refresh its data.
+        gap_recovered: bool,
+    },
 }
 
 #[derive(Serialize, Deserialize, ApiType, Default, Clone)]
@@ -32,6 +48,14 @@ pub struct ParamsOfSubscribeCollection {
     pub filter: Option<serde_json::Value>,
     /// Projection (result) string
     pub result: String,
+    /// Client-side mapping applied to each event before passing it to the callback
+    pub mapping: Option<SubscriptionMapping>,
+    /// Decode the bodies of the received messages into `decoded_body` field before
+    /// the `mapping` is applied.
+    ///
+    /// Bodies are decoded by the endpoint if it supports the decoded body field, and locally
+    /// with the ABI otherwise. Supported for `messages` collection.
+    pub decode_body: Option<BodyDecoding>,
 }
 
 #[derive(Serialize, Deserialize, ApiType, Default, Clone)]
@@ -41,6 +65,8 @@ pub struct ParamsOfSubscribe {
     /// Variables used in subscription. Must be a map with named values that
     /// can be used in query.
     pub variables: Option<serde_json::Value>,
+    /// Client-side mapping applied to each event before passing it to the callback
+    pub mapping: Option<SubscriptionMapping>,
 }
 
 #[derive(Serialize, Deserialize, ApiType, Default, Clone)]
@@ -80,16 +106,27 @@ async fn extract_subscription_handle(
     context.net.subscriptions.lock().await.remove(handle)
 }
 
+fn map_subscription_data(
+    mapping: Option<&SubscriptionMapping>,
+    data: ClientResult<serde_json::Value>,
+) -> Option<ClientResult<serde_json::Value>> {
+    match (mapping, data) {
+        (Some(mapping), Ok(data)) => mapping.apply(data).map(Ok),
+        (_, data) => Some(data),
+    }
+}
+
 async fn create_collection_subscription(
     context: std::sync::Arc<ClientContext>,
     params: &ParamsOfSubscribeCollection,
+    result: &str,
 ) -> ClientResult<super::server_link::Subscription> {
     let client = context.get_server_link()?;
     client
         .subscribe_collection(
             &params.collection,
             params.filter.as_ref().unwrap_or(&json!({})),
-            &params.result,
+            result,
         )
         .await
         .map_err(|err| Error::queries_subscribe_failed(err))
@@ -97,14 +134,67 @@ async fn create_collection_subscription(
         .await
 }
 
+async fn notify_collection_item<F: Future<Output = ()> + Send>(
+    context: &ClientContext,
+    collection: &str,
+    mapping: Option<&SubscriptionMapping>,
+    decoder: Option<&BodyDecoder>,
+    replay: &SubscriptionReplay,
+    item: serde_json::Value,
+    callback: &impl Fn(ClientResult<ResultOfSubscription>) -> F,
+) {
+    context.net.accounts_cache.observe_collection_item(collection, &item).await;
+    let mut item = replay.strip(item);
+    if let Some(decoder) = decoder {
+        if let Err(err) = decoder.decode(&mut item).await {
+            return callback(Err(err)).await;
+        }
+    }
+    if let Some(data) = map_subscription_data(mapping, Ok(item)) {
+        callback(data.map(|data| ResultOfSubscription { result: data })).await
+    }
+}
+
 pub async fn subscribe_collection<F: Future<Output = ()> + Send>(
     context: std::sync::Arc<ClientContext>,
     params: ParamsOfSubscribeCollection,
     callback: impl Fn(ClientResult<ResultOfSubscription>) -> F + Send + Sync + 'static,
+) -> ClientResult<ResultOfSubscribeCollection> {
+    subscribe_collection_with_events(context, params, callback, |_| futures::future::ready(()))
+        .await
+}
+
+/// Same as `subscribe_collection` but also reports the subscription events.
+///
+/// When the network module is resumed the items missed while it was suspended are queried
+/// by the cursor (`lt` or time field) of the last received item and passed to the `callback`,
+/// then the `SubscriptionEvent::Resumed` is passed to the `event_callback`.
+pub async fn subscribe_collection_with_events<
+    F: Future<Output = ()> + Send,
+    EF: Future<Output = ()> + Send,
+>(
+    context: std::sync::Arc<ClientContext>,
+    params: ParamsOfSubscribeCollection,
+    callback: impl Fn(ClientResult<ResultOfSubscription>) -> F + Send + Sync + 'static,
+    event_callback: impl Fn(SubscriptionEvent) -> EF + Send + Sync + 'static,
 ) -> ClientResult<ResultOfSubscribeCollection> {
     let handle = rand::thread_rng().next_u32();
 
-    let mut subscription = Some(create_collection_subscription(context.clone(), &params).await?);
+    let mut params = params;
+    let decoder = BodyDecoder::resolve(
+        &context,
+        &params.collection,
+        params.decode_body.take(),
+        &mut params.result,
+    ).await?;
+    let mut replay =
+        SubscriptionReplay::new(&params.collection, params.filter.as_ref(), &params.result);
+    let mut subscription = Some(
+        create_collection_subscription(context.clone(), &params, replay.result()).await?,
+    );
+    let mapping = params.mapping;
+    let collection = params.collection;
+    let observer = context.clone();
 
     let (sender, mut receiver) = channel(1);
     add_subscription_handle(&context, handle, sender).await;
@@ -118,8 +208,39 @@ pub async fn subscribe_collection<F: Future<Output = ()> + Send>(
         loop {
             futures::select!(
                 // waiting next subscription data
-                data = data_stream.select_next_some() => {
-                    callback(data.map(|data| ResultOfSubscription { result: data })).await
+                data = data_stream.select_next_some() => match data {
+                    Ok(item) => {
+                        if replay.accept(&item) {
+                            notify_collection_item(
+                                &observer,
+                                &collection,
+                                mapping.as_ref(),
+                                decoder.as_ref(),
+                                &replay,
+                                item,
+                                &callback,
+                            ).await;
+                        }
+                    }
+                    Err(err) => {
+                        let resumed = is_network_module_resumed(&err);
+                        callback(Err(err)).await;
+                        if resumed {
+                            let (missed, gap_recovered) = replay.query_missed(&observer).await;
+                            for item in missed {
+                                notify_collection_item(
+                                    &observer,
+                                    &collection,
+                                    mapping.as_ref(),
+                                    decoder.as_ref(),
+                                    &replay,
+                                    item,
+                                    &callback,
+                                ).await;
+                            }
+                            event_callback(SubscriptionEvent::Resumed { gap_recovered }).await;
+                        }
+                    }
                 },
                 // waiting for some action with subscription (the only action is Finish)
                 _action = wait_action => {
@@ -133,6 +254,10 @@ pub async fn subscribe_collection<F: Future<Output = ()> + Send>(
     Ok(ResultOfSubscribeCollection { handle })
 }
 
+fn is_network_module_resumed(err: &ClientError) -> bool {
+    err.code == super::ErrorCode::NetworkModuleResumed as u32
+}
+
 async fn create_subscription(
     context: std::sync::Arc<ClientContext>,
     params: &ParamsOfSubscribe,
@@ -154,6 +279,7 @@ pub async fn subscribe<F: Future<Output = ()> + Send>(
     let handle = rand::thread_rng().next_u32();
 
     let mut subscription = Some(create_subscription(context.clone(), &params).await?);
+    let mapping = params.mapping;
 
     let (sender, mut receiver) = channel(1);
     add_subscription_handle(&context, handle, sender).await;
@@ -168,7 +294,9 @@ pub async fn subscribe<F: Future<Output = ()> + Send>(
             futures::select!(
                 // waiting next subscription data
                 data = data_stream.select_next_some() => {
-                    callback(data.map(|data| ResultOfSubscription { result: data })).await
+                    if let Some(data) = map_subscription_data(mapping.as_ref(), data) {
+                        callback(data.map(|data| ResultOfSubscription { result: data })).await
+                    }
                 },
                 // waiting for some action with subscription (the only action is Finish)
                 _action = wait_action => {
@@ -30,6 +30,8 @@ async fn batch_query() {
                         result: "id".to_owned(),
                         limit: Some(1),
                         order: None,
+                        min_block: None,
+                        decode_body: None,
                     }),
                     ParamsOfQueryOperation::AggregateCollection(ParamsOfAggregateCollection {
                         collection: "accounts".to_owned(),
@@ -38,6 +40,7 @@ async fn batch_query() {
                             field: "".into(),
                             aggregation_fn: AggregationFn::COUNT,
                         }]),
+                        ..Default::default()
                     }),
                     ParamsOfQueryOperation::WaitForCollection(ParamsOfWaitForCollection {
                         collection: "transactions".to_owned(),
@@ -66,6 +69,7 @@ async fn query() {
             ParamsOfQuery {
                 query: "query{info{version}}".to_owned(),
                 variables: None,
+                allow_partial_result: None,
             },
         )
         .await
@@ -88,6 +92,8 @@ async fn block_signatures() {
                 result: "id".to_owned(),
                 limit: Some(1),
                 order: None,
+                min_block: None,
+                decode_body: None,
             },
         )
         .await
@@ -107,6 +113,8 @@ async fn all_accounts() {
                 result: "id balance".to_owned(),
                 limit: None,
                 order: None,
+                min_block: None,
+                decode_body: None,
             },
         )
         .await
@@ -129,6 +137,7 @@ async fn aggregates() {
                     field: "".into(),
                     aggregation_fn: AggregationFn::COUNT,
                 }]),
+                ..Default::default()
             },
         )
         .await
@@ -138,6 +147,83 @@ async fn aggregates() {
     assert!(count > 0);
 }
 
+#[tokio::test(core_threads = 2)]
+async fn aggregates_by_groups() {
+    let client = TestClient::new();
+    let fields = Some(vec![
+        FieldAggregation {
+            field: "".into(),
+            aggregation_fn: AggregationFn::COUNT,
+        },
+        FieldAggregation {
+            field: "tr_count".into(),
+            aggregation_fn: AggregationFn::SUM,
+        },
+    ]);
+
+    let result: ResultOfAggregateCollection = client
+        .request_async(
+            "net.aggregate_collection",
+            ParamsOfAggregateCollection {
+                collection: "blocks".to_owned(),
+                filter: Some(json!({ "workchain_id": { "eq": -1 } })),
+                fields: fields.clone(),
+                group_by: Some(AggregationGroupBy {
+                    field: "shard".into(),
+                    values: vec![json!("8000000000000000"), json!("unknown")],
+                }),
+                having: None,
+            },
+        )
+        .await
+        .unwrap();
+
+    // all the masterchain blocks belong to the single shard
+    let groups = result.groups.unwrap();
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].value, "8000000000000000");
+    assert_ne!(groups[0].values[0], "0");
+    assert_eq!(groups[1].values[0], "0");
+
+    let result: ResultOfAggregateCollection = client
+        .request_async(
+            "net.aggregate_collection",
+            ParamsOfAggregateCollection {
+                collection: "blocks".to_owned(),
+                filter: Some(json!({ "workchain_id": { "eq": -1 } })),
+                fields: fields.clone(),
+                group_by: Some(AggregationGroupBy {
+                    field: "shard".into(),
+                    values: vec![json!("8000000000000000"), json!("unknown")],
+                }),
+                having: Some(vec![FieldPredicate {
+                    path: "0".into(),
+                    operator: PredicateOperator::Gt,
+                    value: Some(json!(0)),
+                }]),
+            },
+        )
+        .await
+        .unwrap();
+    let groups = result.groups.unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].value, "8000000000000000");
+
+    let error = client
+        .request_async::<_, ResultOfAggregateCollection>(
+            "net.aggregate_collection",
+            ParamsOfAggregateCollection {
+                collection: "blocks".to_owned(),
+                fields,
+                having: Some(vec![]),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::InvalidQuery as u32);
+}
+
 #[tokio::test(core_threads = 2)]
 async fn ranges() {
     let client = TestClient::new();
@@ -153,6 +239,8 @@ async fn ranges() {
                 result: "body created_at".to_owned(),
                 limit: None,
                 order: None,
+                min_block: None,
+                decode_body: None,
             },
         )
         .await
@@ -248,6 +336,32 @@ async fn message_sending_addresses() {
     assert!(a_good && e_good)
 }
 
+#[tokio::test(core_threads = 2)]
+async fn message_sending_addresses_health_order() {
+    let client = ClientContext::new(ClientConfig {
+        network: NetworkConfig {
+            endpoints: Some(vec!["a".into(), "b".into(), "c".into(), "d".into()]),
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .unwrap();
+    let link = client.get_server_link().unwrap();
+    link.update_stat(&vec!["c".to_string()], EndpointStat::MessageUndelivered)
+        .await;
+
+    // Addresses rotated by `send_messages` keep their order except the unhealthy ones
+    let mut addresses = vec!["c".to_string(), "d".to_string(), "a".to_string(), "b".to_string()];
+    link.order_by_delivery_health(&mut addresses).await;
+    assert_eq!(addresses, vec!["d", "a", "b", "c"]);
+
+    link.update_stat(&vec!["c".to_string()], EndpointStat::MessageDelivered)
+        .await;
+    let mut addresses = vec!["c".to_string(), "d".to_string(), "a".to_string(), "b".to_string()];
+    link.order_by_delivery_health(&mut addresses).await;
+    assert_eq!(addresses, vec!["c", "d", "a", "b"]);
+}
+
 #[tokio::test(core_threads = 2)]
 async fn subscribe_for_transactions_with_addresses() {
     let client = TestClient::new_with_config(json!({
@@ -265,6 +379,7 @@ async fn subscribe_for_transactions_with_addresses() {
         }),
         signer: Signer::Keys { keys: keys.clone() },
         processing_try_index: None,
+        expiration: None,
         address: None,
         call_set: CallSet::some_with_function("constructor"),
     };
@@ -278,6 +393,9 @@ async fn subscribe_for_transactions_with_addresses() {
     let notifications = std::sync::Arc::new(Mutex::new(vec![]));
     let notifications_copy1 = notifications.clone();
     let notifications_copy2 = notifications.clone();
+    let events = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+    let events_copy1 = events.clone();
+    let events_copy2 = events.clone();
     let address1 = msg.address.clone();
     let address2 = msg.address.clone();
     println!("Account address {}", address1);
@@ -285,10 +403,15 @@ async fn subscribe_for_transactions_with_addresses() {
     let callback1 = move |result: serde_json::Value, response_type: SubscriptionResponseType| {
         let result = match response_type {
             SubscriptionResponseType::Ok => {
-                Ok(serde_json::from_value::<ResultOfSubscription>(result).unwrap())
+                Some(Ok(serde_json::from_value::<ResultOfSubscription>(result).unwrap()))
             }
             SubscriptionResponseType::Error => {
-                Err(serde_json::from_value::<ClientError>(result).unwrap())
+                Some(Err(serde_json::from_value::<ClientError>(result).unwrap()))
+            }
+            SubscriptionResponseType::Event => {
+                let event = serde_json::from_value::<SubscriptionEvent>(result).unwrap();
+                events_copy1.lock().unwrap().push(event);
+                None
             }
         };
         let address1 = address1.clone();
@@ -321,6 +444,7 @@ async fn subscribe_for_transactions_with_addresses() {
                     "status": { "eq": ton_sdk::json_helper::transaction_status_to_u8(ton_block::TransactionProcessingStatus::Finalized) }
                 })),
                 result: "id account_addr status".to_owned(),
+                ..Default::default()
             },
             callback1
         ).await.unwrap();
@@ -352,6 +476,10 @@ async fn subscribe_for_transactions_with_addresses() {
             ParamsOfProcessMessage {
                 message_encode_params: deploy_params,
                 send_events: false,
+                sequence: None,
+                decode_tree: None,
+                retry_policy: None,
+                ..Default::default()
             },
             TestClient::default_callback,
         )
@@ -362,10 +490,15 @@ async fn subscribe_for_transactions_with_addresses() {
     let callback2 = move |result: serde_json::Value, response_type: SubscriptionResponseType| {
         let result = match response_type {
             SubscriptionResponseType::Ok => {
-                Ok(serde_json::from_value::<ResultOfSubscription>(result).unwrap())
+                Some(Ok(serde_json::from_value::<ResultOfSubscription>(result).unwrap()))
             }
             SubscriptionResponseType::Error => {
-                Err(serde_json::from_value::<ClientError>(result).unwrap())
+                Some(Err(serde_json::from_value::<ClientError>(result).unwrap()))
+            }
+            SubscriptionResponseType::Event => {
+                let event = serde_json::from_value::<SubscriptionEvent>(result).unwrap();
+                events_copy2.lock().unwrap().push(event);
+                None
             }
         };
         let transactions_copy = transactions_copy2.clone();
@@ -398,6 +531,7 @@ async fn subscribe_for_transactions_with_addresses() {
                     "status": { "eq": ton_sdk::json_helper::transaction_status_to_u8(ton_block::TransactionProcessingStatus::Finalized) }
                 })),
                 result: "id account_addr status".to_owned(),
+                ..Default::default()
             },
             callback2
         ).await.unwrap();
@@ -430,10 +564,15 @@ async fn subscribe_for_transactions_with_addresses() {
                     deploy_set: None,
                     signer: Signer::Keys { keys },
                     processing_try_index: None,
+                    expiration: None,
                     address: Some(msg.address),
                     call_set: CallSet::some_with_function("touch"),
                 },
                 send_events: false,
+                sequence: None,
+                decode_tree: None,
+                retry_policy: None,
+                ..Default::default()
             },
             TestClient::default_callback,
         )
@@ -452,8 +591,14 @@ async fn subscribe_for_transactions_with_addresses() {
             .map(|x| x["id"].to_string())
             .collect::<Vec<String>>()
     );
-    assert_eq!(transactions.len(), 3);
-    assert_ne!(transactions[0]["id"], transactions[2]["id"]);
+    // the second transaction is missed by the first subscription while the network
+    // is suspended and replayed after resume
+    assert_eq!(transactions.len(), 4);
+    assert_eq!(
+        transactions.iter().map(|x| x["id"].to_string()).collect::<HashSet<_>>().len(),
+        3
+    );
+    assert!(transactions.iter().all(|x| x.get("lt").is_none()));
     // and both subscriptions received notification about resume
     let notifications = notifications.lock().await;
     assert_eq!(notifications.len(), 4);
@@ -461,6 +606,19 @@ async fn subscribe_for_transactions_with_addresses() {
     assert!(!notifications[2].data["query_url"].is_null());
     assert_eq!(notifications[3].code, Error::network_module_resumed().code);
     assert!(!notifications[3].data["query_url"].is_null());
+    // the first subscription has received the transaction before suspend, so the missed
+    // transactions are recovered, the second one has nothing to start from
+    let mut events = events.lock().unwrap().clone();
+    events.sort_by_key(|event| match event {
+        SubscriptionEvent::Resumed { gap_recovered } => !gap_recovered,
+    });
+    assert_eq!(
+        events,
+        vec![
+            SubscriptionEvent::Resumed { gap_recovered: true },
+            SubscriptionEvent::Resumed { gap_recovered: false },
+        ]
+    );
 
     let _: () = subscription_client
         .request_async("net.unsubscribe", handle1)
@@ -472,6 +630,34 @@ async fn subscribe_for_transactions_with_addresses() {
         .unwrap();
 }
 
+#[test]
+fn subscription_replay_cursor() {
+    use crate::net::subscription_replay::SubscriptionReplay;
+
+    let mut replay = SubscriptionReplay::new(
+        "transactions",
+        None,
+        "id account_addr in_message { created_lt }",
+    );
+    assert_eq!(replay.result(), "id account_addr in_message { created_lt } lt");
+
+    let item = json!({ "id": "1", "account_addr": "0:1", "lt": "0x10" });
+    assert!(replay.accept(&item));
+    assert!(!replay.accept(&item));
+    assert!(replay.accept(&json!({ "id": "2", "account_addr": "0:2", "lt": "0x10" })));
+    assert_eq!(
+        replay.strip(item),
+        json!({ "id": "1", "account_addr": "0:1" })
+    );
+
+    // unknown collections are not replayed
+    let mut replay = SubscriptionReplay::new("counterparties", None, "account");
+    assert_eq!(replay.result(), "account");
+    let item = json!({ "account": "0:1" });
+    assert!(replay.accept(&item));
+    assert!(replay.accept(&item));
+}
+
 #[tokio::test(core_threads = 2)]
 async fn subscribe_for_messages() {
     let messages = std::sync::Arc::new(Mutex::new(Vec::new()));
@@ -485,6 +671,7 @@ async fn subscribe_for_messages() {
             SubscriptionResponseType::Error => {
                 Err(serde_json::from_value::<ClientError>(result).unwrap())
             }
+            SubscriptionResponseType::Event => panic!("unexpected subscription event {}", result),
         }
         .unwrap();
         let messages_copy = messages_copy.clone();
@@ -504,6 +691,7 @@ async fn subscribe_for_messages() {
                     "dst": { "eq": "1" }
                 })),
                 result: "id".to_owned(),
+                ..Default::default()
             },
             callback,
         )
@@ -522,6 +710,69 @@ async fn subscribe_for_messages() {
         .unwrap();
 }
 
+#[tokio::test(core_threads = 2)]
+async fn subscribe_block_headers() {
+    let headers = std::sync::Arc::new(Mutex::new(Vec::new()));
+    let headers_copy = headers.clone();
+
+    let callback = move |result: serde_json::Value, response_type: SubscriptionResponseType| {
+        let result = match response_type {
+            SubscriptionResponseType::Ok => {
+                Ok(serde_json::from_value::<BlockHeader>(result).unwrap())
+            }
+            SubscriptionResponseType::Error => {
+                Err(serde_json::from_value::<ClientError>(result).unwrap())
+            }
+            SubscriptionResponseType::Event => panic!("unexpected subscription event {}", result),
+        }
+        .unwrap();
+        let headers_copy = headers_copy.clone();
+        async move {
+            headers_copy.lock().await.push(result);
+        }
+    };
+
+    let client = TestClient::new();
+
+    let handle: ResultOfSubscribeCollection = client
+        .request_async_callback(
+            "net.subscribe_block_headers",
+            ParamsOfSubscribeBlockHeaders {
+                filter: Some(json!({
+                    "workchain_id": { "eq": 0 }
+                })),
+                verify_proofs: None,
+            },
+            callback,
+        )
+        .await
+        .unwrap();
+
+    client
+        .get_tokens_from_giver_async(&client.giver_address().await, None)
+        .await;
+
+    let mut attempts = 0;
+    while headers.lock().await.is_empty() && attempts < 100 {
+        tokio::time::delay_for(std::time::Duration::from_millis(100)).await;
+        attempts += 1;
+    }
+
+    let _: () = client
+        .request_async("net.unsubscribe", handle)
+        .await
+        .unwrap();
+
+    let headers = headers.lock().await;
+    assert!(!headers.is_empty());
+    for header in headers.iter() {
+        assert_eq!(header.workchain_id, 0);
+        assert_eq!(header.id.len(), 64);
+        assert_eq!(header.file_hash.len(), 64);
+        assert!(!header.proof_verified);
+    }
+}
+
 #[tokio::test(core_threads = 2)]
 async fn find_last_shard_block() {
     let client = TestClient::new();
@@ -648,6 +899,71 @@ async fn query_block_id(client: &Arc<ClientContext>) -> String {
         .to_string()
 }
 
+#[tokio::test(core_threads = 2)]
+async fn query_collection_with_min_block_on_single_endpoint() {
+    let client = Arc::new(
+        ClientContext::new(ClientConfig {
+            network: NetworkConfig {
+                endpoints: Some(vec!["a".into(), "b".into()]),
+                max_latency: 1000,
+                // Unpinned query would check the latency and could switch the endpoint
+                latency_detection_interval: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap(),
+    );
+
+    let now = client.env.now_ms();
+    NetworkMock::build()
+        .url("a")
+        .delay(100)
+        .election(now, 500) // winner
+        .blocks("min") // waiting for `min_block`
+        .blocks("1") // query
+        .url("b")
+        .delay(200)
+        .election(now, 500) // looser, has no blocks
+        .reset_client(&client)
+        .await;
+
+    let result = crate::net::query_collection(
+        client.clone(),
+        ParamsOfQueryCollection {
+            collection: "blocks".to_string(),
+            result: "id".to_string(),
+            min_block: Some("min".to_string()),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(result.result[0]["id"], "1");
+}
+
+#[tokio::test(core_threads = 2)]
+async fn consistency_token_of_derived_context() {
+    let client = Arc::new(
+        ClientContext::new(ClientConfig {
+            network: NetworkConfig {
+                endpoints: Some(vec!["a".into()]),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap(),
+    );
+    let derived = Arc::new(client.derive(client.config.clone(), None).unwrap());
+
+    *client.last_sent_block.write().await = Some("sent".to_string());
+
+    let token = get_consistency_token(client.clone()).await.unwrap();
+    assert_eq!(token.min_block, Some("sent".to_string()));
+    let token = get_consistency_token(derived.clone()).await.unwrap();
+    assert_eq!(token.min_block, None);
+}
+
 async fn get_query_url(client: &Arc<ClientContext>) -> String {
     let mut url = client
         .get_server_link()
@@ -734,6 +1050,87 @@ async fn retry_query_on_network_errors() {
     assert_eq!(query_block_id(&client).await, "4");
 }
 
+#[tokio::test(core_threads = 2)]
+async fn query_partial_result() {
+    let client = Arc::new(
+        ClientContext::new(ClientConfig {
+            network: NetworkConfig {
+                endpoints: Some(vec!["a".into()]),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap(),
+    );
+
+    let response = json!({
+        "errors": [
+            {
+                "message": "Counterparties are not available",
+                "path": ["counterparties"],
+                "extensions": {
+                    "code": "INTERNAL_SERVER_ERROR",
+                    "exception": {
+                        "code": 400,
+                        "exchange": "accounts",
+                    }
+                }
+            }
+        ],
+        "data": {
+            "blocks": [{ "id": "1" }],
+            "counterparties": null,
+        }
+    })
+    .to_string();
+    let query = "query{blocks{id} counterparties(account:\"1\"){counterparty}}";
+
+    let now = client.env.now_ms();
+    NetworkMock::build()
+        .url("a")
+        .election(now, 1000)
+        .ok(&response)
+        .ok(&response)
+        .reset_client(&client)
+        .await;
+
+    let error = crate::net::query(
+        client.clone(),
+        ParamsOfQuery {
+            query: query.to_owned(),
+            variables: None,
+            allow_partial_result: None,
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error.data["server_code"], 400);
+    assert_eq!(error.data["graphql_code"], "INTERNAL_SERVER_ERROR");
+    assert_eq!(error.data["exchange"], "accounts");
+
+    let result = crate::net::query(
+        client.clone(),
+        ParamsOfQuery {
+            query: query.to_owned(),
+            variables: None,
+            allow_partial_result: Some(true),
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(result.result["data"]["blocks"][0]["id"], "1");
+    assert_eq!(
+        result.warnings,
+        Some(vec![GraphQLWarning {
+            message: "Counterparties are not available".to_owned(),
+            path: Some(json!(["counterparties"])),
+            code: Some("INTERNAL_SERVER_ERROR".to_owned()),
+            server_code: Some(400),
+            exchange: Some(json!("accounts")),
+        }])
+    );
+}
+
 #[tokio::test(core_threads = 2)]
 async fn querying_endpoint_selection() {
     let client = Arc::new(
@@ -936,6 +1333,7 @@ async fn latency_detection_with_websockets() {
             collection: "blocks".to_string(),
             filter: None,
             result: "id".to_string(),
+            ..Default::default()
         },
         |_| async {},
     )
@@ -1038,6 +1436,8 @@ async fn transaction_tree() {
                 .to_string(),
                 limit: None,
                 order: None,
+                min_block: None,
+                decode_body: None,
             },
         )
         .await
@@ -1088,6 +1488,40 @@ async fn transaction_tree() {
         }
     }
     assert!(has_decoded_bodies);
+
+    // retrieval limited by the depth reports the retrieved nodes via events
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let events_copy = events.clone();
+    let callback = move |event: TransactionTreeNodeEvent, response_type: u32| {
+        assert_eq!(response_type, TransactionTreeResponseType::TransactionTreeNode as u32);
+        let events = events_copy.clone();
+        async move {
+            events.lock().await.push(event);
+        }
+    };
+    let result: ResultOfQueryTransactionTree = client
+        .request_async_callback(
+            "net.query_transaction_tree",
+            ParamsOfQueryTransactionTree {
+                in_msg: messages.result[0]["id"].as_str().unwrap().to_string(),
+                max_depth: Some(0),
+                send_events: Some(true),
+                ..Default::default()
+            },
+            callback,
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.messages.len(), 1);
+    assert_eq!(result.transactions.len(), 1);
+    let events = events.lock().await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].depth, 0);
+    assert_eq!(events[0].message, result.messages[0]);
+    assert_eq!(
+        events[0].transaction.as_ref().map(|transaction| &transaction.id),
+        Some(&result.transactions[0].id),
+    );
 }
 
 #[tokio::test(core_threads = 2)]
@@ -1248,6 +1682,7 @@ async fn low_level_subscribe() {
             SubscriptionResponseType::Error => {
                 Err(serde_json::from_value::<ClientError>(result).unwrap())
             }
+            SubscriptionResponseType::Event => panic!("unexpected subscription event {}", result),
         }
         .unwrap();
         let messages_copy = messages_copy.clone();
@@ -1273,6 +1708,7 @@ async fn low_level_subscribe() {
                 variables: Some(json!({
                     "dst": client.giver_address().await,
                 })),
+                ..Default::default()
             },
             callback,
         )
@@ -1290,3 +1726,958 @@ async fn low_level_subscribe() {
         .await
         .unwrap();
 }
+
+#[test]
+fn test_subscription_mapping() {
+    let mapping: crate::net::SubscriptionMapping = serde_json::from_value(json!({
+        "predicates": [
+            { "path": "workchain_id", "operator": "Eq", "value": -1 },
+            { "path": "seq_no", "operator": "Ge", "value": 100 },
+            { "path": "master.shard_hashes.0.shard", "operator": "Exists" },
+        ],
+        "projection": {
+            "seq_no": "seq_no",
+            "first_shard": "master.shard_hashes.0.shard",
+            "missing": "master.unknown",
+        }
+    }))
+    .unwrap();
+
+    let block = json!({
+        "id": "abc",
+        "workchain_id": -1,
+        "seq_no": 100,
+        "master": { "shard_hashes": [{ "shard": "8000000000000000" }] },
+    });
+    assert_eq!(
+        mapping.apply(block.clone()),
+        Some(json!({
+            "seq_no": 100,
+            "first_shard": "8000000000000000",
+            "missing": null,
+        }))
+    );
+
+    let mut shard_block = block.clone();
+    shard_block["workchain_id"] = json!(0);
+    assert_eq!(mapping.apply(shard_block), None);
+
+    let mut old_block = block;
+    old_block["seq_no"] = json!(99);
+    assert_eq!(mapping.apply(old_block), None);
+}
+
+#[test]
+fn test_sse_parser() {
+    use super::sse::{SseEvent, SseParser};
+
+    let mut parser = SseParser::default();
+    assert_eq!(parser.push(b":\n\nevent: ne"), vec![SseEvent::Comment]);
+    assert_eq!(
+        parser.push(b"xt\r\ndata: {\"data\":\ndata:{}}\n\n"),
+        vec![SseEvent::Event {
+            event: "next".to_string(),
+            data: "{\"data\":\n{}}".to_string(),
+        }],
+    );
+    assert_eq!(
+        parser.push(b"event: complete\ndata:\n\n"),
+        vec![SseEvent::Event {
+            event: "complete".to_string(),
+            data: String::new(),
+        }],
+    );
+    assert_eq!(
+        parser.push(b"data: x\n\n"),
+        vec![SseEvent::Event {
+            event: "message".to_string(),
+            data: "x".to_string(),
+        }],
+    );
+}
+
+fn test_schema() -> Value {
+    let scalar = |name: &str| json!({ "kind": "SCALAR", "name": name, "ofType": null });
+    let input = |name: &str| json!({ "kind": "INPUT_OBJECT", "name": name, "ofType": null });
+    let field = |name: &str, field_type: Value| json!({ "name": name, "args": [], "type": field_type });
+    json!({
+        "queryType": { "name": "Query" },
+        "types": [
+            {
+                "kind": "OBJECT",
+                "name": "Query",
+                "fields": [{
+                    "name": "transactions",
+                    "args": [{ "name": "filter", "type": input("TransactionFilter") }],
+                    "type": {
+                        "kind": "LIST",
+                        "name": null,
+                        "ofType": { "kind": "OBJECT", "name": "Transaction", "ofType": null },
+                    },
+                }],
+            },
+            {
+                "kind": "OBJECT",
+                "name": "Transaction",
+                "fields": [
+                    field("id", json!({ "kind": "NON_NULL", "name": null, "ofType": scalar("String") })),
+                    field("lt", scalar("String")),
+                    field("in_message", json!({ "kind": "OBJECT", "name": "Message", "ofType": null })),
+                ],
+            },
+            {
+                "kind": "OBJECT",
+                "name": "Message",
+                "fields": [field("id", scalar("String")), field("value", scalar("String"))],
+            },
+            {
+                "kind": "INPUT_OBJECT",
+                "name": "TransactionFilter",
+                "inputFields": [
+                    field("id", input("StringFilter")),
+                    field("in_message", input("MessageFilter")),
+                    field("OR", input("TransactionFilter")),
+                ],
+            },
+            {
+                "kind": "INPUT_OBJECT",
+                "name": "MessageFilter",
+                "inputFields": [field("id", input("StringFilter"))],
+            },
+            {
+                "kind": "INPUT_OBJECT",
+                "name": "StringFilter",
+                "inputFields": [
+                    field("eq", scalar("String")),
+                    field("in", json!({ "kind": "LIST", "name": null, "ofType": scalar("String") })),
+                ],
+            },
+            scalar("String"),
+        ],
+    })
+}
+
+#[test]
+fn collection_schema_validation() {
+    let schema = super::schema::GraphQLSchema::from_introspection(&test_schema()).unwrap();
+
+    let collection = schema.get_collection_schema("transactions").unwrap();
+    assert_eq!(collection.type_name, "Transaction");
+    assert_eq!(collection.filter_type_name, "TransactionFilter");
+    assert_eq!(
+        collection.types.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(),
+        vec!["Transaction", "TransactionFilter", "Message", "StringFilter", "MessageFilter"],
+    );
+    let err = schema.get_collection_schema("blocks").unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidQuery as u32);
+
+    schema
+        .validate_result("transactions", "id lt(format: DEC) tx: id in_message { id, value } __typename")
+        .unwrap();
+    let err = schema.validate_result("transactions", "id in_message { id srcx }").unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidQuery as u32);
+    assert_eq!(err.data["param"], "result");
+    assert_eq!(err.data["path"], "in_message.srcx");
+    assert_eq!(err.data["position"], 19);
+    let err = schema.validate_result("transactions", "id in_message").unwrap_err();
+    assert_eq!(err.data["path"], "in_message");
+    let err = schema.validate_result("transactions", "id { id }").unwrap_err();
+    assert_eq!(err.data["path"], "id");
+    assert!(schema.validate_result("transactions", "in_message { id").is_err());
+
+    schema
+        .validate_filter(
+            "transactions",
+            &json!({
+                "id": { "eq": "1" },
+                "OR": { "in_message": { "id": { "in": ["1", "2"] } } },
+            }),
+        )
+        .unwrap();
+    let err = schema
+        .validate_filter("transactions", &json!({ "in_message": { "src": { "eq": "1" } } }))
+        .unwrap_err();
+    assert_eq!(err.data["param"], "filter");
+    assert_eq!(err.data["path"], "in_message.src");
+    assert_eq!(err.data["position"], Value::Null);
+
+    schema.validate_field_path("transactions", "order", "in_message.value").unwrap();
+    let err = schema
+        .validate_field_path("transactions", "order", "balance")
+        .unwrap_err();
+    assert_eq!(err.data["path"], "balance");
+}
+
+#[tokio::test(core_threads = 2)]
+async fn validate_queries() {
+    let client = Arc::new(
+        ClientContext::new(ClientConfig {
+            network: NetworkConfig {
+                endpoints: Some(vec!["a".into()]),
+                validate_queries: Some(true),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap(),
+    );
+
+    let now = client.env.now_ms();
+    NetworkMock::build()
+        .url("a")
+        .election(now, 1000)
+        .ok(&json!({ "data": { "__schema": test_schema() } }).to_string())
+        .reset_client(&client)
+        .await;
+
+    let err = crate::net::query_collection(
+        client.clone(),
+        ParamsOfQueryCollection {
+            collection: "transactions".to_owned(),
+            filter: Some(json!({ "id": { "eq": "1" } })),
+            result: "id in_message { srcx }".to_owned(),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidQuery as u32);
+    assert_eq!(err.data["path"], "in_message.srcx");
+    assert_eq!(err.data["position"], 16);
+
+    // Schema is cached, so the invalid query is not sent and no more requests are made
+    let schema = crate::net::get_collection_schema(
+        client.clone(),
+        ParamsOfGetCollectionSchema {
+            collection: "transactions".to_owned(),
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(schema.type_name, "Transaction");
+    assert_eq!(NetworkMock::get_len(&client).await, 0);
+}
+
+#[tokio::test(core_threads = 2)]
+async fn decode_message_bodies() {
+    let abi = crate::abi::Abi::Json(
+        json!({
+            "ABI version": 2,
+            "version": "2.2",
+            "functions": [{ "name": "ping", "inputs": [], "outputs": [] }],
+            "events": [],
+        })
+        .to_string(),
+    );
+    let new_client = || {
+        Arc::new(
+            ClientContext::new(ClientConfig {
+                network: NetworkConfig {
+                    endpoints: Some(vec!["a".into()]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+    };
+    let client = new_client();
+    let body = crate::abi::encode_message_body(
+        client.clone(),
+        crate::abi::ParamsOfEncodeMessageBody {
+            abi: abi.clone(),
+            call_set: CallSet {
+                function_name: "ping".to_owned(),
+                ..Default::default()
+            },
+            is_internal: true,
+            signer: Signer::None,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap()
+    .body;
+    let messages = |decoded_body: Value| {
+        json!({ "data": { "messages": [
+            { "id": "1", "body": body, "msg_type": 0, "decoded_body": decoded_body },
+            { "id": "2", "body": null, "msg_type": 1 },
+        ] } })
+        .to_string()
+    };
+    let params = |verify_decoding| ParamsOfQueryCollection {
+        collection: "messages".to_owned(),
+        result: "id".to_owned(),
+        decode_body: Some(BodyDecoding {
+            abi: abi.clone(),
+            verify_decoding,
+        }),
+        ..Default::default()
+    };
+
+    // Endpoint schema has no decoded bodies, so they are decoded locally
+    let now = client.env.now_ms();
+    NetworkMock::build()
+        .url("a")
+        .election(now, 1000)
+        .ok(&json!({ "data": { "__schema": test_schema() } }).to_string())
+        .ok(&messages(Value::Null))
+        .reset_client(&client)
+        .await;
+    let result = crate::net::query_collection(client.clone(), params(None))
+        .await
+        .unwrap()
+        .result;
+    assert_eq!(result[0]["decoded_body"]["name"], "ping");
+    assert_eq!(result[1]["decoded_body"], Value::Null);
+
+    let err = crate::net::query_collection(
+        client.clone(),
+        ParamsOfQueryCollection {
+            collection: "transactions".to_owned(),
+            ..params(None)
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidQuery as u32);
+
+    // Endpoint decodes the bodies, the decoding is verified locally
+    let mut schema = test_schema();
+    let object = |name: &str| json!({ "kind": "OBJECT", "name": name, "ofType": null });
+    schema["types"][0]["fields"].as_array_mut().unwrap().push(json!({
+        "name": "messages",
+        "args": [{ "name": "filter", "type": { "kind": "INPUT_OBJECT", "name": "MessageFilter" } }],
+        "type": { "kind": "LIST", "name": null, "ofType": object("Message") },
+    }));
+    schema["types"][2]["fields"].as_array_mut().unwrap().push(json!({
+        "name": "decoded_body",
+        "args": [],
+        "type": object("DecodedBody"),
+    }));
+    let client = new_client();
+    NetworkMock::build()
+        .url("a")
+        .election(now, 1000)
+        .ok(&json!({ "data": { "__schema": schema } }).to_string())
+        .ok(&messages(json!({ "body_type": "Input", "name": "pong", "value": {} })))
+        .ok(&messages(json!({ "body_type": "Input", "name": "pong", "value": {} })))
+        .reset_client(&client)
+        .await;
+    let result = crate::net::query_collection(client.clone(), params(None))
+        .await
+        .unwrap()
+        .result;
+    assert_eq!(result[0]["decoded_body"]["name"], "pong");
+    let err = crate::net::query_collection(client.clone(), params(Some(true)))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::DecodedBodyMismatch as u32);
+    assert_eq!(err.data["message_id"], "1");
+    assert_eq!(err.data["local_decoded"]["name"], "ping");
+}
+
+#[tokio::test(core_threads = 2)]
+async fn quorum_reads() {
+    let client = Arc::new(
+        ClientContext::new(ClientConfig {
+            network: NetworkConfig {
+                endpoints: Some(vec!["a".to_string(), "b".to_string()]),
+                quorum_endpoints_count: Some(2),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap(),
+    );
+    let accounts = |boc: &str| json!({ "data": { "accounts": [{ "boc": boc }] } }).to_string();
+    let params = ParamsOfQueryCollection {
+        collection: "accounts".to_owned(),
+        filter: Some(json!({ "id": { "eq": "0:1" } })),
+        result: "boc".to_owned(),
+        limit: Some(1),
+        ..Default::default()
+    };
+
+    let now = client.env.now_ms();
+    NetworkMock::build()
+        .url("a")
+        .election(now, 1000)
+        .ok(&accounts("te6cc1"))
+        .url("b")
+        .election(now, 1000)
+        .ok(&accounts("te6cc1"))
+        .reset_client(&client)
+        .await;
+    let result = crate::net::quorum::quorum_query_collection(&client, params.clone())
+        .await
+        .unwrap();
+    assert_eq!(result.result, vec![json!({ "boc": "te6cc1" })]);
+    assert_eq!(NetworkMock::get_len(&client).await, 0);
+
+    NetworkMock::build()
+        .url("a")
+        .election(now, 1000)
+        .ok(&accounts("te6cc1"))
+        .url("b")
+        .election(now, 1000)
+        .ok(&accounts("te6cc2"))
+        .reset_client(&client)
+        .await;
+    let err = crate::net::quorum::quorum_query_collection(&client, params.clone())
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::ConflictingEndpointData as u32);
+    assert_eq!(err.data["endpoints"], json!(["a", "b"]));
+
+    let client = Arc::new(
+        ClientContext::new(ClientConfig {
+            network: NetworkConfig {
+                endpoints: Some(vec!["a".to_string()]),
+                quorum_endpoints_count: Some(2),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap(),
+    );
+    let err = crate::net::quorum::quorum_query_collection(&client, params)
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, crate::client::ErrorCode::InvalidConfig as u32);
+}
+
+#[test]
+fn archive_routing() {
+    use crate::net::archive::{filter_min_time, is_archive_operation, is_archive_time};
+
+    assert_eq!(filter_min_time(&json!({ "gen_utime": { "gt": 200, "lt": 300 } })), Some(200));
+    assert_eq!(
+        filter_min_time(&json!({
+            "workchain_id": { "eq": -1 },
+            "OR": { "now": { "in": [500, "100"] } },
+        })),
+        Some(100)
+    );
+    assert_eq!(
+        filter_min_time(&json!({ "in_message": { "created_at": { "ge": 50 } } })),
+        Some(50)
+    );
+    assert_eq!(filter_min_time(&json!({ "seq_no": { "lt": 50 } })), None);
+
+    let now_ms = 1_000_000 * 1000;
+    let config = NetworkConfig {
+        archive_endpoints: Some(vec!["archive".to_string()]),
+        archive_horizon: Some(1000),
+        ..Default::default()
+    };
+    assert!(is_archive_time(&config, now_ms, Some(998_999)));
+    assert!(!is_archive_time(&config, now_ms, Some(999_000)));
+    assert!(is_archive_time(&config, now_ms, None));
+    assert!(!is_archive_time(&NetworkConfig::default(), now_ms, None));
+
+    let operation = |filter: Value| {
+        ParamsOfQueryOperation::QueryCollection(ParamsOfQueryCollection {
+            collection: "transactions".to_owned(),
+            filter: Some(filter),
+            result: "id".to_owned(),
+            ..Default::default()
+        })
+    };
+    assert!(is_archive_operation(
+        &config,
+        now_ms,
+        &operation(json!({ "now": { "gt": 100 } }))
+    ));
+    assert!(!is_archive_operation(
+        &config,
+        now_ms,
+        &operation(json!({ "now": { "gt": 999_500 } }))
+    ));
+    assert!(!is_archive_operation(
+        &config,
+        now_ms,
+        &operation(json!({ "account_addr": { "eq": "0:00" } }))
+    ));
+}
+
+#[tokio::test(core_threads = 2)]
+async fn blockchain_pagination() {
+    let client = Arc::new(
+        ClientContext::new(ClientConfig {
+            network: NetworkConfig {
+                endpoints: Some(vec!["a".into()]),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap(),
+    );
+    let page = |ids: std::ops::Range<u32>, has_next_page: bool| {
+        let edges: Vec<Value> = ids
+            .map(|id| json!({ "node": { "id": id.to_string() }, "cursor": format!("c{}", id) }))
+            .collect();
+        let end_cursor = edges.last().map(|edge| edge["cursor"].clone());
+        json!({
+            "data": {
+                "blockchain": {
+                    "key_blocks": {
+                        "edges": edges,
+                        "pageInfo": {
+                            "hasNextPage": has_next_page,
+                            "endCursor": end_cursor,
+                        },
+                    }
+                }
+            }
+        })
+        .to_string()
+    };
+
+    let now = client.env.now_ms();
+    NetworkMock::build()
+        .url("a")
+        .election(now, 1000)
+        .ok(&page(0..50, true))
+        .ok(&page(50..60, false))
+        .reset_client(&client)
+        .await;
+
+    let result = crate::net::blockchain_key_blocks(
+        client.clone(),
+        crate::net::ParamsOfBlockchainKeyBlocks {
+            limit: Some(70),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(result.items.len(), 60);
+    assert_eq!(result.items[59]["id"], "59");
+    assert_eq!(result.cursor.as_deref(), Some("c59"));
+    assert!(!result.has_more);
+    assert_eq!(NetworkMock::get_len(&client).await, 0);
+}
+
+#[test]
+fn network_clock() {
+    let clock = network_time::NetworkClock::default();
+    assert_eq!(clock.offset(), 0);
+
+    // small deltas are ignored, the local clock is trusted
+    clock.set_endpoint_offset(-1000, 15000);
+    assert_eq!(clock.offset(), 0);
+    clock.set_endpoint_offset(-120000, 15000);
+    assert_eq!(clock.offset(), -120000);
+
+    // observed server time corrects the clock by the median of the recent observations
+    clock.observe_server_time(1_000_000, 1_100_000);
+    assert_eq!(clock.offset(), -100000);
+    clock.observe_server_time(1_000_000, 1_200_000);
+    assert_eq!(clock.offset(), -120000);
+    clock.observe_server_time(1_300_000, 1_000_000);
+    assert_eq!(clock.offset(), -100000);
+    clock.observe_server_time(1_300_000, 1_000_000);
+    assert_eq!(clock.offset(), -100000);
+    clock.observe_server_time(1_300_000, 1_000_000);
+    assert_eq!(clock.offset(), 300000);
+}
+
+#[test]
+fn network_clock_outlier() {
+    let clock = network_time::NetworkClock::default();
+
+    // a single bogus timestamp is clamped to the maximal skew
+    clock.observe_server_time(1_000_000 + 86_400_000, 1_000_000);
+    assert_eq!(clock.offset(), 600_000);
+
+    // and doesn't stick after the next observations
+    clock.observe_server_time(1_001_000, 1_000_000);
+    clock.observe_server_time(1_002_000, 1_000_000);
+    assert_eq!(clock.offset(), 2000);
+    clock.observe_server_time(1_000_000 + 86_400_000, 1_000_000);
+    assert_eq!(clock.offset(), 2000);
+    for _ in 0..5 {
+        clock.observe_server_time(1_001_000, 1_000_000);
+    }
+    assert_eq!(clock.offset(), 1000);
+}
+
+#[tokio::test(core_threads = 2)]
+async fn query_deduplication() {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    let dedup = query_dedup::QueryDeduplicator::new();
+    let runs = AtomicU32::new(0);
+    let query = |value: u32| {
+        let runs = &runs;
+        move || async move {
+            runs.fetch_add(1, Ordering::SeqCst);
+            tokio::time::delay_for(std::time::Duration::from_millis(100)).await;
+            Ok(json!(value))
+        }
+    };
+
+    // identical queries issued concurrently share the single request
+    let (first, second) = futures::join!(
+        dedup.run("a".to_string(), query(1)),
+        dedup.run("a".to_string(), query(2)),
+    );
+    assert_eq!(first.unwrap(), json!(1));
+    assert_eq!(second.unwrap(), json!(1));
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+    assert_eq!(dedup.in_flight_count(), 0);
+
+    // different queries are not coalesced
+    let (first, second) = futures::join!(
+        dedup.run("a".to_string(), query(1)),
+        dedup.run("b".to_string(), query(2)),
+    );
+    assert_eq!(first.unwrap(), json!(1));
+    assert_eq!(second.unwrap(), json!(2));
+    assert_eq!(runs.load(Ordering::SeqCst), 3);
+
+    // waiter runs the query by itself if the leading request is dropped
+    let leader = tokio::time::timeout(
+        std::time::Duration::from_millis(50),
+        dedup.run("a".to_string(), query(1)),
+    );
+    let (leader, waiter) = futures::join!(leader, async {
+        tokio::time::delay_for(std::time::Duration::from_millis(10)).await;
+        dedup.run("a".to_string(), query(2)).await
+    });
+    assert!(leader.is_err());
+    assert_eq!(waiter.unwrap(), json!(2));
+    assert_eq!(runs.load(Ordering::SeqCst), 5);
+    assert_eq!(dedup.in_flight_count(), 0);
+}
+
+#[test]
+fn query_cache() {
+    let cache = query_cache::QueryCache::new(2, 1000);
+    let params = |collection: &str, filter: Value| ParamsOfQueryCollection {
+        collection: collection.to_string(),
+        filter: Some(filter),
+        ..Default::default()
+    };
+    assert!(cache.is_cacheable(&params("blocks", json!({ "id": { "eq": "1" } }))));
+    assert!(cache.is_cacheable(&params("transactions", json!({ "id": { "in": ["1", "2"] } }))));
+    assert!(!cache.is_cacheable(&params("accounts", json!({ "id": { "eq": "1" } }))));
+    // range and paginated queries are not cached, new items can match them
+    assert!(!cache.is_cacheable(&params("blocks", json!({ "seq_no": { "gt": 1 } }))));
+    assert!(!cache.is_cacheable(&params(
+        "messages",
+        json!({ "id": { "eq": "1" }, "created_at": { "gt": 1 } }),
+    )));
+    assert!(!cache.is_cacheable(&ParamsOfQueryCollection {
+        collection: "blocks".to_string(),
+        ..Default::default()
+    }));
+
+    // empty results are not cached, the items can be produced later
+    cache.put("a".to_string(), "blocks", &json!([]), 0);
+    assert_eq!(cache.get("a", 0), None);
+
+    // non-finalized items can change
+    cache.put("a".to_string(), "blocks", &json!([{ "id": "1", "status": 1 }]), 0);
+    assert_eq!(cache.get("a", 0), None);
+    cache.put("a".to_string(), "blocks", &json!([{ "id": "1" }]), 0);
+    assert_eq!(cache.get("a", 0), None);
+    cache.put(
+        "a".to_string(),
+        "messages",
+        &json!([{ "id": "1", "status": 5 }, { "id": "2", "status": 2 }]),
+        0,
+    );
+    assert_eq!(cache.get("a", 0), None);
+
+    cache.put("a".to_string(), "blocks", &json!([{ "id": "1", "status": 2 }]), 0);
+    assert_eq!(cache.get("a", 999), Some(json!([{ "id": "1", "status": 2 }])));
+    assert_eq!(cache.get("a", 1000), None);
+    assert_eq!(cache.len(), 0);
+
+    // least recently used result is dropped when the cache is full
+    let finalized = |id: u32| json!([{ "id": id.to_string(), "status": 3 }]);
+    cache.put("a".to_string(), "transactions", &finalized(1), 0);
+    cache.put("b".to_string(), "transactions", &finalized(2), 0);
+    cache.get("a", 0);
+    cache.put("c".to_string(), "transactions", &finalized(3), 0);
+    assert_eq!(cache.get("b", 0), None);
+    assert_eq!(cache.get("a", 0), Some(finalized(1)));
+    assert_eq!(cache.get("c", 0), Some(finalized(3)));
+
+    let disabled = query_cache::QueryCache::new(0, 1000);
+    assert!(!disabled.is_cacheable(&params("blocks", json!({ "id": { "eq": "1" } }))));
+}
+
+#[tokio::test(core_threads = 2)]
+async fn query_cache_of_immutable_collections() {
+    let client = Arc::new(
+        ClientContext::new(ClientConfig {
+            network: NetworkConfig {
+                endpoints: Some(vec!["a".into()]),
+                query_cache_size: Some(10),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap(),
+    );
+    NetworkMock::build()
+        .url("a")
+        .election(client.env.now_ms(), 1000)
+        .ok(&json!({ "data": { "blocks": [{ "id": "1", "status": 2 }] } }).to_string())
+        .ok(&json!({ "data": { "accounts": [{ "id": "2" }] } }).to_string())
+        .ok(&json!({ "data": { "accounts": [{ "id": "2" }] } }).to_string())
+        .ok(&json!({ "data": { "messages": [{ "id": "3", "status": 2 }] } }).to_string())
+        .ok(&json!({ "data": { "messages": [{ "id": "3", "status": 5 }] } }).to_string())
+        .reset_client(&client)
+        .await;
+
+    let ids = ["1", "1", "2", "2", "3", "3", "3"];
+    let collections = ["blocks", "blocks", "accounts", "accounts", "messages", "messages", "messages"];
+    for (id, collection) in ids.iter().zip(collections.iter()) {
+        let result = query_collection(
+            client.clone(),
+            ParamsOfQueryCollection {
+                collection: collection.to_string(),
+                filter: Some(json!({ "id": { "eq": id } })),
+                result: "id status".to_string(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.result.len(), 1);
+    }
+    // the repeated query of the finalized block is served from the cache, the non-final
+    // message is fetched again until it is finalized
+    assert_eq!(NetworkMock::get_len(&client).await, 0);
+}
+
+#[tokio::test(core_threads = 2)]
+async fn jrpc_queries() {
+    let client = Arc::new(
+        ClientContext::new(ClientConfig {
+            network: NetworkConfig {
+                endpoints: Some(vec!["a/jrpc".into()]),
+                queries_protocol: Some(NetworkQueriesProtocol::Jrpc),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap(),
+    );
+
+    NetworkMock::build()
+        .url("a/jrpc")
+        .ok(&json!({ "jsonrpc": "2.0", "id": 1, "result": [{ "id": "1" }] }).to_string())
+        .ok(&json!({ "jsonrpc": "2.0", "id": 1, "result": { "id": "2" } }).to_string())
+        .ok(&json!({ "jsonrpc": "2.0", "id": 1, "result": null }).to_string())
+        .ok(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "error": { "code": -32602, "message": "Invalid collection", "data": "foo" },
+        })
+        .to_string())
+        .reset_client(&client)
+        .await;
+
+    let result = crate::net::query_collection(
+        client.clone(),
+        ParamsOfQueryCollection {
+            collection: "blocks".to_owned(),
+            result: "id".to_owned(),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(result.result, vec![json!({ "id": "1" })]);
+
+    let wait_for_params = ParamsOfWaitForCollection {
+        collection: "blocks".to_owned(),
+        result: "id".to_owned(),
+        timeout: Some(1000),
+        ..Default::default()
+    };
+    let result = crate::net::wait_for_collection(client.clone(), wait_for_params.clone())
+        .await
+        .unwrap();
+    assert_eq!(result.result, json!({ "id": "2" }));
+
+    let error = crate::net::wait_for_collection(client.clone(), wait_for_params)
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::WaitForFailed as u32);
+
+    let error = crate::net::query_collection(
+        client.clone(),
+        ParamsOfQueryCollection {
+            collection: "foo".to_owned(),
+            result: "id".to_owned(),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(error.message.contains("JRPC server error: Invalid collection"));
+    assert_eq!(error.data["server_code"], -32602);
+    assert_eq!(error.data["server_data"], "foo");
+
+    let error = crate::net::aggregate_collection(
+        client.clone(),
+        ParamsOfAggregateCollection {
+            collection: "blocks".to_owned(),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(error.message.contains("aggregate_collection over JRPC"));
+}
+
+#[test]
+fn endpoint_health_scoring() {
+    use crate::net::endpoint_stats::EndpointStatsCollector;
+
+    let stats = EndpointStatsCollector::default();
+    let network_error = crate::client::Error::http_request_send_error("Network error");
+    let query_error = Error::queries_query_failed("Invalid filter");
+    let addresses = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+
+    stats.record("a", 300, 1000, None);
+    stats.record("b", 100, 1000, None);
+    stats.record("c", 50, 1000, Some(&query_error));
+    assert_eq!(stats.prefer_healthy(addresses.clone(), 1000), vec!["c", "b", "a"]);
+
+    // endpoint is excluded after the sequential network failures
+    let trace_id = Some("1".to_string());
+    for _ in 0..3 {
+        crate::client::trace::with_trace_id(trace_id.as_ref(), || {
+            stats.record("c", 10, 2000, Some(&network_error))
+        });
+    }
+    assert_eq!(stats.prefer_healthy(addresses.clone(), 2000), vec!["b", "a"]);
+    // and is tried again after the recovery time
+    assert_eq!(stats.prefer_healthy(addresses.clone(), 62000), vec!["b", "a", "c"]);
+
+    let c = stats.get_stats(&addresses, Some("b"), 2000).remove(2);
+    assert!(!c.healthy);
+    assert!(!c.current);
+    assert_eq!(c.requests_count, 4);
+    assert_eq!(c.failures_count, 3);
+    assert_eq!(c.consecutive_failures, 3);
+    assert_eq!(c.average_response_time, Some(50));
+    assert_eq!(c.last_error, Some(network_error.message.clone()));
+    assert_eq!(c.last_error_trace_id, trace_id);
+
+    // all unhealthy endpoints are still used
+    for address in &["a", "b"] {
+        for _ in 0..3 {
+            stats.record(address, 10, 2000, Some(&network_error));
+        }
+    }
+    assert_eq!(stats.prefer_healthy(addresses.clone(), 2000).len(), 3);
+}
+
+#[tokio::test(core_threads = 2)]
+async fn endpoint_stats_persistence() {
+    use crate::client::storage::{InMemoryKeyValueStorage, KeyValueStorage};
+    use crate::net::endpoint_stats::{EndpointStatsCollector, StatsStorage};
+
+    let storage: Arc<dyn KeyValueStorage> = Arc::new(InMemoryKeyValueStorage::new());
+    let network_error = crate::client::Error::http_request_send_error("Network error");
+    let addresses = vec!["a".to_string(), "b".to_string()];
+
+    let stats = EndpointStatsCollector::with_storage(StatsStorage::Opened(storage.clone()));
+    stats.load().await;
+    stats.record("a", 100, 1000, None);
+    for _ in 0..3 {
+        stats.record("b", 10, 1000, Some(&network_error));
+    }
+    stats.save().await;
+
+    // the next session doesn't try the failed endpoint once the stats are loaded
+    let stats = EndpointStatsCollector::with_storage(StatsStorage::Opened(storage.clone()));
+    assert_eq!(stats.prefer_healthy(addresses.clone(), 2000), vec!["a", "b"]);
+    stats.load().await;
+    assert_eq!(stats.prefer_healthy(addresses.clone(), 2000), vec!["a"]);
+    let persisted = stats.get_stats(&addresses, None, 2000);
+    assert_eq!(persisted[1].consecutive_failures, 3);
+    assert_eq!(persisted[1].last_error, Some(network_error.message.clone()));
+
+    stats.reset().await.unwrap();
+    assert_eq!(storage.get_str("endpoint_stats").await.unwrap(), None);
+    let stats = EndpointStatsCollector::with_storage(StatsStorage::Opened(storage.clone()));
+    stats.load().await;
+    assert_eq!(stats.get_stats(&addresses, None, 2000)[1].consecutive_failures, 0);
+}
+
+#[tokio::test(core_threads = 2)]
+async fn endpoint_stats() {
+    let client = Arc::new(
+        ClientContext::new(ClientConfig {
+            network: NetworkConfig {
+                endpoints: Some(vec!["a".into()]),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap(),
+    );
+
+    let now = client.env.now_ms();
+    NetworkMock::build()
+        .url("a")
+        .election(now, 1000)
+        .blocks("1")
+        .reset_client(&client)
+        .await;
+    assert_eq!(query_block_id(&client).await, "1");
+
+    let stats = crate::net::get_endpoint_stats(client.clone()).await.unwrap().endpoints;
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats[0].address, "a");
+    assert!(stats[0].current);
+    assert!(stats[0].healthy);
+    assert!(stats[0].requests_count >= 2);
+    assert_eq!(stats[0].failures_count, 0);
+    assert!(stats[0].average_response_time.is_some());
+}
+
+#[test]
+fn synced_accounts() {
+    use super::accounts_sync::{account_lt, SyncedAccounts};
+
+    assert_eq!(account_lt(&json!({ "last_trans_lt": "0x1a" })), Some(26));
+    assert_eq!(account_lt(&json!({ "last_trans_lt": "26" })), Some(26));
+    assert_eq!(account_lt(&json!({ "last_trans_lt": 26 })), Some(26));
+    assert_eq!(account_lt(&json!({})), None);
+
+    let mut synced = SyncedAccounts::default();
+    assert!(synced.update(&json!({ "id": "1", "last_trans_lt": "0x10" })));
+    assert!(synced.update(&json!({ "id": "2", "last_trans_lt": "0x20" })));
+    // subscription update received before the snapshot page
+    assert!(!synced.update(&json!({ "id": "1", "last_trans_lt": "0x10" })));
+    assert!(!synced.update(&json!({ "id": "1", "last_trans_lt": "0x0f" })));
+    assert!(synced.update(&json!({ "id": "1", "last_trans_lt": "0x11" })));
+
+    let found: HashSet<String> = vec!["1".to_string()].into_iter().collect();
+    assert_eq!(synced.remove_missing(&found), vec!["2".to_string()]);
+    // delayed update must not restore the removed account
+    assert!(!synced.update(&json!({ "id": "2", "last_trans_lt": "0x20" })));
+    assert!(synced.update(&json!({ "id": "2", "last_trans_lt": "0x21" })));
+
+    let state = synced.state();
+    assert_eq!(
+        state.accounts,
+        vec![
+            SyncedAccount { id: "1".into(), last_trans_lt: "17".into() },
+            SyncedAccount { id: "2".into(), last_trans_lt: "33".into() },
+        ]
+    );
+    let mut resumed = SyncedAccounts::from_state(&state);
+    assert!(!resumed.update(&json!({ "id": "2", "last_trans_lt": "0x21" })));
+    assert!(resumed.update(&json!({ "id": "3", "last_trans_lt": "0x01" })));
+}
@@ -16,7 +16,7 @@ use serde_json::Value;
 
 use crate::error::{ClientError, ClientResult};
 use crate::net::gql::GraphQLMessageFromClient;
-use crate::net::ParamsOfWaitForCollection;
+use crate::net::{BodyDecoding, FieldPredicate, ParamsOfWaitForCollection};
 use serde::{de::Error, Deserialize, Deserializer};
 
 const COUNTERPARTIES_COLLECTION: &str = "counterparties";
@@ -57,6 +57,14 @@ pub struct FieldAggregation {
     pub aggregation_fn: AggregationFn,
 }
 
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct AggregationGroupBy {
+    /// Dot separated path to the field the records are grouped by, e.g. `shard`
+    pub field: String,
+    /// Field values which form the groups
+    pub values: Vec<Value>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct PostRequest {
     pub id: String,
@@ -71,6 +79,18 @@ pub struct ParamsOfAggregateCollection {
     pub filter: Option<serde_json::Value>,
     /// Projection (result) string
     pub fields: Option<Vec<FieldAggregation>>,
+    /// Groups the records by the field values. The `fields` are aggregated
+    /// for each group separately.
+    ///
+    /// Groups are aggregated in the single request and are returned in `groups`
+    /// of the `aggregate_collection` result.
+    pub group_by: Option<AggregationGroupBy>,
+    /// Predicates the aggregated values of a group must satisfy (like SQL `HAVING`).
+    ///
+    /// Aggregated values are addressed by the index of the `fields` item: `"0"`, `"1"`, ...
+    /// and are compared as numbers. Predicates are applied on the client side
+    /// and require `group_by`.
+    pub having: Option<Vec<FieldPredicate>>,
 }
 
 #[derive(Serialize, ApiType, Default, Clone)]
@@ -85,6 +105,22 @@ pub struct ParamsOfQueryCollection {
     pub order: Option<Vec<OrderBy>>,
     /// Number of documents to return
     pub limit: Option<u32>,
+    /// Id of the shard block which must be already indexed by the endpoint before the query
+    /// is executed.
+    ///
+    /// Allows to read your own writes: pass `shard_block_id` returned by
+    /// `processing.send_message` or the consistency token returned by
+    /// `net.get_consistency_token`, so the query is not served by an endpoint which has not
+    /// caught up with the moment of sending yet. The block precedes the sent message, so
+    /// the message itself may be not processed yet. The waiting and the query are performed
+    /// on the same endpoint bypassing the query cache. Used by `net.query_collection` only.
+    pub min_block: Option<String>,
+    /// Decode the bodies of the returned messages into `decoded_body` field.
+    ///
+    /// Bodies are decoded by the endpoint if it supports the decoded body field, and locally
+    /// with the ABI otherwise. `id`, `body` and `msg_type` fields are added to `result`.
+    /// Supported for `messages` collection. Used by `net.query_collection` only.
+    pub decode_body: Option<BodyDecoding>,
 }
 
 #[derive(Deserialize)]
@@ -96,6 +132,8 @@ struct ParamsOfQueryCollectionFix {
     #[serde(rename = "orderBy")]
     pub order_by: Option<Vec<OrderBy>>,
     pub limit: Option<u32>,
+    pub min_block: Option<String>,
+    pub decode_body: Option<BodyDecoding>,
 }
 
 impl<'de> Deserialize<'de> for ParamsOfQueryCollection {
@@ -113,6 +151,8 @@ impl<'de> Deserialize<'de> for ParamsOfQueryCollection {
                         result: verified.result,
                         order: verified.order,
                         limit: verified.limit,
+                        min_block: verified.min_block,
+                        decode_body: verified.decode_body,
                     })
                 } else {
                     Err(D::Error::custom(
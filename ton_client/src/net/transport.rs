@@ -0,0 +1,166 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+// Network transports: the blockchain data required by the core operations is
+// requested either from the DApp server GraphQL API or from the lite servers via ADNL.
+
+use std::sync::Arc;
+
+use failure::err_msg;
+use ton_block::{AccountIdPrefixFull, Block, Deserializable, MsgAddressInt, MASTERCHAIN_ID};
+
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use crate::net::lite::{lite_backend, LiteClient};
+use crate::net::quorum::quorum_query_collection;
+use crate::net::{Error, ParamsOfQueryCollection, ACCOUNTS_COLLECTION};
+use crate::processing::blocks_walking::find_last_shard_block;
+#[cfg(feature = "proofs")]
+use crate::proofs::data_source::{GraphQLDataSource, ProofDataSource};
+#[cfg(feature = "proofs")]
+use crate::proofs::lite::LiteDataSource;
+
+/// Backend of the core network operations: account states, blocks and proofs data
+#[async_trait::async_trait]
+pub(crate) trait NetworkTransport: Send + Sync {
+    /// Returns the account BOC encoded with base64 or `None` if the account doesn't exist
+    async fn get_account_boc(&self, address: &MsgAddressInt) -> ClientResult<Option<String>>;
+
+    /// Returns the id of the last known block the message to `dst` can be included after
+    async fn get_last_block_id(&self, dst: &MsgAddressInt) -> ClientResult<String>;
+
+    /// Returns the source of the data for the proofs engine
+    #[cfg(feature = "proofs")]
+    fn proof_data_source(&self) -> Arc<dyn ProofDataSource>;
+}
+
+/// Transport over the DApp server GraphQL API
+pub(crate) struct GraphQLTransport {
+    context: Arc<ClientContext>,
+}
+
+impl GraphQLTransport {
+    pub fn new(context: Arc<ClientContext>) -> Self {
+        Self { context }
+    }
+}
+
+#[async_trait::async_trait]
+impl NetworkTransport for GraphQLTransport {
+    async fn get_account_boc(&self, address: &MsgAddressInt) -> ClientResult<Option<String>> {
+        let mut accounts = quorum_query_collection(
+            &self.context,
+            ParamsOfQueryCollection {
+                collection: ACCOUNTS_COLLECTION.to_owned(),
+                filter: Some(json!({
+                    "id": { "eq": address.to_string() }
+                })),
+                result: "boc".to_owned(),
+                limit: Some(1),
+                order: None,
+                min_block: None,
+                decode_body: None,
+            },
+        )
+        .await?
+        .result;
+        Ok(accounts
+            .pop()
+            .and_then(|mut account| account["boc"].take().as_str().map(|boc| boc.to_owned())))
+    }
+
+    async fn get_last_block_id(&self, dst: &MsgAddressInt) -> ClientResult<String> {
+        Ok(find_last_shard_block(&self.context, dst, None).await?.to_string())
+    }
+
+    #[cfg(feature = "proofs")]
+    fn proof_data_source(&self) -> Arc<dyn ProofDataSource> {
+        Arc::new(GraphQLDataSource::new(Arc::clone(&self.context)))
+    }
+}
+
+/// Transport over the lite servers ADNL protocol
+pub(crate) struct LiteTransport {
+    lite: Arc<LiteClient>,
+}
+
+impl LiteTransport {
+    pub fn new(lite: Arc<LiteClient>) -> Self {
+        Self { lite }
+    }
+}
+
+#[async_trait::async_trait]
+impl NetworkTransport for LiteTransport {
+    async fn get_account_boc(&self, address: &MsgAddressInt) -> ClientResult<Option<String>> {
+        let state = self.lite.get_account_state(None, address).await?.state;
+        Ok(if state.is_empty() {
+            None
+        } else {
+            Some(base64::encode(&state))
+        })
+    }
+
+    // Lite servers don't index shard blocks, so the top shard block is taken
+    // from the shards configuration of the last masterchain block
+    async fn get_last_block_id(&self, dst: &MsgAddressInt) -> ClientResult<String> {
+        let last = self.lite.get_masterchain_info().await?.last;
+        if dst.get_workchain_id() == MASTERCHAIN_ID {
+            return Ok(last.root_hash().as_hex_string());
+        }
+        find_shard_block(&self.lite.get_block(&last).await?, dst)
+    }
+
+    #[cfg(feature = "proofs")]
+    fn proof_data_source(&self) -> Arc<dyn ProofDataSource> {
+        Arc::new(LiteDataSource::new(Arc::clone(&self.lite)))
+    }
+}
+
+/// Returns the root hash of the top block of the shard containing `address`
+/// registered in the masterchain block
+pub(crate) fn find_shard_block(mc_block: &[u8], address: &MsgAddressInt) -> ClientResult<String> {
+    let find = || -> ton_types::Result<Option<String>> {
+        let block = Block::construct_from_bytes(mc_block)?;
+        let mc_extra = block.read_extra()?.read_custom()?
+            .ok_or_else(|| err_msg("Unable to read McBlockExtra"))?;
+        let prefix = AccountIdPrefixFull::prefix(address)?;
+        let mut root_hash = None;
+        mc_extra.shards().iterate_shards(|shard, descr| {
+            if !shard.contains_full_prefix(&prefix) {
+                return Ok(true);
+            }
+            root_hash = Some(descr.root_hash.as_hex_string());
+            Ok(false)
+        })?;
+        Ok(root_hash)
+    };
+    find()
+        .map_err(Error::invalid_server_response)?
+        .ok_or_else(|| {
+            Error::invalid_server_response(format!(
+                "no shard for account {} in the masterchain block",
+                address
+            ))
+        })
+}
+
+/// Returns the transport of the context: lite servers if they are the only network
+/// backend (no DApp server endpoints are specified), otherwise the DApp server
+pub(crate) fn get_transport(context: &Arc<ClientContext>) -> Arc<dyn NetworkTransport> {
+    match lite_backend(context) {
+        Some(lite) => Arc::new(LiteTransport::new(Arc::clone(lite))),
+        None => Arc::new(GraphQLTransport::new(Arc::clone(context))),
+    }
+}
@@ -11,6 +11,7 @@
 * limitations under the License.
 */
 
+use crate::net::lite::LiteServerConfig;
 use serde::{Deserialize, Deserializer};
 
 pub const MESSAGES_COLLECTION: &str = "messages";
@@ -62,6 +63,14 @@ pub fn default_query_timeout() -> u32 {
     60000
 }
 
+pub fn default_processing_concurrency_limit() -> u32 {
+    0
+}
+
+pub fn default_accounts_cache_size() -> u32 {
+    0
+}
+
 fn deserialize_network_retries_count<'de, D: Deserializer<'de>>(
     deserializer: D,
 ) -> Result<i8, D::Error> {
@@ -128,7 +137,19 @@ fn deserialize_query_timeout<'de, D: Deserializer<'de>>(
     Ok(Option::deserialize(deserializer)?.unwrap_or(default_query_timeout()))
 }
 
-#[derive(Debug, Clone, PartialEq, ApiType)]
+fn deserialize_processing_concurrency_limit<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<u32, D::Error> {
+    Ok(Option::deserialize(deserializer)?.unwrap_or(default_processing_concurrency_limit()))
+}
+
+fn deserialize_accounts_cache_size<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<u32, D::Error> {
+    Ok(Option::deserialize(deserializer)?.unwrap_or(default_accounts_cache_size()))
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ApiType, Default)]
 pub struct TrustedMcBlockId {
     /// Trusted key-block sequence number
     pub seq_no: u32,
@@ -137,6 +158,46 @@ pub struct TrustedMcBlockId {
     pub root_hash: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ApiType)]
+pub enum SubscriptionsTransport {
+    /// GraphQL subscriptions over websocket with `graphql-ws` protocol.
+    Websocket,
+
+    /// GraphQL subscriptions over Server-Sent Events with `graphql-sse` protocol
+    /// in distinct connections mode: each subscription is a separate streaming
+    /// http request to the endpoint query URL.
+    ///
+    /// Can be used in environments whose proxies terminate websockets. The endpoint
+    /// must support SSE subscriptions. Not supported in wasm environment.
+    ServerSentEvents,
+}
+
+impl Default for SubscriptionsTransport {
+    fn default() -> Self {
+        SubscriptionsTransport::Websocket
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ApiType)]
+pub enum NetworkQueriesProtocol {
+    /// Each query is a GraphQL request to the endpoint `graphql` path.
+    GraphQL,
+
+    /// Queries are JSON-RPC 2.0 requests to the endpoint URL.
+    ///
+    /// Only `query_collection`, `wait_for_collection` and `send_message` are mapped to
+    /// the JRPC API: aggregations, counterparties, raw GraphQL queries and subscriptions
+    /// fail with `NotSupported` error. Endpoint addresses are used as is, without the
+    /// `graphql` path, and the endpoint latency is not detected.
+    Jrpc,
+}
+
+impl Default for NetworkQueriesProtocol {
+    fn default() -> Self {
+        NetworkQueriesProtocol::GraphQL
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, ApiType)]
 pub struct NetworkConfig {
     /// DApp Server public address.
@@ -203,8 +264,8 @@ pub struct NetworkConfig {
     pub wait_for_timeout: u32,
 
     /// Maximum time difference between server and client. If client's device time is out of sync and difference is more than
-    /// the threshold then error will occur. Also an error will occur if the specified threshold is more than
-    /// `message_processing_timeout/2`.
+    /// the threshold then the server time is used to calculate message expiration instead of the device time.
+    /// An error will occur if the specified threshold is more than `message_processing_timeout/2`.
     ///
     /// Must be specified in milliseconds. Default is 15000 (15 sec).
     #[serde(
@@ -258,8 +319,146 @@ pub struct NetworkConfig {
     )]
     pub query_timeout: u32,
 
+    /// Maximum number of message sendings and shard block fetchings performed simultaneously.
+    ///
+    /// When the limit is reached, operations wait in queues according to their
+    /// `priority`: interactive operations are served before batch ones.
+    /// Default is 0 (no limit).
+    #[serde(
+        default = "default_processing_concurrency_limit",
+        deserialize_with = "deserialize_processing_concurrency_limit"
+    )]
+    pub processing_concurrency_limit: u32,
+
+    /// Maximum number of account states kept in the accounts cache.
+    ///
+    /// The cache is used when the account is specified by address in `tvm.run_tvm`,
+    /// `tvm.run_get`, `tvm.run_executor` and by the local message check in processing.
+    /// Cached state is dropped as soon as processing or subscriptions observe
+    /// a newer transaction of the account.
+    /// Default is 0 (cache is disabled).
+    #[serde(
+        default = "default_accounts_cache_size",
+        deserialize_with = "deserialize_accounts_cache_size"
+    )]
+    pub accounts_cache_size: u32,
+
     /// Access key to GraphQL API. At the moment is not used in production.
     pub access_key: Option<String>,
+
+    /// Transport used for subscriptions. Default is `Websocket`.
+    ///
+    /// Resubscription after network errors, keep-alive and latency checks
+    /// work the same way for all the transports.
+    pub subscriptions_transport: Option<SubscriptionsTransport>,
+
+    /// Lite servers to use via ADNL protocol. Requires the library built with `lite` feature.
+    ///
+    /// Lite servers can be queried with `net.lite_*` functions. If no DApp Server
+    /// endpoints are specified, lite servers are also used to get account states,
+    /// to send messages and as the data source for proofs checking.
+    pub lite_servers: Option<Vec<LiteServerConfig>>,
+
+    /// Time in ms without new shard blocks after which `wait_for_transaction` considers
+    /// the block walking stalled.
+    ///
+    /// When the stall is detected, `NoNewBlocks` processing event is sent and the block
+    /// walking continues until the waiting timeout is reached.
+    /// Default is `None` (stalls are not detected).
+    pub block_stall_timeout: Option<u32>,
+
+    /// Select another querying endpoint when the block walking stall is detected.
+    /// Default is `false`.
+    pub switch_endpoint_on_block_stall: Option<bool>,
+
+    /// Validate `result` strings, filters and sorting paths of collection queries
+    /// and subscriptions against the endpoint GraphQL schema before sending them.
+    ///
+    /// The schema is requested from the endpoint once. Queries are sent without
+    /// validation if the schema is not available.
+    /// Default is `false`.
+    pub validate_queries: Option<bool>,
+
+    /// Number of distinct endpoints the critical queries are fetched from.
+    ///
+    /// Account BOCs used for local execution and message encoding and block BOCs used
+    /// for proofs checking are queried from this number of endpoints and the results
+    /// are compared. The query fails with `ConflictingEndpointData` error if the endpoints
+    /// disagree (note that endpoints which are out of sync disagree too).
+    /// Default is `1` - the data is queried from a single endpoint.
+    pub quorum_endpoints_count: Option<u32>,
+
+    /// Maintain the persistent index of the iterated blocks in the local storage.
+    ///
+    /// Blocks returned by the block and transaction iterators are indexed by shard and
+    /// time, so an iterator created with an old `start_time` that was iterated before
+    /// seeks to the indexed block directly.
+    /// Default is `false`.
+    pub iterators_index: Option<bool>,
+
+    /// List of the archive endpoints keeping the full blockchain history.
+    ///
+    /// Production endpoints prune the old data, so the queries with the time filters
+    /// (`gen_utime`, `now`, `created_at`) older than `archive_horizon` and the iterators
+    /// started in the past are routed to the archive endpoints. Note that once an iterator
+    /// is started in the past, it reads all the blocks from the archive endpoints.
+    /// Queries filtered only by `seq_no` or `lt` are not routed.
+    pub archive_endpoints: Option<Vec<String>>,
+
+    /// Age of the data considered historical, in seconds.
+    ///
+    /// Matters only if `archive_endpoints` are specified.
+    /// Default is 604800 (7 days).
+    pub archive_horizon: Option<u32>,
+
+    /// Coalesce identical collection queries issued concurrently into a single request.
+    ///
+    /// Queries with the same collection, filter, result, order and limit that are issued
+    /// while such a query is in flight wait for it and receive its result instead
+    /// of sending a new request.
+    /// Default is `false`.
+    pub deduplicate_queries: Option<bool>,
+
+    /// Maximum number of the collection query results kept in the local query cache.
+    ///
+    /// Only the queries of `blocks`, `transactions` and `messages` collections filtered by
+    /// the exact ids (`id: { eq }` or `id: { in }`) are cached, because these items are
+    /// immutable once finalized. The result is cached only if it includes `status` field
+    /// and all the items are finalized. Empty results are not cached.
+    /// Default is 0 (cache is disabled).
+    pub query_cache_size: Option<u32>,
+
+    /// Time in ms the cached query result is valid for.
+    ///
+    /// Matters only if `query_cache_size` is specified.
+    /// Default is 60000 (1 min).
+    pub query_cache_ttl: Option<u32>,
+
+    /// Keep the endpoint statistics in the local storage between the sessions.
+    ///
+    /// The statistics (failures, response times, last errors) collected by the previous
+    /// sessions are used to order the endpoints when the querying endpoint is selected
+    /// for the first time, so the endpoints that failed recently are not tried first.
+    /// The statistics can be cleared with `net.reset_endpoint_stats`.
+    /// Default is `false`.
+    pub persist_endpoint_stats: Option<bool>,
+
+    /// Protocol used to query the endpoints. Default is `GraphQL`.
+    pub queries_protocol: Option<NetworkQueriesProtocol>,
+
+    /// URL of the relayer the messages are handed over to instead of sending them
+    /// to the endpoints.
+    ///
+    /// The message is posted as JSON `{ "id": <message hash>, "boc": <message BOC> }`
+    /// (both encoded with `base64`) and is considered accepted if the relayer responds
+    /// with a 2xx status. The relayer is expected to deliver the message to the network,
+    /// e.g. paying the fees for the user. The processing of the relayed messages
+    /// (waiting for the transaction, retries) is the same as of the sent ones.
+    /// Not used by default.
+    pub relayer_url: Option<String>,
+
+    /// Access key passed to the relayer in the `Authorization: Bearer` header.
+    pub relayer_access_key: Option<String>,
 }
 
 impl Default for NetworkConfig {
@@ -278,7 +477,25 @@ impl Default for NetworkConfig {
             latency_detection_interval: default_latency_detection_frequency(),
             max_latency: default_max_latency(),
             query_timeout: default_query_timeout(),
+            processing_concurrency_limit: default_processing_concurrency_limit(),
+            accounts_cache_size: default_accounts_cache_size(),
             access_key: None,
+            subscriptions_transport: None,
+            lite_servers: None,
+            block_stall_timeout: None,
+            switch_endpoint_on_block_stall: None,
+            validate_queries: None,
+            quorum_endpoints_count: None,
+            iterators_index: None,
+            archive_endpoints: None,
+            archive_horizon: None,
+            deduplicate_queries: None,
+            query_cache_size: None,
+            query_cache_ttl: None,
+            persist_endpoint_stats: None,
+            queries_protocol: None,
+            relayer_url: None,
+            relayer_access_key: None,
         }
     }
 }
@@ -18,7 +18,8 @@ use crate::net::endpoint::Endpoint;
 use crate::net::gql::{GraphQLMessageFromClient, GraphQLMessageFromServer};
 use crate::net::server_link::NetworkState;
 use crate::net::ton_gql::{GraphQLQuery, GraphQLQueryEvent};
-use crate::net::{Error, NetworkConfig};
+use crate::net::sse;
+use crate::net::{Error, NetworkConfig, SubscriptionsTransport};
 use futures::stream::{Fuse, FusedStream};
 use futures::Sink;
 use futures::{SinkExt, StreamExt};
@@ -251,14 +252,23 @@ impl LinkHandler {
         self.keep_alive = KeepAlive::WaitFirst;
         let endpoint = self.state.get_query_endpoint().await?;
         let mut headers = HashMap::new();
-        headers.insert("Sec-WebSocket-Protocol".into(), "graphql-ws".into());
         for (name, value) in Endpoint::http_headers() {
             headers.insert(name, value);
         }
-        let mut ws = self
-            .client_env
-            .websocket_connect(&endpoint.subscription_url, Some(headers))
-            .await;
+        let transport = self.config.subscriptions_transport.clone().unwrap_or_default();
+        let mut ws = match transport {
+            SubscriptionsTransport::Websocket => {
+                headers.insert("Sec-WebSocket-Protocol".into(), "graphql-ws".into());
+                self.client_env
+                    .websocket_connect(&endpoint.subscription_url, Some(headers))
+                    .await
+            }
+            SubscriptionsTransport::ServerSentEvents => Ok(sse::connect(
+                self.client_env.clone(),
+                &endpoint.query_url,
+                headers,
+            )),
+        };
         if let Ok(ref mut ws) = ws {
             let mut connection_params = json!({});
             if let Some(access_key) = &self.config.access_key {
@@ -427,6 +437,14 @@ impl LinkHandler {
         }
     }
 
+    // Server-Sent Events transport has no connection to keep alive without operations,
+    // so the keep-alive interval is detected again with the next operation.
+    fn reset_keep_alive_if_idle(&mut self) {
+        if self.operations.is_empty() {
+            self.keep_alive = KeepAlive::WaitFirst;
+        }
+    }
+
     fn start_keep_alive_timer(&mut self, timeout: u64) {
         log::debug!("WS keep alive timer {}", timeout);
         let mut sender = self.internal_action_sender.clone();
@@ -449,6 +467,7 @@ impl LinkHandler {
                 if let Some(mut operation) = self.operations.remove(&id) {
                     operation.notify(operation_event).await;
                 }
+                self.reset_keep_alive_if_idle();
             } else if let Some(operation) = self.operations.get_mut(&id) {
                 operation.notify(operation_event).await;
             }
@@ -534,5 +553,6 @@ impl LinkHandler {
                 ws_send(ws, GraphQLMessageFromClient::Stop { id: id.to_string() }).await;
             }
         }
+        self.reset_keep_alive_if_idle();
     }
 }
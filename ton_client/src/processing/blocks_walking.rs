@@ -53,6 +53,8 @@ pub(crate) async fn find_last_shard_block(
                 direction: SortDirection::DESC,
             }]),
             limit: Some(1),
+            min_block: None,
+            decode_body: None,
         }, endpoint.clone())
         .await?;
     debug!("Last block {}", blocks[0]["id"]);
@@ -81,6 +83,8 @@ pub(crate) async fn find_last_shard_block(
                         direction: SortDirection::DESC,
                     }]),
                     limit: Some(1),
+                    min_block: None,
+                    decode_body: None,
                 }, endpoint.clone())
                 .await?;
 
@@ -110,6 +114,8 @@ pub(crate) async fn find_last_shard_block(
                         direction: SortDirection::DESC,
                     }]),
                     limit: Some(1),
+                    min_block: None,
+                    decode_body: None,
                 }, endpoint)
                 .await?;
             blocks[0]["id"]
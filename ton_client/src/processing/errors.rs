@@ -31,6 +31,11 @@ pub enum ErrorCode {
     BlockNotFound = 511,
     InvalidData = 512,
     ExternalSignerMustNotBeUsed = 513,
+    TransactionFinalityTimeout = 514,
+    SequenceReadFailed = 515,
+    TooManyMessages = 516,
+    MessageRejectedLocally = 517,
+    RelayerSendFailed = 518,
 }
 
 pub struct Error;
@@ -199,6 +204,26 @@ impl Error {
         error
     }
 
+    pub fn transaction_finality_timeout(
+        message_id: &str,
+        block_id: &str,
+        depth: u32,
+        timeout: u32,
+    ) -> ClientError {
+        let mut error = Self::processing_error(
+            ErrorCode::TransactionFinalityTimeout,
+            "Transaction has not reached the requested finality in time".into(),
+            message_id,
+            None,
+        );
+
+        error.data["block_id"] = block_id.into();
+        error.data["finality_depth"] = depth.into();
+        error.data["timeout"] = timeout.into();
+
+        error
+    }
+
     pub fn can_not_check_block_shard<E: std::fmt::Display>(err: E) -> ClientError {
         error(
             ErrorCode::CanNotCheckBlockShard,
@@ -213,4 +238,49 @@ impl Error {
     pub fn invalid_data<E: std::fmt::Display>(err: E) -> ClientError {
         error(ErrorCode::InvalidData, format!("Invalid data: {}", err))
     }
+
+    pub fn sequence_read_failed<E: std::fmt::Display>(address: &str, err: E) -> ClientError {
+        error_with_data(
+            ErrorCode::SequenceReadFailed,
+            format!("Can not read the sequence number of {}: {}", address, err),
+            json!({ "address": address }),
+        )
+    }
+
+    pub fn too_many_messages(count: usize, limit: usize) -> ClientError {
+        error(
+            ErrorCode::TooManyMessages,
+            format!("Too many messages: {}, maximum is {}", count, limit),
+        )
+    }
+
+    pub fn message_rejected_locally(message_id: &str, local_error: ClientError) -> ClientError {
+        let mut error = Self::processing_error(
+            ErrorCode::MessageRejectedLocally,
+            format!(
+                "Message is rejected by the local execution and is not sent: {}",
+                local_error.message
+            ),
+            message_id,
+            None,
+        );
+        error.data["exit_code"] = local_error.data["exit_code"].clone();
+        error.data["local_error"] = serde_json::to_value(&local_error).unwrap_or_default();
+        error
+    }
+
+    pub fn relayer_send_failed<E: std::fmt::Display>(
+        err: E,
+        message_id: &str,
+        relayer_url: &str,
+    ) -> ClientError {
+        let mut error = Self::processing_error(
+            ErrorCode::RelayerSendFailed,
+            format!("Relayer didn't accept the message: {}", err),
+            message_id,
+            None,
+        );
+        error.data["relayer_url"] = relayer_url.into();
+        error
+    }
 }
@@ -28,6 +28,7 @@ pub async fn fetch_next_shard_block<F: futures::Future<Output = ()> + Send>(
     callback: impl Fn(ProcessingEvent) -> F + Send + Sync,
 ) -> ClientResult<Block> {
     let start = context.env.now_ms();
+    let stall_timeout = context.config.network.block_stall_timeout.filter(|timeout| *timeout > 0);
 
     // Network retries loop
     loop {
@@ -37,13 +38,36 @@ pub async fn fetch_next_shard_block<F: futures::Future<Output = ()> + Send>(
                 shard_block_id: block_id.to_string(),
                 message_id: message_id.to_string(),
                 message: params.message.clone(),
+                waiters: 1,
             })
             .await;
         }
 
         // Fetch next block
-        match wait_next_block(context, block_id.into(), &address, Some(timeout)).await {
+        let wait_timeout = stall_timeout
+            .map(|stall_timeout| std::cmp::min(stall_timeout, timeout))
+            .unwrap_or(timeout);
+        match wait_next_block(context, block_id.into(), &address, Some(wait_timeout)).await {
             Ok(block) => return Ok(block),
+            Err(err)
+                if stall_timeout.is_some()
+                    && err.code == crate::net::ErrorCode::WaitForTimeout as u32
+                    && context.env.now_ms() < start + timeout as u64 =>
+            {
+                // No new blocks within the stall timeout, but the waiting time is not over yet
+                if params.send_events {
+                    callback(ProcessingEvent::NoNewBlocks {
+                        shard_block_id: block_id.to_string(),
+                        message_id: message_id.to_string(),
+                        message: params.message.clone(),
+                        elapsed: context.env.now_ms() - start,
+                    })
+                    .await;
+                }
+                if context.config.network.switch_endpoint_on_block_stall.unwrap_or_default() {
+                    context.get_server_link()?.invalidate_querying_endpoint().await;
+                }
+            }
             Err(err) => {
                 let is_retryable_error = crate::client::Error::is_network_error(&err) ||
                     err.code == crate::net::ErrorCode::WaitForTimeout as u32;
@@ -127,6 +151,8 @@ pub(crate) async fn fetch_account(
             limit: None,
             order: None,
             result: result.to_owned(),
+            min_block: None,
+            decode_body: None,
         },
     )
     .await?;
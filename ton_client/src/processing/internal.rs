@@ -1,8 +1,8 @@
-use super::fetching::fetch_account;
 use crate::abi::{Abi, ParamsOfDecodeMessage};
 use crate::client::ClientContext;
 use crate::error::{ClientError, ClientResult};
 use crate::processing::Error;
+use crate::tvm::accounts_cache::fetch_account_boc;
 use crate::tvm::{AccountForExecutor, ExecutionOptions, ParamsOfRunExecutor};
 use std::sync::Arc;
 use ton_block::MsgAddressInt;
@@ -57,6 +57,7 @@ pub(crate) async fn get_message_expiration_time(
             ParamsOfDecodeMessage {
                 abi: abi.clone(),
                 message: message.to_string(),
+                partial: None,
             },
         )
         .await
@@ -71,19 +72,14 @@ pub(crate) async fn get_message_expiration_time(
     Ok(time)
 }
 
-async fn get_local_error(
+pub(crate) async fn get_local_error(
     context: Arc<ClientContext>,
     address: &MsgAddressInt,
     message: String,
     time: u32,
     show_tips_on_error: bool,
 ) -> ClientResult<()> {
-    let account = fetch_account(context.clone(), address, "boc").await?;
-
-    let boc = account["boc"]
-        .as_str()
-        .ok_or(Error::invalid_data("Account doesn't contain 'boc'"))?
-        .to_owned();
+    let boc = fetch_account_boc(&context, address).await?;
 
     crate::tvm::run_executor_internal(
         context,
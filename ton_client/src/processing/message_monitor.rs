@@ -0,0 +1,318 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+// Batched monitoring of the sent messages: the transactions of all monitored messages
+// are received by a single subscription instead of a block walking loop per message.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use futures::{Future, FutureExt};
+use rand::RngCore;
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use crate::net::{
+    query_collection, subscribe_collection_with_events, unsubscribe, ParamsOfQueryCollection,
+    ParamsOfSubscribeCollection, ResultOfSubscription, SubscriptionEvent,
+    TRANSACTIONS_COLLECTION,
+};
+
+const TRANSACTION_FIELDS: &str = "id in_msg aborted compute { exit_code }";
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone, PartialEq)]
+pub struct MonitoredMessage {
+    /// Hash of the message.
+    pub hash: String,
+    /// Message expiration time (Unix time in seconds), usually the `expire` header value.
+    pub expiration: u32,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone)]
+pub struct ParamsOfMonitorMessages {
+    /// Messages to monitor.
+    pub messages: Vec<MonitoredMessage>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone)]
+pub struct ResultOfMonitorMessages {
+    /// Monitoring handle. Can be passed to `stop_monitoring` to cancel the monitoring.
+    pub handle: u32,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone)]
+pub struct ParamsOfStopMonitoring {
+    /// Monitoring handle returned by `monitor_messages`.
+    pub handle: u32,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum MessageMonitoringEvent {
+    /// Message is processed by a successful transaction.
+    Finalized {
+        message_hash: String,
+        transaction_id: String,
+    },
+    /// Message is processed by an aborted transaction.
+    Rejected {
+        message_hash: String,
+        transaction_id: String,
+        /// Exit code of the compute phase. Absent if the compute phase was skipped.
+        exit_code: Option<i32>,
+    },
+    /// No transaction is found until the message expiration time
+    /// plus `message_processing_timeout`.
+    Expired { message_hash: String },
+}
+
+/// Unresolved messages of the monitor with their resolution deadlines (in ms)
+pub(crate) struct PendingMessages {
+    deadlines: HashMap<String, u64>,
+}
+
+impl PendingMessages {
+    pub fn new(messages: &[MonitoredMessage], processing_timeout: u32) -> Self {
+        Self {
+            deadlines: messages
+                .iter()
+                .map(|message| {
+                    let deadline = message.expiration as u64 * 1000 + processing_timeout as u64;
+                    (message.hash.clone(), deadline)
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.deadlines.is_empty()
+    }
+
+    pub fn hashes(&self) -> Vec<String> {
+        self.deadlines.keys().cloned().collect()
+    }
+
+    pub fn next_deadline(&self) -> Option<u64> {
+        self.deadlines.values().min().cloned()
+    }
+
+    /// Resolves the message of the received transaction. Returns `None` if the transaction
+    /// doesn't belong to the unresolved messages
+    pub fn resolve(&mut self, transaction: &Value) -> Option<MessageMonitoringEvent> {
+        let message_hash = transaction["in_msg"].as_str()?;
+        self.deadlines.remove(message_hash)?;
+        let message_hash = message_hash.to_string();
+        let transaction_id = transaction["id"].as_str().unwrap_or_default().to_string();
+        Some(if transaction["aborted"].as_bool().unwrap_or_default() {
+            MessageMonitoringEvent::Rejected {
+                message_hash,
+                transaction_id,
+                exit_code: transaction["compute"]["exit_code"].as_i64().map(|code| code as i32),
+            }
+        } else {
+            MessageMonitoringEvent::Finalized {
+                message_hash,
+                transaction_id,
+            }
+        })
+    }
+
+    /// Removes the messages with the deadline reached by `now`
+    pub fn expire(&mut self, now: u64) -> Vec<MessageMonitoringEvent> {
+        let expired: Vec<String> = self
+            .deadlines
+            .iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(hash, _)| hash.clone())
+            .collect();
+        expired
+            .into_iter()
+            .map(|message_hash| {
+                self.deadlines.remove(&message_hash);
+                MessageMonitoringEvent::Expired { message_hash }
+            })
+            .collect()
+    }
+}
+
+pub(crate) enum MonitorAction {
+    Transaction(Value),
+    /// Subscription was restarted, so the transactions of the unresolved messages are queried
+    Recheck,
+    Stop,
+}
+
+/// Running monitors by the handle
+#[derive(Default)]
+pub(crate) struct MessageMonitors {
+    monitors: Mutex<HashMap<u32, mpsc::UnboundedSender<MonitorAction>>>,
+}
+
+impl MessageMonitors {
+    fn add(&self, handle: u32, sender: mpsc::UnboundedSender<MonitorAction>) {
+        self.monitors.lock().unwrap_or_else(PoisonError::into_inner).insert(handle, sender);
+    }
+
+    fn remove(&self, handle: u32) -> Option<mpsc::UnboundedSender<MonitorAction>> {
+        self.monitors.lock().unwrap_or_else(PoisonError::into_inner).remove(&handle)
+    }
+}
+
+fn transactions_filter(hashes: Vec<String>) -> Value {
+    json!({ "in_msg": { "in": hashes } })
+}
+
+async fn query_transactions(
+    context: &Arc<ClientContext>,
+    hashes: Vec<String>,
+) -> ClientResult<Vec<Value>> {
+    let limit = hashes.len() as u32;
+    query_collection(
+        context.clone(),
+        ParamsOfQueryCollection {
+            collection: TRANSACTIONS_COLLECTION.to_string(),
+            filter: Some(transactions_filter(hashes)),
+            result: TRANSACTION_FIELDS.to_string(),
+            limit: Some(limit),
+            ..Default::default()
+        },
+    )
+    .await
+    .map(|result| result.result)
+}
+
+async fn run_monitor<F: Future<Output = ()> + Send>(
+    context: Arc<ClientContext>,
+    handle: u32,
+    mut pending: PendingMessages,
+    mut actions: mpsc::UnboundedReceiver<MonitorAction>,
+    callback: impl Fn(MessageMonitoringEvent) -> F + Send + Sync + 'static,
+) {
+    while let Some(deadline) = pending.next_deadline() {
+        let timeout = deadline.saturating_sub(context.network_now_ms());
+        let timer = context.env.set_timer(timeout).fuse();
+        let action = actions.recv().fuse();
+        futures::pin_mut!(timer, action);
+        futures::select!(
+            action = action => match action {
+                Some(MonitorAction::Transaction(transaction)) => {
+                    if let Some(event) = pending.resolve(&transaction) {
+                        callback(event).await;
+                    }
+                }
+                Some(MonitorAction::Recheck) => {
+                    match query_transactions(&context, pending.hashes()).await {
+                        Ok(transactions) => for transaction in transactions {
+                            if let Some(event) = pending.resolve(&transaction) {
+                                callback(event).await;
+                            }
+                        },
+                        Err(err) => log::debug!("Message monitor recheck failed: {}", err),
+                    }
+                }
+                Some(MonitorAction::Stop) | None => break,
+            },
+            _ = timer => {
+                for event in pending.expire(context.network_now_ms()) {
+                    callback(event).await;
+                }
+            }
+        );
+    }
+    context.net.message_monitors.remove(handle);
+}
+
+/// Starts the monitoring of the messages. Returns the monitoring handle.
+///
+/// Each message is resolved by the single event passed to the `callback`. The monitoring
+/// is finished when all of the messages are resolved.
+pub async fn monitor_messages<F: Future<Output = ()> + Send>(
+    context: Arc<ClientContext>,
+    params: ParamsOfMonitorMessages,
+    callback: impl Fn(MessageMonitoringEvent) -> F + Send + Sync + 'static,
+) -> ClientResult<ResultOfMonitorMessages> {
+    let handle = rand::thread_rng().next_u32();
+    let pending = PendingMessages::new(
+        &params.messages,
+        context.config.network.message_processing_timeout,
+    );
+    if pending.is_empty() {
+        return Ok(ResultOfMonitorMessages { handle });
+    }
+
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let transactions = sender.clone();
+    let recheck = sender.clone();
+    let subscription = subscribe_collection_with_events(
+        context.clone(),
+        ParamsOfSubscribeCollection {
+            collection: TRANSACTIONS_COLLECTION.to_string(),
+            filter: Some(transactions_filter(pending.hashes())),
+            result: TRANSACTION_FIELDS.to_string(),
+            mapping: None,
+            decode_body: None,
+        },
+        move |result: ClientResult<ResultOfSubscription>| {
+            if let Ok(result) = result {
+                let _ = transactions.send(MonitorAction::Transaction(result.result));
+            }
+            futures::future::ready(())
+        },
+        move |event: SubscriptionEvent| {
+            match event {
+                SubscriptionEvent::Resumed { .. } => {
+                    let _ = recheck.send(MonitorAction::Recheck);
+                }
+            }
+            futures::future::ready(())
+        },
+    )
+    .await?;
+
+    // The messages can be processed before the subscription is started
+    let processed = match query_transactions(&context, pending.hashes()).await {
+        Ok(processed) => processed,
+        Err(err) => {
+            let _ = unsubscribe(context, subscription).await;
+            return Err(err);
+        }
+    };
+    for transaction in processed {
+        let _ = sender.send(MonitorAction::Transaction(transaction));
+    }
+
+    context.net.message_monitors.add(handle, sender);
+    let monitor_context = context.clone();
+    context.env.spawn(async move {
+        run_monitor(monitor_context.clone(), handle, pending, receiver, callback).await;
+        let _ = unsubscribe(monitor_context, subscription).await;
+    });
+
+    Ok(ResultOfMonitorMessages { handle })
+}
+
+/// Stops the message monitoring started with `monitor_messages`.
+/// The unresolved messages of the monitor are not reported.
+#[api_function]
+pub async fn stop_monitoring(
+    context: Arc<ClientContext>,
+    params: ParamsOfStopMonitoring,
+) -> ClientResult<()> {
+    if let Some(sender) = context.net.message_monitors.remove(params.handle) {
+        let _ = sender.send(MonitorAction::Stop);
+    }
+    Ok(())
+}
@@ -15,18 +15,63 @@
 #[cfg(test)]
 mod tests;
 
+#[cfg(feature = "net")]
 pub(crate) mod blocks_walking;
 mod errors;
+#[cfg(feature = "net")]
 mod fetching;
+#[cfg(feature = "net")]
 mod internal;
+#[cfg(feature = "net")]
+pub(crate) mod message_monitor;
+#[cfg(feature = "tvm-executor")]
 pub(crate) mod parsing;
+#[cfg(feature = "net")]
 pub(crate) mod process_message;
+#[cfg(feature = "net")]
+pub(crate) mod retry_policy;
+#[cfg(feature = "net")]
+pub(crate) mod scheduler;
+#[cfg(feature = "net")]
 pub(crate) mod send_message;
+#[cfg(feature = "net")]
+pub(crate) mod send_raw_message;
+#[cfg(feature = "net")]
+pub(crate) mod sequence;
 mod types;
+#[cfg(feature = "net")]
 pub(crate) mod wait_for_transaction;
+#[cfg(feature = "net")]
+pub(crate) mod waiters;
 
 pub use errors::{Error, ErrorCode};
+#[cfg(feature = "net")]
+pub use message_monitor::{
+    monitor_messages, stop_monitoring, MessageMonitoringEvent, MonitoredMessage,
+    ParamsOfMonitorMessages, ParamsOfStopMonitoring, ResultOfMonitorMessages,
+};
+#[cfg(feature = "net")]
 pub use process_message::{process_message, ParamsOfProcessMessage};
-pub use send_message::{send_message, ParamsOfSendMessage, ResultOfSendMessage};
-pub use types::{DecodedOutput, ProcessingEvent, ProcessingResponseType, ResultOfProcessMessage};
+#[cfg(feature = "net")]
+pub use retry_policy::{RetryCondition, RetryPolicy};
+#[cfg(feature = "net")]
+pub use scheduler::{
+    get_scheduler_stats, ProcessingLaneStats, ProcessingPriority, ResultOfGetSchedulerStats,
+};
+#[cfg(feature = "net")]
+pub use sequence::{MessageSequence, SequenceProvider};
+#[cfg(feature = "net")]
+pub use send_message::{
+    send_message, send_messages, MessageSendingResult, ParamsOfSendMessage, ParamsOfSendMessages,
+    ResultOfSendMessage, ResultOfSendMessages,
+};
+#[cfg(feature = "net")]
+pub use send_raw_message::{send_raw_message, ParamsOfSendRawMessage};
+pub use types::DecodedOutput;
+#[cfg(feature = "net")]
+pub use types::{
+    ParamsOfDecodeTree, ProcessingEvent, ProcessingResponseType, ResultOfProcessMessage,
+    TransactionFinality, TreeDecodingDepth,
+};
+#[cfg(feature = "net")]
 pub use wait_for_transaction::{wait_for_transaction, ParamsOfWaitForTransaction};
@@ -1,12 +1,22 @@
 use crate::abi::{decode_message, Abi, MessageBodyType, ParamsOfDecodeMessage};
-use crate::boc::{parse_transaction, ParamsOfParse};
 use crate::client::ClientContext;
 use crate::error::ClientResult;
-use crate::processing::fetching::TransactionBoc;
 use crate::processing::types::DecodedOutput;
-use serde_json::Value;
 use std::sync::Arc;
+#[cfg(feature = "net")]
+use crate::boc::{parse_transaction, ParamsOfParse};
+#[cfg(feature = "net")]
+use crate::processing::fetching::TransactionBoc;
+#[cfg(feature = "net")]
+use crate::net::transaction_tree::query_transaction_tree;
+#[cfg(feature = "net")]
+use crate::net::{MessageNode, ParamsOfQueryTransactionTree};
+#[cfg(feature = "net")]
+use crate::processing::types::{ParamsOfDecodeTree, TreeDecodingDepth};
+#[cfg(feature = "net")]
+use serde_json::Value;
 
+#[cfg(feature = "net")]
 pub(crate) async fn parse_transaction_boc(
     context: Arc<ClientContext>,
     transaction: TransactionBoc,
@@ -41,6 +51,7 @@ pub(crate) async fn decode_output(
             ParamsOfDecodeMessage {
                 message,
                 abi: abi.clone(),
+                partial: None,
             },
         ).await;
         let decoded = match decode_result {
@@ -57,5 +68,46 @@ pub(crate) async fn decode_output(
     Ok(DecodedOutput {
         out_messages,
         output,
+        #[cfg(feature = "net")]
+        tree_messages: None,
     })
 }
+
+/// Queries the descendant transactions of the processed transaction and decodes
+/// the messages they produced
+#[cfg(feature = "net")]
+pub(crate) async fn decode_tree(
+    context: &Arc<ClientContext>,
+    abi: &Abi,
+    transaction: &Value,
+    params: &ParamsOfDecodeTree,
+) -> ClientResult<Vec<MessageNode>> {
+    let in_msg = transaction["in_msg"].as_str().unwrap_or_default();
+    let transaction_id = transaction["id"].as_str().unwrap_or_default();
+    let mut abi_registry = vec![abi.clone()];
+    abi_registry.extend(params.abi_registry.iter().flatten().cloned());
+    let max_depth = match params.depth.unwrap_or_default() {
+        TreeDecodingDepth::OneLevel => Some(2),
+        TreeDecodingDepth::FullTree => None,
+    };
+    let tree = query_transaction_tree(
+        context.clone(),
+        ParamsOfQueryTransactionTree {
+            in_msg: in_msg.to_string(),
+            abi_registry: Some(abi_registry),
+            timeout: params.timeout,
+            max_depth,
+            ..Default::default()
+        },
+    )
+    .await?;
+    // The processed message and the processed transaction out messages are skipped
+    Ok(tree
+        .messages
+        .into_iter()
+        .filter(|message| {
+            message.id != in_msg
+                && message.src_transaction_id.as_deref() != Some(transaction_id)
+        })
+        .collect())
+}
@@ -1,21 +1,54 @@
 use crate::abi::ParamsOfEncodeMessage;
 use crate::client::ClientContext;
 use crate::error::{AddNetworkUrl, ClientResult};
-use crate::processing::internal::can_retry_expired_message;
+use crate::processing::parsing::decode_tree;
+use crate::processing::retry_policy::RetryPolicy;
+use crate::processing::types::ParamsOfDecodeTree;
+use crate::processing::scheduler::ProcessingPriority;
+use crate::processing::sequence::{apply_sequence, MessageSequence};
 use crate::processing::{
-    send_message, wait_for_transaction, ErrorCode, ParamsOfSendMessage, ParamsOfWaitForTransaction,
+    send_message, wait_for_transaction, ParamsOfSendMessage, ParamsOfWaitForTransaction,
     ProcessingEvent, ResultOfProcessMessage, ResultOfSendMessage,
 };
-use crate::tvm::StdContractError;
 use std::sync::Arc;
 
 #[derive(Serialize, Deserialize, ApiType, Default, Debug)]
 pub struct ParamsOfProcessMessage {
     /// Message encode parameters.
+    ///
+    /// The `expiration` of the encode parameters applies to each sent message. Note that
+    /// the message with the fixed `expire_at` time is not sent again after it is expired.
     pub message_encode_params: ParamsOfEncodeMessage,
 
     /// Flag for requesting events sending
     pub send_events: bool,
+
+    /// Priority of the message sending and the transaction waiting. Default is `Interactive`.
+    ///
+    /// Matters only when `processing_concurrency_limit` of the network config is reached.
+    pub priority: Option<ProcessingPriority>,
+
+    /// Sequence number (seqno) management of the destination contract.
+    ///
+    /// If specified, the sequence number is read from the destination contract before each
+    /// sending and passed to the `call_set` input. Processings of the messages to the same
+    /// destination are performed one by one inside the client context, and the message
+    /// rejected with a sequence number conflict is sent again with the fresh number.
+    pub sequence: Option<MessageSequence>,
+
+    /// Decoding of the messages produced by the descendant transactions.
+    ///
+    /// If specified, the transaction tree of the processed message is queried after the
+    /// processing and the messages are returned in `decoded.tree_messages`.
+    pub decode_tree: Option<ParamsOfDecodeTree>,
+
+    /// Retry policy of the expired messages.
+    ///
+    /// Defines the number of retries, the delays between them and the errors triggering
+    /// the retry. By default the message is sent again immediately up to
+    /// `NetworkConfig.message_retries_count` times if it is expired and its local emulation
+    /// succeeded or failed with the replay protection error.
+    pub retry_policy: Option<RetryPolicy>,
 }
 
 pub async fn process_message<F: futures::Future<Output = ()> + Send>(
@@ -25,25 +58,56 @@ pub async fn process_message<F: futures::Future<Output = ()> + Send>(
 ) -> ClientResult<ResultOfProcessMessage> {
     let abi = params.message_encode_params.abi.clone();
 
+    // Messages using the destination sequence number are processed one by one
+    let sequence_lock = match (&params.sequence, &params.message_encode_params.address) {
+        (Some(_), Some(address)) => {
+            let address = crate::encoding::account_decode(address)?.to_string();
+            Some(context.net.sequence_locks.get(&address))
+        }
+        _ => None,
+    };
+    let _sequence_guard = match &sequence_lock {
+        Some(lock) => Some(lock.lock().await),
+        None => None,
+    };
+
+    let default_retry_policy = RetryPolicy::default();
+    let retry_policy = params.retry_policy.as_ref().unwrap_or(&default_retry_policy);
+    let start = context.env.now_ms();
     let mut try_index = 0;
     loop {
+        // Expiration of the message is calculated with the network time. The local clock
+        // is used if the endpoint is not available (lite-only or offline mode)
+        if let Ok(link) = context.get_server_link() {
+            if let Err(err) = link.sync_clock().await {
+                log::debug!("Network clock is not synchronized, local clock is used: {}", err);
+            }
+        }
+
         // Encode message
         let mut encode_params = params.message_encode_params.clone();
         encode_params.processing_try_index = Some(try_index);
-        let message = crate::abi::encode_message(context.clone(), encode_params)
-            .await?
-            .message;
+        if let Some(sequence) = &params.sequence {
+            apply_sequence(&context, sequence, &mut encode_params).await?;
+        }
+        let message = crate::abi::encode_message(context.clone(), encode_params).await?;
+        let message_id = message.message_id;
+        let message = message.message;
 
         // Send
         let ResultOfSendMessage {
             shard_block_id,
             sending_endpoints,
+            ..
         } = send_message(
             context.clone(),
             ParamsOfSendMessage {
                 message: message.clone(),
                 abi: Some(abi.clone()),
                 send_events: params.send_events,
+                broadcast_fanout: None,
+                priority: params.priority,
+                validate_locally: None,
             },
             &callback,
         )
@@ -59,6 +123,8 @@ pub async fn process_message<F: futures::Future<Output = ()> + Send>(
                 abi: Some(abi.clone()),
                 shard_block_id: shard_block_id.clone(),
                 sending_endpoints: Some(sending_endpoints),
+                priority: params.priority,
+                finality: None,
             },
             &callback,
         )
@@ -67,24 +133,59 @@ pub async fn process_message<F: futures::Future<Output = ()> + Send>(
         .await;
 
         match wait_for {
-            Ok(output) => {
+            Ok(mut output) => {
+                if let (Some(decode_tree_params), Some(decoded)) =
+                    (&params.decode_tree, output.decoded.as_mut())
+                {
+                    decoded.tree_messages = Some(
+                        decode_tree(&context, &abi, &output.transaction, decode_tree_params)
+                            .await?,
+                    );
+                }
                 // Waiting is complete, return output
                 return Ok(output);
             }
             Err(err) => {
-                let local_exit_code = &err.data["local_error"]["data"]["exit_code"];
-                let can_retry = err.code == ErrorCode::MessageExpired as u32
-                    && (err.data["local_error"].is_null()
-                        || local_exit_code == StdContractError::ReplayProtection as i32
-                        || local_exit_code == StdContractError::ExtMessageExpired as i32)
-                    && can_retry_expired_message(&context, try_index);
-                if !can_retry {
+                let next_try_index = try_index.checked_add(1).unwrap_or(try_index);
+                // The message with the fixed expiration time can't be sent again
+                let fixed_expiration = params
+                    .message_encode_params
+                    .expiration
+                    .as_ref()
+                    .map(|expiration| expiration.expire_at.is_some())
+                    .unwrap_or_default();
+                let delay = if !fixed_expiration
+                    && retry_policy.is_retryable(&err, params.sequence.as_ref())
+                {
+                    retry_policy.next_delay(
+                        context.config.network.message_retries_count,
+                        next_try_index,
+                        context.env.now_ms().saturating_sub(start),
+                    )
+                } else {
+                    None
+                };
+                let delay = match delay {
+                    Some(delay) => delay,
                     // Waiting error is unrecoverable, return it
-                    return Err(err);
-                }
+                    None => return Err(err),
+                };
                 // Waiting is failed but we can retry
+                if params.send_events {
+                    callback(ProcessingEvent::RetryScheduled {
+                        message_id,
+                        message,
+                        try_index: next_try_index as u32,
+                        delay,
+                        error: err,
+                    })
+                    .await;
+                }
+                if delay > 0 {
+                    context.env.set_timer(delay as u64).await?;
+                }
+                try_index = next_try_index;
             }
         };
-        try_index = try_index.checked_add(1).unwrap_or(try_index);
     }
 }
@@ -0,0 +1,131 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use crate::error::ClientError;
+use crate::processing::internal::can_retry_more;
+use crate::processing::sequence::MessageSequence;
+use crate::processing::ErrorCode;
+use crate::tvm::{ErrorCode as TvmErrorCode, StdContractError};
+use rand::Rng;
+
+const DEFAULT_MAX_BACKOFF: u32 = 60000;
+
+#[derive(Serialize, Deserialize, ApiType, Debug, Clone, Copy, PartialEq)]
+pub enum RetryCondition {
+    /// Message expired and its local emulation succeeded or failed with the replay
+    /// protection, message expiration or sequence number conflict error.
+    MessageExpired,
+    /// Message expired and its local emulation failed because the destination account
+    /// has not enough balance.
+    LowBalance,
+    /// Message expired and its local emulation failed because the destination account
+    /// doesn't exist.
+    AccountMissing,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Maximum number of retries. Negative value means unlimited retries.
+    /// Default is `NetworkConfig.message_retries_count`.
+    pub max_retries: Option<i8>,
+    /// Delay before the first retry in ms. Each next delay is doubled.
+    /// Default is 0, i.e. the message is sent again immediately.
+    pub backoff_base: Option<u32>,
+    /// Maximum delay between the retries in ms (without `jitter`). Default is 60000.
+    pub max_backoff: Option<u32>,
+    /// Maximum random addition to the delay in ms. Default is 0.
+    pub jitter: Option<u32>,
+    /// Time in ms since the processing start after which no more retries are scheduled.
+    /// Not limited by default.
+    pub max_elapsed_time: Option<u32>,
+    /// Errors triggering the retry. Default is `[MessageExpired]`.
+    pub retry_on: Option<Vec<RetryCondition>>,
+}
+
+impl RetryPolicy {
+    fn condition(err: &ClientError, sequence: Option<&MessageSequence>) -> Option<RetryCondition> {
+        if err.code != ErrorCode::MessageExpired as u32 {
+            return None;
+        }
+        let local_error = &err.data["local_error"];
+        if local_error.is_null() {
+            return Some(RetryCondition::MessageExpired);
+        }
+        let local_exit_code = &local_error["data"]["exit_code"];
+        if local_exit_code == StdContractError::ReplayProtection as i32
+            || local_exit_code == StdContractError::ExtMessageExpired as i32
+            || sequence.map(|sequence| sequence.is_conflict(local_exit_code)).unwrap_or(false)
+        {
+            return Some(RetryCondition::MessageExpired);
+        }
+        match local_error["code"].as_u64() {
+            Some(code) if code == TvmErrorCode::LowBalance as u64 => {
+                Some(RetryCondition::LowBalance)
+            }
+            Some(code) if code == TvmErrorCode::AccountMissing as u64 => {
+                Some(RetryCondition::AccountMissing)
+            }
+            _ => None,
+        }
+    }
+
+    /// Checks if the processing error matches the `retry_on` conditions
+    pub(crate) fn is_retryable(
+        &self,
+        err: &ClientError,
+        sequence: Option<&MessageSequence>,
+    ) -> bool {
+        match Self::condition(err, sequence) {
+            Some(condition) => match &self.retry_on {
+                Some(retry_on) => retry_on.contains(&condition),
+                None => condition == RetryCondition::MessageExpired,
+            },
+            None => false,
+        }
+    }
+
+    /// Exponential backoff delay before the retry with `try_index` (starting from 1)
+    pub(crate) fn backoff(&self, try_index: u8) -> u32 {
+        let base = self.backoff_base.unwrap_or_default() as u64;
+        let max_backoff = self.max_backoff.unwrap_or(DEFAULT_MAX_BACKOFF) as u64;
+        let shift = std::cmp::min(try_index.saturating_sub(1) as u32, 32);
+        std::cmp::min(base << shift, max_backoff) as u32
+    }
+
+    /// Returns the delay in ms before the retry with `try_index` (starting from 1)
+    /// or `None` if the retry is not allowed
+    pub(crate) fn next_delay(
+        &self,
+        default_max_retries: i8,
+        try_index: u8,
+        elapsed: u64,
+    ) -> Option<u32> {
+        let max_retries = self.max_retries.unwrap_or(default_max_retries);
+        if !can_retry_more(try_index.saturating_sub(1), max_retries) {
+            return None;
+        }
+        let delay = match self.jitter.unwrap_or_default() {
+            0 => self.backoff(try_index),
+            jitter => self
+                .backoff(try_index)
+                .saturating_add(rand::thread_rng().gen_range(0, jitter as u64 + 1) as u32),
+        };
+        if let Some(max_elapsed_time) = self.max_elapsed_time {
+            if elapsed + delay as u64 > max_elapsed_time as u64 {
+                return None;
+            }
+        }
+        Some(delay)
+    }
+}
@@ -0,0 +1,298 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use futures::future::Either;
+use futures::Future;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+use tokio::sync::oneshot;
+
+const LANES_COUNT: usize = 2;
+
+#[derive(Serialize, Deserialize, ApiType, Debug, Clone, Copy, PartialEq)]
+pub enum ProcessingPriority {
+    /// Operations the user is waiting for.
+    ///
+    /// They are served first when `processing_concurrency_limit` is reached.
+    Interactive,
+    /// Background operations, e.g. mass payouts.
+    ///
+    /// They are served only when there are no queued interactive operations. A batch
+    /// operation waiting for a shard block gives its slot up to a queued interactive one
+    /// and resumes the waiting after it gets a slot again.
+    Batch,
+}
+
+impl Default for ProcessingPriority {
+    fn default() -> Self {
+        ProcessingPriority::Interactive
+    }
+}
+
+impl ProcessingPriority {
+    fn lane(&self) -> usize {
+        match self {
+            ProcessingPriority::Interactive => 0,
+            ProcessingPriority::Batch => 1,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone, PartialEq)]
+pub struct ProcessingLaneStats {
+    /// Number of operations waiting for a free slot
+    pub queued: u32,
+    /// Number of operations holding a slot
+    pub active: u32,
+    /// Total number of operations that have got a slot
+    pub started: u64,
+    /// Total time spent by the operations in the queue, in milliseconds
+    pub total_queue_delay: u64,
+    /// Maximum time spent by an operation in the queue, in milliseconds
+    pub max_queue_delay: u64,
+    /// Total number of times the operations have given their slots up to interactive ones
+    pub preempted: u64,
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    active: usize,
+    queues: [VecDeque<oneshot::Sender<()>>; LANES_COUNT],
+    stats: [ProcessingLaneStats; LANES_COUNT],
+    // Batch operations which can give their slots up to queued interactive ones
+    preemptible: VecDeque<oneshot::Sender<()>>,
+}
+
+/// Limits the number of simultaneous message sendings and shard block fetchings.
+///
+/// When the limit is reached, the released slot is handed over to the oldest queued
+/// interactive operation, and only then to the batch ones. Queued interactive operation
+/// also asks one of the preemptible batch operations (see `run_preemptible`) to give
+/// its slot up, so it doesn't wait for the whole batch block fetching.
+pub(crate) struct ProcessingScheduler {
+    limit: usize,
+    state: Mutex<SchedulerState>,
+}
+
+pub(crate) struct ProcessingPermit {
+    scheduler: Arc<ProcessingScheduler>,
+    lane: usize,
+}
+
+impl Drop for ProcessingPermit {
+    fn drop(&mut self) {
+        self.scheduler.release(self.lane);
+    }
+}
+
+// Returns the slot back if the waiting is cancelled after the slot has been handed over
+struct QueuedWaiter<'a> {
+    scheduler: &'a Arc<ProcessingScheduler>,
+    receiver: oneshot::Receiver<()>,
+    lane: usize,
+    completed: bool,
+}
+
+impl<'a> Drop for QueuedWaiter<'a> {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.receiver.close();
+            if self.receiver.try_recv().is_ok() {
+                self.scheduler.release(self.lane);
+            }
+        }
+    }
+}
+
+impl ProcessingScheduler {
+    pub fn new(limit: u32) -> Self {
+        Self {
+            limit: limit as usize,
+            state: Mutex::new(SchedulerState::default()),
+        }
+    }
+
+    pub async fn acquire(
+        self: &Arc<Self>,
+        context: &ClientContext,
+        priority: ProcessingPriority,
+    ) -> ProcessingPermit {
+        let lane = priority.lane();
+        let receiver = {
+            let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+            if self.limit == 0 || state.active < self.limit {
+                state.active += 1;
+                state.stats[lane].active += 1;
+                state.stats[lane].started += 1;
+                return ProcessingPermit { scheduler: self.clone(), lane };
+            }
+            let (sender, receiver) = oneshot::channel();
+            state.queues[lane].push_back(sender);
+            if priority == ProcessingPriority::Interactive {
+                Self::preempt_batch(&mut state);
+            }
+            receiver
+        };
+
+        let queued_at = context.env.now_ms();
+        let mut waiter = QueuedWaiter { scheduler: self, receiver, lane, completed: false };
+        // The sender can not be dropped without sending while the scheduler is alive
+        let _ = (&mut waiter.receiver).await;
+        waiter.completed = true;
+
+        let delay = context.env.now_ms().saturating_sub(queued_at);
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let stats = &mut state.stats[lane];
+        stats.started += 1;
+        stats.total_queue_delay += delay;
+        stats.max_queue_delay = std::cmp::max(stats.max_queue_delay, delay);
+
+        ProcessingPermit { scheduler: self.clone(), lane }
+    }
+
+    /// Runs the `operation` holding a processing slot.
+    ///
+    /// Batch operation gives its slot up when an interactive operation is queued: the
+    /// operation is cancelled and started again after the slot is acquired again. So the
+    /// operation must be safe to restart, e.g. waiting for a shard block.
+    pub async fn run_preemptible<F, Fut, R>(
+        self: &Arc<Self>,
+        context: &ClientContext,
+        priority: ProcessingPriority,
+        operation: F,
+    ) -> R
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = R>,
+    {
+        loop {
+            let permit = self.acquire(context, priority).await;
+            if priority != ProcessingPriority::Batch {
+                return operation().await;
+            }
+            let preemption = self.register_preemptible();
+            match futures::future::select(Box::pin(operation()), preemption).await {
+                Either::Left((result, _)) => return result,
+                Either::Right((Ok(()), _)) => {
+                    log::debug!("Batch operation gives its processing slot up to an interactive one");
+                    drop(permit);
+                }
+                // The sender can not be dropped without sending while the scheduler is alive
+                Either::Right((Err(_), operation)) => return operation.await,
+            }
+        }
+    }
+
+    fn register_preemptible(&self) -> oneshot::Receiver<()> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.preemptible.retain(|sender| !sender.is_closed());
+        let (sender, receiver) = oneshot::channel();
+        state.preemptible.push_back(sender);
+        receiver
+    }
+
+    fn preempt_batch(state: &mut SchedulerState) {
+        let batch_lane = ProcessingPriority::Batch.lane();
+        while let Some(sender) = state.preemptible.pop_front() {
+            // Closed sender means that the batch operation has already released its slot
+            if sender.send(()).is_ok() {
+                state.stats[batch_lane].preempted += 1;
+                return;
+            }
+        }
+    }
+
+    /// Checks if some thread has panicked while holding the scheduler state
+    pub fn is_poisoned(&self) -> bool {
+        self.state.is_poisoned()
+    }
+
+    fn release(&self, lane: usize) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        state.stats[lane].active -= 1;
+        for next_lane in 0..LANES_COUNT {
+            while let Some(sender) = state.queues[next_lane].pop_front() {
+                // The slot is handed over, so the total number of active operations is unchanged
+                if sender.send(()).is_ok() {
+                    state.stats[next_lane].active += 1;
+                    return;
+                }
+            }
+        }
+        state.active -= 1;
+    }
+
+    fn stats(&self, priority: ProcessingPriority) -> ProcessingLaneStats {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let lane = priority.lane();
+        ProcessingLaneStats {
+            queued: state.queues[lane].iter().filter(|sender| !sender.is_closed()).count() as u32,
+            ..state.stats[lane].clone()
+        }
+    }
+}
+
+pub(crate) async fn run_preemptible_processing<F, Fut, R>(
+    context: &ClientContext,
+    priority: Option<ProcessingPriority>,
+    operation: F,
+) -> R
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = R>,
+{
+    context
+        .net
+        .processing_scheduler
+        .run_preemptible(context, priority.unwrap_or_default(), operation)
+        .await
+}
+
+pub(crate) async fn acquire_processing_permit(
+    context: &ClientContext,
+    priority: Option<ProcessingPriority>,
+) -> ProcessingPermit {
+    context
+        .net
+        .processing_scheduler
+        .acquire(context, priority.unwrap_or_default())
+        .await
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone, PartialEq)]
+pub struct ResultOfGetSchedulerStats {
+    /// Maximum number of simultaneous processing operations. `0` means no limit.
+    pub concurrency_limit: u32,
+    /// Statistics of the interactive lane
+    pub interactive: ProcessingLaneStats,
+    /// Statistics of the batch lane
+    pub batch: ProcessingLaneStats,
+}
+
+/// Returns statistics of the processing scheduler lanes
+///
+/// Use queue delays to check whether `processing_concurrency_limit` suits the load.
+#[api_function]
+pub async fn get_scheduler_stats(
+    context: Arc<ClientContext>,
+) -> ClientResult<ResultOfGetSchedulerStats> {
+    let scheduler = &context.net.processing_scheduler;
+    Ok(ResultOfGetSchedulerStats {
+        concurrency_limit: scheduler.limit as u32,
+        interactive: scheduler.stats(ProcessingPriority::Interactive),
+        batch: scheduler.stats(ProcessingPriority::Batch),
+    })
+}
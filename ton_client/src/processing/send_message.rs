@@ -12,19 +12,24 @@
  *
  */
 
-use super::blocks_walking::find_last_shard_block;
 use crate::abi::Abi;
 use crate::boc::internal::{deserialize_object_from_boc, DeserializedObject};
-use crate::client::ClientContext;
+use crate::client::{ClientContext, FetchMethod};
 use crate::encoding::{base64_decode, hex_decode};
-use crate::error::{AddNetworkUrl, ClientResult};
-use crate::net::Endpoint;
-use crate::processing::internal::get_message_expiration_time;
+use crate::error::{AddNetworkUrl, ClientError, ClientResult};
+use crate::net::lite::lite_backend;
+use crate::net::transport::get_transport;
+use crate::net::{Endpoint, EndpointStat};
+use crate::processing::internal::{get_local_error, get_message_expiration_time};
+use crate::processing::scheduler::{acquire_processing_permit, ProcessingPriority};
 use crate::processing::types::ProcessingEvent;
 use crate::processing::Error;
+use std::collections::HashMap;
 use std::sync::Arc;
 use ton_block::{Message, MsgAddressInt};
 
+const MAX_SENT_MESSAGES: usize = 100;
+
 #[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone)]
 pub struct ParamsOfSendMessage {
     /// Message BOC.
@@ -46,6 +51,29 @@ pub struct ParamsOfSendMessage {
 
     /// Flag for requesting events sending
     pub send_events: bool,
+
+    /// Number of endpoints the message is sent to in parallel.
+    ///
+    /// Overrides `sending_endpoint_count` of the network config for this call.
+    /// Healthy endpoints (without recent delivery failures) are selected first:
+    /// an endpoint is considered unhealthy after the sending to it fails or the sent
+    /// message is not delivered, until some message sent through it is delivered.
+    /// Sending the same message to several endpoints is safe because the message
+    /// is deduplicated on the server side, while it reduces the probability of
+    /// message loss during endpoint hiccups.
+    pub broadcast_fanout: Option<u32>,
+
+    /// Priority of the sending. Default is `Interactive`.
+    ///
+    /// Matters only when `processing_concurrency_limit` of the network config is reached.
+    pub priority: Option<ProcessingPriority>,
+
+    /// Run the message on the current account state with the local executor before sending.
+    ///
+    /// If the local execution fails, the message is not sent and the
+    /// `MessageRejectedLocally` error with the `exit_code` of the local execution
+    /// is returned. Default is `false`.
+    pub validate_locally: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, ApiType, Default, PartialEq, Debug)]
@@ -62,6 +90,18 @@ pub struct ResultOfSendMessage {
     /// This list id must be used as a parameter of the
     /// `wait_for_transaction`.
     pub sending_endpoints: Vec<String>,
+
+    /// The list of endpoints which acknowledged the message receipt.
+    ///
+    /// Subset of `sending_endpoints`. Endpoints missing here accepted the request
+    /// but responded with an error, so the message may or may not have been
+    /// delivered through them.
+    pub acknowledged_endpoints: Vec<String>,
+}
+
+struct SentMessage {
+    address: String,
+    acknowledged: bool,
 }
 
 #[derive(Clone)]
@@ -92,7 +132,7 @@ impl SendingMessage {
         let message_expiration_time =
             get_message_expiration_time(context.clone(), abi, &serialized).await?;
         if let Some(message_expiration_time) = message_expiration_time {
-            if message_expiration_time <= context.env.now_ms() {
+            if message_expiration_time <= context.network_now_ms() {
                 return Err(Error::message_already_expired());
             }
         }
@@ -106,6 +146,14 @@ impl SendingMessage {
         })
     }
 
+    /// Executes the message on the current state of the destination account
+    async fn validate_locally(&self, context: &Arc<ClientContext>) -> ClientResult<()> {
+        let block_time = (context.network_now_ms() / 1000) as u32;
+        get_local_error(context.clone(), &self.dst, self.serialized.clone(), block_time, true)
+            .await
+            .map_err(|err| Error::message_rejected_locally(&self.id, err))
+    }
+
     async fn prepare_to_send<F: futures::Future<Output = ()> + Send>(
         &self,
         context: &Arc<ClientContext>,
@@ -114,8 +162,9 @@ impl SendingMessage {
         if let Some(callback) = callback {
             callback(ProcessingEvent::WillFetchFirstBlock {}).await;
         }
-        let shard_block_id = match find_last_shard_block(&context, &self.dst, None).await {
-            Ok(block) => block.to_string(),
+        let shard_block = get_transport(context).get_last_block_id(&self.dst).await;
+        let shard_block_id = match shard_block {
+            Ok(block) => block,
             Err(err) => {
                 if let Some(callback) = &callback {
                     callback(ProcessingEvent::FetchFirstBlockFailed { error: err.clone() }).await;
@@ -134,10 +183,82 @@ impl SendingMessage {
         Ok(shard_block_id)
     }
 
-    async fn send(&self, context: &Arc<ClientContext>) -> ClientResult<Vec<String>> {
+    async fn send(
+        &self,
+        context: &Arc<ClientContext>,
+        broadcast_fanout: Option<u32>,
+    ) -> ClientResult<Vec<SentMessage>> {
+        if let Some(relayer_url) = &context.config.network.relayer_url {
+            return self.send_to_relayer(context, relayer_url).await;
+        }
+        if let Some(lite) = lite_backend(context) {
+            let address = lite.send_message(&self.body).await?;
+            return Ok(vec![SentMessage {
+                address,
+                acknowledged: true,
+            }]);
+        }
         let addresses = context.get_server_link()?.get_addresses_for_sending().await;
-        let mut last_result = None::<ClientResult<String>>;
-        let succedeed_limit = context.config.network.sending_endpoint_count as usize;
+        self.send_to_addresses(context, &addresses, broadcast_fanout).await
+    }
+
+    /// Hands the message over to the relayer instead of the endpoints
+    async fn send_to_relayer(
+        &self,
+        context: &Arc<ClientContext>,
+        relayer_url: &str,
+    ) -> ClientResult<Vec<SentMessage>> {
+        let mut headers = HashMap::new();
+        headers.insert("Content-Type".to_string(), "application/json".to_string());
+        if let Some(access_key) = &context.config.network.relayer_access_key {
+            headers.insert("Authorization".to_string(), format!("Bearer {}", access_key));
+        }
+        let request = json!({
+            "id": base64::encode(&hex_decode(&self.id)?),
+            "boc": self.serialized,
+        });
+        let response = context
+            .env
+            .fetch(
+                relayer_url,
+                FetchMethod::Post,
+                Some(headers),
+                Some(request.to_string()),
+                context.config.network.query_timeout,
+            )
+            .await
+            .map_err(|err| Error::relayer_send_failed(err, &self.id, relayer_url))?;
+        if !(200..300).contains(&response.status) {
+            return Err(Error::relayer_send_failed(
+                format!("relayer responded with code {}: {}", response.status, response.body),
+                &self.id,
+                relayer_url,
+            ));
+        }
+        Ok(vec![SentMessage {
+            address: relayer_url.to_string(),
+            acknowledged: true,
+        }])
+    }
+
+    /// Sends the message to the first `broadcast_fanout` of `addresses` accepting it.
+    /// Addresses with recent delivery failures are tried last.
+    async fn send_to_addresses(
+        &self,
+        context: &Arc<ClientContext>,
+        addresses: &[String],
+        broadcast_fanout: Option<u32>,
+    ) -> ClientResult<Vec<SentMessage>> {
+        let link = context.get_server_link()?;
+        let mut addresses = addresses.to_vec();
+        link.order_by_delivery_health(&mut addresses).await;
+        let mut last_result = None::<ClientResult<SentMessage>>;
+        let succedeed_limit = std::cmp::max(
+            broadcast_fanout
+                .filter(|fanout| *fanout > 0)
+                .unwrap_or(context.config.network.sending_endpoint_count as u32) as usize,
+            1,
+        );
         let mut succeeded = Vec::new();
         'sending: for selected_addresses in addresses.chunks(succedeed_limit) {
             let mut futures = vec![];
@@ -148,24 +269,21 @@ impl SendingMessage {
                     message.send_to_address(context, &address).await
                 }));
             }
-            for result in futures::future::join_all(futures).await {
-                if let Ok(address) = &result {
-                    succeeded.push(address.clone());
-                    if succeeded.len() >= succedeed_limit {
-                        break 'sending;
-                    }
-                }
-                // If one of the endpoints responds with clock out of sync error
-                // we leave this error as a final error.
-                // This is required by out of sync unit test.
-                let last_result_is_out_of_sync = if let Some(Err(err)) = &last_result {
-                    err.code == crate::net::ErrorCode::ClockOutOfSync as u32
-                } else {
-                    false
-                };
-                if !last_result_is_out_of_sync {
-                    last_result = Some(result);
+            let mut failed = Vec::new();
+            let results = futures::future::join_all(futures).await;
+            for (address, result) in selected_addresses.iter().zip(results) {
+                if let Ok(sent) = result {
+                    succeeded.push(sent);
+                    continue;
                 }
+                failed.push(address.clone());
+                last_result = Some(result);
+            }
+            if !failed.is_empty() {
+                link.update_stat(&failed, EndpointStat::MessageUndelivered).await;
+            }
+            if succeeded.len() >= succedeed_limit {
+                break 'sending;
             }
         }
         if succeeded.len() > 0 {
@@ -182,7 +300,7 @@ impl SendingMessage {
         &self,
         context: Arc<ClientContext>,
         address: &str,
-    ) -> ClientResult<String> {
+    ) -> ClientResult<SentMessage> {
         let endpoint =
             Endpoint::resolve(&context.env, &context.config.network, address).await?;
 
@@ -193,7 +311,10 @@ impl SendingMessage {
             .await
             .add_endpoint_from_context(&context, &endpoint)
             .await
-            .map(|_| address.to_string())
+            .map(|post_error| SentMessage {
+                address: address.to_string(),
+                acknowledged: post_error.is_none(),
+            })
     }
 }
 
@@ -203,6 +324,9 @@ pub async fn send_message<F: futures::Future<Output = ()> + Send>(
     callback: impl Fn(ProcessingEvent) -> F + Send + Sync + Clone,
 ) -> ClientResult<ResultOfSendMessage> {
     let message = SendingMessage::new(&context, &params.message, params.abi.as_ref()).await?;
+    if params.validate_locally.unwrap_or_default() {
+        message.validate_locally(&context).await?;
+    }
 
     let callback = if params.send_events {
         Some(callback)
@@ -210,8 +334,10 @@ pub async fn send_message<F: futures::Future<Output = ()> + Send>(
         None
     };
 
+    let permit = acquire_processing_permit(&context, params.priority).await;
     let shard_block_id = message.prepare_to_send(&context, &callback).await?;
-    let result = message.send(&context).await;
+    let result = message.send(&context, params.broadcast_fanout).await;
+    drop(permit);
     if let Some(callback) = &callback {
         callback(match &result {
             Ok(_) => ProcessingEvent::DidSend {
@@ -228,8 +354,130 @@ pub async fn send_message<F: futures::Future<Output = ()> + Send>(
         })
         .await;
     }
-    result.map(|sending_endpoints| ResultOfSendMessage {
+    if result.is_ok() {
+        *context.last_sent_block.write().await = Some(shard_block_id.clone());
+    }
+    result.map(|sent| ResultOfSendMessage {
         shard_block_id,
-        sending_endpoints,
+        acknowledged_endpoints: sent
+            .iter()
+            .filter(|sent| sent.acknowledged)
+            .map(|sent| sent.address.clone())
+            .collect(),
+        sending_endpoints: sent.into_iter().map(|sent| sent.address).collect(),
+    })
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone)]
+pub struct ParamsOfSendMessages {
+    /// Message BOCs. Maximum number of the messages is 100.
+    pub messages: Vec<String>,
+
+    /// Optional ABI of the messages.
+    ///
+    /// If specified, the messages with the `expire` header are checked against
+    /// the current time and the already expired messages are not sent.
+    pub abi: Option<Abi>,
+
+    /// Number of endpoints each message is sent to in parallel.
+    ///
+    /// Overrides `sending_endpoint_count` of the network config for this call.
+    pub broadcast_fanout: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, PartialEq, Debug, Clone)]
+pub struct MessageSendingResult {
+    /// Message hash. Absent if the message BOC can't be parsed.
+    pub message_id: Option<String>,
+
+    /// The list of endpoints to which the message was sent.
+    pub sending_endpoints: Vec<String>,
+
+    /// Sending error. Absent if the message was sent.
+    pub error: Option<ClientError>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, PartialEq, Debug)]
+pub struct ResultOfSendMessages {
+    /// Sending results in the order of `messages`.
+    pub results: Vec<MessageSendingResult>,
+}
+
+async fn send_one_of_many(
+    context: &Arc<ClientContext>,
+    serialized: &str,
+    abi: Option<&Abi>,
+    addresses: &[String],
+    broadcast_fanout: Option<u32>,
+) -> MessageSendingResult {
+    let message = match SendingMessage::new(context, serialized, abi).await {
+        Ok(message) => message,
+        Err(err) => {
+            return MessageSendingResult {
+                message_id: None,
+                sending_endpoints: Vec::new(),
+                error: Some(err),
+            }
+        }
+    };
+    let result = if addresses.is_empty() {
+        message.send(context, broadcast_fanout).await
+    } else {
+        message.send_to_addresses(context, addresses, broadcast_fanout).await
+    };
+    match result {
+        Ok(sent) => MessageSendingResult {
+            message_id: Some(message.id),
+            sending_endpoints: sent.into_iter().map(|sent| sent.address).collect(),
+            error: None,
+        },
+        Err(err) => MessageSendingResult {
+            message_id: Some(message.id),
+            sending_endpoints: Vec::new(),
+            error: Some(err),
+        },
+    }
+}
+
+/// Sends the batch of messages to the network
+///
+/// Messages are sent concurrently and distributed across all the sending endpoints:
+/// each next message starts with the next endpoint. Unlike `send_message`, the shard
+/// block of the destination is not fetched before the sending and no events are reported,
+/// so the messages can't be passed to `wait_for_transaction`. Use `monitor_messages`
+/// to track their processing.
+///
+/// Returns the result of each message, the failure of one message doesn't affect the others.
+#[api_function]
+pub async fn send_messages(
+    context: Arc<ClientContext>,
+    params: ParamsOfSendMessages,
+) -> ClientResult<ResultOfSendMessages> {
+    if params.messages.len() > MAX_SENT_MESSAGES {
+        return Err(Error::too_many_messages(params.messages.len(), MAX_SENT_MESSAGES));
+    }
+    // Relayer and lite backend send the messages by their own
+    let addresses = if context.config.network.relayer_url.is_some()
+        || lite_backend(&context).is_some()
+    {
+        Vec::new()
+    } else {
+        context.get_server_link()?.get_addresses_for_sending().await
+    };
+    let broadcast_fanout = params.broadcast_fanout;
+    let futures = params.messages.iter().enumerate().map(|(index, message)| {
+        let mut addresses = addresses.clone();
+        if !addresses.is_empty() {
+            let offset = index % addresses.len();
+            addresses.rotate_left(offset);
+        }
+        let context = context.clone();
+        let abi = params.abi.clone();
+        async move {
+            send_one_of_many(&context, message, abi.as_ref(), &addresses, broadcast_fanout).await
+        }
+    });
+    Ok(ResultOfSendMessages {
+        results: futures::future::join_all(futures).await,
     })
 }
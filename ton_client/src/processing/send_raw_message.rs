@@ -0,0 +1,181 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use crate::abi::{Abi, ParamsOfDecodeMessage};
+use crate::boc::internal::deserialize_object_from_boc;
+use crate::client::ClientContext;
+use crate::error::{AddNetworkUrl, ClientResult};
+use crate::processing::internal::can_retry_expired_message;
+use crate::processing::scheduler::ProcessingPriority;
+use crate::processing::{
+    send_message, wait_for_transaction, Error, ErrorCode, ParamsOfSendMessage,
+    ParamsOfWaitForTransaction, ProcessingEvent, ResultOfProcessMessage, ResultOfSendMessage,
+    TransactionFinality,
+};
+use std::sync::Arc;
+use ton_block::{Message, Serializable};
+use ton_types::AccountId;
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone)]
+pub struct ParamsOfSendRawMessage {
+    /// External inbound message BOC encoded with `base64`.
+    pub message: String,
+
+    /// Optional ABI of the destination contract.
+    ///
+    /// If specified, the message body must be decodable with this ABI, the `expire`
+    /// header is used as the message expiration time and the output messages of the
+    /// result transaction are decoded.
+    pub abi: Option<Abi>,
+
+    /// Message expiration time (unix time in seconds) known from the message producer.
+    ///
+    /// Overrides the `expire` header decoded with `abi`. If the transaction is not found
+    /// within `message_processing_timeout`, the same message is sent again until it expires.
+    pub expire: Option<u32>,
+
+    /// Expected message destination address.
+    ///
+    /// If specified, the message is rejected with `InvalidMessageBoc` error when it is
+    /// addressed to another account.
+    pub dst: Option<String>,
+
+    /// Flag for requesting events sending
+    pub send_events: bool,
+
+    /// Priority of the message sending and the transaction waiting. Default is `Interactive`.
+    ///
+    /// Matters only when `processing_concurrency_limit` of the network config is reached.
+    pub priority: Option<ProcessingPriority>,
+
+    /// Finality the transaction must reach before the function resolves.
+    pub finality: Option<TransactionFinality>,
+}
+
+/// Checks the message structure and returns its expiration time in ms
+async fn check_raw_message(
+    context: &Arc<ClientContext>,
+    params: &ParamsOfSendRawMessage,
+) -> ClientResult<Option<u64>> {
+    let message = deserialize_object_from_boc::<Message>(context, &params.message, "message")
+        .await?
+        .object;
+    let header = message
+        .ext_in_header()
+        .ok_or_else(|| Error::invalid_message_boc("message is not an external inbound message"))?;
+
+    if let Some(dst) = &params.dst {
+        let expected = crate::encoding::account_decode(dst)?;
+        if header.dst != expected {
+            return Err(Error::invalid_message_boc(format!(
+                "message destination {} differs from the expected {}",
+                header.dst, expected
+            )));
+        }
+    }
+
+    if let Some(state_init) = message.state_init() {
+        let state_init_hash = state_init
+            .serialize()
+            .map_err(|err| Error::invalid_message_boc(err))?
+            .repr_hash();
+        if header.dst.address() != AccountId::from(state_init_hash) {
+            return Err(Error::invalid_message_boc(
+                "message destination doesn't match the attached state init",
+            ));
+        }
+    }
+
+    let abi_expire = match &params.abi {
+        Some(abi) => crate::abi::decode_message(
+            context.clone(),
+            ParamsOfDecodeMessage {
+                abi: abi.clone(),
+                message: params.message.clone(),
+                partial: None,
+            },
+        )
+        .await
+        .map_err(|err| Error::invalid_message_boc(err.message))?
+        .header
+        .and_then(|header| header.expire),
+        None => None,
+    };
+    let expire = params.expire.or(abi_expire).map(|expire| expire as u64 * 1000);
+    if let Some(expire) = expire {
+        if expire <= context.env.now_ms() {
+            return Err(Error::message_already_expired());
+        }
+    }
+    Ok(expire)
+}
+
+pub async fn send_raw_message<F: futures::Future<Output = ()> + Send>(
+    context: Arc<ClientContext>,
+    params: ParamsOfSendRawMessage,
+    callback: impl Fn(ProcessingEvent) -> F + Send + Sync + 'static,
+) -> ClientResult<ResultOfProcessMessage> {
+    let expire = check_raw_message(&context, &params).await?;
+
+    let mut try_index = 0;
+    loop {
+        let ResultOfSendMessage {
+            shard_block_id,
+            sending_endpoints,
+            ..
+        } = send_message(
+            context.clone(),
+            ParamsOfSendMessage {
+                message: params.message.clone(),
+                abi: params.abi.clone(),
+                send_events: params.send_events,
+                broadcast_fanout: None,
+                priority: params.priority,
+                validate_locally: None,
+            },
+            &callback,
+        )
+        .await
+        .add_network_url_from_context(&context)
+        .await?;
+
+        let wait_for = wait_for_transaction(
+            context.clone(),
+            ParamsOfWaitForTransaction {
+                message: params.message.clone(),
+                send_events: params.send_events,
+                abi: params.abi.clone(),
+                shard_block_id,
+                sending_endpoints: Some(sending_endpoints),
+                priority: params.priority,
+                finality: params.finality.clone(),
+            },
+            &callback,
+        )
+        .await
+        .add_network_url_from_context(&context)
+        .await;
+
+        // The message can't be re-encoded, so the same message is sent again
+        // while it is not expired
+        match wait_for {
+            Err(err)
+                if err.code == ErrorCode::TransactionWaitTimeout as u32
+                    && expire.map(|expire| expire > context.env.now_ms()).unwrap_or(false)
+                    && can_retry_expired_message(&context, try_index) => {}
+            result => return result,
+        }
+        try_index = try_index.checked_add(1).unwrap_or(try_index);
+    }
+}
@@ -0,0 +1,205 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use crate::abi::{Abi, CallSet, ParamsOfEncodeMessage, Signer};
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use crate::processing::Error;
+use crate::tvm::accounts_cache::fetch_account_boc;
+use crate::tvm::{run_get, run_tvm, ParamsOfRunGet, ParamsOfRunTvm};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError};
+
+const DEFAULT_GET_METHOD: &str = "seqno";
+const DEFAULT_INPUT_NAME: &str = "seqno";
+/// Exit code of the standard wallet contracts on the seqno mismatch
+const DEFAULT_CONFLICT_EXIT_CODE: i32 = 33;
+
+#[derive(Serialize, Deserialize, ApiType, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum SequenceProvider {
+    /// Sequence number is returned by the get-method of the destination contract,
+    /// like `seqno` of the standard wallet contracts.
+    GetMethod {
+        /// Get-method name. Default is `seqno`.
+        method: Option<String>,
+    },
+    /// Sequence number is returned by the ABI function of the destination contract.
+    /// The function is called locally with the `abi` of the processed message.
+    AbiFunction {
+        /// Function name.
+        function_name: String,
+        /// Name of the function output value with the sequence number.
+        /// The first output value is used by default.
+        output_name: Option<String>,
+    },
+}
+
+impl Default for SequenceProvider {
+    fn default() -> Self {
+        SequenceProvider::GetMethod { method: None }
+    }
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone, PartialEq)]
+pub struct MessageSequence {
+    /// Source of the destination contract sequence number.
+    pub provider: SequenceProvider,
+    /// Name of the `call_set` input parameter receiving the sequence number.
+    /// Default is `seqno`.
+    pub input_name: Option<String>,
+    /// Contract exit codes meaning the sequence number mismatch.
+    ///
+    /// If the expired message is rejected by the contract with one of these codes,
+    /// the sequence number is read again and the message is sent once more.
+    /// Default is `[33]` (seqno mismatch of the standard wallet contracts).
+    pub conflict_exit_codes: Option<Vec<i32>>,
+}
+
+impl MessageSequence {
+    pub(crate) fn is_conflict(&self, exit_code: &Value) -> bool {
+        let exit_code = match exit_code.as_i64() {
+            Some(exit_code) => exit_code as i32,
+            None => return false,
+        };
+        match &self.conflict_exit_codes {
+            Some(codes) => codes.contains(&exit_code),
+            None => exit_code == DEFAULT_CONFLICT_EXIT_CODE,
+        }
+    }
+}
+
+/// Serializes the message sendings to the same destination
+#[derive(Default)]
+pub(crate) struct SequenceLocks {
+    locks: std::sync::Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl SequenceLocks {
+    /// Returns the lock of the destination. Locks that are not held by anyone are dropped
+    pub fn get(&self, address: &str) -> Arc<tokio::sync::Mutex<()>> {
+        let mut locks = self.locks.lock().unwrap_or_else(PoisonError::into_inner);
+        locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+        locks.entry(address.to_string()).or_default().clone()
+    }
+
+    /// Checks if some thread has panicked while holding the locks map
+    pub fn is_poisoned(&self) -> bool {
+        self.locks.is_poisoned()
+    }
+}
+
+fn parse_sequence(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(number) => number.as_u64(),
+        Value::String(string) if string.starts_with("0x") => {
+            u64::from_str_radix(&string[2..], 16).ok()
+        }
+        Value::String(string) => string.parse().ok(),
+        _ => None,
+    }
+}
+
+async fn read_sequence(
+    context: &Arc<ClientContext>,
+    address: &str,
+    abi: &Abi,
+    provider: &SequenceProvider,
+) -> ClientResult<u64> {
+    // The cached state can be older than the last processed message
+    context.net.accounts_cache.remove(address).await;
+    let account = fetch_account_boc(context, &crate::encoding::account_decode(address)?).await?;
+
+    let value = match provider {
+        SequenceProvider::GetMethod { method } => {
+            let result = run_get(
+                context.clone(),
+                ParamsOfRunGet {
+                    account,
+                    function_name: method.as_deref().unwrap_or(DEFAULT_GET_METHOD).to_string(),
+                    ..Default::default()
+                },
+            )
+            .await?;
+            result.output[0].clone()
+        }
+        SequenceProvider::AbiFunction {
+            function_name,
+            output_name,
+        } => {
+            let message = crate::abi::encode_message::encode_message_internal(
+                context.clone(),
+                ParamsOfEncodeMessage {
+                    abi: abi.clone(),
+                    address: Some(address.to_string()),
+                    call_set: CallSet::some_with_function(function_name),
+                    signer: Signer::None,
+                    ..Default::default()
+                },
+            )
+            .await?
+            .message;
+            let result = run_tvm(
+                context.clone(),
+                ParamsOfRunTvm {
+                    message,
+                    account,
+                    abi: Some(abi.clone()),
+                    ..Default::default()
+                },
+            )
+            .await?;
+            let output = result.decoded.and_then(|decoded| decoded.output).unwrap_or_default();
+            match output_name {
+                Some(name) => output[name].clone(),
+                None => output
+                    .as_object()
+                    .and_then(|output| output.values().next().cloned())
+                    .unwrap_or_default(),
+            }
+        }
+    };
+
+    parse_sequence(&value)
+        .ok_or_else(|| Error::invalid_data(format!("invalid sequence number {}", value)))
+}
+
+/// Reads the destination sequence number and puts it into the message `call_set` input
+pub(crate) async fn apply_sequence(
+    context: &Arc<ClientContext>,
+    sequence: &MessageSequence,
+    encode_params: &mut ParamsOfEncodeMessage,
+) -> ClientResult<()> {
+    let address = encode_params
+        .address
+        .clone()
+        .ok_or_else(Error::message_has_not_destination_address)?;
+    let seqno = read_sequence(context, &address, &encode_params.abi, &sequence.provider)
+        .await
+        .map_err(|err| Error::sequence_read_failed(&address, err))?;
+
+    let call_set = encode_params
+        .call_set
+        .as_mut()
+        .ok_or_else(|| Error::invalid_data("`call_set` is required to pass the sequence number"))?;
+    let mut input = call_set.input.take().unwrap_or_else(|| json!({}));
+    if !input.is_object() {
+        return Err(Error::invalid_data("`call_set.input` must be an object"));
+    }
+    input[sequence.input_name.as_deref().unwrap_or(DEFAULT_INPUT_NAME)] = seqno.into();
+    call_set.input = Some(input);
+
+    Ok(())
+}
@@ -1,3 +1,5 @@
+use crate::client::{ClientConfig, ClientContext, NetworkMock};
+use crate::net::NetworkConfig;
 use crate::abi::{
     CallSet, DecodedMessageBody, DeploySet, FunctionHeader, MessageBodyType, ParamsOfEncodeMessage,
     Signer,
@@ -7,13 +9,16 @@ use crate::tvm::{AccountForExecutor, ParamsOfRunExecutor, ResultOfRunExecutor};
 use crate::json_interface::modules::ProcessingModule;
 use crate::processing::types::DecodedOutput;
 use crate::processing::{
-    ErrorCode, ParamsOfProcessMessage, ParamsOfSendMessage, ParamsOfWaitForTransaction,
-    ProcessingEvent, ProcessingResponseType,
+    ErrorCode, MessageSequence, ParamsOfDecodeTree, ParamsOfProcessMessage, ParamsOfSendMessage,
+    ParamsOfSendRawMessage, ParamsOfWaitForTransaction, ProcessingEvent, ProcessingPriority,
+    ProcessingResponseType, ResultOfGetSchedulerStats, ResultOfProcessMessage, SequenceProvider,
+    TransactionFinality, TreeDecodingDepth,
 };
 use crate::tests::{TestClient, EVENTS, HELLO};
 use crate::tvm::ErrorCode as TvmErrorCode;
 use crate::utils::conversion::abi_uint;
 use api_info::ApiModule;
+use std::sync::Arc;
 
 fn processing_event_name(e: Option<&ProcessingEvent>) -> &str {
     if let Some(e) = e {
@@ -22,7 +27,10 @@ fn processing_event_name(e: Option<&ProcessingEvent>) -> &str {
             ProcessingEvent::FetchFirstBlockFailed { .. } => "FetchFirstBlockFailed",
             ProcessingEvent::FetchNextBlockFailed { .. } => "FetchNextBlockFailed",
             ProcessingEvent::MessageExpired { .. } => "MessageExpired",
+            ProcessingEvent::NoNewBlocks { .. } => "NoNewBlocks",
+            ProcessingEvent::RetryScheduled { .. } => "RetryScheduled",
             ProcessingEvent::SendFailed { .. } => "SendFailed",
+            ProcessingEvent::TransactionProofed { .. } => "TransactionProofed",
             ProcessingEvent::WillFetchFirstBlock { .. } => "WillFetchFirstBlock",
             ProcessingEvent::WillFetchNextBlock { .. } => "WillFetchNextBlock",
             ProcessingEvent::WillSend { .. } => "WillSend",
@@ -91,6 +99,7 @@ async fn test_wait_message() {
         }),
         signer: Signer::Keys { keys: keys.clone() },
         processing_try_index: None,
+        expiration: None,
     };
 
     let encoded = client.encode_message(encode_params.clone()).await.unwrap();
@@ -106,11 +115,38 @@ async fn test_wait_message() {
                 message: encoded.message.clone(),
                 send_events: true,
                 abi: Some(abi.clone()),
+                broadcast_fanout: Some(2),
+                validate_locally: None,
+                ..Default::default()
             },
             callback.clone(),
         )
         .await
         .unwrap();
+    assert!(result
+        .acknowledged_endpoints
+        .iter()
+        .all(|endpoint| result.sending_endpoints.contains(endpoint)));
+
+    let token: crate::net::ResultOfGetConsistencyToken = client
+        .request_async("net.get_consistency_token", json!(null))
+        .await
+        .unwrap();
+    assert_eq!(token.min_block.as_ref(), Some(&result.shard_block_id));
+    let accounts: crate::net::ResultOfQueryCollection = client
+        .request_async(
+            "net.query_collection",
+            crate::net::ParamsOfQueryCollection {
+                collection: "accounts".to_owned(),
+                filter: Some(json!({ "id": { "eq": encoded.address } })),
+                result: "id".to_owned(),
+                min_block: token.min_block,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(accounts.result.len(), 1);
 
     let output = wait_for_transaction
         .call_with_callback(
@@ -120,6 +156,8 @@ async fn test_wait_message() {
                 send_events: true,
                 abi: Some(abi.clone()),
                 sending_endpoints: Some(result.sending_endpoints),
+                finality: None,
+                ..Default::default()
             },
             callback.clone(),
         )
@@ -132,6 +170,7 @@ async fn test_wait_message() {
         Some(DecodedOutput {
             out_messages: vec![],
             output: None,
+            tree_messages: None,
         })
     );
     assert_events(
@@ -179,6 +218,7 @@ async fn test_process_message() {
         }),
         signer: Signer::Keys { keys: keys.clone() },
         processing_try_index: None,
+        expiration: None,
     };
 
     let encoded = client.encode_message(encode_params.clone()).await.unwrap();
@@ -192,6 +232,10 @@ async fn test_process_message() {
             ParamsOfProcessMessage {
                 message_encode_params: encode_params,
                 send_events: true,
+                sequence: None,
+                decode_tree: None,
+                retry_policy: None,
+                ..Default::default()
             },
             callback,
         )
@@ -205,6 +249,7 @@ async fn test_process_message() {
         Some(DecodedOutput {
             out_messages: vec![],
             output: None,
+            tree_messages: None,
         })
     );
     assert_events(
@@ -242,8 +287,13 @@ async fn test_process_message() {
                     ),
                     signer: Signer::Keys { keys: keys.clone() },
                     processing_try_index: None,
+                    expiration: None,
                 },
                 send_events: true,
+                sequence: None,
+                decode_tree: None,
+                retry_policy: None,
+                ..Default::default()
             },
             callback,
         )
@@ -259,17 +309,20 @@ async fn test_process_message() {
                     name: "EventThrown".into(),
                     value: Some(json!({"id": abi_uint(1, 256)})),
                     header: None,
+                    diagnostics: None,
                 }),
                 Some(DecodedMessageBody {
                     body_type: MessageBodyType::Output,
                     name: "returnValue".into(),
                     value: Some(json!({"value0": abi_uint(1, 256)})),
                     header: None,
+                    diagnostics: None,
                 })
             ],
             output: Some(json!({
                 "value0": abi_uint(1, 256)
             })),
+            tree_messages: None,
         })
     );
 
@@ -314,6 +367,7 @@ async fn test_error_resolving() {
         }),
         signer: Signer::Keys { keys: keys.clone() },
         processing_try_index: None,
+        expiration: None,
         address: None,
         call_set: CallSet::some_with_function("constructor"),
     };
@@ -329,6 +383,7 @@ async fn test_error_resolving() {
         deploy_set: None,
         signer: Signer::Keys { keys },
         processing_try_index: None,
+        expiration: None,
         address: Some(address.clone()),
         call_set: Some(CallSet {
             function_name: "sendAllMoney".to_owned(),
@@ -349,6 +404,10 @@ async fn test_error_resolving() {
             ParamsOfProcessMessage {
                 message_encode_params: deploy_params.clone(),
                 send_events: false,
+                sequence: None,
+                decode_tree: None,
+                retry_policy: None,
+                ..Default::default()
             },
             TestClient::default_callback,
         )
@@ -376,6 +435,10 @@ async fn test_error_resolving() {
             ParamsOfProcessMessage {
                 message_encode_params: deploy_params.clone(),
                 send_events: false,
+                sequence: None,
+                decode_tree: None,
+                retry_policy: None,
+                ..Default::default()
             },
             TestClient::default_callback,
         )
@@ -409,6 +472,10 @@ async fn test_error_resolving() {
             ParamsOfProcessMessage {
                 message_encode_params: run_params.clone(),
                 send_events: false,
+                sequence: None,
+                decode_tree: None,
+                retry_policy: None,
+                ..Default::default()
             },
             TestClient::default_callback,
         )
@@ -432,6 +499,10 @@ async fn test_error_resolving() {
             ParamsOfProcessMessage {
                 message_encode_params: deploy_params.clone(),
                 send_events: false,
+                sequence: None,
+                decode_tree: None,
+                retry_policy: None,
+                ..Default::default()
             },
             TestClient::default_callback,
         )
@@ -445,6 +516,10 @@ async fn test_error_resolving() {
             ParamsOfProcessMessage {
                 message_encode_params: run_params.clone(),
                 send_events: false,
+                sequence: None,
+                decode_tree: None,
+                retry_policy: None,
+                ..Default::default()
             },
             TestClient::default_callback,
         )
@@ -489,6 +564,7 @@ async fn test_retries() {
                 call_set: CallSet::some_with_function("constructor"),
                 signer: Signer::Keys { keys: keys.clone() },
                 processing_try_index: None,
+                expiration: None,
                 address: None,
             },
             None,
@@ -511,9 +587,14 @@ async fn test_retries() {
                             call_set: CallSet::some_with_function("touch"),
                             deploy_set: None,
                             processing_try_index: None,
+                            expiration: None,
                             signer: Signer::Keys { keys },
                         },
                         send_events: false,
+                        sequence: None,
+                        decode_tree: None,
+                        retry_policy: None,
+                        ..Default::default()
                     },
                     TestClient::default_callback,
                 )
@@ -539,6 +620,7 @@ async fn test_fees() {
                 call_set: CallSet::some_with_function("constructor"),
                 signer: Signer::Keys { keys: keys.clone() },
                 processing_try_index: None,
+                expiration: None,
                 address: None,
             },
             None,
@@ -558,6 +640,7 @@ async fn test_fees() {
         ),
         deploy_set: None,
         processing_try_index: None,
+        expiration: None,
         signer: Signer::Keys { keys },
     };
 
@@ -588,6 +671,10 @@ async fn test_fees() {
             ParamsOfProcessMessage {
                 message_encode_params: params,
                 send_events: false,
+                sequence: None,
+                decode_tree: None,
+                retry_policy: None,
+                ..Default::default()
             },
             TestClient::default_callback,
         ).await.unwrap();
@@ -607,3 +694,797 @@ async fn test_fees() {
     assert!(local_result.fees.in_msg_fwd_fee > 0);
     assert!(local_result.fees.total_account_fees > 0);
 }
+
+#[tokio::test(core_threads = 2)]
+async fn test_scheduler_priority_lanes() {
+    let client = TestClient::new_with_config(json!({
+        "network": {
+            "processing_concurrency_limit": 1,
+        }
+    }));
+    let context = client.context();
+    let scheduler = context.net.processing_scheduler.clone();
+    let permit = scheduler.acquire(&context, ProcessingPriority::Batch).await;
+
+    let order = std::sync::Arc::new(tokio::sync::Mutex::new(Vec::new()));
+    let spawn = |priority: ProcessingPriority| {
+        let context = context.clone();
+        let scheduler = scheduler.clone();
+        let order = order.clone();
+        tokio::spawn(async move {
+            let _permit = scheduler.acquire(&context, priority).await;
+            order.lock().await.push(priority);
+        })
+    };
+
+    let batch = spawn(ProcessingPriority::Batch);
+    tokio::time::delay_for(std::time::Duration::from_millis(100)).await;
+    let interactive = spawn(ProcessingPriority::Interactive);
+    tokio::time::delay_for(std::time::Duration::from_millis(100)).await;
+
+    let stats: ResultOfGetSchedulerStats = client
+        .request_async("processing.get_scheduler_stats", json!(null))
+        .await
+        .unwrap();
+    assert_eq!(stats.concurrency_limit, 1);
+    assert_eq!(stats.batch.active, 1);
+    assert_eq!(stats.batch.queued, 1);
+    assert_eq!(stats.interactive.queued, 1);
+
+    drop(permit);
+    batch.await.unwrap();
+    interactive.await.unwrap();
+
+    // Queued interactive operation is served before the batch one queued earlier
+    assert_eq!(
+        *order.lock().await,
+        vec![ProcessingPriority::Interactive, ProcessingPriority::Batch]
+    );
+
+    let stats: ResultOfGetSchedulerStats = client
+        .request_async("processing.get_scheduler_stats", json!(null))
+        .await
+        .unwrap();
+    assert_eq!(stats.interactive.active, 0);
+    assert_eq!(stats.interactive.started, 1);
+    assert_eq!(stats.batch.active, 0);
+    assert_eq!(stats.batch.started, 2);
+    assert!(stats.batch.max_queue_delay >= stats.interactive.max_queue_delay);
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_scheduler_preempts_batch_operations() {
+    let client = TestClient::new_with_config(json!({
+        "network": {
+            "processing_concurrency_limit": 1,
+        }
+    }));
+    let context = client.context();
+    let scheduler = context.net.processing_scheduler.clone();
+
+    let attempts = Arc::new(std::sync::atomic::AtomicU32::new(0));
+    let batch = {
+        let context = context.clone();
+        let scheduler = scheduler.clone();
+        let attempts = attempts.clone();
+        tokio::spawn(async move {
+            scheduler
+                .run_preemptible(&context, ProcessingPriority::Batch, || {
+                    let attempts = attempts.clone();
+                    async move {
+                        let attempt = attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                        // The first attempt waits for a shard block which is never produced
+                        if attempt == 0 {
+                            futures::future::pending::<()>().await;
+                        }
+                        attempt
+                    }
+                })
+                .await
+        })
+    };
+    tokio::time::delay_for(std::time::Duration::from_millis(100)).await;
+
+    let permit = tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        scheduler.acquire(&context, ProcessingPriority::Interactive),
+    )
+    .await
+    .expect("Interactive operation must preempt the batch one");
+
+    let stats: ResultOfGetSchedulerStats = client
+        .request_async("processing.get_scheduler_stats", json!(null))
+        .await
+        .unwrap();
+    assert_eq!(stats.interactive.active, 1);
+    assert_eq!(stats.batch.active, 0);
+    assert_eq!(stats.batch.queued, 1);
+    assert_eq!(stats.batch.preempted, 1);
+
+    drop(permit);
+    assert_eq!(batch.await.unwrap(), 1);
+}
+
+#[test]
+fn test_finality_params() {
+    let params: ParamsOfWaitForTransaction = serde_json::from_value(json!({
+        "message": "",
+        "shard_block_id": "",
+        "send_events": false,
+        "finality": { "mode": "masterchain_commit", "depth": 2 },
+    }))
+    .unwrap();
+    assert_eq!(
+        params.finality,
+        Some(TransactionFinality::MasterchainCommit {
+            depth: Some(2),
+            check_proofs: None,
+        })
+    );
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_block_stall_detection() {
+    let client = Arc::new(
+        ClientContext::new(ClientConfig {
+            network: NetworkConfig {
+                endpoints: Some(vec!["a".into()]),
+                block_stall_timeout: Some(1000),
+                switch_endpoint_on_block_stall: Some(true),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap(),
+    );
+
+    let now = client.env.now_ms();
+    NetworkMock::build()
+        .url("a")
+        .election(now, 1000)
+        .ok(&json!({ "data": { "blocks": [] } }).to_string())
+        // querying endpoint is selected again after the stall
+        .election(now, 1000)
+        .ok(&json!({
+            "data": {
+                "blocks": [{
+                    "id": "2",
+                    "gen_utime": now / 1000,
+                    "after_split": false,
+                    "workchain_id": 0,
+                    "shard": "8000000000000000",
+                    "in_msg_descr": [],
+                }],
+            }
+        })
+        .to_string())
+        .reset_client(&client)
+        .await;
+
+    let events = Arc::new(tokio::sync::Mutex::new(Vec::new()));
+    let events_copy = events.clone();
+    let params = ParamsOfWaitForTransaction {
+        message: "message".into(),
+        shard_block_id: "1".into(),
+        send_events: true,
+        ..Default::default()
+    };
+    let address = format!("0:{}", "0".repeat(64)).parse().unwrap();
+    let block = super::fetching::fetch_next_shard_block(
+        &client,
+        &params,
+        &address,
+        "1",
+        "message_id",
+        60000,
+        move |event| {
+            let events = events_copy.clone();
+            async move {
+                events.lock().await.push(event);
+            }
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(block.id.to_string(), "2");
+    assert_eq!(NetworkMock::get_len(&client).await, 0);
+    let events = events.lock().await;
+    assert_events(
+        events.clone(),
+        vec!["WillFetchNextBlock", "NoNewBlocks", "WillFetchNextBlock"],
+    );
+    match &events[1] {
+        ProcessingEvent::NoNewBlocks { shard_block_id, elapsed, .. } => {
+            assert_eq!(shard_block_id, "1");
+            assert!(*elapsed < 60000);
+        }
+        _ => panic!("NoNewBlocks event expected"),
+    }
+}
+
+#[test]
+fn test_message_sequence() {
+    let sequence: MessageSequence = serde_json::from_value(json!({
+        "provider": { "type": "GetMethod" },
+    }))
+    .unwrap();
+    assert_eq!(sequence.provider, SequenceProvider::GetMethod { method: None });
+    assert!(sequence.is_conflict(&json!(33)));
+    assert!(!sequence.is_conflict(&json!(52)));
+    assert!(!sequence.is_conflict(&json!(null)));
+
+    let sequence = MessageSequence {
+        provider: SequenceProvider::AbiFunction {
+            function_name: "getSeqno".into(),
+            output_name: None,
+        },
+        conflict_exit_codes: Some(vec![101]),
+        ..Default::default()
+    };
+    assert!(sequence.is_conflict(&json!(101)));
+    assert!(!sequence.is_conflict(&json!(33)));
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_sequence_locks() {
+    let locks = super::sequence::SequenceLocks::default();
+    let lock = locks.get("0:1");
+    let guard = lock.lock().await;
+
+    // Only the sendings to the same destination are serialized
+    assert!(locks.get("0:1").try_lock().is_err());
+    assert!(locks.get("0:2").try_lock().is_ok());
+
+    drop(guard);
+    assert!(locks.get("0:1").try_lock().is_ok());
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_transaction_waiters() {
+    use super::waiters::{TransactionWaiters, Waiter};
+
+    let waiters = TransactionWaiters::default();
+    let leader = match waiters.attach("message".into()) {
+        Waiter::Leader(guard) => guard,
+        Waiter::Attached(_) => panic!("Monitor must be started"),
+    };
+    let attached = match waiters.attach("message".into()) {
+        Waiter::Attached(waiter) => waiter,
+        Waiter::Leader(_) => panic!("Waiter must be attached"),
+    };
+    // Waiters of the other messages start their own monitors
+    assert!(matches!(waiters.attach("other".into()), Waiter::Leader(_)));
+    assert_eq!(leader.monitor.waiters(), 2);
+
+    let events = Arc::new(tokio::sync::Mutex::new(Vec::new()));
+    let events_copy = events.clone();
+    let attached = tokio::spawn(attached.wait(move |event| {
+        let events = events_copy.clone();
+        async move {
+            events.lock().await.push(event);
+        }
+    }));
+    tokio::task::yield_now().await;
+
+    let event = leader.monitor.notify(ProcessingEvent::WillFetchNextBlock {
+        shard_block_id: "1".into(),
+        message_id: "message".into(),
+        message: "boc".into(),
+        waiters: 1,
+    });
+    match event {
+        ProcessingEvent::WillFetchNextBlock { waiters, .. } => assert_eq!(waiters, 2),
+        _ => panic!("WillFetchNextBlock event expected"),
+    }
+    let result = ResultOfProcessMessage {
+        transaction: json!({ "id": "transaction" }),
+        ..Default::default()
+    };
+    leader.complete(&Ok(result.clone()));
+    assert_eq!(waiters.monitors_count(), 0);
+
+    assert_eq!(attached.await.unwrap().unwrap().unwrap(), result);
+    assert_events(events.lock().await.clone(), vec!["WillFetchNextBlock"]);
+
+    // Attached waiter gets no result if the monitor is dropped, so it starts a new monitor
+    let leader = waiters.attach("message".into());
+    let attached = match waiters.attach("message".into()) {
+        Waiter::Attached(waiter) => waiter,
+        Waiter::Leader(_) => panic!("Waiter must be attached"),
+    };
+    drop(leader);
+    assert!(attached.wait(|_| async {}).await.is_none());
+    assert!(matches!(waiters.attach("message".into()), Waiter::Leader(_)));
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_send_messages() {
+    let client = TestClient::new();
+    let (events_abi, events_tvc) = TestClient::package(EVENTS, Some(2));
+    let keys = client.generate_sign_keys();
+
+    let encoded = client
+        .encode_message(ParamsOfEncodeMessage {
+            abi: events_abi.clone(),
+            address: None,
+            deploy_set: DeploySet::some_with_tvc(events_tvc),
+            call_set: CallSet::some_with_function("constructor"),
+            signer: Signer::Keys { keys },
+            processing_try_index: None,
+            expiration: None,
+        })
+        .await
+        .unwrap();
+
+    let result: super::ResultOfSendMessages = client
+        .request_async(
+            "processing.send_messages",
+            super::ParamsOfSendMessages {
+                messages: vec![encoded.message.clone(), "invalid".to_string()],
+                abi: Some(events_abi),
+                broadcast_fanout: None,
+            },
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result.results.len(), 2);
+    assert_eq!(result.results[0].message_id.as_ref(), Some(&encoded.message_id));
+    assert!(result.results[0].error.is_none());
+    assert!(!result.results[0].sending_endpoints.is_empty());
+    // The failure of one message doesn't affect the others
+    assert_eq!(result.results[1].message_id, None);
+    assert!(result.results[1].error.is_some());
+
+    let error = client
+        .request_async::<_, super::ResultOfSendMessages>(
+            "processing.send_messages",
+            super::ParamsOfSendMessages {
+                messages: vec![encoded.message; 101],
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(error.code, ErrorCode::TooManyMessages as u32);
+}
+
+#[test]
+fn test_retry_policy() {
+    use super::{RetryCondition, RetryPolicy};
+    use crate::error::ClientError;
+
+    let expired = |local_error: serde_json::Value| {
+        ClientError::new(
+            ErrorCode::MessageExpired as u32,
+            String::new(),
+            json!({ "local_error": local_error }),
+        )
+    };
+    let low_balance = expired(json!({ "code": TvmErrorCode::LowBalance as u32, "data": {} }));
+    let replay_protection = expired(json!({ "code": 414, "data": { "exit_code": 52 } }));
+
+    let policy = RetryPolicy::default();
+    assert!(policy.is_retryable(&expired(serde_json::Value::Null), None));
+    assert!(policy.is_retryable(&replay_protection, None));
+    assert!(!policy.is_retryable(&low_balance, None));
+    assert!(!policy.is_retryable(
+        &ClientError::with_code_message(ErrorCode::TransactionWaitTimeout as u32, String::new()),
+        None,
+    ));
+    // Retries count is taken from the network config by default
+    assert_eq!(policy.next_delay(2, 2, 0), Some(0));
+    assert_eq!(policy.next_delay(2, 3, 0), None);
+    assert_eq!(policy.next_delay(-1, 100, 0), Some(0));
+
+    let policy = RetryPolicy {
+        max_retries: Some(10),
+        backoff_base: Some(1000),
+        max_backoff: Some(5000),
+        max_elapsed_time: Some(20000),
+        retry_on: Some(vec![RetryCondition::LowBalance]),
+        ..Default::default()
+    };
+    assert!(policy.is_retryable(&low_balance, None));
+    assert!(!policy.is_retryable(&replay_protection, None));
+    assert_eq!(policy.backoff(1), 1000);
+    assert_eq!(policy.backoff(2), 2000);
+    assert_eq!(policy.backoff(3), 4000);
+    assert_eq!(policy.backoff(4), 5000);
+    assert_eq!(policy.backoff(200), 5000);
+    assert_eq!(policy.next_delay(0, 3, 10000), Some(4000));
+    assert_eq!(policy.next_delay(0, 3, 17000), None);
+    assert_eq!(policy.next_delay(0, 11, 0), None);
+
+    let policy = RetryPolicy {
+        backoff_base: Some(100),
+        jitter: Some(50),
+        ..Default::default()
+    };
+    let delay = policy.next_delay(5, 1, 0).unwrap();
+    assert!((100..=150).contains(&delay));
+}
+
+#[test]
+fn test_pending_monitored_messages() {
+    use super::message_monitor::PendingMessages;
+    use super::{MessageMonitoringEvent, MonitoredMessage};
+
+    let mut pending = PendingMessages::new(
+        &[
+            MonitoredMessage { hash: "1".into(), expiration: 10 },
+            MonitoredMessage { hash: "2".into(), expiration: 20 },
+            MonitoredMessage { hash: "3".into(), expiration: 30 },
+        ],
+        5000,
+    );
+    assert_eq!(pending.next_deadline(), Some(15000));
+
+    assert_eq!(
+        pending.resolve(&json!({ "id": "t1", "in_msg": "1", "aborted": false })),
+        Some(MessageMonitoringEvent::Finalized {
+            message_hash: "1".into(),
+            transaction_id: "t1".into(),
+        })
+    );
+    // Each message is resolved once, unknown messages are ignored
+    assert_eq!(pending.resolve(&json!({ "id": "t1", "in_msg": "1" })), None);
+    assert_eq!(pending.resolve(&json!({ "id": "t4", "in_msg": "4" })), None);
+    assert_eq!(pending.next_deadline(), Some(25000));
+
+    assert_eq!(
+        pending.resolve(&json!({
+            "id": "t2",
+            "in_msg": "2",
+            "aborted": true,
+            "compute": { "exit_code": 33 },
+        })),
+        Some(MessageMonitoringEvent::Rejected {
+            message_hash: "2".into(),
+            transaction_id: "t2".into(),
+            exit_code: Some(33),
+        })
+    );
+
+    assert_eq!(pending.expire(34999), vec![]);
+    assert_eq!(
+        pending.expire(35000),
+        vec![MessageMonitoringEvent::Expired { message_hash: "3".into() }]
+    );
+    assert!(pending.is_empty());
+    assert_eq!(pending.next_deadline(), None);
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_send_raw_message_checks() {
+    let client = TestClient::new();
+    let (events_abi, events_tvc) = TestClient::package(EVENTS, Some(2));
+    let keys = client.generate_sign_keys();
+
+    let deploy: crate::abi::ResultOfEncodeMessage = client
+        .request_async(
+            "abi.encode_message",
+            ParamsOfEncodeMessage {
+                abi: events_abi.clone(),
+                deploy_set: DeploySet::some_with_tvc(events_tvc),
+                call_set: Some(CallSet {
+                    function_name: "constructor".into(),
+                    header: Some(FunctionHeader {
+                        pubkey: Some(keys.public.clone()),
+                        ..Default::default()
+                    }),
+                    input: None,
+                }),
+                signer: Signer::Keys { keys },
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let internal: crate::abi::ResultOfEncodeInternalMessage = client
+        .request_async(
+            "abi.encode_internal_message",
+            crate::abi::ParamsOfEncodeInternalMessage {
+                address: Some(deploy.address.clone()),
+                value: "1000000000".into(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let send = |params: ParamsOfSendRawMessage| {
+        crate::processing::send_raw_message(client.context(), params, |_| {
+            futures::future::ready(())
+        })
+    };
+    let raw_message = |message: &str| ParamsOfSendRawMessage {
+        message: message.to_string(),
+        ..Default::default()
+    };
+
+    let err = send(raw_message(&internal.message)).await.unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidMessageBoc as u32);
+
+    let err = send(ParamsOfSendRawMessage {
+        dst: Some(format!("0:{}", "1".repeat(64))),
+        ..raw_message(&deploy.message)
+    })
+    .await
+    .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidMessageBoc as u32);
+
+    let err = send(ParamsOfSendRawMessage {
+        abi: Some(crate::abi::Abi::Json(
+            r#"{"ABI version": 2, "functions": [], "events": []}"#.into(),
+        )),
+        ..raw_message(&deploy.message)
+    })
+    .await
+    .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidMessageBoc as u32);
+
+    let err = send(ParamsOfSendRawMessage {
+        dst: Some(deploy.address.clone()),
+        abi: Some(events_abi),
+        expire: Some(1),
+        ..raw_message(&deploy.message)
+    })
+    .await
+    .unwrap_err();
+    assert_eq!(err.code, ErrorCode::MessageAlreadyExpired as u32);
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_decode_tree() {
+    let client = TestClient::new();
+    let dest = "0:0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+    // Bounceable transfer to the missing account is bounced back to the giver
+    let result = client
+        .net_process_message(
+            ParamsOfProcessMessage {
+                message_encode_params: ParamsOfEncodeMessage {
+                    abi: TestClient::giver_abi(),
+                    address: Some(client.giver_address().await),
+                    call_set: CallSet::some_with_function_and_input(
+                        "sendTransaction",
+                        json!({
+                            "dest": dest,
+                            "value": 500_000_000u64,
+                            "bounce": true,
+                        }),
+                    ),
+                    deploy_set: None,
+                    processing_try_index: None,
+                    expiration: None,
+                    signer: Signer::Keys {
+                        keys: TestClient::giver_keys(),
+                    },
+                },
+                send_events: false,
+                sequence: None,
+                decode_tree: Some(ParamsOfDecodeTree {
+                    depth: Some(TreeDecodingDepth::OneLevel),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            TestClient::default_callback,
+        )
+        .await
+        .unwrap();
+
+    let tree_messages = result.decoded.unwrap().tree_messages.unwrap();
+    assert_eq!(tree_messages.len(), 1);
+    assert_eq!(tree_messages[0].src.as_deref(), Some(dest));
+    assert_eq!(tree_messages[0].dst, Some(client.giver_address().await));
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_validate_locally() {
+    let client = TestClient::new();
+    let keys = client.generate_sign_keys();
+    let abi = TestClient::abi(HELLO, None);
+
+    let address = client
+        .deploy_with_giver_async(
+            ParamsOfEncodeMessage {
+                abi: abi.clone(),
+                deploy_set: Some(DeploySet {
+                    tvc: TestClient::tvc(HELLO, None),
+                    ..Default::default()
+                }),
+                signer: Signer::Keys { keys },
+                processing_try_index: None,
+                expiration: None,
+                address: None,
+                call_set: CallSet::some_with_function("constructor"),
+            },
+            None,
+        )
+        .await;
+
+    // The message signed by the foreign keys is rejected by the contract
+    let message = client
+        .encode_message(ParamsOfEncodeMessage {
+            abi: abi.clone(),
+            deploy_set: None,
+            signer: Signer::Keys {
+                keys: client.generate_sign_keys(),
+            },
+            processing_try_index: None,
+            expiration: None,
+            address: Some(address),
+            call_set: CallSet::some_with_function("touch"),
+        })
+        .await
+        .unwrap();
+
+    let error = client
+        .request_async::<_, super::ResultOfSendMessage>(
+            "processing.send_message",
+            ParamsOfSendMessage {
+                message: message.message,
+                abi: Some(abi),
+                send_events: false,
+                broadcast_fanout: None,
+                validate_locally: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(error.code, ErrorCode::MessageRejectedLocally as u32);
+    assert_eq!(error.data["message_id"], message.message_id);
+    assert!(error.data["exit_code"].is_number());
+    assert_eq!(error.data["local_error"]["data"]["exit_code"], error.data["exit_code"]);
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_transaction_proofed_event() {
+    // proofs are not supported by TON OS SE
+    if TestClient::node_se() {
+        return;
+    }
+
+    let client = TestClient::new();
+    let (abi, tvc) = TestClient::package(EVENTS, Some(2));
+    let keys = client.generate_sign_keys();
+
+    let events = Arc::new(tokio::sync::Mutex::new(vec![]));
+    let events_copy = events.clone();
+    let callback = move |event: ProcessingEvent, _: ProcessingResponseType| {
+        let events = events_copy.clone();
+        async move {
+            events.lock().await.push(event);
+        }
+    };
+    let send_message = client.wrap_async_callback(
+        crate::json_interface::processing::send_message,
+        ProcessingModule::api(),
+        crate::json_interface::processing::send_message_api(),
+    );
+    let wait_for_transaction = client.wrap_async_callback(
+        crate::json_interface::processing::wait_for_transaction,
+        ProcessingModule::api(),
+        crate::json_interface::processing::wait_for_transaction_api(),
+    );
+
+    let encode_params = ParamsOfEncodeMessage {
+        abi: abi.clone(),
+        address: None,
+        deploy_set: DeploySet::some_with_tvc(tvc),
+        call_set: CallSet::some_with_function("constructor"),
+        signer: Signer::Keys { keys },
+        processing_try_index: None,
+        expiration: None,
+    };
+    let encoded = client.encode_message(encode_params.clone()).await.unwrap();
+    client.get_tokens_from_giver_async(&encoded.address, None).await;
+
+    let encoded = client.encode_message(encode_params).await.unwrap();
+    let sent = send_message
+        .call_with_callback(
+            ParamsOfSendMessage {
+                message: encoded.message.clone(),
+                abi: Some(abi.clone()),
+                send_events: false,
+                broadcast_fanout: None,
+                validate_locally: None,
+                ..Default::default()
+            },
+            callback.clone(),
+        )
+        .await
+        .unwrap();
+    let output = wait_for_transaction
+        .call_with_callback(
+            ParamsOfWaitForTransaction {
+                message: encoded.message.clone(),
+                shard_block_id: sent.shard_block_id,
+                send_events: true,
+                abi: Some(abi),
+                sending_endpoints: Some(sent.sending_endpoints),
+                finality: Some(TransactionFinality::MasterchainCommit {
+                    depth: None,
+                    check_proofs: Some(true),
+                }),
+                ..Default::default()
+            },
+            callback,
+        )
+        .await
+        .unwrap();
+
+    let events = events.lock().await.clone();
+    match events.last() {
+        Some(ProcessingEvent::TransactionProofed { message_id, transaction_id, .. }) => {
+            assert_eq!(message_id, &encoded.message_id);
+            assert_eq!(Some(transaction_id.as_str()), output.transaction["id"].as_str());
+        }
+        event => panic!("TransactionProofed event expected, got {:?}", event),
+    }
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_relayer() {
+    let context = Arc::new(
+        ClientContext::new(ClientConfig {
+            network: NetworkConfig {
+                endpoints: Some(vec!["a".into()]),
+                relayer_url: Some("r".into()),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .unwrap(),
+    );
+    let (abi, tvc) = TestClient::package(EVENTS, Some(2));
+    let encoded = crate::abi::encode_message(
+        context.clone(),
+        ParamsOfEncodeMessage {
+            abi,
+            address: None,
+            deploy_set: DeploySet::some_with_tvc(tvc),
+            call_set: CallSet::some_with_function("constructor"),
+            signer: Signer::Keys {
+                keys: crate::crypto::generate_random_sign_keys(context.clone()).unwrap(),
+            },
+            processing_try_index: None,
+            expiration: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    NetworkMock::build()
+        .url("r")
+        .ok("{}")
+        .status(402, "Sponsorship limit is reached")
+        .reset_client(&context)
+        .await;
+
+    let send = || {
+        super::send_messages(
+            context.clone(),
+            super::ParamsOfSendMessages {
+                messages: vec![encoded.message.clone()],
+                ..Default::default()
+            },
+        )
+    };
+
+    // The message is handed over to the relayer instead of the endpoints
+    let result = send().await.unwrap();
+    assert_eq!(result.results[0].sending_endpoints, vec!["r".to_string()]);
+    assert!(result.results[0].error.is_none());
+
+    let result = send().await.unwrap();
+    let error = result.results[0].error.as_ref().unwrap();
+    assert_eq!(error.code, ErrorCode::RelayerSendFailed as u32);
+    assert_eq!(error.data["relayer_url"], "r");
+    assert_eq!(error.data["message_id"], encoded.message_id);
+}
@@ -1,6 +1,12 @@
+#[cfg(feature = "net")]
+use crate::abi::Abi;
 use crate::abi::DecodedMessageBody;
+#[cfg(feature = "net")]
 use crate::error::ClientError;
+#[cfg(feature = "net")]
+use crate::net::MessageNode;
 use serde_json::Value;
+#[cfg(feature = "net")]
 use ton_sdk::TransactionFees;
 
 #[derive(Serialize, Deserialize, ApiType, Default, Debug, PartialEq, Clone)]
@@ -13,8 +19,49 @@ pub struct DecodedOutput {
 
     /// Decoded body of the function output message.
     pub output: Option<Value>,
+
+    /// Messages produced by the descendant transactions of the processed transaction.
+    ///
+    /// Message bodies are decoded with the processed message ABI and the `abi_registry`
+    /// of `decode_tree`. Includes the external outbound messages (events) of the descendant
+    /// transactions, so the final outcome of a cascaded operation can be checked.
+    /// Present only if `decode_tree` is specified.
+    #[cfg(feature = "net")]
+    pub tree_messages: Option<Vec<MessageNode>>,
+}
+
+#[cfg(feature = "net")]
+#[derive(Serialize, Deserialize, ApiType, Debug, Clone, Copy, PartialEq)]
+pub enum TreeDecodingDepth {
+    /// Messages produced by the transactions of the processed transaction out messages
+    OneLevel,
+    /// Messages of the whole transaction tree (up to 50 transactions)
+    FullTree,
+}
+
+#[cfg(feature = "net")]
+impl Default for TreeDecodingDepth {
+    fn default() -> Self {
+        TreeDecodingDepth::OneLevel
+    }
+}
+
+#[cfg(feature = "net")]
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone)]
+pub struct ParamsOfDecodeTree {
+    /// Depth of the descendant transactions. Default is `OneLevel`.
+    pub depth: Option<TreeDecodingDepth>,
+
+    /// ABIs of the descendant contracts used to decode the message bodies
+    /// in addition to the processed message ABI.
+    pub abi_registry: Option<Vec<Abi>>,
+
+    /// Timeout of waiting for the descendant messages and transactions.
+    /// Default value is 60000 (1 min).
+    pub timeout: Option<u32>,
 }
 
+#[cfg(feature = "net")]
 #[derive(Serialize, Deserialize, ApiType, Default, Debug, PartialEq, Clone)]
 pub struct ResultOfProcessMessage {
     /// Parsed transaction.
@@ -35,11 +82,31 @@ pub struct ResultOfProcessMessage {
     pub fees: TransactionFees,
 }
 
+#[cfg(feature = "net")]
+#[derive(Serialize, Deserialize, ApiType, Debug, Clone, PartialEq)]
+#[serde(tag = "mode")]
+pub enum TransactionFinality {
+    /// Transaction is final when its block is referenced by a masterchain block
+    /// (masterchain blocks reference themselves) and `depth` further masterchain blocks
+    /// are generated after the referencing one.
+    #[serde(rename = "masterchain_commit")]
+    MasterchainCommit {
+        /// Number of masterchain blocks generated after the referencing one. Default is 0.
+        depth: Option<u32>,
+        /// Check proofs of the transaction block and of the referencing masterchain block.
+        /// Default is `false`.
+        check_proofs: Option<bool>,
+    },
+}
+
+#[cfg(feature = "net")]
 #[derive(Clone, num_derive::FromPrimitive, PartialEq, Debug)]
 pub enum ProcessingResponseType {
     ProcessingEvent = 100,
+    MonitoringEvent = 101,
 }
 
+#[cfg(feature = "net")]
 #[derive(Serialize, Deserialize, ApiType, Debug, Clone)]
 #[serde(tag = "type")]
 pub enum ProcessingEvent {
@@ -109,6 +176,10 @@ pub enum ProcessingEvent {
         shard_block_id: String,
         message_id: String,
         message: String,
+        /// Number of `wait_for_transaction` calls waiting for this message.
+        /// Concurrent calls for the same message share a single block walking loop.
+        #[serde(default)]
+        waiters: u32,
     },
 
     /// Notifies the app that the next block can't be fetched.
@@ -126,6 +197,20 @@ pub enum ProcessingEvent {
         error: ClientError,
     },
 
+    /// Notifies the app that no new shard blocks were received for `elapsed` ms
+    /// after the block `shard_block_id`.
+    ///
+    /// Sent each time the `NetworkConfig.block_stall_timeout` passes without a new block.
+    /// This may happen when the shard stops producing blocks or the endpoint stalls.
+    /// If `NetworkConfig.switch_endpoint_on_block_stall` is set, then SDK selects
+    /// another querying endpoint before continuing the block walking.
+    NoNewBlocks {
+        shard_block_id: String,
+        message_id: String,
+        message: String,
+        elapsed: u64,
+    },
+
     /// Notifies the app that the message was not executed within expire timeout on-chain and will 
     /// never be because it is already expired.
     /// The expiration timeout can be configured with `AbiConfig` parameters. 
@@ -141,4 +226,32 @@ pub enum ProcessingEvent {
         message: String,
         error: ClientError,
     },
+
+    /// Notifies the app that the processing of the message is failed and the new message
+    /// will be sent after `delay` ms according to the `retry_policy` of `process_message`.
+    ///
+    /// `try_index` is the number of the retry starting from 1, `error` is the processing error
+    /// of the failed message.
+    RetryScheduled {
+        message_id: String,
+        message: String,
+        try_index: u32,
+        delay: u32,
+        error: ClientError,
+    },
+
+    /// Notifies the app that the proof of the shard block containing the transaction
+    /// is checked, so the transaction is not only seen in the block, but is cryptographically
+    /// proven to be included into the blockchain.
+    ///
+    /// Sent by `wait_for_transaction` with the `MasterchainCommit` finality when
+    /// its `check_proofs` is set. `verification_time` is the time in ms spent
+    /// on the proof checking.
+    TransactionProofed {
+        shard_block_id: String,
+        message_id: String,
+        message: String,
+        transaction_id: String,
+        verification_time: u64,
+    },
 }
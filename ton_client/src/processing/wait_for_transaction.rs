@@ -4,8 +4,11 @@ use crate::client::ClientContext;
 use crate::error::{AddNetworkUrl, ClientResult};
 use crate::net::EndpointStat;
 use crate::processing::internal::{get_message_expiration_time, resolve_error};
+use crate::processing::scheduler::{run_preemptible_processing, ProcessingPriority};
+use crate::processing::waiters::Waiter;
+use crate::tvm::accounts_cache::parse_lt;
 use crate::processing::{fetching, internal, Error};
-use crate::processing::{ProcessingEvent, ResultOfProcessMessage};
+use crate::processing::{ProcessingEvent, ResultOfProcessMessage, TransactionFinality};
 use std::sync::Arc;
 
 //--------------------------------------------------------------------------- wait_for_transaction
@@ -37,20 +40,144 @@ pub struct ParamsOfWaitForTransaction {
     /// Provide the same value as the `send_message` has returned.
     /// If the message was not delivered (expired), SDK will log the endpoint URLs, used for its sending.
     pub sending_endpoints: Option<Vec<String>>,
+
+    /// Priority of the shard block fetching. Default is `Interactive`.
+    ///
+    /// Matters only when `processing_concurrency_limit` of the network config is reached.
+    pub priority: Option<ProcessingPriority>,
+
+    /// Finality the transaction must reach before the function resolves.
+    ///
+    /// By default the function resolves as soon as the transaction is found in a shard block.
+    /// The waiting time for the finality is limited by `message_processing_timeout`
+    /// of the network config.
+    pub finality: Option<TransactionFinality>,
+}
+
+#[cfg(feature = "proofs")]
+async fn wait_for_finality<F: futures::Future<Output = ()> + Send>(
+    context: &Arc<ClientContext>,
+    finality: &TransactionFinality,
+    params: &ParamsOfWaitForTransaction,
+    message_id: &str,
+    transaction_id: &str,
+    block_id: &str,
+    callback: &(impl Fn(ProcessingEvent) -> F + Send + Sync),
+) -> ClientResult<()> {
+    match finality {
+        TransactionFinality::MasterchainCommit { depth, check_proofs } => {
+            let depth = depth.unwrap_or_default();
+            let timeout = context.get_server_link()?.config().message_processing_timeout;
+            let deadline = context.env.now_ms() + timeout as u64;
+            let committed = crate::proofs::wait_for_masterchain_commit(
+                context.clone(),
+                block_id,
+                depth,
+                check_proofs.unwrap_or_default(),
+                deadline,
+                |verification_time| async move {
+                    if params.send_events {
+                        callback(ProcessingEvent::TransactionProofed {
+                            shard_block_id: block_id.to_string(),
+                            message_id: message_id.to_string(),
+                            message: params.message.clone(),
+                            transaction_id: transaction_id.to_string(),
+                            verification_time,
+                        })
+                        .await;
+                    }
+                },
+            )
+            .await?;
+            if committed.is_none() {
+                return Err(Error::transaction_finality_timeout(
+                    message_id, block_id, depth, timeout,
+                ));
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(not(feature = "proofs"))]
+async fn wait_for_finality<F: futures::Future<Output = ()> + Send>(
+    _context: &Arc<ClientContext>,
+    _finality: &TransactionFinality,
+    _params: &ParamsOfWaitForTransaction,
+    _message_id: &str,
+    _transaction_id: &str,
+    _block_id: &str,
+    _callback: &(impl Fn(ProcessingEvent) -> F + Send + Sync),
+) -> ClientResult<()> {
+    Err(crate::client::Error::not_implemented("library is built without `proofs` feature"))
+}
+
+/// Waiters of the same message share the monitor only if their results are the same
+fn monitor_key(message_id: &str, params: &ParamsOfWaitForTransaction) -> String {
+    json!([message_id, params.abi, params.finality]).to_string()
 }
 
 pub async fn wait_for_transaction<F: futures::Future<Output = ()> + Send>(
     context: Arc<ClientContext>,
-    params: ParamsOfWaitForTransaction,
+    mut params: ParamsOfWaitForTransaction,
     callback: impl Fn(ProcessingEvent) -> F + Send + Sync,
 ) -> ClientResult<ResultOfProcessMessage> {
-    let net = context.get_server_link()?;
-
-    // Prepare to wait
     let message =
         deserialize_object_from_boc::<ton_block::Message>(&context, &params.message, "message")
             .await?;
     let message_id = message.cell.repr_hash().as_hex_string();
+    let key = monitor_key(&message_id, &params);
+    let send_events = params.send_events;
+
+    loop {
+        match context.net.transaction_waiters.attach(key.clone()) {
+            Waiter::Leader(guard) => {
+                // Events are always produced because the attached waiters can require them
+                params.send_events = true;
+                let monitor = &guard.monitor;
+                let result = walk_blocks(&context, &params, message, &message_id, |event| {
+                    let event = monitor.notify(event);
+                    let notify = if send_events { Some(callback(event)) } else { None };
+                    async move {
+                        if let Some(notify) = notify {
+                            notify.await
+                        }
+                    }
+                })
+                .await;
+                guard.complete(&result);
+                return result;
+            }
+            Waiter::Attached(waiter) => {
+                let result = waiter
+                    .wait(|event| {
+                        let notify = if send_events { Some(callback(event)) } else { None };
+                        async move {
+                            if let Some(notify) = notify {
+                                notify.await
+                            }
+                        }
+                    })
+                    .await;
+                // If the waiter running the monitor was dropped, start a new monitor
+                if let Some(result) = result {
+                    return result;
+                }
+            }
+        }
+    }
+}
+
+async fn walk_blocks<F: futures::Future<Output = ()> + Send>(
+    context: &Arc<ClientContext>,
+    params: &ParamsOfWaitForTransaction,
+    message: crate::boc::internal::DeserializedObject<ton_block::Message>,
+    message_id: &str,
+    callback: impl Fn(ProcessingEvent) -> F + Send + Sync,
+) -> ClientResult<ResultOfProcessMessage> {
+    let net = context.get_server_link()?;
+
+    // Prepare to wait
     let address = message
         .object
         .dst_ref().cloned()
@@ -59,7 +186,7 @@ pub async fn wait_for_transaction<F: futures::Future<Output = ()> + Send>(
         get_message_expiration_time(context.clone(), params.abi.as_ref(), &params.message).await?;
     let processing_timeout = net.config().message_processing_timeout;
     let max_block_time =
-        message_expiration_time.unwrap_or(context.env.now_ms() + processing_timeout as u64);
+        message_expiration_time.unwrap_or(context.network_now_ms() + processing_timeout as u64);
     log::debug!(
         "message_expiration_time {}",
         message_expiration_time.unwrap_or_default() / 1000
@@ -68,32 +195,37 @@ pub async fn wait_for_transaction<F: futures::Future<Output = ()> + Send>(
 
     // Block walking loop
     loop {
-        let now = context.env.now_ms();
-        let fetch_block_timeout =
-            (std::cmp::max(max_block_time, now) - now) as u32 + processing_timeout;
-        log::debug!("fetch_block_timeout {}", fetch_block_timeout);
-
-        let block = fetching::fetch_next_shard_block(
-            &context,
-            &params,
-            &address,
-            &shard_block_id,
-            &message_id,
-            fetch_block_timeout,
-            &callback,
-        )
+        // Batch fetching is restarted if it gives its slot up to an interactive operation
+        let block = run_preemptible_processing(context, params.priority, || {
+            let now = context.network_now_ms();
+            let fetch_block_timeout =
+                (std::cmp::max(max_block_time, now) - now) as u32 + processing_timeout;
+            log::debug!("fetch_block_timeout {}", fetch_block_timeout);
+
+            fetching::fetch_next_shard_block(
+                context,
+                params,
+                &address,
+                &shard_block_id,
+                message_id,
+                fetch_block_timeout,
+                &callback,
+            )
+        })
         .await
-        .add_network_url_from_context(&context)
+        .add_network_url_from_context(context)
         .await?;
-        let transaction_ids = internal::find_transactions(&block, &message_id, &shard_block_id)?;
+        // Block can't be generated later than the current network time
+        net.clock().observe_server_time(block.gen_utime as u64 * 1000, context.env.now_ms());
+        let transaction_ids = internal::find_transactions(&block, message_id, &shard_block_id)?;
         let mut last_error = None;
         for transaction_id in transaction_ids {
             // Transaction has been found.
             // Let's fetch other stuff.
             let result = fetching::fetch_transaction_result(
-                &context,
+                context,
                 &shard_block_id,
-                &message_id,
+                message_id,
                 &params.message,
                 &transaction_id,
                 &params.abi,
@@ -102,9 +234,30 @@ pub async fn wait_for_transaction<F: futures::Future<Output = ()> + Send>(
                 block.gen_utime,
             )
             .await
-            .add_network_url_from_context(&context)
+            .add_network_url_from_context(context)
             .await;
-            if result.is_ok() {
+            if let Ok(output) = &result {
+                if let Some(finality) = &params.finality {
+                    wait_for_finality(
+                        context,
+                        finality,
+                        params,
+                        message_id,
+                        output.transaction["id"].as_str().unwrap_or_default(),
+                        &block.id.to_string(),
+                        &callback,
+                    )
+                    .await
+                        .add_network_url_from_context(context)
+                        .await?;
+                }
+                if let Some(lt) = parse_lt(&output.transaction["lt"]) {
+                    context
+                        .net
+                        .accounts_cache
+                        .observe_transaction(&address.to_string(), lt)
+                        .await;
+                }
                 if let Some(endpoints) = &params.sending_endpoints {
                     context
                         .get_server_link()?
@@ -124,7 +277,7 @@ pub async fn wait_for_transaction<F: futures::Future<Output = ()> + Send>(
             let waiting_expiration_time = (max_block_time / 1000) as u32;
             let error = if message_expiration_time.is_some() {
                 Error::message_expired(
-                    &message_id,
+                    message_id,
                     &shard_block_id,
                     waiting_expiration_time,
                     block.gen_utime,
@@ -132,7 +285,7 @@ pub async fn wait_for_transaction<F: futures::Future<Output = ()> + Send>(
                 )
             } else {
                 Error::transaction_wait_timeout(
-                    &message_id,
+                    message_id,
                     &shard_block_id,
                     waiting_expiration_time,
                     processing_timeout,
@@ -149,7 +302,7 @@ pub async fn wait_for_transaction<F: futures::Future<Output = ()> + Send>(
                 true,
             )
             .await
-            .add_network_url_from_context(&context)
+            .add_network_url_from_context(context)
             .await;
             if let (Some(endpoints), Err(err)) = (&params.sending_endpoints, &resolved) {
                 if err.data["local_error"].is_null() {
@@ -0,0 +1,191 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+// Sharing of the transaction monitor: concurrent `wait_for_transaction` calls for the same
+// message are served by a single block walking loop. The first waiter runs the loop,
+// the others receive its events and result.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use tokio::sync::{broadcast, oneshot};
+
+use crate::error::ClientResult;
+use crate::processing::{ProcessingEvent, ResultOfProcessMessage};
+
+const EVENTS_CAPACITY: usize = 100;
+
+type ResultSender = oneshot::Sender<ClientResult<ResultOfProcessMessage>>;
+
+pub(crate) struct TransactionMonitor {
+    waiters: AtomicU32,
+    events: Mutex<Option<broadcast::Sender<ProcessingEvent>>>,
+    results: Mutex<Vec<ResultSender>>,
+}
+
+impl TransactionMonitor {
+    fn new() -> Self {
+        Self {
+            waiters: AtomicU32::new(1),
+            events: Mutex::new(Some(broadcast::channel(EVENTS_CAPACITY).0)),
+            results: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Number of the waiters attached to the monitor including the one running it
+    pub fn waiters(&self) -> u32 {
+        self.waiters.load(Ordering::Relaxed)
+    }
+
+    /// Fills the number of the waiters in the event and passes it to the attached waiters
+    pub fn notify(&self, mut event: ProcessingEvent) -> ProcessingEvent {
+        if let ProcessingEvent::WillFetchNextBlock { waiters, .. } = &mut event {
+            *waiters = self.waiters();
+        }
+        if let Some(events) = &*self.events.lock().unwrap_or_else(PoisonError::into_inner) {
+            // fails only if no waiters are attached
+            let _ = events.send(event.clone());
+        }
+        event
+    }
+
+    fn subscribe(&self) -> Option<broadcast::Receiver<ProcessingEvent>> {
+        self.events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            .map(|events| events.subscribe())
+    }
+
+    /// Closes the events channel and passes the result to the attached waiters.
+    /// If there is no result, the attached waiters get the closed result channel.
+    fn finish(&self, result: Option<&ClientResult<ResultOfProcessMessage>>) {
+        self.events.lock().unwrap_or_else(PoisonError::into_inner).take();
+        let results =
+            std::mem::take(&mut *self.results.lock().unwrap_or_else(PoisonError::into_inner));
+        if let Some(result) = result {
+            for sender in results {
+                let _ = sender.send(result.clone());
+            }
+        }
+    }
+}
+
+pub(crate) enum Waiter<'a> {
+    /// The monitor was not running, so the waiter must run it and `complete` the guard
+    Leader(LeaderGuard<'a>),
+    /// The waiter is attached to the running monitor
+    Attached(AttachedWaiter),
+}
+
+/// Removes the monitor when the waiter running it is finished or dropped.
+/// If the guard is dropped without completion, attached waiters get the closed channel
+/// and one of them starts a new monitor.
+pub(crate) struct LeaderGuard<'a> {
+    waiters: &'a TransactionWaiters,
+    key: Option<String>,
+    pub monitor: Arc<TransactionMonitor>,
+}
+
+impl<'a> LeaderGuard<'a> {
+    pub fn complete(mut self, result: &ClientResult<ResultOfProcessMessage>) {
+        if let Some(key) = self.key.take() {
+            self.waiters.remove(&key);
+            self.monitor.finish(Some(result));
+        }
+    }
+}
+
+impl<'a> Drop for LeaderGuard<'a> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.waiters.remove(&key);
+            self.monitor.finish(None);
+        }
+    }
+}
+
+pub(crate) struct AttachedWaiter {
+    monitor: Arc<TransactionMonitor>,
+    events: Option<broadcast::Receiver<ProcessingEvent>>,
+    result: oneshot::Receiver<ClientResult<ResultOfProcessMessage>>,
+}
+
+impl AttachedWaiter {
+    /// Passes the monitor events to the `callback` and returns the monitor result.
+    /// Returns `None` if the monitor was dropped before completion.
+    pub async fn wait<F: futures::Future<Output = ()> + Send>(
+        mut self,
+        callback: impl Fn(ProcessingEvent) -> F,
+    ) -> Option<ClientResult<ResultOfProcessMessage>> {
+        if let Some(events) = &mut self.events {
+            loop {
+                match events.recv().await {
+                    Ok(event) => callback(event).await,
+                    // some events are skipped because the callback is too slow
+                    Err(broadcast::RecvError::Lagged(_)) => {}
+                    // the monitor is finished
+                    Err(broadcast::RecvError::Closed) => break,
+                }
+            }
+        }
+        (&mut self.result).await.ok()
+    }
+}
+
+impl Drop for AttachedWaiter {
+    fn drop(&mut self) {
+        self.monitor.waiters.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Running transaction monitors by the message hash
+#[derive(Default)]
+pub(crate) struct TransactionWaiters {
+    monitors: Mutex<HashMap<String, Arc<TransactionMonitor>>>,
+}
+
+impl TransactionWaiters {
+    /// Attaches the waiter to the monitor with the `key` or starts a new monitor
+    pub fn attach(&self, key: String) -> Waiter {
+        let mut monitors = self.monitors.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(monitor) = monitors.get(&key) {
+            let (sender, result) = oneshot::channel();
+            monitor.results.lock().unwrap_or_else(PoisonError::into_inner).push(sender);
+            monitor.waiters.fetch_add(1, Ordering::Relaxed);
+            return Waiter::Attached(AttachedWaiter {
+                monitor: monitor.clone(),
+                events: monitor.subscribe(),
+                result,
+            });
+        }
+        let monitor = Arc::new(TransactionMonitor::new());
+        monitors.insert(key.clone(), monitor.clone());
+        Waiter::Leader(LeaderGuard {
+            waiters: self,
+            key: Some(key),
+            monitor,
+        })
+    }
+
+    fn remove(&self, key: &str) {
+        self.monitors.lock().unwrap_or_else(PoisonError::into_inner).remove(key);
+    }
+
+    #[cfg(test)]
+    pub fn monitors_count(&self) -> usize {
+        self.monitors.lock().unwrap_or_else(PoisonError::into_inner).len()
+    }
+}
@@ -0,0 +1,273 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use failure::{bail, err_msg};
+use serde_json::Value;
+use ton_block::ShardIdent;
+use ton_types::{Result, UInt256};
+use tokio::sync::RwLock;
+
+use crate::client::NetworkUID;
+use crate::proofs::data_source::{read_mc_block_shards, ProofDataSource};
+use crate::proofs::BlockProof;
+
+const MASTERCHAIN_SHARD: u64 = 0x8000_0000_0000_0000;
+
+#[derive(Clone)]
+struct ArchiveEntry {
+    path: PathBuf,
+    root_hash: String,
+    file_hash: String,
+}
+
+#[derive(Default)]
+struct ArchiveIndex {
+    zerostate: Option<ArchiveEntry>,
+    // Blocks by (workchain_id, tagged shard prefix, seq_no)
+    blocks: BTreeMap<(i32, u64, u32), ArchiveEntry>,
+    blocks_by_root_hash: HashMap<String, PathBuf>,
+    mc_proofs: BTreeMap<u32, ArchiveEntry>,
+}
+
+// Parses entry name in the node archive format:
+// `<kind>_(<workchain_id>,<shard>,<seq_no>):<root_hash>:<file_hash>`
+pub(crate) fn parse_entry_name(name: &str) -> Option<(&str, i32, u64, u32, String, String)> {
+    let (kind, rest) = name.split_at(name.find("_(")?);
+    let rest = &rest[2..];
+    let (id, hashes) = rest.split_at(rest.find(')')?);
+    let mut id = id.split(',');
+    let workchain_id = id.next()?.trim().parse().ok()?;
+    let shard = u64::from_str_radix(id.next()?.trim(), 16).ok()?;
+    let seq_no = id.next()?.trim().parse().ok()?;
+    let mut hashes = hashes[1..].split(':').skip(1);
+    let root_hash = hashes.next()?.to_lowercase();
+    let file_hash = hashes.next()?.to_lowercase();
+    Some((kind, workchain_id, shard, seq_no, root_hash, file_hash))
+}
+
+impl ArchiveIndex {
+    fn scan(path: &Path) -> Result<Self> {
+        let mut index = Self::default();
+        for entry in std::fs::read_dir(path)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let (kind, workchain_id, shard, seq_no, root_hash, file_hash) =
+                match parse_entry_name(&name) {
+                    Some(parsed) => parsed,
+                    None => continue,
+                };
+            let archive_entry = ArchiveEntry { path: entry.path(), root_hash, file_hash };
+            match kind {
+                "block" => {
+                    index.blocks_by_root_hash
+                        .insert(archive_entry.root_hash.clone(), archive_entry.path.clone());
+                    index.blocks.insert((workchain_id, shard, seq_no), archive_entry);
+                }
+                "proof" if workchain_id == -1 => {
+                    index.mc_proofs.insert(seq_no, archive_entry);
+                }
+                "zerostate" if workchain_id == -1 => {
+                    index.zerostate = Some(archive_entry);
+                }
+                _ => {}
+            }
+        }
+        Ok(index)
+    }
+}
+
+/// Reads blocks and proofs from a local directory with the entries of node archive packages.
+///
+/// Entries are files named as `block_(<workchain_id>,<shard>,<seq_no>):<root_hash>:<file_hash>`,
+/// `proof_(...)` for masterchain block proofs and `zerostate_(-1,8000000000000000,0):...`
+/// for the masterchain zerostate. Shard is a hex encoded shard prefix with tag.
+/// New entries added to the directory are picked up when requested data is missing in the index.
+pub(crate) struct ArchiveDataSource {
+    path: PathBuf,
+    index: RwLock<ArchiveIndex>,
+}
+
+impl ArchiveDataSource {
+    pub async fn new(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let index = ArchiveIndex::scan(&path)
+            .map_err(|err| err_msg(format!("Unable to read archive {}: {}", path.display(), err)))?;
+        Ok(Self { path, index: RwLock::new(index) })
+    }
+
+    async fn rescan(&self) -> Result<()> {
+        *self.index.write().await = ArchiveIndex::scan(&self.path)?;
+        Ok(())
+    }
+
+    /// Looks for the data in the index, rescanning the archive once if it is not found
+    async fn find<T>(&self, find: impl Fn(&ArchiveIndex) -> Option<T>) -> Result<Option<T>> {
+        if let Some(found) = find(&*self.index.read().await) {
+            return Ok(Some(found));
+        }
+        self.rescan().await?;
+        Ok(find(&*self.index.read().await))
+    }
+
+    async fn read(path: &Path) -> Result<Vec<u8>> {
+        tokio::fs::read(path).await
+            .map_err(|err| err_msg(format!("Unable to read {}: {}", path.display(), err)))
+    }
+
+    fn proof_to_value(seq_no: u32, entry: &ArchiveEntry, boc: &[u8]) -> Value {
+        json!({
+            "id": entry.root_hash,
+            "workchain_id": -1,
+            "shard": format!("{:016x}", MASTERCHAIN_SHARD),
+            "seq_no": seq_no,
+            "file_hash": entry.file_hash,
+            "boc": base64::encode(boc),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl ProofDataSource for ArchiveDataSource {
+    async fn query_network_uid(&self) -> Result<NetworkUID> {
+        let (zerostate, first_block) = self.find(|index| {
+            let first_block = index.blocks.get(&(-1, MASTERCHAIN_SHARD, 1))
+                .or_else(|| index.mc_proofs.get(&1))?;
+            Some((index.zerostate.clone()?, first_block.clone()))
+        }).await?
+            .ok_or_else(|| err_msg(
+                "Archive must contain masterchain zerostate and masterchain block #1 or its proof"
+            ))?;
+
+        Ok(NetworkUID {
+            zerostate_root_hash: UInt256::from_str(&zerostate.root_hash)?,
+            first_master_block_root_hash: UInt256::from_str(&first_block.root_hash)?,
+        })
+    }
+
+    async fn query_zerostate_boc(&self) -> Result<Vec<u8>> {
+        let zerostate = self.find(|index| index.zerostate.clone()).await?
+            .ok_or_else(|| err_msg("Masterchain zerostate is not found in the archive"))?;
+        Self::read(&zerostate.path).await
+    }
+
+    async fn query_block_boc(&self, root_hash: &str) -> Result<Option<Vec<u8>>> {
+        let root_hash = root_hash.to_lowercase();
+        match self.find(|index| index.blocks_by_root_hash.get(&root_hash).cloned()).await? {
+            Some(path) => Ok(Some(Self::read(&path).await?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn query_mc_blocks_file_hashes(&self, seq_nos: &[u32]) -> Result<Vec<(u32, String)>> {
+        let mut sorted = seq_nos.to_vec();
+        sorted.sort();
+        let index = self.index.read().await;
+        Ok(sorted.into_iter()
+            .filter_map(|seq_no| {
+                index.mc_proofs.get(&seq_no)
+                    .or_else(|| index.blocks.get(&(-1, MASTERCHAIN_SHARD, seq_no)))
+                    .map(|entry| (seq_no, entry.file_hash.clone()))
+            })
+            .collect())
+    }
+
+    async fn query_mc_blocks_proofs(
+        &self,
+        seq_no_range: Range<u32>,
+        key_blocks_only: bool,
+    ) -> Result<Vec<(u32, Value)>> {
+        let entries: Vec<(u32, ArchiveEntry)> = self.find(|index| {
+            let entries: Vec<_> = index.mc_proofs.range(seq_no_range.clone())
+                .map(|(seq_no, entry)| (*seq_no, entry.clone()))
+                .collect();
+            // Key blocks are sparse, so a missing proof can be found only for a single block
+            if entries.is_empty() && seq_no_range.len() == 1 { None } else { Some(entries) }
+        }).await?.unwrap_or_default();
+
+        let mut result = Vec::with_capacity(entries.len());
+        for (seq_no, entry) in entries {
+            let boc = Self::read(&entry.path).await?;
+            if key_blocks_only {
+                let (block, _) = BlockProof::deserialize(&boc)?.virtualize_block()?;
+                if !block.read_info()?.key_block() {
+                    continue;
+                }
+            }
+            result.push((seq_no, Self::proof_to_value(seq_no, &entry, &boc)));
+        }
+
+        Ok(result)
+    }
+
+    async fn query_mc_blocks_shards(
+        &self,
+        first_seq_no: u32,
+        limit: u32,
+    ) -> Result<Vec<(u32, Vec<(ShardIdent, u32)>)>> {
+        let entries: Vec<(u32, PathBuf)> = self.find(|index| {
+            let entries: Vec<_> = index.blocks
+                .range((-1, MASTERCHAIN_SHARD, first_seq_no)..=(-1, MASTERCHAIN_SHARD, u32::MAX))
+                .take(limit as usize)
+                .map(|((_, _, seq_no), entry)| (*seq_no, entry.path.clone()))
+                .collect();
+            if entries.is_empty() { None } else { Some(entries) }
+        }).await?
+            .ok_or_else(|| err_msg(format!(
+                "Masterchain blocks starting from seq_no {} are not found in the archive",
+                first_seq_no,
+            )))?;
+
+        let mut result = Vec::with_capacity(entries.len());
+        for (seq_no, path) in entries {
+            result.push((seq_no, read_mc_block_shards(&Self::read(&path).await?)?));
+        }
+
+        Ok(result)
+    }
+
+    async fn query_shard_blocks_bocs(
+        &self,
+        shard: &ShardIdent,
+        seq_no_range: Range<u32>,
+    ) -> Result<Vec<(u32, Vec<u8>)>> {
+        let workchain_id = shard.workchain_id();
+        let prefix = shard.shard_prefix_with_tag();
+        let expected_len = seq_no_range.len();
+        let entries: Vec<(u32, PathBuf)> = self.find(|index| {
+            let entries: Vec<_> = index.blocks
+                .range((workchain_id, prefix, seq_no_range.start)..(workchain_id, prefix, seq_no_range.end))
+                .map(|((_, _, seq_no), entry)| (*seq_no, entry.path.clone()))
+                .collect();
+            if entries.len() < expected_len { None } else { Some(entries) }
+        }).await?.unwrap_or_default();
+
+        let mut result = Vec::with_capacity(entries.len());
+        for (seq_no, path) in entries {
+            result.push((seq_no, Self::read(&path).await?));
+        }
+
+        Ok(result)
+    }
+
+    async fn query_transaction_data(&self, _id: &str, _fields: &str) -> Result<Option<Value>> {
+        bail!("Transaction `boc` must be provided to check proofs against the archive")
+    }
+
+    async fn query_message_data(&self, _id: &str, _fields: &str) -> Result<Option<Value>> {
+        bail!("Message `boc` must be provided to check proofs against the archive")
+    }
+}
@@ -0,0 +1,479 @@
+use std::sync::Arc;
+
+use failure::bail;
+use serde_json::Value;
+use ton_block::{Account, Block, Deserializable, ShardStateUnsplit, Transaction};
+use ton_types::{Result, UInt256};
+
+use crate::boc::internal::{deserialize_object_from_boc_bin, get_boc_hash};
+use crate::ClientContext;
+use crate::encoding::{account_decode, base64_decode};
+use crate::error::ClientResult;
+use crate::proofs::{
+    account_get_last_transaction, BlockProof, check_account_state_update,
+    check_transaction_in_block, get_current_network_uid, get_trusted_key_blocks, json,
+    ParamsOfProofBlockData, ParamsOfProofTransactionData, proof_block_data,
+    proof_transaction_data, transaction_get_required_data,
+};
+use crate::tvm::accounts_cache::query_account_boc;
+use crate::proofs::engine::ProofHelperEngineImpl;
+use crate::proofs::errors::Error;
+
+const ATTESTATION_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Debug, ApiType, PartialEq)]
+pub enum AttestedDataKind {
+    /// Block's data, proven by `proofs.proof_block_data`.
+    Block,
+    /// Transaction's data, proven by `proofs.proof_transaction_data`.
+    Transaction,
+    /// Account's state, proven by the last transaction of the account: the transaction is
+    /// proven by `proofs.proof_transaction_data` and its state update must end with the
+    /// account state hash.
+    Account,
+}
+
+impl Default for AttestedDataKind {
+    fn default() -> Self {
+        AttestedDataKind::Block
+    }
+}
+
+/// Self-contained envelope with the proven data and everything required to prove it again
+/// without access to DApp server.
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ProofAttestation {
+    /// Version of the attestation format.
+    pub version: u32,
+    /// Kind of the attested data.
+    pub kind: AttestedDataKind,
+    /// Attested data, exactly as it was passed to `proofs.attest`.
+    pub data: Value,
+    /// BOC of the attested transaction or account encoded as `base64`. Not set for blocks.
+    pub boc: Option<String>,
+    /// BOC of the last transaction of the attested account encoded as `base64`.
+    /// Set only for accounts.
+    pub transaction: Option<String>,
+    /// Root hash of the network's zerostate.
+    pub zerostate_root_hash: String,
+    /// Network's zerostate BOC encoded as `base64`.
+    /// Set only if the proof chain starts from the zerostate instead of a trusted key-block.
+    pub zerostate: Option<String>,
+    /// Chain of masterchain block proofs in the same format as stored by SDK.
+    /// The first one is the proof of the trusted key-block (or of the first key-block after the
+    /// zerostate), each next key-block is signed by validators of the previous one and the last
+    /// one is the proof of the masterchain block which contains or references the attested data.
+    pub mc_proofs: Vec<Value>,
+    /// BOC of the masterchain block, proven by the last item of `mc_proofs`, encoded as `base64`.
+    pub mc_block: String,
+    /// BOCs of shard blocks encoded as `base64`, starting from the block containing the data and
+    /// up to the top shard block referenced by `mc_block`. Empty for masterchain data.
+    pub shard_blocks: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ParamsOfAttest {
+    /// Kind of the data to attest.
+    pub kind: AttestedDataKind,
+    /// Single block's, transaction's or account's data as queried from DApp server. Required
+    /// fields are the same as for `proofs.proof_block_data` and `proofs.proof_transaction_data`
+    /// for blocks and transactions. Accounts require `boc` or `id` field.
+    pub data: Value,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ResultOfAttest {
+    /// Attestation envelope.
+    pub attestation: ProofAttestation,
+}
+
+/// Proves the given data and exports it with the proof path into a self-contained envelope.
+///
+/// The data is proven the same way as `proofs.proof_block_data` or
+/// `proofs.proof_transaction_data` do. Accounts are proven with their last transaction, whose
+/// state update must end with the account state hash. Then the envelope is filled with the BOCs of blocks
+/// linking the data with a masterchain block and with validators-signed proofs of all key-blocks
+/// from the trusted authority root (a hardcoded trusted key-block or the zerostate) up to this
+/// masterchain block.
+///
+/// The envelope can be passed to third parties, which are able to re-verify it with
+/// `proofs.verify_attestation` without any requests to DApp server.
+#[api_function]
+pub async fn attest(
+    context: Arc<ClientContext>,
+    params: ParamsOfAttest,
+) -> ClientResult<ResultOfAttest> {
+    let engine = ProofHelperEngineImpl::new(Arc::clone(&context)).await
+        .map_err(|err| Error::proof_check_failed(err))?;
+
+    let (block_boc, boc, transaction) = match params.kind {
+        AttestedDataKind::Block => {
+            proof_block_data(
+                Arc::clone(&context),
+                ParamsOfProofBlockData { block: params.data.clone(), send_events: None, verification_policy: None },
+            ).await?;
+
+            let block_boc = if let Some(boc) = params.data["boc"].as_str() {
+                base64_decode(boc)?
+            } else if let Some(id) = params.data["id"].as_str() {
+                engine.download_block_boc(id).await
+                    .map_err(|err| Error::proof_check_failed(err))?
+            } else {
+                return Err(Error::invalid_data("Block's BOC or id are required"));
+            };
+
+            (block_boc, None, None)
+        }
+        AttestedDataKind::Transaction => {
+            proof_transaction_data(
+                Arc::clone(&context),
+                ParamsOfProofTransactionData { transaction: params.data.clone(), verification_policy: None },
+            ).await?;
+
+            let (_root_hash, block_id, boc, _transaction) =
+                transaction_get_required_data(&engine, &params.data).await?;
+            let block_boc = engine.download_block_boc(&block_id).await
+                .map_err(|err| Error::invalid_data(err))?;
+
+            (block_boc, Some(base64::encode(&boc)), None)
+        }
+        AttestedDataKind::Account => {
+            let boc = if let Some(boc) = params.data["boc"].as_str() {
+                base64_decode(boc)?
+            } else if let Some(id) = params.data["id"].as_str() {
+                base64_decode(&query_account_boc(&context, &account_decode(id)?).await?)?
+            } else {
+                return Err(Error::invalid_data("Account's BOC or id are required"));
+            };
+            let (account, account_hash) = deserialize_object_from_boc_bin::<Account>(&boc)?;
+
+            let transaction = account_get_last_transaction(&context, &account, &account_hash).await?;
+            proof_transaction_data(
+                Arc::clone(&context),
+                ParamsOfProofTransactionData { transaction: transaction.clone(), verification_policy: None },
+            ).await?;
+
+            let block_id = transaction["block_id"].as_str()
+                .ok_or_else(|| Error::invalid_data("Transaction has no `block_id`"))?;
+            let block_boc = engine.download_block_boc(block_id).await
+                .map_err(|err| Error::invalid_data(err))?;
+
+            (
+                block_boc,
+                Some(base64::encode(&boc)),
+                transaction["boc"].as_str().map(|boc| boc.to_string()),
+            )
+        }
+    };
+
+    let attestation = collect_attestation(&engine, &block_boc).await
+        .map_err(|err| Error::internal_error(err))?;
+
+    Ok(ResultOfAttest {
+        attestation: ProofAttestation {
+            kind: params.kind,
+            data: params.data,
+            boc,
+            transaction,
+            ..attestation
+        },
+    })
+}
+
+async fn collect_attestation(
+    engine: &ProofHelperEngineImpl,
+    block_boc: &[u8],
+) -> Result<ProofAttestation> {
+    let block = Block::construct_from_bytes(block_boc)?;
+    let info = block.read_info()?;
+
+    let (mc_seq_no, mc_block, shard_blocks) = if info.shard().is_masterchain() {
+        (info.seq_no(), block_boc.to_vec(), Vec::new())
+    } else {
+        engine.collect_shard_block_path(block_boc).await?
+    };
+
+    let (mc_proofs, from_zerostate) = engine.collect_mc_proof_chain(mc_seq_no).await?;
+    let zerostate = if from_zerostate {
+        Some(base64::encode(&engine.load_zerostate_boc().await?))
+    } else {
+        None
+    };
+
+    let zerostate_root_hash = get_current_network_uid(engine.context()).await?
+        .zerostate_root_hash
+        .as_hex_string();
+
+    Ok(ProofAttestation {
+        version: ATTESTATION_VERSION,
+        zerostate_root_hash,
+        zerostate,
+        mc_proofs,
+        mc_block: base64::encode(&mc_block),
+        shard_blocks: shard_blocks.iter().map(|boc| base64::encode(boc)).collect(),
+        ..Default::default()
+    })
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ParamsOfVerifyAttestation {
+    /// Attestation envelope produced by `proofs.attest`.
+    pub attestation: ProofAttestation,
+    /// Root hash of the zerostate of the network the data is expected to belong to.
+    /// If not specified, any network with trusted key-blocks (hardcoded ones for MainNet
+    /// and DevNet or configured in `proofs.trusted_key_blocks`) is accepted.
+    pub zerostate_root_hash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ResultOfVerifyAttestation {
+    /// Verified data.
+    pub data: Value,
+    /// `seq_no` of the masterchain block which contains or references the data.
+    pub mc_seq_no: u32,
+    /// Generation time of the masterchain block which contains or references the data.
+    pub mc_gen_utime: u32,
+}
+
+/// Verifies the attestation envelope, produced by `proofs.attest`, offline.
+///
+/// The function checks that the first proof in the chain belongs to a trusted
+/// key-block (or to the first key-block after the zerostate with the expected root hash), then
+/// checks validators' signatures of each next proof using the validator set of the previous one,
+/// follows the links from the proven masterchain block to the block with the data and finally
+/// compares the attested data with the proven.
+///
+/// No requests to DApp server are made, so the function can be used by the services which
+/// receive the data from untrusted parties.
+#[api_function]
+pub fn verify_attestation(
+    context: Arc<ClientContext>,
+    params: ParamsOfVerifyAttestation,
+) -> ClientResult<ResultOfVerifyAttestation> {
+    let attestation = params.attestation;
+    if attestation.version != ATTESTATION_VERSION {
+        return Err(Error::invalid_data(format!(
+            "Unsupported attestation version: {}",
+            attestation.version,
+        )));
+    }
+
+    let zerostate_root_hash = UInt256::from_str(&attestation.zerostate_root_hash)
+        .map_err(|err| Error::invalid_data(err))?;
+    let trusted_key_blocks = get_trusted_key_blocks(&context, &zerostate_root_hash)
+        .map_err(|err| Error::invalid_data(err))?;
+    match params.zerostate_root_hash {
+        Some(expected) => {
+            let expected = UInt256::from_str(&expected)
+                .map_err(|err| Error::invalid_data(err))?;
+            if expected != zerostate_root_hash {
+                return Err(Error::proof_check_failed(format!(
+                    "Attestation belongs to the network with zerostate root_hash `{}`, \
+                        but `{}` is expected",
+                    zerostate_root_hash,
+                    expected,
+                )));
+            }
+        }
+        None => if trusted_key_blocks.is_empty() {
+            return Err(Error::proof_check_failed(format!(
+                "Unknown network with zerostate root_hash `{}`",
+                zerostate_root_hash,
+            )));
+        }
+    }
+
+    let mc_proof = check_mc_proof_chain(&attestation, &zerostate_root_hash, &trusted_key_blocks)
+        .map_err(|err| Error::proof_check_failed(err))?;
+    let (_virt_block, mc_block_info) = mc_proof.pre_check_block_proof()
+        .map_err(|err| Error::proof_check_failed(err))?;
+
+    let (block_id, block, block_boc) = check_block_path(&attestation, &mc_proof)
+        .map_err(|err| Error::proof_check_failed(err))?;
+
+    match attestation.kind {
+        AttestedDataKind::Block => {
+            let block_json = json::serialize_block(block_id, block, block_boc)
+                .map_err(|err| Error::invalid_data(err))?;
+
+            json::compare_blocks(&attestation.data, &block_json)?;
+        }
+        AttestedDataKind::Transaction => {
+            let boc = base64_decode(
+                attestation.boc.as_deref()
+                    .ok_or_else(|| Error::invalid_data("Transaction's BOC is required"))?
+            )?;
+            let (transaction, root_hash) = deserialize_object_from_boc_bin::<Transaction>(&boc)?;
+
+            check_transaction_in_block(&block, &block_id, &root_hash)?;
+
+            let workchain_id = block.read_info()
+                .map_err(|err| Error::invalid_data(err))?
+                .shard()
+                .workchain_id();
+            let transaction_json = json::serialize_transaction(
+                root_hash,
+                transaction,
+                block_id,
+                workchain_id,
+                boc,
+            ).map_err(|err| Error::invalid_data(err))?;
+
+            json::compare_transactions(&attestation.data, &transaction_json)?;
+        }
+        AttestedDataKind::Account => {
+            let boc = base64_decode(
+                attestation.boc.as_deref()
+                    .ok_or_else(|| Error::invalid_data("Account's BOC is required"))?
+            )?;
+            let (account, account_hash) = deserialize_object_from_boc_bin::<Account>(&boc)?;
+            let transaction_boc = base64_decode(
+                attestation.transaction.as_deref()
+                    .ok_or_else(|| Error::invalid_data("Last transaction's BOC is required"))?
+            )?;
+            let (transaction, root_hash) =
+                deserialize_object_from_boc_bin::<Transaction>(&transaction_boc)?;
+
+            check_transaction_in_block(&block, &block_id, &root_hash)?;
+            check_account_state_update(&transaction, &account, &account_hash)?;
+
+            compare_account(&attestation.data, &account, &boc)?;
+        }
+    }
+
+    Ok(ResultOfVerifyAttestation {
+        data: attestation.data,
+        mc_seq_no: mc_proof.id().seq_no(),
+        mc_gen_utime: mc_block_info.gen_utime().0,
+    })
+}
+
+/// Compares the attested account's `id` and `boc` fields (if present) with the proven account
+fn compare_account(data: &Value, account: &Account, boc: &[u8]) -> ClientResult<()> {
+    if let Some(id) = data["id"].as_str() {
+        let address = account.get_addr()
+            .ok_or_else(|| Error::invalid_data("Account has no address"))?;
+        if account_decode(id)? != *address {
+            return Err(Error::data_differs_from_proven(format!(
+                "Account id `{}` differs from the proven account address `{}`",
+                id,
+                address,
+            )));
+        }
+    }
+    if let Some(data_boc) = data["boc"].as_str() {
+        if base64_decode(data_boc)? != boc {
+            return Err(Error::data_differs_from_proven("Account's `boc` differs from the proven one"));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_mc_proof_chain(
+    attestation: &ProofAttestation,
+    zerostate_root_hash: &UInt256,
+    trusted_key_blocks: &[(u32, [u8; 32])],
+) -> Result<BlockProof> {
+    let mut proofs = attestation.mc_proofs.iter()
+        .map(|value| BlockProof::from_value(value));
+
+    let first_proof = match proofs.next() {
+        Some(proof) => proof?,
+        None => bail!("Masterchain proofs chain is empty"),
+    };
+    let (virt_block, virt_block_info) = first_proof.pre_check_block_proof()?;
+
+    if let Some(ref zerostate) = attestation.zerostate {
+        let boc = base64::decode(zerostate)?;
+        let actual_hash = UInt256::from_str(&get_boc_hash(&boc)?)?;
+        if actual_hash != *zerostate_root_hash {
+            bail!(
+                "Zerostate hashes mismatch (expected `{}`, but attested is `{}`)",
+                zerostate_root_hash,
+                actual_hash,
+            );
+        }
+        let zerostate = ShardStateUnsplit::construct_from_bytes(&boc)?;
+        first_proof.check_with_zerostate(&zerostate, &virt_block, &virt_block_info)?;
+    } else {
+        let seq_no = first_proof.id().seq_no();
+        let is_trusted = trusted_key_blocks.iter()
+            .any(|(trusted_seq_no, root_hash)| {
+                *trusted_seq_no == seq_no && root_hash == first_proof.id().root_hash().as_array()
+            });
+        if !is_trusted {
+            bail!(
+                "The first proof in the chain (seq_no: {}) doesn't belong to a trusted key-block",
+                seq_no,
+            );
+        }
+    }
+
+    let mut prev_proof = first_proof;
+    for proof in proofs {
+        let proof = proof?;
+        let (virt_block, virt_block_info) = proof.pre_check_block_proof()?;
+        proof.check_with_prev_key_block_proof(&prev_proof, &virt_block, &virt_block_info)?;
+        prev_proof = proof;
+    }
+
+    Ok(prev_proof)
+}
+
+fn check_block_path(
+    attestation: &ProofAttestation,
+    mc_proof: &BlockProof,
+) -> Result<(UInt256, Block, Vec<u8>)> {
+    let mc_boc = base64::decode(&attestation.mc_block)?;
+    let mc_cell = ton_types::deserialize_tree_of_cells(&mut std::io::Cursor::new(&mc_boc))?;
+    if mc_cell.repr_hash() != *mc_proof.id().root_hash() {
+        bail!(
+            "`root_hash` of the attested masterchain block mismatches `root_hash` of its proof",
+        );
+    }
+    let mc_block = Block::construct_from_cell(mc_cell)?;
+
+    if attestation.shard_blocks.is_empty() {
+        return Ok((mc_proof.id().root_hash().clone(), mc_block, mc_boc));
+    }
+
+    let mut shard_chain = Vec::with_capacity(attestation.shard_blocks.len());
+    for boc in &attestation.shard_blocks {
+        let boc = base64::decode(boc)?;
+        let cell = ton_types::deserialize_tree_of_cells(&mut std::io::Cursor::new(&boc))?;
+        shard_chain.push((cell.repr_hash(), Block::construct_from_cell(cell)?, boc));
+    }
+
+    let shard = shard_chain[0].1.read_info()?.shard().clone();
+    if shard.is_masterchain() {
+        bail!("Shard-chain of the attestation contains masterchain block");
+    }
+
+    let (mut expected_seq_no, mut expected_root_hash) =
+        ProofHelperEngineImpl::extract_top_shard_block(&mc_block, &shard)?;
+    for (root_hash, block, _boc) in shard_chain.iter().rev() {
+        let info = block.read_info()?;
+        if *info.shard() != shard
+            || info.seq_no() != expected_seq_no
+            || *root_hash != expected_root_hash
+        {
+            bail!(
+                "Shard block (seq_no: {}, root_hash: {}) mismatches reference \
+                    (seq_no: {}, root_hash: {}) of the next block or the masterchain block",
+                info.seq_no(),
+                root_hash,
+                expected_seq_no,
+                expected_root_hash,
+            );
+        }
+
+        let prev_ref = info.read_prev_ref()?.prev1()?;
+        expected_seq_no = prev_ref.seq_no;
+        expected_root_hash = prev_ref.root_hash;
+    }
+
+    let (root_hash, block, boc) = shard_chain.swap_remove(0);
+
+    Ok((root_hash, block, boc))
+}
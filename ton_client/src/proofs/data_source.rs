@@ -0,0 +1,494 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use failure::{bail, err_msg};
+use serde_json::Value;
+use ton_block::{Block, Deserializable, ShardIdent};
+use ton_types::{Result, UInt256};
+
+use crate::client::NetworkUID;
+use crate::encoding::base64_decode;
+use crate::net::quorum::quorum_query_collection_with_min_count;
+use crate::net::transport::get_transport;
+use crate::net::{query_collection, OrderBy, ParamsOfQueryCollection, SortDirection};
+use crate::proofs::archive::ArchiveDataSource;
+use crate::proofs::Error;
+use crate::utils::json::JsonHelper;
+use crate::ClientContext;
+
+const PROOF_QUERY_RESULT: &str = "\
+    id \
+    workchain_id \
+    shard \
+    seq_no \
+    gen_utime \
+    signatures {\
+        proof \
+        catchain_seqno \
+        validator_list_hash_short \
+        sig_weight \
+        signatures {\
+            node_id \
+            r \
+            s\
+        }\
+    }\
+";
+
+/// Source of the blockchain data required to check proofs.
+///
+/// All the returned data is untrusted: the proofs engine checks it before use.
+#[async_trait::async_trait]
+pub(crate) trait ProofDataSource: Send + Sync {
+    /// Root hashes of the zerostate and of the first masterchain block
+    async fn query_network_uid(&self) -> Result<NetworkUID>;
+
+    async fn query_zerostate_boc(&self) -> Result<Vec<u8>>;
+
+    async fn query_block_boc(&self, root_hash: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Same as `query_block_boc`, but the BOC is cross-checked across at least
+    /// `endpoints_count` distinct endpoints. Sources without multiple endpoints return
+    /// the BOC of the single one.
+    async fn query_block_boc_cross_checked(
+        &self,
+        root_hash: &str,
+        _endpoints_count: u32,
+    ) -> Result<Option<Vec<u8>>> {
+        self.query_block_boc(root_hash).await
+    }
+
+    /// File hashes of the masterchain blocks with the specified `seq_nos` sorted by `seq_no`.
+    /// Blocks which are not available are skipped.
+    async fn query_mc_blocks_file_hashes(&self, seq_nos: &[u32]) -> Result<Vec<(u32, String)>>;
+
+    /// Proofs of the masterchain blocks sorted by `seq_no`. Proofs are returned in the
+    /// format accepted by `BlockProof::from_value`, `file_hash` may be absent.
+    /// The result can be limited, so the rest of the range must be requested again.
+    async fn query_mc_blocks_proofs(
+        &self,
+        seq_no_range: Range<u32>,
+        key_blocks_only: bool,
+    ) -> Result<Vec<(u32, Value)>>;
+
+    /// Top shard blocks (shard and `seq_no`) registered in the masterchain blocks with
+    /// `seq_no` starting from `first_seq_no`. Empty result means that the masterchain blocks
+    /// are not produced yet.
+    async fn query_mc_blocks_shards(
+        &self,
+        first_seq_no: u32,
+        limit: u32,
+    ) -> Result<Vec<(u32, Vec<(ShardIdent, u32)>)>>;
+
+    /// BOCs of the shard blocks sorted by `seq_no`
+    async fn query_shard_blocks_bocs(
+        &self,
+        shard: &ShardIdent,
+        seq_no_range: Range<u32>,
+    ) -> Result<Vec<(u32, Vec<u8>)>>;
+
+    async fn query_transaction_data(&self, id: &str, fields: &str) -> Result<Option<Value>>;
+
+    async fn query_message_data(&self, id: &str, fields: &str) -> Result<Option<Value>>;
+}
+
+/// Top shard blocks (shard and `seq_no`) registered in the masterchain block
+pub(crate) fn read_mc_block_shards(boc: &[u8]) -> Result<Vec<(ShardIdent, u32)>> {
+    let block = Block::construct_from_bytes(boc)?;
+    let extra = block.read_extra()?;
+    let mc_extra = extra.read_custom()?
+        .ok_or_else(|| err_msg("Unable to read McBlockExtra"))?;
+    let mut shards = Vec::new();
+    mc_extra.shards().iterate_shards(|shard, descr| {
+        shards.push((shard, descr.seq_no));
+        Ok(true)
+    })?;
+
+    Ok(shards)
+}
+
+/// Returns the data source configured for the context: the local archive
+/// if `proofs.archive_path` is set, the offline data source if `proofs.offline_mode` is set
+/// or the client is switched to the offline mode, otherwise the network transport data source.
+pub(crate) async fn get_data_source(
+    context: &Arc<ClientContext>,
+) -> Result<Arc<dyn ProofDataSource>> {
+    let path = match &context.config.proofs.archive_path {
+        Some(path) => path,
+        None if context.config.proofs.offline_mode.unwrap_or(false) || context.is_offline() => {
+            return Ok(Arc::new(OfflineDataSource));
+        }
+        None => return Ok(get_transport(context).proof_data_source()),
+    };
+
+    if let Some(archive) = context.proofs_archive.read().await.as_ref() {
+        return Ok(Arc::clone(archive) as Arc<dyn ProofDataSource>);
+    }
+
+    let mut write_guard = context.proofs_archive.write().await;
+    if let Some(archive) = write_guard.as_ref() {
+        return Ok(Arc::clone(archive) as Arc<dyn ProofDataSource>);
+    }
+    let archive = Arc::new(ArchiveDataSource::new(path).await?);
+    *write_guard = Some(Arc::clone(&archive));
+
+    Ok(archive)
+}
+
+/// Data source of the offline mode. It has no data, so the proofs engine uses only the data
+/// cached in the proofs storage and fails with `DataNotCached` error on a cache miss
+/// instead of waiting for the network.
+pub(crate) struct OfflineDataSource;
+
+fn not_cached<T>(what: impl std::fmt::Display) -> Result<T> {
+    Err(Error::data_not_cached(what).into())
+}
+
+#[async_trait::async_trait]
+impl ProofDataSource for OfflineDataSource {
+    async fn query_network_uid(&self) -> Result<NetworkUID> {
+        not_cached("network zerostate root hash")
+    }
+
+    async fn query_zerostate_boc(&self) -> Result<Vec<u8>> {
+        not_cached("masterchain zerostate")
+    }
+
+    async fn query_block_boc(&self, root_hash: &str) -> Result<Option<Vec<u8>>> {
+        not_cached(format!("block {}", root_hash))
+    }
+
+    async fn query_mc_blocks_file_hashes(&self, seq_nos: &[u32]) -> Result<Vec<(u32, String)>> {
+        not_cached(format!("file hashes of masterchain blocks {:?}", seq_nos))
+    }
+
+    async fn query_mc_blocks_proofs(
+        &self,
+        seq_no_range: Range<u32>,
+        _key_blocks_only: bool,
+    ) -> Result<Vec<(u32, Value)>> {
+        not_cached(format!("proofs of masterchain blocks {:?}", seq_no_range))
+    }
+
+    async fn query_mc_blocks_shards(
+        &self,
+        first_seq_no: u32,
+        _limit: u32,
+    ) -> Result<Vec<(u32, Vec<(ShardIdent, u32)>)>> {
+        not_cached(format!("shards of masterchain blocks starting from {}", first_seq_no))
+    }
+
+    async fn query_shard_blocks_bocs(
+        &self,
+        shard: &ShardIdent,
+        seq_no_range: Range<u32>,
+    ) -> Result<Vec<(u32, Vec<u8>)>> {
+        not_cached(format!("blocks of shard {} {:?}", shard, seq_no_range))
+    }
+
+    async fn query_transaction_data(&self, id: &str, _fields: &str) -> Result<Option<Value>> {
+        not_cached(format!("transaction {}", id))
+    }
+
+    async fn query_message_data(&self, id: &str, _fields: &str) -> Result<Option<Value>> {
+        not_cached(format!("message {}", id))
+    }
+}
+
+pub(crate) struct GraphQLDataSource {
+    context: Arc<ClientContext>,
+}
+
+impl GraphQLDataSource {
+    pub fn new(context: Arc<ClientContext>) -> Self {
+        Self { context }
+    }
+
+    fn sorting_by_seq_no() -> Vec<OrderBy> {
+        vec![
+            OrderBy {
+                path: "seq_no".to_string(),
+                direction: SortDirection::ASC,
+            },
+        ]
+    }
+
+    fn preprocess_query_result(blocks: Vec<Value>) -> Result<Vec<(u32, Value)>> {
+        let mut result = Vec::with_capacity(blocks.len());
+
+        let mut last_seq_no = 0;
+        let mut last_gen_utime = 0;
+        for block in blocks {
+            let seq_no = block.get_u32("seq_no")?;
+            let gen_utime = block.get_u32("gen_utime")?;
+            if seq_no != last_seq_no {
+                result.push((seq_no, block));
+                last_seq_no = seq_no;
+                last_gen_utime =  gen_utime;
+            } else if gen_utime > last_gen_utime {
+                let last_index = result.len() - 1;
+                result[last_index].1 = block;
+                last_gen_utime = gen_utime;
+            }
+        }
+
+        Ok(result)
+    }
+
+    async fn query_blocks(
+        &self,
+        filter: Value,
+        result: &str,
+        limit: Option<u32>,
+    ) -> Result<Vec<(u32, Value)>> {
+        Self::preprocess_query_result(query_collection(
+            Arc::clone(&self.context),
+            ParamsOfQueryCollection {
+                collection: "blocks".to_string(),
+                result: result.to_string(),
+                filter: Some(filter),
+                order: Some(Self::sorting_by_seq_no()),
+                limit,
+                ..Default::default()
+            }
+        ).await?.result)
+    }
+
+    async fn query_by_id(&self, collection: &str, id: &str, fields: &str) -> Result<Option<Value>> {
+        let mut items = query_collection(
+            Arc::clone(&self.context),
+            ParamsOfQueryCollection {
+                collection: collection.to_string(),
+                result: fields.to_string(),
+                filter: Some(json!({
+                    "id": {
+                        "eq": id,
+                    },
+                })),
+                limit: Some(1),
+                ..Default::default()
+            }
+        ).await?.result;
+
+        Ok(if items.is_empty() { None } else { Some(items.remove(0)) })
+    }
+}
+
+#[async_trait::async_trait]
+impl ProofDataSource for GraphQLDataSource {
+    async fn query_network_uid(&self) -> Result<NetworkUID> {
+        let blocks = query_collection(Arc::clone(&self.context), ParamsOfQueryCollection {
+            collection: "blocks".to_string(),
+            filter: Some(json!({
+                "workchain_id": {
+                    "eq": -1
+                },
+                "seq_no": {
+                    "eq": 1
+                },
+            })),
+            result: "id, prev_ref{root_hash}".to_string(),
+            limit: Some(1),
+            ..Default::default()
+        }).await?.result;
+
+        if blocks.is_empty() {
+            bail!("Unable to resolve zerostate's root hash: can't get masterchain block #1");
+        }
+
+        let prev_ref = &blocks[0]["prev_ref"];
+        if prev_ref.is_null() {
+            bail!("Unable to resolve zerostate's root hash: prev_ref of the block #1 is not set");
+        }
+
+        let first_master_block_root_hash = UInt256::from_str(blocks[0].get_str("id")?)?;
+        let zerostate_root_hash = UInt256::from_str(prev_ref.get_str("root_hash")?)?;
+
+        Ok(NetworkUID { zerostate_root_hash, first_master_block_root_hash })
+    }
+
+    async fn query_zerostate_boc(&self) -> Result<Vec<u8>> {
+        let zerostates = query_collection(
+            Arc::clone(&self.context),
+            ParamsOfQueryCollection {
+                collection: "zerostates".to_string(),
+                result: "boc".to_string(),
+                limit: Some(1),
+                ..Default::default()
+            }
+        ).await?.result;
+
+        if zerostates.is_empty() {
+            bail!("Unable to download network's zerostate from DApp server");
+        }
+
+        let boc = zerostates[0].get_str("boc")?;
+
+        Ok(base64::decode(boc)?)
+    }
+
+    async fn query_block_boc(&self, root_hash: &str) -> Result<Option<Vec<u8>>> {
+        self.query_block_boc_cross_checked(root_hash, 1).await
+    }
+
+    async fn query_block_boc_cross_checked(
+        &self,
+        root_hash: &str,
+        endpoints_count: u32,
+    ) -> Result<Option<Vec<u8>>> {
+        let mut blocks = quorum_query_collection_with_min_count(
+            &self.context,
+            ParamsOfQueryCollection {
+                collection: "blocks".to_string(),
+                result: "seq_no gen_utime boc".to_string(),
+                filter: Some(json!({
+                    "id": {
+                        "eq": root_hash,
+                    },
+                })),
+                limit: Some(1),
+                ..Default::default()
+            },
+            endpoints_count,
+        ).await?.result;
+        match blocks.pop() {
+            Some(block) => Ok(Some(base64::decode(block.get_str("boc")?)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn query_mc_blocks_file_hashes(&self, seq_nos: &[u32]) -> Result<Vec<(u32, String)>> {
+        // File hash of the block is known from `prev_ref` of the next block
+        let mut blocks = self.query_blocks(
+            json!({
+                "workchain_id": {
+                    "eq": -1,
+                },
+                "seq_no": {
+                    "in": seq_nos.iter().map(|seq_no| *seq_no + 1).collect::<Vec<u32>>(),
+                }
+            }),
+            "seq_no gen_utime prev_ref{file_hash}",
+            None,
+        ).await?;
+
+        let mut result = Vec::with_capacity(blocks.len());
+        for (seq_no, block) in blocks.iter_mut() {
+            result.push((*seq_no - 1, block["prev_ref"].get_str("file_hash")?.to_string()));
+        }
+
+        Ok(result)
+    }
+
+    async fn query_mc_blocks_proofs(
+        &self,
+        seq_no_range: Range<u32>,
+        key_blocks_only: bool,
+    ) -> Result<Vec<(u32, Value)>> {
+        let mut filter = json!({
+            "workchain_id": {
+                "eq": -1,
+            },
+            "seq_no": {
+                "ge": seq_no_range.start,
+                "lt": seq_no_range.end,
+            }
+        });
+        if key_blocks_only {
+            filter["key_block"] = json!({ "eq": true });
+        }
+
+        self.query_blocks(filter, PROOF_QUERY_RESULT, None).await
+    }
+
+    async fn query_mc_blocks_shards(
+        &self,
+        first_seq_no: u32,
+        limit: u32,
+    ) -> Result<Vec<(u32, Vec<(ShardIdent, u32)>)>> {
+        let blocks = self.query_blocks(
+            json!({
+                "workchain_id": { "eq": -1 },
+                "seq_no": { "ge": first_seq_no },
+            }),
+            "\
+                seq_no \
+                gen_utime \
+                master { \
+                    shard_hashes { \
+                        workchain_id \
+                        shard \
+                        descr { \
+                            seq_no \
+                            root_hash \
+                        }\
+                    }\
+                }",
+            Some(limit),
+        ).await?;
+
+        let mut result = Vec::with_capacity(blocks.len());
+        for (seq_no, value) in &blocks {
+            let mut shards = Vec::new();
+            for item in value["master"].get_array("shard_hashes")? {
+                let shard = ShardIdent::with_tagged_prefix(
+                    item.get_i32("workchain_id")?,
+                    u64::from_str_radix(item.get_str("shard")?, 16)?,
+                )?;
+                shards.push((shard, item["descr"].get_u32("seq_no")?));
+            }
+            result.push((*seq_no, shards));
+        }
+
+        Ok(result)
+    }
+
+    async fn query_shard_blocks_bocs(
+        &self,
+        shard: &ShardIdent,
+        seq_no_range: Range<u32>,
+    ) -> Result<Vec<(u32, Vec<u8>)>> {
+        let blocks = self.query_blocks(
+            json!({
+                "workchain_id": { "eq": shard.workchain_id() },
+                "shard": { "eq": shard.shard_prefix_as_str_with_tag() },
+                "seq_no": { "in": seq_no_range.collect::<Vec<u32>>() },
+            }),
+            "\
+                seq_no \
+                gen_utime \
+                id \
+                boc \
+            ",
+            None,
+        ).await?;
+
+        let mut result = Vec::with_capacity(blocks.len());
+        for (seq_no, block) in &blocks {
+            result.push((*seq_no, base64_decode(block.get_str("boc")?)?));
+        }
+
+        Ok(result)
+    }
+
+    async fn query_transaction_data(&self, id: &str, fields: &str) -> Result<Option<Value>> {
+        self.query_by_id("transactions", id, fields).await
+    }
+
+    async fn query_message_data(&self, id: &str, fields: &str) -> Result<Option<Value>> {
+        self.query_by_id("messages", id, fields).await
+    }
+}
@@ -1,91 +1,131 @@
+use std::collections::HashMap;
 use std::convert::TryInto;
 use std::future::Future;
 use std::io::Cursor;
 use std::ops::Range;
-use std::sync::Arc;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, Weak};
 
 use failure::{bail, err_msg};
 use serde_json::Value;
-use ton_block::{BinTreeType, Block, Deserializable, InRefValue, ShardIdent, ShardStateUnsplit};
+use ton_block::{BinTreeType, Block, BlockInfo, Deserializable, InRefValue, ShardIdent, ShardStateUnsplit};
 use ton_types::{deserialize_tree_of_cells, Result, UInt256};
 
 use crate::boc::internal::get_boc_hash;
-use crate::client::storage::{InMemoryKeyValueStorage, KeyValueStorage};
+use crate::client::storage::{InMemoryKeyValueStorage, KeyValueStorage, LruKeyValueStorage};
 use crate::ClientContext;
-use crate::encoding::base64_decode;
 use crate::error::ClientResult;
-use crate::net::{OrderBy, ParamsOfQueryCollection, query_collection, SortDirection};
-use crate::proofs::{BlockProof, get_current_network_uid, ProofHelperEngine, resolve_initial_trusted_key_block};
-use crate::proofs::Error;
+use crate::proofs::data_source::{get_data_source, GraphQLDataSource, ProofDataSource};
+use crate::proofs::{BlockProof, get_current_network_uid, get_trust_scope, get_trusted_key_blocks, ProofHelperEngine, resolve_initial_trusted_key_block, run_blocking};
+use crate::proofs::{Error, ProofChainProgressEvent, ProofsStorageType, ProofsVerificationPolicy};
+use crate::proofs::forensics::ForensicRecord;
 use crate::utils::json::JsonHelper;
 
+lazy_static! {
+    // Proof storages shared between all client contexts of the process connected to the same
+    // network. Storages are held weakly, so they are released with the last context using them.
+    static ref SHARED_PROOF_STORAGES: Mutex<HashMap<String, Weak<dyn KeyValueStorage>>> =
+        Mutex::new(HashMap::new());
+}
+
 const ZEROSTATE_KEY: &str = "zerostate";
 const ZEROSTATE_RIGHT_BOUND_KEY: &str = "zs_right_boundary_seq_no";
-const PROOF_QUERY_RESULT: &str = "\
-    id \
-    workchain_id \
-    shard \
-    seq_no \
-    gen_utime \
-    signatures {\
-        proof \
-        catchain_seqno \
-        validator_list_hash_short \
-        sig_weight \
-        signatures {\
-            node_id \
-            r \
-            s\
-        }\
-    }\
-";
+/// Minimal number of endpoints the block BOCs are cross-checked across in the paranoid mode
+const PARANOID_MIN_ENDPOINTS: u32 = 2;
+
+pub(crate) type ProgressCallback =
+    Arc<dyn Fn(ProofChainProgressEvent) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
 
 pub(crate) struct ProofHelperEngineImpl {
     context: Arc<ClientContext>,
     storage: Arc<dyn KeyValueStorage>,
+    data_source: Arc<dyn ProofDataSource>,
+    forensics: Option<Mutex<ForensicRecord>>,
+    on_progress: Option<ProgressCallback>,
+    policy: ProofsVerificationPolicy,
 }
 
 impl ProofHelperEngineImpl {
     pub async fn new(context: Arc<ClientContext>) -> Result<Self> {
         let storage = Self::obtain_proof_storage(&context).await?;
+        let data_source = get_data_source(&context).await?;
+        let forensics = Self::new_forensic_record(&context);
+        let policy = context.config.proofs.verification_policy.unwrap_or_default();
 
-        Ok(Self::with_values(context, storage))
+        Ok(Self { context, storage, data_source, forensics, on_progress: None, policy })
     }
 
     pub fn with_values(context: Arc<ClientContext>, storage: Arc<dyn KeyValueStorage>) -> Self {
-        Self { context, storage }
+        let data_source = Arc::new(GraphQLDataSource::new(Arc::clone(&context)));
+        let forensics = Self::new_forensic_record(&context);
+        let policy = context.config.proofs.verification_policy.unwrap_or_default();
+        Self { context, storage, data_source, forensics, on_progress: None, policy }
+    }
+
+    /// Sets the callback notified about each key block verified while downloading
+    /// the proof chain
+    pub fn with_progress(mut self, on_progress: ProgressCallback) -> Self {
+        self.on_progress = Some(on_progress);
+        self
+    }
+
+    /// Overrides the verification policy of the config for this engine
+    pub fn with_policy(mut self, policy: Option<ProofsVerificationPolicy>) -> Self {
+        if let Some(policy) = policy {
+            self.policy = policy;
+        }
+        self
+    }
+
+    pub fn policy(&self) -> ProofsVerificationPolicy {
+        self.policy
+    }
+
+    fn new_forensic_record(context: &ClientContext) -> Option<Mutex<ForensicRecord>> {
+        if context.config.proofs.forensic_dumps.unwrap_or_default() {
+            Some(Mutex::new(ForensicRecord::default()))
+        } else {
+            None
+        }
     }
 
     pub fn context(&self) -> &Arc<ClientContext> {
         &self.context
     }
 
-    #[cfg(test)]
     pub fn storage(&self) -> &Arc<dyn KeyValueStorage> {
         &self.storage
     }
 
+    /// Returns the data used by the checks if forensic dumps are enabled
+    pub(crate) fn forensic_record(&self) -> Option<ForensicRecord> {
+        self.forensics.as_ref()
+            .and_then(|forensics| forensics.lock().ok().map(|record| record.clone()))
+    }
+
+    fn record_block(&self, root_hash: &str, boc: &[u8]) {
+        if let Some(Ok(mut record)) = self.forensics.as_ref().map(|forensics| forensics.lock()) {
+            if !record.blocks.iter().any(|(id, _)| id == root_hash) {
+                record.blocks.push((root_hash.to_string(), boc.to_vec()));
+            }
+        }
+    }
+
+    fn record_proof(&self, proof: &Value) {
+        if let Some(Ok(mut record)) = self.forensics.as_ref().map(|forensics| forensics.lock()) {
+            record.proofs.push(proof.clone());
+        }
+    }
+
     async fn obtain_proof_storage(context: &Arc<ClientContext>) -> Result<Arc<dyn KeyValueStorage>> {
         if let Some(storage) = context.proofs_storage.read().await.as_ref() {
             return Ok(Arc::clone(storage));
         }
 
-        let new_storage = if !context.config.proofs.cache_in_local_storage {
-            Arc::new(InMemoryKeyValueStorage::new()) as Arc<dyn KeyValueStorage>
+        let new_storage = if context.config.proofs.shared_cache {
+            Self::obtain_shared_proof_storage(context).await?
         } else {
-            let network_uid = get_current_network_uid(&context).await?;
-
-            let storage_name = format!(
-                "proofs/{}/{}",
-                Self::gen_root_hash_prefix(network_uid.zerostate_root_hash.as_slice()),
-                Self::gen_root_hash_prefix(network_uid.first_master_block_root_hash.as_slice()),
-            );
-            Arc::new(
-                crate::client::LocalStorage::new(
-                    context.config.local_storage_path.clone(),
-                    storage_name,
-                ).await?
-            ) as Arc<dyn KeyValueStorage>
+            Self::create_proof_storage(context).await?
         };
 
         let mut write_guard = context.proofs_storage.write().await;
@@ -98,70 +138,94 @@ impl ProofHelperEngineImpl {
         Ok(new_storage)
     }
 
-    fn gen_root_hash_prefix(root_hash: &[u8]) -> String {
-        hex::encode(&root_hash[..std::cmp::min(4, root_hash.len())])
+    async fn create_proof_storage(context: &Arc<ClientContext>) -> Result<Arc<dyn KeyValueStorage>> {
+        let storage = Self::create_backend_storage(context).await?;
+        Ok(match context.config.proofs.max_storage_size {
+            Some(max_size) => Arc::new(LruKeyValueStorage::new(storage, max_size).await?),
+            None => storage,
+        })
     }
 
-    fn mc_proof_key(mc_seq_no: u32) -> String {
-        format!("proof_mc_{}", mc_seq_no)
-    }
+    async fn create_backend_storage(context: &Arc<ClientContext>) -> Result<Arc<dyn KeyValueStorage>> {
+        match context.config.proofs.storage_type() {
+            ProofsStorageType::Memory => return Ok(Arc::new(InMemoryKeyValueStorage::new())),
+            ProofsStorageType::FileSystem if cfg!(feature = "wasm") => {
+                bail!("`FileSystem` proofs storage is not available for the web")
+            }
+            ProofsStorageType::IndexedDb if !cfg!(feature = "wasm") => {
+                bail!("`IndexedDb` proofs storage is available only for the web")
+            }
+            _ => {}
+        }
 
-    fn block_key(root_hash: &str) -> String {
-        format!("temp_block_{}", root_hash)
-    }
+        let network_uid = get_current_network_uid(&context).await?;
 
-    fn trusted_block_right_bound_key(seq_no: u32) -> String {
-        format!("trusted_{}_right_boundary_seq_no", seq_no)
+        let mut storage_name = format!(
+            "proofs/{}/{}",
+            Self::gen_root_hash_prefix(network_uid.zerostate_root_hash.as_slice()),
+            Self::gen_root_hash_prefix(network_uid.first_master_block_root_hash.as_slice()),
+        );
+        if let Some(scope) = get_trust_scope(context, &network_uid.zerostate_root_hash)? {
+            storage_name = format!("{}/{}", storage_name, scope);
+        }
+        Ok(Arc::new(
+            crate::client::LocalStorage::new(
+                context.config.local_storage_path.clone(),
+                storage_name,
+            ).await?
+        ))
     }
 
-    fn filter_for_block(root_hash: &str) -> Value {
-        json!({
-            "id": {
-                "eq": root_hash,
-            }
-        })
+    async fn obtain_shared_proof_storage(context: &Arc<ClientContext>) -> Result<Arc<dyn KeyValueStorage>> {
+        let network_uid = get_current_network_uid(&context).await?;
+        let key = format!(
+            "{}/{}/{}/{}",
+            match context.config.proofs.storage_type() {
+                ProofsStorageType::Memory => ":memory:",
+                _ => context.config.local_storage_path.as_deref().unwrap_or(""),
+            },
+            network_uid.zerostate_root_hash.as_hex_string(),
+            network_uid.first_master_block_root_hash.as_hex_string(),
+            get_trust_scope(context, &network_uid.zerostate_root_hash)?.unwrap_or_default(),
+        );
+
+        if let Some(storage) = Self::get_shared_proof_storage(&key) {
+            return Ok(storage);
+        }
+
+        let new_storage = Self::create_proof_storage(context).await?;
+
+        let mut storages = SHARED_PROOF_STORAGES.lock()
+            .map_err(|_| err_msg("Shared proof storages lock is poisoned"))?;
+        if let Some(storage) = storages.get(&key).and_then(|storage| storage.upgrade()) {
+            return Ok(storage);
+        }
+        storages.retain(|_, storage| storage.strong_count() > 0);
+        storages.insert(key, Arc::downgrade(&new_storage));
+
+        Ok(new_storage)
     }
 
-    fn filter_for_mc_block(mc_seq_no: u32) -> Value {
-        json!({
-            "workchain_id": {
-                "eq": -1,
-            },
-            "seq_no": {
-                "eq": mc_seq_no,
-            }
-        })
+    fn get_shared_proof_storage(key: &str) -> Option<Arc<dyn KeyValueStorage>> {
+        SHARED_PROOF_STORAGES.lock().ok()?
+            .get(key)
+            .and_then(|storage| storage.upgrade())
     }
 
-    fn sorting_by_seq_no() -> Vec<OrderBy> {
-        vec![
-            OrderBy {
-                path: "seq_no".to_string(),
-                direction: SortDirection::ASC,
-            },
-        ]
+    fn gen_root_hash_prefix(root_hash: &[u8]) -> String {
+        hex::encode(&root_hash[..std::cmp::min(4, root_hash.len())])
     }
 
-    fn preprocess_query_result(blocks: Vec<Value>) -> Result<Vec<(u32, Value)>> {
-        let mut result = Vec::with_capacity(blocks.len());
+    fn mc_proof_key(mc_seq_no: u32) -> String {
+        format!("proof_mc_{}", mc_seq_no)
+    }
 
-        let mut last_seq_no = 0;
-        let mut last_gen_utime = 0;
-        for block in blocks {
-            let seq_no = block.get_u32("seq_no")?;
-            let gen_utime = block.get_u32("gen_utime")?;
-            if seq_no != last_seq_no {
-                result.push((seq_no, block));
-                last_seq_no = seq_no;
-                last_gen_utime =  gen_utime;
-            } else if gen_utime > last_gen_utime {
-                let last_index = result.len() - 1;
-                result[last_index].1 = block;
-                last_gen_utime = gen_utime;
-            }
-        }
+    fn block_key(root_hash: &str) -> String {
+        format!("temp_block_{}", root_hash)
+    }
 
-        Ok(result)
+    fn trusted_block_right_bound_key(seq_no: u32) -> String {
+        format!("trusted_{}_right_boundary_seq_no", seq_no)
     }
 
     async fn get_value(&self, key: &str) -> Result<Option<Value>> {
@@ -253,78 +317,42 @@ impl ProofHelperEngineImpl {
     }
 
     pub(crate) async fn query_zerostate_boc(&self) -> Result<Vec<u8>> {
-        let zerostates = query_collection(
-            Arc::clone(&self.context),
-            ParamsOfQueryCollection {
-                collection: "zerostates".to_string(),
-                result: "boc".to_string(),
-                limit: Some(1),
-                ..Default::default()
-            }
-        ).await?.result;
-
-        if zerostates.is_empty() {
-            bail!("Unable to download network's zerostate from DApp server");
-        }
-
-        let boc = zerostates[0].get_str("boc")?;
-
-        Ok(base64::decode(boc)?)
+        self.data_source.query_zerostate_boc().await
     }
 
     pub(crate) async fn query_file_hash_from_next_block(
         &self,
-        mut mc_seq_no: u32,
+        mc_seq_no: u32,
     ) -> Result<Option<String>> {
-        mc_seq_no += 1;
-        let blocks = Self::preprocess_query_result(query_collection(
-            Arc::clone(&self.context),
-            ParamsOfQueryCollection {
-                collection: "blocks".to_string(),
-                result: "seq_no gen_utime prev_ref{file_hash}".to_string(),
-                filter: Some(Self::filter_for_mc_block(mc_seq_no)),
-                order: Some(Self::sorting_by_seq_no()),
-                ..Default::default()
-            }
-        ).await?.result)?;
+        let file_hashes = self.data_source.query_mc_blocks_file_hashes(&[mc_seq_no]).await?;
 
-        if blocks.is_empty() {
-            return Ok(None)
-        }
-
-        Ok(Some(blocks[0].1["prev_ref"].get_str("file_hash")?.to_string()))
+        Ok(file_hashes.into_iter()
+            .find(|(seq_no, _file_hash)| *seq_no == mc_seq_no)
+            .map(|(_seq_no, file_hash)| file_hash))
     }
 
     pub(crate) async fn download_block_boc(
         &self,
         root_hash: &str,
     ) -> Result<Vec<u8>> {
-        if let Some(boc) = self.read_block(root_hash).await? {
-            Ok(boc)
-        } else {
-            let blocks = query_collection(
-                Arc::clone(&self.context),
-                ParamsOfQueryCollection {
-                    collection: "blocks".to_string(),
-                    result: "seq_no gen_utime boc".to_string(),
-                    filter: Some(Self::filter_for_block(root_hash)),
-                    limit: Some(1),
-                    ..Default::default()
-                }
-            ).await?.result;
-
-            if blocks.is_empty() {
-                bail!(
-                    "Unable to download block with `root_hash`: {} from DApp server",
-                    root_hash,
-                );
-            }
+        let boc = match self.read_block(root_hash).await? {
+            Some(boc) => boc,
+            None if self.policy == ProofsVerificationPolicy::Paranoid => self.data_source
+                .query_block_boc_cross_checked(root_hash, PARANOID_MIN_ENDPOINTS).await?
+                .ok_or_else(|| Self::block_not_found(root_hash))?,
+            None => self.data_source.query_block_boc(root_hash).await?
+                .ok_or_else(|| Self::block_not_found(root_hash))?,
+        };
+        self.record_block(root_hash, &boc);
 
-            let block_json = &blocks[0];
-            let boc_base64 = block_json.get_str("boc")?;
+        Ok(boc)
+    }
 
-            Ok(base64::decode(boc_base64)?)
-        }
+    fn block_not_found(root_hash: &str) -> failure::Error {
+        err_msg(format!(
+            "Unable to download block with `root_hash`: {} from DApp server",
+            root_hash,
+        ))
     }
 
     pub(crate) async fn download_block_boc_and_calc_file_hash(
@@ -349,16 +377,8 @@ impl ProofHelperEngineImpl {
     }
 
     pub(crate) async fn query_mc_block_proof(&self, mc_seq_no: u32) -> Result<Value> {
-        let mut blocks = Self::preprocess_query_result(query_collection(
-            Arc::clone(&self.context),
-            ParamsOfQueryCollection {
-                collection: "blocks".to_string(),
-                result: PROOF_QUERY_RESULT.to_string(),
-                filter: Some(Self::filter_for_mc_block(mc_seq_no)),
-                order: Some(Self::sorting_by_seq_no()),
-                ..Default::default()
-            }
-        ).await?.result)?;
+        let mut blocks = self.data_source
+            .query_mc_blocks_proofs(mc_seq_no..mc_seq_no + 1, false).await?;
 
         if blocks.is_empty() {
             bail!(
@@ -368,10 +388,13 @@ impl ProofHelperEngineImpl {
         }
 
         let (seq_no, mut result) = blocks.remove(0);
-        result["file_hash"] = self.query_mc_block_file_hash(
-            seq_no,
-            result.get_str("id")?,
-        ).await?.into();
+        if result["file_hash"].is_null() {
+            result["file_hash"] = self.query_mc_block_file_hash(
+                seq_no,
+                result.get_str("id")?,
+            ).await?.into();
+        }
+        self.record_proof(&result);
 
         Ok(result)
     }
@@ -392,6 +415,9 @@ impl ProofHelperEngineImpl {
                     root_hash,
                 )
             }
+            if self.policy == ProofsVerificationPolicy::Paranoid {
+                BlockProof::from_value(&proof_json)?.check_proof(self).await?;
+            }
             return Ok(());
         }
 
@@ -427,84 +453,61 @@ impl ProofHelperEngineImpl {
                 return Ok(result);
             }
 
-            let key_blocks = query_collection(
-                Arc::clone(&self.context),
-                ParamsOfQueryCollection {
-                    collection: "blocks".to_string(),
-                    result: PROOF_QUERY_RESULT.to_string(),
-                    filter: Some(json!({
-                        "workchain_id": {
-                            "eq": -1,
-                        },
-                        "key_block": {
-                            "eq": true,
-                        },
-                        "seq_no": {
-                            "ge": mc_seq_no_range.start,
-                            "lt": mc_seq_no_range.end,
-                        }
-                    })),
-                    order: Some(Self::sorting_by_seq_no()),
-                    ..Default::default()
-                }
-            ).await?.result;
+            let mut key_blocks = self.data_source
+                .query_mc_blocks_proofs(mc_seq_no_range.clone(), true).await?;
 
             if key_blocks.is_empty() {
                 return Ok(result);
             }
 
-            result.append(&mut Self::preprocess_query_result(key_blocks)?);
+            for (_, proof) in &key_blocks {
+                self.record_proof(proof);
+            }
+            result.append(&mut key_blocks);
             mc_seq_no_range.start = result[result.len() - 1].0 + 1;
         }
     }
 
     pub(crate) async fn add_mc_blocks_file_hashes(
         &self,
-        mut proofs_sorted: &mut [(u32, Value)],
+        proofs_sorted: &mut [(u32, Value)],
     ) -> Result<()> {
+        let mut proofs_sorted: Vec<&mut (u32, Value)> = proofs_sorted
+            .iter_mut()
+            .filter(|(_seq_no, proof)| proof["file_hash"].is_null())
+            .collect();
+
         while proofs_sorted.len() > 0 {
-            let mut blocks = Self::preprocess_query_result(query_collection(
-                Arc::clone(&self.context),
-                ParamsOfQueryCollection {
-                    collection: "blocks".to_string(),
-                    result: "seq_no gen_utime prev_ref{file_hash}".to_string(),
-                    filter: Some(json!({
-                        "workchain_id": {
-                            "eq": -1,
-                        },
-                        "seq_no": {
-                            "in": proofs_sorted.iter()
-                                    .map(|(seq_no, _value)| *seq_no + 1)
-                                    .collect::<Vec<u32>>(),
-                        }
-                    })),
-                    order: Some(Self::sorting_by_seq_no()),
-                    ..Default::default()
-                }
-            ).await?.result)?;
+            let file_hashes = self.data_source.query_mc_blocks_file_hashes(
+                &proofs_sorted.iter().map(|(seq_no, _value)| *seq_no).collect::<Vec<u32>>()
+            ).await?;
 
-            if proofs_sorted.len() < blocks.len() {
+            if file_hashes.is_empty() {
+                bail!(
+                    "Unable to get file hash of masterchain block with seq_no: {}",
+                    proofs_sorted[0].0,
+                );
+            }
+
+            if proofs_sorted.len() < file_hashes.len() {
                 bail!(
                     "DApp server returned more blocks ({}) than expected ({})",
-                    blocks.len(),
+                    file_hashes.len(),
                     proofs_sorted.len(),
                 )
             }
 
-            let (expected, remaining) = proofs_sorted.split_at_mut(blocks.len());
-            for i in 0..blocks.len() {
-                let (seq_no, mut block) = blocks.remove(0);
-
-                let expected_seq_no = expected[i].0 + 1;
-                if seq_no != expected_seq_no {
+            let remaining = proofs_sorted.split_off(file_hashes.len());
+            for (expected, (seq_no, file_hash)) in proofs_sorted.iter_mut().zip(file_hashes) {
+                if seq_no != expected.0 {
                     bail!(
                         "Block with seq_no: {} missed on DApp server (actual seq_no: {})",
-                        expected_seq_no,
-                        seq_no,
+                        expected.0 + 1,
+                        seq_no + 1,
                     );
                 }
 
-                expected[i].1["file_hash"] = block["prev_ref"]["file_hash"].take();
+                expected.1["file_hash"] = file_hash.into();
             }
 
             proofs_sorted = remaining;
@@ -563,18 +566,105 @@ impl ProofHelperEngineImpl {
         let mut proof_values = self.query_key_blocks_proofs(mc_seq_no_range).await?;
         self.add_mc_blocks_file_hashes(&mut proof_values).await?;
 
-        let mut last_proof = None;
-        for (mc_seq_no, proof_json) in proof_values {
-            let proof = BlockProof::from_value(&proof_json)?;
-            proof.check_proof(self).await?;
+        let total_count = proof_values.len() as u32;
+        let workers = self.verification_workers();
+        let mut verified_count = 0;
+        let mut last_proof: Option<Arc<BlockProof>> = None;
+        let mut proof_values = proof_values.into_iter().peekable();
+        while proof_values.peek().is_some() {
+            let mut batch = Vec::with_capacity(workers);
+            for (mc_seq_no, proof_json) in proof_values.by_ref() {
+                let proof = Arc::new(BlockProof::from_value(&proof_json)?);
+                batch.push((mc_seq_no, proof_json, proof));
+                if batch.len() == workers {
+                    break;
+                }
+            }
+
+            let verified = if workers > 1 {
+                Self::verify_batch_in_parallel(last_proof.clone(), &batch).await
+            } else {
+                batch.iter().map(|_| Ok(false)).collect()
+            };
+
+            // Proofs are committed in the chain order, so the chain is stored only up to
+            // the first failed proof
+            for ((mc_seq_no, proof_json, proof), verified) in batch.into_iter().zip(verified) {
+                if !verified? {
+                    proof.check_proof(self).await?;
+                }
+
+                self.write_mc_block_proof(mc_seq_no, &proof_json).await?;
+                on_store_block(mc_seq_no).await?;
+
+                verified_count += 1;
+                if let Some(on_progress) = &self.on_progress {
+                    on_progress(ProofChainProgressEvent {
+                        seq_no: mc_seq_no,
+                        verified_count,
+                        total_count,
+                    }).await;
+                }
+
+                last_proof = Some(proof);
+            }
+        }
 
-            self.write_mc_block_proof(mc_seq_no, &proof_json).await?;
-            on_store_block(mc_seq_no).await?;
+        last_proof
+            .map(|proof| Arc::try_unwrap(proof).unwrap_or_else(|proof| proof.as_ref().clone()))
+            .ok_or_else(|| err_msg("Empty proof chain"))
+    }
 
-            last_proof = Some(proof);
+    fn verification_workers(&self) -> usize {
+        if cfg!(feature = "wasm") {
+            return 1;
         }
+        std::cmp::max(self.context.config.proofs.parallel_verification.unwrap_or(1), 1) as usize
+    }
 
-        last_proof.ok_or_else(|| err_msg("Empty proof chain"))
+    /// Verifies each proof of the batch on a separate thread against the previous proof
+    /// of the chain. Returns `false` for the proofs whose previous key block is not
+    /// in the batch (e.g. the first proof of the chain), they must be checked with
+    /// `BlockProof::check_proof` after the previous proofs are stored.
+    async fn verify_batch_in_parallel(
+        prev_proof: Option<Arc<BlockProof>>,
+        batch: &[(u32, Value, Arc<BlockProof>)],
+    ) -> Vec<Result<bool>> {
+        let mut prev_proof = prev_proof;
+        let mut results = Vec::with_capacity(batch.len());
+        for (_, _, proof) in batch {
+            let (sender, receiver) = futures::channel::oneshot::channel();
+            let proof = Arc::clone(proof);
+            let prev = prev_proof.replace(Arc::clone(&proof));
+            std::thread::spawn(move || {
+                let result = Self::verify_with_prev_proof(&proof, prev.as_deref());
+                // Release the proofs before the result is received, so the last proof of
+                // the chain can be unwrapped without copying
+                drop((proof, prev));
+                let _ = sender.send(result);
+            });
+            results.push(async move {
+                receiver.await
+                    .unwrap_or_else(|_| Err(err_msg("Proof verification thread terminated")))
+            });
+        }
+        futures::future::join_all(results).await
+    }
+
+    fn verify_with_prev_proof(proof: &BlockProof, prev_proof: Option<&BlockProof>) -> Result<bool> {
+        let prev_proof = match prev_proof {
+            Some(prev_proof) => prev_proof,
+            None => return Ok(false),
+        };
+        if !proof.id().shard().is_masterchain() {
+            bail!("Only masterchain block proofs are supported");
+        }
+        let (virt_block, virt_block_info) = proof.pre_check_block_proof()?;
+        if virt_block_info.prev_key_block_seqno() != prev_proof.id().seq_no {
+            return Ok(false);
+        }
+        proof.check_with_prev_key_block_proof(prev_proof, &virt_block, &virt_block_info)?;
+        Ok(true)
     }
 
     pub(crate) fn extract_top_shard_block(
@@ -609,44 +699,16 @@ impl ProofHelperEngineImpl {
         shard_block_seq_no: u32,
     ) -> Result<Option<u32>> {
         loop {
-            let blocks = Self::preprocess_query_result(query_collection(
-                Arc::clone(&self.context),
-                ParamsOfQueryCollection {
-                    collection: "blocks".to_string(),
-                    result: "\
-                        seq_no \
-                        gen_utime \
-                        master { \
-                            shard_hashes { \
-                                workchain_id \
-                                shard \
-                                descr { \
-                                    seq_no \
-                                    root_hash \
-                                }\
-                            }\
-                        }".to_string(),
-                    filter: Some(json!({
-                        "workchain_id": { "eq": -1 },
-                        "seq_no": { "ge": *first_mc_seq_no },
-                    })),
-                    order: Some(Self::sorting_by_seq_no()),
-                    limit: Some(10),
-                    ..Default::default()
-                }
-            ).await?.result)?;
+            let blocks = self.data_source
+                .query_mc_blocks_shards(*first_mc_seq_no, 10).await?;
 
             if blocks.is_empty() {
                 return Ok(None);
             }
 
-            for (seq_no, value) in &blocks {
-                let shard_hashes = value["master"].get_array("shard_hashes")?;
-                for item in shard_hashes {
-                    if item["workchain_id"] == shard.workchain_id()
-                        && item["shard"] == shard.shard_prefix_as_str_with_tag()
-                        && item["descr"].get_u32("seq_no")? >= shard_block_seq_no
-                    {
+            for (seq_no, shards) in &blocks {
+                for (item_shard, item_seq_no) in shards {
+                    if item_shard == shard && *item_seq_no >= shard_block_seq_no {
                         return Ok(Some(*seq_no))
                     }
                 }
@@ -656,30 +718,19 @@ impl ProofHelperEngineImpl {
         }
     }
 
+    pub(crate) async fn mc_block_exists(&self, mc_seq_no: u32) -> Result<bool> {
+        let blocks = self.data_source.query_mc_blocks_shards(mc_seq_no, 1).await?;
+
+        Ok(blocks.iter().any(|(seq_no, _)| *seq_no >= mc_seq_no))
+    }
+
     pub(crate) async fn query_shard_block_bocs(
         &self,
         shard: &ShardIdent,
         seq_no_range: Range<u32>,
     ) -> Result<Vec<Vec<u8>>> {
-        let blocks = Self::preprocess_query_result(query_collection(
-            Arc::clone(&self.context),
-            ParamsOfQueryCollection {
-                collection: "blocks".to_string(),
-                result: "\
-                    seq_no \
-                    gen_utime \
-                    id \
-                    boc \
-                ".to_string(),
-                filter: Some(json!({
-                    "workchain_id": { "eq": shard.workchain_id() },
-                    "shard": { "eq": shard.shard_prefix_as_str_with_tag() },
-                    "seq_no": { "in": seq_no_range.clone().collect::<Vec<u32>>() },
-                })),
-                order: Some(Self::sorting_by_seq_no()),
-                ..Default::default()
-            }
-        ).await?.result)?;
+        let blocks = self.data_source
+            .query_shard_blocks_bocs(shard, seq_no_range.clone()).await?;
 
         if blocks.is_empty() {
             bail!(
@@ -717,7 +768,7 @@ impl ProofHelperEngineImpl {
                 );
             }
 
-            result.push(base64_decode(block.get_str("boc")?)?);
+            result.push(block.clone());
         }
 
         Ok(result)
@@ -741,25 +792,35 @@ impl ProofHelperEngineImpl {
                     info.seq_no(),
                 ).await?
             {
-                let mc_proof_json = self.query_mc_block_proof(mc_seq_no).await?;
-                let mc_proof = BlockProof::from_value(&mc_proof_json)?;
-                let (_mc_block, _mc_block_info) = mc_proof.check_proof(self).await?;
+                let fast = self.policy == ProofsVerificationPolicy::Fast;
+                let (mc_proof, cached) = match self.read_mc_proof(mc_seq_no).await? {
+                    Some(mc_proof_json) => (BlockProof::from_value(&mc_proof_json)?, true),
+                    None => (BlockProof::from_value(&self.query_mc_block_proof(mc_seq_no).await?)?, false),
+                };
+                if !(fast && cached) {
+                    let (_mc_block, _mc_block_info) = mc_proof.check_proof(self).await?;
+                }
+                let mc_root_hash = mc_proof.id().root_hash().as_hex_string();
 
-                let mc_boc = self.download_block_boc(
-                    &mc_proof.id().root_hash().as_hex_string(),
-                ).await?;
-                let mc_cell = deserialize_tree_of_cells(&mut Cursor::new(&mc_boc))?;
+                let cached_mc_boc = if fast { self.read_block(&mc_root_hash).await? } else { None };
+                let mc_block = match cached_mc_boc {
+                    Some(mc_boc) => Block::construct_from_bytes(&mc_boc)?,
+                    None => {
+                        let mc_boc = self.download_block_boc(&mc_root_hash).await?;
+                        let mc_cell = deserialize_tree_of_cells(&mut Cursor::new(&mc_boc))?;
 
-                if mc_cell.repr_hash() != *mc_proof.id().root_hash() {
-                    bail!(
-                        "Proof checking failed: `root_hash` of MC block's BOC downloaded from DApp \
-                            server mismatches `root_hash` of proof for this MC block",
-                    );
-                }
+                        if mc_cell.repr_hash() != *mc_proof.id().root_hash() {
+                            bail!(
+                                "Proof checking failed: `root_hash` of MC block's BOC downloaded \
+                                    from DApp server mismatches `root_hash` of proof for this MC block",
+                            );
+                        }
 
-                self.write_block(&mc_cell.repr_hash().as_hex_string(), &mc_boc).await?;
+                        self.write_block(&mc_root_hash, &mc_boc).await?;
 
-                let mc_block = Block::construct_from_bytes(&mc_boc)?;
+                        Block::construct_from_cell(mc_cell)?
+                    }
+                };
 
                 let (top_seq_no, top_root_hash) =
                     Self::extract_top_shard_block(&mc_block, info.shard())?;
@@ -841,49 +902,13 @@ impl ProofHelperEngineImpl {
     }
 
     pub(crate) async fn query_transaction_data(&self, id: &str, fields: &str) -> Result<Value> {
-        let mut transactions = query_collection(
-            Arc::clone(&self.context),
-            ParamsOfQueryCollection {
-                collection: "transactions".to_string(),
-                result: fields.to_string(),
-                filter: Some(json!({
-                    "id": {
-                        "eq": id,
-                    },
-                })),
-                limit: Some(1),
-                ..Default::default()
-            }
-        ).await?.result;
-
-        if transactions.is_empty() {
-            bail!("Unable to download transaction data from DApp server");
-        }
-
-        Ok(transactions.remove(0))
+        self.data_source.query_transaction_data(id, fields).await?
+            .ok_or_else(|| err_msg("Unable to download transaction data from DApp server"))
     }
 
     pub(crate) async fn query_message_data(&self, id: &str, fields: &str) -> Result<Value> {
-        let mut messages = query_collection(
-            Arc::clone(&self.context),
-            ParamsOfQueryCollection {
-                collection: "messages".to_string(),
-                result: fields.to_string(),
-                filter: Some(json!({
-                    "id": {
-                        "eq": id,
-                    },
-                })),
-                limit: Some(1),
-                ..Default::default()
-            }
-        ).await?.result;
-
-        if messages.is_empty() {
-            bail!("Unable to download message data from DApp server");
-        }
-
-        Ok(messages.remove(0))
+        self.data_source.query_message_data(id, fields).await?
+            .ok_or_else(|| err_msg("Unable to download message data from DApp server"))
     }
 
     pub(crate) async fn proof_block_boc(
@@ -910,13 +935,316 @@ impl ProofHelperEngineImpl {
 
         Ok(())
     }
-}
 
-#[async_trait::async_trait]
-impl ProofHelperEngine for ProofHelperEngineImpl {
-    async fn load_zerostate(&self) -> Result<ShardStateUnsplit> {
+    async fn remove_mc_proof(&self, mc_seq_no: u32) -> Result<()> {
+        self.storage.remove(&Self::mc_proof_key(mc_seq_no)).await
+            .map_err(|err| err.into())
+    }
+
+    /// Checks the stored zerostate against the network's zerostate root hash and removes it if
+    /// it is corrupted. Returns `true` if the stored zerostate is absent or valid.
+    pub(crate) async fn verify_stored_zerostate(&self) -> Result<bool> {
+        let boc = match self.storage.get_bin(ZEROSTATE_KEY).await? {
+            Some(boc) => boc,
+            None => return Ok(true),
+        };
+
+        let expected_hash = get_current_network_uid(self.context()).await?
+            .zerostate_root_hash;
+        let is_valid = match get_boc_hash(&boc) {
+            Ok(hash) => UInt256::from_str(&hash).ok() == Some(expected_hash),
+            Err(_) => false,
+        };
+
+        if !is_valid {
+            self.storage.remove(ZEROSTATE_KEY).await?;
+        }
+
+        Ok(is_valid)
+    }
+
+    /// Checks the stored proof of the trusted key-block and removes it if it is corrupted.
+    /// Returns `true` if the stored proof is absent or valid.
+    pub(crate) async fn verify_stored_trusted_key_block_proof(
+        &self,
+        trusted_seq_no: u32,
+        trusted_root_hash: &UInt256,
+    ) -> Result<bool> {
+        let value = match self.read_mc_proof(trusted_seq_no).await {
+            Ok(Some(value)) => value,
+            Ok(None) => return Ok(true),
+            Err(_) => {
+                self.remove_mc_proof(trusted_seq_no).await?;
+                return Ok(false);
+            }
+        };
+
+        let is_valid = match BlockProof::from_value(&value) {
+            Ok(proof) => proof.id().seq_no() == trusted_seq_no
+                && proof.id().root_hash() == trusted_root_hash
+                && proof.pre_check_block_proof().is_ok(),
+            Err(_) => false,
+        };
+
+        if !is_valid {
+            self.remove_mc_proof(trusted_seq_no).await?;
+        }
+
+        Ok(is_valid)
+    }
+
+    async fn read_stored_chain_link(&self, mc_seq_no: u32) -> Option<(BlockProof, Block, BlockInfo)> {
+        let value = self.read_mc_proof(mc_seq_no).await.ok()??;
+        let proof = BlockProof::from_value(&value).ok()?;
+        if proof.id().seq_no() != mc_seq_no {
+            return None;
+        }
+        let (virt_block, virt_block_info) = proof.pre_check_block_proof().ok()?;
+        if !virt_block_info.key_block() {
+            return None;
+        }
+
+        Some((proof, virt_block, virt_block_info))
+    }
+
+    /// Re-validates the chain of stored key-block proofs from `base_seq_no` (the trusted key-block
+    /// or zerostate when `base_seq_no` is 0) up to the `right_bound` recorded in the metadata.
+    ///
+    /// The chain is walked backwards by `prev_key_block_seqno` links and then checked forwards,
+    /// each proof against the previous one, without any network requests. Proofs following the
+    /// first broken link are removed from the storage and the right boundary is truncated to the
+    /// last valid key-block.
+    pub(crate) async fn verify_stored_proof_chain(
+        &self,
+        base_seq_no: u32,
+        right_bound: u32,
+    ) -> Result<StoredChainStatus> {
+        let mut status = StoredChainStatus {
+            valid_count: 0,
+            corrupted_count: 0,
+            last_valid_seq_no: base_seq_no,
+        };
+        if right_bound <= base_seq_no {
+            return Ok(status);
+        }
+
+        let mut chain = Vec::new();
+        let mut dropped_seq_nos = Vec::new();
+        let mut cursor = right_bound;
+        while cursor > base_seq_no {
+            match self.read_stored_chain_link(cursor).await {
+                Some((proof, virt_block, virt_block_info)) => {
+                    let prev_seq_no = virt_block_info.prev_key_block_seqno();
+                    chain.push((cursor, proof, virt_block, virt_block_info));
+                    cursor = prev_seq_no;
+                }
+                None => {
+                    // The link is missing or corrupted, so everything above it can't be trusted
+                    if !matches!(self.read_mc_proof(cursor).await, Ok(None)) {
+                        status.corrupted_count += 1;
+                        dropped_seq_nos.push(cursor);
+                    }
+                    dropped_seq_nos.extend(chain.drain(..).map(|(seq_no, ..)| seq_no));
+                    break;
+                }
+            }
+        }
+        if cursor < base_seq_no {
+            dropped_seq_nos.extend(chain.drain(..).map(|(seq_no, ..)| seq_no));
+        }
+
+        let base_proof = if base_seq_no == 0 || chain.is_empty() {
+            None
+        } else {
+            self.read_mc_proof(base_seq_no).await?
+                .map(|value| BlockProof::from_value(&value))
+                .transpose()?
+        };
+        let zerostate = if base_seq_no == 0 && !chain.is_empty() {
+            Some(self.load_zerostate().await?)
+        } else {
+            None
+        };
+
+        // Returns the valid proofs of the chain and the proofs following the first broken link
+        let (valid_seq_nos, corrupted_seq_nos) = run_blocking(move || {
+            let mut valid_seq_nos = Vec::new();
+            let mut prev_proof = base_proof;
+            while let Some((seq_no, proof, virt_block, virt_block_info)) = chain.pop() {
+                let check_result = match (&prev_proof, &zerostate) {
+                    (Some(prev_proof), _) => proof.check_with_prev_key_block_proof(
+                        prev_proof,
+                        &virt_block,
+                        &virt_block_info,
+                    ),
+                    (None, Some(zerostate)) => proof.check_with_zerostate(
+                        zerostate,
+                        &virt_block,
+                        &virt_block_info,
+                    ),
+                    (None, None) => Err(err_msg("Base of the proof chain is not found")),
+                };
+
+                if check_result.is_err() {
+                    let mut corrupted_seq_nos = vec![seq_no];
+                    corrupted_seq_nos.extend(chain.drain(..).map(|(seq_no, ..)| seq_no));
+                    return Ok((valid_seq_nos, corrupted_seq_nos));
+                }
+
+                valid_seq_nos.push(seq_no);
+                prev_proof = Some(proof);
+            }
+            Ok((valid_seq_nos, Vec::new()))
+        }).await?;
+
+        if !corrupted_seq_nos.is_empty() {
+            status.corrupted_count += 1;
+            dropped_seq_nos.extend(corrupted_seq_nos);
+        }
+        if let Some(seq_no) = valid_seq_nos.last() {
+            status.valid_count += valid_seq_nos.len() as u32;
+            status.last_valid_seq_no = *seq_no;
+        }
+
+        for seq_no in dropped_seq_nos {
+            self.remove_mc_proof(seq_no).await?;
+        }
+
+        Ok(status)
+    }
+
+    /// Verifies all the stored proof chains of the current network and optionally re-downloads
+    /// the ranges found corrupted.
+    pub(crate) async fn verify_local_cache(&self, repair: bool) -> Result<LocalCacheStatus> {
+        let mut result = LocalCacheStatus::default();
+
+        if !self.verify_stored_zerostate().await? {
+            result.corrupted_count += 1;
+        }
+
+        let zs_right_bound = self.read_zs_right_bound().await?;
+        let status = self.verify_stored_proof_chain(0, zs_right_bound).await?;
+        result.add_chain_status(&status);
+        if status.last_valid_seq_no < zs_right_bound {
+            self.write_metadata_value_u32(ZEROSTATE_RIGHT_BOUND_KEY, status.last_valid_seq_no).await?;
+            if repair {
+                let range = status.last_valid_seq_no + 1..zs_right_bound + 1;
+                self.download_proof_chain(
+                    range.clone(),
+                    move |mc_seq_no| async move { self.update_zs_right_bound(mc_seq_no).await },
+                ).await?;
+                result.repaired_ranges.push(range);
+            }
+        }
+
+        let network_uid = get_current_network_uid(self.context()).await?;
+        let trusted_key_blocks =
+            get_trusted_key_blocks(self.context(), &network_uid.zerostate_root_hash)?;
+
+        for (trusted_seq_no, trusted_root_hash) in trusted_key_blocks {
+            let trusted_root_hash = UInt256::from_slice(&trusted_root_hash);
+            let bound_key = Self::trusted_block_right_bound_key(trusted_seq_no);
+            let right_bound = self.read_metadata_value_u32(&bound_key).await?;
+
+            if !self.verify_stored_trusted_key_block_proof(trusted_seq_no, &trusted_root_hash).await? {
+                result.corrupted_count += 1;
+                if repair {
+                    self.download_trusted_key_block_proof(trusted_seq_no, &trusted_root_hash).await?;
+                    result.repaired_ranges.push(trusted_seq_no..trusted_seq_no + 1);
+                } else {
+                    // The chain can't be checked without its base
+                    if let Some(right_bound) = right_bound {
+                        self.drop_stored_proof_chain(trusted_seq_no, right_bound).await?;
+                        self.storage.remove(&bound_key).await?;
+                    }
+                    continue;
+                }
+            }
+
+            let right_bound = match right_bound {
+                Some(right_bound) => right_bound,
+                None => continue,
+            };
+
+            let status = self.verify_stored_proof_chain(trusted_seq_no, right_bound).await?;
+            result.add_chain_status(&status);
+            if status.last_valid_seq_no < right_bound {
+                self.write_metadata_value_u32(&bound_key, status.last_valid_seq_no).await?;
+                if repair {
+                    let range = status.last_valid_seq_no + 1..right_bound + 1;
+                    self.download_proof_chain(
+                        range.clone(),
+                        move |mc_seq_no| async move {
+                            self.update_trusted_block_right_bound(trusted_seq_no, mc_seq_no).await
+                        },
+                    ).await?;
+                    result.repaired_ranges.push(range);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    async fn drop_stored_proof_chain(&self, base_seq_no: u32, right_bound: u32) -> Result<()> {
+        let mut cursor = right_bound;
+        while cursor > base_seq_no {
+            let prev_seq_no = self.read_stored_chain_link(cursor).await
+                .map(|(_, _, info)| info.prev_key_block_seqno());
+            self.remove_mc_proof(cursor).await?;
+            match prev_seq_no {
+                Some(prev_seq_no) => cursor = prev_seq_no,
+                None => break,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads the stored chain of key-block proofs from the `right_bound` down to (but not
+    /// including) `base_seq_no` by `prev_key_block_seqno` links. The walk stops at the first
+    /// missing or corrupted link. Proofs are returned with their `seq_no` in the walk order.
+    pub(crate) async fn read_stored_proof_chain(
+        &self,
+        base_seq_no: u32,
+        right_bound: u32,
+    ) -> Result<Vec<(u32, Value)>> {
+        let mut chain = Vec::new();
+        let mut cursor = right_bound;
+        while cursor > base_seq_no {
+            let prev_seq_no = match self.read_stored_chain_link(cursor).await {
+                Some((_, _, info)) => info.prev_key_block_seqno(),
+                None => break,
+            };
+            if let Some(value) = self.read_mc_proof(cursor).await? {
+                chain.push((cursor, value));
+            }
+            cursor = prev_seq_no;
+        }
+
+        Ok(chain)
+    }
+
+    /// Reads the stored proof of the masterchain block
+    pub(crate) async fn read_stored_mc_proof(&self, mc_seq_no: u32) -> Result<Option<Value>> {
+        self.read_mc_proof(mc_seq_no).await
+    }
+
+    /// Stores the proof of the masterchain block unless the proof for this `seq_no` is already
+    /// stored, so the proofs checked before are never replaced. Returns `true` if the proof
+    /// is written.
+    pub(crate) async fn import_mc_proof(&self, mc_seq_no: u32, value: &Value) -> Result<bool> {
+        if self.read_mc_proof(mc_seq_no).await?.is_some() {
+            return Ok(false);
+        }
+        self.write_mc_block_proof(mc_seq_no, value).await?;
+
+        Ok(true)
+    }
+
+    pub(crate) async fn load_zerostate_boc(&self) -> Result<Vec<u8>> {
         if let Some(boc) = self.storage.get_bin(ZEROSTATE_KEY).await? {
-            return ShardStateUnsplit::construct_from_bytes(&boc);
+            return Ok(boc);
         }
 
         let boc = self.query_zerostate_boc().await?;
@@ -934,7 +1262,114 @@ impl ProofHelperEngine for ProofHelperEngineImpl {
 
         self.storage.put_bin(ZEROSTATE_KEY, &boc).await?;
 
-        ShardStateUnsplit::construct_from_bytes(&boc)
+        Ok(boc)
+    }
+
+    async fn read_or_query_mc_proof(&self, mc_seq_no: u32) -> Result<Value> {
+        if let Some(value) = self.read_mc_proof(mc_seq_no).await? {
+            return Ok(value);
+        }
+
+        self.query_mc_block_proof(mc_seq_no).await
+    }
+
+    /// Collects proofs of the masterchain block with `mc_seq_no` and of all the key-blocks it
+    /// depends on, walking `prev_key_block_seqno` links back to the closest trusted
+    /// key-block or to the zerostate.
+    ///
+    /// Returns proofs ordered from the authority root to the requested block and a flag, which
+    /// is set when the chain starts from the zerostate rather than from a trusted key-block.
+    pub(crate) async fn collect_mc_proof_chain(&self, mc_seq_no: u32) -> Result<(Vec<Value>, bool)> {
+        let network_uid = get_current_network_uid(self.context()).await?;
+        let trusted_key_blocks =
+            get_trusted_key_blocks(self.context(), &network_uid.zerostate_root_hash)?;
+
+        let mut chain = Vec::new();
+        let mut seq_no = mc_seq_no;
+        loop {
+            let proof_json = self.read_or_query_mc_proof(seq_no).await?;
+            let proof = BlockProof::from_value(&proof_json)?;
+            chain.push(proof_json);
+
+            let is_trusted = trusted_key_blocks.iter()
+                .any(|(trusted_seq_no, root_hash)| {
+                    *trusted_seq_no == seq_no && root_hash == proof.id().root_hash().as_array()
+                });
+            if is_trusted {
+                chain.reverse();
+                return Ok((chain, false));
+            }
+
+            let (_virt_block, virt_block_info) = proof.pre_check_block_proof()?;
+            seq_no = virt_block_info.prev_key_block_seqno();
+            if seq_no == 0 {
+                chain.reverse();
+                return Ok((chain, true));
+            }
+        }
+    }
+
+    /// Collects the path from the already proven shard block to the masterchain block that
+    /// references it: `seq_no` and BOC of the masterchain block and BOCs of the shard-chain
+    /// starting from the given block up to the top block referenced by the masterchain.
+    pub(crate) async fn collect_shard_block_path(
+        &self,
+        boc: &[u8],
+    ) -> Result<(u32, Vec<u8>, Vec<Vec<u8>>)> {
+        let block = Block::construct_from_bytes(boc)?;
+        let info = block.read_info()?;
+        let master_ref = info.read_master_ref()?
+            .ok_or_else(|| err_msg("Unable to read master_ref of block"))?;
+
+        let mut first_mc_seq_no = master_ref.master.seq_no;
+        let mc_seq_no = self.query_closest_mc_block_for_shard_block(
+            &mut first_mc_seq_no,
+            info.shard(),
+            info.seq_no(),
+        ).await?
+            .ok_or_else(|| err_msg("Masterchain block referencing the shard block is not found"))?;
+
+        let mc_proof = BlockProof::from_value(&self.read_or_query_mc_proof(mc_seq_no).await?)?;
+        let mc_boc = self.download_block_boc(&mc_proof.id().root_hash().as_hex_string()).await?;
+        let mc_block = Block::construct_from_bytes(&mc_boc)?;
+
+        let (top_seq_no, _top_root_hash) = Self::extract_top_shard_block(&mc_block, info.shard())?;
+
+        let mut shard_chain = vec![boc.to_vec()];
+        if top_seq_no > info.seq_no() {
+            shard_chain.append(
+                &mut self.query_shard_block_bocs(info.shard(), (info.seq_no() + 1)..(top_seq_no + 1)).await?
+            );
+        }
+
+        Ok((mc_seq_no, mc_boc, shard_chain))
+    }
+}
+
+pub(crate) struct StoredChainStatus {
+    pub valid_count: u32,
+    pub corrupted_count: u32,
+    pub last_valid_seq_no: u32,
+}
+
+#[derive(Default)]
+pub(crate) struct LocalCacheStatus {
+    pub valid_count: u32,
+    pub corrupted_count: u32,
+    pub repaired_ranges: Vec<Range<u32>>,
+}
+
+impl LocalCacheStatus {
+    fn add_chain_status(&mut self, status: &StoredChainStatus) {
+        self.valid_count += status.valid_count;
+        self.corrupted_count += status.corrupted_count;
+    }
+}
+
+#[async_trait::async_trait]
+impl ProofHelperEngine for ProofHelperEngineImpl {
+    async fn load_zerostate(&self) -> Result<ShardStateUnsplit> {
+        ShardStateUnsplit::construct_from_bytes(&self.load_zerostate_boc().await?)
     }
 
     async fn load_key_block_proof(&self, mc_seq_no: u32) -> Result<BlockProof> {
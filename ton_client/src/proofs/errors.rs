@@ -1,3 +1,4 @@
+use std::any::Any;
 use std::fmt::Display;
 
 use crate::error::ClientError;
@@ -8,6 +9,7 @@ pub enum ErrorCode {
     ProofCheckFailed = 902,
     InternalError = 903,
     DataDiffersFromProven = 904,
+    DataNotCached = 905,
 }
 
 pub struct Error;
@@ -16,6 +18,20 @@ fn error(code: ErrorCode, message: String) -> ClientError {
     ClientError::with_code_message(code as u32, message)
 }
 
+/// Extracts `DataNotCached` error from the error of the proof checking, so it is returned
+/// to the caller as is instead of being wrapped into the generic proofs error
+fn data_not_cached_cause(err: &dyn Any) -> Option<ClientError> {
+    let client_err = match err.downcast_ref::<failure::Error>() {
+        Some(err) => err.downcast_ref::<ClientError>()?,
+        None => err.downcast_ref::<ClientError>()?,
+    };
+    if client_err.code == ErrorCode::DataNotCached as u32 {
+        Some(client_err.clone())
+    } else {
+        None
+    }
+}
+
 impl Error {
     pub fn invalid_data(err: impl Display) -> ClientError {
         error(
@@ -24,7 +40,10 @@ impl Error {
         )
     }
 
-    pub fn proof_check_failed(err: impl Display) -> ClientError {
+    pub fn proof_check_failed(err: impl Display + 'static) -> ClientError {
+        if let Some(err) = data_not_cached_cause(&err) {
+            return err;
+        }
         error(
             ErrorCode::ProofCheckFailed,
             format!("Proof check failed: {}", err),
@@ -38,10 +57,20 @@ impl Error {
         )
     }
 
-    pub fn internal_error(err: impl Display) -> ClientError {
+    pub fn internal_error(err: impl Display + 'static) -> ClientError {
+        if let Some(err) = data_not_cached_cause(&err) {
+            return err;
+        }
         error(
             ErrorCode::InternalError,
             format!("Internal error during proof checking: {}", err),
         )
     }
+
+    pub fn data_not_cached(err: impl Display) -> ClientError {
+        error(
+            ErrorCode::DataNotCached,
+            format!("Data is not cached and can't be downloaded in the offline mode: {}", err),
+        )
+    }
 }
@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use failure::bail;
+use serde_json::Value;
+use ton_block::{Block, Deserializable, ShardStateUnsplit, Transaction};
+use ton_types::{Result, UInt256};
+
+use crate::boc::internal::{deserialize_object_from_boc_bin, get_boc_hash};
+use crate::ClientContext;
+use crate::encoding::base64_decode;
+use crate::error::ClientResult;
+use crate::proofs::{
+    BlockProof, check_transaction_in_block, get_current_network_uid, INITIAL_TRUSTED_KEY_BLOCKS,
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::client::ClientContext;
+use crate::error::{ClientError, ClientResult};
+use crate::proofs::engine::ProofHelperEngineImpl;
+use crate::proofs::errors::Error;
+use crate::proofs::{json, prove_block, prove_transaction};
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ParamsOfProofBlockField {
+    /// Single block's data, retrieved from TONOS API. Required fields are `id` and/or
+    /// top-level `boc` (for block identification) and the fields to be verified.
+    pub block: Value,
+    /// Fields to be verified. Nested fields are separated with dots, array items are
+    /// specified by index, e.g. `master.shard_hashes.0.descr.seq_no`.
+    pub fields: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ParamsOfProofTransactionField {
+    /// Single transaction's data as queried from DApp server. Required fields are `id`
+    /// and/or top-level `boc` and the fields to be verified.
+    /// In order to reduce network requests count, it is recommended to provide `block_id` and `boc`
+    /// of transaction.
+    pub transaction: Value,
+    /// Fields to be verified. Nested fields are separated with dots, array items are
+    /// specified by index, e.g. `balance_delta` or `compute.exit_code`.
+    pub fields: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default, Debug, PartialEq)]
+pub struct FieldVerdict {
+    /// Field path as specified in the params.
+    pub field: String,
+    /// `true` if the given field value equals to the proven one.
+    pub verified: bool,
+    /// Field value from the proven data. `null` if the proven data has no such field.
+    pub proven_value: Value,
+    /// The reason why the field is not verified.
+    pub error: Option<ClientError>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default, Debug, PartialEq)]
+pub struct ResultOfProofFields {
+    /// Verdicts in the same order as the fields are specified in the params.
+    pub fields: Vec<FieldVerdict>,
+}
+
+fn verdicts(proven: &Value, compared: Vec<(String, ClientResult<()>)>) -> ResultOfProofFields {
+    ResultOfProofFields {
+        fields: compared
+            .into_iter()
+            .map(|(field, result)| FieldVerdict {
+                proven_value: json::get_field(proven, &field).clone(),
+                verified: result.is_ok(),
+                error: result.err(),
+                field,
+            })
+            .collect(),
+    }
+}
+
+/// Proves the specified fields of a given block's data, which is queried from TONOS API.
+///
+/// Checks block proofs the same way as `proof_block_data` does, but compares only
+/// the requested fields and returns a verdict for each of them instead of throwing
+/// an exception on the first difference. Useful when only a few displayed values
+/// must be trusted.
+///
+/// Failure to prove the block itself is returned as an error.
+#[api_function]
+pub async fn proof_block_field(
+    context: Arc<ClientContext>,
+    params: ParamsOfProofBlockField,
+) -> ClientResult<ResultOfProofFields> {
+    if params.fields.is_empty() {
+        return Err(Error::invalid_data("At least one field must be specified"));
+    }
+    let engine = ProofHelperEngineImpl::new(context).await
+        .map_err(|err| Error::proof_check_failed(err))?;
+
+    let block_json = prove_block(&engine, &params.block).await?;
+
+    Ok(verdicts(
+        &block_json,
+        json::compare_block_fields(&params.block, &block_json, &params.fields),
+    ))
+}
+
+/// Proves the specified fields of a given transaction's data, which is queried from TONOS API.
+///
+/// Checks the transaction and its block proofs the same way as `proof_transaction_data` does,
+/// but compares only the requested fields (e.g. `balance_delta`) and returns a verdict for each
+/// of them instead of throwing an exception on the first difference.
+///
+/// Failure to prove the transaction itself is returned as an error.
+#[api_function]
+pub async fn proof_transaction_field(
+    context: Arc<ClientContext>,
+    params: ParamsOfProofTransactionField,
+) -> ClientResult<ResultOfProofFields> {
+    if params.fields.is_empty() {
+        return Err(Error::invalid_data("At least one field must be specified"));
+    }
+    let engine = ProofHelperEngineImpl::new(context).await
+        .map_err(|err| Error::proof_check_failed(err))?;
+
+    let transaction_json = prove_transaction(&engine, &params.transaction).await?;
+
+    Ok(verdicts(
+        &transaction_json,
+        json::compare_transaction_fields(&params.transaction, &transaction_json, &params.fields),
+    ))
+}
@@ -0,0 +1,138 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use std::sync::Arc;
+
+use serde_json::Value;
+use sha2::Digest;
+
+use crate::client::storage::KeyValueStorage;
+use crate::error::{ClientError, ClientResult};
+use crate::proofs::engine::ProofHelperEngineImpl;
+use crate::proofs::{Error, ErrorCode};
+use crate::ClientContext;
+
+/// Data used by the proof checking, collected for the forensic bundle
+#[derive(Default, Clone)]
+pub(crate) struct ForensicRecord {
+    /// Block root hashes and BOCs
+    pub blocks: Vec<(String, Vec<u8>)>,
+    /// Masterchain block proofs JSON, including the key blocks proofs with validator sets
+    pub proofs: Vec<Value>,
+}
+
+fn bundle_key(id: &str) -> String {
+    format!("forensic_{}", id)
+}
+
+fn is_verification_failure(err: &ClientError) -> bool {
+    err.code == ErrorCode::ProofCheckFailed as u32
+        || err.code == ErrorCode::DataDiffersFromProven as u32
+}
+
+async fn endpoint_identity(context: &Arc<ClientContext>) -> Value {
+    let endpoint = match context.get_server_link() {
+        Ok(server_link) => server_link.state().await.query_endpoint().await,
+        Err(_) => None,
+    };
+    match endpoint {
+        Some(endpoint) => json!({
+            "url": endpoint.query_url,
+            "ip_address": endpoint.ip_address,
+        }),
+        None => Value::Null,
+    }
+}
+
+async fn save_bundle(
+    engine: &ProofHelperEngineImpl,
+    function: &str,
+    input: &Value,
+    err: &ClientError,
+) -> ClientResult<String> {
+    let context = engine.context();
+    let record = engine.forensic_record().unwrap_or_default();
+    let bundle = json!({
+        "time": context.env.now_ms(),
+        "function": function,
+        "input": input,
+        "error": err,
+        "endpoint": endpoint_identity(context).await,
+        "blocks": record.blocks.iter()
+            .map(|(id, boc)| json!({ "id": id, "boc": base64::encode(boc) }))
+            .collect::<Vec<_>>(),
+        "proofs": record.proofs,
+    })
+    .to_string();
+    let id = hex::encode(sha2::Sha256::digest(bundle.as_bytes()));
+    engine.storage().put_str(&bundle_key(&id), &bundle).await?;
+    Ok(id)
+}
+
+/// Saves the forensic bundle if the proof checking has failed and forensic dumps are enabled.
+/// The bundle id is returned in the `forensic_bundle_id` field of the error data.
+pub(crate) async fn capture_failure<T>(
+    engine: &ProofHelperEngineImpl,
+    function: &str,
+    input: &Value,
+    result: ClientResult<T>,
+) -> ClientResult<T> {
+    let mut err = match result {
+        Err(err) if is_verification_failure(&err) && engine.forensic_record().is_some() => err,
+        result => return result,
+    };
+    // Failures of the nested checks are already captured
+    if !err.data["forensic_bundle_id"].is_null() {
+        return Err(err);
+    }
+    match save_bundle(engine, function, input, &err).await {
+        Ok(id) => err.data["forensic_bundle_id"] = id.into(),
+        Err(save_err) => log::warn!("Forensic bundle is not saved: {}", save_err.message),
+    }
+    Err(err)
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ParamsOfGetForensicBundle {
+    /// Bundle id from the `forensic_bundle_id` field of the proof check error data.
+    pub id: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ResultOfGetForensicBundle {
+    /// Forensic bundle.
+    ///
+    /// Contains the failed function name and its input, the error, the endpoint identity
+    /// (`url` and `ip_address`) and the data used by the check: `blocks` BOCs and
+    /// masterchain `proofs` including the key blocks proofs carrying the validator sets.
+    pub bundle: Value,
+}
+
+/// Returns the forensic bundle saved on the proof check failure.
+///
+/// Bundles are saved if `forensic_dumps` is enabled in the proofs config, into the same
+/// storage as the proofs cache.
+#[api_function]
+pub async fn get_forensic_bundle(
+    context: Arc<ClientContext>,
+    params: ParamsOfGetForensicBundle,
+) -> ClientResult<ResultOfGetForensicBundle> {
+    let engine = ProofHelperEngineImpl::new(context).await
+        .map_err(|err| Error::internal_error(err))?;
+    let bundle = engine.storage().get_str(&bundle_key(&params.id)).await?
+        .ok_or_else(|| Error::invalid_data(format!("Forensic bundle {} not found", params.id)))?;
+    Ok(ResultOfGetForensicBundle {
+        bundle: serde_json::from_str(&bundle).map_err(|err| Error::internal_error(err))?,
+    })
+}
@@ -541,3 +541,97 @@ pub(crate) fn compare_messages(actual: &Value, expected: &Value) -> ClientResult
         &MESSAGE_NUMERIC_FIELDS,
     )
 }
+
+/// Returns the value of the field specified by dot-separated `path`.
+/// Numeric path segments are treated as array indexes.
+pub(crate) fn get_field<'v>(value: &'v Value, path: &str) -> &'v Value {
+    path.split('.').fold(value, |value, segment| match (value, segment.parse::<usize>()) {
+        (Value::Array(items), Ok(index)) => items.get(index).unwrap_or(&Value::Null),
+        _ => &value[segment],
+    })
+}
+
+fn compare_field(
+    actual: &Value,
+    expected: &Value,
+    path: &JsonPath<'_, '_>,
+    segments: &[&str],
+    ignore_fields: &HashSet<&'static str>,
+    numeric_fields: &HashSet<&'static str>,
+) -> ClientResult<()> {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => return Err(Error::invalid_data("Field path is empty")),
+    };
+    let (actual, expected, path) = match (actual, segment.parse::<usize>()) {
+        (Value::Array(_), Ok(index)) => (
+            get_field(actual, segment),
+            get_field(expected, segment),
+            path.join_index(index),
+        ),
+        _ => (&actual[*segment], &expected[*segment], path.join_field(segment)),
+    };
+    if ignore_fields.contains(path.gen_flat_str().as_str()) {
+        return Err(Error::invalid_data(format!("Field `{}` can not be proven", path)));
+    }
+    if !rest.is_empty() {
+        return compare_field(actual, expected, &path, rest, ignore_fields, numeric_fields);
+    }
+    if actual.is_null() && !expected.is_null() {
+        return Err(Error::data_differs_from_proven(format!(
+            "field `{path}`: expected {expected:?}, actual is not provided",
+            path = path,
+            expected = expected,
+        )));
+    }
+    compare_values(actual, expected, path, ignore_fields, numeric_fields)
+}
+
+fn compare_fields(
+    actual: &Value,
+    expected: &Value,
+    fields: &[String],
+    path: JsonPath<'_, '_>,
+    ignore_fields: &HashSet<&'static str>,
+    numeric_fields: &HashSet<&'static str>,
+) -> Vec<(String, ClientResult<()>)> {
+    fields
+        .iter()
+        .map(|field| {
+            let segments: Vec<&str> = field.split('.').filter(|s| !s.is_empty()).collect();
+            let result =
+                compare_field(actual, expected, &path, &segments, ignore_fields, numeric_fields);
+            (field.clone(), result)
+        })
+        .collect()
+}
+
+pub(crate) fn compare_block_fields(
+    actual: &Value,
+    expected: &Value,
+    fields: &[String],
+) -> Vec<(String, ClientResult<()>)> {
+    compare_fields(
+        actual,
+        expected,
+        fields,
+        JsonPath::new("blocks"),
+        &BLOCK_IGNORE_FIELDS,
+        &BLOCK_NUMERIC_FIELDS,
+    )
+}
+
+pub(crate) fn compare_transaction_fields(
+    actual: &Value,
+    expected: &Value,
+    fields: &[String],
+) -> Vec<(String, ClientResult<()>)> {
+    compare_fields(
+        actual,
+        expected,
+        fields,
+        JsonPath::new("transactions"),
+        &TRANSACTION_IGNORE_FIELDS,
+        &TRANSACTION_NUMERIC_FIELDS,
+    )
+}
@@ -0,0 +1,211 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use failure::bail;
+use serde_json::Value;
+use ton_block::{BlockIdExt, ShardIdent};
+use ton_types::Result;
+
+use crate::client::NetworkUID;
+use crate::error::ClientError;
+use crate::net::lite::tl::BlockLinkForward;
+use crate::net::lite::LiteClient;
+use crate::net::ErrorCode;
+use crate::proofs::data_source::{read_mc_block_shards, ProofDataSource};
+
+fn is_lite_server_error(err: &ClientError) -> bool {
+    err.code == ErrorCode::LiteServerError as u32
+}
+
+/// Converts the forward block link into the proof format accepted by `BlockProof::from_value`.
+/// Link doesn't declare the signatures weight, so it is only calculated during the check.
+fn link_to_proof(link: &BlockLinkForward) -> Value {
+    let signatures: Vec<Value> = link.signatures.signatures
+        .iter()
+        .map(|signature| {
+            let (r, s) = signature.signature.split_at(std::cmp::min(32, signature.signature.len()));
+            json!({
+                "node_id": hex::encode(&signature.node_id_short),
+                "r": hex::encode(r),
+                "s": hex::encode(s),
+            })
+        })
+        .collect();
+
+    json!({
+        "id": link.to.root_hash().as_hex_string(),
+        "workchain_id": link.to.shard().workchain_id(),
+        "shard": link.to.shard().shard_prefix_as_str_with_tag(),
+        "seq_no": link.to.seq_no(),
+        "file_hash": link.to.file_hash().as_hex_string(),
+        "signatures": {
+            "proof": base64::encode(&link.dest_proof),
+            "catchain_seqno": link.signatures.catchain_seqno,
+            "validator_list_hash_short": link.signatures.validator_set_hash,
+            "signatures": signatures,
+        },
+    })
+}
+
+/// Requests blocks and proofs from lite servers.
+///
+/// Lite servers address blocks by `seq_no`, so blocks requested by root hash only
+/// are not available. Masterchain block proofs are assembled from the forward links
+/// of `liteServer.getBlockProof`.
+pub(crate) struct LiteDataSource {
+    lite: Arc<LiteClient>,
+}
+
+impl LiteDataSource {
+    pub fn new(lite: Arc<LiteClient>) -> Self {
+        Self { lite }
+    }
+
+    async fn lookup_mc_block(&self, seq_no: u32) -> std::result::Result<BlockIdExt, ClientError> {
+        self.lite.lookup_block(&ShardIdent::masterchain(), seq_no).await
+    }
+
+    /// Returns `None` if the block is not known to the server yet
+    async fn lookup_block_opt(&self, shard: &ShardIdent, seq_no: u32) -> Result<Option<BlockIdExt>> {
+        match self.lite.lookup_block(shard, seq_no).await {
+            Ok(id) => Ok(Some(id)),
+            Err(err) if is_lite_server_error(&err) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ProofDataSource for LiteDataSource {
+    async fn query_network_uid(&self) -> Result<NetworkUID> {
+        let info = self.lite.get_masterchain_info().await?;
+        let first_block = self.lookup_mc_block(1).await?;
+
+        Ok(NetworkUID {
+            zerostate_root_hash: info.zerostate_root_hash,
+            first_master_block_root_hash: first_block.root_hash().clone(),
+        })
+    }
+
+    async fn query_zerostate_boc(&self) -> Result<Vec<u8>> {
+        let info = self.lite.get_masterchain_info().await?;
+        let zerostate_id = BlockIdExt::with_params(
+            ShardIdent::masterchain(),
+            0,
+            info.zerostate_root_hash,
+            info.zerostate_file_hash,
+        );
+
+        Ok(self.lite.get_state(&zerostate_id).await?)
+    }
+
+    async fn query_block_boc(&self, root_hash: &str) -> Result<Option<Vec<u8>>> {
+        bail!(
+            "Block {} can not be requested from lite servers: blocks are available by seq_no only",
+            root_hash,
+        )
+    }
+
+    async fn query_mc_blocks_file_hashes(&self, seq_nos: &[u32]) -> Result<Vec<(u32, String)>> {
+        let mut sorted = seq_nos.to_vec();
+        sorted.sort();
+        let mut result = Vec::with_capacity(sorted.len());
+        for seq_no in sorted {
+            if let Some(id) = self.lookup_block_opt(&ShardIdent::masterchain(), seq_no).await? {
+                result.push((seq_no, id.file_hash().as_hex_string()));
+            }
+        }
+
+        Ok(result)
+    }
+
+    async fn query_mc_blocks_proofs(
+        &self,
+        seq_no_range: Range<u32>,
+        key_blocks_only: bool,
+    ) -> Result<Vec<(u32, Value)>> {
+        if seq_no_range.start == 0 || seq_no_range.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let known_block = self.lookup_mc_block(seq_no_range.start - 1).await?;
+        let target_block = if key_blocks_only {
+            // Forward proof to the last block passes through all the key blocks in between
+            self.lite.get_masterchain_info().await?.last
+        } else {
+            match self.lookup_block_opt(&ShardIdent::masterchain(), seq_no_range.start).await? {
+                Some(id) => id,
+                None => return Ok(Vec::new()),
+            }
+        };
+
+        let proof = self.lite.get_block_proof(&known_block, &target_block).await?;
+        let mut result = Vec::new();
+        for link in &proof.forward_links {
+            let seq_no = link.to.seq_no();
+            if !seq_no_range.contains(&seq_no) || (key_blocks_only && !link.to_key_block) {
+                continue;
+            }
+            result.push((seq_no, link_to_proof(link)));
+        }
+        result.sort_by_key(|(seq_no, _)| *seq_no);
+
+        Ok(result)
+    }
+
+    async fn query_mc_blocks_shards(
+        &self,
+        first_seq_no: u32,
+        limit: u32,
+    ) -> Result<Vec<(u32, Vec<(ShardIdent, u32)>)>> {
+        let mut result = Vec::new();
+        for seq_no in first_seq_no..first_seq_no.saturating_add(limit) {
+            let id = match self.lookup_block_opt(&ShardIdent::masterchain(), seq_no).await? {
+                Some(id) => id,
+                None => break,
+            };
+            let boc = self.lite.get_block(&id).await?;
+            result.push((seq_no, read_mc_block_shards(&boc)?));
+        }
+
+        Ok(result)
+    }
+
+    async fn query_shard_blocks_bocs(
+        &self,
+        shard: &ShardIdent,
+        seq_no_range: Range<u32>,
+    ) -> Result<Vec<(u32, Vec<u8>)>> {
+        let mut result = Vec::with_capacity(seq_no_range.len());
+        for seq_no in seq_no_range {
+            let id = match self.lookup_block_opt(shard, seq_no).await? {
+                Some(id) => id,
+                None => break,
+            };
+            result.push((seq_no, self.lite.get_block(&id).await?));
+        }
+
+        Ok(result)
+    }
+
+    async fn query_transaction_data(&self, _id: &str, _fields: &str) -> Result<Option<Value>> {
+        bail!("Transaction `boc` must be provided to check proofs against lite servers data")
+    }
+
+    async fn query_message_data(&self, _id: &str, _fields: &str) -> Result<Option<Value>> {
+        bail!("Message `boc` must be provided to check proofs against lite servers data")
+    }
+}
@@ -1,12 +1,15 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::future::Future;
 use std::io::Cursor;
+use std::pin::Pin;
 use std::sync::Arc;
 
 use failure::{bail, err_msg};
 use serde::{Deserialize, Deserializer};
 use serde_json::Value;
-use ton_block::{Block, BlockIdExt, BlockInfo, CryptoSignature, CryptoSignaturePair, Deserializable, HashmapAugType, MerkleProof, Message, ShardIdent, ShardStateUnsplit, Transaction, ValidatorDescr};
+use sha2::Digest;
+use ton_block::{Account, Block, BlockIdExt, BlockInfo, CryptoSignature, CryptoSignaturePair, Deserializable, HashmapAugType, MerkleProof, Message, ShardIdent, ShardStateUnsplit, Transaction, ValidatorDescr};
 use ton_types::{Cell, UInt256};
 use ton_types::Result;
 
@@ -17,13 +20,42 @@ use crate::client::NetworkUID;
 use crate::ClientContext;
 use crate::encoding::base64_decode;
 use crate::error::ClientResult;
-use crate::net::{ParamsOfQueryCollection, query_collection};
+use crate::net::{query_collection, ParamsOfQueryCollection, TrustedMcBlockId, TRANSACTIONS_COLLECTION};
+use crate::proofs::data_source::get_data_source;
 use crate::proofs::engine::ProofHelperEngineImpl;
 use crate::proofs::errors::Error;
 use crate::proofs::validators::{calc_subset_for_workchain, check_crypto_signatures};
 use crate::utils::json::JsonHelper;
 
+pub use attestation::{
+    attest, verify_attestation, AttestedDataKind, ParamsOfAttest, ParamsOfVerifyAttestation,
+    ProofAttestation, ResultOfAttest, ResultOfVerifyAttestation,
+};
+pub use forensics::{get_forensic_bundle, ParamsOfGetForensicBundle, ResultOfGetForensicBundle};
+pub use fields::{
+    proof_block_field, proof_transaction_field, FieldVerdict, ParamsOfProofBlockField,
+    ParamsOfProofTransactionField, ResultOfProofFields,
+};
+pub use prefetch::prefetch_key_blocks;
+pub use trusted_state::{
+    export_trusted_state, import_trusted_state, ParamsOfImportTrustedState,
+    ResultOfExportTrustedState, ResultOfImportTrustedState,
+};
+pub use transaction_list::{
+    proof_transaction_list, ParamsOfProofTransactionList, ResultOfProofTransactionList,
+    TransactionProofResult,
+};
+
 pub mod errors;
+pub(crate) mod archive;
+pub(crate) mod attestation;
+pub(crate) mod data_source;
+pub(crate) mod fields;
+pub(crate) mod forensics;
+pub(crate) mod lite;
+pub(crate) mod prefetch;
+pub(crate) mod transaction_list;
+pub(crate) mod trusted_state;
 mod engine;
 mod validators;
 
@@ -31,7 +63,7 @@ mod validators;
 mod tests;
 mod json;
 
-#[derive(Deserialize, Debug, Clone, ApiType)]
+#[derive(Serialize, Deserialize, Debug, Clone, ApiType)]
 pub struct ProofsConfig {
     /// Cache proofs in the local storage. Default is `true`.
     /// If this value is set to `true`, downloaded proofs and master-chain BOCs are saved into the
@@ -43,12 +75,150 @@ pub struct ProofsConfig {
         deserialize_with = "deserialize_cache_in_local_storage"
     )]
     pub cache_in_local_storage: bool,
+
+    /// Share proofs cache between all client contexts of the process connected to the same
+    /// network. Default is `true`.
+    /// If this value is set to `true`, proofs verified by one context are reused by the others
+    /// instead of being downloaded and checked again.
+    /// The cache is shared only by the contexts with the same `trusted_key_blocks`
+    /// and `archive_path`, so the proofs accepted due to the trust anchors of one context
+    /// are not trusted by the others.
+    #[serde(
+        default = "default_shared_cache",
+        deserialize_with = "deserialize_shared_cache"
+    )]
+    pub shared_cache: bool,
+
+    /// Directory with the blocks and proofs extracted from node archive packages.
+    /// If it is specified, proofs are checked against the archive data instead of the data
+    /// downloaded from DApp server.
+    ///
+    /// Files must be named as the archive package entries:
+    /// `block_(<workchain_id>,<shard>,<seq_no>):<root_hash>:<file_hash>` for blocks,
+    /// `proof_(...)` for masterchain block proofs and `zerostate_(...)` for the masterchain
+    /// zerostate.
+    pub archive_path: Option<String>,
+
+    /// Save a forensic bundle when the proof check fails. Default is `false`.
+    ///
+    /// The bundle contains the checked data, the error, the endpoint identity and the blocks
+    /// and proofs used by the check. It is saved into the proofs storage and its id is returned
+    /// in the `forensic_bundle_id` field of the error data, so the suspected endpoint
+    /// misbehavior can be analyzed later with `proofs.get_forensic_bundle`.
+    pub forensic_dumps: Option<bool>,
+
+    /// Proofs cache storage backend.
+    ///
+    /// Overrides `cache_in_local_storage` if specified. `FileSystem` is available only for
+    /// native environments and `IndexedDb` only for the web.
+    pub storage: Option<ProofsStorageType>,
+
+    /// Maximum total size of the cached proofs and blocks, in bytes.
+    ///
+    /// If specified, the least recently used records are removed from the cache when
+    /// the size is exceeded. By default the cache is not limited.
+    pub max_storage_size: Option<u64>,
+
+    /// Number of worker threads verifying key block proofs in parallel while downloading
+    /// the proof chain. Default is 1 (sequential verification).
+    ///
+    /// Signature checks are CPU-bound, so proofs are verified in batches of this size, each one
+    /// against the validator set of the previous key block of the chain. Verified proofs are
+    /// still stored and reported in the chain order. Ignored in the web environment.
+    pub parallel_verification: Option<u32>,
+
+    /// Check proofs using only the proofs and blocks cached in the proofs storage.
+    /// Default is `false`.
+    ///
+    /// In the offline mode the data missing in the cache is not downloaded: the check fails
+    /// with `DataNotCached` error instead of waiting for the network. The same applies
+    /// if the client is switched to the offline mode with `client.set_offline`.
+    pub offline_mode: Option<bool>,
+
+    /// Trusted key-blocks used as the roots of the proof chains in addition to the hardcoded
+    /// ones, grouped by networks.
+    ///
+    /// Proofs are checked starting from the closest trusted key-block instead of the zerostate,
+    /// so trusting a recent key-block shortens the proof chains to download. Trust only the
+    /// key-blocks obtained from a reliable source.
+    ///
+    /// Proofs checked with the custom key-blocks are cached separately from the proofs
+    /// checked with the hardcoded ones.
+    pub trusted_key_blocks: Option<Vec<NetworkTrustedKeyBlocks>>,
+
+    /// Strictness of the proofs verification. Default is `Standard`.
+    ///
+    /// Can be overridden for a single call with the `verification_policy` parameter of
+    /// `proof_block_data`, `proof_transaction_data` and `proof_message_data`, e.g. to use
+    /// `Paranoid` mode only for the high-value operations.
+    pub verification_policy: Option<ProofsVerificationPolicy>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ApiType, Default)]
+pub struct NetworkTrustedKeyBlocks {
+    /// Root hash of the network zerostate, encoded as HEX.
+    pub zerostate_root_hash: String,
+    /// Trusted key-blocks of the network.
+    pub key_blocks: Vec<TrustedMcBlockId>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, ApiType, PartialEq)]
+pub enum ProofsStorageType {
+    /// Proofs are cached only in memory of the client context (or the process if
+    /// `shared_cache` is enabled) and lost after its destruction.
+    Memory,
+    /// Proofs are cached in the `local_storage_path` directory
+    FileSystem,
+    /// Proofs are cached in the browser's IndexedDB
+    IndexedDb,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, ApiType, PartialEq)]
+pub enum ProofsVerificationPolicy {
+    /// Cached intermediate results are trusted: masterchain proofs read from the cache
+    /// are not re-checked while proving the shard blocks and the cached masterchain blocks
+    /// are not re-hashed.
+    Fast,
+    /// Cached proofs are trusted once they are checked, all the downloaded data is checked.
+    Standard,
+    /// Cached proofs are re-validated on each read and the downloaded block BOCs are
+    /// cross-checked across at least two distinct endpoints (or `quorum_endpoints_count`
+    /// if it is greater).
+    Paranoid,
+}
+
+impl Default for ProofsVerificationPolicy {
+    fn default() -> Self {
+        ProofsVerificationPolicy::Standard
+    }
+}
+
+impl ProofsConfig {
+    /// Storage backend selected by `storage` or `cache_in_local_storage`
+    pub(crate) fn storage_type(&self) -> ProofsStorageType {
+        match self.storage {
+            Some(storage) => storage,
+            None if !self.cache_in_local_storage => ProofsStorageType::Memory,
+            None if cfg!(feature = "wasm") => ProofsStorageType::IndexedDb,
+            None => ProofsStorageType::FileSystem,
+        }
+    }
 }
 
 fn default_cache_in_local_storage() -> bool {
     true
 }
 
+fn default_shared_cache() -> bool {
+    true
+}
+
+fn deserialize_shared_cache<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<bool, D::Error> {
+    Ok(Option::deserialize(deserializer)?.unwrap_or(default_shared_cache()))
+}
+
 fn deserialize_cache_in_local_storage<'de, D: Deserializer<'de>>(
     deserializer: D,
 ) -> std::result::Result<bool, D::Error> {
@@ -59,86 +229,91 @@ impl Default for ProofsConfig {
     fn default() -> Self {
         Self {
             cache_in_local_storage: default_cache_in_local_storage(),
+            shared_cache: default_shared_cache(),
+            archive_path: None,
+            forensic_dumps: None,
+            storage: None,
+            max_storage_size: None,
+            parallel_verification: None,
+            offline_mode: None,
+            trusted_key_blocks: None,
+            verification_policy: None,
         }
     }
 }
 
+#[derive(Clone, num_derive::FromPrimitive, PartialEq, Debug)]
+pub enum ProofsResponseType {
+    ProofChainProgress = 100,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone, PartialEq)]
+pub struct ProofChainProgressEvent {
+    /// `seq_no` of the last verified key block.
+    pub seq_no: u32,
+    /// Number of the key blocks of the downloaded chain verified so far.
+    pub verified_count: u32,
+    /// Total number of the key blocks in the downloaded chain.
+    pub total_count: u32,
+}
+
 #[derive(Serialize, Deserialize, Clone, ApiType, Default)]
 pub struct ParamsOfProofBlockData {
     /// Single block's data, retrieved from TONOS API, that needs proof.
     /// Required fields are `id` and/or top-level `boc` (for block identification), others are
     /// optional.
     pub block: Value,
+    /// Flag for requesting events sending. Default is `false`.
+    ///
+    /// If enabled, each key block verified while downloading the proof chain is reported
+    /// via `ProofChainProgressEvent`.
+    pub send_events: Option<bool>,
+    /// Verification policy of this call. Default is `proofs.verification_policy` config value.
+    pub verification_policy: Option<ProofsVerificationPolicy>,
 }
 
-/// Proves that a given block's data, which is queried from TONOS API, can be trusted.
-/// 
-/// This function checks block proofs and compares given data with the proven.
-/// If the given data differs from the proven, the exception will be thrown.
-/// The input param is a single block's JSON object, which was queried from DApp server using
-/// functions such as `net.query`, `net.query_collection` or `net.wait_for_collection`.
-/// If block's BOC is not provided in the JSON, it will be queried from DApp server
-/// (in this case it is required to provide at least `id` of block).
-///
-/// Please note, that joins (like `signatures` in `Block`) are separated entities and not supported,
-/// so function will throw an exception in a case if JSON being checked has such entities in it.
-///
-/// If `cache_in_local_storage` in config is set to `true` (default), downloaded proofs and
-/// master-chain BOCs are saved into the persistent local storage (e.g. file system for native
-/// environments or browser's IndexedDB for the web); otherwise all the data is cached only in
-/// memory in current client's context and will be lost after destruction of the client.
-///
-/// **Why Proofs are needed**
-///
-/// Proofs are needed to ensure that the data downloaded from a DApp server is real blockchain
-/// data. Checking proofs can protect from the malicious DApp server which can potentially provide
-/// fake data, or also from "Man in the Middle" attacks class.
-///
-/// **What Proofs are**
-///
-/// Simply, proof is a list of signatures of validators', which have signed this particular master-
-/// block.
-///
-/// The very first validator set's public keys are included in the zero-state. Whe know a root hash
-/// of the zero-state, because it is stored in the network configuration file, it is our authority
-/// root. For proving zero-state it is enough to calculate and compare its root hash.
-///
-/// In each new validator cycle the validator set is changed. The new one is stored in a key-block,
-/// which is signed by the validator set, which we already trust, the next validator set will be
-/// stored to the new key-block and signed by the current validator set, and so on.
-///
-/// In order to prove any block in the master-chain we need to check, that it has been signed by
-/// a trusted validator set. So we need to check all key-blocks' proofs, started from the zero-state
-/// and until the block, which we want to prove. But it can take a lot of time and traffic to
-/// download and prove all key-blocks on a client. For solving this, special trusted blocks are used
-/// in TON-SDK.
-///
-/// The trusted block is the authority root, as well, as the zero-state. Each trusted block is the
-/// `id` (e.g. `root_hash`) of the already proven key-block. There can be plenty of trusted
-/// blocks, so there can be a lot of authority roots. The hashes of trusted blocks for MainNet
-/// and DevNet are hardcoded in SDK in a separated binary file (trusted_key_blocks.bin) and can 
-/// be updated for each release.
-/// In future SDK releases, one will also be able to provide their hashes of trusted blocks for
-/// other networks, besides for MainNet and DevNet.
-/// By using trusted key-blocks, in order to prove any block, we can prove chain of key-blocks to
-/// the closest previous trusted key-block, not only to the zero-state.
-///
-/// But shard-blocks don't have proofs on DApp server. In this case, in order to prove any shard-
-/// block data, we search for a corresponding master-block, which contains the root hash of this
-/// shard-block, or some shard block which is linked to that block in shard-chain. After proving
-/// this master-block, we traverse through each link and calculate and compare hashes with links,
-/// one-by-one. After that we can ensure that this shard-block has also been proven.
-#[api_function]
+/// Proves the block data. See `proofs.proof_block_data` API function for details.
 pub async fn proof_block_data(
     context: Arc<ClientContext>,
     params: ParamsOfProofBlockData,
 ) -> ClientResult<()> {
-    let engine = ProofHelperEngineImpl::new(context).await
-        .map_err(|err| Error::proof_check_failed(err))?;
+    proof_block_data_with_progress(context, params, |_| futures::future::ready(())).await
+}
 
-    let id_opt = params.block["id"].as_str();
+/// Proves the block data reporting the key blocks verified while downloading
+/// the proof chain to `on_progress`.
+pub async fn proof_block_data_with_progress<F: Future<Output = ()> + Send + 'static>(
+    context: Arc<ClientContext>,
+    params: ParamsOfProofBlockData,
+    on_progress: impl Fn(ProofChainProgressEvent) -> F + Send + Sync + 'static,
+) -> ClientResult<()> {
+    let mut engine = ProofHelperEngineImpl::new(context).await
+        .map_err(|err| Error::proof_check_failed(err))?
+        .with_policy(params.verification_policy);
+    if params.send_events.unwrap_or(false) {
+        engine = engine.with_progress(Arc::new(
+            move |event| -> Pin<Box<dyn Future<Output = ()> + Send>> {
+                Box::pin(on_progress(event))
+            },
+        ));
+    }
 
-    let boc = if let Some(boc) = params.block["boc"].as_str() {
+    let result = match prove_block(&engine, &params.block).await {
+        Ok(block_json) => json::compare_blocks(&params.block, &block_json),
+        Err(err) => Err(err),
+    };
+    forensics::capture_failure(&engine, "proof_block_data", &params.block, result).await
+}
+
+/// Proves the block identified by `id` and/or `boc` of the given block's JSON and
+/// returns JSON of the proven block.
+pub(crate) async fn prove_block(
+    engine: &ProofHelperEngineImpl,
+    block_json: &Value,
+) -> ClientResult<Value> {
+    let id_opt = block_json["id"].as_str();
+
+    let boc = if let Some(boc) = block_json["boc"].as_str() {
         base64_decode(boc)?
     } else if let Some(id) = id_opt {
         engine.download_block_boc(id).await
@@ -151,10 +326,74 @@ pub async fn proof_block_data(
 
     engine.proof_block_boc(&root_hash, &block, &boc).await?;
 
-    let block_json = json::serialize_block(root_hash, block, boc)
+    json::serialize_block(root_hash, block, boc)
+        .map_err(|err| Error::invalid_data(err))
+}
+
+/// Waits for the masterchain block which commits the block with `block_id` (the block itself
+/// for masterchain blocks) and for `depth` further masterchain blocks. If `check_proofs` is set,
+/// the block is proven together with the committing masterchain block.
+///
+/// Returns `seq_no` of the committing masterchain block or `None` if the finality
+/// has not been reached until the `deadline` (in ms).
+pub(crate) async fn wait_for_masterchain_commit<F: futures::Future<Output = ()>>(
+    context: Arc<ClientContext>,
+    block_id: &str,
+    depth: u32,
+    check_proofs: bool,
+    deadline: u64,
+    on_proof_checked: impl FnOnce(u64) -> F,
+) -> ClientResult<Option<u32>> {
+    let engine = ProofHelperEngineImpl::new(context.clone()).await
+        .map_err(|err| Error::proof_check_failed(err))?;
+    let boc = engine.download_block_boc(block_id).await
+        .map_err(|err| Error::proof_check_failed(err))?;
+    let (block, root_hash) = deserialize_object_from_boc_bin::<Block>(&boc)?;
+    let info = block.read_info()
         .map_err(|err| Error::invalid_data(err))?;
 
-    json::compare_blocks(&params.block, &block_json)
+    let commit_seq_no = if info.shard().is_masterchain() {
+        info.seq_no()
+    } else {
+        let master_ref = info.read_master_ref()
+            .map_err(|err| Error::invalid_data(err))?
+            .ok_or_else(|| Error::invalid_data("Unable to read master_ref of block"))?;
+        let mut first_mc_seq_no = master_ref.master.seq_no;
+        loop {
+            let seq_no = engine
+                .query_closest_mc_block_for_shard_block(
+                    &mut first_mc_seq_no,
+                    info.shard(),
+                    info.seq_no(),
+                ).await
+                .map_err(|err| Error::proof_check_failed(err))?;
+            if let Some(seq_no) = seq_no {
+                break seq_no;
+            }
+            if context.env.now_ms() > deadline {
+                return Ok(None);
+            }
+            context.env.set_timer(1000).await?;
+        }
+    };
+
+    if check_proofs {
+        let start = context.env.now_ms();
+        engine.proof_block_boc(&root_hash, &block, &boc).await?;
+        on_proof_checked(context.env.now_ms() - start).await;
+    }
+
+    let final_seq_no = commit_seq_no + depth;
+    while !engine.mc_block_exists(final_seq_no).await
+        .map_err(|err| Error::proof_check_failed(err))?
+    {
+        if context.env.now_ms() > deadline {
+            return Ok(None);
+        }
+        context.env.set_timer(1000).await?;
+    }
+
+    Ok(Some(commit_seq_no))
 }
 
 #[derive(Serialize, Deserialize, Clone, ApiType, Default)]
@@ -164,6 +403,8 @@ pub struct ParamsOfProofTransactionData {
     /// In order to reduce network requests count, it is recommended to provide `block_id` and `boc`
     /// of transaction.
     pub transaction: Value,
+    /// Verification policy of this call. Default is `proofs.verification_policy` config value.
+    pub verification_policy: Option<ProofsVerificationPolicy>,
 }
 
 /// Proves that a given transaction's data, which is queried from TONOS API, can be trusted.
@@ -189,10 +430,25 @@ pub async fn proof_transaction_data(
     params: ParamsOfProofTransactionData,
 ) -> ClientResult<()> {
     let engine = ProofHelperEngineImpl::new(context).await
-        .map_err(|err| Error::proof_check_failed(err))?;
+        .map_err(|err| Error::proof_check_failed(err))?
+        .with_policy(params.verification_policy);
+
+    let result = match prove_transaction(&engine, &params.transaction).await {
+        Ok(transaction_json) => json::compare_transactions(&params.transaction, &transaction_json),
+        Err(err) => Err(err),
+    };
+    forensics::capture_failure(&engine, "proof_transaction_data", &params.transaction, result)
+        .await
+}
 
+/// Proves the transaction identified by `id` and/or `boc` of the given transaction's JSON
+/// together with its block and returns JSON of the proven transaction.
+pub(crate) async fn prove_transaction(
+    engine: &ProofHelperEngineImpl,
+    transaction_json: &Value,
+) -> ClientResult<Value> {
     let (root_hash, block_id, boc, transaction) =
-        transaction_get_required_data(&engine, &params.transaction).await?;
+        transaction_get_required_data(engine, transaction_json).await?;
 
     let block_boc = engine.download_block_boc(&block_id).await
         .map_err(|err| Error::invalid_data(err))?;
@@ -203,42 +459,16 @@ pub async fn proof_transaction_data(
 
     let block_info = block.read_info()
         .map_err(|err| Error::invalid_data(err))?;
-    let block_extra = block.read_extra()
-        .map_err(|err| Error::invalid_data(err))?;
-    let account_blocks = block_extra.read_account_blocks()
-        .map_err(|err| Error::invalid_data(err))?;
-
-    let mut transaction_found_in_block = false;
-    account_blocks.iterate_objects(|account_block| {
-        account_block.transaction_iterate_full(|_key, cell, _cc| {
-            if root_hash == cell.repr_hash() {
-                transaction_found_in_block = true;
-                return Ok(false);
-            }
-            Ok(true)
-        })
-    })
-        .map_err(|err| Error::internal_error(err))?;
 
-    if !transaction_found_in_block {
-        return Err(Error::proof_check_failed(
-            format!(
-                "Transaction with `id`: {} not found in block with `id`: {}",
-                root_hash.as_hex_string(),
-                block_id.as_hex_string(),
-            )
-        ));
-    }
+    check_transaction_in_block(&block, &block_id, &root_hash)?;
 
-    let transaction_json = json::serialize_transaction(
+    json::serialize_transaction(
         root_hash,
         transaction,
         block_id,
         block_info.shard().workchain_id(),
         boc,
-    ).map_err(|err| Error::invalid_data(err))?;
-
-    json::compare_transactions(&params.transaction, &transaction_json)
+    ).map_err(|err| Error::invalid_data(err))
 }
 
 #[derive(Serialize, Deserialize, Clone, ApiType, Default)]
@@ -248,6 +478,8 @@ pub struct ParamsOfProofMessageData {
     /// In order to reduce network requests count, it is recommended to provide at least
     /// `boc` of message and non-null `src_transaction.id` or `dst_transaction.id`.
     pub message: Value,
+    /// Verification policy of this call. Default is `proofs.verification_policy` config value.
+    pub verification_policy: Option<ProofsVerificationPolicy>,
 }
 
 /// Proves that a given message's data, which is queried from TONOS API, can be trusted.
@@ -273,10 +505,20 @@ pub async fn proof_message_data(
     params: ParamsOfProofMessageData,
 ) -> ClientResult<()> {
     let engine = ProofHelperEngineImpl::new(Arc::clone(&context)).await
-        .map_err(|err| Error::proof_check_failed(err))?;
+        .map_err(|err| Error::proof_check_failed(err))?
+        .with_policy(params.verification_policy);
+
+    let result = check_message_data(&engine, context, &params.message).await;
+    forensics::capture_failure(&engine, "proof_message_data", &params.message, result).await
+}
 
+async fn check_message_data(
+    engine: &ProofHelperEngineImpl,
+    context: Arc<ClientContext>,
+    message_json: &Value,
+) -> ClientResult<()> {
     let (root_hash, transaction_id, boc, message) =
-        message_get_required_data(&engine, &params.message).await?;
+        message_get_required_data(engine, message_json).await?;
 
     let transaction_json = engine.query_transaction_data(
         &transaction_id, "id boc in_msg out_msgs"
@@ -295,16 +537,121 @@ pub async fn proof_message_data(
         context,
         ParamsOfProofTransactionData {
             transaction: transaction_json,
+            verification_policy: Some(engine.policy()),
         }
     ).await?;
 
-    let message_json = json::serialize_message(
+    let proven_json = json::serialize_message(
         root_hash,
         message,
         boc,
     ).map_err(|err| Error::invalid_data(err))?;
 
-    json::compare_messages(&params.message, &message_json)
+    json::compare_messages(message_json, &proven_json)
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ParamsOfVerifyLocalCache {
+    /// Re-download the parts of the proof chain found corrupted. Default is `true`.
+    pub repair: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ProofsCacheRange {
+    /// First masterchain block `seq_no` of the range.
+    pub start_seq_no: u32,
+    /// Masterchain block `seq_no` following the last one of the range.
+    pub end_seq_no: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ResultOfVerifyLocalCache {
+    /// Number of stored key-block proofs which passed the check.
+    pub valid_count: u32,
+    /// Number of stored records (proofs and zerostate) found corrupted and removed.
+    pub corrupted_count: u32,
+    /// Masterchain ranges of key-block proofs re-downloaded during the repair.
+    pub repaired_ranges: Vec<ProofsCacheRange>,
+}
+
+/// Re-validates the proofs stored in the local cache and repairs the corrupted ones.
+///
+/// The function checks the stored zerostate and each stored chain of key-block proofs (starting
+/// from the zerostate or from a trusted key-block) against the previous proof in the chain.
+/// The chain is cut at the first corrupted or missing proof, its boundary metadata is truncated
+/// to the last valid key-block and, if `repair` is `true` (default), the cut part is downloaded
+/// and proven again.
+///
+/// This function is useful for long-living applications with persistent proofs cache
+/// (`cache_in_local_storage` is `true`), where truncated writes could damage stored data.
+#[api_function]
+pub async fn verify_local_cache(
+    context: Arc<ClientContext>,
+    params: ParamsOfVerifyLocalCache,
+) -> ClientResult<ResultOfVerifyLocalCache> {
+    let engine = ProofHelperEngineImpl::new(context).await
+        .map_err(|err| Error::internal_error(err))?;
+
+    let status = engine.verify_local_cache(params.repair.unwrap_or(true)).await
+        .map_err(|err| Error::proof_check_failed(err))?;
+
+    Ok(ResultOfVerifyLocalCache {
+        valid_count: status.valid_count,
+        corrupted_count: status.corrupted_count,
+        repaired_ranges: status.repaired_ranges.into_iter()
+            .map(|range| ProofsCacheRange {
+                start_seq_no: range.start,
+                end_seq_no: range.end,
+            })
+            .collect(),
+    })
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ParamsOfSetTrustedKeyBlock {
+    /// Key-block to trust.
+    pub key_block: TrustedMcBlockId,
+}
+
+/// Proves the key-block and pins it as trusted for the current network.
+///
+/// Proofs of the later blocks are checked starting from the pinned key-block, so the proof
+/// chains to download become shorter. The key-block is pinned while the client context lives,
+/// use `proofs.trusted_key_blocks` config parameter to trust it from the start.
+#[api_function]
+pub async fn set_trusted_key_block(
+    context: Arc<ClientContext>,
+    params: ParamsOfSetTrustedKeyBlock,
+) -> ClientResult<()> {
+    let seq_no = params.key_block.seq_no;
+    let root_hash = UInt256::from_str(&params.key_block.root_hash)
+        .map_err(|err| Error::invalid_data(err))?;
+
+    let engine = ProofHelperEngineImpl::new(Arc::clone(&context)).await
+        .map_err(|err| Error::internal_error(err))?;
+    engine.check_mc_block_proof(seq_no, &root_hash).await
+        .map_err(|err| Error::proof_check_failed(err))?;
+
+    let proof_json = engine.read_stored_mc_proof(seq_no).await
+        .map_err(|err| Error::internal_error(err))?
+        .ok_or_else(|| Error::internal_error("Proven key-block proof is not stored"))?;
+    let (_virt_block, virt_block_info) = BlockProof::from_value(&proof_json)
+        .and_then(|proof| proof.pre_check_block_proof())
+        .map_err(|err| Error::proof_check_failed(err))?;
+    if !virt_block_info.key_block() {
+        return Err(Error::invalid_data(format!("Block {} is not a key-block", seq_no)));
+    }
+
+    let network_uid = get_current_network_uid(&context).await
+        .map_err(|err| Error::internal_error(err))?;
+    let mut pinned = context.net.pinned_key_blocks.write()
+        .map_err(|_| Error::internal_error("Pinned key-blocks lock is poisoned"))?;
+    let key_blocks = pinned.entry(*network_uid.zerostate_root_hash.as_array()).or_default();
+    if !key_blocks.contains(&(seq_no, *root_hash.as_array())) {
+        key_blocks.push((seq_no, *root_hash.as_array()));
+    }
+
+    Ok(())
 }
 
 pub(crate) async fn transaction_get_required_data<'trans>(
@@ -453,6 +800,107 @@ async fn message_query_required_fields<'msg>(
     Ok(())
 }
 
+/// Queries the last transaction of the account (`id`, `block_id` and `boc` fields) and checks
+/// that the account state is the result of this transaction, so proving the transaction
+/// proves the account state.
+pub(crate) async fn account_get_last_transaction(
+    context: &Arc<ClientContext>,
+    account: &Account,
+    account_hash: &UInt256,
+) -> ClientResult<Value> {
+    let address = account.get_addr()
+        .ok_or_else(|| Error::invalid_data("Account has no address"))?;
+    let lt = account.last_tr_time()
+        .ok_or_else(|| Error::invalid_data("Account has no transactions"))?;
+
+    let mut transactions = query_collection(
+        Arc::clone(context),
+        ParamsOfQueryCollection {
+            collection: TRANSACTIONS_COLLECTION.to_owned(),
+            filter: Some(json!({
+                "account_addr": { "eq": address.to_string() },
+                "lt": { "eq": format!("0x{:x}", lt) },
+            })),
+            result: "id block_id boc".to_owned(),
+            limit: Some(1),
+            ..Default::default()
+        },
+    ).await?.result;
+    let transaction_json = transactions.pop()
+        .ok_or_else(|| Error::invalid_data(format!("Last transaction of {} is not found", address)))?;
+
+    let transaction = transaction_json["boc"].as_str()
+        .ok_or_else(|| Error::invalid_data("Transaction has no `boc`"))
+        .and_then(|boc| deserialize_object_from_base64::<Transaction>(boc, "transaction"))?
+        .object;
+    check_account_state_update(&transaction, account, account_hash)?;
+
+    Ok(transaction_json)
+}
+
+/// Checks that the account state is the result of the transaction: the transaction belongs
+/// to the account and its state update ends with the account state hash
+pub(crate) fn check_account_state_update(
+    transaction: &Transaction,
+    account: &Account,
+    account_hash: &UInt256,
+) -> ClientResult<()> {
+    let address = account.get_addr()
+        .ok_or_else(|| Error::invalid_data("Account has no address"))?;
+    if *transaction.account_id() != address.address() {
+        return Err(Error::data_differs_from_proven(format!(
+            "Transaction belongs to another account than {}",
+            address,
+        )));
+    }
+    let state_update = transaction.read_state_update()
+        .map_err(|err| Error::invalid_data(err))?;
+    if state_update.new_hash != *account_hash {
+        return Err(Error::data_differs_from_proven(format!(
+            "Account state hash {:x} differs from the last transaction state hash {:x}",
+            account_hash,
+            state_update.new_hash,
+        )));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn check_transaction_in_block(
+    block: &Block,
+    block_id: &UInt256,
+    root_hash: &UInt256,
+) -> ClientResult<()> {
+    let block_extra = block.read_extra()
+        .map_err(|err| Error::invalid_data(err))?;
+    let account_blocks = block_extra.read_account_blocks()
+        .map_err(|err| Error::invalid_data(err))?;
+
+    let mut transaction_found_in_block = false;
+    account_blocks.iterate_objects(|account_block| {
+        account_block.transaction_iterate_full(|_key, cell, _cc| {
+            if *root_hash == cell.repr_hash() {
+                transaction_found_in_block = true;
+                return Ok(false);
+            }
+            Ok(true)
+        })
+    })
+        .map_err(|err| Error::internal_error(err))?;
+
+    if !transaction_found_in_block {
+        return Err(Error::proof_check_failed(
+            format!(
+                "Transaction with `id`: {} not found in block with `id`: {}",
+                root_hash.as_hex_string(),
+                block_id.as_hex_string(),
+            )
+        ));
+    }
+
+    Ok(())
+}
+
 pub(crate) fn is_transaction_refers_to_message(transaction_json: &Value, message_id: &Value) -> bool {
     if transaction_json["in_msg"] == *message_id {
         return true;
@@ -475,10 +923,25 @@ lazy_static! {
             .expect("FATAL: failed to read trusted key-blocks binary file!");
 }
 
+/// Runs the CPU heavy check on the blocking threads pool, so the signatures verification
+/// (parallelized with `rayon`) doesn't occupy the async runtime workers. In the web
+/// environment the check is performed in place.
+pub(crate) async fn run_blocking<R: Send + 'static>(
+    check: impl FnOnce() -> Result<R> + Send + 'static,
+) -> Result<R> {
+    #[cfg(not(feature = "wasm"))]
+    return tokio::task::spawn_blocking(check).await
+        .map_err(|err| err_msg(format!("Proof check task failed: {}", err)))?;
+    #[cfg(feature = "wasm")]
+    check()
+}
+
+#[derive(Clone)]
 pub(crate) struct Signatures {
     validator_list_hash_short: u32,
     catchain_seqno: u32,
-    sig_weight: u64,
+    // Declared weight is absent in the proofs assembled from lite server block links
+    sig_weight: Option<u64>,
     pure_signatures: Vec<CryptoSignaturePair>,
 }
 
@@ -491,7 +954,7 @@ impl Signatures {
         self.catchain_seqno
     }
 
-    pub fn sig_weight(&self) -> u64 {
+    pub fn sig_weight(&self) -> Option<u64> {
         self.sig_weight
     }
 
@@ -500,6 +963,7 @@ impl Signatures {
     }
 }
 
+#[derive(Clone)]
 pub(crate) struct BlockProof {
     id: BlockIdExt,
     root: Cell,
@@ -508,6 +972,11 @@ pub(crate) struct BlockProof {
 
 impl BlockProof {
     pub fn from_value(value: &Value) -> Result<Self> {
+        // Proofs read from the node archives are kept as is
+        if let Some(boc) = value["boc"].as_str() {
+            return Self::deserialize(&base64::decode(boc)?);
+        }
+
         let workchain_id = value.get_i32("workchain_id")?;
         let shard_prefix_tagged = u64::from_str_radix(value.get_str("shard")?, 16)?;
         let shard_id = ShardIdent::with_tagged_prefix(workchain_id, shard_prefix_tagged)?;
@@ -536,18 +1005,16 @@ impl BlockProof {
         let signatures = Signatures {
             validator_list_hash_short: signatures_json.get_u32("validator_list_hash_short")?,
             catchain_seqno: signatures_json.get_u32("catchain_seqno")?,
-            sig_weight: u64::from_str_radix(
-                signatures_json.get_str("sig_weight")?
-                    .trim_start_matches("0x"),
-                16,
-            )?,
+            sig_weight: match signatures_json["sig_weight"].as_str() {
+                Some(sig_weight) => Some(u64::from_str_radix(sig_weight.trim_start_matches("0x"), 16)?),
+                None => None,
+            },
             pure_signatures,
         };
 
         Ok(Self { id, root, signatures })
     }
 
-    #[cfg(test)]
     pub fn deserialize(data: &[u8]) -> Result<Self> {
         let proof = ton_block::BlockProof::construct_from_bytes(data)?;
         let signatures = proof.signatures
@@ -566,7 +1033,7 @@ impl BlockProof {
             signatures: Signatures {
                 validator_list_hash_short: signatures.validator_info.validator_list_hash_short,
                 catchain_seqno: signatures.validator_info.catchain_seqno,
-                sig_weight: signatures.pure_signatures.weight(),
+                sig_weight: Some(signatures.pure_signatures.weight()),
                 pure_signatures,
             }
         })
@@ -611,17 +1078,25 @@ impl BlockProof {
         let (virt_block, virt_block_info) = self.pre_check_block_proof()?;
         let prev_key_block_seqno = virt_block_info.prev_key_block_seqno();
 
-        if prev_key_block_seqno == 0 {
+        let (validators, validators_hash_short) = if prev_key_block_seqno == 0 {
             let zerostate = engine.load_zerostate().await?;
-            self.check_with_zerostate(
+            self.zerostate_validators(
                 &zerostate,
                 &virt_block,
                 &virt_block_info,
-            )?;
+            )?
         } else {
             let prev_key_block_proof = engine.load_key_block_proof(prev_key_block_seqno).await?;
 
-            self.check_with_prev_key_block_proof(&prev_key_block_proof, &virt_block, &virt_block_info)?;
+            self.prev_key_block_validators(&prev_key_block_proof, &virt_block, &virt_block_info)?
+        };
+
+        #[cfg(feature = "wasm")]
+        self.check_signatures_chunked(validators, validators_hash_short).await?;
+        #[cfg(not(feature = "wasm"))]
+        {
+            let proof = self.clone();
+            run_blocking(move || proof.check_signatures(validators, validators_hash_short)).await?;
         }
 
         Ok((virt_block, virt_block_info))
@@ -633,6 +1108,20 @@ impl BlockProof {
         virt_block: &Block,
         virt_block_info: &BlockInfo
     ) -> Result<()> {
+        let (validators, validators_hash_short) =
+            self.prev_key_block_validators(prev_key_block_proof, virt_block, virt_block_info)?;
+
+        self.check_signatures(validators, validators_hash_short)
+    }
+
+    /// Checks the block against the previous key block and returns the validators
+    /// expected to sign the block
+    fn prev_key_block_validators(
+        &self,
+        prev_key_block_proof: &BlockProof,
+        virt_block: &Block,
+        virt_block_info: &BlockInfo
+    ) -> Result<(Vec<ValidatorDescr>, u32)> {
         if !self.id().shard().is_masterchain() {
             bail!(
                 "Can't verify non masterchain block {} using previous key masterchain block",
@@ -669,7 +1158,7 @@ impl BlockProof {
             self.pre_check_key_block_proof(virt_block)?;
         }
 
-        self.check_signatures(validators, validators_hash_short)
+        Ok((validators, validators_hash_short))
     }
 
     fn check_with_zerostate(
@@ -678,14 +1167,25 @@ impl BlockProof {
         virt_block: &Block,
         virt_block_info: &BlockInfo,
     ) -> Result<()> {
+        let (validators, validators_hash_short) =
+            self.zerostate_validators(zerostate, virt_block, virt_block_info)?;
+
+        self.check_signatures(validators, validators_hash_short)
+    }
+
+    /// Checks the block against the zerostate and returns the validators expected to sign
+    /// the block
+    fn zerostate_validators(
+        &self,
+        zerostate: &ShardStateUnsplit,
+        virt_block: &Block,
+        virt_block_info: &BlockInfo,
+    ) -> Result<(Vec<ValidatorDescr>, u32)> {
         if virt_block_info.key_block() {
             self.pre_check_key_block_proof(&virt_block)?;
         }
 
-        let (validators, validators_hash_short) =
-            self.process_zerostate(zerostate, virt_block_info)?;
-
-        self.check_signatures(validators, validators_hash_short)
+        self.process_zerostate(zerostate, virt_block_info)
     }
 
     fn pre_check_block_proof(&self) -> Result<(Block, BlockInfo)> {
@@ -840,6 +1340,36 @@ impl BlockProof {
     }
 
     fn check_signatures(&self, validators_list: Vec<ValidatorDescr>, list_hash_short: u32) -> Result<()> {
+        let checked_data = self.data_for_sign(list_hash_short)?;
+        let weight = check_crypto_signatures(
+            &self.signatures,
+            &validators_list,
+            &checked_data,
+        )
+            .map_err(|err| self.signatures_error(err))?;
+
+        self.check_signatures_weight(&validators_list, weight)
+    }
+
+    /// Same as `check_signatures`, but yields to the event loop while checking the signatures
+    #[cfg(feature = "wasm")]
+    async fn check_signatures_chunked(
+        &self,
+        validators_list: Vec<ValidatorDescr>,
+        list_hash_short: u32,
+    ) -> Result<()> {
+        let checked_data = self.data_for_sign(list_hash_short)?;
+        let weight = validators::check_crypto_signatures_chunked(
+            &self.signatures,
+            &validators_list,
+            &checked_data,
+        ).await
+            .map_err(|err| self.signatures_error(err))?;
+
+        self.check_signatures_weight(&validators_list, weight)
+    }
+
+    fn data_for_sign(&self, list_hash_short: u32) -> Result<Vec<u8>> {
         // Pre checks
         if self.signatures.validator_list_hash_short() != list_hash_short {
             bail!(
@@ -849,31 +1379,32 @@ impl BlockProof {
                 self.signatures.validator_list_hash_short(),
             );
         }
-        // Check signatures
-        let checked_data = ton_block::Block::build_data_for_sign(
+
+        Ok(ton_block::Block::build_data_for_sign(
             &self.id().root_hash(),
             &self.id().file_hash()
-        );
+        ))
+    }
+
+    fn signatures_error(&self, err: failure::Error) -> failure::Error {
+        Error::invalid_data(
+            format!("Proof for {}: error while check signatures: {}", self.id(), err)
+        ).into()
+    }
+
+    fn check_signatures_weight(&self, validators_list: &[ValidatorDescr], weight: u64) -> Result<()> {
         let total_weight: u64 = validators_list.iter().map(|v| v.weight).sum();
-        let weight = check_crypto_signatures(
-            &self.signatures,
-            &validators_list,
-            &checked_data,
-        )
-            .map_err(|err| {
-                Error::invalid_data(
-                    format!("Proof for {}: error while check signatures: {}", self.id(), err)
-                )
-            })?;
 
         // Check weight
-        if weight != self.signatures.sig_weight() {
-            bail!(
-                "Proof for {}: total signature weight mismatch: declared: {}, calculated: {}",
-                self.id(),
-                self.signatures.sig_weight(),
-                weight,
-            );
+        if let Some(declared_weight) = self.signatures.sig_weight() {
+            if weight != declared_weight {
+                bail!(
+                    "Proof for {}: total signature weight mismatch: declared: {}, calculated: {}",
+                    self.id(),
+                    declared_weight,
+                    weight,
+                );
+            }
         }
 
         if weight * 3 <= total_weight * 2 {
@@ -946,34 +1477,86 @@ async fn get_current_network_uid(
 async fn query_current_network_uid(
     context: Arc<ClientContext>,
 ) -> Result<Arc<NetworkUID>> {
-    let blocks = query_collection(context, ParamsOfQueryCollection {
-        collection: "blocks".to_string(),
-        filter: Some(json!({
-            "workchain_id": {
-                "eq": -1
-            },
-            "seq_no": {
-                "eq": 1
-            },
-        })),
-        result: "id, prev_ref{root_hash}".to_string(),
-        limit: Some(1),
-        ..Default::default()
-    }).await?.result;
+    let data_source = get_data_source(&context).await?;
+
+    Ok(Arc::new(data_source.query_network_uid().await?))
+}
 
-    if blocks.is_empty() {
-        bail!("Unable to resolve zerostate's root hash: can't get masterchain block #1");
+/// Returns the trusted key-blocks of the network sorted by `seq_no`: the hardcoded ones,
+/// the ones specified in `proofs.trusted_key_blocks` config and the ones pinned with
+/// `proofs.set_trusted_key_block`.
+pub(crate) fn get_trusted_key_blocks(
+    context: &ClientContext,
+    zerostate_root_hash: &UInt256,
+) -> Result<Vec<(u32, [u8; 32])>> {
+    let mut key_blocks = INITIAL_TRUSTED_KEY_BLOCKS
+        .get(zerostate_root_hash.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    for network in context.config.proofs.trusted_key_blocks.iter().flatten() {
+        if UInt256::from_str(&network.zerostate_root_hash)? != *zerostate_root_hash {
+            continue;
+        }
+        for key_block in &network.key_blocks {
+            let root_hash = UInt256::from_str(&key_block.root_hash)?;
+            key_blocks.push((key_block.seq_no, *root_hash.as_array()));
+        }
     }
 
-    let prev_ref = &blocks[0]["prev_ref"];
-    if prev_ref.is_null() {
-        bail!("Unable to resolve zerostate's root hash: prev_ref of the block #1 is not set");
+    let pinned = context.net.pinned_key_blocks.read()
+        .map_err(|_| err_msg("Pinned key-blocks lock is poisoned"))?;
+    if let Some(pinned) = pinned.get(zerostate_root_hash.as_array()) {
+        key_blocks.extend(pinned.iter().cloned());
     }
 
-    let first_master_block_root_hash = UInt256::from_str(blocks[0].get_str("id")?)?;
-    let zerostate_root_hash = UInt256::from_str(prev_ref.get_str("root_hash")?)?;
+    key_blocks.sort();
+    key_blocks.dedup();
 
-    Ok(Arc::new(NetworkUID { zerostate_root_hash, first_master_block_root_hash }))
+    Ok(key_blocks)
+}
+
+/// Returns the trust scope of the proofs checked by the context: a hash of the key-blocks
+/// specified in `proofs.trusted_key_blocks` config for the network and of the archive data
+/// source, or `None` if the context uses only the hardcoded key-blocks and the network data.
+///
+/// Stored proofs are trusted without re-checking, so the proofs storage is not shared between
+/// the scopes: a proof accepted due to the anchors of one context must not be trusted by
+/// the contexts which don't have these anchors. Key-blocks pinned with
+/// `proofs.set_trusted_key_block` don't change the scope, because they are proven with
+/// the scope anchors before pinning.
+pub(crate) fn get_trust_scope(
+    context: &ClientContext,
+    zerostate_root_hash: &UInt256,
+) -> Result<Option<String>> {
+    let mut anchors = Vec::new();
+    for network in context.config.proofs.trusted_key_blocks.iter().flatten() {
+        if UInt256::from_str(&network.zerostate_root_hash)? != *zerostate_root_hash {
+            continue;
+        }
+        for key_block in &network.key_blocks {
+            let root_hash = UInt256::from_str(&key_block.root_hash)?;
+            anchors.push((key_block.seq_no, *root_hash.as_array()));
+        }
+    }
+    let archive_path = context.config.proofs.archive_path.as_ref();
+    if anchors.is_empty() && archive_path.is_none() {
+        return Ok(None);
+    }
+
+    anchors.sort();
+    anchors.dedup();
+    let mut data = Vec::new();
+    for (seq_no, root_hash) in anchors {
+        data.extend_from_slice(&seq_no.to_be_bytes());
+        data.extend_from_slice(&root_hash);
+    }
+    if let Some(path) = archive_path {
+        data.extend_from_slice(b"archive:");
+        data.extend_from_slice(path.as_bytes());
+    }
+
+    Ok(Some(hex::encode(&sha2::Sha256::digest(&data)[..8])))
 }
 
 async fn resolve_initial_trusted_key_block(
@@ -981,22 +1564,21 @@ async fn resolve_initial_trusted_key_block(
     mc_seq_no: u32,
 ) -> Result<(u32, UInt256)> {
     let network_uid = get_current_network_uid(context).await?;
+    let trusted_mc_blocks = get_trusted_key_blocks(context, &network_uid.zerostate_root_hash)?;
 
-    if let Some(hardcoded_mc_blocks) =
-        INITIAL_TRUSTED_KEY_BLOCKS.get(network_uid.zerostate_root_hash.as_array())
-    {
-        let index = match hardcoded_mc_blocks.binary_search_by_key(
+    if !trusted_mc_blocks.is_empty() {
+        let index = match trusted_mc_blocks.binary_search_by_key(
             &mc_seq_no, |(seq_no, _root_hash)| *seq_no,
         ) {
             Ok(seq_no) => seq_no,
-            Err(seq_no) => if seq_no >= hardcoded_mc_blocks.len() {
+            Err(seq_no) => if seq_no >= trusted_mc_blocks.len() {
                 seq_no - 1
             } else {
                 seq_no
             },
         };
 
-        let (seq_no, ref root_hash) = hardcoded_mc_blocks[index];
+        let (seq_no, ref root_hash) = trusted_mc_blocks[index];
         return Ok((seq_no, UInt256::from_slice(root_hash)));
     }
 
@@ -0,0 +1,131 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use std::sync::Arc;
+
+use failure::bail;
+use serde_json::Value;
+use tokio::sync::Mutex;
+use ton_types::{Result, UInt256};
+
+use crate::error::ClientResult;
+use crate::net::{
+    query_collection, subscribe_collection, OrderBy, ParamsOfQueryCollection,
+    ParamsOfSubscribeCollection, ResultOfSubscribeCollection, ResultOfSubscription, SortDirection,
+};
+use crate::proofs::engine::ProofHelperEngineImpl;
+use crate::proofs::{Error, ProofHelperEngine};
+use crate::utils::json::JsonHelper;
+use crate::ClientContext;
+
+const KEY_BLOCK_FIELDS: &str = "id seq_no";
+
+fn key_blocks_filter() -> Value {
+    json!({
+        "workchain_id": { "eq": -1 },
+        "key_block": { "eq": true },
+    })
+}
+
+/// Proves the key block and stores its proof together with the chain of the key block
+/// proofs leading to it. Prefetches are serialized by the engine lock, so the chain
+/// is downloaded only once if the key blocks arrive faster than they are proven.
+async fn prefetch_key_block(engine: &Mutex<ProofHelperEngineImpl>, block: &Value) -> Result<()> {
+    let seq_no = block.get_u32("seq_no")?;
+    let root_hash = UInt256::from_str(block.get_str("id")?)?;
+
+    let engine = engine.lock().await;
+    let proof = engine.load_key_block_proof(seq_no).await?;
+    if *proof.id().root_hash() != root_hash {
+        bail!(
+            "`id` ({}) of the key block with seq_no: {} mismatches `root_hash` ({}) of its proof",
+            root_hash,
+            seq_no,
+            proof.id().root_hash(),
+        );
+    }
+    Ok(())
+}
+
+async fn prefetch_key_block_logged(engine: &Mutex<ProofHelperEngineImpl>, block: &Value) {
+    if let Err(err) = prefetch_key_block(engine, block).await {
+        log::warn!("Key block proof is not prefetched: {}", err);
+    }
+}
+
+/// Starts prefetching of the masterchain key block proofs.
+///
+/// The proof chain is extended up to the latest key block in the background, then the new
+/// key blocks are received by the subscription and their proofs are checked and stored as
+/// soon as the key blocks appear. So the proof chain is always up to date and proving
+/// of the recent blocks doesn't need to download it.
+///
+/// Prefetch failures are logged and don't stop the prefetching.
+/// The prefetching must be stopped with `net.unsubscribe`.
+#[api_function]
+pub async fn prefetch_key_blocks(
+    context: Arc<ClientContext>,
+) -> ClientResult<ResultOfSubscribeCollection> {
+    let engine = Arc::new(Mutex::new(
+        ProofHelperEngineImpl::new(context.clone()).await
+            .map_err(|err| Error::internal_error(err))?
+    ));
+
+    let latest_engine = engine.clone();
+    let latest_context = context.clone();
+    context.env.spawn(async move {
+        let latest = query_collection(
+            latest_context,
+            ParamsOfQueryCollection {
+                collection: "blocks".to_owned(),
+                filter: Some(key_blocks_filter()),
+                result: KEY_BLOCK_FIELDS.to_owned(),
+                order: Some(vec![OrderBy {
+                    path: "seq_no".to_owned(),
+                    direction: SortDirection::DESC,
+                }]),
+                limit: Some(1),
+                ..Default::default()
+            },
+        ).await;
+        match latest {
+            Ok(latest) => if let Some(block) = latest.result.get(0) {
+                prefetch_key_block_logged(&latest_engine, block).await;
+            },
+            Err(err) => log::warn!("Latest key block is not queried: {}", err.message),
+        }
+    });
+
+    let callback = move |result: ClientResult<ResultOfSubscription>| {
+        let engine = engine.clone();
+        async move {
+            match result {
+                Ok(result) => prefetch_key_block_logged(&engine, &result.result).await,
+                Err(err) => log::warn!("Key blocks subscription failed: {}", err.message),
+            }
+        }
+    };
+
+    subscribe_collection(
+        context,
+        ParamsOfSubscribeCollection {
+            collection: "blocks".to_owned(),
+            filter: Some(key_blocks_filter()),
+            result: KEY_BLOCK_FIELDS.to_owned(),
+            mapping: None,
+            decode_body: None,
+        },
+        callback,
+    ).await
+}
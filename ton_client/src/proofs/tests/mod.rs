@@ -4,15 +4,24 @@ use std::sync::Arc;
 use failure::bail;
 use graphql_parser::schema::{Definition, ObjectType, Type, TypeDefinition};
 use serde_json::Value;
-use ton_block::{BinTreeType, Block, BlockIdExt, Deserializable, InRefValue, MASTERCHAIN_ID, ShardHashes, ShardIdent, ShardStateUnsplit};
+use ton_block::{BinTreeType, Block, BlockIdExt, Deserializable, InRefValue, MASTERCHAIN_ID, ShardHashes, ShardIdent, ShardStateUnsplit, ValidatorDescr};
 use ton_types::{Result, UInt256};
 
 use crate::client::storage::InMemoryKeyValueStorage;
 use crate::ClientContext;
+use crate::error::ClientResult;
 use crate::net::{ParamsOfQueryCollection, query_collection};
-use crate::proofs::{BlockProof, get_current_network_uid, INITIAL_TRUSTED_KEY_BLOCKS, is_transaction_refers_to_message, message_get_required_data, ParamsOfProofBlockData, ParamsOfProofMessageData, ParamsOfProofTransactionData, proof_message_data, proof_transaction_data, query_current_network_uid, resolve_initial_trusted_key_block, transaction_get_required_data};
+use crate::proofs::{attest, AttestedDataKind, BlockProof, get_current_network_uid, INITIAL_TRUSTED_KEY_BLOCKS, is_transaction_refers_to_message, message_get_required_data, ParamsOfProofBlockData, ParamsOfProofMessageData, ParamsOfProofTransactionData, proof_message_data, proof_transaction_data, query_current_network_uid, resolve_initial_trusted_key_block, transaction_get_required_data, verify_attestation, ParamsOfAttest, ParamsOfVerifyAttestation};
+use crate::proofs::{json, proof_transaction_field, ParamsOfProofTransactionField};
+use crate::proofs::archive::ArchiveDataSource;
+use crate::proofs::forensics;
+use crate::proofs::{export_trusted_state, import_trusted_state, ParamsOfImportTrustedState};
+use crate::proofs::{get_trusted_key_blocks, set_trusted_key_block, ParamsOfSetTrustedKeyBlock};
+use crate::proofs::prefetch_key_blocks;
+use crate::net::TrustedMcBlockId;
+use crate::proofs::data_source::ProofDataSource;
 use crate::proofs::engine::ProofHelperEngineImpl;
-use crate::proofs::validators::{calc_subset_for_workchain, calc_workchain_id, calc_workchain_id_by_adnl_id};
+use crate::proofs::validators::{calc_subset_for_workchain, calc_workchain_id, calc_workchain_id_by_adnl_id, check_crypto_signatures, check_crypto_signatures_chunked};
 use crate::tests::TestClient;
 
 const GQL_SCHEMA: &str = include_str!("data/schema.graphql");
@@ -52,6 +61,91 @@ fn test_check_master_blocks_proof_shuffle() -> Result<()> {
     Ok(())
 }
 
+fn read_signed_proof() -> Result<(BlockProof, Vec<ValidatorDescr>, u32)> {
+    let key_block_proof = BlockProof::read_from_file(
+        "src/proofs/tests/data/test_master_block_proof/key_proof__3082181"
+    )?;
+    let proof = BlockProof::read_from_file(
+        "src/proofs/tests/data/test_master_block_proof/proof__3082182"
+    )?;
+    let (virt_block, virt_block_info) = proof.pre_check_block_proof()?;
+    let (validators, list_hash_short) =
+        proof.prev_key_block_validators(&key_block_proof, &virt_block, &virt_block_info)?;
+
+    Ok((proof, validators, list_hash_short))
+}
+
+#[tokio::test]
+async fn test_check_signatures() -> Result<()> {
+    let (proof, validators, list_hash_short) = read_signed_proof()?;
+    let data = proof.data_for_sign(list_hash_short)?;
+
+    let weight = check_crypto_signatures(&proof.signatures, &validators, &data)?;
+    assert!(weight > 0);
+    if let Some(declared_weight) = proof.signatures.sig_weight() {
+        assert_eq!(weight, declared_weight);
+    }
+    assert_eq!(
+        check_crypto_signatures_chunked(&proof.signatures, &validators, &data).await?,
+        weight,
+    );
+    proof.check_signatures(validators, list_hash_short)?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_check_signatures_bad_signature() -> Result<()> {
+    let (mut proof, validators, list_hash_short) = read_signed_proof()?;
+    let data = proof.data_for_sign(list_hash_short)?;
+
+    // The first bad signature is reported even if the signatures are checked in parallel
+    let signatures = &mut proof.signatures.pure_signatures;
+    let last = signatures.len() - 1;
+    signatures[1].sign = signatures[0].sign.clone();
+    signatures[last].sign = signatures[0].sign.clone();
+    let expected = format!(
+        "bad signature from validator with pub_key {}",
+        base64::encode(signatures[1].node_id_short.as_slice()),
+    );
+
+    let err = check_crypto_signatures(&proof.signatures, &validators, &data).unwrap_err();
+    assert_eq!(err.to_string(), expected);
+    let err = check_crypto_signatures_chunked(&proof.signatures, &validators, &data).await
+        .unwrap_err();
+    assert_eq!(err.to_string(), expected);
+    let err = proof.check_signatures(validators, list_hash_short).unwrap_err();
+    assert!(err.to_string().contains(&expected), "{}", err);
+
+    Ok(())
+}
+
+#[test]
+fn test_check_signatures_weight_threshold() -> Result<()> {
+    let (mut proof, validators, list_hash_short) = read_signed_proof()?;
+    let data = proof.data_for_sign(list_hash_short)?;
+    let total_weight: u64 = validators.iter().map(|vd| vd.weight).sum();
+
+    // Leave the minimal set of signatures whose weight exceeds 2/3 of the total weight
+    proof.signatures.sig_weight = None;
+    loop {
+        let mut reduced = proof.signatures.clone();
+        reduced.pure_signatures.pop();
+        let weight = check_crypto_signatures(&reduced, &validators, &data)?;
+        if weight * 3 <= total_weight * 2 {
+            break;
+        }
+        proof.signatures = reduced;
+    }
+    proof.check_signatures(validators.clone(), list_hash_short)?;
+
+    proof.signatures.pure_signatures.pop();
+    let err = proof.check_signatures(validators, list_hash_short).unwrap_err();
+    assert!(err.to_string().contains("too small signatures weight"), "{}", err);
+
+    Ok(())
+}
+
 #[test]
 fn test_calc_workchain_id_by_adnl_id() {
     assert_eq!(calc_workchain_id_by_adnl_id(&[0; 32]), -1);
@@ -100,6 +194,50 @@ fn test_any_keyblock_validator_set() -> Result<()> {
     check_any_keyblock_validator_set("src/proofs/tests/data/key_block.boc")
 }
 
+#[tokio::test]
+async fn test_archive_data_source() -> Result<()> {
+    let boc = std::fs::read("src/proofs/tests/data/key_block.boc")?;
+    let block = Block::construct_from_bytes(&boc)?;
+    let info = block.read_info()?;
+    let root_hash = ton_types::deserialize_tree_of_cells(&mut std::io::Cursor::new(&boc))?
+        .repr_hash();
+    let file_hash = UInt256::calc_file_hash(&boc);
+
+    let path = std::env::temp_dir().join(format!("proofs_archive_{}", std::process::id()));
+    std::fs::create_dir_all(&path)?;
+    let archive = ArchiveDataSource::new(&path).await?;
+
+    // Entries added after the archive is opened are picked up on the next request
+    std::fs::write(
+        path.join(format!(
+            "block_(-1,8000000000000000,{}):{}:{}",
+            info.seq_no(),
+            root_hash.as_hex_string().to_uppercase(),
+            file_hash.as_hex_string().to_uppercase(),
+        )),
+        &boc,
+    )?;
+    std::fs::write(path.join("unrelated.txt"), "skipped")?;
+
+    assert_eq!(archive.query_block_boc(&root_hash.as_hex_string()).await?, Some(boc));
+    assert_eq!(archive.query_block_boc(&UInt256::default().as_hex_string()).await?, None);
+    assert_eq!(
+        archive.query_mc_blocks_file_hashes(&[info.seq_no() + 1, info.seq_no()]).await?,
+        vec![(info.seq_no(), file_hash.as_hex_string())],
+    );
+
+    let shards = archive.query_mc_blocks_shards(info.seq_no(), 10).await?;
+    assert_eq!(shards.len(), 1);
+    assert_eq!(shards[0].0, info.seq_no());
+    assert!(!shards[0].1.is_empty());
+
+    assert!(archive.query_zerostate_boc().await.is_err());
+
+    std::fs::remove_dir_all(&path)?;
+
+    Ok(())
+}
+
 fn get_top_blocks(shards: &ShardHashes, workchains: &[i32]) -> Result<Vec<BlockIdExt>> {
     let mut result = Vec::new();
     for workchain_id in workchains {
@@ -232,6 +370,132 @@ fn create_engine_mainnet() -> ProofHelperEngineImpl {
     ProofHelperEngineImpl::with_values(client.context(), storage)
 }
 
+#[tokio::test]
+async fn test_trusted_key_blocks() -> Result<()> {
+    let zerostate_root_hash = UInt256::from_str(MAINNET_ZEROSTATE_ROOT_HASH)?;
+    let configured_root_hash = "1".repeat(64);
+    let client = TestClient::new_with_config(json!({
+        "network": MAINNET_CONFIG["network"].clone(),
+        "proofs": {
+            "trusted_key_blocks": [{
+                "zerostate_root_hash": MAINNET_ZEROSTATE_ROOT_HASH,
+                "key_blocks": [{ "seq_no": 1, "root_hash": configured_root_hash }],
+            }],
+        },
+    }));
+    let context = client.context();
+
+    let hardcoded = INITIAL_TRUSTED_KEY_BLOCKS.get(zerostate_root_hash.as_array()).unwrap();
+    let trusted = get_trusted_key_blocks(&context, &zerostate_root_hash)?;
+    assert_eq!(trusted.len(), hardcoded.len() + 1);
+    assert_eq!(trusted[0], (1, *UInt256::from_str(&configured_root_hash)?.as_array()));
+
+    let client = TestClient::new_with_config(MAINNET_CONFIG.clone());
+    let context = client.context();
+    let engine = ProofHelperEngineImpl::new(Arc::clone(&context)).await?;
+    let (seq_no, proof) = engine.query_key_blocks_proofs(100..100000).await?.remove(0);
+    let root_hash = proof["id"].as_str().unwrap().to_string();
+
+    set_trusted_key_block(
+        Arc::clone(&context),
+        ParamsOfSetTrustedKeyBlock {
+            key_block: TrustedMcBlockId { seq_no, root_hash: root_hash.clone() },
+        },
+    ).await.unwrap();
+
+    let trusted = get_trusted_key_blocks(&context, &zerostate_root_hash)?;
+    assert!(trusted.contains(&(seq_no, *UInt256::from_str(&root_hash)?.as_array())));
+    assert_eq!(
+        resolve_initial_trusted_key_block(&context, seq_no).await?,
+        (seq_no, UInt256::from_str(&root_hash)?),
+    );
+
+    let proof = engine.query_mc_block_proof(seq_no + 1).await?;
+    let err = set_trusted_key_block(
+        Arc::clone(&context),
+        ParamsOfSetTrustedKeyBlock {
+            key_block: TrustedMcBlockId {
+                seq_no: seq_no + 1,
+                root_hash: proof["id"].as_str().unwrap().to_string(),
+            },
+        },
+    ).await.unwrap_err();
+    assert_eq!(err.code, crate::proofs::ErrorCode::InvalidData as u32);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_shared_proof_storage() -> Result<()> {
+    let config = json!({
+        "network": MAINNET_CONFIG["network"].clone(),
+        "proofs": {
+            "cache_in_local_storage": false,
+        },
+    });
+    let client1 = TestClient::new_with_config(config.clone());
+    let client2 = TestClient::new_with_config(config.clone());
+
+    let engine1 = ProofHelperEngineImpl::new(client1.context()).await?;
+    let engine2 = ProofHelperEngineImpl::new(client2.context()).await?;
+
+    engine1.storage().put_str("shared_test", "value").await?;
+    assert_eq!(engine2.storage().get_str("shared_test").await?, Some("value".to_string()));
+
+    let mut config = config;
+    config["proofs"]["shared_cache"] = json!(false);
+    let client3 = TestClient::new_with_config(config);
+    let engine3 = ProofHelperEngineImpl::new(client3.context()).await?;
+
+    assert_eq!(engine3.storage().get_str("shared_test").await?, None);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_proof_storage_trust_anchors() -> Result<()> {
+    let storage_path = std::env::temp_dir()
+        .join(format!("proofs_trust_anchors_{}", std::process::id()));
+    let config = |shared_cache: bool, anchor: Option<String>| {
+        let mut config = json!({
+            "network": MAINNET_CONFIG["network"].clone(),
+            "local_storage_path": storage_path.to_str().unwrap(),
+            "proofs": {
+                "shared_cache": shared_cache,
+            },
+        });
+        if let Some(root_hash) = anchor {
+            config["proofs"]["trusted_key_blocks"] = json!([{
+                "zerostate_root_hash": MAINNET_ZEROSTATE_ROOT_HASH,
+                "key_blocks": [{ "seq_no": 1, "root_hash": root_hash }],
+            }]);
+        }
+        config
+    };
+
+    for &shared_cache in &[true, false] {
+        let key = format!("anchors_test_{}", shared_cache);
+        let client1 = TestClient::new_with_config(config(shared_cache, Some("1".repeat(64))));
+        let client2 = TestClient::new_with_config(config(shared_cache, Some("2".repeat(64))));
+        let client3 = TestClient::new_with_config(config(shared_cache, None));
+        let client4 = TestClient::new_with_config(config(shared_cache, Some("1".repeat(64))));
+
+        let engine1 = ProofHelperEngineImpl::new(client1.context()).await?;
+        engine1.storage().put_str(&key, "value").await?;
+
+        let engine2 = ProofHelperEngineImpl::new(client2.context()).await?;
+        assert_eq!(engine2.storage().get_str(&key).await?, None);
+        let engine3 = ProofHelperEngineImpl::new(client3.context()).await?;
+        assert_eq!(engine3.storage().get_str(&key).await?, None);
+        let engine4 = ProofHelperEngineImpl::new(client4.context()).await?;
+        assert_eq!(engine4.storage().get_str(&key).await?, Some("value".to_string()));
+    }
+
+    let _ = std::fs::remove_dir_all(&storage_path);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_metadata_storage() -> Result<()> {
     let engine = create_engine_mainnet();
@@ -349,6 +613,33 @@ async fn test_query_key_blocks_proofs() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_parallel_proof_chain_verification() -> Result<()> {
+    let config = json!({
+        "network": MAINNET_CONFIG["network"].clone(),
+        "proofs": {
+            "parallel_verification": 4,
+        },
+    });
+    let client = TestClient::new_with_config(config);
+    let storage = Arc::new(InMemoryKeyValueStorage::new());
+    let engine = ProofHelperEngineImpl::with_values(client.context(), storage);
+
+    let stored = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let stored_copy = Arc::clone(&stored);
+    let last_proof = engine.download_proof_chain(0..1000000, move |mc_seq_no| {
+        stored_copy.lock().unwrap().push(mc_seq_no);
+        futures::future::ready(Ok(()))
+    }).await?;
+
+    let stored = stored.lock().unwrap();
+    assert_eq!(stored.len(), 110);
+    assert!(stored.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(last_proof.id().seq_no, *stored.last().unwrap());
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_add_file_hashes() -> Result<()> {
     let engine = create_engine_mainnet();
@@ -395,6 +686,39 @@ async fn test_mc_proofs() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_verify_local_cache() -> Result<()> {
+    let engine = create_engine_mainnet();
+
+    let (trusted_seq_no, _trusted_root_hash) = resolve_initial_trusted_key_block(
+        engine.context(), 10000000,
+    ).await?;
+
+    let proof = BlockProof::from_value(&engine.query_mc_block_proof(trusted_seq_no + 50000).await?)?;
+    proof.check_proof(&engine).await?;
+
+    let right_bound = engine.read_trusted_block_right_bound(trusted_seq_no).await?;
+    assert!(right_bound > trusted_seq_no);
+
+    let status = engine.verify_local_cache(false).await?;
+    assert_eq!(status.corrupted_count, 0);
+    assert!(status.valid_count > 0);
+    assert!(status.repaired_ranges.is_empty());
+
+    engine.storage().put_str(&format!("proof_mc_{}", right_bound), "{\"id\":").await?;
+
+    let status = engine.verify_local_cache(true).await?;
+    assert_eq!(status.corrupted_count, 1);
+    assert_eq!(status.repaired_ranges.len(), 1);
+    assert_eq!(status.repaired_ranges[0].end, right_bound + 1);
+    assert_eq!(engine.read_trusted_block_right_bound(trusted_seq_no).await?, right_bound);
+
+    let status = engine.verify_local_cache(false).await?;
+    assert_eq!(status.corrupted_count, 0);
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_extract_top_shard_block() -> Result<()> {
     let engine = create_engine_mainnet();
@@ -641,6 +965,42 @@ fn gen_full_schema_query(object_type: &str) -> Result<String> {
     bail!("Object type is not found in the schema: {}", object_type)
 }
 
+#[tokio::test]
+async fn test_proof_block_data_progress() -> Result<()> {
+    let client = TestClient::new_with_config(MAINNET_CONFIG.clone());
+
+    let block_json = query_block_data(
+        client.context(),
+        "8bde590a572437332977e68bace66fa00f9cebac6baa57f6bf2d2f1276db2848",
+        "id boc",
+    ).await?;
+
+    let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let events_copy = Arc::clone(&events);
+    crate::proofs::proof_block_data_with_progress(
+        client.context(),
+        ParamsOfProofBlockData { block: block_json, send_events: Some(true), verification_policy: None },
+        move |event| {
+            events_copy.lock().unwrap().push(event);
+            futures::future::ready(())
+        },
+    ).await?;
+
+    let events = events.lock().unwrap();
+    for (index, event) in events.iter().enumerate() {
+        assert_eq!(event.verified_count, index as u32 + 1);
+        assert!(event.verified_count <= event.total_count);
+        if index > 0 {
+            assert!(event.seq_no > events[index - 1].seq_no);
+        }
+    }
+    if let Some(last) = events.last() {
+        assert_eq!(last.verified_count, last.total_count);
+    }
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_proof_block_data() -> Result<()> {
     let client = TestClient::new_with_config(MAINNET_CONFIG.clone());
@@ -653,14 +1013,14 @@ async fn test_proof_block_data() -> Result<()> {
 
     client.request_async(
         "proofs.proof_block_data",
-        ParamsOfProofBlockData { block: block_json.clone() },
+        ParamsOfProofBlockData { block: block_json.clone(), send_events: None, verification_policy: None },
     ).await?;
 
     block_json["boc"] = Value::Null;
 
     client.request_async(
         "proofs.proof_block_data",
-        ParamsOfProofBlockData { block: block_json.clone() },
+        ParamsOfProofBlockData { block: block_json.clone(), send_events: None, verification_policy: None },
     ).await?;
 
     block_json["boc"] = SHARD_BLOCK_0_A000000000000000_99_BOC.into();
@@ -668,7 +1028,7 @@ async fn test_proof_block_data() -> Result<()> {
     assert!(
         client.request_async::<_, ()>(
             "proofs.proof_block_data",
-            ParamsOfProofBlockData { block: block_json.clone() },
+            ParamsOfProofBlockData { block: block_json.clone(), send_events: None, verification_policy: None },
         ).await
             .is_err()
     );
@@ -679,7 +1039,7 @@ async fn test_proof_block_data() -> Result<()> {
     assert!(
         client.request_async::<_, ()>(
             "proofs.proof_block_data",
-            ParamsOfProofBlockData { block: block_json },
+            ParamsOfProofBlockData { block: block_json, send_events: None, verification_policy: None },
         ).await
             .is_err()
     );
@@ -712,7 +1072,7 @@ async fn test_proof_block_data() -> Result<()> {
     assert!(
         client.request_async::<_, ()>(
             "proofs.proof_block_data",
-            ParamsOfProofBlockData { block: proof_json },
+            ParamsOfProofBlockData { block: proof_json, send_events: None, verification_policy: None },
         ).await
             .is_err()
     );
@@ -849,7 +1209,7 @@ async fn test_proof_block_data() -> Result<()> {
 
     client.request_async(
         "proofs.proof_block_data",
-        ParamsOfProofBlockData { block: block_json.clone() },
+        ParamsOfProofBlockData { block: block_json.clone(), send_events: None, verification_policy: None },
     ).await?;
 
     // Shardchain block
@@ -861,7 +1221,7 @@ async fn test_proof_block_data() -> Result<()> {
 
     client.request_async(
         "proofs.proof_block_data",
-        ParamsOfProofBlockData { block: block_json.clone() },
+        ParamsOfProofBlockData { block: block_json.clone(), send_events: None, verification_policy: None },
     ).await?;
 
     Ok(())
@@ -925,7 +1285,7 @@ async fn test_proof_transaction_data() -> Result<()> {
 
     proof_transaction_data(
         client.context(),
-        ParamsOfProofTransactionData { transaction: transaction_json },
+        ParamsOfProofTransactionData { transaction: transaction_json, verification_policy: None },
     ).await?;
 
     let transaction_json = query_transaction_data(
@@ -951,12 +1311,71 @@ async fn test_proof_transaction_data() -> Result<()> {
 
     proof_transaction_data(
         client.context(),
-        ParamsOfProofTransactionData { transaction: transaction_json },
+        ParamsOfProofTransactionData { transaction: transaction_json, verification_policy: None },
     ).await?;
 
     Ok(())
 }
 
+#[tokio::test]
+async fn test_proof_transaction_field() -> Result<()> {
+    let client = TestClient::new_with_config(MAINNET_CONFIG.clone());
+
+    let mut transaction_json = query_transaction_data(
+        client.context(),
+        "0c7e395e8eb14c173d2dde7189200f28787a05df1fa188b19224f6e19a439dc6",
+        "id boc balance_delta(format:DEC) lt(format:DEC) compute { exit_code }",
+    ).await?;
+    transaction_json["lt"] = Value::from("1");
+
+    let result = proof_transaction_field(
+        client.context(),
+        ParamsOfProofTransactionField {
+            transaction: transaction_json.clone(),
+            fields: vec![
+                "balance_delta".to_owned(),
+                "lt".to_owned(),
+                "compute.exit_code".to_owned(),
+                "total_fees".to_owned(),
+            ],
+        },
+    ).await?;
+
+    let verified: Vec<bool> = result.fields.iter().map(|verdict| verdict.verified).collect();
+    assert_eq!(verified, vec![true, false, true, false]);
+    assert_eq!(result.fields[2].proven_value, transaction_json["compute"]["exit_code"]);
+    assert!(result.fields[1].error.is_some());
+
+    Ok(())
+}
+
+#[test]
+fn test_compare_transaction_fields() {
+    let proven = json!({
+        "balance_delta": "0x10",
+        "compute": { "exit_code": 0 },
+        "out_msgs": ["a", "b"],
+        "chain_order": "1",
+    });
+    let actual = json!({
+        "balance_delta": "16",
+        "compute": { "exit_code": 1 },
+        "out_msgs": ["a", "c"],
+        "chain_order": "1",
+    });
+    let fields: Vec<String> = ["balance_delta", "compute.exit_code", "out_msgs.0", "out_msgs.1", "chain_order", "lt"]
+        .iter()
+        .map(|field| field.to_string())
+        .collect();
+
+    let verified: Vec<bool> = json::compare_transaction_fields(&actual, &proven, &fields)
+        .into_iter()
+        .map(|(_, result)| result.is_ok())
+        .collect();
+    assert_eq!(verified, vec![true, false, true, false, false, true]);
+    assert_eq!(json::get_field(&proven, "out_msgs.1"), "b");
+}
+
 #[tokio::test]
 async fn test_message_get_required_data() -> Result<()> {
     async fn test(
@@ -1083,7 +1502,7 @@ async fn test_proof_message_data() -> Result<()> {
 
     proof_message_data(
         client.context(),
-        ParamsOfProofMessageData { message: message_json },
+        ParamsOfProofMessageData { message: message_json, verification_policy: None },
     ).await?;
 
     let message_json = query_message_data(
@@ -1103,8 +1522,348 @@ async fn test_proof_message_data() -> Result<()> {
 
     proof_message_data(
         client.context(),
-        ParamsOfProofMessageData { message: message_json },
+        ParamsOfProofMessageData { message: message_json, verification_policy: None },
+    ).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_attestation() -> Result<()> {
+    let client = TestClient::new_with_config(MAINNET_CONFIG.clone());
+
+    let transaction_json = query_transaction_data(
+        client.context(),
+        "0c7e395e8eb14c173d2dde7189200f28787a05df1fa188b19224f6e19a439dc6",
+        "id boc lt(format:DEC) total_fees(format:DEC)",
+    ).await?;
+
+    let attestation = attest(
+        client.context(),
+        ParamsOfAttest {
+            kind: AttestedDataKind::Transaction,
+            data: transaction_json.clone(),
+        },
+    ).await?.attestation;
+
+    assert!(!attestation.mc_proofs.is_empty());
+
+    // Verification must not depend on the network, so the fresh client without endpoints is used
+    let offline_client = TestClient::new_with_config(json!({}));
+    let result = verify_attestation(
+        offline_client.context(),
+        ParamsOfVerifyAttestation {
+            attestation: attestation.clone(),
+            zerostate_root_hash: Some(attestation.zerostate_root_hash.clone()),
+        },
+    )?;
+    assert_eq!(result.data, transaction_json);
+
+    let mut forged = attestation.clone();
+    forged.data["total_fees"] = json!("1");
+    assert!(verify_attestation(
+        offline_client.context(),
+        ParamsOfVerifyAttestation { attestation: forged, zerostate_root_hash: None },
+    ).is_err());
+
+    let mut truncated = attestation;
+    truncated.mc_proofs.remove(0);
+    assert!(verify_attestation(
+        offline_client.context(),
+        ParamsOfVerifyAttestation { attestation: truncated, zerostate_root_hash: None },
+    ).is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_account_attestation() -> Result<()> {
+    let client = TestClient::new_with_config(MAINNET_CONFIG.clone());
+
+    let account_addr = query_transaction_data(
+        client.context(),
+        "0c7e395e8eb14c173d2dde7189200f28787a05df1fa188b19224f6e19a439dc6",
+        "account_addr",
+    ).await?["account_addr"].as_str().unwrap().to_string();
+
+    let attestation = attest(
+        client.context(),
+        ParamsOfAttest {
+            kind: AttestedDataKind::Account,
+            data: json!({ "id": account_addr }),
+        },
+    ).await?.attestation;
+
+    assert_eq!(attestation.kind, AttestedDataKind::Account);
+    assert!(attestation.boc.is_some());
+    assert!(attestation.transaction.is_some());
+
+    let offline_client = TestClient::new_with_config(json!({}));
+    let result = verify_attestation(
+        offline_client.context(),
+        ParamsOfVerifyAttestation {
+            attestation: attestation.clone(),
+            zerostate_root_hash: Some(attestation.zerostate_root_hash.clone()),
+        },
+    )?;
+    assert_eq!(result.data, json!({ "id": account_addr }));
+
+    // The account must be the result of the attested transaction
+    let mut forged = attestation.clone();
+    forged.data["id"] = json!(format!("0:{}", "0".repeat(64)));
+    assert!(verify_attestation(
+        offline_client.context(),
+        ParamsOfVerifyAttestation { attestation: forged, zerostate_root_hash: None },
+    ).is_err());
+
+    let mut forged = attestation;
+    forged.transaction = None;
+    assert!(verify_attestation(
+        offline_client.context(),
+        ParamsOfVerifyAttestation { attestation: forged, zerostate_root_hash: None },
+    ).is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_trusted_state_export_import() -> ClientResult<()> {
+    let config = json!({
+        "network": MAINNET_CONFIG["network"].clone(),
+        "proofs": {
+            "cache_in_local_storage": false,
+            "shared_cache": false,
+        },
+    });
+    let source = TestClient::new_with_config(config.clone());
+    let engine = ProofHelperEngineImpl::new(source.context()).await.unwrap();
+
+    let (trusted_seq_no, _trusted_root_hash) = resolve_initial_trusted_key_block(
+        engine.context(), 10000000,
+    ).await.unwrap();
+    let proof = BlockProof::from_value(
+        &engine.query_mc_block_proof(trusted_seq_no + 50000).await.unwrap()
+    ).unwrap();
+    proof.check_proof(&engine).await.unwrap();
+    let right_bound = engine.read_trusted_block_right_bound(trusted_seq_no).await.unwrap();
+
+    let exported = export_trusted_state(source.context()).await?;
+    assert!(exported.proofs_count > 1);
+
+    let target = TestClient::new_with_config(config);
+    let params = ParamsOfImportTrustedState { state: exported.state.clone() };
+    let imported = import_trusted_state(target.context(), params.clone()).await?;
+    assert_eq!(imported.imported_count, exported.proofs_count);
+    assert_eq!(imported.corrupted_count, 0);
+
+    let target_engine = ProofHelperEngineImpl::new(target.context()).await.unwrap();
+    assert_eq!(
+        target_engine.read_trusted_block_right_bound(trusted_seq_no).await.unwrap(),
+        right_bound,
+    );
+
+    // Stored proofs are kept
+    let imported = import_trusted_state(target.context(), params).await?;
+    assert_eq!(imported.imported_count, 0);
+
+    let mut state: Value = serde_json::from_slice(&base64::decode(&exported.state).unwrap()).unwrap();
+    state["zerostate_root_hash"] = json!("0".repeat(64));
+    let err = import_trusted_state(
+        target.context(),
+        ParamsOfImportTrustedState { state: base64::encode(&state.to_string()) },
+    ).await.unwrap_err();
+    assert_eq!(err.code, crate::proofs::ErrorCode::InvalidData as u32);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_offline_mode() -> Result<()> {
+    let client = TestClient::new_with_config(json!({
+        "network": MAINNET_CONFIG["network"].clone(),
+        "proofs": {
+            "cache_in_local_storage": false,
+            "shared_cache": false,
+            "offline_mode": true,
+        },
+    }));
+    let engine = ProofHelperEngineImpl::new(client.context()).await?;
+
+    let root_hash = "0".repeat(64);
+    let err = crate::proofs::proof_block_data(
+        client.context(),
+        ParamsOfProofBlockData { block: json!({ "id": root_hash }), ..Default::default() },
+    ).await.unwrap_err();
+    assert_eq!(err.code, crate::proofs::ErrorCode::DataNotCached as u32);
+
+    // Cached data is used without network
+    engine.write_block(&root_hash, &[1, 2, 3]).await?;
+    assert_eq!(engine.download_block_boc(&root_hash).await?, vec![1, 2, 3]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_verification_policy() -> Result<()> {
+    use crate::proofs::{ProofsConfig, ProofsVerificationPolicy};
+
+    assert_eq!(
+        ProofsConfig::default().verification_policy.unwrap_or_default(),
+        ProofsVerificationPolicy::Standard,
+    );
+
+    let context = Arc::new(ClientContext::new(serde_json::from_value(json!({
+        "network": {
+            "endpoints": ["a"],
+        },
+        "proofs": {
+            "verification_policy": "Paranoid",
+        },
+    }))?).unwrap());
+    let storage = Arc::new(InMemoryKeyValueStorage::new());
+    let engine = ProofHelperEngineImpl::with_values(context.clone(), storage.clone());
+    assert_eq!(engine.policy(), ProofsVerificationPolicy::Paranoid);
+
+    // Block BOC must be cross-checked across two endpoints, but only one is configured
+    let root_hash = "0".repeat(64);
+    let err = engine.download_block_boc(&root_hash).await.unwrap_err();
+    assert!(err.to_string().contains("quorum read requires 2 endpoints"));
+
+    // Cached block is used without cross-checking
+    engine.write_block(&root_hash, &[1, 2, 3]).await?;
+    assert_eq!(engine.download_block_boc(&root_hash).await?, vec![1, 2, 3]);
+
+    // Per-call policy overrides the config one
+    let engine = ProofHelperEngineImpl::with_values(context, storage)
+        .with_policy(Some(ProofsVerificationPolicy::Fast));
+    assert_eq!(engine.policy(), ProofsVerificationPolicy::Fast);
+    assert_eq!(engine.with_policy(None).policy(), ProofsVerificationPolicy::Fast);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_prefetch_key_blocks() -> Result<()> {
+    let client = TestClient::new_with_config(json!({
+        "network": MAINNET_CONFIG["network"].clone(),
+        "proofs": {
+            "cache_in_local_storage": false,
+            "shared_cache": false,
+        },
+    }));
+    let context = client.context();
+
+    let latest = query_collection(
+        Arc::clone(&context),
+        ParamsOfQueryCollection {
+            collection: "blocks".to_owned(),
+            filter: Some(json!({
+                "workchain_id": { "eq": -1 },
+                "key_block": { "eq": true },
+            })),
+            result: "seq_no".to_owned(),
+            order: Some(vec![crate::net::OrderBy {
+                path: "seq_no".to_owned(),
+                direction: crate::net::SortDirection::DESC,
+            }]),
+            limit: Some(1),
+            ..Default::default()
+        },
+    ).await?;
+    let seq_no = latest.result[0]["seq_no"].as_u64().unwrap() as u32;
+
+    let subscription = prefetch_key_blocks(Arc::clone(&context)).await?;
+
+    // Proof of the latest key block is stored in the background
+    let engine = ProofHelperEngineImpl::new(Arc::clone(&context)).await?;
+    let mut prefetched = false;
+    for _ in 0..120 {
+        if engine.read_stored_mc_proof(seq_no).await?.is_some() {
+            prefetched = true;
+            break;
+        }
+        tokio::time::delay_for(std::time::Duration::from_secs(1)).await;
+    }
+
+    crate::net::unsubscribe(Arc::clone(&context), subscription).await?;
+    assert!(prefetched);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_forensic_bundle() -> Result<()> {
+    let mut config = MAINNET_CONFIG.clone();
+    config["proofs"] = json!({ "forensic_dumps": true });
+    let client = TestClient::new_with_config(config);
+    let storage = Arc::new(InMemoryKeyValueStorage::new());
+    let engine = ProofHelperEngineImpl::with_values(client.context(), storage);
+
+    let root_hash = "0".repeat(64);
+    engine.write_block(&root_hash, &[1, 2, 3]).await?;
+    engine.download_block_boc(&root_hash).await?;
+
+    let input = json!({ "id": root_hash });
+    let err = forensics::capture_failure(
+        &engine,
+        "proof_block_data",
+        &input,
+        Err::<(), _>(crate::proofs::Error::proof_check_failed("test")),
+    ).await.unwrap_err();
+    let id = err.data["forensic_bundle_id"].as_str().unwrap().to_string();
+
+    let bundle: Value = serde_json::from_str(
+        &engine.storage().get_str(&format!("forensic_{}", id)).await?.unwrap()
+    )?;
+    assert_eq!(bundle["function"], "proof_block_data");
+    assert_eq!(bundle["input"], input);
+    assert_eq!(bundle["error"]["code"], err.code);
+    assert_eq!(bundle["blocks"][0]["id"], root_hash);
+    assert_eq!(bundle["blocks"][0]["boc"], base64::encode(&[1, 2, 3]));
+
+    // Failure already captured by the nested check is not saved again
+    let nested = forensics::capture_failure(&engine, "proof_message_data", &input, Err::<(), _>(err))
+        .await.unwrap_err();
+    assert_eq!(nested.data["forensic_bundle_id"], id);
+
+    // Invalid input is not a verification failure
+    let err = forensics::capture_failure(
+        &engine,
+        "proof_block_data",
+        &input,
+        Err::<(), _>(crate::proofs::Error::invalid_data("test")),
+    ).await.unwrap_err();
+    assert!(err.data["forensic_bundle_id"].is_null());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_proof_transaction_list() -> Result<()> {
+    let client = TestClient::new_with_config(MAINNET_CONFIG.clone());
+
+    let transaction_json = query_transaction_data(
+        client.context(),
+        "0c7e395e8eb14c173d2dde7189200f28787a05df1fa188b19224f6e19a439dc6",
+        "id account_addr lt(format:DEC)",
     ).await?;
 
+    let result = crate::proofs::proof_transaction_list(
+        client.context(),
+        crate::proofs::ParamsOfProofTransactionList {
+            account: transaction_json["account_addr"].as_str().unwrap().to_owned(),
+            start_lt: Some(transaction_json["lt"].as_str().unwrap().to_owned()),
+            end_lt: None,
+            limit: Some(2),
+        },
+    ).await?;
+
+    assert_eq!(result.transactions.len(), 2);
+    assert_eq!(result.transactions[0].id, transaction_json["id"].as_str().unwrap());
+    assert_eq!(result.transactions[0].lt, transaction_json["lt"].as_str().unwrap());
+    assert!(result.transactions.iter().all(|item| item.verified && item.error.is_none()));
+    let next_start_lt: u64 = result.next_start_lt.unwrap().parse()?;
+    assert_eq!(next_start_lt, result.transactions[1].lt.parse::<u64>()? + 1);
+
     Ok(())
 }
@@ -0,0 +1,203 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde_json::Value;
+use ton_block::Block;
+use ton_types::UInt256;
+
+use crate::boc::internal::{deserialize_object_from_base64, deserialize_object_from_boc_bin};
+use crate::client::ClientContext;
+use crate::error::{ClientError, ClientResult};
+use crate::net::{
+    query_collection, OrderBy, ParamsOfQueryCollection, SortDirection, TRANSACTIONS_COLLECTION,
+};
+use crate::proofs::engine::ProofHelperEngineImpl;
+use crate::proofs::errors::Error;
+use crate::proofs::{check_transaction_in_block, json};
+use crate::tvm::accounts_cache::parse_lt;
+
+const DEFAULT_LIMIT: u32 = 50;
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ParamsOfProofTransactionList {
+    /// Account address.
+    pub account: String,
+    /// Logical time of the first transaction of the page (inclusive).
+    /// Decimal or `0x` prefixed hexadecimal string. By default the history starts
+    /// from the first transaction of the account.
+    pub start_lt: Option<String>,
+    /// Logical time the page ends at (exclusive).
+    /// Decimal or `0x` prefixed hexadecimal string. By default the history is not limited.
+    pub end_lt: Option<String>,
+    /// Maximum number of transactions in the page. Default is 50.
+    pub limit: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default, Debug, PartialEq)]
+pub struct TransactionProofResult {
+    /// Transaction id.
+    pub id: String,
+    /// Transaction logical time as decimal string.
+    pub lt: String,
+    /// Id of the block containing the transaction.
+    pub block_id: String,
+    /// `true` if the transaction and its block are proven.
+    pub verified: bool,
+    /// The reason why the transaction is not verified.
+    pub error: Option<ClientError>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default, Debug, PartialEq)]
+pub struct ResultOfProofTransactionList {
+    /// Verification results of the page transactions in the ascending `lt` order.
+    pub transactions: Vec<TransactionProofResult>,
+    /// `start_lt` of the next page as decimal string.
+    /// `None` if the page is the last one in the requested range.
+    pub next_start_lt: Option<String>,
+}
+
+/// Proves a page of the account transaction history.
+///
+/// Queries the account transactions within the `lt` range from TONOS API together with
+/// their blocks, proves each block against the masterchain (the same way as
+/// `proof_block_data` does), checks that each transaction is included in its block and
+/// compares the queried transaction data with the proven one.
+///
+/// Unlike `proof_transaction_data`, failure to prove a transaction doesn't throw an exception,
+/// but is returned in the transaction's result, so the whole history can be verified page by page
+/// using `next_start_lt`.
+///
+/// For more information about proofs checking, see description of `proof_block_data` function.
+#[api_function]
+pub async fn proof_transaction_list(
+    context: Arc<ClientContext>,
+    params: ParamsOfProofTransactionList,
+) -> ClientResult<ResultOfProofTransactionList> {
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
+    if limit == 0 {
+        return Err(Error::invalid_data("`limit` must be greater than zero"));
+    }
+
+    let mut lt_filter = json!({});
+    if let Some(start_lt) = &params.start_lt {
+        lt_filter["ge"] = start_lt.as_str().into();
+    }
+    if let Some(end_lt) = &params.end_lt {
+        lt_filter["lt"] = end_lt.as_str().into();
+    }
+    let transactions = query_collection(
+        Arc::clone(&context),
+        ParamsOfQueryCollection {
+            collection: TRANSACTIONS_COLLECTION.to_owned(),
+            filter: Some(json!({
+                "account_addr": { "eq": params.account },
+                "lt": lt_filter,
+            })),
+            result: "id lt boc block_id".to_owned(),
+            order: Some(vec![OrderBy {
+                path: "lt".to_owned(),
+                direction: SortDirection::ASC,
+            }]),
+            limit: Some(limit),
+            ..Default::default()
+        },
+    )
+    .await?
+    .result;
+
+    let engine = ProofHelperEngineImpl::new(context).await
+        .map_err(|err| Error::proof_check_failed(err))?;
+
+    // Each block is proven once for all of its transactions
+    let mut blocks = HashMap::new();
+    let mut results = Vec::with_capacity(transactions.len());
+    for transaction in &transactions {
+        let block_id = transaction["block_id"].as_str().unwrap_or_default().to_owned();
+        if !blocks.contains_key(&block_id) {
+            let block = prove_transaction_block(&engine, &block_id).await;
+            blocks.insert(block_id.clone(), block);
+        }
+        let result = match &blocks[&block_id] {
+            Ok((block, root_hash, workchain_id)) => {
+                check_transaction(transaction, block, root_hash, *workchain_id)
+            }
+            Err(err) => Err(err.clone()),
+        };
+        results.push(TransactionProofResult {
+            id: transaction["id"].as_str().unwrap_or_default().to_owned(),
+            lt: parse_lt(&transaction["lt"]).unwrap_or_default().to_string(),
+            block_id,
+            verified: result.is_ok(),
+            error: result.err(),
+        });
+    }
+
+    let next_start_lt = if results.len() < limit as usize {
+        None
+    } else {
+        transactions
+            .last()
+            .and_then(|transaction| parse_lt(&transaction["lt"]))
+            .map(|lt| (lt + 1).to_string())
+    };
+
+    Ok(ResultOfProofTransactionList {
+        transactions: results,
+        next_start_lt,
+    })
+}
+
+async fn prove_transaction_block(
+    engine: &ProofHelperEngineImpl,
+    block_id: &str,
+) -> ClientResult<(Block, UInt256, i32)> {
+    if block_id.is_empty() {
+        return Err(Error::invalid_data("Transaction's `block_id` is required"));
+    }
+    let boc = engine.download_block_boc(block_id).await
+        .map_err(|err| Error::proof_check_failed(err))?;
+    let (block, root_hash) = deserialize_object_from_boc_bin::<Block>(&boc)?;
+    engine.proof_block_boc(&root_hash, &block, &boc).await?;
+    let workchain_id = block.read_info()
+        .map_err(|err| Error::invalid_data(err))?
+        .shard()
+        .workchain_id();
+    Ok((block, root_hash, workchain_id))
+}
+
+fn check_transaction(
+    transaction_json: &Value,
+    block: &Block,
+    block_id: &UInt256,
+    workchain_id: i32,
+) -> ClientResult<()> {
+    let boc = transaction_json["boc"].as_str()
+        .ok_or_else(|| Error::invalid_data("Transaction's `boc` is required"))?;
+    let transaction = deserialize_object_from_base64(boc, "transaction")?;
+    let root_hash = transaction.cell.repr_hash();
+    check_transaction_in_block(block, block_id, &root_hash)?;
+
+    let proven_json = json::serialize_transaction(
+        root_hash,
+        transaction.object,
+        block_id.clone(),
+        workchain_id,
+        transaction.boc.bytes("transaction")?,
+    ).map_err(|err| Error::invalid_data(err))?;
+
+    json::compare_transactions(transaction_json, &proven_json)
+}
@@ -0,0 +1,248 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use crate::encoding::base64_decode;
+use crate::error::ClientResult;
+use crate::proofs::engine::ProofHelperEngineImpl;
+use crate::proofs::{BlockProof, get_current_network_uid, get_trusted_key_blocks, Error};
+use crate::ClientContext;
+
+const TRUSTED_STATE_VERSION: u8 = 1;
+
+/// Right boundary of the proven chain starting from the trusted key-block
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct TrustedBlockRightBound {
+    trusted_seq_no: u32,
+    right_bound: u32,
+}
+
+/// Stored key-block proof
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct StoredProof {
+    seq_no: u32,
+    proof: Value,
+}
+
+/// Portable snapshot of the proofs storage. It is serialized to JSON and encoded with `base64`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct TrustedState {
+    version: u8,
+    /// Root hash of the network zerostate in `hex`
+    zerostate_root_hash: String,
+    zs_right_bound: u32,
+    trusted_right_bounds: Vec<TrustedBlockRightBound>,
+    proofs: Vec<StoredProof>,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ResultOfExportTrustedState {
+    /// Trusted state encoded with `base64`.
+    pub state: String,
+    /// Number of the key-block proofs in the state.
+    pub proofs_count: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ParamsOfImportTrustedState {
+    /// Trusted state encoded with `base64`, returned by `proofs.export_trusted_state`.
+    pub state: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, ApiType, Default)]
+pub struct ResultOfImportTrustedState {
+    /// Number of the key-block proofs added to the local storage.
+    pub imported_count: u32,
+    /// Number of the stored key-block proofs which passed the check after the import.
+    pub valid_count: u32,
+    /// Number of the stored records found corrupted and removed after the import.
+    pub corrupted_count: u32,
+}
+
+/// Collects `seq_no` of the proofs forming the chain from the `right_bound` down to
+/// the `base_seq_no` by `prev_key_block_seqno` links
+fn collect_chain(
+    proofs: &HashMap<u32, &Value>,
+    base_seq_no: u32,
+    right_bound: u32,
+    chain: &mut HashSet<u32>,
+) {
+    let mut cursor = right_bound;
+    while cursor > base_seq_no {
+        let prev_seq_no = proofs.get(&cursor)
+            .and_then(|value| BlockProof::from_value(value).ok())
+            .filter(|proof| proof.id().seq_no() == cursor)
+            .and_then(|proof| proof.pre_check_block_proof().ok())
+            .map(|(_, info)| info.prev_key_block_seqno());
+        match prev_seq_no {
+            Some(prev_seq_no) => {
+                chain.insert(cursor);
+                cursor = prev_seq_no;
+            }
+            None => break,
+        }
+    }
+}
+
+/// Exports the proven state of the proofs storage into a single portable blob.
+///
+/// The state contains the right boundaries of the proven chains starting from the zerostate
+/// and from the trusted key-blocks together with the stored key-block proofs
+/// of these chains. Applications can ship the exported state and import it with
+/// `proofs.import_trusted_state` to skip downloading of the proof chains on the first start.
+#[api_function]
+pub async fn export_trusted_state(
+    context: Arc<ClientContext>,
+) -> ClientResult<ResultOfExportTrustedState> {
+    let engine = ProofHelperEngineImpl::new(context).await
+        .map_err(|err| Error::internal_error(err))?;
+    let network_uid = get_current_network_uid(engine.context()).await
+        .map_err(|err| Error::internal_error(err))?;
+
+    let zs_right_bound = engine.read_zs_right_bound().await
+        .map_err(|err| Error::internal_error(err))?;
+    let mut proofs = engine.read_stored_proof_chain(0, zs_right_bound).await
+        .map_err(|err| Error::internal_error(err))?;
+
+    let trusted_key_blocks = get_trusted_key_blocks(engine.context(), &network_uid.zerostate_root_hash)
+        .map_err(|err| Error::internal_error(err))?;
+    let mut trusted_right_bounds = Vec::new();
+    for (trusted_seq_no, _) in trusted_key_blocks {
+        let trusted_proof = engine.read_stored_mc_proof(trusted_seq_no).await
+            .map_err(|err| Error::internal_error(err))?;
+        let trusted_proof = match trusted_proof {
+            Some(proof) => proof,
+            None => continue,
+        };
+        let right_bound = engine.read_trusted_block_right_bound(trusted_seq_no).await
+            .map_err(|err| Error::internal_error(err))?;
+        proofs.push((trusted_seq_no, trusted_proof));
+        proofs.append(
+            &mut engine.read_stored_proof_chain(trusted_seq_no, right_bound).await
+                .map_err(|err| Error::internal_error(err))?
+        );
+        trusted_right_bounds.push(TrustedBlockRightBound { trusted_seq_no, right_bound });
+    }
+
+    proofs.sort_by_key(|(seq_no, _)| *seq_no);
+    proofs.dedup_by_key(|(seq_no, _)| *seq_no);
+
+    let state = TrustedState {
+        version: TRUSTED_STATE_VERSION,
+        zerostate_root_hash: network_uid.zerostate_root_hash.as_hex_string(),
+        zs_right_bound,
+        trusted_right_bounds,
+        proofs: proofs.into_iter()
+            .map(|(seq_no, proof)| StoredProof { seq_no, proof })
+            .collect(),
+    };
+    let proofs_count = state.proofs.len() as u32;
+    let json = serde_json::to_vec(&state)
+        .map_err(|err| Error::internal_error(err))?;
+
+    Ok(ResultOfExportTrustedState {
+        state: base64::encode(&json),
+        proofs_count,
+    })
+}
+
+/// Imports the state exported with `proofs.export_trusted_state` into the proofs storage.
+///
+/// The state must belong to the network the client is connected to. Proofs already present
+/// in the storage are kept, the boundaries of the proven chains are only extended.
+/// Imported chains are checked the same way as `proofs.verify_local_cache` does (without
+/// repair): the chain from the zerostate is checked against the zerostate, which is downloaded
+/// if it isn't stored yet, the chains from the trusted key-blocks are checked offline.
+/// Parts of the chains failed the check are removed.
+#[api_function]
+pub async fn import_trusted_state(
+    context: Arc<ClientContext>,
+    params: ParamsOfImportTrustedState,
+) -> ClientResult<ResultOfImportTrustedState> {
+    let state: TrustedState = serde_json::from_slice(&base64_decode(&params.state)?)
+        .map_err(|err| Error::invalid_data(format!("Invalid trusted state: {}", err)))?;
+    if state.version != TRUSTED_STATE_VERSION {
+        return Err(Error::invalid_data(format!(
+            "Unsupported trusted state version {}",
+            state.version
+        )));
+    }
+
+    let engine = ProofHelperEngineImpl::new(context).await
+        .map_err(|err| Error::internal_error(err))?;
+    let network_uid = get_current_network_uid(engine.context()).await
+        .map_err(|err| Error::internal_error(err))?;
+    if state.zerostate_root_hash != network_uid.zerostate_root_hash.as_hex_string() {
+        return Err(Error::invalid_data(format!(
+            "Trusted state belongs to another network (zerostate root hash `{}`, expected `{}`)",
+            state.zerostate_root_hash,
+            network_uid.zerostate_root_hash.as_hex_string(),
+        )));
+    }
+
+    let trusted_key_blocks = get_trusted_key_blocks(engine.context(), &network_uid.zerostate_root_hash)
+        .map_err(|err| Error::internal_error(err))?;
+    if let Some(bound) = state.trusted_right_bounds.iter()
+        .find(|bound| !trusted_key_blocks.iter().any(|(seq_no, _)| *seq_no == bound.trusted_seq_no))
+    {
+        return Err(Error::invalid_data(format!(
+            "Key-block {} is not trusted in the current network",
+            bound.trusted_seq_no,
+        )));
+    }
+
+    // Only the proofs of the chains being imported are stored, so every imported proof
+    // is checked below
+    let proofs = state.proofs.iter()
+        .map(|stored| (stored.seq_no, &stored.proof))
+        .collect::<HashMap<_, _>>();
+    let mut chain = HashSet::new();
+    collect_chain(&proofs, 0, state.zs_right_bound, &mut chain);
+    for bound in &state.trusted_right_bounds {
+        chain.insert(bound.trusted_seq_no);
+        collect_chain(&proofs, bound.trusted_seq_no, bound.right_bound, &mut chain);
+    }
+
+    let mut imported_count = 0;
+    for StoredProof { seq_no, proof } in &state.proofs {
+        if !chain.contains(seq_no) {
+            continue;
+        }
+        if engine.import_mc_proof(*seq_no, proof).await
+            .map_err(|err| Error::internal_error(err))?
+        {
+            imported_count += 1;
+        }
+    }
+
+    engine.update_zs_right_bound(state.zs_right_bound).await
+        .map_err(|err| Error::internal_error(err))?;
+    for bound in &state.trusted_right_bounds {
+        engine.update_trusted_block_right_bound(bound.trusted_seq_no, bound.right_bound).await
+            .map_err(|err| Error::internal_error(err))?;
+    }
+
+    let status = engine.verify_local_cache(false).await
+        .map_err(|err| Error::proof_check_failed(err))?;
+
+    Ok(ResultOfImportTrustedState {
+        imported_count,
+        valid_count: status.valid_count,
+        corrupted_count: status.corrupted_count,
+    })
+}
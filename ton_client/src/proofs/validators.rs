@@ -5,8 +5,8 @@ use std::fmt::{Display, Formatter};
 use ed25519_dalek::Digest;
 use failure::bail;
 use ton_block::{
-    CatchainConfig, ConfigParams, UnixTime32, ValidatorDescr, ValidatorSet, WorkchainDescr,
-    Workchains,
+    CatchainConfig, ConfigParams, CryptoSignaturePair, UnixTime32, ValidatorDescr, ValidatorSet,
+    WorkchainDescr, Workchains,
 };
 use ton_types::Result;
 
@@ -122,27 +122,95 @@ impl Display for AdnlKeyId {
     }
 }
 
-pub(crate) fn check_crypto_signatures(
-    signatures: &Signatures,
-    validators_list: &[ValidatorDescr],
-    data: &[u8],
-) -> Result<u64> {
+/// Number of the signatures checked between the yields to the event loop in the web environment
+#[cfg(any(feature = "wasm", test))]
+const SIGNATURES_CHUNK_SIZE: usize = 16;
+
+/// Signature paired with the validator it belongs to
+type SignatureCheck<'a> = (AdnlKeyId, &'a ValidatorDescr, &'a CryptoSignaturePair);
+
+fn match_signatures<'a>(
+    signatures: &'a Signatures,
+    validators_list: &'a [ValidatorDescr],
+) -> Vec<SignatureCheck<'a>> {
     // Calc validators short ids
     let validators_map = validators_list.iter().map(|desc| {
         let key = AdnlKeyId::from_type_and_public_key(AdnlKeyId::KEY_ED25519, desc.public_key.as_slice());
         (key, desc)
     }).collect::<HashMap<_, _>>();
-    // Check signatures
+    // Signatures of the validators absent in the set are skipped
+    signatures.pure_signatures().iter()
+        .filter_map(|sign| {
+            let key = AdnlKeyId(sign.node_id_short.inner());
+            let vd = *validators_map.get(&key)?;
+            Some((key, vd, sign))
+        })
+        .collect()
+}
+
+/// Verifies the signatures and returns their total weight. Signatures are verified
+/// in parallel if the `rayon` feature is enabled
+fn verify_signatures(checks: &[SignatureCheck], data: &[u8]) -> Result<u64> {
+    #[cfg(feature = "rayon")]
+    let failed = {
+        use rayon::prelude::*;
+        checks.par_iter()
+            .find_first(|(_, vd, sign)| !vd.public_key.verify_signature(data, &sign.sign))
+    };
+    #[cfg(not(feature = "rayon"))]
+    let failed = checks.iter()
+        .find(|(_, vd, sign)| !vd.public_key.verify_signature(data, &sign.sign));
+
+    if let Some((key, _, _)) = failed {
+        bail!("bad signature from validator with pub_key {}", key)
+    }
+
+    Ok(checks.iter().map(|(_, vd, _)| vd.weight).sum())
+}
+
+pub(crate) fn check_crypto_signatures(
+    signatures: &Signatures,
+    validators_list: &[ValidatorDescr],
+    data: &[u8],
+) -> Result<u64> {
+    verify_signatures(&match_signatures(signatures, validators_list), data)
+}
+
+/// Checks the signatures in chunks yielding to the event loop between them, so checking
+/// of hundreds of signatures doesn't block the web page
+#[cfg(any(feature = "wasm", test))]
+pub(crate) async fn check_crypto_signatures_chunked(
+    signatures: &Signatures,
+    validators_list: &[ValidatorDescr],
+    data: &[u8],
+) -> Result<u64> {
+    let checks = match_signatures(signatures, validators_list);
     let mut weight = 0;
-    for sign in signatures.pure_signatures() {
-        let key = AdnlKeyId(sign.node_id_short.inner());
-        if let Some(vd) = validators_map.get(&key) {
-            if !vd.public_key.verify_signature(data, &sign.sign) {
-                bail!("bad signature from validator with pub_key {}", key)
-            }
-            weight += vd.weight;
-        }
+    for chunk in checks.chunks(SIGNATURES_CHUNK_SIZE) {
+        weight += verify_signatures(chunk, data)?;
+        YieldNow(false).await;
     }
 
     Ok(weight)
 }
+
+/// Future which is pending once, so the executor can run other tasks
+#[cfg(any(feature = "wasm", test))]
+struct YieldNow(bool);
+
+#[cfg(any(feature = "wasm", test))]
+impl std::future::Future for YieldNow {
+    type Output = ();
+
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<()> {
+        if self.0 {
+            return std::task::Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        std::task::Poll::Pending
+    }
+}
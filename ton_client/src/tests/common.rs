@@ -43,6 +43,8 @@ fn test_parallel_requests() {
                     result: "id".to_owned(),
                     limit: Some(1),
                     order: None,
+                    min_block: None,
+                    decode_body: None,
                 },
             )
             .unwrap();
@@ -105,6 +107,8 @@ async fn test_clock_sync() {
                 limit: Some(1),
                 filter: None,
                 order: None,
+                min_block: None,
+                decode_body: None,
             },
         )
         .await
@@ -117,6 +121,7 @@ async fn test_clock_sync() {
             call_set: CallSet::some_with_function("touch"),
             deploy_set: None,
             processing_try_index: None,
+            expiration: None,
             signer: Signer::None,
         })
         .await
@@ -129,12 +134,19 @@ async fn test_clock_sync() {
                 abi: None,
                 message: msg.message,
                 send_events: false,
+                broadcast_fanout: None,
+                validate_locally: None,
+                ..Default::default()
             },
         )
-        .await
-        .unwrap_err();
-
-    assert!(result
-        .message
-        .ends_with("Synchronize your device time with internet time"));
+        .await;
+
+    // sending is not rejected, the network clock is corrected by the server time instead
+    if let Err(err) = result {
+        assert_ne!(err.code, crate::net::ErrorCode::ClockOutOfSync as u32);
+    }
+    let context = client.context();
+    let server_link = context.get_server_link().unwrap();
+    let endpoint = server_link.get_query_endpoint().await.unwrap();
+    assert_eq!(server_link.clock().offset(), endpoint.time_delta());
 }
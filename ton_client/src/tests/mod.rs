@@ -607,9 +607,14 @@ impl TestClient {
                         input: Some(input),
                     }),
                     processing_try_index: None,
+                    expiration: None,
                     signer,
                 },
                 send_events: false,
+                sequence: None,
+                decode_tree: None,
+                retry_policy: None,
+                ..Default::default()
             },
             Self::default_callback,
         )
@@ -660,6 +665,10 @@ impl TestClient {
                 ParamsOfProcessMessage {
                     message_encode_params: params,
                     send_events: false,
+                    sequence: None,
+                    decode_tree: None,
+                    retry_policy: None,
+                    ..Default::default()
                 },
                 Self::default_callback,
             )
@@ -713,6 +722,10 @@ impl TestClient {
         Runtime::required_context(self.context)
             .expect("Unable to get current ClientContext by handle")
     }
+
+    pub fn handle(&self) -> ContextHandle {
+        self.context
+    }
 }
 
 impl Drop for TestClient {
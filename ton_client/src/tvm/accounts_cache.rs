@@ -0,0 +1,180 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+#[cfg(feature = "net")]
+use crate::boc::internal::deserialize_object_from_base64;
+use crate::client::ClientContext;
+use crate::encoding::account_decode;
+use crate::error::ClientResult;
+#[cfg(feature = "net")]
+use crate::net::transport::get_transport;
+#[cfg(feature = "net")]
+use crate::net::{ACCOUNTS_COLLECTION, TRANSACTIONS_COLLECTION};
+#[cfg(feature = "net")]
+use crate::tvm::Error;
+#[cfg(feature = "net")]
+use lru::LruCache;
+#[cfg(feature = "net")]
+use serde_json::Value;
+use std::sync::Arc;
+#[cfg(feature = "net")]
+use tokio::sync::Mutex;
+#[cfg(feature = "net")]
+use ton_block::Account;
+use ton_block::MsgAddressInt;
+
+#[cfg(feature = "net")]
+struct CachedAccount {
+    boc: String,
+    last_trans_lt: u64,
+}
+
+/// Keeps the recently downloaded account states.
+///
+/// Cached state is used until some transaction of the account with the greater `lt`
+/// is observed by message processing or subscriptions.
+#[cfg(feature = "net")]
+pub(crate) struct AccountsCache {
+    accounts: Option<Mutex<LruCache<String, CachedAccount>>>,
+}
+
+#[cfg(feature = "net")]
+impl AccountsCache {
+    pub fn new(max_size: u32) -> Self {
+        Self {
+            accounts: if max_size > 0 {
+                Some(Mutex::new(LruCache::new(max_size as usize)))
+            } else {
+                None
+            },
+        }
+    }
+
+    pub async fn get(&self, address: &str) -> Option<String> {
+        let accounts = self.accounts.as_ref()?;
+        accounts.lock().await.get(address).map(|account| account.boc.clone())
+    }
+
+    pub async fn put(&self, address: String, boc: String, last_trans_lt: u64) {
+        if let Some(accounts) = &self.accounts {
+            accounts.lock().await.put(address, CachedAccount { boc, last_trans_lt });
+        }
+    }
+
+    pub async fn remove(&self, address: &str) {
+        if let Some(accounts) = &self.accounts {
+            accounts.lock().await.pop(address);
+        }
+    }
+
+    /// Drops the cached account state if it is older than the observed transaction
+    pub async fn observe_transaction(&self, address: &str, lt: u64) {
+        if let Some(accounts) = &self.accounts {
+            let mut accounts = accounts.lock().await;
+            let outdated = accounts
+                .peek(address)
+                .map(|account| account.last_trans_lt < lt)
+                .unwrap_or(false);
+            if outdated {
+                accounts.pop(address);
+            }
+        }
+    }
+
+    /// Checks subscription data of `accounts` and `transactions` collections
+    /// for the newer account states
+    pub async fn observe_collection_item(&self, collection: &str, item: &Value) {
+        if self.accounts.is_none() {
+            return;
+        }
+        let (address, lt) = match collection {
+            ACCOUNTS_COLLECTION => (&item["id"], &item["last_trans_lt"]),
+            TRANSACTIONS_COLLECTION => (&item["account_addr"], &item["lt"]),
+            _ => return,
+        };
+        if let (Some(address), Some(lt)) = (address.as_str(), parse_lt(lt)) {
+            self.observe_transaction(address, lt).await;
+        }
+    }
+}
+
+#[cfg(feature = "net")]
+pub(crate) fn parse_lt(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(number) => number.as_u64(),
+        Value::String(string) => {
+            if string.starts_with("0x") {
+                u64::from_str_radix(&string[2..], 16).ok()
+            } else {
+                string.parse().ok()
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the account BOC from the accounts cache or downloads it from the network
+#[cfg(feature = "net")]
+pub(crate) async fn fetch_account_boc(
+    context: &Arc<ClientContext>,
+    address: &MsgAddressInt,
+) -> ClientResult<String> {
+    let id = address.to_string();
+    let cache = &context.net.accounts_cache;
+    if let Some(boc) = cache.get(&id).await {
+        return Ok(boc);
+    }
+
+    let boc = query_account_boc(context, address).await?;
+
+    let account = deserialize_object_from_base64::<Account>(&boc, "account")?.object;
+    cache
+        .put(id, boc.clone(), account.last_tr_time().unwrap_or_default())
+        .await;
+    Ok(boc)
+}
+
+#[cfg(not(feature = "net"))]
+pub(crate) async fn fetch_account_boc(
+    _context: &Arc<ClientContext>,
+    _address: &MsgAddressInt,
+) -> ClientResult<String> {
+    Err(crate::client::Error::net_module_not_init())
+}
+
+/// Downloads the account BOC with the network transport bypassing the accounts cache
+#[cfg(feature = "net")]
+pub(crate) async fn query_account_boc(
+    context: &Arc<ClientContext>,
+    address: &MsgAddressInt,
+) -> ClientResult<String> {
+    get_transport(context)
+        .get_account_boc(address)
+        .await?
+        .ok_or_else(|| Error::account_missing(address))
+}
+
+/// Resolves the account BOC for the functions accepting either account BOC or address
+pub(crate) async fn resolve_account_boc(
+    context: &Arc<ClientContext>,
+    account: &str,
+    address: Option<&String>,
+) -> ClientResult<String> {
+    match address {
+        Some(address) if account.is_empty() => {
+            fetch_account_boc(context, &account_decode(address)?).await
+        }
+        _ => Ok(account.to_owned()),
+    }
+}
@@ -0,0 +1,238 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use super::call_tvm::call_tvm_code;
+use super::types::ResolvedExecutionOptions;
+use crate::abi::{decode_message, Abi, DecodedMessageBody, ParamsOfDecodeMessage};
+use crate::boc::internal::serialize_object_to_base64;
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use crate::tvm::Error;
+use serde_json::Value;
+use std::sync::Arc;
+use ton_block::{
+    Account, CommonMsgInfo, Deserializable, Grams, Message, MsgAddressInt, OutAction, OutActions,
+    Serializable, Transaction, TransactionDescr,
+};
+use ton_types::{Cell, UInt256};
+use ton_vm::stack::{integer::IntegerData, Stack, StackItem};
+
+#[derive(Serialize, Deserialize, ApiType, Debug, PartialEq, Clone)]
+#[serde(tag = "type")]
+pub enum ExecutorAction {
+    /// `SENDRAWMSG` action.
+    SendMsg {
+        /// Send mode flags.
+        mode: u8,
+        /// Message as it was created by the contract, parsed to JSON. The message actually sent
+        /// by the action phase can differ in the value and the fees fields.
+        message: Value,
+        /// Message body decoded with the `abi` of the executor parameters.
+        /// `None` if the ABI is not specified or the body can't be decoded with it.
+        decoded_body: Option<DecodedMessageBody>,
+        /// `true` if the message was not sent by the action phase, e.g. because of
+        /// the insufficient balance with the "ignore errors" mode flag.
+        skipped: bool,
+    },
+    /// `SETCODE` action.
+    SetCode {
+        /// Hash of the new contract code.
+        new_code_hash: String,
+    },
+    /// `RAWRESERVE` action.
+    ReserveCurrency {
+        /// Reserve mode flags.
+        mode: u8,
+        /// Reserved value in nanotokens.
+        value: String,
+    },
+    /// `SETLIBCODE` or `CHANGELIB` action.
+    ChangeLibrary {
+        /// Library change mode flags.
+        mode: u8,
+        /// Hash of the library code.
+        hash: Option<String>,
+    },
+    /// `COPYLEFT` action.
+    CopyLeft {
+        /// License type.
+        license: u8,
+        /// Address of the license owner.
+        address: String,
+    },
+}
+
+/// Runs the compute phase of the transaction again and returns the action list cell
+/// committed by the contract
+fn restore_action_list(
+    account: &Account,
+    message: &Message,
+    options: ResolvedExecutionOptions,
+    storage_fees: &Grams,
+) -> ClientResult<Cell> {
+    let state_init = message.state_init();
+    let code = account
+        .get_code()
+        .or_else(|| state_init.and_then(|state_init| state_init.code.clone()))
+        .ok_or_else(|| Error::invalid_account_boc("Account has no code"))?;
+    let data = account
+        .get_data()
+        .or_else(|| state_init.and_then(|state_init| state_init.data.clone()))
+        .unwrap_or_default();
+    let addr = message
+        .dst_ref()
+        .ok_or_else(|| Error::invalid_message_type())?
+        .clone();
+
+    // Storage fees are collected and inbound internal message value is credited
+    // before the compute phase
+    let mut balance = account.balance().cloned().unwrap_or_default();
+    balance.grams.0 = balance.grams.0.saturating_sub(storage_fees.0);
+    let msg_value = message.get_value().cloned().unwrap_or_default();
+    balance
+        .add(&msg_value)
+        .map_err(|err| Error::internal_error(format!("can not credit message value: {}", err)))?;
+
+    let msg_cell = message
+        .serialize()
+        .map_err(|err| Error::internal_error(format!("can not serialize message: {}", err)))?;
+    let function_selector = match message.header() {
+        CommonMsgInfo::IntMsgInfo(_) => ton_vm::int!(0),
+        CommonMsgInfo::ExtInMsgInfo(_) => ton_vm::int!(-1),
+        CommonMsgInfo::ExtOutMsgInfo(_) => return Err(Error::invalid_message_type()),
+    };
+    let mut stack = Stack::new();
+    stack
+        .push(ton_vm::int!(balance.grams.0))
+        .push(ton_vm::int!(msg_value.grams.0))
+        .push(StackItem::Cell(msg_cell))
+        .push(StackItem::Slice(message.body().unwrap_or_default()))
+        .push(function_selector);
+
+    let (engine, _) = call_tvm_code(code, data, &addr, &balance, options, stack)?;
+    engine
+        .get_actions()
+        .as_cell()
+        .map(|cell| cell.clone())
+        .map_err(|err| Error::internal_error(format!("can not get actions: {}", err)))
+}
+
+fn message_key(message: &Message) -> (Option<MsgAddressInt>, Option<UInt256>) {
+    (
+        message.dst_ref().cloned(),
+        message.body().map(|body| body.into_cell().repr_hash()),
+    )
+}
+
+/// Decodes the action list produced by the compute phase of the `transaction`.
+///
+/// The action list is not stored in the transaction, so the compute phase is repeated on
+/// the input `account` and the restored list is checked against the action list hash
+/// of the transaction action phase. Returns an empty list if there was no action phase.
+pub(crate) async fn decode_executor_actions(
+    context: &Arc<ClientContext>,
+    account: &Account,
+    message: &Message,
+    options: ResolvedExecutionOptions,
+    transaction: &Transaction,
+    out_messages: &[Message],
+    abi: Option<&Abi>,
+) -> ClientResult<Vec<ExecutorAction>> {
+    let descr = match transaction
+        .read_description()
+        .map_err(|err| Error::can_not_read_transaction(err))?
+    {
+        TransactionDescr::Ordinary(descr) => descr,
+        _ => return Ok(Vec::new()),
+    };
+    let action_phase = match descr.action {
+        Some(action_phase) => action_phase,
+        None => return Ok(Vec::new()),
+    };
+    let storage_fees = descr
+        .storage_ph
+        .map(|storage| storage.storage_fees_collected)
+        .unwrap_or_default();
+
+    let actions_cell = restore_action_list(account, message, options, &storage_fees)?;
+    if actions_cell.repr_hash() != action_phase.action_list_hash {
+        return Err(Error::action_list_mismatch(
+            &actions_cell.repr_hash(),
+            &action_phase.action_list_hash,
+        ));
+    }
+    let actions = OutActions::construct_from_cell(actions_cell)
+        .map_err(|err| Error::internal_error(format!("can not parse actions: {}", err)))?;
+
+    // Sent messages keep the order of the actions, so the message actions which have no
+    // corresponding out message were skipped
+    let mut sent_messages = out_messages.iter().map(message_key).peekable();
+    let mut result = Vec::with_capacity(actions.len());
+    for action in actions {
+        result.push(match action {
+            OutAction::SendMsg { mode, out_msg } => {
+                let skipped = if sent_messages.peek() == Some(&message_key(&out_msg)) {
+                    sent_messages.next();
+                    false
+                } else {
+                    true
+                };
+                let boc = serialize_object_to_base64(&out_msg, "message")?;
+                let decoded_body = match abi {
+                    Some(abi) => decode_message(
+                        context.clone(),
+                        ParamsOfDecodeMessage {
+                            abi: abi.clone(),
+                            message: boc.clone(),
+                            partial: None,
+                        },
+                    )
+                    .await
+                    .ok(),
+                    None => None,
+                };
+                ExecutorAction::SendMsg {
+                    mode,
+                    message: crate::boc::parse_message(
+                        context.clone(),
+                        crate::boc::ParamsOfParse { boc },
+                    )
+                    .await?
+                    .parsed,
+                    decoded_body,
+                    skipped,
+                }
+            }
+            OutAction::SetCode { new_code } => ExecutorAction::SetCode {
+                new_code_hash: new_code.repr_hash().as_hex_string(),
+            },
+            OutAction::ReserveCurrency { mode, value } => ExecutorAction::ReserveCurrency {
+                mode,
+                value: value.grams.0.to_string(),
+            },
+            OutAction::ChangeLibrary { mode, code, hash } => ExecutorAction::ChangeLibrary {
+                mode,
+                hash: hash
+                    .or_else(|| code.map(|code| code.repr_hash()))
+                    .map(|hash| hash.as_hex_string()),
+            },
+            OutAction::CopyLeft { license, address } => ExecutorAction::CopyLeft {
+                license,
+                address: address.to_hex_string(),
+            },
+            OutAction::None => continue,
+        });
+    }
+    Ok(result)
+}
@@ -13,8 +13,12 @@
  */
 
 use super::types::ResolvedExecutionOptions;
+use crate::client::ClientContext;
 use crate::error::ClientResult;
 use crate::tvm::Error;
+use futures::FutureExt;
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
 use std::sync::Arc;
 use ton_block::{
     Account, CommonMsgInfo, ConfigParams, CurrencyCollection, Deserializable, Message,
@@ -36,11 +40,28 @@ pub(crate) fn call_tvm(
         .ok_or_else(|| Error::invalid_account_boc("Account has no code"))?;
     let addr = account
         .get_addr()
-        .ok_or_else(|| Error::invalid_account_boc("Account has no address"))?;
+        .ok_or_else(|| Error::invalid_account_boc("Account has no address"))?
+        .clone();
     let balance = account
         .balance()
-        .ok_or_else(|| Error::invalid_account_boc("Account has no balance"))?;
+        .ok_or_else(|| Error::invalid_account_boc("Account has no balance"))?
+        .clone();
+
+    let (engine, data) = call_tvm_code(code, data, &addr, &balance, options, stack)?;
+    account.set_data(data);
+    Ok(engine)
+}
 
+/// Runs the `code` with the `data` of the contract. Returns the engine and
+/// the committed contract data.
+pub(crate) fn call_tvm_code(
+    code: Cell,
+    data: Cell,
+    addr: &MsgAddressInt,
+    balance: &CurrencyCollection,
+    options: ResolvedExecutionOptions,
+    stack: Stack,
+) -> ClientResult<(ton_vm::executor::Engine, Cell)> {
     let mut ctrls = SaveList::new();
     ctrls
         .put(4, &mut StackItem::Cell(data))
@@ -59,7 +80,12 @@ pub(crate) fn call_tvm(
         .put(7, &mut sci.into_temp_data())
         .map_err(|err| Error::internal_error(format!("can not put SCI to registers: {}", err)))?;
 
-    let gas_limit = 1_000_000_000;
+    let gas_limit = i64::try_from(options.gas_limit).map_err(|_| {
+        crate::client::Error::invalid_params(
+            &json!({ "gas_limit": options.gas_limit }).to_string(),
+            format!("`gas_limit` must not exceed {}", i64::MAX),
+        )
+    })?;
     let gas = Gas::new(gas_limit, 0, gas_limit, 10);
 
     let mut engine = ton_vm::executor::Engine::new().setup(
@@ -73,6 +99,9 @@ pub(crate) fn call_tvm(
         Err(err) => {
             let exception = ton_vm::error::tvm_exception(err)
                 .map_err(|err| Error::unknown_execution_error(err))?;
+            if exception.exception_code() == Some(ton_types::ExceptionCode::OutOfGas) {
+                return Err(Error::gas_limit_exceeded(options.gas_limit));
+            }
             let code = if let Some(code) = exception.custom_code() {
                 code
             } else {
@@ -92,15 +121,85 @@ pub(crate) fn call_tvm(
             ))
         }
         Ok(_) => match engine.get_committed_state().get_root() {
-            StackItem::Cell(data) => {
-                account.set_data(data);
-                Ok(engine)
-            }
+            StackItem::Cell(data) => Ok((engine, data)),
             _ => Err(Error::internal_error("invalid committed state")),
         },
     }
 }
 
+/// Maximum number of the timed out executions which are still running on their threads
+const MAX_ABANDONED_EXECUTIONS: usize = 4;
+
+static ABANDONED_EXECUTIONS: AtomicUsize = AtomicUsize::new(0);
+
+const EXECUTION_RUNNING: u8 = 0;
+const EXECUTION_FINISHED: u8 = 1;
+const EXECUTION_ABANDONED: u8 = 2;
+
+/// Runs TVM execution bounded by the wall-clock `timeout`.
+///
+/// If timeout is specified the execution is performed on a separate thread, so the caller
+/// receives `ExecutionLimitExceeded` error when the timeout expires instead of waiting for
+/// a runaway execution.
+///
+/// Threads can't be cancelled, so the abandoned execution keeps running detached until
+/// it finishes or exhausts its gas limit (executor also stops on the compute phase
+/// gas limit of the blockchain config). At most `MAX_ABANDONED_EXECUTIONS` abandoned
+/// executions may run at the same time: executions with timeout are rejected with
+/// `ExecutionLimitExceeded` error until some of them finish.
+pub(crate) async fn run_with_timeout<R: Send + 'static>(
+    context: &ClientContext,
+    timeout: Option<u32>,
+    execute: impl FnOnce() -> ClientResult<R> + Send + 'static,
+) -> ClientResult<R> {
+    let timeout = match timeout {
+        Some(timeout) if cfg!(not(feature = "wasm")) => timeout,
+        _ => return execute(),
+    };
+    if ABANDONED_EXECUTIONS.load(Ordering::SeqCst) >= MAX_ABANDONED_EXECUTIONS {
+        return Err(Error::too_many_abandoned_executions(MAX_ABANDONED_EXECUTIONS));
+    }
+
+    let state = Arc::new(AtomicU8::new(EXECUTION_RUNNING));
+    let thread_state = state.clone();
+    let (sender, receiver) = futures::channel::oneshot::channel();
+    std::thread::spawn(move || {
+        let result = execute();
+        let finished = thread_state.compare_exchange(
+            EXECUTION_RUNNING,
+            EXECUTION_FINISHED,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        );
+        if finished.is_err() {
+            ABANDONED_EXECUTIONS.fetch_sub(1, Ordering::SeqCst);
+        }
+        let _ = sender.send(result);
+    });
+
+    let result = receiver.fuse();
+    let timer = context.env.set_timer(timeout as u64).fuse();
+    futures::pin_mut!(result, timer);
+    futures::select! {
+        result = result => result
+            .map_err(|_| Error::internal_error("TVM execution thread terminated"))?,
+        _ = timer => {
+            // Counted before the state change, so the thread never decrements it first
+            ABANDONED_EXECUTIONS.fetch_add(1, Ordering::SeqCst);
+            let abandoned = state.compare_exchange(
+                EXECUTION_RUNNING,
+                EXECUTION_ABANDONED,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            );
+            if abandoned.is_err() {
+                ABANDONED_EXECUTIONS.fetch_sub(1, Ordering::SeqCst);
+            }
+            Err(Error::timeout_exceeded(timeout))
+        }
+    }
+}
+
 pub(crate) fn call_tvm_msg(
     account: &mut Account,
     options: ResolvedExecutionOptions,
@@ -16,7 +16,7 @@ use crate::error::ClientError;
 use serde_json::Value;
 use std::fmt::Display;
 use ton_block::{AccStatusChange, ComputeSkipReason, MsgAddressInt};
-use ton_types::{ExceptionCode, Cell};
+use ton_types::{ExceptionCode, Cell, UInt256};
 
 #[derive(ApiType)]
 pub enum ErrorCode {
@@ -34,6 +34,10 @@ pub enum ErrorCode {
     InvalidAccountBoc = 412,
     InvalidMessageType = 413,
     ContractExecutionError = 414,
+    InvalidGetterOutput = 415,
+    ExecutionLimitExceeded = 416,
+    AccountSnapshotNotFound = 417,
+    ActionListMismatch = 418,
 }
 pub struct Error;
 
@@ -143,6 +147,12 @@ impl Error {
             }
         }
 
+        // Exit arg is either a string cell (e.g. `require` message) or an integer
+        let contract_error = exit_arg.as_ref().and_then(Self::read_error_message);
+        let exit_arg = match &contract_error {
+            Some(error_message) => Some(Value::String(error_message.clone())),
+            None => exit_arg.map(Self::decode_integer_exit_arg),
+        };
         error.data["phase"] = "computeVm".into();
         error.data["exit_code"] = exit_code.into();
         error.data["exit_arg"] = serde_json::json!(exit_arg);
@@ -169,10 +179,12 @@ impl Error {
                 error.message.push_str(". ");
                 error.message.push_str(tip);
             }
-        } else if let Some(ref exit_arg) = exit_arg {
-            if let Some(error_message) = Self::read_error_message(exit_arg) {
-                error.message.push_str(&format!(", contract error: \"{}\"", error_message));
-                error.data["contract_error"] = error_message.into();
+        } else if let Some(error_message) = contract_error {
+            error.message.push_str(&format!(", contract error: \"{}\"", error_message));
+            error.data["contract_error"] = error_message.into();
+        } else if let Some(Value::Number(exit_arg)) = &exit_arg {
+            if exit_arg.as_i64() != Some(0) {
+                error.message.push_str(&format!(", exit arg: {}", exit_arg));
             }
         }
 
@@ -298,13 +310,90 @@ impl Error {
         )
     }
 
+    pub fn gas_limit_exceeded(gas_limit: u64) -> ClientError {
+        let mut error = error(
+            ErrorCode::ExecutionLimitExceeded,
+            format!("TVM execution exceeded gas limit {}", gas_limit),
+        );
+        error.data = serde_json::json!({
+            "gas_limit": gas_limit,
+        });
+        error
+    }
+
+    pub fn action_list_mismatch(restored_hash: &UInt256, expected_hash: &UInt256) -> ClientError {
+        let mut error = error(
+            ErrorCode::ActionListMismatch,
+            format!(
+                "Can not restore the action list: hash of the restored list ({}) mismatches \
+                    the action list hash of the transaction ({})",
+                restored_hash.as_hex_string(),
+                expected_hash.as_hex_string(),
+            ),
+        );
+        error.data = serde_json::json!({
+            "restored_hash": restored_hash.as_hex_string(),
+            "expected_hash": expected_hash.as_hex_string(),
+        });
+        error
+    }
+
+    pub fn timeout_exceeded(timeout: u32) -> ClientError {
+        let mut error = error(
+            ErrorCode::ExecutionLimitExceeded,
+            format!("TVM execution exceeded timeout {} ms", timeout),
+        );
+        error.data = serde_json::json!({
+            "timeout": timeout,
+        });
+        error
+    }
+
+    pub fn too_many_abandoned_executions(limit: usize) -> ClientError {
+        let mut error = error(
+            ErrorCode::ExecutionLimitExceeded,
+            format!(
+                "{} timed out TVM executions are still running, retry after they finish",
+                limit
+            ),
+        );
+        error.data = serde_json::json!({
+            "abandoned_executions": limit,
+        });
+        error
+    }
+
+    pub fn invalid_getter_output<E: Display>(err: E) -> ClientError {
+        error(
+            ErrorCode::InvalidGetterOutput,
+            format!("Invalid paginated getter output: {}", err),
+        )
+    }
+
+    pub fn account_snapshot_not_found(handle: u32) -> ClientError {
+        error(
+            ErrorCode::AccountSnapshotNotFound,
+            format!("Account snapshot is not found or released. Handle {}", handle),
+        )
+    }
+
     fn read_error_message(exit_arg: &Value) -> Option<String> {
         let cell = match Self::extract_cell(exit_arg) {
             Some(cell) => cell,
             None => return None,
         };
 
-        String::from_utf8(Self::load_boc_data(&cell)).ok()
+        String::from_utf8(Self::load_boc_data(&cell))
+            .ok()
+            .filter(|message| !message.is_empty())
+    }
+
+    /// Integer stack items are serialized as strings, small ones are converted to numbers
+    fn decode_integer_exit_arg(exit_arg: Value) -> Value {
+        match exit_arg.as_str().and_then(|value| value.parse::<i64>().ok()) {
+            Some(value) => value.into(),
+            None => exit_arg,
+        }
     }
 
     fn extract_cell(exit_arg: &Value) -> Option<Cell> {
@@ -12,17 +12,26 @@
  *
  */
 
+pub(crate) mod accounts_cache;
+pub(crate) mod actions;
 pub(crate) mod call_tvm;
 pub(crate) mod check_transaction;
 mod errors;
 pub(crate) mod run_get;
 pub(crate) mod run_message;
+pub(crate) mod run_paginated;
+pub(crate) mod simulation;
+pub(crate) mod snapshots;
+pub(crate) mod state_diff;
+#[cfg(feature = "proofs")]
+pub(crate) mod system_getter;
 pub(crate) mod types;
 
 mod stack;
 #[cfg(test)]
 mod tests;
 
+pub use actions::ExecutorAction;
 pub use errors::{Error, ErrorCode, StdContractError};
 pub use run_get::{run_get, ParamsOfRunGet, ResultOfRunGet};
 pub use run_message::{
@@ -30,5 +39,23 @@ pub use run_message::{
     ResultOfRunExecutor, ResultOfRunTvm,
 };
 pub(crate) use run_message::run_executor_internal;
+pub use run_paginated::{
+    run_tvm_paginated, PageFetchedEvent, PaginationResponseType, ParamsOfRunTvmPaginated,
+    ResultOfRunTvmPaginated,
+};
+pub use simulation::{
+    simulate_and_summarize, BalanceChange, DeployedContract, ParamsOfSimulateAndSummarize,
+    ResultOfSimulateAndSummarize, TokenApproval, TokenTransfer,
+};
+pub use snapshots::{
+    fork, get_snapshot_account, release_snapshot, snapshot_account, AccountSnapshotHandle,
+    ParamsOfGetSnapshotAccount, ParamsOfSnapshotAccount, RegisteredAccountSnapshot,
+    ResultOfGetSnapshotAccount,
+};
+pub use state_diff::AccountStateDiff;
+#[cfg(feature = "proofs")]
+pub use system_getter::{
+    run_system_getter, ParamsOfRunSystemGetter, ResultOfRunSystemGetter, SystemContract,
+};
 pub use ton_sdk::TransactionFees;
 pub use types::ExecutionOptions;
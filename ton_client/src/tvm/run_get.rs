@@ -18,6 +18,7 @@ use super::types::{ExecutionOptions, ResolvedExecutionOptions};
 use crate::boc::internal::deserialize_object_from_boc;
 use crate::client::ClientContext;
 use crate::error::ClientResult;
+use crate::tvm::accounts_cache::resolve_account_boc;
 use crate::tvm::Error;
 use std::sync::Arc;
 use ton_vm::stack::integer::IntegerData;
@@ -39,6 +40,9 @@ pub struct ParamsOfRunGet {
     /// set this flag to true.
     /// This may happen, for example, when elector contract contains too many participants
     pub tuple_list_as_array: Option<bool>,
+    /// Account address. If `account` is empty, the account state is loaded from
+    /// the network by this address, using the accounts cache if it is enabled.
+    pub address: Option<String>,
 }
 
 #[allow(non_snake_case)]
@@ -58,8 +62,10 @@ pub async fn run_get(
     context: std::sync::Arc<ClientContext>,
     params: ParamsOfRunGet,
 ) -> ClientResult<ResultOfRunGet> {
+    let account_boc =
+        resolve_account_boc(&context, &params.account, params.address.as_ref()).await?;
     let mut account: ton_block::Account =
-        deserialize_object_from_boc(&context, &params.account, "account").await?.object;
+        deserialize_object_from_boc(&context, &account_boc, "account").await?.object;
     let options = ResolvedExecutionOptions::from_options(&context, params.execution_options).await?;
 
     if account.is_none() {
@@ -84,11 +90,11 @@ pub async fn run_get(
         function_id,
     ))));
 
-    let engine = super::call_tvm::call_tvm(&mut account, options, stack_in)?;
-    Ok(ResultOfRunGet {
-        output: stack::serialize_items(
-            Box::new(engine.stack().iter()),
-            params.tuple_list_as_array.unwrap_or_default(),
-        )?,
-    })
+    let timeout = options.timeout;
+    let tuple_list_as_array = params.tuple_list_as_array.unwrap_or_default();
+    let output = super::call_tvm::run_with_timeout(&context, timeout, move || {
+        let engine = super::call_tvm::call_tvm(&mut account, options, stack_in)?;
+        stack::serialize_items(Box::new(engine.stack().iter()), tuple_list_as_array)
+    }).await?;
+    Ok(ResultOfRunGet { output })
 }
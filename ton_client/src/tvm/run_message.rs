@@ -21,9 +21,14 @@ use crate::boc::internal::{
     serialize_object_to_cell
 };
 use crate::client::ClientContext;
+use crate::encoding::account_decode;
 use crate::error::ClientResult;
 use crate::processing::{parsing::decode_output, DecodedOutput};
 use crate::tvm::{check_transaction::calc_transaction_fees, Error};
+use crate::tvm::accounts_cache::{fetch_account_boc, resolve_account_boc};
+use crate::tvm::snapshots::{get_snapshot, update_snapshot, AccountSnapshotHandle};
+use crate::tvm::actions::{decode_executor_actions, ExecutorAction};
+use crate::tvm::state_diff::{calc_account_state_diff, AccountStateDiff};
 use serde_json::Value;
 use std::convert::TryFrom;
 use std::sync::{atomic::AtomicU64, Arc};
@@ -49,6 +54,28 @@ pub enum AccountForExecutor {
         /// transaction fees without balance check
         unlimited_balance: Option<bool>,
     },
+    /// Account state loaded from the network by address.
+    ///
+    /// The state is taken from the accounts cache if it is enabled with
+    /// `accounts_cache_size` of the network config.
+    Address {
+        /// Account address.
+        address: String,
+        /// Flag for running account with the unlimited balance. Can be used to calculate
+        /// transaction fees without balance check
+        unlimited_balance: Option<bool>,
+    },
+    /// Account state registered with `tvm.snapshot_account` or `tvm.fork`.
+    ///
+    /// If the execution succeeds, the snapshot state is replaced with the resulting
+    /// account state. Fork the snapshot to keep the current state.
+    Snapshot {
+        /// Handle of the account snapshot.
+        snapshot: AccountSnapshotHandle,
+        /// Flag for running account with the unlimited balance. Can be used to calculate
+        /// transaction fees without balance check. Original balance is kept in the snapshot
+        unlimited_balance: Option<bool>,
+    },
 }
 
 impl Default for AccountForExecutor {
@@ -79,29 +106,59 @@ impl AccountForExecutor {
             AccountForExecutor::Account {
                 boc,
                 unlimited_balance,
+            } => Self::get_account_from_boc(context, boc, *unlimited_balance).await,
+            AccountForExecutor::Address {
+                address,
+                unlimited_balance,
             } => {
+                let boc = fetch_account_boc(context, &account_decode(address)?).await?;
+                Self::get_account_from_boc(context, &boc, *unlimited_balance).await
+            }
+            AccountForExecutor::Snapshot {
+                snapshot,
+                unlimited_balance,
+            } => {
+                let account = get_snapshot(context, snapshot)?;
                 if unlimited_balance.unwrap_or_default() {
-                    let mut account: Account =
-                        deserialize_object_from_boc(context, &boc, "account").await?.object;
-                    let original_balance = account
-                        .balance()
-                        .ok_or_else(|| Error::invalid_account_boc(
-                            "can not set unlimited balance for non existed account",
-                        ))?
-                        .clone();
-                    let mut balance = original_balance.clone();
-                    balance.grams = UNLIMITED_BALANCE.into();
-                    account.set_balance(balance);
-                    let account = serialize_object_to_cell(&account, "account")?;
-                    Ok((account, Some(original_balance)))
+                    Self::set_unlimited_balance(deserialize_object_from_cell(account, "account")?)
                 } else {
-                    let (_, account) = deserialize_cell_from_boc(context, &boc, "account").await?;
                     Ok((account, None))
                 }
             }
         }
     }
 
+    async fn get_account_from_boc(
+        context: &Arc<ClientContext>,
+        boc: &str,
+        unlimited_balance: Option<bool>,
+    ) -> ClientResult<(Cell, Option<CurrencyCollection>)> {
+        if unlimited_balance.unwrap_or_default() {
+            Self::set_unlimited_balance(
+                deserialize_object_from_boc(context, &boc, "account").await?.object,
+            )
+        } else {
+            let (_, account) = deserialize_cell_from_boc(context, &boc, "account").await?;
+            Ok((account, None))
+        }
+    }
+
+    fn set_unlimited_balance(
+        mut account: Account,
+    ) -> ClientResult<(Cell, Option<CurrencyCollection>)> {
+        let original_balance = account
+            .balance()
+            .ok_or_else(|| Error::invalid_account_boc(
+                "can not set unlimited balance for non existed account",
+            ))?
+            .clone();
+        let mut balance = original_balance.clone();
+        balance.grams = UNLIMITED_BALANCE.into();
+        account.set_balance(balance);
+        let account = serialize_object_to_cell(&account, "account")?;
+        Ok((account, Some(original_balance)))
+    }
+
     pub fn restore_balance_if_needed(
         account: Cell,
         balance: Option<CurrencyCollection>,
@@ -132,6 +189,15 @@ pub struct ParamsOfRunExecutor {
     pub boc_cache: Option<BocCacheType>,
     /// Return updated account flag. Empty string is returned if the flag is `false`
     pub return_updated_account: Option<bool>,
+    /// Return the difference between the input and the output account state.
+    /// `state_diff` is `None` in the result if the flag is `false`
+    pub return_state_diff: Option<bool>,
+    /// Return the decoded action list of the compute phase.
+    /// `actions` is `None` in the result if the flag is `false`.
+    ///
+    /// The action list is restored by running the compute phase once more, so the
+    /// execution takes about twice as long with this flag.
+    pub return_actions: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, ApiType, Clone, Default)]
@@ -147,7 +213,10 @@ pub struct ParamsOfRunTvm {
     /// Cache type to put the result. The BOC itself returned if no cache type provided
     pub boc_cache: Option<BocCacheType>,
     /// Return updated account flag. Empty string is returned if the flag is `false`
-    pub return_updated_account: Option<bool>
+    pub return_updated_account: Option<bool>,
+    /// Account address. If `account` is empty, the account state is loaded from
+    /// the network by this address, using the accounts cache if it is enabled.
+    pub address: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, ApiType, Default, Debug, PartialEq, Clone)]
@@ -171,6 +240,14 @@ pub struct ResultOfRunExecutor {
 
     /// Transaction fees
     pub fees: TransactionFees,
+
+    /// Difference between the input and the output account state.
+    /// Returned only if `return_state_diff` flag is set
+    pub state_diff: Option<AccountStateDiff>,
+
+    /// Actions committed by the compute phase in the order of their execution.
+    /// Returned only if `return_actions` flag is set
+    pub actions: Option<Vec<ExecutorAction>>,
 }
 
 #[derive(Serialize, Deserialize, ApiType, Default, Debug, PartialEq, Clone)]
@@ -221,6 +298,8 @@ async fn parse_transaction(
 ///
 /// One more use case - you can produce the sequence of operations,
 /// thus emulating the sequential contract calls locally.
+/// Use `AccountForExecutor::Snapshot` with `tvm.snapshot_account` and `tvm.fork`
+/// to run the alternative sequences from the same account state.
 /// And so on.
 ///
 /// Transaction executor requires account BOC (bag of cells) as a parameter.
@@ -254,7 +333,8 @@ pub async fn run_executor_internal(
 ) -> ClientResult<ResultOfRunExecutor> {
     let message = deserialize_object_from_boc::<Message>(&context, &params.message, "message").await?.object;
     let msg_address = message.dst_ref().ok_or_else(|| Error::invalid_message_type())?.clone();
-    let (account, _) = params.account.get_account(&context, msg_address.clone()).await?;
+    let (account, original_balance) =
+        params.account.get_account(&context, msg_address.clone()).await?;
     let options = ResolvedExecutionOptions::from_options(&context, params.execution_options).await?;
 
     let account_copy = account.clone();
@@ -267,6 +347,22 @@ pub async fn run_executor_internal(
         }
     };
 
+    let state_diff_input = if params.return_state_diff.unwrap_or_default() {
+        Some(deserialize_object_from_cell::<Account>(account.clone(), "account")?)
+    } else {
+        None
+    };
+
+    let actions_input = if params.return_actions.unwrap_or_default() {
+        Some((
+            deserialize_object_from_cell::<Account>(account.clone(), "account")?,
+            message.clone(),
+            options.clone(),
+        ))
+    } else {
+        None
+    };
+
     let (transaction, modified_account) =
         call_executor(
             account.clone(),
@@ -289,12 +385,14 @@ pub async fn run_executor_internal(
     .await?;
 
     let mut out_messages = vec![];
+    let mut out_message_objects = vec![];
     for i in 0..transaction.outmsg_cnt {
         let message = transaction
             .get_out_msg(i)
             .map_err(|err| Error::can_not_read_transaction(err))?
             .ok_or_else(|| Error::can_not_read_transaction("message missing"))?;
         out_messages.push(serialize_object_to_base64(&message, "message")?);
+        out_message_objects.push(message);
     }
 
     // TODO decode Message object without converting to string
@@ -304,6 +402,39 @@ pub async fn run_executor_internal(
         None
     };
 
+    let state_diff = if let Some(input_account) = state_diff_input {
+        let output_account =
+            deserialize_object_from_cell::<Account>(modified_account.clone(), "account")?;
+        Some(calc_account_state_diff(&input_account, &output_account)?)
+    } else {
+        None
+    };
+
+    let actions = if let Some((input_account, message, options)) = actions_input {
+        Some(
+            decode_executor_actions(
+                &context,
+                &input_account,
+                &message,
+                options,
+                &transaction,
+                &out_message_objects,
+                params.abi.as_ref(),
+            )
+            .await?,
+        )
+    } else {
+        None
+    };
+
+    if let AccountForExecutor::Snapshot { snapshot, .. } = &params.account {
+        let account = AccountForExecutor::restore_balance_if_needed(
+            modified_account.clone(),
+            original_balance,
+        )?;
+        update_snapshot(&context, snapshot, account)?;
+    }
+
     let account = if params.return_updated_account.unwrap_or_default() {
         serialize_cell_to_boc(&context, modified_account, "account", params.boc_cache).await?
     } else {
@@ -316,6 +447,8 @@ pub async fn run_executor_internal(
         account,
         decoded,
         fees,
+        state_diff,
+        actions,
     })
 }
 
@@ -339,14 +472,22 @@ pub async fn run_tvm(
     context: std::sync::Arc<ClientContext>,
     params: ParamsOfRunTvm,
 ) -> ClientResult<ResultOfRunTvm> {
-    let mut account = deserialize_object_from_boc::<Account>(&context, &params.account, "account").await?;
+    let account_boc =
+        resolve_account_boc(&context, &params.account, params.address.as_ref()).await?;
+    let mut account = deserialize_object_from_boc::<Account>(&context, &account_boc, "account").await?;
     let message = deserialize_object_from_boc::<Message>(&context, &params.message, "message").await?.object;
     let options = ResolvedExecutionOptions::from_options(&context, params.execution_options).await?;
     if account.object.is_none() {
         return Err(Error::invalid_account_boc("Account is None"))
     }
 
-    let messages = super::call_tvm::call_tvm_msg(&mut account.object, options, &message)?;
+    let timeout = options.timeout;
+    let mut account_object = std::mem::take(&mut account.object);
+    let (account_object, messages) = super::call_tvm::run_with_timeout(&context, timeout, move || {
+        let messages = super::call_tvm::call_tvm_msg(&mut account_object, options, &message)?;
+        Ok((account_object, messages))
+    }).await?;
+    account.object = account_object;
 
     let mut out_messages = vec![];
     for message in messages {
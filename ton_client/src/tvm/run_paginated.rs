@@ -0,0 +1,192 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use super::run_message::{run_tvm, ParamsOfRunTvm};
+use super::types::ExecutionOptions;
+use crate::abi::encode_message::encode_message_internal;
+use crate::abi::{Abi, CallSet, ParamsOfEncodeMessage, Signer};
+use crate::boc::internal::deserialize_object_from_boc;
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use crate::tvm::Error;
+use futures::Future;
+use serde_json::Value;
+use std::sync::Arc;
+
+#[derive(Clone, num_derive::FromPrimitive, PartialEq, Debug)]
+pub enum PaginationResponseType {
+    PageFetched = 100,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone)]
+pub struct PageFetchedEvent {
+    /// Zero-based index of the fetched page.
+    pub page: u32,
+    /// Number of items in the fetched page.
+    pub items_count: u32,
+    /// Total number of items fetched so far.
+    pub total_items_count: u32,
+    /// Cursor which will be passed to the next getter call. `None` if there are no more pages.
+    pub next_cursor: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ParamsOfRunTvmPaginated {
+    /// Account BOC. Must be encoded as base64.
+    pub account: String,
+    /// Contract ABI.
+    pub abi: Abi,
+    /// Name of the paginated getter function.
+    pub function_name: String,
+    /// Getter input parameters except the cursor one.
+    pub input: Option<Value>,
+    /// Name of the getter input parameter which receives the cursor (e.g. `startKey`).
+    pub cursor_param: String,
+    /// Cursor value for the first call.
+    pub initial_cursor: Value,
+    /// Name of the getter output field with the page items. The field must be an array
+    /// or a mapping (JSON object).
+    pub items_output: String,
+    /// Name of the getter output field with the cursor for the next call. The iteration stops
+    /// when this field is `null`, absent or doesn't change.
+    pub next_cursor_output: String,
+    /// Maximum number of getter calls. Default is unlimited.
+    pub max_pages: Option<u32>,
+    /// Execution options.
+    pub execution_options: Option<ExecutionOptions>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, Clone)]
+pub struct ResultOfRunTvmPaginated {
+    /// Merged items of all fetched pages. Array if the getter returns arrays, JSON object
+    /// if the getter returns mappings.
+    pub items: Value,
+    /// Number of getter calls performed.
+    pub pages: u32,
+    /// Cursor for the next page if the iteration was stopped by `max_pages` limit.
+    pub next_cursor: Option<Value>,
+}
+
+pub(crate) fn merge_items(items: &mut Value, page: Value) -> ClientResult<u32> {
+    match (items, page) {
+        (Value::Array(items), Value::Array(page)) => {
+            let count = page.len() as u32;
+            items.extend(page);
+            Ok(count)
+        }
+        (Value::Object(items), Value::Object(page)) => {
+            let count = page.len() as u32;
+            items.extend(page);
+            Ok(count)
+        }
+        (items, page) if items.is_null() => {
+            let count = match &page {
+                Value::Array(page) => page.len() as u32,
+                Value::Object(page) => page.len() as u32,
+                Value::Null => 0,
+                _ => return Err(Error::invalid_getter_output(
+                    "page items must be an array or a mapping",
+                )),
+            };
+            *items = page;
+            Ok(count)
+        }
+        (_, Value::Null) => Ok(0),
+        _ => Err(Error::invalid_getter_output(
+            "page items have different type than the items of the previous pages",
+        )),
+    }
+}
+
+pub async fn run_tvm_paginated<F: Future<Output = ()> + Send>(
+    context: Arc<ClientContext>,
+    params: ParamsOfRunTvmPaginated,
+    callback: impl Fn(PageFetchedEvent) -> F + Send + Sync,
+) -> ClientResult<ResultOfRunTvmPaginated> {
+    let account: ton_block::Account =
+        deserialize_object_from_boc(&context, &params.account, "account").await?.object;
+    let address = account
+        .get_addr()
+        .ok_or_else(|| Error::invalid_account_boc("Account is None"))?
+        .to_string();
+
+    let mut input = match params.input.clone().unwrap_or_else(|| json!({})) {
+        Value::Object(input) => input,
+        _ => return Err(Error::invalid_getter_output("getter input must be a JSON object")),
+    };
+
+    let mut result = ResultOfRunTvmPaginated {
+        items: Value::Null,
+        pages: 0,
+        next_cursor: Some(params.initial_cursor.clone()),
+    };
+    let mut total_items_count = 0;
+
+    while let Some(cursor) = result.next_cursor.take() {
+        if params.max_pages.map(|max| result.pages >= max).unwrap_or_default() {
+            result.next_cursor = Some(cursor);
+            break;
+        }
+
+        input.insert(params.cursor_param.clone(), cursor.clone());
+        let message = encode_message_internal(
+            context.clone(),
+            ParamsOfEncodeMessage {
+                abi: params.abi.clone(),
+                address: Some(address.clone()),
+                call_set: Some(CallSet {
+                    function_name: params.function_name.clone(),
+                    header: None,
+                    input: Some(Value::Object(input.clone())),
+                }),
+                signer: Signer::None,
+                ..Default::default()
+            },
+        ).await?.message;
+
+        let mut output = run_tvm(
+            context.clone(),
+            ParamsOfRunTvm {
+                message,
+                account: params.account.clone(),
+                abi: Some(params.abi.clone()),
+                execution_options: params.execution_options.clone(),
+                ..Default::default()
+            },
+        ).await?
+            .decoded
+            .and_then(|decoded| decoded.output)
+            .ok_or_else(|| Error::invalid_getter_output("getter returned no output"))?;
+
+        let items_count = merge_items(&mut result.items, output[&params.items_output].take())?;
+        total_items_count += items_count;
+        result.pages += 1;
+
+        let next_cursor = output[&params.next_cursor_output].take();
+        result.next_cursor = if next_cursor.is_null() || next_cursor == cursor || items_count == 0 {
+            None
+        } else {
+            Some(next_cursor)
+        };
+
+        callback(PageFetchedEvent {
+            page: result.pages - 1,
+            items_count,
+            total_items_count,
+            next_cursor: result.next_cursor.clone(),
+        }).await;
+    }
+
+    Ok(result)
+}
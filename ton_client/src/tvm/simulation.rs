@@ -0,0 +1,315 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use super::types::ExecutionOptions;
+use crate::abi::{decode_message, Abi, DecodedMessageBody, MessageBodyType, ParamsOfDecodeMessage};
+use crate::boc::internal::deserialize_object_from_boc;
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use crate::tvm::{run_executor_internal, AccountForExecutor, ParamsOfRunExecutor};
+use serde_json::Value;
+use std::sync::Arc;
+use ton_block::Message;
+use ton_sdk::TransactionFees;
+
+const AMOUNT_FIELDS: &[&str] = &["amount", "tokens", "value", "_value", "_amount"];
+const RECIPIENT_FIELDS: &[&str] = &[
+    "to",
+    "_to",
+    "recipient",
+    "recipientTokenWallet",
+    "recipient_address",
+    "dest",
+    "destination",
+];
+const SPENDER_FIELDS: &[&str] = &["spender", "_spender", "to", "_to"];
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ParamsOfSimulateAndSummarize {
+    /// Input message BOC. Must be encoded as base64.
+    pub message: String,
+    /// Account to run on executor
+    pub account: AccountForExecutor,
+    /// Execution options.
+    pub execution_options: Option<ExecutionOptions>,
+    /// List of contract ABIs that will be used to decode message bodies.
+    /// Library will try to decode each message body using any ABI from the registry.
+    /// Token transfers and approvals are recognized only in the decoded bodies.
+    pub abi_registry: Option<Vec<Abi>>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, PartialEq, Clone)]
+pub struct BalanceChange {
+    /// Account address.
+    pub address: String,
+    /// Balance change in nanotokens. Encoded as a decimal string, negative if
+    /// the balance decreases.
+    pub delta: String,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, PartialEq, Clone)]
+pub struct TokenTransfer {
+    /// Address of the contract which received the transfer call (e.g. token wallet).
+    pub token_contract: String,
+    /// Name of the called function.
+    pub function_name: String,
+    /// Address of the transfer initiator.
+    pub from: String,
+    /// Transfer recipient. `None` if the function has no recognized recipient parameter.
+    pub to: Option<String>,
+    /// Transferred amount as specified in the call.
+    /// `None` if the function has no recognized amount parameter.
+    pub amount: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, PartialEq, Clone)]
+pub struct TokenApproval {
+    /// Address of the contract which received the approval call.
+    pub token_contract: String,
+    /// Name of the called function.
+    pub function_name: String,
+    /// Address of the tokens owner.
+    pub owner: String,
+    /// Approved spender. `None` if the function has no recognized spender parameter.
+    pub spender: Option<String>,
+    /// Approved amount as specified in the call.
+    /// `None` if the function has no recognized amount parameter.
+    pub amount: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, PartialEq, Clone)]
+pub struct DeployedContract {
+    /// Address of the deployed contract.
+    pub address: String,
+    /// Hash of the contract code. `None` if the state init has no code.
+    pub code_hash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, PartialEq, Clone)]
+pub struct ResultOfSimulateAndSummarize {
+    /// Address of the account the message was simulated on.
+    pub address: String,
+
+    /// `true` if the transaction is aborted. All the effects except of
+    /// the fees will not happen in this case.
+    pub aborted: bool,
+
+    /// Exit code of the compute phase. `None` if the compute phase was skipped.
+    pub exit_code: Option<i32>,
+
+    /// Transaction fees.
+    pub fees: TransactionFees,
+
+    /// Balance changes of the simulated account and of the recipients of
+    /// its outbound internal messages.
+    ///
+    /// Recipients' changes are the values of the messages they receive: their own
+    /// transactions are not simulated.
+    pub balance_changes: Vec<BalanceChange>,
+
+    /// Token transfers recognized in the decoded message bodies.
+    pub token_transfers: Vec<TokenTransfer>,
+
+    /// Token approvals recognized in the decoded message bodies.
+    pub approvals: Vec<TokenApproval>,
+
+    /// Contracts deployed by the transaction: the simulated account itself and
+    /// the destinations of outbound messages carrying the state init.
+    pub deployed_contracts: Vec<DeployedContract>,
+
+    /// Output messages with bodies decoded with `abi_registry`, in the order
+    /// of the transaction out messages. `None` if the body was not decoded.
+    pub decoded_out_messages: Vec<Option<DecodedMessageBody>>,
+}
+
+fn add_balance_change(changes: &mut Vec<BalanceChange>, address: String, delta: i128) {
+    if let Some(change) = changes.iter_mut().find(|change| change.address == address) {
+        let current: i128 = change.delta.parse().unwrap_or_default();
+        change.delta = (current + delta).to_string();
+    } else {
+        changes.push(BalanceChange {
+            address,
+            delta: delta.to_string(),
+        });
+    }
+}
+
+fn find_field(value: &Value, names: &[&str]) -> Option<String> {
+    names.iter().find_map(|name| match &value[*name] {
+        Value::String(string) => Some(string.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    })
+}
+
+async fn try_decode(
+    context: &Arc<ClientContext>,
+    abi_registry: &[Abi],
+    message: &str,
+) -> Option<DecodedMessageBody> {
+    for abi in abi_registry {
+        if let Ok(decoded) = decode_message(
+            context.clone(),
+            ParamsOfDecodeMessage {
+                abi: abi.clone(),
+                message: message.to_string(),
+                partial: None,
+            },
+        )
+        .await
+        {
+            return Some(decoded);
+        }
+    }
+    None
+}
+
+fn summarize_call(
+    result: &mut ResultOfSimulateAndSummarize,
+    decoded: &DecodedMessageBody,
+    token_contract: String,
+    sender: String,
+) {
+    if decoded.body_type != MessageBodyType::Input {
+        return;
+    }
+    let name = decoded.name.to_lowercase();
+    let params = decoded.value.clone().unwrap_or_default();
+    if name.contains("approve") {
+        result.approvals.push(TokenApproval {
+            token_contract,
+            function_name: decoded.name.clone(),
+            owner: sender,
+            spender: find_field(&params, SPENDER_FIELDS),
+            amount: find_field(&params, AMOUNT_FIELDS),
+        });
+    } else if name.contains("transfer") {
+        result.token_transfers.push(TokenTransfer {
+            token_contract,
+            function_name: decoded.name.clone(),
+            from: sender,
+            to: find_field(&params, RECIPIENT_FIELDS),
+            amount: find_field(&params, AMOUNT_FIELDS),
+        });
+    }
+}
+
+/// Simulates the message processing and summarizes its effects.
+///
+/// Runs the message on the executor as `run_executor` does and produces
+/// the summary suitable for a wallet confirmation screen: which balances
+/// will change, which tokens will be transferred or approved and which contracts
+/// will be deployed.
+///
+/// Token transfers and approvals are recognized by the names of the called functions
+/// (containing `transfer` or `approve`) in the message bodies decoded with `abi_registry`.
+/// Only the simulated transaction is executed, so the effects of the outbound messages
+/// on their recipients are described by the messages themselves.
+///
+/// Aborted transaction is returned as a summary with `aborted` flag set, not as an error.
+#[api_function]
+pub async fn simulate_and_summarize(
+    context: Arc<ClientContext>,
+    params: ParamsOfSimulateAndSummarize,
+) -> ClientResult<ResultOfSimulateAndSummarize> {
+    let abi_registry = params.abi_registry.unwrap_or_default();
+    let message =
+        deserialize_object_from_boc::<Message>(&context, &params.message, "message").await?.object;
+
+    let executed = run_executor_internal(
+        context.clone(),
+        ParamsOfRunExecutor {
+            message: params.message.clone(),
+            account: params.account,
+            execution_options: params.execution_options,
+            skip_transaction_check: Some(true),
+            return_state_diff: Some(true),
+            ..Default::default()
+        },
+        false,
+    )
+    .await?;
+
+    let address = executed.transaction["account_addr"]
+        .as_str()
+        .map(|address| address.to_string())
+        .or_else(|| message.dst_ref().map(|address| address.to_string()))
+        .unwrap_or_default();
+    let aborted = executed.transaction["aborted"].as_bool().unwrap_or_default();
+
+    let mut result = ResultOfSimulateAndSummarize {
+        address: address.clone(),
+        aborted,
+        exit_code: executed.transaction["compute"]["exit_code"]
+            .as_i64()
+            .map(|code| code as i32),
+        fees: executed.fees,
+        ..Default::default()
+    };
+
+    let state_diff = executed.state_diff.unwrap_or_default();
+    add_balance_change(
+        &mut result.balance_changes,
+        address.clone(),
+        state_diff.balance_delta.parse().unwrap_or_default(),
+    );
+    if state_diff.old_code_hash.is_none() && state_diff.new_code_hash.is_some() {
+        result.deployed_contracts.push(DeployedContract {
+            address: address.clone(),
+            code_hash: state_diff.new_code_hash.clone(),
+        });
+    }
+
+    // Calls and out messages take effect only if the transaction is not aborted
+    if aborted {
+        return Ok(result);
+    }
+
+    if let Some(decoded) = try_decode(&context, &abi_registry, &params.message).await {
+        let sender = message
+            .src_ref()
+            .map(|address| address.to_string())
+            .unwrap_or_else(|| address.clone());
+        summarize_call(&mut result, &decoded, address.clone(), sender);
+    }
+
+    for boc in &executed.out_messages {
+        let out_message =
+            deserialize_object_from_boc::<Message>(&context, boc, "message").await?.object;
+        let decoded = try_decode(&context, &abi_registry, boc).await;
+        if let Some(header) = out_message.int_header() {
+            let destination = header.dst.to_string();
+            add_balance_change(
+                &mut result.balance_changes,
+                destination.clone(),
+                header.value.grams.0 as i128,
+            );
+            if let Some(state_init) = out_message.state_init() {
+                result.deployed_contracts.push(DeployedContract {
+                    address: destination.clone(),
+                    code_hash: state_init
+                        .code
+                        .as_ref()
+                        .map(|code| code.repr_hash().as_hex_string()),
+                });
+            }
+            if let Some(decoded) = &decoded {
+                summarize_call(&mut result, decoded, destination, address.clone());
+            }
+        }
+        result.decoded_out_messages.push(decoded);
+    }
+
+    Ok(result)
+}
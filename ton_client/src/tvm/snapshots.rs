@@ -0,0 +1,151 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use crate::boc::internal::{
+    deserialize_cell_from_boc, deserialize_object_from_cell, serialize_cell_to_boc,
+};
+use crate::boc::BocCacheType;
+use crate::client::ClientContext;
+use crate::error::ClientResult;
+use crate::tvm::accounts_cache::resolve_account_boc;
+use crate::tvm::Error;
+use std::sync::Arc;
+use ton_block::Account;
+use ton_types::Cell;
+
+#[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default, PartialEq)]
+pub struct AccountSnapshotHandle(pub u32);
+
+impl From<u32> for AccountSnapshotHandle {
+    fn from(handle: u32) -> Self {
+        Self(handle)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default, PartialEq)]
+pub struct RegisteredAccountSnapshot {
+    /// Handle of the account snapshot.
+    pub snapshot: AccountSnapshotHandle,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default, PartialEq)]
+pub struct ParamsOfSnapshotAccount {
+    /// Account BOC or BOC cache reference. Must be encoded as base64.
+    pub account: String,
+    /// Account address. If `account` is empty, the account state is loaded from
+    /// the network by this address, using the accounts cache if it is enabled.
+    pub address: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default, PartialEq)]
+pub struct ParamsOfGetSnapshotAccount {
+    /// Handle of the account snapshot.
+    pub snapshot: AccountSnapshotHandle,
+    /// Cache type to put the result. The BOC itself returned if no cache type provided
+    pub boc_cache: Option<BocCacheType>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, ApiType, Default, PartialEq)]
+pub struct ResultOfGetSnapshotAccount {
+    /// Current account state of the snapshot. Encoded as base64.
+    pub account: String,
+}
+
+fn register(context: &ClientContext, account: Cell) -> RegisteredAccountSnapshot {
+    let id = context.get_next_id();
+    context.account_snapshots.insert(id, account);
+    RegisteredAccountSnapshot {
+        snapshot: AccountSnapshotHandle(id),
+    }
+}
+
+pub(crate) fn get_snapshot(
+    context: &ClientContext,
+    snapshot: &AccountSnapshotHandle,
+) -> ClientResult<Cell> {
+    context
+        .account_snapshots
+        .get(&snapshot.0)
+        .map(|guard| guard.1.clone())
+        .ok_or_else(|| Error::account_snapshot_not_found(snapshot.0))
+}
+
+/// Replaces the state of the registered snapshot
+pub(crate) fn update_snapshot(
+    context: &ClientContext,
+    snapshot: &AccountSnapshotHandle,
+    account: Cell,
+) -> ClientResult<()> {
+    get_snapshot(context, snapshot)?;
+    context.account_snapshots.insert(snapshot.0, account);
+    Ok(())
+}
+
+/// Creates an in-memory snapshot of the account state.
+///
+/// The account BOC is parsed once: the snapshot can be used as
+/// `AccountForExecutor::Snapshot` to run messages on it without passing and parsing
+/// the account BOC again. Each execution on the snapshot replaces its state
+/// with the resulting account state, so a sequence of messages can be executed
+/// one by one. Use `tvm.fork` to branch alternative sequences from the same state.
+///
+/// Snapshot must be released with `tvm.release_snapshot` when it is not needed anymore.
+#[api_function]
+pub async fn snapshot_account(
+    context: Arc<ClientContext>,
+    params: ParamsOfSnapshotAccount,
+) -> ClientResult<RegisteredAccountSnapshot> {
+    let boc = resolve_account_boc(&context, &params.account, params.address.as_ref()).await?;
+    let (_, account) = deserialize_cell_from_boc(&context, &boc, "account").await?;
+    deserialize_object_from_cell::<Account>(account.clone(), "account")?;
+
+    Ok(register(&context, account))
+}
+
+/// Creates a new snapshot with the current state of the specified one.
+///
+/// Forked snapshots share the cells of the state, so forking is cheap.
+/// Executions on the fork do not affect the original snapshot and vice versa.
+#[api_function]
+pub fn fork(
+    context: Arc<ClientContext>,
+    params: RegisteredAccountSnapshot,
+) -> ClientResult<RegisteredAccountSnapshot> {
+    let account = get_snapshot(&context, &params.snapshot)?;
+
+    Ok(register(&context, account))
+}
+
+/// Returns the current account state of the snapshot.
+#[api_function]
+pub async fn get_snapshot_account(
+    context: Arc<ClientContext>,
+    params: ParamsOfGetSnapshotAccount,
+) -> ClientResult<ResultOfGetSnapshotAccount> {
+    let account = get_snapshot(&context, &params.snapshot)?;
+
+    Ok(ResultOfGetSnapshotAccount {
+        account: serialize_cell_to_boc(&context, account, "account", params.boc_cache).await?,
+    })
+}
+
+/// Releases the account snapshot.
+#[api_function]
+pub fn release_snapshot(
+    context: Arc<ClientContext>,
+    params: RegisteredAccountSnapshot,
+) -> ClientResult<()> {
+    context.account_snapshots.remove(&params.snapshot.0);
+    Ok(())
+}
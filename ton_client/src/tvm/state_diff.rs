@@ -0,0 +1,101 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use crate::error::ClientResult;
+use crate::tvm::Error;
+use std::collections::HashSet;
+use ton_block::{Account, StateInitLib};
+use ton_types::{Cell, UInt256};
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, PartialEq, Clone)]
+pub struct AccountStateDiff {
+    /// Difference between the final and the initial account balance in nanotokens.
+    /// Encoded as a decimal string, negative if the balance decreased.
+    pub balance_delta: String,
+
+    /// Hash of the account code before the transaction. `None` if account had no code.
+    pub old_code_hash: Option<String>,
+
+    /// Hash of the account code after the transaction. `None` if account has no code.
+    pub new_code_hash: Option<String>,
+
+    /// `true` if the account code was changed.
+    pub code_changed: bool,
+
+    /// Hash of the account data cell before the transaction. `None` if account had no data.
+    pub old_data_hash: Option<String>,
+
+    /// Hash of the account data cell after the transaction. `None` if account has no data.
+    pub new_data_hash: Option<String>,
+
+    /// `true` if the account data was changed.
+    pub data_changed: bool,
+
+    /// Hashes of the libraries which were added to the account state by the transaction.
+    pub new_library_refs: Vec<String>,
+}
+
+fn balance(account: &Account) -> i128 {
+    account
+        .balance()
+        .map(|balance| balance.grams.0 as i128)
+        .unwrap_or_default()
+}
+
+fn cell_hash(cell: Option<Cell>) -> Option<String> {
+    cell.map(|cell| cell.repr_hash().as_hex_string())
+}
+
+fn library_refs(account: &Account) -> ClientResult<HashSet<String>> {
+    let mut refs = HashSet::new();
+    let library = account
+        .state_init()
+        .map(|state_init| state_init.library.clone())
+        .unwrap_or_else(StateInitLib::default);
+    library
+        .iterate_keys(|key: UInt256| {
+            refs.insert(key.as_hex_string());
+            Ok(true)
+        })
+        .map_err(|err| Error::invalid_account_boc(err))?;
+    Ok(refs)
+}
+
+pub(crate) fn calc_account_state_diff(
+    old_account: &Account,
+    new_account: &Account,
+) -> ClientResult<AccountStateDiff> {
+    let old_code_hash = cell_hash(old_account.get_code());
+    let new_code_hash = cell_hash(new_account.get_code());
+    let old_data_hash = cell_hash(old_account.get_data());
+    let new_data_hash = cell_hash(new_account.get_data());
+
+    let old_library_refs = library_refs(old_account)?;
+    let mut new_library_refs: Vec<String> = library_refs(new_account)?
+        .into_iter()
+        .filter(|hash| !old_library_refs.contains(hash))
+        .collect();
+    new_library_refs.sort();
+
+    Ok(AccountStateDiff {
+        balance_delta: (balance(new_account) - balance(old_account)).to_string(),
+        code_changed: old_code_hash != new_code_hash,
+        data_changed: old_data_hash != new_data_hash,
+        old_code_hash,
+        new_code_hash,
+        old_data_hash,
+        new_data_hash,
+        new_library_refs,
+    })
+}
@@ -0,0 +1,161 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use serde_json::Value;
+use std::sync::Arc;
+use ton_block::{ConfigParamEnum, MsgAddressInt};
+use ton_types::UInt256;
+
+use crate::boc::internal::deserialize_object_from_boc;
+use crate::client::ClientContext;
+use crate::encoding::account_encode;
+use crate::error::ClientResult;
+use crate::proofs::{
+    account_get_last_transaction, proof_transaction_data, ParamsOfProofTransactionData,
+};
+use crate::tvm::accounts_cache::{fetch_account_boc, query_account_boc};
+use crate::tvm::types::resolve_blockchain_config;
+use crate::tvm::{run_get, Error, ExecutionOptions, ParamsOfRunGet};
+
+#[derive(Serialize, Deserialize, ApiType, Clone, Copy, Debug, PartialEq)]
+pub enum SystemContract {
+    /// Config contract (config param 0)
+    Config,
+    /// Elector contract (config param 1)
+    Elector,
+    /// DNS root contract (config param 4)
+    DnsRoot,
+}
+
+impl Default for SystemContract {
+    fn default() -> Self {
+        SystemContract::Elector
+    }
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ParamsOfRunSystemGetter {
+    /// System contract
+    pub contract: SystemContract,
+    /// Function name
+    pub function_name: String,
+    /// Input parameters
+    pub input: Option<Value>,
+    /// Execution options.
+    ///
+    /// The `blockchain_config` is also used to resolve the system contract address.
+    pub execution_options: Option<ExecutionOptions>,
+    /// Convert lists based on nested tuples in the **result** into plain arrays. Default is `false`.
+    pub tuple_list_as_array: Option<bool>,
+    /// Prove the account state before running the getter. Default is `false`.
+    ///
+    /// The last account transaction is checked with `proofs.proof_transaction_data`
+    /// and the account state hash is compared with the transaction state update.
+    pub proven: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Clone)]
+pub struct ResultOfRunSystemGetter {
+    /// Values returned by get-method on stack
+    pub output: Value,
+    /// System contract address
+    pub address: String,
+}
+
+fn system_contract_id(
+    config: &ton_block::ConfigParams,
+    contract: SystemContract,
+) -> ClientResult<UInt256> {
+    let param = match contract {
+        SystemContract::Config => return Ok(config.config_addr.clone()),
+        SystemContract::Elector => 1,
+        SystemContract::DnsRoot => 4,
+    };
+    match config.config(param) {
+        Ok(Some(ConfigParamEnum::ConfigParam1(param))) => Ok(param.elector_addr),
+        Ok(Some(ConfigParamEnum::ConfigParam4(param))) => Ok(param.dns_root_addr),
+        Ok(_) => Err(Error::can_not_read_blockchain_config(format!(
+            "config param {} is missing",
+            param
+        ))),
+        Err(err) => Err(Error::can_not_read_blockchain_config(err)),
+    }
+}
+
+/// Downloads the account BOC bypassing the accounts cache and proves it
+/// with the last account transaction
+async fn fetch_proven_account_boc(
+    context: &Arc<ClientContext>,
+    address: &MsgAddressInt,
+) -> ClientResult<String> {
+    let boc = query_account_boc(context, address).await?;
+    let account = deserialize_object_from_boc::<ton_block::Account>(context, &boc, "account").await?;
+    if account.object.last_tr_time().is_none() {
+        return Err(Error::invalid_account_boc("account has no transactions"));
+    }
+    let transaction =
+        account_get_last_transaction(context, &account.object, &account.cell.repr_hash()).await?;
+
+    proof_transaction_data(context.clone(), ParamsOfProofTransactionData { transaction, verification_policy: None })
+        .await?;
+    Ok(boc)
+}
+
+/// Runs a get-method of the system contract.
+///
+/// The contract address is resolved from the blockchain config of the current network,
+/// the account state is fetched from the network (and optionally proven) and the
+/// get-method is executed as `run_get` does.
+#[api_function]
+pub async fn run_system_getter(
+    context: Arc<ClientContext>,
+    params: ParamsOfRunSystemGetter,
+) -> ClientResult<ResultOfRunSystemGetter> {
+    let config = resolve_blockchain_config(
+        &context,
+        params
+            .execution_options
+            .as_ref()
+            .and_then(|options| options.blockchain_config.clone()),
+    )
+    .await?;
+    let id = system_contract_id(config.raw_config(), params.contract)?;
+    let address = MsgAddressInt::with_standart(None, -1, id.into())
+        .map_err(|err| Error::internal_error(err))?;
+
+    let account = if params.proven.unwrap_or(false) {
+        fetch_proven_account_boc(&context, &address).await?
+    } else {
+        fetch_account_boc(&context, &address).await?
+    };
+
+    let output = run_get(
+        context,
+        ParamsOfRunGet {
+            account,
+            function_name: params.function_name,
+            input: params.input,
+            execution_options: params.execution_options,
+            tuple_list_as_array: params.tuple_list_as_array,
+            address: None,
+        },
+    )
+    .await?
+    .output;
+
+    Ok(ResultOfRunSystemGetter {
+        output,
+        address: account_encode(&address),
+    })
+}
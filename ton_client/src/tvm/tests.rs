@@ -15,7 +15,7 @@
 use super::types::resolve_blockchain_config;
 use super::*;
 use crate::abi::{
-    encode_account::{ParamsOfEncodeAccount, StateInitSource},
+    encode_account::{ParamsOfEncodeAccount, ResultOfEncodeAccount, StateInitSource},
     Abi, CallSet, DeploySet, ParamsOfEncodeMessage, ResultOfEncodeMessage, Signer,
 };
 use crate::boc::{internal::{deserialize_object_from_base64, serialize_cell_to_base64}, BocCacheType};
@@ -73,6 +73,7 @@ async fn test_execute_get() {
             input: None,
             execution_options: None,
             tuple_list_as_array: Some(true),
+            address: None,
         })
         .await
         .unwrap()
@@ -125,6 +126,50 @@ async fn test_execute_get() {
         .output;
 
     assert_eq!(result[0][0][0], "1588268660");
+
+    let err = run_get
+        .call(ParamsOfRunGet {
+            account: elector.clone(),
+            function_name: "participant_list".into(),
+            execution_options: Some(ExecutionOptions {
+                gas_limit: Some(100),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::ExecutionLimitExceeded as u32);
+    assert_eq!(err.data["gas_limit"], 100);
+
+    let err = run_get
+        .call(ParamsOfRunGet {
+            account: elector.clone(),
+            function_name: "participant_list".into(),
+            execution_options: Some(ExecutionOptions {
+                gas_limit: Some(u64::MAX),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(err.code, crate::client::ErrorCode::InvalidParams as u32);
+
+    let result = run_get
+        .call(ParamsOfRunGet {
+            account: elector.clone(),
+            function_name: "past_elections".into(),
+            execution_options: Some(ExecutionOptions {
+                timeout: Some(10000),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .output;
+    assert_eq!(result[0][0][0], "1588268660");
 }
 
 #[tokio::test(core_threads = 2)]
@@ -149,6 +194,7 @@ async fn test_run_executor() {
                     unlimited_balance: None,
                 },
                 return_updated_account: Some(true),
+                return_actions: Some(true),
                 ..Default::default()
             })
             .await
@@ -157,6 +203,18 @@ async fn test_run_executor() {
         assert_eq!(result.transaction["in_msg"], message.message_id);
         assert!(result.fees.total_account_fees > 0);
 
+        // every sent message has a corresponding action
+        let sent_messages = result
+            .actions
+            .unwrap()
+            .into_iter()
+            .filter(|action| match action {
+                ExecutorAction::SendMsg { skipped, .. } => !skipped,
+                _ => false,
+            })
+            .count();
+        assert_eq!(sent_messages, result.out_messages.len());
+
         result.account
     };
 
@@ -178,6 +236,7 @@ async fn test_run_tvm() {
                 execution_options: None,
                 boc_cache: None,
                 return_updated_account: Some(true),
+                address: None,
             })
             .await
             .unwrap();
@@ -215,6 +274,7 @@ async fn test_run_message<F>(
                 ),
                 signer: Signer::Keys { keys: keys.clone() },
                 processing_try_index: None,
+                expiration: None,
                 address: None,
             },
             None,
@@ -243,6 +303,7 @@ async fn test_run_message<F>(
             signer: Signer::Keys { keys: keys.clone() },
             deploy_set: None,
             processing_try_index: None,
+            expiration: None,
         })
         .await
         .unwrap();
@@ -264,6 +325,7 @@ async fn test_run_message<F>(
             address: Some(address.clone()),
             deploy_set: None,
             processing_try_index: None,
+            expiration: None,
         })
         .await
         .unwrap();
@@ -276,6 +338,7 @@ async fn test_run_message<F>(
             execution_options: None,
             boc_cache: None,
             return_updated_account: None,
+            address: None,
         })
         .await
         .unwrap();
@@ -356,6 +419,7 @@ async fn test_run_account_uninit() {
                 ..Default::default()
             }),
             processing_try_index: None,
+            expiration: None,
             signer: Signer::Keys { keys: keys.clone() },
         })
         .await
@@ -366,11 +430,19 @@ async fn test_run_account_uninit() {
             message: message.message.to_owned(),
             account: AccountForExecutor::Uninit,
             return_updated_account: Some(true),
+            return_state_diff: Some(true),
             ..Default::default()
         })
         .await
         .unwrap();
 
+    let state_diff = result.state_diff.unwrap();
+    assert!(state_diff.code_changed);
+    assert!(state_diff.data_changed);
+    assert_eq!(state_diff.old_code_hash, None);
+    assert!(state_diff.new_code_hash.is_some());
+    assert!(state_diff.balance_delta.starts_with('-'));
+
     let parsed: crate::boc::ResultOfParse = client
         .request_async(
             "boc.parse_account",
@@ -384,6 +456,154 @@ async fn test_run_account_uninit() {
     assert_eq!(parsed.parsed["acc_type_name"], "Active");
 }
 
+#[tokio::test(core_threads = 2)]
+async fn test_simulate_and_summarize() {
+    TestClient::init_log();
+    let client = TestClient::new();
+
+    let keys = client.generate_sign_keys();
+    let (abi, tvc) = TestClient::package(HELLO, None);
+    let message = client
+        .encode_message(ParamsOfEncodeMessage {
+            abi: abi.clone(),
+            address: None,
+            call_set: CallSet::some_with_function("constructor"),
+            deploy_set: DeploySet::some_with_tvc(tvc),
+            processing_try_index: None,
+            expiration: None,
+            signer: Signer::Keys { keys },
+        })
+        .await
+        .unwrap();
+
+    let result: ResultOfSimulateAndSummarize = client
+        .request_async(
+            "tvm.simulate_and_summarize",
+            ParamsOfSimulateAndSummarize {
+                message: message.message,
+                account: AccountForExecutor::Uninit,
+                abi_registry: Some(vec![abi]),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result.address, message.address);
+    assert!(!result.aborted);
+    assert_eq!(result.exit_code, Some(0));
+    assert_eq!(result.deployed_contracts.len(), 1);
+    assert_eq!(result.deployed_contracts[0].address, message.address);
+    assert!(result.deployed_contracts[0].code_hash.is_some());
+    assert_eq!(result.balance_changes.len(), 1);
+    assert_eq!(result.balance_changes[0].address, message.address);
+    assert!(result.balance_changes[0].delta.starts_with('-'));
+    assert!(result.token_transfers.is_empty());
+    assert!(result.approvals.is_empty());
+}
+
+#[tokio::test(core_threads = 2)]
+async fn test_account_snapshots() {
+    TestClient::init_log();
+    let client = TestClient::new();
+
+    let keys = client.generate_sign_keys();
+    let (abi, tvc) = TestClient::package(HELLO, None);
+    let deploy_message = client
+        .encode_message(ParamsOfEncodeMessage {
+            abi: abi.clone(),
+            address: None,
+            call_set: CallSet::some_with_function("constructor"),
+            deploy_set: DeploySet::some_with_tvc(tvc),
+            processing_try_index: None,
+            expiration: None,
+            signer: Signer::Keys { keys: keys.clone() },
+        })
+        .await
+        .unwrap();
+    let deployed: ResultOfRunExecutor = client
+        .request_async(
+            "tvm.run_executor",
+            ParamsOfRunExecutor {
+                message: deploy_message.message,
+                account: AccountForExecutor::Uninit,
+                return_updated_account: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let original: RegisteredAccountSnapshot = client
+        .request_async(
+            "tvm.snapshot_account",
+            ParamsOfSnapshotAccount {
+                account: deployed.account.clone(),
+                address: None,
+            },
+        )
+        .await
+        .unwrap();
+    let fork: RegisteredAccountSnapshot = client
+        .request_async("tvm.fork", original.clone())
+        .await
+        .unwrap();
+    assert_ne!(fork.snapshot, original.snapshot);
+
+    let touch_message = client
+        .encode_message(ParamsOfEncodeMessage {
+            abi: abi.clone(),
+            address: Some(deploy_message.address.clone()),
+            call_set: CallSet::some_with_function("touch"),
+            deploy_set: None,
+            processing_try_index: None,
+            expiration: None,
+            signer: Signer::Keys { keys },
+        })
+        .await
+        .unwrap();
+    let touched: ResultOfRunExecutor = client
+        .request_async(
+            "tvm.run_executor",
+            ParamsOfRunExecutor {
+                message: touch_message.message,
+                account: AccountForExecutor::Snapshot {
+                    snapshot: fork.snapshot.clone(),
+                    unlimited_balance: None,
+                },
+                return_updated_account: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let get_account = |snapshot: &RegisteredAccountSnapshot| {
+        client.request_async::<_, ResultOfGetSnapshotAccount>(
+            "tvm.get_snapshot_account",
+            ParamsOfGetSnapshotAccount {
+                snapshot: snapshot.snapshot.clone(),
+                boc_cache: None,
+            },
+        )
+    };
+
+    // execution updates the fork only
+    assert_eq!(get_account(&fork).await.unwrap().account, touched.account);
+    assert_eq!(get_account(&original).await.unwrap().account, deployed.account);
+
+    let _: () = client
+        .request_async("tvm.release_snapshot", fork.clone())
+        .await
+        .unwrap();
+    let err = get_account(&fork).await.unwrap_err();
+    assert_eq!(err.code, ErrorCode::AccountSnapshotNotFound as u32);
+    let _: () = client
+        .request_async("tvm.release_snapshot", original)
+        .await
+        .unwrap();
+}
+
 #[allow(dead_code)]
 //#[tokio::test(core_threads = 2)]
 async fn profile_tvm() {
@@ -403,6 +623,7 @@ async fn profile_tvm() {
             address: Some(address.clone()),
             deploy_set: None,
             processing_try_index: None,
+            expiration: None,
         })
         .await
         .unwrap();
@@ -415,6 +636,7 @@ async fn profile_tvm() {
             execution_options: None,
             boc_cache: Some(BocCacheType::Unpinned),
             return_updated_account: Some(true),
+            address: None,
         })
         .await
         .unwrap();
@@ -446,6 +668,7 @@ async fn profile_tvm() {
                     address: Some(address.clone()),
                     deploy_set: None,
                     processing_try_index: None,
+                    expiration: None,
                 })
                 .await
                 .unwrap();
@@ -474,6 +697,7 @@ async fn profile_tvm() {
                     execution_options: None,
                     boc_cache: Some(BocCacheType::Unpinned),
                     return_updated_account: None,
+                    address: None,
                 })
                 .await
                 .unwrap();
@@ -492,6 +716,57 @@ async fn profile_tvm() {
     );
 }
 
+#[test]
+fn test_merge_paginated_items() {
+    let mut items = Value::Null;
+    assert_eq!(run_paginated::merge_items(&mut items, json!([1, 2])).unwrap(), 2);
+    assert_eq!(run_paginated::merge_items(&mut items, json!([3])).unwrap(), 1);
+    assert_eq!(run_paginated::merge_items(&mut items, Value::Null).unwrap(), 0);
+    assert_eq!(items, json!([1, 2, 3]));
+
+    let mut items = Value::Null;
+    run_paginated::merge_items(&mut items, json!({"0x01": "a"})).unwrap();
+    run_paginated::merge_items(&mut items, json!({"0x02": "b"})).unwrap();
+    assert_eq!(items, json!({"0x01": "a", "0x02": "b"}));
+
+    let err = run_paginated::merge_items(&mut items, json!([1])).unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidGetterOutput as u32);
+}
+
+#[test]
+fn test_exit_arg_decoding() {
+    let address = ELECTOR_ADDRESS.parse().unwrap();
+    let text = "Not enough funds";
+    let cell = BuilderData::with_raw(text.as_bytes().to_vec(), text.len() * 8)
+        .unwrap()
+        .into_cell()
+        .unwrap();
+    let exit_arg = json!({
+        "type": "Cell",
+        "value": serialize_cell_to_base64(&cell, "exit arg").unwrap(),
+    });
+
+    // `require` message is decoded from the string cell
+    let error = Error::tvm_execution_failed("", 101, Some(exit_arg), &address, None, false);
+    assert_eq!(error.data["exit_arg"], json!(text));
+    assert_eq!(error.data["contract_error"], json!(text));
+    assert!(error.message.contains("contract error: \"Not enough funds\""));
+
+    // integer argument is converted to number
+    let error = Error::tvm_execution_failed("", 101, Some(json!("7")), &address, None, false);
+    assert_eq!(error.data["exit_arg"], json!(7));
+    assert!(error.message.contains("exit arg: 7"));
+
+    let error = Error::tvm_execution_failed("", 101, Some(json!(0)), &address, None, false);
+    assert_eq!(error.data["exit_arg"], json!(0));
+    assert!(!error.message.contains("exit arg"));
+
+    // big integers are kept as strings
+    let big = format!("0x{}", "f".repeat(64));
+    let error = Error::tvm_execution_failed("", 101, Some(json!(big)), &address, None, false);
+    assert_eq!(error.data["exit_arg"], json!(big));
+}
+
 #[test]
 fn test_stack_serialization() {
     let empty_cell = Cell::default();
@@ -720,6 +995,8 @@ async fn test_tvm_error_message() {
                 result: "boc".to_owned(),
                 order: None,
                 limit: Some(1),
+                min_block: None,
+                decode_body: None,
             },
         )
         .await
@@ -749,6 +1026,7 @@ async fn test_method_error(
             signer: Signer::None,
             ..Default::default()
         }
+        expiration: None,
     }
 
     async fn encode_message(client: &Arc<TestClient>, params: &ParamsOfEncodeMessage) -> String {
@@ -950,3 +1228,106 @@ async fn test_my_code() {
 
     println!("{:?}", get_my_code);
 }
+
+#[tokio::test(core_threads = 2)]
+async fn test_accounts_cache() {
+    let client = TestClient::new_with_config(json!({
+        "network": {
+            "accounts_cache_size": 10,
+        }
+    }));
+    let elector: ResultOfEncodeAccount = client
+        .request_async(
+            "abi.encode_account",
+            ParamsOfEncodeAccount {
+                state_init: StateInitSource::StateInit {
+                    code: ELECTOR_CODE.into(),
+                    data: ELECTOR_DATA.into(),
+                    library: None,
+                },
+                last_trans_lt: Some(10),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let context = client.context();
+    let cache = &context.net.accounts_cache;
+    cache.put(ELECTOR_ADDRESS.to_owned(), elector.account.clone(), 10).await;
+
+    // Account state is taken from the cache, so no network request is performed
+    let result: ResultOfRunGet = client
+        .request_async(
+            "tvm.run_get",
+            ParamsOfRunGet {
+                address: Some(ELECTOR_ADDRESS.to_owned()),
+                function_name: "participant_list".into(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert!(!result.output.is_null());
+
+    // Older and unrelated transactions do not invalidate the cached state
+    cache
+        .observe_collection_item("transactions", &json!({ "account_addr": ELECTOR_ADDRESS, "lt": "0xa" }))
+        .await;
+    cache
+        .observe_collection_item("transactions", &json!({ "account_addr": "0:1", "lt": "0xff" }))
+        .await;
+    assert_eq!(cache.get(ELECTOR_ADDRESS).await, Some(elector.account.clone()));
+
+    cache
+        .observe_collection_item("accounts", &json!({ "id": ELECTOR_ADDRESS, "last_trans_lt": "0xb" }))
+        .await;
+    assert_eq!(cache.get(ELECTOR_ADDRESS).await, None);
+}
+
+
+#[tokio::test(core_threads = 2)]
+async fn test_run_system_getter() {
+    let client = TestClient::new_with_config(json!({
+        "network": {
+            "accounts_cache_size": 10,
+        }
+    }));
+    let elector: ResultOfEncodeAccount = client
+        .request_async(
+            "abi.encode_account",
+            ParamsOfEncodeAccount {
+                state_init: StateInitSource::StateInit {
+                    code: ELECTOR_CODE.into(),
+                    data: ELECTOR_DATA.into(),
+                    library: None,
+                },
+                last_trans_lt: Some(10),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+    let context = client.context();
+    *context.blockchain_config.write().await = Some(Arc::new(mainnet_config()));
+    context
+        .net
+        .accounts_cache
+        .put(ELECTOR_ADDRESS.to_owned(), elector.account, 10)
+        .await;
+
+    let result: ResultOfRunSystemGetter = client
+        .request_async(
+            "tvm.run_system_getter",
+            ParamsOfRunSystemGetter {
+                contract: SystemContract::Elector,
+                function_name: "past_elections".into(),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.address, ELECTOR_ADDRESS);
+    assert_eq!(result.output[0][0][0], "1588268660");
+}
@@ -13,14 +13,16 @@
  */
 
 use super::Error;
-use crate::{boc::{
+use crate::boc::internal::deserialize_object_from_boc;
+#[cfg(feature = "net")]
+use crate::boc::{
     blockchain_config::{extract_config_from_block, extract_config_from_zerostate},
-    internal::{deserialize_object_from_base64, deserialize_object_from_boc},
-}, net::ServerLink};
-use crate::net::ParamsOfQueryCollection;
+    internal::deserialize_object_from_base64,
+};
 use crate::client::ClientContext;
 use crate::error::ClientResult;
-use crate::net::{OrderBy, SortDirection};
+#[cfg(feature = "net")]
+use crate::net::{OrderBy, ParamsOfQueryCollection, ServerLink, SortDirection};
 use std::sync::Arc;
 use ton_block::Deserializable;
 use ton_executor::BlockchainConfig;
@@ -35,13 +37,30 @@ pub struct ExecutionOptions {
     pub block_lt: Option<u64>,
     /// transaction logical time
     pub transaction_lt: Option<u64>,
+    /// Gas limit for `run_tvm` and `run_get` execution. Default is 1 000 000 000.
+    /// Execution exceeding the limit fails with `ExecutionLimitExceeded` error.
+    /// Must not exceed `i64::MAX`.
+    pub gas_limit: Option<u64>,
+    /// Wall-clock timeout in milliseconds for `run_tvm` and `run_get` execution.
+    /// Execution exceeding the timeout fails with `ExecutionLimitExceeded` error.
+    /// By default the execution is bounded by the gas limit only.
+    ///
+    /// Timed out execution can't be cancelled and keeps running in background until it
+    /// exhausts the gas limit, so specify a reasonable `gas_limit` as well. While several
+    /// timed out executions are still running, new executions with `timeout` are rejected.
+    pub timeout: Option<u32>,
 }
 
+pub(crate) const DEFAULT_GAS_LIMIT: u64 = 1_000_000_000;
+
+#[derive(Clone)]
 pub(crate) struct ResolvedExecutionOptions {
     pub blockchain_config: Arc<BlockchainConfig>,
     pub block_time: u32,
     pub block_lt: u64,
     pub transaction_lt: u64,
+    pub gas_limit: u64,
+    pub timeout: Option<u32>,
 }
 
 pub(crate) async fn blockchain_config_from_boc(context: &ClientContext, b64: &str) -> ClientResult<BlockchainConfig> {
@@ -72,6 +91,8 @@ impl ResolvedExecutionOptions {
             block_time,
             blockchain_config: config,
             transaction_lt,
+            gas_limit: options.gas_limit.unwrap_or(DEFAULT_GAS_LIMIT),
+            timeout: options.timeout,
         })
     }
 }
@@ -104,6 +125,7 @@ pub(crate) async fn get_default_config(context: &Arc<ClientContext>) -> ClientRe
         return Ok(config.clone());
     }
 
+    #[cfg(feature = "net")]
     let config = if let Ok(link) = context.get_server_link() {
         get_network_config(link)
             .await
@@ -111,6 +133,8 @@ pub(crate) async fn get_default_config(context: &Arc<ClientContext>) -> ClientRe
     } else {
         mainnet_config()
     };
+    #[cfg(not(feature = "net"))]
+    let config = mainnet_config();
     let config = Arc::new(config);
 
     *config_lock = Some(config.clone());
@@ -118,6 +142,7 @@ pub(crate) async fn get_default_config(context: &Arc<ClientContext>) -> ClientRe
     Ok(config)
 }
 
+#[cfg(feature = "net")]
 pub(crate) async fn get_network_config(link: &ServerLink) -> ClientResult<BlockchainConfig> {
     let key_block = link.query_collection(ParamsOfQueryCollection {
         collection: "blocks".to_owned(),
@@ -128,6 +153,8 @@ pub(crate) async fn get_network_config(link: &ServerLink) -> ClientResult<Blockc
         order: Some(vec![OrderBy { path: "seq_no".to_owned(), direction: SortDirection::DESC }]),
         limit: Some(1),
         result: "boc".to_owned(),
+        min_block: None,
+        decode_body: None,
     }, None).await?;
 
     let config = if let Some(block_boc) = key_block[0]["boc"].as_str() {
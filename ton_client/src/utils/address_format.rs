@@ -0,0 +1,117 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::client::ClientContext;
+use crate::encoding::{account_decode, account_encode_ex, AccountAddressType, Base64AddressParams};
+use crate::error::ClientResult;
+use crate::net::{query_collection, ParamsOfQueryCollection};
+use std::sync::Arc;
+
+/// Reason of the suggested address format
+#[derive(Serialize, Deserialize, ApiType, Debug, Clone, PartialEq)]
+pub enum AddressFormatRationale {
+    /// Account is active, so the funds can be safely sent in a bounceable message:
+    /// if the contract fails to process it, the funds are returned to the sender.
+    ActiveAccount,
+    /// Account is frozen. Bounceable message returns the funds to the sender
+    /// instead of leaving them on the frozen account.
+    FrozenAccount,
+    /// Account is not deployed yet. Bounceable message is returned to the sender,
+    /// so the funds intended to deploy the account must be sent in a non-bounceable one.
+    UninitAccount,
+    /// Account doesn't exist. Like for the uninitialized account, only a non-bounceable
+    /// message leaves the funds on the account.
+    NonExistentAccount,
+}
+
+impl Default for AddressFormatRationale {
+    fn default() -> Self {
+        AddressFormatRationale::NonExistentAccount
+    }
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug)]
+pub struct ParamsOfSuggestAddressFormat {
+    /// Account address in any TON format.
+    pub address: String,
+    /// Use the testnet flag in the resulting address. Default is `false`.
+    pub test: Option<bool>,
+    /// Use the URL-safe base64 alphabet in the resulting address. Default is `true`.
+    pub url: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug)]
+pub struct ResultOfSuggestAddressFormat {
+    /// User-friendly (base64) address with the suggested bounce flag.
+    pub address: String,
+    /// Suggested bounce flag.
+    pub bounce: bool,
+    /// Reason of the suggested format.
+    pub rationale: AddressFormatRationale,
+}
+
+fn rationale_by_acc_type(acc_type: Option<u64>) -> AddressFormatRationale {
+    match acc_type {
+        Some(0) => AddressFormatRationale::UninitAccount,
+        Some(1) => AddressFormatRationale::ActiveAccount,
+        Some(2) => AddressFormatRationale::FrozenAccount,
+        _ => AddressFormatRationale::NonExistentAccount,
+    }
+}
+
+/// Suggests the user-friendly address format to send funds to the account.
+///
+/// The account status is queried from the network. Bounceable address is suggested for
+/// the active and frozen accounts, so the funds are returned if the message fails.
+/// Non-bounceable address is suggested for the uninitialized and non-existent accounts,
+/// otherwise the message is bounced and the account can't be deployed with these funds.
+#[api_function]
+pub async fn suggest_address_format(
+    context: Arc<ClientContext>,
+    params: ParamsOfSuggestAddressFormat,
+) -> ClientResult<ResultOfSuggestAddressFormat> {
+    let address = account_decode(&params.address)?;
+    let accounts = query_collection(
+        context,
+        ParamsOfQueryCollection {
+            collection: "accounts".to_owned(),
+            filter: Some(json!({ "id": { "eq": address.to_string() } })),
+            result: "acc_type".to_owned(),
+            limit: Some(1),
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    let rationale = rationale_by_acc_type(
+        accounts.result.get(0).and_then(|account| account["acc_type"].as_u64()),
+    );
+    let bounce = match rationale {
+        AddressFormatRationale::ActiveAccount | AddressFormatRationale::FrozenAccount => true,
+        AddressFormatRationale::UninitAccount | AddressFormatRationale::NonExistentAccount => false,
+    };
+
+    Ok(ResultOfSuggestAddressFormat {
+        address: account_encode_ex(
+            &address,
+            AccountAddressType::Base64,
+            Some(Base64AddressParams {
+                url: params.url.unwrap_or(true),
+                test: params.test.unwrap_or(false),
+                bounce,
+            }),
+        )?,
+        bounce,
+        rationale,
+    })
+}
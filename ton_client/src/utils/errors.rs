@@ -18,6 +18,7 @@ use crate::error::ClientError;
 #[derive(ApiType)]
 pub enum ErrorCode {
     CompressionError = 701,
+    InvalidPaymentUrl = 702,
 }
 
 pub struct Error;
@@ -34,4 +35,8 @@ impl Error {
     pub fn decompression_error<E: Display>(err: E) -> ClientError {
         error(ErrorCode::CompressionError, format!("Decompression error: {}", err))
     }
+
+    pub fn invalid_payment_url<E: Display>(err: E) -> ClientError {
+        error(ErrorCode::InvalidPaymentUrl, format!("Invalid payment URL: {}", err))
+    }
 }
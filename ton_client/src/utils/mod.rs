@@ -15,12 +15,23 @@
 #[cfg(test)]
 mod tests;
 
+#[cfg(feature = "net")]
+pub(crate) mod address_format;
+#[cfg(feature = "tvm-executor")]
 pub(crate) mod calc_storage_fee;
 pub(crate) mod conversion;
 pub(crate) mod compression;
+#[cfg(feature = "proofs")]
 pub(crate) mod json;
+pub(crate) mod payment_url;
 mod errors;
 
+#[cfg(feature = "net")]
+pub use address_format::{
+    suggest_address_format, AddressFormatRationale, ParamsOfSuggestAddressFormat,
+    ResultOfSuggestAddressFormat,
+};
+#[cfg(feature = "tvm-executor")]
 pub use calc_storage_fee::{
     calc_storage_fee, ParamsOfCalcStorageFee, ResultOfCalcStorageFee
 };
@@ -29,5 +40,9 @@ pub use conversion::{
     get_address_type, ParamsOfGetAddressType, ResultOfGetAddressType,
 };
 pub use compression::{compress_zstd, decompress_zstd};
+pub use payment_url::{
+    decode_payment_url, encode_payment_url, ParamsOfDecodePaymentUrl, ParamsOfEncodePaymentUrl,
+    PaymentUrlScheme, ResultOfDecodePaymentUrl, ResultOfEncodePaymentUrl,
+};
 pub use errors::{Error, ErrorCode};
 pub use crate::encoding::AccountAddressType;
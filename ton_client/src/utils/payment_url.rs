@@ -0,0 +1,281 @@
+/*
+* Copyright 2018-2021 TON Labs LTD.
+*
+* Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+* this file except in compliance with the License.
+*
+* Unless required by applicable law or agreed to in writing, software
+* distributed under the License is distributed on an "AS IS" BASIS,
+* WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+* See the License for the specific TON DEV software governing permissions and
+* limitations under the License.
+*/
+
+use crate::boc::internal::{deserialize_cell_from_base64, serialize_cell_to_base64};
+use crate::client::ClientContext;
+use crate::encoding::account_decode;
+use crate::error::ClientResult;
+use crate::utils::Error;
+use std::sync::Arc;
+use ton_types::{BuilderData, Cell, IBitstring};
+
+const TRANSFER_PATH: &str = "transfer/";
+// Comment cell starts with 32-bit zero op code
+const FIRST_COMMENT_CELL_BYTES: usize = 123;
+const COMMENT_CELL_BYTES: usize = 127;
+
+#[derive(Serialize, Deserialize, ApiType, Debug, Clone, PartialEq)]
+pub enum PaymentUrlScheme {
+    /// `ton://transfer/<address>?...`
+    Ton,
+    /// `everscale://transfer/<address>?...`
+    Everscale,
+}
+
+impl Default for PaymentUrlScheme {
+    fn default() -> Self {
+        PaymentUrlScheme::Ton
+    }
+}
+
+impl PaymentUrlScheme {
+    fn prefix(&self) -> &'static str {
+        match self {
+            PaymentUrlScheme::Ton => "ton://",
+            PaymentUrlScheme::Everscale => "everscale://",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug)]
+pub struct ParamsOfEncodePaymentUrl {
+    /// Recipient address in any TON format. It is put into the URL as is.
+    pub address: String,
+    /// Amount in nanotokens as a decimal string.
+    pub amount: Option<String>,
+    /// Text comment.
+    pub text: Option<String>,
+    /// Binary payload BOC encoded as `base64`. Can't be used together with `text`.
+    pub bin: Option<String>,
+    /// URL scheme. Default is `Ton`.
+    pub scheme: Option<PaymentUrlScheme>,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug)]
+pub struct ResultOfEncodePaymentUrl {
+    /// Payment URL.
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug)]
+pub struct ParamsOfDecodePaymentUrl {
+    /// Payment URL.
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize, ApiType, Default, Debug, PartialEq)]
+pub struct ResultOfDecodePaymentUrl {
+    /// URL scheme.
+    pub scheme: PaymentUrlScheme,
+    /// Recipient address in the standard `workchain:account_id` format.
+    pub address: String,
+    /// Amount in nanotokens as a decimal string.
+    pub amount: Option<String>,
+    /// Text comment.
+    pub text: Option<String>,
+    /// Message body BOC encoded as `base64`: the binary payload or the text comment
+    /// cell (32-bit zero prefix followed by the comment bytes).
+    pub payload: Option<String>,
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                result.push(byte as char)
+            }
+            _ => result.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    result
+}
+
+fn percent_decode(value: &str) -> ClientResult<String> {
+    let bytes = value.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let byte = value
+                    .get(i + 1..i + 3)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .ok_or_else(|| Error::invalid_payment_url("invalid percent encoding"))?;
+                result.push(byte);
+                i += 3;
+            }
+            b'+' => {
+                result.push(b' ');
+                i += 1;
+            }
+            byte => {
+                result.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(result).map_err(|err| Error::invalid_payment_url(err))
+}
+
+fn check_amount(amount: &str) -> ClientResult<()> {
+    amount
+        .parse::<u128>()
+        .map(|_| ())
+        .map_err(|err| Error::invalid_payment_url(format!("invalid amount `{}`: {}", amount, err)))
+}
+
+/// Builds the text comment cell: 32-bit zero prefix and the UTF-8 text
+/// continued in the chain of referenced cells.
+pub(crate) fn build_comment_cell(text: &str) -> ClientResult<Cell> {
+    let bytes = text.as_bytes();
+    let first_len = std::cmp::min(bytes.len(), FIRST_COMMENT_CELL_BYTES);
+    let (first, rest) = bytes.split_at(first_len);
+
+    let mut next: Option<Cell> = None;
+    for chunk in rest.chunks(COMMENT_CELL_BYTES).rev() {
+        let mut builder = BuilderData::new();
+        builder.append_raw(chunk, chunk.len() * 8).map_err(|err| Error::invalid_payment_url(err))?;
+        if let Some(cell) = next.take() {
+            builder.append_reference_cell(cell);
+        }
+        next = Some(builder.into_cell().map_err(|err| Error::invalid_payment_url(err))?);
+    }
+
+    let mut builder = BuilderData::new();
+    builder
+        .append_u32(0)
+        .and_then(|builder| builder.append_raw(first, first.len() * 8))
+        .map_err(|err| Error::invalid_payment_url(err))?;
+    if let Some(cell) = next {
+        builder.append_reference_cell(cell);
+    }
+    builder.into_cell().map_err(|err| Error::invalid_payment_url(err))
+}
+
+fn decode_bin(bin: &str) -> ClientResult<String> {
+    let bytes = base64::decode_config(bin.trim_end_matches('='), base64::URL_SAFE_NO_PAD)
+        .or_else(|_| base64::decode(bin))
+        .map_err(|err| Error::invalid_payment_url(format!("invalid `bin` parameter: {}", err)))?;
+    let boc = base64::encode(&bytes);
+    deserialize_cell_from_base64(&boc, "payload")?;
+    Ok(boc)
+}
+
+/// Encodes the payment URL of the `ton://transfer/<address>?amount=&text=&bin=` scheme
+/// (or its `everscale://` variant).
+///
+/// The address is validated with the address parser. The binary payload is put into
+/// the `bin` parameter as URL-safe base64.
+#[api_function]
+pub fn encode_payment_url(
+    _context: Arc<ClientContext>,
+    params: ParamsOfEncodePaymentUrl,
+) -> ClientResult<ResultOfEncodePaymentUrl> {
+    account_decode(&params.address)?;
+    if params.text.is_some() && params.bin.is_some() {
+        return Err(Error::invalid_payment_url("`text` and `bin` can't be used together"));
+    }
+
+    let mut query = Vec::new();
+    if let Some(amount) = &params.amount {
+        check_amount(amount)?;
+        query.push(format!("amount={}", amount));
+    }
+    if let Some(text) = &params.text {
+        query.push(format!("text={}", percent_encode(text)));
+    }
+    if let Some(bin) = &params.bin {
+        let (bytes, _) = deserialize_cell_from_base64(bin, "payload")?;
+        query.push(format!("bin={}", base64::encode_config(&bytes, base64::URL_SAFE_NO_PAD)));
+    }
+
+    let mut url = format!(
+        "{}{}{}",
+        params.scheme.unwrap_or_default().prefix(),
+        TRANSFER_PATH,
+        percent_encode(&params.address),
+    );
+    if !query.is_empty() {
+        url.push('?');
+        url.push_str(&query.join("&"));
+    }
+
+    Ok(ResultOfEncodePaymentUrl { url })
+}
+
+/// Decodes the payment URL of the `ton://transfer/<address>?amount=&text=&bin=` scheme
+/// (or its `everscale://` variant).
+///
+/// Validates the address and the amount and builds the message body for the comment:
+/// the text comment cell for `text` or the `bin` payload as is.
+/// Unknown query parameters are ignored.
+#[api_function]
+pub fn decode_payment_url(
+    _context: Arc<ClientContext>,
+    params: ParamsOfDecodePaymentUrl,
+) -> ClientResult<ResultOfDecodePaymentUrl> {
+    let url = params.url.trim();
+    let (scheme, rest) = [PaymentUrlScheme::Ton, PaymentUrlScheme::Everscale]
+        .iter()
+        .find_map(|scheme| {
+            let prefix = scheme.prefix();
+            if url.len() >= prefix.len() && url[..prefix.len()].eq_ignore_ascii_case(prefix) {
+                Some((scheme.clone(), &url[prefix.len()..]))
+            } else {
+                None
+            }
+        })
+        .ok_or_else(|| Error::invalid_payment_url("unsupported URL scheme"))?;
+    if !rest.starts_with(TRANSFER_PATH) {
+        return Err(Error::invalid_payment_url("only `transfer` action is supported"));
+    }
+    let rest = &rest[TRANSFER_PATH.len()..];
+    let (address, query) = match rest.find('?') {
+        Some(pos) => (&rest[..pos], &rest[pos + 1..]),
+        None => (rest, ""),
+    };
+    let address = account_decode(percent_decode(address.trim_end_matches('/'))?.as_str())?;
+
+    let mut result = ResultOfDecodePaymentUrl {
+        scheme,
+        address: address.to_string(),
+        ..Default::default()
+    };
+    let mut bin = None;
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (name, value) = match pair.find('=') {
+            Some(pos) => (&pair[..pos], &pair[pos + 1..]),
+            None => (pair, ""),
+        };
+        match name {
+            "amount" => {
+                check_amount(value)?;
+                result.amount = Some(value.to_string());
+            }
+            "text" => result.text = Some(percent_decode(value)?),
+            "bin" => bin = Some(decode_bin(&percent_decode(value)?)?),
+            _ => {}
+        }
+    }
+
+    result.payload = match (&result.text, bin) {
+        (Some(_), Some(_)) => {
+            return Err(Error::invalid_payment_url("`text` and `bin` can't be used together"))
+        }
+        (Some(text), None) => Some(serialize_cell_to_base64(&build_comment_cell(text)?, "payload")?),
+        (None, bin) => bin,
+    };
+
+    Ok(result)
+}
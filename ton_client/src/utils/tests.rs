@@ -89,6 +89,34 @@ async fn test_calc_storage_fee() {
     assert_eq!(result.fee, "330");
 }
 
+#[tokio::test(core_threads = 2)]
+async fn test_suggest_address_format() {
+    let client = TestClient::new();
+
+    let giver: ResultOfSuggestAddressFormat = client.request_async(
+        "utils.suggest_address_format",
+        ParamsOfSuggestAddressFormat {
+            address: client.giver_address().await,
+            ..Default::default()
+        },
+    ).await.unwrap();
+    assert_eq!(giver.rationale, AddressFormatRationale::ActiveAccount);
+    assert!(giver.bounce);
+    assert!(giver.address.starts_with("E"));
+
+    let missing: ResultOfSuggestAddressFormat = client.request_async(
+        "utils.suggest_address_format",
+        ParamsOfSuggestAddressFormat {
+            address: "0:919db8e740d50bf349df2eea03fa30c385d846b991ff5542e67098ee833fc7f7".into(),
+            test: Some(false),
+            url: Some(true),
+        },
+    ).await.unwrap();
+    assert_eq!(missing.rationale, AddressFormatRationale::NonExistentAccount);
+    assert!(!missing.bounce);
+    assert_eq!(missing.address, "UQCRnbjnQNUL80nfLuoD-jDDhdhGuZH_VULmcJjugz_H91tj");
+}
+
 #[test]
 fn test_compression() {
     let client = TestClient::new();
@@ -212,3 +240,77 @@ fn get_address_type(client: &TestClient, address: &'static str) -> ClientResult<
     )
         .map(|result| result.address_type)
 }
+
+#[test]
+fn test_payment_url() {
+    let client = TestClient::new();
+    let address = "0:919db8e740d50bf349df2eea03fa30c385d846b991ff5542e67098ee833fc7f7";
+
+    let encoded: ResultOfEncodePaymentUrl = client.request(
+        "utils.encode_payment_url",
+        ParamsOfEncodePaymentUrl {
+            address: address.to_owned(),
+            amount: Some("1000000000".to_owned()),
+            text: Some("Привет, world!".to_owned()),
+            ..Default::default()
+        },
+    ).unwrap();
+    assert_eq!(
+        encoded.url,
+        "ton://transfer/0%3A919db8e740d50bf349df2eea03fa30c385d846b991ff5542e67098ee833fc7f7\
+            ?amount=1000000000&text=%D0%9F%D1%80%D0%B8%D0%B2%D0%B5%D1%82%2C%20world%21",
+    );
+
+    let decoded: ResultOfDecodePaymentUrl = client.request(
+        "utils.decode_payment_url",
+        ParamsOfDecodePaymentUrl { url: encoded.url },
+    ).unwrap();
+    assert_eq!(decoded.scheme, PaymentUrlScheme::Ton);
+    assert_eq!(decoded.address, address);
+    assert_eq!(decoded.amount.as_deref(), Some("1000000000"));
+    assert_eq!(decoded.text.as_deref(), Some("Привет, world!"));
+    let comment = payment_url::build_comment_cell("Привет, world!").unwrap();
+    assert_eq!(
+        decoded.payload,
+        Some(crate::boc::internal::serialize_cell_to_base64(&comment, "payload").unwrap()),
+    );
+
+    // Long comments are continued in the referenced cells
+    let long_comment = payment_url::build_comment_cell(&"a".repeat(300)).unwrap();
+    assert_eq!(long_comment.bit_length(), 127 * 8);
+    assert_eq!(long_comment.references_count(), 1);
+    assert_eq!(long_comment.reference(0).unwrap().references_count(), 1);
+
+    let payload = crate::boc::internal::serialize_cell_to_base64(&long_comment, "payload").unwrap();
+    let encoded: ResultOfEncodePaymentUrl = client.request(
+        "utils.encode_payment_url",
+        ParamsOfEncodePaymentUrl {
+            address: address.to_owned(),
+            bin: Some(payload.clone()),
+            scheme: Some(PaymentUrlScheme::Everscale),
+            ..Default::default()
+        },
+    ).unwrap();
+    assert!(encoded.url.starts_with("everscale://transfer/"));
+    let decoded: ResultOfDecodePaymentUrl = client.request(
+        "utils.decode_payment_url",
+        ParamsOfDecodePaymentUrl { url: encoded.url },
+    ).unwrap();
+    assert_eq!(decoded.scheme, PaymentUrlScheme::Everscale);
+    assert_eq!(decoded.text, None);
+    assert_eq!(decoded.payload, Some(payload));
+
+    for url in &[
+        "http://transfer/0:919db8e740d50bf349df2eea03fa30c385d846b991ff5542e67098ee833fc7f7",
+        "ton://transfer/invalid",
+        "ton://transfer/0:919db8e740d50bf349df2eea03fa30c385d846b991ff5542e67098ee833fc7f7?amount=-1",
+        "ton://transfer/0:919db8e740d50bf349df2eea03fa30c385d846b991ff5542e67098ee833fc7f7?text=%ZZ",
+    ] {
+        assert!(client
+            .request::<_, ResultOfDecodePaymentUrl>(
+                "utils.decode_payment_url",
+                ParamsOfDecodePaymentUrl { url: url.to_string() },
+            )
+            .is_err());
+    }
+}
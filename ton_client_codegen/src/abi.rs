@@ -0,0 +1,49 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use serde_derive::Deserialize;
+
+/// Subset of the contract ABI required for code generation.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct AbiContract {
+    #[serde(default)]
+    pub functions: Vec<AbiFunction>,
+    #[serde(default)]
+    pub events: Vec<AbiEvent>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct AbiFunction {
+    pub name: String,
+    #[serde(default)]
+    pub inputs: Vec<AbiParam>,
+    #[serde(default)]
+    pub outputs: Vec<AbiParam>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct AbiEvent {
+    pub name: String,
+    #[serde(default)]
+    pub inputs: Vec<AbiParam>,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct AbiParam {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub param_type: String,
+    #[serde(default)]
+    pub components: Vec<AbiParam>,
+}
@@ -0,0 +1,327 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use crate::abi::{AbiContract, AbiParam};
+use crate::names::{pascal_case, snake_case};
+use crate::CodegenError;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+const STRUCT_DERIVE: &str = "#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default, PartialEq)]";
+
+pub(crate) struct Generator {
+    contract_name: String,
+    abi_json: String,
+    type_names: HashSet<String>,
+    structs: String,
+}
+
+impl Generator {
+    pub fn new(contract_name: &str, abi_json: &str) -> Self {
+        Self {
+            contract_name: pascal_case(contract_name),
+            abi_json: abi_json.to_string(),
+            type_names: HashSet::new(),
+            structs: String::new(),
+        }
+    }
+
+    pub fn generate(mut self, contract: &AbiContract) -> Result<String, CodegenError> {
+        let mut methods = String::new();
+        for function in &contract.functions {
+            let base_name = pascal_case(&function.name);
+            let input = self.add_struct(&format!("{}Input", base_name), &function.inputs)?;
+            let output = self.add_struct(&format!("{}Output", base_name), &function.outputs)?;
+            self.write_methods(&mut methods, &function.name, &input, &output);
+        }
+        for event in &contract.events {
+            self.add_struct(&format!("{}Event", pascal_case(&event.name)), &event.inputs)?;
+        }
+
+        let mut code = String::new();
+        code.push_str("// Generated by ton_client_codegen. Do not edit.\n\n");
+        code.push_str(&self.structs);
+        self.write_client(&mut code, &methods)?;
+        Ok(code)
+    }
+
+    fn unique_type_name(&mut self, name: &str) -> String {
+        let mut unique = name.to_string();
+        let mut index = 1;
+        while self.type_names.contains(&unique) {
+            index += 1;
+            unique = format!("{}{}", name, index);
+        }
+        self.type_names.insert(unique.clone());
+        unique
+    }
+
+    fn add_struct(&mut self, name: &str, params: &[AbiParam]) -> Result<String, CodegenError> {
+        let name = self.unique_type_name(name);
+        let mut fields = String::new();
+        let mut field_names = HashSet::new();
+        for param in params {
+            let mut field_name = snake_case(&param.name);
+            while !field_names.insert(field_name.clone()) {
+                field_name.push('_');
+            }
+            let field_type = self.resolve_type(
+                &format!("{}{}", name, pascal_case(&param.name)),
+                &param.param_type,
+                &param.components,
+            )?;
+            writeln!(fields, "    #[serde(rename = \"{}\")]", param.name).unwrap();
+            writeln!(fields, "    pub {}: {},", field_name, field_type).unwrap();
+        }
+        writeln!(self.structs, "{}", STRUCT_DERIVE).unwrap();
+        if fields.is_empty() {
+            writeln!(self.structs, "pub struct {} {{}}\n", name).unwrap();
+        } else {
+            writeln!(self.structs, "pub struct {} {{\n{}}}\n", name, fields).unwrap();
+        }
+        Ok(name)
+    }
+
+    fn resolve_type(
+        &mut self,
+        type_name: &str,
+        param_type: &str,
+        components: &[AbiParam],
+    ) -> Result<String, CodegenError> {
+        let param_type = param_type.trim();
+        if param_type.ends_with(']') {
+            let open = param_type
+                .rfind('[')
+                .ok_or_else(|| CodegenError::UnsupportedType(param_type.to_string()))?;
+            let item = self.resolve_type(type_name, &param_type[..open], components)?;
+            return Ok(format!("Vec<{}>", item));
+        }
+        if let Some(inner) = unwrap_generic(param_type, "optional") {
+            let inner = self.resolve_type(type_name, inner, components)?;
+            return Ok(format!("Option<{}>", inner));
+        }
+        if let Some(inner) = unwrap_generic(param_type, "ref") {
+            return self.resolve_type(type_name, inner, components);
+        }
+        if let Some(inner) = unwrap_generic(param_type, "map") {
+            let comma = find_top_level_comma(inner)
+                .ok_or_else(|| CodegenError::UnsupportedType(param_type.to_string()))?;
+            let value = self.resolve_type(type_name, &inner[comma + 1..], components)?;
+            return Ok(format!("std::collections::HashMap<String, {}>", value));
+        }
+        if param_type == "tuple" {
+            return self.add_struct(type_name, components);
+        }
+        if param_type == "bool" {
+            return Ok("bool".to_string());
+        }
+        if param_type == "pubkey" {
+            return Ok("Option<String>".to_string());
+        }
+        // Numbers are passed as decimal or `0x` prefixed hex strings to avoid precision loss,
+        // cells as base64 BOCs, bytes as hex strings.
+        const STRING_TYPES: &[&str] = &[
+            "int", "uint", "varint", "varuint", "gram", "token", "address", "address_std",
+            "cell", "bytes", "fixedbytes", "string", "time", "expire",
+        ];
+        let base = param_type.trim_end_matches(|ch: char| ch.is_ascii_digit());
+        if STRING_TYPES.contains(&base) {
+            return Ok("String".to_string());
+        }
+        Err(CodegenError::UnsupportedType(param_type.to_string()))
+    }
+
+    fn write_methods(&self, methods: &mut String, function_name: &str, input: &str, output: &str) {
+        let name = snake_case(function_name);
+        write!(
+            methods,
+            r#"
+    /// Encodes external inbound message calling `{function}`.
+    pub async fn encode_{name}(
+        &self,
+        input: &{input},
+        signer: ton_client::abi::Signer,
+    ) -> ton_client::error::ClientResult<String> {{
+        self.encode("{function}", input, signer).await
+    }}
+
+    /// Calls `{function}` on-chain and waits for the result transaction.
+    pub async fn call_{name}(
+        &self,
+        input: &{input},
+        signer: ton_client::abi::Signer,
+    ) -> ton_client::error::ClientResult<{output}> {{
+        Self::decode_output(self.process("{function}", input, signer).await?)
+    }}
+
+    /// Runs `{function}` locally on the provided account state (BOC encoded as base64).
+    pub async fn run_{name}(
+        &self,
+        account: &str,
+        input: &{input},
+    ) -> ton_client::error::ClientResult<{output}> {{
+        Self::decode_output(self.run("{function}", account, input).await?)
+    }}
+"#,
+            function = function_name,
+            name = name,
+            input = input,
+            output = output,
+        )
+        .unwrap();
+    }
+
+    fn write_client(&self, code: &mut String, methods: &str) -> Result<(), CodegenError> {
+        let mut hashes = String::from("#");
+        while self.abi_json.contains(&format!("\"{}", hashes)) {
+            hashes.push('#');
+        }
+        write!(
+            code,
+            r#"/// Typed client of the `{name}` contract.
+#[derive(Clone)]
+pub struct {name} {{
+    context: std::sync::Arc<ton_client::ClientContext>,
+    abi: ton_client::abi::Abi,
+    address: String,
+}}
+
+impl {name} {{
+    /// Contract ABI.
+    pub const ABI: &'static str = r{hashes}"{abi}"{hashes};
+
+    pub fn new(context: std::sync::Arc<ton_client::ClientContext>, address: impl Into<String>) -> Self {{
+        Self {{
+            context,
+            abi: ton_client::abi::Abi::Json(Self::ABI.to_string()),
+            address: address.into(),
+        }}
+    }}
+
+    pub fn address(&self) -> &str {{
+        &self.address
+    }}
+
+    pub fn abi(&self) -> &ton_client::abi::Abi {{
+        &self.abi
+    }}
+
+    fn encode_params<I: serde::Serialize>(
+        &self,
+        function_name: &str,
+        input: &I,
+        signer: ton_client::abi::Signer,
+    ) -> ton_client::error::ClientResult<ton_client::abi::ParamsOfEncodeMessage> {{
+        let input = serde_json::to_value(input).map_err(ton_client::abi::Error::invalid_json)?;
+        Ok(ton_client::abi::ParamsOfEncodeMessage {{
+            abi: self.abi.clone(),
+            address: Some(self.address.clone()),
+            call_set: Some(ton_client::abi::CallSet {{
+                function_name: function_name.to_string(),
+                header: None,
+                input: Some(input),
+            }}),
+            signer,
+            ..Default::default()
+        }})
+    }}
+
+    fn decode_output<O: serde::de::DeserializeOwned>(
+        output: Option<serde_json::Value>,
+    ) -> ton_client::error::ClientResult<O> {{
+        serde_json::from_value(output.unwrap_or_else(|| serde_json::json!({{}})))
+            .map_err(ton_client::abi::Error::invalid_data_for_decode)
+    }}
+
+    async fn encode<I: serde::Serialize>(
+        &self,
+        function_name: &str,
+        input: &I,
+        signer: ton_client::abi::Signer,
+    ) -> ton_client::error::ClientResult<String> {{
+        let params = self.encode_params(function_name, input, signer)?;
+        Ok(ton_client::abi::encode_message(self.context.clone(), params).await?.message)
+    }}
+
+    async fn process<I: serde::Serialize>(
+        &self,
+        function_name: &str,
+        input: &I,
+        signer: ton_client::abi::Signer,
+    ) -> ton_client::error::ClientResult<Option<serde_json::Value>> {{
+        let params = ton_client::processing::ParamsOfProcessMessage {{
+            message_encode_params: self.encode_params(function_name, input, signer)?,
+            ..Default::default()
+        }};
+        let result = ton_client::processing::process_message(
+            self.context.clone(),
+            params,
+            |_| async {{}},
+        )
+        .await?;
+        Ok(result.decoded.and_then(|decoded| decoded.output))
+    }}
+
+    async fn run<I: serde::Serialize>(
+        &self,
+        function_name: &str,
+        account: &str,
+        input: &I,
+    ) -> ton_client::error::ClientResult<Option<serde_json::Value>> {{
+        let message = self.encode(function_name, input, ton_client::abi::Signer::None).await?;
+        let result = ton_client::tvm::run_tvm(
+            self.context.clone(),
+            ton_client::tvm::ParamsOfRunTvm {{
+                message,
+                account: account.to_string(),
+                abi: Some(self.abi.clone()),
+                ..Default::default()
+            }},
+        )
+        .await?;
+        Ok(result.decoded.and_then(|decoded| decoded.output))
+    }}
+{methods}}}
+"#,
+            name = self.contract_name,
+            hashes = hashes,
+            abi = self.abi_json,
+            methods = methods,
+        )
+        .map_err(|err| CodegenError::InvalidAbi(err.to_string()))
+    }
+}
+
+fn unwrap_generic<'a>(param_type: &'a str, name: &str) -> Option<&'a str> {
+    if param_type.starts_with(name) && param_type.ends_with(')') {
+        let rest = &param_type[name.len()..];
+        if rest.starts_with('(') {
+            return Some(&rest[1..rest.len() - 1]);
+        }
+    }
+    None
+}
+
+fn find_top_level_comma(types: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (index, ch) in types.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => return Some(index),
+            _ => {}
+        }
+    }
+    None
+}
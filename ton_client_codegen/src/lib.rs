@@ -0,0 +1,99 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+//! Build-time generator of typed Rust bindings for contract ABIs.
+//!
+//! Turns a contract ABI JSON into input/output structs for every function
+//! and a client wrapper calling `abi.encode_message`, `processing.process_message`
+//! and `tvm.run_tvm` of `ton_client`.
+//!
+//! Typical usage from a `build.rs`:
+//!
+//! ```ignore
+//! let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
+//! ton_client_codegen::generate_file("Wallet", "contracts/Wallet.abi.json", out_dir.join("wallet.rs"))
+//!     .unwrap();
+//! ```
+//!
+//! and in the crate sources:
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/wallet.rs"));
+//! ```
+//!
+//! The generated code depends on `ton_client`, `serde` and `serde_json` crates.
+
+mod abi;
+mod generator;
+mod names;
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt::Display;
+use std::path::Path;
+
+#[derive(Debug)]
+pub enum CodegenError {
+    /// ABI can not be read
+    Io(std::io::Error),
+    /// ABI JSON is malformed
+    InvalidAbi(String),
+    /// ABI contains a parameter type the generator doesn't support
+    UnsupportedType(String),
+}
+
+impl Display for CodegenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CodegenError::Io(err) => write!(f, "Can not read ABI: {}", err),
+            CodegenError::InvalidAbi(err) => write!(f, "Invalid ABI: {}", err),
+            CodegenError::UnsupportedType(param_type) => {
+                write!(f, "Unsupported ABI type: {}", param_type)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CodegenError {}
+
+impl From<std::io::Error> for CodegenError {
+    fn from(err: std::io::Error) -> Self {
+        CodegenError::Io(err)
+    }
+}
+
+/// Generates Rust bindings for the contract ABI.
+///
+/// `contract_name` is used as the name of the generated client struct.
+pub fn generate(contract_name: &str, abi_json: &str) -> Result<String, CodegenError> {
+    let contract: abi::AbiContract = serde_json::from_str(abi_json)
+        .map_err(|err| CodegenError::InvalidAbi(err.to_string()))?;
+    generator::Generator::new(contract_name, abi_json).generate(&contract)
+}
+
+/// Reads the ABI file and writes the generated bindings to `out_path`.
+///
+/// Intended to be called from build scripts.
+pub fn generate_file(
+    contract_name: &str,
+    abi_path: impl AsRef<Path>,
+    out_path: impl AsRef<Path>,
+) -> Result<(), CodegenError> {
+    let abi_json = std::fs::read_to_string(abi_path.as_ref())?;
+    let code = generate(contract_name, &abi_json)?;
+    std::fs::write(out_path, code)?;
+    println!("cargo:rerun-if-changed={}", abi_path.as_ref().display());
+    Ok(())
+}
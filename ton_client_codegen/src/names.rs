@@ -0,0 +1,66 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "do", "dyn", "else",
+    "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop", "macro",
+    "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "self", "Self",
+    "static", "struct", "super", "trait", "true", "try", "type", "typeof", "unsafe", "unsized",
+    "use", "virtual", "where", "while", "yield",
+];
+
+/// Converts ABI name (usually camelCase, sometimes with leading underscores)
+/// into a valid snake_case Rust identifier.
+pub fn snake_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len() + 4);
+    let mut prev_is_lower = false;
+    for ch in name.trim_start_matches('_').chars() {
+        if ch.is_ascii_uppercase() {
+            if prev_is_lower {
+                result.push('_');
+            }
+            result.push(ch.to_ascii_lowercase());
+            prev_is_lower = false;
+        } else if ch.is_ascii_alphanumeric() {
+            result.push(ch);
+            prev_is_lower = ch.is_ascii_lowercase() || ch.is_ascii_digit();
+        } else {
+            result.push('_');
+            prev_is_lower = false;
+        }
+    }
+    if result.is_empty() || result.starts_with(|ch: char| ch.is_ascii_digit()) {
+        result.insert(0, '_');
+    }
+    if KEYWORDS.contains(&result.as_str()) {
+        result.push('_');
+    }
+    result
+}
+
+/// Converts ABI name into a PascalCase Rust type name.
+pub fn pascal_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    for part in name.split(|ch: char| !ch.is_ascii_alphanumeric()) {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            result.push(first.to_ascii_uppercase());
+            result.extend(chars);
+        }
+    }
+    if result.is_empty() || result.starts_with(|ch: char| ch.is_ascii_digit()) {
+        result.insert(0, '_');
+    }
+    result
+}
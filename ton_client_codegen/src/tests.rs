@@ -0,0 +1,88 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+use crate::names::{pascal_case, snake_case};
+use crate::{generate, CodegenError};
+
+const ABI: &str = r#"{
+    "ABI version": 2,
+    "header": ["time", "expire"],
+    "functions": [
+        {
+            "name": "sendTransaction",
+            "inputs": [
+                {"name": "dest", "type": "address"},
+                {"name": "value", "type": "uint128"},
+                {"name": "bounce", "type": "bool"}
+            ],
+            "outputs": []
+        },
+        {
+            "name": "getCustodians",
+            "inputs": [],
+            "outputs": [
+                {"components": [
+                    {"name": "index", "type": "uint8"},
+                    {"name": "pubkey", "type": "uint256"}
+                ], "name": "custodians", "type": "tuple[]"},
+                {"name": "limits", "type": "map(uint64,optional(uint128))"}
+            ]
+        }
+    ],
+    "events": [
+        {"name": "Deposit", "inputs": [{"name": "_type", "type": "uint8"}]}
+    ]
+}"#;
+
+#[test]
+fn test_names() {
+    assert_eq!(snake_case("sendTransaction"), "send_transaction");
+    assert_eq!(snake_case("_answer_id"), "answer_id");
+    assert_eq!(snake_case("type"), "type_");
+    assert_eq!(snake_case("value0"), "value0");
+    assert_eq!(pascal_case("sendTransaction"), "SendTransaction");
+    assert_eq!(pascal_case("get_info"), "GetInfo");
+}
+
+#[test]
+fn test_generate() {
+    let code = generate("multisig_wallet", ABI).unwrap();
+
+    assert!(code.contains("pub struct SendTransactionInput {"));
+    assert!(code.contains("    #[serde(rename = \"value\")]\n    pub value: String,"));
+    assert!(code.contains("    pub bounce: bool,"));
+    assert!(code.contains("pub struct SendTransactionOutput {}"));
+    assert!(code.contains("pub custodians: Vec<GetCustodiansOutputCustodians>,"));
+    assert!(code.contains("pub struct GetCustodiansOutputCustodians {"));
+    assert!(code.contains(
+        "pub limits: std::collections::HashMap<String, Option<String>>,"
+    ));
+    assert!(code.contains("pub struct DepositEvent {"));
+    assert!(code.contains("    #[serde(rename = \"_type\")]\n    pub type_: String,"));
+
+    assert!(code.contains("pub struct MultisigWallet {"));
+    assert!(code.contains("pub async fn call_send_transaction("));
+    assert!(code.contains("pub async fn run_get_custodians("));
+    assert!(code.contains("pub async fn encode_get_custodians("));
+    assert!(code.contains(&format!("pub const ABI: &'static str = r#\"{}\"#;", ABI)));
+}
+
+#[test]
+fn test_unsupported_type() {
+    let abi = r#"{"functions": [{"name": "f", "inputs": [{"name": "a", "type": "unknown"}]}]}"#;
+    match generate("Test", abi) {
+        Err(CodegenError::UnsupportedType(param_type)) => assert_eq!(param_type, "unknown"),
+        _ => panic!("unsupported type error expected"),
+    }
+}
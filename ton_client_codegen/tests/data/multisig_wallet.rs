@@ -0,0 +1,227 @@
+// Generated by ton_client_codegen. Do not edit.
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SendTransactionInput {
+    #[serde(rename = "dest")]
+    pub dest: String,
+    #[serde(rename = "value")]
+    pub value: String,
+    #[serde(rename = "bounce")]
+    pub bounce: bool,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SendTransactionOutput {}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct GetCustodiansInput {}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct GetCustodiansOutputCustodians {
+    #[serde(rename = "index")]
+    pub index: String,
+    #[serde(rename = "pubkey")]
+    pub pubkey: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct GetCustodiansOutput {
+    #[serde(rename = "custodians")]
+    pub custodians: Vec<GetCustodiansOutputCustodians>,
+    #[serde(rename = "limits")]
+    pub limits: std::collections::HashMap<String, Option<String>>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct DepositEvent {
+    #[serde(rename = "_type")]
+    pub type_: String,
+}
+
+/// Typed client of the `MultisigWallet` contract.
+#[derive(Clone)]
+pub struct MultisigWallet {
+    context: std::sync::Arc<ton_client::ClientContext>,
+    abi: ton_client::abi::Abi,
+    address: String,
+}
+
+impl MultisigWallet {
+    /// Contract ABI.
+    pub const ABI: &'static str = r#"{
+    "ABI version": 2,
+    "header": ["time", "expire"],
+    "functions": [
+        {
+            "name": "sendTransaction",
+            "inputs": [
+                {"name": "dest", "type": "address"},
+                {"name": "value", "type": "uint128"},
+                {"name": "bounce", "type": "bool"}
+            ],
+            "outputs": []
+        },
+        {
+            "name": "getCustodians",
+            "inputs": [],
+            "outputs": [
+                {"components": [
+                    {"name": "index", "type": "uint8"},
+                    {"name": "pubkey", "type": "uint256"}
+                ], "name": "custodians", "type": "tuple[]"},
+                {"name": "limits", "type": "map(uint64,optional(uint128))"}
+            ]
+        }
+    ],
+    "events": [
+        {"name": "Deposit", "inputs": [{"name": "_type", "type": "uint8"}]}
+    ]
+}
+"#;
+
+    pub fn new(context: std::sync::Arc<ton_client::ClientContext>, address: impl Into<String>) -> Self {
+        Self {
+            context,
+            abi: ton_client::abi::Abi::Json(Self::ABI.to_string()),
+            address: address.into(),
+        }
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub fn abi(&self) -> &ton_client::abi::Abi {
+        &self.abi
+    }
+
+    fn encode_params<I: serde::Serialize>(
+        &self,
+        function_name: &str,
+        input: &I,
+        signer: ton_client::abi::Signer,
+    ) -> ton_client::error::ClientResult<ton_client::abi::ParamsOfEncodeMessage> {
+        let input = serde_json::to_value(input).map_err(ton_client::abi::Error::invalid_json)?;
+        Ok(ton_client::abi::ParamsOfEncodeMessage {
+            abi: self.abi.clone(),
+            address: Some(self.address.clone()),
+            call_set: Some(ton_client::abi::CallSet {
+                function_name: function_name.to_string(),
+                header: None,
+                input: Some(input),
+            }),
+            signer,
+            ..Default::default()
+        })
+    }
+
+    fn decode_output<O: serde::de::DeserializeOwned>(
+        output: Option<serde_json::Value>,
+    ) -> ton_client::error::ClientResult<O> {
+        serde_json::from_value(output.unwrap_or_else(|| serde_json::json!({})))
+            .map_err(ton_client::abi::Error::invalid_data_for_decode)
+    }
+
+    async fn encode<I: serde::Serialize>(
+        &self,
+        function_name: &str,
+        input: &I,
+        signer: ton_client::abi::Signer,
+    ) -> ton_client::error::ClientResult<String> {
+        let params = self.encode_params(function_name, input, signer)?;
+        Ok(ton_client::abi::encode_message(self.context.clone(), params).await?.message)
+    }
+
+    async fn process<I: serde::Serialize>(
+        &self,
+        function_name: &str,
+        input: &I,
+        signer: ton_client::abi::Signer,
+    ) -> ton_client::error::ClientResult<Option<serde_json::Value>> {
+        let params = ton_client::processing::ParamsOfProcessMessage {
+            message_encode_params: self.encode_params(function_name, input, signer)?,
+            ..Default::default()
+        };
+        let result = ton_client::processing::process_message(
+            self.context.clone(),
+            params,
+            |_| async {},
+        )
+        .await?;
+        Ok(result.decoded.and_then(|decoded| decoded.output))
+    }
+
+    async fn run<I: serde::Serialize>(
+        &self,
+        function_name: &str,
+        account: &str,
+        input: &I,
+    ) -> ton_client::error::ClientResult<Option<serde_json::Value>> {
+        let message = self.encode(function_name, input, ton_client::abi::Signer::None).await?;
+        let result = ton_client::tvm::run_tvm(
+            self.context.clone(),
+            ton_client::tvm::ParamsOfRunTvm {
+                message,
+                account: account.to_string(),
+                abi: Some(self.abi.clone()),
+                ..Default::default()
+            },
+        )
+        .await?;
+        Ok(result.decoded.and_then(|decoded| decoded.output))
+    }
+
+    /// Encodes external inbound message calling `sendTransaction`.
+    pub async fn encode_send_transaction(
+        &self,
+        input: &SendTransactionInput,
+        signer: ton_client::abi::Signer,
+    ) -> ton_client::error::ClientResult<String> {
+        self.encode("sendTransaction", input, signer).await
+    }
+
+    /// Calls `sendTransaction` on-chain and waits for the result transaction.
+    pub async fn call_send_transaction(
+        &self,
+        input: &SendTransactionInput,
+        signer: ton_client::abi::Signer,
+    ) -> ton_client::error::ClientResult<SendTransactionOutput> {
+        Self::decode_output(self.process("sendTransaction", input, signer).await?)
+    }
+
+    /// Runs `sendTransaction` locally on the provided account state (BOC encoded as base64).
+    pub async fn run_send_transaction(
+        &self,
+        account: &str,
+        input: &SendTransactionInput,
+    ) -> ton_client::error::ClientResult<SendTransactionOutput> {
+        Self::decode_output(self.run("sendTransaction", account, input).await?)
+    }
+
+    /// Encodes external inbound message calling `getCustodians`.
+    pub async fn encode_get_custodians(
+        &self,
+        input: &GetCustodiansInput,
+        signer: ton_client::abi::Signer,
+    ) -> ton_client::error::ClientResult<String> {
+        self.encode("getCustodians", input, signer).await
+    }
+
+    /// Calls `getCustodians` on-chain and waits for the result transaction.
+    pub async fn call_get_custodians(
+        &self,
+        input: &GetCustodiansInput,
+        signer: ton_client::abi::Signer,
+    ) -> ton_client::error::ClientResult<GetCustodiansOutput> {
+        Self::decode_output(self.process("getCustodians", input, signer).await?)
+    }
+
+    /// Runs `getCustodians` locally on the provided account state (BOC encoded as base64).
+    pub async fn run_get_custodians(
+        &self,
+        account: &str,
+        input: &GetCustodiansInput,
+    ) -> ton_client::error::ClientResult<GetCustodiansOutput> {
+        Self::decode_output(self.run("getCustodians", account, input).await?)
+    }
+}
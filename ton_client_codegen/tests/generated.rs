@@ -0,0 +1,45 @@
+/*
+ * Copyright 2018-2021 TON Labs LTD.
+ *
+ * Licensed under the SOFTWARE EVALUATION License (the "License"); you may not use
+ * this file except in compliance with the License.
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific TON DEV software governing permissions and
+ * limitations under the License.
+ *
+ */
+
+// The client generated from `data/multisig_wallet.abi.json` is compiled against `ton_client`
+// as a part of this test, so the drift of the generated code from the `ton_client` API
+// breaks the build.
+
+#[allow(dead_code, clippy::all)]
+mod generated {
+    include!("data/multisig_wallet.rs");
+}
+
+use std::sync::Arc;
+
+#[test]
+fn generated_client_is_up_to_date() {
+    let code = ton_client_codegen::generate(
+        "multisig_wallet",
+        include_str!("data/multisig_wallet.abi.json"),
+    )
+    .unwrap();
+    assert!(
+        code == include_str!("data/multisig_wallet.rs"),
+        "tests/data/multisig_wallet.rs must be regenerated from tests/data/multisig_wallet.abi.json"
+    );
+}
+
+#[test]
+fn generated_client() {
+    let context = Arc::new(ton_client::ClientContext::new(Default::default()).unwrap());
+    let wallet = generated::MultisigWallet::new(context, "0:1234");
+    assert_eq!(wallet.address(), "0:1234");
+    assert!(matches!(wallet.abi(), ton_client::abi::Abi::Json(abi) if abi == generated::MultisigWallet::ABI));
+}
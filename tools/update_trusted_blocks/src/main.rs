@@ -34,6 +34,8 @@ async fn query_current_network_zs_hash(
 
     proof_block_data(Arc::clone(&context), ParamsOfProofBlockData {
         block: block.clone(),
+        send_events: None,
+        verification_policy: None,
     }).await?;
 
     let prev_ref = &block["prev_ref"];
@@ -104,6 +106,8 @@ async fn query_network_keyblocks(server_address: &'static str) -> Result<(UInt25
             print!("Proof for key_block #{}...", seq_no);
             proof_block_data(Arc::clone(&context), ParamsOfProofBlockData {
                 block: key_block.clone(),
+                send_events: None,
+                verification_policy: None,
             }).await?;
             let root_hash = UInt256::from_str(key_block["id"].as_str()
                 .expect("Field `id` must be a string"))?;